@@ -422,6 +422,7 @@ export const TOOLS: ToolDefinition[] = [
       name: 'tauri_ipc_emit_event',
       description:
          '[Tauri Apps Only] Emit a Tauri event to test event handlers. ' +
+         'Reaches both frontend listen() handlers and Rust app.listen() handlers. ' +
          'Requires active tauri_driver_session. Events are Tauri-specific (not DOM events). ' +
          'For browser DOM events, use Chrome DevTools MCP instead.',
       category: TOOL_CATEGORIES.IPC_PLUGIN,
@@ -8,7 +8,9 @@ import {
    getBackendState,
    executeIPCCommand,
 } from '../../src/driver/plugin-commands';
+import { connectPlugin, getPluginClient } from '../../src/driver/plugin-client';
 import { manageDriverSession } from '../../src/driver/session-manager';
+import { executeJavaScript } from '../../src/driver/webview-interactions';
 
 /**
  * E2E tests for MCP Bridge Plugin.
@@ -97,6 +99,352 @@ describe('MCP Bridge Plugin E2E Tests', () => {
       expect(result).toContain('emitted successfully');
    }, TIMEOUT);
 
+   it('should report largest contentful paint after load', async () => {
+      const result = await executeIPCCommand('plugin:mcp-bridge|get_web_vitals');
+
+      const parsed = JSON.parse(result);
+
+      expect(parsed.success).toBe(true);
+      expect(parsed.result).toHaveProperty('cls');
+      expect(parsed.result).toHaveProperty('lcpMs');
+      // WebKitGTK and WKWebView don't report LCP entries
+      if (!parsed.result.unsupported.includes('largest-contentful-paint')) {
+         expect(parsed.result.lcpMs).toBeGreaterThan(0);
+      }
+   }, TIMEOUT);
+
+   it('should report a failed image load', async () => {
+      const since = Date.now();
+
+      await executeJavaScript({
+         script: `const img = document.createElement('img');
+            img.src = '/__mcp_missing_image__.png';
+            document.body.appendChild(img);`,
+      });
+      await new Promise((resolve) => { return setTimeout(resolve, 1000); });
+
+      const result = await executeIPCCommand('plugin:mcp-bridge|get_failed_requests', { since });
+      const parsed = JSON.parse(result);
+
+      expect(parsed.success).toBe(true);
+      const failure = parsed.result.find((entry: { url: string }) => {
+         return entry.url.endsWith('/__mcp_missing_image__.png');
+      });
+
+      expect(failure).toBeTruthy();
+      expect(failure.type).toBe('img');
+      expect(failure.status).toBeNull();
+   }, TIMEOUT);
+
+   it('should read visible text without scripts or hidden elements', async () => {
+      await executeJavaScript({
+         script: `const sample = document.createElement('section');
+            sample.id = '__mcp_readable__';
+            sample.innerHTML = '<h2>Readable heading</h2><p>Visible paragraph</p>'
+               + '<script>var readableSecret = 1;</script>'
+               + '<p hidden>Hidden paragraph</p>'
+               + '<p style="display: none">Undisplayed paragraph</p>';
+            document.body.appendChild(sample);`,
+      });
+
+      const result = await executeIPCCommand('plugin:mcp-bridge|get_readable_text');
+      const parsed = JSON.parse(result);
+
+      await executeJavaScript({ script: `document.getElementById('__mcp_readable__').remove();` });
+
+      expect(parsed.success).toBe(true);
+      expect(parsed.result.text).toContain('## Readable heading');
+      expect(parsed.result.text).toContain('Visible paragraph');
+      expect(parsed.result.text).not.toContain('readableSecret');
+      expect(parsed.result.text).not.toContain('Hidden paragraph');
+      expect(parsed.result.text).not.toContain('Undisplayed paragraph');
+      expect(parsed.result.truncated).toBe(false);
+   }, TIMEOUT);
+
+   it('should fill a form and read it back', async () => {
+      await executeJavaScript({
+         script: `const form = document.createElement('form');
+            form.id = '__mcp_form__';
+            form.innerHTML = '<input name="email" id="__mcp_form_email__">'
+               + '<select name="plan" id="__mcp_form_plan__"><option value="free">Free</option><option value="pro">Pro</option></select>'
+               + '<input type="checkbox" name="terms" id="__mcp_form_terms__">'
+               + '<input type="radio" name="size" value="s" id="__mcp_form_small__"><input type="radio" name="size" value="l" id="__mcp_form_large__">';
+            document.body.appendChild(form);`,
+      });
+
+      try {
+         const filled = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|fill_form', {
+            fields: {
+               '#__mcp_form_email__': 'user@example.com',
+               '#__mcp_form_plan__': 'pro',
+               '#__mcp_form_terms__': true,
+               '#__mcp_form_large__': true,
+               '#__mcp_form_missing__': 'x',
+            },
+         }));
+
+         expect(filled.success).toBe(true);
+         expect(filled.result.successCount).toBe(4);
+         expect(filled.result.failureCount).toBe(1);
+         expect(filled.result.results['#__mcp_form_missing__'].error).toContain('No element matches selector');
+
+         const read = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|read_form', { formSelector: '#__mcp_form__' }));
+
+         expect(read.success).toBe(true);
+         expect(read.result).toEqual({ email: 'user@example.com', plan: 'pro', terms: true, size: 'l' });
+      } finally {
+         await executeJavaScript({ script: `document.getElementById('__mcp_form__').remove();` });
+      }
+   }, TIMEOUT);
+
+   it('should focus an input and read it back as active', async () => {
+      await executeJavaScript({
+         script: `const input = document.createElement('input');
+            input.id = '__mcp_focus__';
+            input.value = 'focused text';
+            document.body.appendChild(input);`,
+      });
+
+      try {
+         const focus = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|focus_element', { selector: '#__mcp_focus__' }));
+
+         expect(focus.success).toBe(true);
+         expect(focus.result.focused).toBe(true);
+
+         const active = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|get_active_element'));
+
+         expect(active.success).toBe(true);
+         expect(active.result).toMatchObject({
+            tagName: 'input',
+            id: '__mcp_focus__',
+            selector: '#__mcp_focus__',
+            value: 'focused text',
+         });
+      } finally {
+         await executeJavaScript({ script: `document.getElementById('__mcp_focus__').remove();` });
+      }
+   }, TIMEOUT);
+
+   // get_element_box only reads the measurement back through WebView2
+   it.skipIf(process.platform !== 'win32')('should resolve an element from its center coordinates', async () => {
+      await executeJavaScript({
+         script: `const target = document.createElement('div');
+            target.id = '__mcp_point__';
+            target.className = 'probe';
+            target.textContent = 'Point target';
+            target.style.cssText = 'position: fixed; left: 40px; top: 40px; width: 120px; height: 60px; z-index: 2147483647;';
+            document.body.appendChild(target);`,
+      });
+
+      try {
+         const box = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|get_element_box', { selector: '#__mcp_point__' }));
+         const hit = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|element_from_point', {
+            x: box.result.x + box.result.width / 2,
+            y: box.result.y + box.result.height / 2,
+         }));
+
+         expect(hit.success).toBe(true);
+         expect(hit.result).toMatchObject({
+            tagName: 'div',
+            id: '__mcp_point__',
+            classes: [ 'probe' ],
+            text: 'Point target',
+            selector: '#__mcp_point__',
+         });
+
+         const outside = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|element_from_point', { x: -10, y: -10 }));
+
+         expect(outside.success).toBe(true);
+         expect(outside.result).toBeNull();
+      } finally {
+         await executeJavaScript({ script: `document.getElementById('__mcp_point__').remove();` });
+      }
+   }, TIMEOUT);
+
+   it.skipIf(process.platform !== 'win32')('should reuse an element handle across operations', async () => {
+      await executeJavaScript({
+         script: `const button = document.createElement('button');
+            button.id = '__mcp_handle__';
+            button.textContent = 'Handle target';
+            button.addEventListener('click', () => { button.dataset.clicks = String(Number(button.dataset.clicks || 0) + 1); });
+            document.body.appendChild(button);`,
+      });
+
+      await connectPlugin();
+      const client = getPluginClient();
+      const query = await client.sendCommand({
+         command: 'query_element_handle',
+         args: { selector: '#__mcp_handle__' },
+      });
+
+      expect(query.success).toBe(true);
+      const { handleId } = query.data as { handleId: string; tagName: string };
+
+      // Moving the element doesn't invalidate the handle
+      await executeJavaScript({
+         script: `document.body.prepend(document.getElementById('__mcp_handle__'));`,
+      });
+
+      const box = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|get_element_box', { handleId }));
+      const click = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|dispatch_dom_event', {
+         handleId,
+         eventType: 'click',
+      }));
+      const clicks = await executeJavaScript({
+         script: `const button = document.getElementById('__mcp_handle__');
+            const clicks = button.dataset.clicks;
+            button.remove();
+            return clicks;`,
+      });
+
+      expect(box.success).toBe(true);
+      expect(box.result.width).toBeGreaterThan(0);
+      expect(click.success).toBe(true);
+      expect(clicks).toContain('1');
+
+      // Removed elements no longer resolve
+      const stale = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|get_element_box', { handleId }));
+
+      expect(stale.success).toBe(false);
+      expect(stale.error).toContain('Element handle no longer refers to an element in the page');
+   }, TIMEOUT);
+
+   it('should stub a fetch with a mock', async () => {
+      await connectPlugin();
+      const client = getPluginClient();
+      const added = await client.sendCommand({
+         command: 'add_mock',
+         args: { urlPattern: '*/__mcp_mock__/users*', method: 'GET', status: 200, body: { stubbed: true } },
+      });
+
+      expect(added.success).toBe(true);
+
+      try {
+         const result = await executeJavaScript({
+            script: `const response = await fetch('/__mcp_mock__/users?page=1');
+               return JSON.stringify({ status: response.status, body: await response.json() });`,
+         });
+
+         expect(result).toContain('"status":200');
+         expect(result).toContain('"stubbed":true');
+      } finally {
+         const cleared = await client.sendCommand({ command: 'clear_mocks' });
+
+         expect(cleared.success).toBe(true);
+      }
+   }, TIMEOUT);
+
+   it('should restore a modified window layout from a snapshot', async () => {
+      interface LayoutResults {
+         results: { label: string; data?: { layout: { width: number; height: number } } }[];
+         failureCount: number;
+      }
+
+      await connectPlugin();
+      const client = getPluginClient();
+      const snapshot = await client.sendCommand({ command: 'get_layout' });
+
+      expect(snapshot.success).toBe(true);
+
+      const layout = snapshot.data as { label: string; width: number; height: number }[];
+      const main = layout.find((w) => { return w.label === 'main'; });
+
+      if (!main) {
+         throw new Error('No main window in the layout');
+      }
+
+      try {
+         const modified = await client.sendCommand({
+            command: 'set_layout',
+            args: { layout: [ { ...main, width: main.width - 100, height: main.height - 50 } ] },
+         });
+
+         expect(modified.success).toBe(true);
+         expect((modified.data as LayoutResults).results[0].data?.layout.width).toBe(main.width - 100);
+      } finally {
+         const restored = await client.sendCommand({ command: 'set_layout', args: { layout } }),
+               data = restored.data as LayoutResults;
+
+         expect(restored.success).toBe(true);
+         expect(data.failureCount).toBe(0);
+         expect(data.results.find((r) => { return r.label === 'main'; })?.data?.layout)
+            .toMatchObject({ width: main.width, height: main.height });
+      }
+   }, TIMEOUT);
+
+   it('should capture a scroll container whole', async () => {
+      const size = await executeJavaScript({
+         script: `const pane = document.createElement('div');
+            pane.id = '__mcp_scroll_pane__';
+            pane.style.cssText = 'position: fixed; left: 10px; top: 10px; width: 300px; height: 200px; overflow-y: scroll; z-index: 2147483647; background: white;';
+            for (let i = 0; i < 40; i++) {
+               const row = document.createElement('div');
+               row.style.cssText = 'height: 50px; background: hsl(' + (i * 9) + ', 80%, 50%);';
+               pane.appendChild(row);
+            }
+            document.body.appendChild(pane);
+            return JSON.stringify({ width: pane.clientWidth, height: pane.scrollHeight });`,
+      });
+      const { width, height } = JSON.parse(size) as { width: number; height: number };
+
+      try {
+         await connectPlugin();
+         const response = await getPluginClient().sendCommand({
+            command: 'capture_native_screenshot',
+            args: { format: 'png', scrollContainerSelector: '#__mcp_scroll_pane__' },
+         }, 30000);
+
+         expect(response.success).toBe(true);
+
+         const dataUrl = (response.data as { dataUrl: string }).dataUrl,
+               png = Buffer.from(dataUrl.split(',')[1], 'base64');
+
+         // The stitched image has the pane's full content height, not its box
+         expect(height).toBe(2000);
+         expect(png.readUInt32BE(20) / png.readUInt32BE(16)).toBeCloseTo(height / width, 1);
+
+         const scrollTop = await executeJavaScript({ script: `return document.getElementById('__mcp_scroll_pane__').scrollTop;` });
+
+         expect(String(scrollTop)).toBe('0');
+      } finally {
+         await executeJavaScript({ script: `document.getElementById('__mcp_scroll_pane__').remove();` });
+      }
+   }, TIMEOUT);
+
+   it('should keep isolated-world globals out of the page', async () => {
+      const isolated = await executeIPCCommand('plugin:mcp-bridge|execute_js', {
+         script: `window.__mcpIsolated = 1; var __mcpIsolatedVar = 2;
+            return document.body !== null;`,
+         world: 'isolated',
+      });
+
+      expect(JSON.parse(isolated).success).toBe(true);
+
+      const result = await executeIPCCommand('plugin:mcp-bridge|execute_js', {
+         script: `return [typeof window.__mcpIsolated, typeof window.__mcpIsolatedVar];`,
+      });
+      const parsed = JSON.parse(result);
+
+      expect(parsed.success).toBe(true);
+      expect(parsed.result.data).toEqual([ 'undefined', 'undefined' ]);
+   }, TIMEOUT);
+
+   it('should set the dark theme and read it back', async () => {
+      const set = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|set_theme', { theme: 'dark' }));
+
+      try {
+         expect(set.success).toBe(true);
+         expect(set.result.theme).toBe('dark');
+
+         const get = JSON.parse(await executeIPCCommand('plugin:mcp-bridge|get_theme'));
+
+         expect(get.success).toBe(true);
+         expect(get.result).toEqual(set.result);
+      } finally {
+         await executeIPCCommand('plugin:mcp-bridge|set_theme', { theme: 'auto' });
+      }
+   }, TIMEOUT);
+
    it('should execute IPC commands via plugin', async () => {
       const result = await executeIPCCommand('add_numbers', { a: 5, b: 3 });
 
@@ -7,6 +7,72 @@ fn main() {
         "start_ipc_monitor",
         "stop_ipc_monitor",
         "get_ipc_events",
+        "wait_for_event",
+        "execute_js_all",
+        "get_system_status",
+        "get_bridge_logs",
+        "set_window_flags",
+        "execute_js_file",
+        "list_resources",
+        "read_resource",
+        "get_element_box",
+        "report_ipc_event",
+        "get_input_value",
+        "set_input_value",
+        "is_interactable",
+        "get_active_window",
+        "set_active_window",
+        "execute_js_if",
+        "get_clients",
+        "get_app_paths",
+        "reset_window",
+        "get_computed_style",
+        "pause_ipc_monitor",
+        "resume_ipc_monitor",
+        "list_event_listeners",
+        "track_event_listener",
+        "get_ready_state",
+        "wait_for_ready",
+        "get_selection",
+        "set_selection",
+        "dispatch_dom_event",
+        "abort_all",
+        "get_current_url",
+        "get_console_history",
+        "report_console_entry",
+        "drag_and_drop",
+        "get_user_agent",
+        "set_user_agent",
+        "get_injected_globals_count",
+        "get_meta_tags",
+        "wait_for_network_idle",
+        "get_config",
+        "observe_mutations",
+        "emulate_media",
+        "clear_cache",
+        "get_scroll_info",
+        "assert_element",
+        "freeze_time",
+        "step_time",
+        "unfreeze_time",
+        "capture_window_with_chrome",
+        "get_web_vitals",
+        "list_macros",
+        "run_macro",
+        "get_failed_requests",
+        "report_failed_request",
+        "get_readable_text",
+        "get_theme",
+        "set_theme",
+        "compare_screenshot",
+        "request_mocks",
+        "element_from_point",
+        "fill_form",
+        "read_form",
+        "get_active_element",
+        "focus_element",
+        "get_layout",
+        "set_layout",
     ])
     .build();
 }
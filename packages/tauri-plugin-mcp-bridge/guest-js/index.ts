@@ -8,6 +8,54 @@ export interface WindowInfo {
    title: string;
    focused: boolean;
    visible: boolean;
+   alwaysOnTop: boolean;
+   decorations: boolean;
+   resizable: boolean;
+   fullscreen: boolean;
+}
+
+export interface WindowFlags {
+   alwaysOnTop?: boolean;
+   decorations?: boolean;
+   resizable?: boolean;
+   fullscreen?: boolean;
+}
+
+export interface WindowLayout {
+   label: string;
+   x: number;
+   y: number;
+   width: number;
+   height: number;
+   maximized?: boolean;
+   minimized?: boolean;
+   fullscreen?: boolean;
+   alwaysOnTop?: boolean;
+   url?: string;
+}
+
+export interface SetLayoutResult {
+   results: {
+      label: string;
+      success: boolean;
+      data?: { created: boolean; layout: WindowLayout };
+      error?: string;
+   }[];
+   successCount: number;
+   failureCount: number;
+}
+
+export interface ResetOptions {
+   clearCache?: boolean;
+   clearStorage?: boolean;
+   clearCookies?: boolean;
+}
+
+export interface ResetReport {
+   windowLabel: string;
+   cleared: Array<'cache' | 'storage' | 'cookies'>;
+   native: boolean;
+   notes: string[];
 }
 
 export interface BackendState {
@@ -15,6 +63,267 @@ export interface BackendState {
    windows: number;
 }
 
+export interface AppPaths {
+   appDataDir: string | null;
+   appConfigDir: string | null;
+   appCacheDir: string | null;
+   appLogDir: string | null;
+   resourceDir: string | null;
+}
+
+export interface SystemStatus {
+   batteryPercent?: number;
+   charging?: boolean;
+   online?: boolean;
+   memoryUsedBytes: number;
+   memoryTotalBytes: number;
+   cpuCount: number;
+}
+
+export interface ElementBox {
+   x: number;
+   y: number;
+   width: number;
+   height: number;
+   top: number;
+   right: number;
+   bottom: number;
+   left: number;
+   devicePixelRatio: number;
+   devicePixels: { x: number; y: number; width: number; height: number };
+   scrollX: number;
+   scrollY: number;
+   inViewport: boolean;
+}
+
+export interface FillFormResult {
+   results: Record<string, { success: true; value: InputValue } | { success: false; error: string }>;
+   successCount: number;
+   failureCount: number;
+}
+
+export interface ElementAtPoint {
+   tagName: string;
+   id: string | null;
+   classes: string[];
+   text: string;
+   selector: string;
+}
+
+export interface ActiveElement extends ElementAtPoint {
+   value?: string | string[];
+   checked?: boolean;
+}
+
+export interface FocusResult {
+   focused: boolean;
+   activeElement: ActiveElement | null;
+}
+
+export interface InputValue {
+   type: string;
+   value: string | string[];
+   checked?: boolean;
+}
+
+export interface InteractableState {
+   exists: boolean;
+   visible: boolean;
+   inViewport: boolean;
+   enabled: boolean;
+   occluded: boolean | null;
+}
+
+export interface ReadyStateReport {
+   readyState: 'interactive' | 'complete';
+   waitedMs: number;
+}
+
+export interface NetworkIdleReport {
+   idle: boolean;
+   pendingAtTimeout?: number;
+   waitedMs: number;
+}
+
+export interface MutationSummary {
+   addedNodes: number;
+   removedNodes: number;
+   attributeChanges: number;
+   characterDataChanges: number;
+   stable: boolean;
+   lastMutationMs?: number;
+}
+
+export interface MediaFeatures {
+   prefersColorScheme?: 'light' | 'dark';
+   prefersReducedMotion?: 'reduce' | 'no-preference';
+   print?: boolean;
+}
+
+export interface MediaEmulation {
+   applied: MediaFeatures;
+   method: 'devtools' | 'css';
+   rewrittenRules?: number;
+   skippedStylesheets?: number;
+   matches: Record<string, boolean>;
+}
+
+export interface WindowTheme {
+   theme: 'light' | 'dark';
+   method: 'native' | 'media';
+}
+
+export interface CacheClearReport {
+   windowLabel: string;
+   kind: 'disk' | 'memory' | 'all';
+   cleared: string[];
+   api: string;
+}
+
+export interface ScrollInfo {
+   scrollX: number;
+   scrollY: number;
+   scrollWidth: number;
+   scrollHeight: number;
+   clientWidth: number;
+   clientHeight: number;
+   atTop: boolean;
+   atBottom: boolean;
+   atLeft: boolean;
+   atRight: boolean;
+}
+
+export interface ScreenshotComparison {
+   match: boolean;
+   diffPixelCount: number;
+   diffRatio: number;
+   diffImageDataUrl?: string;
+}
+
+export interface WindowCapture {
+   dataUrl: string;
+   width: number;
+   height: number;
+   chromeIncluded: boolean;
+   method: 'window' | 'viewport';
+   warning?: string;
+}
+
+export interface WebVitals {
+   cls: number | null;
+   lcpMs: number | null;
+   fidMs: number | null;
+   inpMs: number | null;
+   unsupported: string[];
+}
+
+export type MacroStep =
+   | { type: 'scroll'; selector?: string; x?: number; y?: number }
+   | { type: 'click'; selector: string }
+   | { type: 'wait'; ms?: number; selector?: string; timeoutMs?: number }
+   | { type: 'screenshot'; format?: 'png' | 'jpeg'; quality?: number };
+
+export interface MacroInfo {
+   name: string;
+   steps: MacroStep[];
+}
+
+export interface MacroStepResult {
+   index: number;
+   type: MacroStep['type'];
+   success: boolean;
+   data?: unknown;
+   error?: string;
+   durationMs: number;
+}
+
+export interface MacroReport {
+   name: string;
+   completed: boolean;
+   steps: MacroStepResult[];
+}
+
+export interface SelectionInfo {
+   text: string;
+   isCollapsed: boolean;
+   anchorSelector?: string;
+   rangeCount: number;
+}
+
+export interface ReadableText {
+   title: string;
+   url: string;
+   text: string;
+   bytes: number;
+   truncated: boolean;
+}
+
+export interface AbortSummary {
+   operationsCancelled: number;
+   pendingResultsCleared: number;
+   screencastsStopped: number;
+   ipcMonitorStopped: boolean;
+}
+
+export interface ConsoleEntry {
+   level: 'log' | 'info' | 'warn' | 'error';
+   message: string;
+   timestamp: number;
+}
+
+export interface FailedRequest {
+   url: string;
+   /** `fetch`, `xhr`, or the tag of the element that failed to load, such as `img` or `script` */
+   type: string;
+   /** HTTP status, or `null` for element loads and network errors */
+   status: number | null;
+   timestamp: number;
+}
+
+export interface DragAndDropResult {
+   dropped: boolean;
+   method: 'html5' | 'pointer';
+   source: { x: number; y: number };
+   target: { x: number; y: number };
+}
+
+export interface UserAgentInfo {
+   userAgent: string;
+   overrideUserAgent?: string;
+   reloadRequired: boolean;
+}
+
+export interface InjectedGlobals {
+   count: number;
+   names: string[];
+}
+
+export interface MetaTag {
+   name?: string;
+   property?: string;
+   content: string | null;
+}
+
+export interface MetaTags {
+   tags: MetaTag[];
+   openGraph: Record<string, string | string[]>;
+   twitter: Record<string, string | string[]>;
+}
+
+export interface ResourceList {
+   resourceDir: string;
+   files: string[];
+}
+
+export interface ResourceContent {
+   path: string;
+   sizeBytes: number;
+   truncated: boolean;
+   binary: boolean;
+   encoding: 'utf8' | 'base64';
+   content: string;
+}
+
 export interface IPCEvent {
    timestamp: number;
    command: string;
@@ -22,6 +331,31 @@ export interface IPCEvent {
    result?: unknown;
    error?: string;
    duration_ms?: number;
+   windowLabel?: string;
+   source?: string;
+   stack?: string;
+}
+
+export interface ReportedIPCEvent {
+   command: string;
+   args?: unknown;
+   result?: unknown;
+   error?: string;
+   stack?: string;
+   durationMs?: number;
+}
+
+export interface ClientInfo {
+   id: number;
+   address: string;
+   name: string | null;
+   connectedAt: number;
+   requests: number;
+}
+
+export interface EventListenerCount {
+   event: string;
+   count: number;
 }
 
 /**
@@ -38,6 +372,79 @@ export async function getWindowInfo(): Promise<WindowInfo> {
    return await invoke('plugin:mcp-bridge|get_window_info');
 }
 
+/**
+ * List the clients connected to the bridge's WebSocket server
+ */
+export async function getClients(): Promise<ClientInfo[]> {
+   return await invoke('plugin:mcp-bridge|get_clients');
+}
+
+/**
+ * Get the console entries the window logged, oldest first. The last 500 per window are kept.
+ */
+export async function getConsoleHistory(
+   options: { levels?: ConsoleEntry['level'][]; limit?: number } = {}
+): Promise<ConsoleEntry[]> {
+   return await invoke('plugin:mcp-bridge|get_console_history', { ...options });
+}
+
+/**
+ * Get the resource loads and fetch/XHR calls that failed in the window, oldest first. The last 200 per window are kept.
+ */
+export async function getFailedRequests(options: { since?: number } = {}): Promise<FailedRequest[]> {
+   return await invoke('plugin:mcp-bridge|get_failed_requests', { ...options });
+}
+
+/**
+ * List the Tauri event listeners registered by the frontend, per event.
+ * Covers listeners registered after the bridge script loaded.
+ */
+export async function listEventListeners(windowLabel?: string): Promise<EventListenerCount[]> {
+   return await invoke('plugin:mcp-bridge|list_event_listeners', { windowLabel });
+}
+
+/**
+ * Get the label of the focused window, or null if none has focus
+ */
+export async function getActiveWindow(): Promise<string | null> {
+   return await invoke('plugin:mcp-bridge|get_active_window');
+}
+
+/**
+ * Bring a window to the front and focus it
+ */
+export async function setActiveWindow(windowLabel: string): Promise<string> {
+   return await invoke('plugin:mcp-bridge|set_active_window', { windowLabel });
+}
+
+/**
+ * Snapshot the position, size and state of every window
+ */
+export async function getLayout(): Promise<WindowLayout[]> {
+   return await invoke('plugin:mcp-bridge|get_layout');
+}
+
+/**
+ * Restore a layout from `getLayout`, recreating closed windows from their `url`
+ */
+export async function setLayout(layout: WindowLayout[]): Promise<SetLayoutResult> {
+   return await invoke('plugin:mcp-bridge|set_layout', { layout });
+}
+
+/**
+ * Clear a window's cache, storage and/or cookies (all by default), then reload it
+ */
+export async function resetWindow(windowLabel?: string, options: ResetOptions = {}): Promise<ResetReport> {
+   return await invoke('plugin:mcp-bridge|reset_window', { windowLabel, ...options });
+}
+
+/**
+ * Set window flags; only the provided flags are changed
+ */
+export async function setWindowFlags(flags: WindowFlags): Promise<Required<WindowFlags>> {
+   return await invoke('plugin:mcp-bridge|set_window_flags', { ...flags });
+}
+
 /**
  * Get backend application state
  */
@@ -46,17 +453,381 @@ export async function getBackendState(): Promise<BackendState> {
 }
 
 /**
- * Emit a custom event for testing
+ * Get the directories the app stores data, config, cache, logs and resources in
+ */
+export async function getAppPaths(): Promise<AppPaths> {
+   return await invoke('plugin:mcp-bridge|get_app_paths');
+}
+
+/**
+ * Get live host metrics (requires the `system-status` feature)
+ */
+export async function getSystemStatus(): Promise<SystemStatus> {
+   return await invoke('plugin:mcp-bridge|get_system_status');
+}
+
+/**
+ * Emit a custom event for testing, delivered to frontend and Rust listeners alike
  */
 export async function emitEvent(eventName: string, payload?: unknown): Promise<string> {
    return await invoke('plugin:mcp-bridge|emit_event', { eventName, payload });
 }
 
+export interface BridgeLogEntry {
+   level: 'info' | 'error';
+   scope: string;
+   message: string;
+   timestamp: number;
+}
+
+/**
+ * Measure the first element matching a CSS selector, in CSS and device pixels.
+ * Windows only.
+ */
+export async function getElementBox(selector: string): Promise<ElementBox> {
+   return await invoke('plugin:mcp-bridge|get_element_box', { selector });
+}
+
+/**
+ * Get the topmost element at viewport coordinates in CSS pixels, or null if
+ * nothing is there.
+ */
+export async function elementFromPoint(x: number, y: number): Promise<ElementAtPoint | null> {
+   return await invoke('plugin:mcp-bridge|element_from_point', { x, y });
+}
+
+/**
+ * Read computed CSS properties of an element; omit `properties` for a common set
+ * (display, visibility, colors, font, ...). Unknown properties are null.
+ */
+export async function getElementComputedStyle(selector: string, properties?: string[]): Promise<Record<string, string | null>> {
+   return await invoke('plugin:mcp-bridge|get_computed_style', { selector, properties });
+}
+
+/**
+ * Check an element's text, value, attribute or match count against an expected value.
+ * With `operator: 'matches'`, `expected` is a regular expression
+ */
+export async function assertElement(
+   selector: string,
+   property: 'text' | 'value' | 'attribute' | 'count',
+   expected: string | number,
+   options: { operator?: 'eq' | 'contains' | 'matches'; attributeName?: string } = {}
+): Promise<{ passed: boolean; actual: string | number | null; expected: string | number }> {
+   return await invoke('plugin:mcp-bridge|assert_element', { selector, property, expected, ...options });
+}
+
+/**
+ * Check whether an element is visible, enabled and not covered before clicking it
+ */
+export async function isInteractable(selector: string): Promise<InteractableState> {
+   return await invoke('plugin:mcp-bridge|is_interactable', { selector });
+}
+
+/**
+ * List the per-execution __mcp_* globals left on the page, for leak tests
+ */
+export async function getInjectedGlobalsCount(): Promise<InjectedGlobals> {
+   return await invoke('plugin:mcp-bridge|get_injected_globals_count');
+}
+
+/**
+ * Get the page's meta tags, with OpenGraph and Twitter card fields grouped
+ */
+export async function getMetaTags(): Promise<MetaTags> {
+   return await invoke('plugin:mcp-bridge|get_meta_tags');
+}
+
+/**
+ * Get the page's location.href, which follows history.pushState route changes
+ */
+export async function getCurrentUrl(): Promise<string> {
+   return await invoke('plugin:mcp-bridge|get_current_url');
+}
+
+/**
+ * Get the user agent the page reports, and any override set with setUserAgent
+ */
+export async function getUserAgent(): Promise<UserAgentInfo> {
+   return await invoke('plugin:mcp-bridge|get_user_agent');
+}
+
+/**
+ * Override the window's user agent. Pages see it after their next load
+ */
+export async function setUserAgent(userAgent: string): Promise<UserAgentInfo> {
+   return await invoke('plugin:mcp-bridge|set_user_agent', { userAgent });
+}
+
+/**
+ * Get the document.readyState of the window: 'loading', 'interactive' or 'complete'
+ */
+export async function getReadyState(): Promise<DocumentReadyState> {
+   return await invoke('plugin:mcp-bridge|get_ready_state');
+}
+
+/**
+ * Wait until the document reaches a ready state (or a later one), surviving navigations
+ */
+export async function waitForReady(state: 'interactive' | 'complete', timeoutMs?: number): Promise<ReadyStateReport> {
+   return await invoke('plugin:mcp-bridge|wait_for_ready', { state, timeoutMs });
+}
+
+/**
+ * Wait until no fetch or XMLHttpRequest calls have been in flight for idleMs
+ */
+export async function waitForNetworkIdle(
+   options: { idleMs?: number; timeoutMs?: number } = {}
+): Promise<NetworkIdleReport> {
+   return await invoke('plugin:mcp-bridge|wait_for_network_idle', { ...options });
+}
+
+/**
+ * Count DOM changes over durationMs and report whether the page has settled
+ */
+export async function observeMutations(durationMs: number, quietMs?: number): Promise<MutationSummary> {
+   return await invoke('plugin:mcp-bridge|observe_mutations', { durationMs, quietMs });
+}
+
+/**
+ * Emulate CSS media features such as dark mode or print, or clear the emulation with reset
+ */
+export async function emulateMedia(
+   options: { features?: MediaFeatures; reset?: boolean } = {}
+): Promise<MediaEmulation> {
+   return await invoke('plugin:mcp-bridge|emulate_media', { ...options });
+}
+
+/**
+ * Get the window's effective light/dark theme
+ */
+export async function getTheme(): Promise<WindowTheme> {
+   return await invoke('plugin:mcp-bridge|get_theme');
+}
+
+/**
+ * Switch the window's theme, including native chrome, or follow the system again with 'auto'
+ */
+export async function setTheme(theme: 'light' | 'dark' | 'auto'): Promise<WindowTheme> {
+   return await invoke('plugin:mcp-bridge|set_theme', { theme });
+}
+
+/**
+ * Clear the window's HTTP cache through the native webview, keeping storage and cookies
+ */
+export async function clearCache(
+   options: { kind?: 'disk' | 'memory' | 'all' } = {}
+): Promise<CacheClearReport> {
+   return await invoke('plugin:mcp-bridge|clear_cache', { ...options });
+}
+
+/**
+ * Get the page's scroll position and document size, for planning scrolls and full-page captures
+ */
+export async function getScrollInfo(): Promise<ScrollInfo> {
+   return await invoke('plugin:mcp-bridge|get_scroll_info');
+}
+
+/**
+ * Capture the whole native window including its frame and titlebar, falling back to the viewport where unsupported
+ */
+export async function captureWindowWithChrome(
+   options: { format?: 'png' | 'jpeg'; quality?: number } = {}
+): Promise<WindowCapture> {
+   return await invoke('plugin:mcp-bridge|capture_window_with_chrome', { ...options });
+}
+
+/**
+ * Capture the viewport and compare it with a baseline PNG from the configured baselines directory, with an optional highlighted diff image
+ */
+export async function compareScreenshot(
+   baselinePath: string,
+   options: { threshold?: number; maxDiffRatio?: number; diffImage?: boolean } = {}
+): Promise<ScreenshotComparison> {
+   return await invoke('plugin:mcp-bridge|compare_screenshot', { baselinePath, ...options });
+}
+
+/**
+ * Get the page's core web vitals (CLS, LCP, FID, INP); metrics not available yet are null
+ */
+export async function getWebVitals(): Promise<WebVitals> {
+   return await invoke('plugin:mcp-bridge|get_web_vitals');
+}
+
+/**
+ * List the macros registered with `Builder::register_macro`
+ */
+export async function listMacros(): Promise<MacroInfo[]> {
+   return await invoke('plugin:mcp-bridge|list_macros');
+}
+
+/**
+ * Run a registered macro, filling its `{{param}}` placeholders from `params`. Stops at the first failing step
+ */
+export async function runMacro(name: string, params?: Record<string, unknown>): Promise<MacroReport> {
+   return await invoke('plugin:mcp-bridge|run_macro', { name, params });
+}
+
+/**
+ * Freeze the page's clock and timers for deterministic screenshots. Intrusive; use only in tests
+ */
+export async function freezeTime(options: { at?: number } = {}): Promise<{ frozen: true; alreadyFrozen: boolean; now: number; pendingTimers: number }> {
+   return await invoke('plugin:mcp-bridge|freeze_time', { ...options });
+}
+
+/**
+ * Advance a frozen clock by `ms`, running the timers and animation frames that come due
+ */
+export async function stepTime(ms: number): Promise<{ now: number; elapsed: number; fired: number; pendingTimers: number; errors: string[] }> {
+   return await invoke('plugin:mcp-bridge|step_time', { ms });
+}
+
+/**
+ * Restore the page's real clock and timers
+ */
+export async function unfreezeTime(): Promise<{ frozen: false; resumedTimers: number }> {
+   return await invoke('plugin:mcp-bridge|unfreeze_time');
+}
+
+/**
+ * Get the visible text of the page as markdown, skipping scripts, styles and hidden elements
+ */
+export async function getReadableText(options: { maxBytes?: number } = {}): Promise<ReadableText> {
+   return await invoke('plugin:mcp-bridge|get_readable_text', { ...options });
+}
+
+/**
+ * Get the page's current text selection, including text selected inside form fields
+ */
+export async function getSelection(): Promise<SelectionInfo> {
+   return await invoke('plugin:mcp-bridge|get_selection');
+}
+
+/**
+ * Select the contents of an element and return the new selection
+ */
+export async function setSelection(selector: string): Promise<SelectionInfo> {
+   return await invoke('plugin:mcp-bridge|set_selection', { selector });
+}
+
+/**
+ * Dispatch a DOM event on an element. Returns false if a listener canceled it
+ */
+export async function dispatchDomEvent(
+   selector: string,
+   eventType: string,
+   options: { bubbles?: boolean; detail?: unknown } = {}
+): Promise<boolean> {
+   return await invoke('plugin:mcp-bridge|dispatch_dom_event', { selector, eventType, ...options });
+}
+
+/**
+ * Drag one element onto another, using HTML5 drag events or pointer events
+ */
+export async function dragAndDrop(
+   sourceSelector: string,
+   targetSelector: string,
+   options: { mode?: 'auto' | 'html5' | 'pointer' } = {}
+): Promise<DragAndDropResult> {
+   return await invoke('plugin:mcp-bridge|drag_and_drop', { sourceSelector, targetSelector, ...options });
+}
+
+/**
+ * Read the value of a form field or contenteditable element
+ */
+export async function getInputValue(selector: string): Promise<InputValue> {
+   return await invoke('plugin:mcp-bridge|get_input_value', { selector });
+}
+
+/**
+ * Set several form fields at once, keyed by CSS selector; each field reports its own outcome
+ */
+export async function fillForm(fields: Record<string, string | boolean | string[]>): Promise<FillFormResult> {
+   return await invoke('plugin:mcp-bridge|fill_form', { fields });
+}
+
+/**
+ * Read every named field of a form as `{ name: value }`
+ */
+export async function readForm(formSelector: string): Promise<Record<string, string | boolean | string[] | null>> {
+   return await invoke('plugin:mcp-bridge|read_form', { formSelector });
+}
+
+/**
+ * Describe the element with keyboard focus, or `null` when focus is on the page itself
+ */
+export async function getActiveElement(): Promise<ActiveElement | null> {
+   return await invoke('plugin:mcp-bridge|get_active_element');
+}
+
+/**
+ * Move keyboard focus to an element and report whether it took focus
+ */
+export async function focusElement(selector: string): Promise<FocusResult> {
+   return await invoke('plugin:mcp-bridge|focus_element', { selector });
+}
+
+/**
+ * Set a form field's value and dispatch the input/change events frameworks expect
+ */
+export async function setInputValue(selector: string, value: string | boolean | string[]): Promise<InputValue> {
+   return await invoke('plugin:mcp-bridge|set_input_value', { selector, value });
+}
+
+/**
+ * List files bundled in the app's resource directory, optionally filtered by a glob
+ */
+export async function listResources(glob?: string): Promise<ResourceList> {
+   return await invoke('plugin:mcp-bridge|list_resources', { glob });
+}
+
+/**
+ * Read a file from the app's resource directory (text as UTF-8, binary as base64)
+ */
+export async function readResource(path: string, maxBytes?: number): Promise<ResourceContent> {
+   return await invoke('plugin:mcp-bridge|read_resource', { path, maxBytes });
+}
+
+/**
+ * Get the most recent internal bridge log lines, oldest first
+ */
+export async function getBridgeLogs(limit?: number): Promise<BridgeLogEntry[]> {
+   return await invoke('plugin:mcp-bridge|get_bridge_logs', { limit });
+}
+
+/**
+ * Get the bridge's effective configuration, with secrets redacted
+ */
+export async function getConfig(): Promise<Record<string, unknown>> {
+   return await invoke('plugin:mcp-bridge|get_config');
+}
+
+export interface WaitForEventResult {
+   eventName: string;
+   payload: unknown;
+}
+
+/**
+ * Wait for the app to emit an event, resolving with its payload
+ */
+export async function waitForEvent(eventName: string, timeoutMs?: number): Promise<WaitForEventResult> {
+   return await invoke('plugin:mcp-bridge|wait_for_event', { eventName, timeoutMs });
+}
+
+/**
+ * Cancel every in-flight script execution and wait, and clear pending script results
+ */
+export async function abortAll(
+   options: { stopIpcMonitor?: boolean; stopScreencasts?: boolean } = {}
+): Promise<AbortSummary> {
+   return await invoke('plugin:mcp-bridge|abort_all', { ...options });
+}
+
 /**
  * Start IPC monitoring - captures all invoke() calls
  */
-export async function startIPCMonitor(): Promise<string> {
-   return await invoke('plugin:mcp-bridge|start_ipc_monitor');
+export async function startIPCMonitor(windowLabel?: string): Promise<string> {
+   return await invoke('plugin:mcp-bridge|start_ipc_monitor', { windowLabel });
 }
 
 /**
@@ -66,9 +837,30 @@ export async function stopIPCMonitor(): Promise<string> {
    return await invoke('plugin:mcp-bridge|stop_ipc_monitor');
 }
 
+/**
+ * Pause IPC capture, keeping the events captured so far
+ */
+export async function pauseIPCMonitor(): Promise<string> {
+   return await invoke('plugin:mcp-bridge|pause_ipc_monitor');
+}
+
+/**
+ * Resume IPC capture after pauseIPCMonitor()
+ */
+export async function resumeIPCMonitor(): Promise<string> {
+   return await invoke('plugin:mcp-bridge|resume_ipc_monitor');
+}
+
 /**
  * Get all captured IPC events
  */
-export async function getIPCEvents(): Promise<IPCEvent[]> {
-   return await invoke('plugin:mcp-bridge|get_ipc_events');
+export async function getIPCEvents(windowLabel?: string): Promise<IPCEvent[]> {
+   return await invoke('plugin:mcp-bridge|get_ipc_events', { windowLabel });
+}
+
+/**
+ * Report an IPC call the app observed itself to the IPC monitor
+ */
+export async function reportIPCEvent(event: ReportedIPCEvent): Promise<void> {
+   await invoke('plugin:mcp-bridge|report_ipc_event', event);
 }
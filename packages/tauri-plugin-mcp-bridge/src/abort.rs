@@ -0,0 +1,218 @@
+//! Bridge-wide cancellation for the `abort_all` command.
+//!
+//! Long-running commands (script execution, `wait_for_event`,
+//! `wait_for_ready`, `wait_for_network_idle`) run through [`cancellable`],
+//! which races them against the managed [`AbortSignal`]. `abort_all` fires
+//! the signal and every such operation in flight at that moment resolves
+//! with [`ABORTED`]. Operations started afterwards run normally.
+//!
+//! Screencasts hold a separate [`AbortToken`] and end at their next frame
+//! when `abort_all` is asked to stop them.
+
+use std::future::Future;
+use std::sync::Arc;
+use tauri::{Manager, Runtime};
+use tokio::sync::watch;
+
+/// Error returned by operations cancelled with `abort_all`.
+pub const ABORTED: &str = "Cancelled by abort_all";
+
+tokio::task_local! {
+    /// Set while a cancellable operation runs, so operations nested in it
+    /// (e.g. the scripts behind `wait_for_ready`) aren't counted twice.
+    static IN_OPERATION: ();
+}
+
+/// Cancellation signal shared by the bridge's long-running operations.
+///
+/// Cheap to clone; clones share the same signal. The plugin manages one as
+/// Tauri state.
+#[derive(Clone)]
+pub struct AbortSignal {
+    operations: Arc<watch::Sender<u64>>,
+    streams: Arc<watch::Sender<u64>>,
+}
+
+impl Default for AbortSignal {
+    fn default() -> Self {
+        Self {
+            operations: Arc::new(watch::channel(0).0),
+            streams: Arc::new(watch::channel(0).0),
+        }
+    }
+}
+
+impl AbortSignal {
+    /// Creates a signal with nothing in flight.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a token for an operation, aborted by the next
+    /// [`abort_operations`](Self::abort_operations).
+    pub fn operation_token(&self) -> AbortToken {
+        AbortToken {
+            rx: self.operations.subscribe(),
+        }
+    }
+
+    /// Returns a token for a stream, aborted by the next
+    /// [`abort_streams`](Self::abort_streams).
+    pub fn stream_token(&self) -> AbortToken {
+        AbortToken {
+            rx: self.streams.subscribe(),
+        }
+    }
+
+    /// Number of operations currently holding a token.
+    pub fn operations_in_flight(&self) -> usize {
+        self.operations.receiver_count()
+    }
+
+    /// Aborts every operation in flight.
+    ///
+    /// # Returns
+    ///
+    /// The number of operations that were aborted.
+    pub fn abort_operations(&self) -> usize {
+        let count = self.operations.receiver_count();
+        self.operations.send_modify(|generation| *generation += 1);
+        count
+    }
+
+    /// Aborts every running stream.
+    ///
+    /// # Returns
+    ///
+    /// The number of streams that were aborted.
+    pub fn abort_streams(&self) -> usize {
+        let count = self.streams.receiver_count();
+        self.streams.send_modify(|generation| *generation += 1);
+        count
+    }
+
+    /// Runs `operation` until it completes or the signal aborts it.
+    ///
+    /// An operation started inside another one is covered by the outer one
+    /// and runs as is.
+    ///
+    /// # Returns
+    ///
+    /// The operation's result, or `Err(ABORTED)` if it was aborted.
+    pub async fn run<T, F>(&self, operation: F) -> Result<T, String>
+    where
+        F: Future<Output = Result<T, String>>,
+    {
+        if IN_OPERATION.try_with(|_| ()).is_ok() {
+            return operation.await;
+        }
+
+        let mut token = self.operation_token();
+        IN_OPERATION
+            .scope((), async move {
+                tokio::select! {
+                    result = operation => result,
+                    _ = token.aborted() => Err(ABORTED.to_string()),
+                }
+            })
+            .await
+    }
+}
+
+/// Observes one [`AbortSignal`] channel from the moment it was created.
+pub struct AbortToken {
+    rx: watch::Receiver<u64>,
+}
+
+impl AbortToken {
+    /// Whether the signal fired since the token was created.
+    pub fn is_aborted(&self) -> bool {
+        self.rx.has_changed().unwrap_or(false)
+    }
+
+    /// Resolves once the signal fires.
+    pub async fn aborted(&mut self) {
+        // Without a sender the signal can never fire
+        if self.rx.changed().await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Runs `operation` under the app's [`AbortSignal`], or as is when the
+/// plugin isn't set up (as in unit tests).
+pub async fn cancellable<R, M, T, F>(manager: &M, operation: F) -> Result<T, String>
+where
+    R: Runtime,
+    M: Manager<R>,
+    F: Future<Output = Result<T, String>>,
+{
+    let signal = manager
+        .try_state::<AbortSignal>()
+        .map(|signal| signal.inner().clone());
+    match signal {
+        Some(signal) => signal.run(operation).await,
+        None => operation.await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn long_operation(signal: AbortSignal) -> Result<(), String> {
+        signal
+            .run(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(())
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_abort_cancels_every_operation_in_flight() {
+        let signal = AbortSignal::new();
+        let operations: Vec<_> = (0..3)
+            .map(|_| tokio::spawn(long_operation(signal.clone())))
+            .collect();
+        while signal.operations_in_flight() < 3 {
+            tokio::task::yield_now().await;
+        }
+
+        assert_eq!(signal.abort_operations(), 3);
+        for operation in operations {
+            let result = tokio::time::timeout(Duration::from_secs(1), operation)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(result, Err(ABORTED.to_string()));
+        }
+        assert_eq!(signal.operations_in_flight(), 0);
+
+        // Later operations aren't affected
+        assert_eq!(signal.run(async { Ok(7) }).await, Ok(7));
+    }
+
+    #[tokio::test]
+    async fn test_nested_operations_count_once() {
+        let signal = AbortSignal::new();
+        let inner = signal.clone();
+        let outer = tokio::spawn({
+            let signal = signal.clone();
+            async move { signal.run(long_operation(inner)).await }
+        });
+        while signal.operations_in_flight() < 1 {
+            tokio::task::yield_now().await;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        assert_eq!(signal.abort_operations(), 1);
+        assert_eq!(outer.await.unwrap(), Err(ABORTED.to_string()));
+
+        let stream = signal.stream_token();
+        assert!(!stream.is_aborted());
+        assert_eq!(signal.abort_streams(), 1);
+        assert!(stream.is_aborted());
+    }
+}
@@ -0,0 +1,213 @@
+//! Token authentication for WebSocket clients.
+//!
+//! With a token configured through [`Builder::auth_token`] (or
+//! [`Builder::auth_token_from_keyring`] with the `keyring` feature), the
+//! `capabilities` frame reports `auth: true` and a client must send
+//!
+//! ```json
+//! { "id": "1", "command": "authenticate", "args": { "token": "..." } }
+//! ```
+//!
+//! before anything but `hello` and the read-only commands. A wrong token
+//! closes the connection with `4001 unauthorized`.
+//!
+//! # Read-only access
+//!
+//! Unauthenticated connections may still call the read-only commands set
+//! with [`Builder::read_only_commands`], so monitoring dashboards can connect
+//! without the token. By default these are
+//!
+//! | Command             | Sent as                                                     |
+//! |---------------------|-------------------------------------------------------------|
+//! | `get_system_status` | `invoke_tauri` with `plugin:mcp-bridge\|get_system_status` |
+//! | `list_windows`      | `list_windows`                                              |
+//! | `get_backend_state` | `invoke_tauri` with `plugin:mcp-bridge\|get_backend_state` |
+//!
+//! Any other command gets `{ success: false, error: "unauthorized" }` until
+//! the connection authenticates, and the connection stays open.
+//!
+//! [`Builder::auth_token`]: crate::Builder::auth_token
+//! [`Builder::auth_token_from_keyring`]: crate::Builder::auth_token_from_keyring
+//! [`Builder::read_only_commands`]: crate::Builder::read_only_commands
+
+use serde_json::Value;
+
+/// Commands a client may send before authenticating.
+const UNAUTHENTICATED_COMMANDS: &[&str] = &["hello"];
+
+/// Prefix of this plugin's commands sent through `invoke_tauri`.
+const PLUGIN_COMMAND_PREFIX: &str = "plugin:mcp-bridge|";
+
+/// What to do with a request, as decided by [`AuthGate::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthOutcome {
+    /// Run the command
+    Allowed,
+    /// The request was an `authenticate` with the right token
+    Authenticated,
+    /// Respond with an `unauthorized` error, keeping the connection open
+    Denied,
+    /// Close the connection with `4001 unauthorized`
+    Rejected,
+}
+
+/// Authentication state of one connection.
+#[derive(Debug, Clone)]
+pub struct AuthGate {
+    token: Option<String>,
+    read_only: Vec<String>,
+    authenticated: bool,
+}
+
+impl AuthGate {
+    /// Creates the gate for a new connection. Without a token every
+    /// request is allowed.
+    pub fn new(token: Option<String>) -> Self {
+        Self {
+            token,
+            read_only: Vec::new(),
+            authenticated: false,
+        }
+    }
+
+    /// Lets unauthenticated connections call `commands`.
+    pub fn with_read_only(mut self, commands: Vec<String>) -> Self {
+        self.read_only = commands;
+        self
+    }
+
+    /// Checks a request, recording a successful `authenticate`.
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - The request's command name, as returned by
+    ///   [`gated_command`]
+    /// * `token` - The `token` argument of the request, if any
+    pub fn check(&mut self, command: &str, token: Option<&str>) -> AuthOutcome {
+        if command == "authenticate" {
+            return match (&self.token, token) {
+                (None, _) => AuthOutcome::Authenticated,
+                (Some(expected), Some(token)) if tokens_match(expected, token) => {
+                    self.authenticated = true;
+                    AuthOutcome::Authenticated
+                }
+                _ => AuthOutcome::Rejected,
+            };
+        }
+
+        if self.token.is_none()
+            || self.authenticated
+            || UNAUTHENTICATED_COMMANDS.contains(&command)
+            || self.read_only.iter().any(|allowed| allowed == command)
+        {
+            AuthOutcome::Allowed
+        } else {
+            AuthOutcome::Denied
+        }
+    }
+}
+
+/// The command a request runs, looking through `invoke_tauri` to the
+/// plugin command it invokes.
+///
+/// `invoke_tauri` calls of other plugins' or the app's commands stay
+/// `invoke_tauri`, so they are never read-only.
+pub fn gated_command<'a>(command: &'a str, request: &'a Value) -> &'a str {
+    if command != "invoke_tauri" {
+        return command;
+    }
+    request
+        .get("args")
+        .and_then(|args| args.get("command"))
+        .and_then(Value::as_str)
+        .and_then(|invoked| invoked.strip_prefix(PLUGIN_COMMAND_PREFIX))
+        .unwrap_or(command)
+}
+
+/// Reads the `token` argument of a request.
+pub fn token_arg(command: &Value) -> Option<&str> {
+    command.get("args")?.get("token")?.as_str()
+}
+
+/// Compares tokens in time independent of where they first differ.
+fn tokens_match(expected: &str, actual: &str) -> bool {
+    let (expected, actual) = (expected.as_bytes(), actual.as_bytes());
+    expected.len() == actual.len()
+        && expected
+            .iter()
+            .zip(actual)
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_commands_require_the_configured_token() {
+        let mut gate = AuthGate::new(Some("s3cret".to_string()));
+
+        assert_eq!(gate.check("hello", None), AuthOutcome::Allowed);
+        assert_eq!(gate.check("list_windows", None), AuthOutcome::Denied);
+        assert_eq!(
+            gate.check("authenticate", Some("s3cre")),
+            AuthOutcome::Rejected
+        );
+        assert_eq!(gate.check("authenticate", None), AuthOutcome::Rejected);
+
+        assert_eq!(
+            gate.check("authenticate", Some("s3cret")),
+            AuthOutcome::Authenticated
+        );
+        assert_eq!(gate.check("list_windows", None), AuthOutcome::Allowed);
+    }
+
+    #[test]
+    fn test_no_token_allows_everything() {
+        let mut gate = AuthGate::new(None);
+        assert_eq!(gate.check("list_windows", None), AuthOutcome::Allowed);
+        assert_eq!(
+            gate.check("authenticate", Some("anything")),
+            AuthOutcome::Authenticated
+        );
+
+        let request = json!({ "id": "1", "command": "authenticate", "args": { "token": "t" } });
+        assert_eq!(token_arg(&request), Some("t"));
+        assert_eq!(token_arg(&json!({ "command": "hello" })), None);
+    }
+
+    #[test]
+    fn test_read_only_commands_work_without_a_token() {
+        let read_only = ["get_system_status", "list_windows", "get_backend_state"];
+        let mut gate = AuthGate::new(Some("s3cret".to_string()))
+            .with_read_only(read_only.iter().map(|c| c.to_string()).collect());
+        let mut check = |request: Value| {
+            let command = request["command"].as_str().unwrap();
+            gate.check(gated_command(command, &request), None)
+        };
+
+        assert_eq!(
+            check(json!({ "command": "list_windows" })),
+            AuthOutcome::Allowed
+        );
+        assert_eq!(
+            check(
+                json!({ "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|get_backend_state" } })
+            ),
+            AuthOutcome::Allowed
+        );
+        assert_eq!(
+            check(json!({ "command": "execute_js", "args": { "script": "1" } })),
+            AuthOutcome::Denied
+        );
+        // Another plugin's command of the same name isn't read-only
+        assert_eq!(
+            check(
+                json!({ "command": "invoke_tauri", "args": { "command": "plugin:other|list_windows" } })
+            ),
+            AuthOutcome::Denied
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Standardized results for multi-item operations.
+//!
+//! Commands that act on several items at once (multiple windows, multiple
+//! scripts) report one entry per item instead of failing as a whole, so a
+//! single failing item does not hide the results of the others.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Outcome of a single item within a multi-item operation.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchItemResult {
+    /// Position of the item in the request, for index-addressed operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<usize>,
+    /// Label of the item (e.g. window label), for label-addressed operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Whether this item succeeded
+    pub success: bool,
+    /// Item result data, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    /// Error message if this item failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Aggregated outcome of a multi-item operation.
+///
+/// # Examples
+///
+/// ```rust
+/// use tauri_plugin_mcp_bridge::batch::BatchResult;
+///
+/// let mut batch = BatchResult::new();
+/// batch.push_labeled("main", Ok(None));
+/// batch.push_labeled("settings", Err("Window 'settings' not found".to_string()));
+///
+/// assert_eq!(batch.success_count, 1);
+/// assert_eq!(batch.failure_count, 1);
+/// ```
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchResult {
+    /// Per-item results in request order
+    pub results: Vec<BatchItemResult>,
+    /// Number of items that succeeded
+    pub success_count: usize,
+    /// Number of items that failed
+    pub failure_count: usize,
+}
+
+impl BatchResult {
+    /// Creates an empty batch result.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the outcome of an index-addressed item.
+    pub fn push_indexed(&mut self, index: usize, outcome: Result<Option<Value>, String>) {
+        self.push(Some(index), None, outcome);
+    }
+
+    /// Records the outcome of a label-addressed item.
+    pub fn push_labeled(&mut self, label: &str, outcome: Result<Option<Value>, String>) {
+        self.push(None, Some(label.to_string()), outcome);
+    }
+
+    fn push(
+        &mut self,
+        index: Option<usize>,
+        label: Option<String>,
+        outcome: Result<Option<Value>, String>,
+    ) {
+        let item = match outcome {
+            Ok(data) => {
+                self.success_count += 1;
+                BatchItemResult {
+                    index,
+                    label,
+                    success: true,
+                    data,
+                    error: None,
+                }
+            }
+            Err(error) => {
+                self.failure_count += 1;
+                BatchItemResult {
+                    index,
+                    label,
+                    success: false,
+                    data: None,
+                    error: Some(error),
+                }
+            }
+        };
+        self.results.push(item);
+    }
+
+    /// Returns true if at least one item succeeded.
+    pub fn any_succeeded(&self) -> bool {
+        self.success_count > 0
+    }
+
+    /// Serializes the batch result to JSON.
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or(Value::Null)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_partial_failure_keeps_successful_items() {
+        let mut batch = BatchResult::new();
+        batch.push_labeled("main", Ok(Some(json!(1))));
+        batch.push_labeled("broken", Err("Failed to clear scripts".to_string()));
+        batch.push_labeled("settings", Ok(Some(json!(2))));
+
+        assert_eq!(batch.success_count, 2);
+        assert_eq!(batch.failure_count, 1);
+        assert!(batch.any_succeeded());
+
+        let value = batch.to_value();
+        let results = value["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["label"], "main");
+        assert_eq!(results[0]["data"], 1);
+        assert_eq!(results[1]["success"], false);
+        assert_eq!(results[1]["error"], "Failed to clear scripts");
+        assert!(results[1].get("data").is_none());
+        assert_eq!(results[2]["success"], true);
+        assert_eq!(value["successCount"], 2);
+        assert_eq!(value["failureCount"], 1);
+    }
+
+    #[test]
+    fn test_indexed_items() {
+        let mut batch = BatchResult::new();
+        batch.push_indexed(0, Err("boom".to_string()));
+        batch.push_indexed(1, Ok(None));
+
+        let value = batch.to_value();
+        assert_eq!(value["results"][0]["index"], 0);
+        assert!(value["results"][0].get("label").is_none());
+        assert_eq!(value["results"][1]["index"], 1);
+        assert!(value["results"][1].get("data").is_none());
+    }
+
+    #[test]
+    fn test_all_failed() {
+        let mut batch = BatchResult::new();
+        batch.push_labeled("main", Err("gone".to_string()));
+
+        assert!(!batch.any_succeeded());
+        assert_eq!(batch.failure_count, 1);
+    }
+}
@@ -1,12 +1,34 @@
 // MCP Bridge: Enables eval() contexts to communicate with Tauri IPC
 // This bridge is automatically injected by the mcp-bridge plugin
 // It forwards DOM events from eval() contexts to Tauri IPC and back
+//
+// Scripts added with Builder::additional_init_script run after this one and
+// may use the globals it installs:
+//   window.__MCP_CONSOLE_LOGS__      the latest 500 console entries { level, message, timestamp }
+//   window.__MCP_BRIDGE_READY__      true once Tauri IPC is available
+//   window.__MCP_NETWORK__           { pending, lastChange } of in-flight fetch/XHR calls
+//   window.__MCP_MOCKS__             { set(rules) } replaces the add_mock rules stubbing fetch/XHR
+//   window.__MCP_WEB_VITALS__        { cls, lcp, fid, inp, unsupported } from PerformanceObserver
+//   window.__MCP_HANDLES__           { put, get, release } element handle registry
+//   window.__MCP_INJECT_SCRIPTS__(scripts), __MCP_REMOVE_SCRIPT__(id),
+//   __MCP_CLEAR_SCRIPTS__()          the registered script pipeline
+// It also reports the page's Tauri event listeners to
+// plugin:mcp-bridge|track_event_listener as they are added and removed, and
+// console.log/info/warn/error calls to plugin:mcp-bridge|report_console_entry,
+// and failed resource loads and fetch/XHR calls to
+// plugin:mcp-bridge|report_failed_request.
+// and the DOM events it listens for:
+//   __mcp_exec_request / __mcp_exec_response   execute_js round trip
+//   __mcp_script_result                        forwarded to plugin:mcp-bridge|script_result
 
 (function() {
    'use strict';
 
    var origLog, origDebug, origInfo, origWarn, origError, bridgeLogger;
 
+   // Entries kept in __MCP_CONSOLE_LOGS__, so long sessions don't grow it without bound
+   var MAX_CONSOLE_LOGS = 500;
+
    // MCP bridge logger - scoped with levels and tags
    function createMcpLogger(scope) {
       return {
@@ -49,6 +71,18 @@
 
       window.__MCP_CONSOLE_LOGS__ = [];
 
+      // Keep the window's console history in Rust for get_console_history.
+      // The bridge's own [MCP] lines aren't part of the app's history.
+      function reportEntry(entry) {
+         var internals = window.__TAURI_INTERNALS__;
+         if (!internals || entry.level === 'debug' || entry.message.indexOf('[MCP]') === 0) {
+            return;
+         }
+         internals.invoke('plugin:mcp-bridge|report_console_entry', entry).catch(function() {
+            // History is best-effort
+         });
+      }
+
       function captureLog(level, origFn) {
          return function() {
             args = Array.prototype.slice.call(arguments);
@@ -61,11 +95,16 @@
             } catch(e) {
                message = args.map(String).join(' ');
             }
-            window.__MCP_CONSOLE_LOGS__.push({
+            var entry = {
                level: level,
                message: message,
                timestamp: Date.now(),
-            });
+            };
+            window.__MCP_CONSOLE_LOGS__.push(entry);
+            if (window.__MCP_CONSOLE_LOGS__.length > MAX_CONSOLE_LOGS) {
+               window.__MCP_CONSOLE_LOGS__.shift();
+            }
+            reportEntry(entry);
             origFn.apply(console, args);
          };
       }
@@ -79,6 +118,556 @@
       bridgeLogger.info('Console capture initialized');
    }
 
+   // Track the app's Tauri event listeners for list_event_listeners.
+   // listen() and once() can't be wrapped (the API objects are frozen), so this
+   // watches the listener table Tauri keeps in the page, and the unregister hook
+   // that unlisten() calls. Both are Tauri 2 internals; if they change, tracking
+   // stops without affecting the app.
+   function trackEventListeners() {
+      var tableName = '__internal_unstable_listeners_object_id__';
+      var internals = window.__TAURI_INTERNALS__;
+      var eventInternals = window.__TAURI_EVENT_PLUGIN_INTERNALS__;
+      var unregister;
+
+      if (!internals || typeof Proxy !== 'function' || window[tableName] !== undefined) {
+         return;
+      }
+
+      function report(args) {
+         internals.invoke('plugin:mcp-bridge|track_event_listener', args).catch(function() {
+            // Tracking is best-effort
+         });
+      }
+
+      // Listeners of the previous page are gone
+      report({ action: 'reset' });
+
+      // Tauri adds each listener as a property of its event's table
+      function watchEvent(event) {
+         return new Proxy(Object.create(null), {
+            defineProperty: function(target, id, descriptor) {
+               report({ action: 'listen', event: event, id: Number(id) });
+               return Reflect.defineProperty(target, id, descriptor);
+            },
+         });
+      }
+
+      Object.defineProperty(window, tableName, {
+         value: new Proxy(Object.create(null), {
+            defineProperty: function(target, event, descriptor) {
+               if (event in target) {
+                  return Reflect.defineProperty(target, event, descriptor);
+               }
+               // Writable, so the proxy may hold a different value than requested
+               return Reflect.defineProperty(target, event, {
+                  value: watchEvent(event),
+                  writable: true,
+               });
+            },
+         }),
+      });
+
+      if (eventInternals && typeof eventInternals.unregisterListener === 'function') {
+         unregister = eventInternals.unregisterListener;
+         eventInternals.unregisterListener = function(event, eventId) {
+            report({ action: 'unlisten', event: event, id: eventId });
+            return unregister.apply(this, arguments);
+         };
+      }
+   }
+
+   trackEventListeners();
+
+   // Answer fetch and XMLHttpRequest calls matching an add_mock rule with its
+   // canned response instead of the network. Installed first, so the network
+   // and failure tracking below see mocked calls like real ones. The rules
+   // come from plugin:mcp-bridge|request_mocks; calls made before they arrive
+   // wait for them, except synchronous XHRs, which can't.
+   function installNetworkMocks() {
+      var rules = [], loaded = false, waiting = [], xhrRequests, origFetch, origOpen, origSend;
+
+      if (window.__MCP_MOCKS__) {
+         return;
+      }
+
+      function setRules(next) {
+         rules = (next || []).map(function(rule) {
+            var source = rule.urlPattern.split('*').map(function(part) {
+               return part.replace(/[.+?^${}()|[\]\\]/g, '\\$&');
+            }).join('.*');
+            return { rule: rule, pattern: new RegExp('^' + source + '$') };
+         });
+         loaded = true;
+         waiting.splice(0).forEach(function(resume) {
+            resume();
+         });
+      }
+
+      Object.defineProperty(window, '__MCP_MOCKS__', { value: { set: setRules } });
+
+      function load() {
+         var internals = window.__TAURI_INTERNALS__;
+         if (!internals) {
+            setTimeout(load, 50);
+            return;
+         }
+         internals.invoke('plugin:mcp-bridge|request_mocks').then(setRules, function() {
+            setRules([]);
+         });
+      }
+
+      load();
+
+      function whenLoaded(callback) {
+         if (loaded) {
+            callback();
+         } else {
+            waiting.push(callback);
+         }
+      }
+
+      function findRule(method, url) {
+         var absolute, i;
+         try {
+            absolute = new URL(url, document.baseURI).href;
+         } catch (e) {
+            absolute = String(url);
+         }
+         method = String(method || 'GET').toUpperCase();
+         for (i = 0; i < rules.length; i++) {
+            if ((!rules[i].rule.method || rules[i].rule.method === method) && rules[i].pattern.test(absolute)) {
+               return { rule: rules[i].rule, url: absolute };
+            }
+         }
+         return null;
+      }
+
+      function statusText(status) {
+         return { 200: 'OK', 201: 'Created', 204: 'No Content', 400: 'Bad Request', 401: 'Unauthorized',
+            403: 'Forbidden', 404: 'Not Found', 500: 'Internal Server Error', 503: 'Service Unavailable' }[status] || '';
+      }
+
+      if (typeof window.fetch === 'function') {
+         origFetch = window.fetch;
+         window.fetch = function(input, init) {
+            var self = this, args = arguments;
+            var isRequest = input && typeof input === 'object' && 'url' in input;
+            var url = isRequest ? input.url : input;
+            var method = (init && init.method) || (isRequest ? input.method : 'GET');
+            return new Promise(function(resolve) {
+               whenLoaded(function() {
+                  var match = findRule(method, url), rule, response;
+                  if (!match) {
+                     resolve(origFetch.apply(self, args));
+                     return;
+                  }
+                  rule = match.rule;
+                  // Null-body statuses can't carry a body
+                  response = new Response([204, 205, 304].indexOf(rule.status) === -1 ? rule.body : null, {
+                     status: rule.status,
+                     statusText: statusText(rule.status),
+                     headers: rule.headers || {}
+                  });
+                  Object.defineProperty(response, 'url', { value: match.url });
+                  resolve(response);
+               });
+            });
+         };
+      }
+
+      if (window.XMLHttpRequest && window.XMLHttpRequest.prototype) {
+         xhrRequests = new WeakMap();
+         origOpen = window.XMLHttpRequest.prototype.open;
+         origSend = window.XMLHttpRequest.prototype.send;
+         window.XMLHttpRequest.prototype.open = function(method, url, async) {
+            xhrRequests.set(this, { method: method, url: url, async: async !== false });
+            return origOpen.apply(this, arguments);
+         };
+
+         // Plays the canned response on the instance, shadowing the native
+         // getters, then fires the events a completed request would
+         function respond(xhr, match) {
+            var rule = match.rule, headers = rule.headers || {}, names = Object.keys(headers), response;
+            var define = function(name, value) {
+               Object.defineProperty(xhr, name, { value: value, configurable: true });
+            };
+            switch (xhr.responseType) {
+               case 'json':
+                  try {
+                     response = JSON.parse(rule.body);
+                  } catch (e) {
+                     response = null;
+                  }
+                  break;
+               case 'arraybuffer':
+                  response = new TextEncoder().encode(rule.body).buffer;
+                  break;
+               case 'blob':
+                  response = new Blob([rule.body], { type: headers['content-type'] || '' });
+                  break;
+               default:
+                  response = rule.body;
+            }
+            define('readyState', 4);
+            define('status', rule.status);
+            define('statusText', statusText(rule.status));
+            define('responseURL', match.url);
+            define('response', response);
+            if (!xhr.responseType || xhr.responseType === 'text') {
+               define('responseText', rule.body);
+            }
+            define('getResponseHeader', function(name) {
+               var lower = String(name).toLowerCase(), i;
+               for (i = 0; i < names.length; i++) {
+                  if (names[i].toLowerCase() === lower) {
+                     return headers[names[i]];
+                  }
+               }
+               return null;
+            });
+            define('getAllResponseHeaders', function() {
+               return names.map(function(name) {
+                  return name.toLowerCase() + ': ' + headers[name] + '\r\n';
+               }).join('');
+            });
+            ['readystatechange', 'load', 'loadend'].forEach(function(type) {
+               xhr.dispatchEvent(type === 'readystatechange' || typeof ProgressEvent !== 'function'
+                  ? new Event(type) : new ProgressEvent(type));
+            });
+         }
+
+         window.XMLHttpRequest.prototype.send = function() {
+            var xhr = this, args = arguments, request = xhrRequests.get(xhr), match;
+            if (!request) {
+               return origSend.apply(xhr, args);
+            }
+            if (!request.async) {
+               match = findRule(request.method, request.url);
+               if (match) {
+                  respond(xhr, match);
+                  return undefined;
+               }
+               return origSend.apply(xhr, args);
+            }
+            whenLoaded(function() {
+               var match = findRule(request.method, request.url);
+               if (!match) {
+                  origSend.apply(xhr, args);
+                  return;
+               }
+               // Asynchronous, like a real response
+               setTimeout(function() {
+                  respond(xhr, match);
+               }, 0);
+            });
+            return undefined;
+         };
+      }
+   }
+
+   installNetworkMocks();
+
+   // Count in-flight fetch and XMLHttpRequest calls for wait_for_network_idle.
+   // Installed before the page's own scripts run, so requests made during
+   // startup are counted too.
+   function trackNetworkActivity() {
+      var state, origFetch, origSend;
+
+      if (window.__MCP_NETWORK__) {
+         return;
+      }
+
+      state = { pending: 0, lastChange: Date.now() };
+      Object.defineProperty(window, '__MCP_NETWORK__', { value: state });
+
+      function started() {
+         state.pending++;
+         state.lastChange = Date.now();
+      }
+
+      function finished() {
+         state.pending = Math.max(0, state.pending - 1);
+         state.lastChange = Date.now();
+      }
+
+      if (typeof window.fetch === 'function') {
+         origFetch = window.fetch;
+         window.fetch = function() {
+            var request;
+            started();
+            try {
+               request = origFetch.apply(this, arguments);
+            } catch (e) {
+               finished();
+               throw e;
+            }
+            request.then(finished, finished);
+            return request;
+         };
+      }
+
+      if (window.XMLHttpRequest && window.XMLHttpRequest.prototype) {
+         origSend = window.XMLHttpRequest.prototype.send;
+         window.XMLHttpRequest.prototype.send = function() {
+            var xhr = this;
+            function done() {
+               xhr.removeEventListener('loadend', done);
+               finished();
+            }
+            xhr.addEventListener('loadend', done);
+            started();
+            try {
+               return origSend.apply(this, arguments);
+            } catch (e) {
+               done();
+               throw e;
+            }
+         };
+      }
+   }
+
+   trackNetworkActivity();
+
+   // Report resources that fail to load, and fetch/XHR calls that fail or get
+   // a 4xx/5xx response, for get_failed_requests. Installed before the page's
+   // own scripts run, so a broken script or stylesheet in <head> is caught.
+   function trackFailedRequests() {
+      var queued = [], flushScheduled = false, xhrUrls, origFetch, origOpen, origSend;
+
+      if (window.__MCP_FAILED_REQUESTS_TRACKED__) {
+         return;
+      }
+      Object.defineProperty(window, '__MCP_FAILED_REQUESTS_TRACKED__', { value: true });
+
+      // Failures before Tauri IPC is available are sent once it is
+      function flush() {
+         var internals = window.__TAURI_INTERNALS__;
+         if (!internals) {
+            if (!flushScheduled) {
+               flushScheduled = true;
+               setTimeout(function() {
+                  flushScheduled = false;
+                  flush();
+               }, 50);
+            }
+            return;
+         }
+         queued.splice(0).forEach(function(failure) {
+            internals.invoke('plugin:mcp-bridge|report_failed_request', failure).catch(function() {
+               // Reporting is best-effort
+            });
+         });
+      }
+
+      function report(url, resourceType, status) {
+         var absolute;
+         try {
+            absolute = new URL(url, document.baseURI).href;
+         } catch (e) {
+            absolute = String(url);
+         }
+         queued.push({ url: absolute, resourceType: resourceType, status: status, timestamp: Date.now() });
+         flush();
+      }
+
+      // Element load errors don't bubble, but window sees them while capturing
+      window.addEventListener('error', function(event) {
+         var target = event.target, url;
+         if (!target || target === window || !target.tagName) {
+            return;
+         }
+         url = target.currentSrc || target.src || target.href;
+         if (url) {
+            report(url, target.tagName.toLowerCase(), null);
+         }
+      }, true);
+
+      if (typeof window.fetch === 'function') {
+         origFetch = window.fetch;
+         window.fetch = function(input) {
+            var url = input && typeof input === 'object' && 'url' in input ? input.url : input;
+            var request = origFetch.apply(this, arguments);
+            request.then(function(response) {
+               if (response.status >= 400) {
+                  report(response.url || url, 'fetch', response.status);
+               }
+            }, function(error) {
+               if (!error || error.name !== 'AbortError') {
+                  report(url, 'fetch', null);
+               }
+            });
+            return request;
+         };
+      }
+
+      if (window.XMLHttpRequest && window.XMLHttpRequest.prototype) {
+         xhrUrls = new WeakMap();
+         origOpen = window.XMLHttpRequest.prototype.open;
+         origSend = window.XMLHttpRequest.prototype.send;
+         window.XMLHttpRequest.prototype.open = function(method, url) {
+            xhrUrls.set(this, url);
+            return origOpen.apply(this, arguments);
+         };
+         window.XMLHttpRequest.prototype.send = function() {
+            var xhr = this, aborted = false;
+            xhr.addEventListener('abort', function() {
+               aborted = true;
+            });
+            xhr.addEventListener('loadend', function() {
+               // Status 0 is a network error, unless the page aborted the call
+               if (xhr.status >= 400 || (xhr.status === 0 && !aborted)) {
+                  report(xhr.responseURL || xhrUrls.get(xhr), 'xhr', xhr.status || null);
+               }
+            });
+            return origSend.apply(this, arguments);
+         };
+      }
+   }
+
+   trackFailedRequests();
+
+   // Record web vitals for get_web_vitals. Observing from document start,
+   // with buffered entries, catches shifts and paints during the first load.
+   function trackWebVitals() {
+      var vitals, session, interactions, supported;
+
+      if (window.__MCP_WEB_VITALS__) {
+         return;
+      }
+
+      vitals = { cls: null, lcp: null, fid: null, inp: null, unsupported: [] };
+      Object.defineProperty(window, '__MCP_WEB_VITALS__', { value: vitals });
+
+      // Shifts less than 1s apart, over at most 5s, form a session window.
+      // CLS is the largest session's total.
+      session = { value: 0, first: 0, last: 0 };
+      interactions = {};
+      supported = (window.PerformanceObserver && PerformanceObserver.supportedEntryTypes) || [];
+
+      function observe(type, handle, options) {
+         var observer;
+
+         if (supported.indexOf(type) === -1) {
+            vitals.unsupported.push(type);
+            return;
+         }
+         options = options || {};
+         options.type = type;
+         options.buffered = true;
+         try {
+            observer = new PerformanceObserver(function(list) {
+               list.getEntries().forEach(handle);
+            });
+            observer.observe(options);
+         } catch (e) {
+            vitals.unsupported.push(type);
+         }
+      }
+
+      // Until a shift is reported, the page is stable
+      if (supported.indexOf('layout-shift') !== -1) {
+         vitals.cls = 0;
+      }
+
+      observe('layout-shift', function(entry) {
+         if (entry.hadRecentInput) {
+            return;
+         }
+         if (session.value && entry.startTime - session.last < 1000 && entry.startTime - session.first < 5000) {
+            session.value += entry.value;
+         } else {
+            session.value = entry.value;
+            session.first = entry.startTime;
+         }
+         session.last = entry.startTime;
+         vitals.cls = Math.max(vitals.cls || 0, session.value);
+      });
+
+      observe('largest-contentful-paint', function(entry) {
+         vitals.lcp = entry.renderTime || entry.loadTime || entry.startTime;
+      });
+
+      observe('first-input', function(entry) {
+         if (vitals.fid === null) {
+            vitals.fid = entry.processingStart - entry.startTime;
+         }
+      });
+
+      // INP is the slowest interaction; events of one interaction share an id
+      observe('event', function(entry) {
+         var id = entry.interactionId;
+         if (!id) {
+            return;
+         }
+         interactions[id] = Math.max(interactions[id] || 0, entry.duration);
+         vitals.inp = Math.max(vitals.inp || 0, interactions[id]);
+      }, { durationThreshold: 16 });
+   }
+
+   trackWebVitals();
+
+   // Registry behind query_element_handle: handle ids map to weak references
+   // of live elements, so a handle never keeps an element alive. Entries of
+   // collected elements are dropped by the FinalizationRegistry, and the
+   // server releases a connection's handles when it disconnects.
+   function installElementHandles() {
+      var entries = new Map(), next = 0, page, finalizer;
+
+      if (window.__MCP_HANDLES__) {
+         return;
+      }
+
+      // Keeps ids issued before a reload from matching elements after it
+      page = Math.random().toString(36).slice(2, 8);
+      finalizer = typeof FinalizationRegistry === 'function'
+         ? new FinalizationRegistry(function(id) { entries.delete(id); })
+         : null;
+
+      function weak(el) {
+         return typeof WeakRef === 'function' ? new WeakRef(el) : { deref: function() { return el; } };
+      }
+
+      function remove(id, entry) {
+         entries.delete(id);
+         if (finalizer) {
+            finalizer.unregister(entry);
+         }
+      }
+
+      Object.defineProperty(window, '__MCP_HANDLES__', { value: {
+         put: function(el, owner) {
+            var id = owner + '.' + page + '.' + (++next), entry = { ref: weak(el), owner: owner };
+
+            entries.set(id, entry);
+            if (finalizer) {
+               finalizer.register(el, id, entry);
+            }
+            return id;
+         },
+         // The element, or null once it was collected or left the document
+         get: function(id) {
+            var entry = entries.get(id), el = entry && entry.ref.deref();
+
+            if (entry && !(el && el.isConnected)) {
+               remove(id, entry);
+            }
+            return el && el.isConnected ? el : null;
+         },
+         release: function(owner) {
+            entries.forEach(function(entry, id) {
+               if (entry.owner === owner) {
+                  remove(id, entry);
+               }
+            });
+         },
+         size: function() {
+            return entries.size;
+         }
+      } });
+   }
+
+   installElementHandles();
+
    // Wait for Tauri API to be available
    function waitForTauri(callback) {
       if (window.__TAURI__ && window.__TAURI__.core && window.__TAURI__.core.invoke) {
@@ -220,7 +809,7 @@
                bridgeLogger.error('Failed to load URL script:', entry.id);
             };
          } else {
-            script.textContent = entry.content;
+            script.text = entry.content;
          }
          document.head.appendChild(script);
          bridgeLogger.info('Injected script:', entry.id);
@@ -0,0 +1,538 @@
+//! Controlling the bridge server from the app.
+//!
+//! Every plugin has an [`McpBridgeHandle`]. [`init_with_handle`] returns it
+//! alongside the plugin, and the plugin manages it as Tauri state, so the app
+//! can push frames to connected clients, read the port the server got, and
+//! stop it:
+//!
+//! ```rust,ignore
+//! let (plugin, bridge) =
+//!     tauri_plugin_mcp_bridge::init_with_handle(tauri_plugin_mcp_bridge::Config::default());
+//!
+//! tauri::Builder::default().plugin(plugin).setup(move |_app| {
+//!     let bridge = bridge.clone();
+//!     tauri::async_runtime::spawn(async move {
+//!         if let Some(port) = bridge.ready().await {
+//!             println!("MCP Bridge listening on {port}");
+//!             bridge.broadcast(r#"{"type":"appReady"}"#);
+//!         }
+//!     });
+//!     Ok(())
+//! });
+//! ```
+//!
+//! The handle has nothing to serve until the plugin's setup has run: the
+//! status is [`BridgeStatus::Starting`], [`port`](McpBridgeHandle::port) is
+//! `None` and broadcasts are dropped. Setup picks the port and binds it
+//! before the app's own setup hook runs, so from then on `port` is known;
+//! [`ready`](McpBridgeHandle::ready) waits for that point from anywhere.
+//!
+//! With [`Builder::lazy_start`](crate::Builder::lazy_start) the plugin
+//! doesn't bind a port during setup. The app starts the server through the
+//! handle once automation is actually wanted, e.g. from a debug menu, and
+//! can stop it again to release the port:
+//!
+//! ```rust,ignore
+//! use tauri::Manager;
+//! use tauri_plugin_mcp_bridge::McpBridgeHandle;
+//!
+//! let port = app.state::<McpBridgeHandle>().activate()?;
+//! // ...
+//! app.state::<McpBridgeHandle>().deactivate().await;
+//! ```
+//!
+//! Emitting [`ACTIVATE_EVENT`] or [`DEACTIVATE_EVENT`] does the same from
+//! anywhere in the app, including the frontend.
+//!
+//! [`init_with_handle`]: crate::init_with_handle
+
+use crate::discovery::{bind_available_port, bind_available_port_locked, PortLock};
+use crate::logging::{mcp_log_error, mcp_log_info};
+use std::future::Future;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use tokio::sync::{broadcast, watch};
+
+/// Event that activates a lazily started bridge.
+pub const ACTIVATE_EVENT: &str = "mcp-bridge:activate";
+
+/// Event that deactivates a lazily started bridge.
+pub const DEACTIVATE_EVENT: &str = "mcp-bridge:deactivate";
+
+/// Runs the server on a bound listener until the sender is set.
+type ServeFn = dyn Fn(TcpListener, watch::Sender<bool>) -> Pin<Box<dyn Future<Output = ()> + Send>>
+    + Send
+    + Sync;
+
+/// How activation picks its port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PortChoice {
+    /// Exactly this port, failing if it's taken
+    Explicit(u16),
+    /// The first free port of a range
+    Scan { base_port: u16, attempts: u16 },
+}
+
+/// Where the bridge server is in its life.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BridgeStatus {
+    /// The plugin's setup hasn't run yet
+    Starting,
+    /// Not accepting clients: waiting for activation, deactivated, or the
+    /// port couldn't be bound
+    Inactive,
+    /// Accepting clients on this port
+    Listening(u16),
+}
+
+impl BridgeStatus {
+    /// Returns the port clients can connect to, if any.
+    pub fn port(self) -> Option<u16> {
+        match self {
+            Self::Listening(port) => Some(port),
+            Self::Starting | Self::Inactive => None,
+        }
+    }
+}
+
+/// What activation starts, known once the plugin's setup has run.
+pub(crate) struct BridgeServer {
+    bind_address: String,
+    ports: PortChoice,
+    lock_dir: Option<PathBuf>,
+    events: broadcast::Sender<String>,
+    serve: Box<ServeFn>,
+}
+
+impl BridgeServer {
+    /// Runs `serve` on every activation, broadcasting through `events`.
+    pub(crate) fn new<F, Fut>(
+        bind_address: &str,
+        ports: PortChoice,
+        events: broadcast::Sender<String>,
+        serve: F,
+    ) -> Self
+    where
+        F: Fn(TcpListener, watch::Sender<bool>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        Self {
+            bind_address: bind_address.to_string(),
+            ports,
+            lock_dir: None,
+            events,
+            serve: Box::new(move |listener, shutdown_tx| Box::pin(serve(listener, shutdown_tx))),
+        }
+    }
+
+    /// Claims scanned ports in `lock_dir`, see
+    /// [`Builder::port_lock_dir`](crate::Builder::port_lock_dir).
+    pub(crate) fn with_port_lock_dir(mut self, lock_dir: Option<PathBuf>) -> Self {
+        self.lock_dir = lock_dir;
+        self
+    }
+
+    fn bind(&self) -> Result<(TcpListener, Option<PortLock>), String> {
+        match (self.ports, &self.lock_dir) {
+            (PortChoice::Explicit(port), _) => {
+                TcpListener::bind(format!("{}:{port}", self.bind_address))
+                    .map(|listener| (listener, None))
+                    .map_err(|e| {
+                        format!(
+                            "MCP Bridge: Port {port} on {} is unavailable: {e}",
+                            self.bind_address
+                        )
+                    })
+            }
+            (
+                PortChoice::Scan {
+                    base_port,
+                    attempts,
+                },
+                Some(dir),
+            ) => bind_available_port_locked(&self.bind_address, base_port, attempts, dir)
+                .map(|(listener, lock)| (listener, Some(lock))),
+            (
+                PortChoice::Scan {
+                    base_port,
+                    attempts,
+                },
+                None,
+            ) => bind_available_port(&self.bind_address, base_port, attempts)
+                .map(|listener| (listener, None)),
+        }
+    }
+}
+
+/// A running server.
+struct Active {
+    port: u16,
+    shutdown_tx: watch::Sender<bool>,
+    task: tauri::async_runtime::JoinHandle<()>,
+    /// Claim on a scanned port, released with the server
+    lock: Option<PortLock>,
+}
+
+/// Talks to the bridge server from the app: broadcasts frames to connected
+/// clients, reports the port, and starts and stops the server.
+///
+/// Each activation binds a port and serves clients until the next
+/// deactivation, which closes the connections and releases the port. The
+/// bridge can be activated again afterwards, as often as needed. Without
+/// [`Builder::lazy_start`](crate::Builder::lazy_start) the plugin's setup
+/// activates it once; reactivating binds the same port again.
+///
+/// Cheap to clone; clones control the same server. Returned by
+/// [`init_with_handle`](crate::init_with_handle), and managed by the plugin
+/// as Tauri state.
+#[derive(Clone)]
+pub struct McpBridgeHandle {
+    server: Arc<OnceLock<BridgeServer>>,
+    active: Arc<Mutex<Option<Active>>>,
+    status: Arc<watch::Sender<BridgeStatus>>,
+}
+
+impl McpBridgeHandle {
+    /// Creates a handle for a plugin whose setup hasn't run yet.
+    pub(crate) fn pending() -> Self {
+        let (status, _) = watch::channel(BridgeStatus::Starting);
+        Self {
+            server: Arc::new(OnceLock::new()),
+            active: Arc::new(Mutex::new(None)),
+            status: Arc::new(status),
+        }
+    }
+
+    /// Gives the handle the server it controls, activating it if `start` is
+    /// set. Called once, by the plugin's setup.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - Once the handle is usable
+    /// * `Err(String)` - If the server couldn't be started; the handle is
+    ///   still usable and stays inactive
+    pub(crate) fn attach(&self, server: BridgeServer, start: bool) -> Result<(), String> {
+        if self.server.set(server).is_err() {
+            return Err("MCP Bridge handle is already attached to a plugin".to_string());
+        }
+        let started = if start {
+            self.activate().map(|_| ())
+        } else {
+            Ok(())
+        };
+        // Activation already published its port; otherwise report inactive
+        self.status.send_if_modified(|status| {
+            let starting = *status == BridgeStatus::Starting;
+            if starting {
+                *status = BridgeStatus::Inactive;
+            }
+            starting
+        });
+        started
+    }
+
+    /// Binds a port and starts accepting clients.
+    ///
+    /// Does nothing when the bridge is already active.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(u16)` - The port clients can connect to
+    /// * `Err(String)` - If the plugin's setup hasn't run yet, or no port
+    ///   could be bound
+    pub fn activate(&self) -> Result<u16, String> {
+        let server = self
+            .server
+            .get()
+            .ok_or_else(|| "MCP Bridge is not set up yet; the app hasn't started".to_string())?;
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(active) = active.as_ref() {
+            return Ok(active.port);
+        }
+
+        let (listener, lock) = server.bind()?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read bound address: {e}"))?
+            .port();
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure listener: {e}"))?;
+
+        let (shutdown_tx, _) = watch::channel(false);
+        let task = tauri::async_runtime::spawn((server.serve)(listener, shutdown_tx.clone()));
+        *active = Some(Active {
+            port,
+            shutdown_tx,
+            task,
+            lock,
+        });
+        self.status.send_replace(BridgeStatus::Listening(port));
+
+        mcp_log_info(
+            "PLUGIN",
+            &format!("MCP Bridge activated on {}:{port}", server.bind_address),
+        );
+        Ok(port)
+    }
+
+    /// Closes all connections and releases the port.
+    ///
+    /// Resolves once the port is free again and the connections have sent
+    /// their Close frames. Clients still in the WebSocket handshake are
+    /// given up on after a couple of seconds.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the bridge was active.
+    pub async fn deactivate(&self) -> bool {
+        let Some(active) = self.take() else {
+            return false;
+        };
+        // `send` would drop the value if the server task hasn't subscribed yet
+        active.shutdown_tx.send_replace(true);
+        if let Err(e) = active.task.await {
+            mcp_log_error("PLUGIN", &format!("MCP Bridge server task failed: {e}"));
+        }
+        drop(active.lock);
+
+        mcp_log_info(
+            "PLUGIN",
+            &format!("MCP Bridge deactivated, released port {}", active.port),
+        );
+        true
+    }
+
+    /// Asks the server to close all connections and stop, without waiting
+    /// for it.
+    ///
+    /// The plugin calls this when the app exits. Use
+    /// [`deactivate`](Self::deactivate) to know when the port is free again.
+    pub fn shutdown(&self) {
+        if let Some(active) = self.take() {
+            active.shutdown_tx.send_replace(true);
+        }
+    }
+
+    /// Sends a frame to every connected client, like the plugin's own
+    /// events.
+    ///
+    /// Clients that subscribed to topics receive it only if its `type` is one
+    /// of them, so `message` should be a JSON object with a `type` field.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The frame to send
+    ///
+    /// # Returns
+    ///
+    /// The number of connections the frame was queued for; `0` before the
+    /// plugin's setup has run or when no client is connected.
+    pub fn broadcast(&self, message: &str) -> usize {
+        self.server
+            .get()
+            .and_then(|server| server.events.send(message.to_string()).ok())
+            .unwrap_or(0)
+    }
+
+    /// Returns where the server is in its life.
+    pub fn status(&self) -> BridgeStatus {
+        *self.status.borrow()
+    }
+
+    /// Returns whether the bridge is accepting clients.
+    pub fn is_active(&self) -> bool {
+        self.port().is_some()
+    }
+
+    /// Returns the port of the active bridge.
+    ///
+    /// `None` until the plugin's setup has run, and while the bridge is
+    /// inactive.
+    pub fn port(&self) -> Option<u16> {
+        self.status().port()
+    }
+
+    /// Waits for the plugin's setup to run.
+    ///
+    /// Resolves right away once it has. Never resolves if the plugin isn't
+    /// added to a running app.
+    ///
+    /// # Returns
+    ///
+    /// The port clients can connect to, or `None` if the bridge is inactive:
+    /// with [`Builder::lazy_start`](crate::Builder::lazy_start), or when no
+    /// port could be bound.
+    pub async fn ready(&self) -> Option<u16> {
+        let mut status = self.status.subscribe();
+        let ready = status
+            .wait_for(|status| *status != BridgeStatus::Starting)
+            .await
+            .ok()?;
+        ready.port()
+    }
+
+    fn take(&self) -> Option<Active> {
+        let mut active = self.active.lock().unwrap_or_else(|e| e.into_inner());
+        let taken = active.take();
+        if taken.is_some() {
+            self.status.send_replace(BridgeStatus::Inactive);
+        }
+        taken
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use tokio::io::AsyncWriteExt;
+
+    /// Accepts connections and answers each with "ok" until stopped.
+    fn echo_handle(ports: PortChoice) -> McpBridgeHandle {
+        let (events, _) = broadcast::channel(16);
+        let server = BridgeServer::new(
+            "127.0.0.1",
+            ports,
+            events,
+            |listener, shutdown_tx| async move {
+                let listener = tokio::net::TcpListener::from_std(listener).unwrap();
+                let mut stop = shutdown_tx.subscribe();
+                loop {
+                    let (mut stream, _) = tokio::select! {
+                        accepted = listener.accept() => accepted.unwrap(),
+                        _ = stop.wait_for(|stopped| *stopped) => return,
+                    };
+                    let _ = stream.write_all(b"ok").await;
+                }
+            },
+        );
+        let handle = McpBridgeHandle::pending();
+        handle.attach(server, false).unwrap();
+        handle
+    }
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test]
+    async fn test_deactivate_closes_connections_and_releases_the_port() {
+        use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+        use tokio_tungstenite::tungstenite::Message;
+
+        let port = free_port();
+        let (plugin, handle) = crate::Builder::new()
+            .bind_address("127.0.0.1")
+            .port(port)
+            .lazy_start(true)
+            .build_with_handle();
+        let _app = tauri::test::mock_builder()
+            .plugin(plugin)
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        // Lazily started bridges leave the port alone until activated
+        assert_eq!(handle.status(), BridgeStatus::Inactive);
+        assert_eq!(handle.ready().await, None);
+        assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+
+        for _ in 0..3 {
+            assert_eq!(handle.activate(), Ok(port));
+            assert_eq!(handle.activate(), Ok(port));
+            assert_eq!(handle.status(), BridgeStatus::Listening(port));
+
+            let (mut client, _) =
+                tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{port}"))
+                    .await
+                    .unwrap();
+            // The capabilities frame comes first, once the connection listens
+            client.next().await.unwrap().unwrap();
+
+            assert!(handle.deactivate().await);
+            assert!(!handle.is_active());
+            assert!(TcpListener::bind(("127.0.0.1", port)).is_ok());
+
+            // Deactivation waited for the Close frame to go out
+            let closed = tokio::time::timeout(std::time::Duration::from_millis(100), client.next())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            let Message::Close(Some(frame)) = closed else {
+                panic!("expected a Close frame, got {closed:?}");
+            };
+            assert_eq!(frame.code, CloseCode::Away);
+        }
+
+        assert!(!handle.deactivate().await);
+    }
+
+    #[tokio::test]
+    async fn test_activation_fails_on_a_taken_explicit_port() {
+        // A taken port whose neighbour is free, for the scan to land on
+        let (taken, port) = loop {
+            let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = taken.local_addr().unwrap().port();
+            if port < u16::MAX && TcpListener::bind(("127.0.0.1", port + 1)).is_ok() {
+                break (taken, port);
+            }
+        };
+
+        let err = echo_handle(PortChoice::Explicit(port))
+            .activate()
+            .unwrap_err();
+        assert!(err.contains(&format!("Port {port} on 127.0.0.1 is unavailable")));
+
+        let scanning = echo_handle(PortChoice::Scan {
+            base_port: port,
+            attempts: 2,
+        });
+        assert_eq!(scanning.activate(), Ok(port + 1));
+        assert!(scanning.deactivate().await);
+        drop(taken);
+    }
+
+    #[tokio::test]
+    async fn test_handle_broadcasts_once_the_app_started() {
+        let port = free_port();
+        let (plugin, handle) = crate::init_with_handle(crate::Config {
+            bind_address: "127.0.0.1".to_string(),
+            port: Some(port),
+            ..crate::Config::default()
+        });
+        assert_eq!(handle.status(), BridgeStatus::Starting);
+        assert_eq!(handle.broadcast(r#"{"type":"early"}"#), 0);
+
+        let _app = tauri::test::mock_builder()
+            .plugin(plugin)
+            .build(tauri::test::mock_context(tauri::test::noop_assets()))
+            .unwrap();
+        // Setup has run, so the port is known without waiting
+        assert_eq!(handle.port(), Some(port));
+        assert_eq!(handle.ready().await, Some(port));
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{port}"))
+            .await
+            .unwrap();
+        // The capabilities frame comes first, once the connection listens
+        client.next().await.unwrap().unwrap();
+
+        let frame = r#"{"type":"appEvent","value":42}"#;
+        assert_eq!(handle.broadcast(frame), 1);
+        let received = tokio::time::timeout(std::time::Duration::from_secs(5), client.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(received.into_text().unwrap().as_str(), frame);
+
+        handle.shutdown();
+        assert_eq!(handle.status(), BridgeStatus::Inactive);
+    }
+}
@@ -0,0 +1,189 @@
+//! Connection-level capability negotiation.
+//!
+//! Right after the WebSocket handshake the server sends a single
+//! `capabilities` frame so clients can learn which optional features this
+//! bridge instance supports before using them:
+//!
+//! ```json
+//! {
+//!   "type": "capabilities",
+//!   "protocolVersion": 1,
+//!   "serverVersion": "0.4.0",
+//!   "features": { "tls": false, "compression": false, ... },
+//!   "limits": { "maxResultBytes": 10485760, "chunkThresholdBytes": 1048576 }
+//! }
+//! ```
+//!
+//! # Feature keys
+//!
+//! | Key                    | Meaning                                                        |
+//! |------------------------|----------------------------------------------------------------|
+//! | `tls`                  | Connections are served over `wss://`                           |
+//! | `compression`          | Per-message compression is negotiated                          |
+//! | `binaryScreenshots`    | Screenshots can be sent as binary frames instead of base64     |
+//! | `jsonRpc`              | Requests may use JSON-RPC 2.0 framing                          |
+//! | `auth`                 | Clients must authenticate before issuing commands              |
+//! | `batchResults`         | Multi-item commands report per-item `results`                  |
+//! | `resultSizeLimit`      | `execute_js` enforces `maxResultBytes` (see `limits`)          |
+//! | `chunkedResults`       | Large `execute_js` results arrive as `chunk` frames            |
+//! | `screenshotBackground` | `capture_native_screenshot` accepts a `background` option      |
+//! | `canvasCapture`        | `capture_native_screenshot` accepts a `canvasSelector` option  |
+//! | `jsSandbox`            | `execute_js` runs with network/storage/eval globals shadowed   |
+//! | `msgpack`              | `hello` accepts `format: "msgpack"` for binary frames          |
+//! | `mcp`                  | MCP `initialize`/`tools/list`/`tools/call` messages are answered |
+//!
+//! Clients may optionally send a `hello` command listing the features they
+//! want to use; the response splits them into `accepted` and `unsupported`.
+//! A `hello` may also pick the connection's wire format, see
+//! [`response_format`](crate::response_format).
+
+use crate::config::Config;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Version of the request/response protocol spoken over the WebSocket.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Optional features supported by this bridge instance.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Features {
+    pub tls: bool,
+    pub compression: bool,
+    pub binary_screenshots: bool,
+    pub json_rpc: bool,
+    pub auth: bool,
+    pub batch_results: bool,
+    pub result_size_limit: bool,
+    pub chunked_results: bool,
+    pub screenshot_background: bool,
+    pub canvas_capture: bool,
+    pub js_sandbox: bool,
+    pub msgpack: bool,
+    pub mcp: bool,
+}
+
+/// Limits that apply to requests on this bridge instance.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Limits {
+    pub max_result_bytes: usize,
+    pub chunk_threshold_bytes: usize,
+}
+
+/// The `capabilities` frame sent to every client after connecting.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    #[serde(rename = "type")]
+    pub frame_type: &'static str,
+    pub protocol_version: u32,
+    pub server_version: &'static str,
+    pub features: Features,
+    pub limits: Limits,
+    /// Prefix the bridge's commands must carry, see
+    /// [`Builder::command_namespace`](crate::Builder::command_namespace)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_namespace: Option<String>,
+}
+
+impl Capabilities {
+    /// Describes the capabilities of a bridge running with `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            frame_type: "capabilities",
+            protocol_version: PROTOCOL_VERSION,
+            server_version: env!("CARGO_PKG_VERSION"),
+            features: Features {
+                tls: false,
+                compression: false,
+                binary_screenshots: false,
+                json_rpc: false,
+                auth: config.auth_token.is_some(),
+                batch_results: true,
+                result_size_limit: true,
+                chunked_results: config.chunk_threshold_bytes > 0,
+                screenshot_background: true,
+                canvas_capture: true,
+                js_sandbox: config.js_sandbox,
+                msgpack: cfg!(feature = "msgpack"),
+                mcp: config.mcp_mode,
+            },
+            limits: Limits {
+                max_result_bytes: config.max_result_bytes,
+                chunk_threshold_bytes: config.chunk_threshold_bytes,
+            },
+            command_namespace: config.command_namespace.clone(),
+        }
+    }
+
+    /// Returns true if the named feature key is supported.
+    pub fn supports(&self, feature: &str) -> bool {
+        serde_json::to_value(&self.features)
+            .ok()
+            .and_then(|features| features.get(feature).and_then(|v| v.as_bool()))
+            .unwrap_or(false)
+    }
+
+    /// Splits the features requested in a client `hello` into accepted and
+    /// unsupported lists.
+    pub fn negotiate(&self, requested: &[String]) -> Value {
+        let (accepted, unsupported): (Vec<&String>, Vec<&String>) =
+            requested.iter().partition(|feature| self.supports(feature));
+
+        serde_json::json!({
+            "accepted": accepted,
+            "unsupported": unsupported,
+            "protocolVersion": self.protocol_version,
+            "serverVersion": self.server_version,
+        })
+    }
+
+    /// Serializes the capabilities frame to a JSON string.
+    pub fn to_frame(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_frame_shape() {
+        let frame: Value =
+            serde_json::from_str(&Capabilities::from_config(&Config::default()).to_frame())
+                .unwrap();
+
+        assert_eq!(frame["type"], "capabilities");
+        assert_eq!(frame["protocolVersion"], PROTOCOL_VERSION);
+        assert_eq!(frame["serverVersion"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(frame["features"]["tls"], false);
+        assert_eq!(frame["features"]["batchResults"], true);
+        assert_eq!(frame["features"]["jsSandbox"], false);
+        assert_eq!(frame["features"]["chunkedResults"], true);
+        assert_eq!(
+            frame["limits"]["maxResultBytes"],
+            Config::default().max_result_bytes
+        );
+        assert!(frame.get("commandNamespace").is_none());
+    }
+
+    #[test]
+    fn test_negotiate_splits_requested_features() {
+        let capabilities = Capabilities::from_config(&Config::default());
+        let requested = vec![
+            "batchResults".to_string(),
+            "compression".to_string(),
+            "teleport".to_string(),
+        ];
+
+        let result = capabilities.negotiate(&requested);
+
+        assert_eq!(result["accepted"], serde_json::json!(["batchResults"]));
+        assert_eq!(
+            result["unsupported"],
+            serde_json::json!(["compression", "teleport"])
+        );
+    }
+}
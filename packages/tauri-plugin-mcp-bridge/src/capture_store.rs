@@ -0,0 +1,233 @@
+//! Short-lived storage for screenshots downloaded over HTTP.
+//!
+//! Large captures can be kept out of the WebSocket channel: with
+//! `delivery: "url"`, `capture_native_screenshot` stores the image here and
+//! responds with `{ id, width, height, url }`. The client then fetches the
+//! bytes from the same port with a plain `GET /screenshot/{id}` (see
+//! [`http_fallback`](crate::http_fallback)).
+//!
+//! Captures expire after [`CAPTURE_TTL`], and only the newest
+//! [`MAX_STORED_CAPTURES`] are kept, which bounds the memory held for
+//! clients that never download them.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a stored capture can be downloaded.
+pub const CAPTURE_TTL: Duration = Duration::from_secs(60);
+
+/// Maximum number of captures kept at once; the oldest is evicted first.
+pub const MAX_STORED_CAPTURES: usize = 8;
+
+/// Whether a capture's `delivery` argument asks for a download URL.
+///
+/// # Returns
+///
+/// * `Ok(false)` - For `"inline"` or no value: the image is sent in the response
+/// * `Ok(true)` - For `"url"`: the image is stored for download
+/// * `Err(String)` - For any other value
+pub fn delivery_by_url(delivery: Option<&str>) -> Result<bool, String> {
+    match delivery {
+        None | Some("inline") => Ok(false),
+        Some("url") => Ok(true),
+        Some(other) => Err(format!(
+            "Unknown delivery '{other}' (expected \"inline\" or \"url\")"
+        )),
+    }
+}
+
+/// A stored capture, as returned to the client in place of the image bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRef {
+    /// Id to download the capture with
+    pub id: String,
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// MIME type of the image, e.g. `image/png`
+    pub mime_type: String,
+    /// Size of the image in bytes
+    pub size_bytes: usize,
+    /// Milliseconds until the capture expires
+    pub expires_in_ms: u64,
+}
+
+struct StoredCapture {
+    id: String,
+    mime_type: String,
+    bytes: Arc<Vec<u8>>,
+    expires_at: Instant,
+}
+
+/// Shared store of recent captures.
+///
+/// Cheap to clone; clones share the same captures. The plugin manages one
+/// as Tauri state for the capture command and hands a clone to the
+/// WebSocket server for the HTTP downloads.
+#[derive(Clone)]
+pub struct CaptureStore {
+    captures: Arc<Mutex<VecDeque<StoredCapture>>>,
+    ttl: Duration,
+}
+
+impl Default for CaptureStore {
+    fn default() -> Self {
+        Self::with_ttl(CAPTURE_TTL)
+    }
+}
+
+impl CaptureStore {
+    /// Creates an empty store with the default TTL.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty store whose captures expire after `ttl`.
+    pub fn with_ttl(ttl: Duration) -> Self {
+        Self {
+            captures: Arc::new(Mutex::new(VecDeque::new())),
+            ttl,
+        }
+    }
+
+    /// Stores an encoded image.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - Image format, e.g. `"png"` or `"jpeg"`
+    /// * `bytes` - The encoded image
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CaptureRef)` - The id and dimensions of the stored capture
+    /// * `Err(String)` - If the image can't be decoded to read its size
+    pub fn insert(&self, format: &str, bytes: Vec<u8>) -> Result<CaptureRef, String> {
+        let (width, height) = image::ImageReader::new(Cursor::new(&bytes))
+            .with_guessed_format()
+            .map_err(|e| format!("Failed to read image: {e}"))?
+            .into_dimensions()
+            .map_err(|e| format!("Failed to read image size: {e}"))?;
+
+        let now = Instant::now();
+        let capture = StoredCapture {
+            id: uuid::Uuid::new_v4().to_string(),
+            mime_type: format!("image/{format}"),
+            bytes: Arc::new(bytes),
+            expires_at: now + self.ttl,
+        };
+        let reference = CaptureRef {
+            id: capture.id.clone(),
+            width,
+            height,
+            mime_type: capture.mime_type.clone(),
+            size_bytes: capture.bytes.len(),
+            expires_in_ms: self.ttl.as_millis() as u64,
+        };
+
+        let mut captures = self.captures.lock().unwrap();
+        purge_expired(&mut captures, now);
+        while captures.len() >= MAX_STORED_CAPTURES {
+            captures.pop_front();
+        }
+        captures.push_back(capture);
+
+        Ok(reference)
+    }
+
+    /// Returns the MIME type and bytes of a capture that hasn't expired.
+    pub fn get(&self, id: &str) -> Option<(String, Arc<Vec<u8>>)> {
+        let mut captures = self.captures.lock().unwrap();
+        purge_expired(&mut captures, Instant::now());
+        captures
+            .iter()
+            .find(|capture| capture.id == id)
+            .map(|capture| (capture.mime_type.clone(), capture.bytes.clone()))
+    }
+
+    /// Returns the number of captures currently stored.
+    pub fn len(&self) -> usize {
+        self.captures.lock().unwrap().len()
+    }
+
+    /// Returns true if no captures are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Drops expired captures. They are stored oldest first, so expired ones
+/// are always at the front.
+fn purge_expired(captures: &mut VecDeque<StoredCapture>, now: Instant) {
+    while captures
+        .front()
+        .is_some_and(|capture| capture.expires_at <= now)
+    {
+        captures.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x2 PNG.
+    fn png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(3, 2)
+            .write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_stored_capture_can_be_fetched_by_id() {
+        let store = CaptureStore::new();
+        let reference = store.insert("png", png()).unwrap();
+
+        assert_eq!((reference.width, reference.height), (3, 2));
+        assert_eq!(reference.mime_type, "image/png");
+        let (mime_type, bytes) = store.get(&reference.id).unwrap();
+        assert_eq!(mime_type, "image/png");
+        assert_eq!(*bytes, png());
+        assert!(store.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_expired_captures_are_dropped() {
+        let store = CaptureStore::with_ttl(Duration::ZERO);
+        let reference = store.insert("png", png()).unwrap();
+
+        assert!(store.get(&reference.id).is_none());
+        assert!(store.is_empty());
+    }
+
+    #[test]
+    fn test_oldest_capture_is_evicted_at_capacity() {
+        let store = CaptureStore::new();
+        let first = store.insert("png", png()).unwrap();
+        for _ in 0..MAX_STORED_CAPTURES {
+            store.insert("png", png()).unwrap();
+        }
+
+        assert_eq!(store.len(), MAX_STORED_CAPTURES);
+        assert!(store.get(&first.id).is_none());
+    }
+
+    #[test]
+    fn test_delivery_values() {
+        assert_eq!(delivery_by_url(None), Ok(false));
+        assert_eq!(delivery_by_url(Some("inline")), Ok(false));
+        assert_eq!(delivery_by_url(Some("url")), Ok(true));
+        assert!(delivery_by_url(Some("file")).is_err());
+    }
+
+    #[test]
+    fn test_undecodable_image_is_rejected() {
+        assert!(CaptureStore::new().insert("png", vec![1, 2, 3]).is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Chunked transfer of oversized `execute_js` results.
+//!
+//! A result whose serialized `data` exceeds the connection's chunk threshold
+//! isn't sent in one frame. The response carries the transfer details instead
+//! of `data`, and is followed by the chunks in order:
+//!
+//! ```json
+//! { "id": "1", "success": true, "data": null, "chunked": true, "totalBytes": 2500000, "chunkCount": 3, "resultId": "..." }
+//! { "type": "chunk", "resultId": "...", "index": 0, "data": "{\"html\":\"<!DOCTYPE html>..." }
+//! { "type": "chunk", "resultId": "...", "index": 1, "data": "..." }
+//! { "type": "chunk", "resultId": "...", "index": 2, "data": "...\"}" }
+//! ```
+//!
+//! Each chunk's `data` holds up to the threshold in bytes of the result's
+//! JSON text. Clients concatenate the chunks by `index` and parse the result
+//! with `JSON.parse`. Chunks are split on character boundaries, so each one
+//! is valid UTF-8 on its own.
+
+use serde_json::Value;
+
+/// Splits an oversized `data` out of `response` into chunk frames.
+///
+/// # Arguments
+///
+/// * `response` - The response to a request; replaced by the transfer
+///   announcement when chunked
+/// * `threshold` - The largest serialized `data`, in bytes, sent inline, and
+///   the size of each chunk
+///
+/// # Returns
+///
+/// The chunk frames to send after `response`, in order; empty if `data`
+/// fits in the response
+pub fn split(response: &mut Value, threshold: usize) -> Vec<Value> {
+    let Some(data) = response.get("data") else {
+        return Vec::new();
+    };
+    let Ok(text) = serde_json::to_string(data) else {
+        return Vec::new();
+    };
+    if threshold == 0 || text.len() <= threshold {
+        return Vec::new();
+    }
+
+    let result_id = uuid::Uuid::new_v4().to_string();
+    let chunks: Vec<Value> = chunk_str(&text, threshold)
+        .enumerate()
+        .map(|(index, data)| {
+            serde_json::json!({
+                "type": "chunk",
+                "resultId": result_id,
+                "index": index,
+                "data": data
+            })
+        })
+        .collect();
+
+    response["data"] = Value::Null;
+    response["chunked"] = serde_json::json!(true);
+    response["totalBytes"] = serde_json::json!(text.len());
+    response["chunkCount"] = serde_json::json!(chunks.len());
+    response["resultId"] = serde_json::json!(result_id);
+    chunks
+}
+
+/// Splits `text` into pieces of at most `size` bytes, ending each on a
+/// character boundary. A piece is longer only if a single character is.
+fn chunk_str(text: &str, size: usize) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut end = size.min(rest.len());
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+        if end == 0 {
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_oversized_result_is_chunked_and_reassembles() {
+        let html = format!("<html>{}</html>", "héllo wörld ".repeat(200));
+        let data = json!({ "html": html });
+        let mut response = json!({ "id": "1", "success": true, "data": data });
+
+        let chunks = split(&mut response, 256);
+
+        let total = serde_json::to_string(&data).unwrap().len();
+        assert_eq!(response["data"], Value::Null);
+        assert_eq!(response["chunked"], true);
+        assert_eq!(response["totalBytes"], total);
+        assert_eq!(response["chunkCount"], chunks.len());
+        assert!(chunks.len() > 1);
+
+        let mut text = String::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk["type"], "chunk");
+            assert_eq!(chunk["resultId"], response["resultId"]);
+            assert_eq!(chunk["index"], index);
+            let piece = chunk["data"].as_str().unwrap();
+            assert!(piece.len() <= 256);
+            text.push_str(piece);
+        }
+        assert_eq!(text.len(), total);
+        assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), data);
+    }
+
+    #[test]
+    fn test_small_results_are_sent_inline() {
+        let mut response = json!({ "id": "1", "success": true, "data": "short" });
+        let original = response.clone();
+
+        assert!(split(&mut response, 256).is_empty());
+        assert_eq!(response, original);
+
+        // A zero threshold turns chunking off
+        let mut big = json!({ "id": "2", "success": true, "data": "x".repeat(1000) });
+        assert!(split(&mut big, 0).is_empty());
+
+        assert_eq!(chunk_str("aéb", 1).collect::<Vec<_>>(), ["a", "é", "b"]);
+    }
+}
@@ -0,0 +1,227 @@
+//! Tracking of connected WebSocket clients.
+//!
+//! Every connection is registered when it is accepted and removed when it
+//! closes. A client can attach a human-readable name to its connection with
+//! `identify`, so that `get_clients` and the bridge logs show which agent
+//! did what when several share one app:
+//!
+//! ```json
+//! { "id": "1", "command": "identify", "args": { "name": "agent-1" } }
+//! ```
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// Longest accepted client name, in characters.
+pub const MAX_CLIENT_NAME_CHARS: usize = 64;
+
+/// A connected client, as reported by `get_clients`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientInfo {
+    /// Connection id, unique for the lifetime of the app
+    pub id: u64,
+    /// Remote address of the connection
+    pub address: String,
+    /// Name set with `identify`, if any
+    pub name: Option<String>,
+    /// Unix timestamp in milliseconds when the connection was accepted
+    pub connected_at: u64,
+    /// Number of requests received on the connection
+    pub requests: u64,
+}
+
+#[derive(Default)]
+struct Registry {
+    next_id: u64,
+    clients: BTreeMap<u64, ClientInfo>,
+}
+
+/// Shared list of connected clients.
+///
+/// Cheap to clone; clones share the same list. The WebSocket server
+/// registers connections in it and the plugin manages a clone as Tauri
+/// state for the `get_clients` command.
+#[derive(Clone, Default)]
+pub struct ClientRegistry {
+    inner: Arc<Mutex<Registry>>,
+}
+
+impl ClientRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a new connection.
+    ///
+    /// The connection stays listed until the returned handle is dropped.
+    pub fn register(&self, address: SocketAddr) -> ClientHandle {
+        let mut registry = self.inner.lock().unwrap();
+        registry.next_id += 1;
+        let id = registry.next_id;
+        registry.clients.insert(
+            id,
+            ClientInfo {
+                id,
+                address: address.to_string(),
+                name: None,
+                connected_at: crate::monitor::current_timestamp(),
+                requests: 0,
+            },
+        );
+
+        ClientHandle {
+            id,
+            registry: self.clone(),
+        }
+    }
+
+    /// Returns the connected clients, oldest connection first.
+    pub fn list(&self) -> Vec<ClientInfo> {
+        self.inner
+            .lock()
+            .unwrap()
+            .clients
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the number of connected clients.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().clients.len()
+    }
+
+    /// Returns true if no client is connected.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn update<T>(&self, id: u64, f: impl FnOnce(&mut ClientInfo) -> T) -> Option<T> {
+        self.inner.lock().unwrap().clients.get_mut(&id).map(f)
+    }
+}
+
+/// One connection's entry in a [`ClientRegistry`].
+///
+/// Dropping the handle removes the entry.
+pub struct ClientHandle {
+    id: u64,
+    registry: ClientRegistry,
+}
+
+impl ClientHandle {
+    /// The connection's id.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Sets the connection's name.
+    ///
+    /// Surrounding whitespace is trimmed. Calling it again replaces the name.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ClientInfo)` - The updated entry
+    /// * `Err(String)` - If the name is empty or longer than
+    ///   [`MAX_CLIENT_NAME_CHARS`]
+    pub fn identify(&self, name: &str) -> Result<ClientInfo, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Client name must not be empty".to_string());
+        }
+        if name.chars().count() > MAX_CLIENT_NAME_CHARS {
+            return Err(format!(
+                "Client name is longer than {MAX_CLIENT_NAME_CHARS} characters"
+            ));
+        }
+
+        self.registry
+            .update(self.id, |client| {
+                client.name = Some(name.to_string());
+                client.clone()
+            })
+            .ok_or_else(|| format!("Client {} is no longer registered", self.id))
+    }
+
+    /// Counts a request received on the connection.
+    pub fn record_request(&self) {
+        self.registry.update(self.id, |client| client.requests += 1);
+    }
+
+    /// Label used in logs: the client's name, or `client-<id>` until it
+    /// identifies itself.
+    pub fn label(&self) -> String {
+        self.registry
+            .update(self.id, |client| client.name.clone())
+            .flatten()
+            .unwrap_or_else(|| format!("client-{}", self.id))
+    }
+}
+
+impl Drop for ClientHandle {
+    fn drop(&mut self) {
+        self.registry.inner.lock().unwrap().clients.remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_identified_client_name_is_listed() {
+        let registry = ClientRegistry::new();
+        let agent = registry.register(address(50001));
+        let other = registry.register(address(50002));
+
+        assert_eq!(agent.label(), format!("client-{}", agent.id()));
+        let info = agent.identify("  agent-1 ").unwrap();
+        assert_eq!(info.name.as_deref(), Some("agent-1"));
+        agent.record_request();
+
+        let clients = registry.list();
+        assert_eq!(clients.len(), 2);
+        assert_eq!(clients[0].name.as_deref(), Some("agent-1"));
+        assert_eq!(clients[0].address, "127.0.0.1:50001");
+        assert_eq!(clients[0].requests, 1);
+        assert_eq!(clients[1].id, other.id());
+        assert_eq!(clients[1].name, None);
+        assert_eq!(agent.label(), "agent-1");
+
+        let json = serde_json::to_value(&clients[0]).unwrap();
+        assert_eq!(json["name"], "agent-1");
+        assert!(json["connectedAt"].is_u64());
+    }
+
+    #[test]
+    fn test_invalid_names_are_rejected() {
+        let registry = ClientRegistry::new();
+        let client = registry.register(address(50001));
+
+        assert!(client.identify("   ").is_err());
+        assert!(client
+            .identify(&"x".repeat(MAX_CLIENT_NAME_CHARS + 1))
+            .is_err());
+        assert_eq!(registry.list()[0].name, None);
+    }
+
+    #[test]
+    fn test_dropping_handle_unregisters_client() {
+        let registry = ClientRegistry::new();
+        let first = registry.register(address(50001));
+        let second = registry.register(address(50002));
+
+        drop(first);
+
+        let ids: Vec<u64> = registry.list().iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![second.id()]);
+    }
+}
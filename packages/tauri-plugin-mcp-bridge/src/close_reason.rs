@@ -0,0 +1,163 @@
+//! Close codes sent when the server ends a connection.
+//!
+//! When the bridge closes a connection itself, it sends a WebSocket Close
+//! frame with one of these codes so clients can tell a shutdown apart from a
+//! rejected request and adjust their retry logic:
+//!
+//! | Code   | Reason                | When                                             | Retry?              |
+//! |--------|-----------------------|--------------------------------------------------|---------------------|
+//! | `1001` | `going away`          | The app is shutting down                         | Yes, after restart  |
+//! | `1002` | `protocol error`      | The client sent a malformed WebSocket frame      | After fixing client |
+//! | `4001` | `unauthorized`        | Authentication failed                            | With new credentials|
+//! | `4008` | `policy violation: …` | A message exceeded the size limit or other policy| After fixing request|
+//! | `4009` | `slow_consumer`       | The client stopped reading its frames            | Yes, read promptly  |
+//! | `4029` | `rate limited`        | The client sent too many requests                | Yes, with backoff   |
+//! | `4408` | `idle timeout`        | No message arrived within the idle timeout       | Yes, when needed    |
+//!
+//! Connections closed by the client, or dropped at the TCP level, end without
+//! a Close frame from the server.
+
+use tokio_tungstenite::tungstenite::{
+    self,
+    protocol::{frame::coding::CloseCode, CloseFrame},
+    Message,
+};
+
+/// Maximum length of a close reason; control frame payloads are limited to
+/// 125 bytes, two of which hold the code.
+const MAX_REASON_BYTES: usize = 123;
+
+/// Why the server is closing a connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The app is shutting down
+    GoingAway,
+    /// The client violated the WebSocket protocol
+    ProtocolError,
+    /// Authentication failed
+    Unauthorized,
+    /// The client violated a server policy, such as the message size limit
+    PolicyViolation(String),
+    /// The client exceeded the request rate limit
+    RateLimited,
+    /// The client sent no message within the configured idle timeout
+    IdleTimeout,
+    /// The client didn't read its frames fast enough, see
+    /// [`outbound`](crate::outbound)
+    SlowConsumer,
+}
+
+impl CloseReason {
+    /// The WebSocket close code for this reason.
+    pub fn code(&self) -> u16 {
+        match self {
+            Self::GoingAway => 1001,
+            Self::ProtocolError => 1002,
+            Self::Unauthorized => 4001,
+            Self::PolicyViolation(_) => 4008,
+            Self::SlowConsumer => 4009,
+            Self::RateLimited => 4029,
+            Self::IdleTimeout => 4408,
+        }
+    }
+
+    /// Human-readable reason, truncated to fit in a Close frame.
+    pub fn reason(&self) -> String {
+        let reason = match self {
+            Self::GoingAway => "going away".to_string(),
+            Self::ProtocolError => "protocol error".to_string(),
+            Self::Unauthorized => "unauthorized".to_string(),
+            Self::PolicyViolation(detail) => format!("policy violation: {detail}"),
+            Self::RateLimited => "rate limited".to_string(),
+            Self::IdleTimeout => "idle timeout".to_string(),
+            Self::SlowConsumer => "slow_consumer".to_string(),
+        };
+        truncate_to_char_boundary(reason, MAX_REASON_BYTES)
+    }
+
+    /// Builds the Close frame to send to the client.
+    pub fn to_message(&self) -> Message {
+        Message::Close(Some(CloseFrame {
+            code: CloseCode::from(self.code()),
+            reason: self.reason().into(),
+        }))
+    }
+
+    /// Maps a receive error to the close reason reported to the client.
+    ///
+    /// Returns `None` for errors where the connection is already unusable
+    /// (I/O failures, closed sockets), since no Close frame can be delivered.
+    pub fn from_error(error: &tungstenite::Error) -> Option<Self> {
+        match error {
+            tungstenite::Error::Capacity(e) => Some(Self::PolicyViolation(e.to_string())),
+            tungstenite::Error::Protocol(_) | tungstenite::Error::Utf8(_) => {
+                Some(Self::ProtocolError)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn truncate_to_char_boundary(mut text: String, max_bytes: usize) -> String {
+    if text.len() > max_bytes {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tungstenite::error::CapacityError;
+
+    fn close_frame(message: Message) -> CloseFrame {
+        match message {
+            Message::Close(Some(frame)) => frame,
+            other => panic!("expected close frame, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_codes_map_to_close_frames() {
+        let frame = close_frame(CloseReason::Unauthorized.to_message());
+        assert_eq!(u16::from(frame.code), 4001);
+        assert_eq!(frame.reason.as_str(), "unauthorized");
+
+        let frame = close_frame(CloseReason::GoingAway.to_message());
+        assert_eq!(frame.code, CloseCode::Away);
+
+        assert_eq!(CloseReason::RateLimited.code(), 4029);
+        assert_eq!(CloseReason::IdleTimeout.code(), 4408);
+        assert_eq!(CloseReason::SlowConsumer.code(), 4009);
+        assert_eq!(CloseReason::SlowConsumer.reason(), "slow_consumer");
+    }
+
+    #[test]
+    fn test_oversized_message_is_policy_violation() {
+        let error = tungstenite::Error::Capacity(CapacityError::MessageTooLong {
+            size: 100,
+            max_size: 10,
+        });
+
+        let reason = CloseReason::from_error(&error).unwrap();
+        assert_eq!(reason.code(), 4008);
+        assert!(reason.reason().starts_with("policy violation: "));
+
+        assert_eq!(
+            CloseReason::from_error(&tungstenite::Error::ConnectionClosed),
+            None
+        );
+    }
+
+    #[test]
+    fn test_long_reasons_fit_in_control_frame() {
+        let reason = CloseReason::PolicyViolation("é".repeat(100)).reason();
+
+        assert!(reason.len() <= MAX_REASON_BYTES);
+        assert!(reason.starts_with("policy violation: é"));
+    }
+}
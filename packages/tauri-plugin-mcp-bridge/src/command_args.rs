@@ -0,0 +1,909 @@
+//! Typed arguments for WebSocket commands.
+//!
+//! Each command's `args` object is deserialized into a struct with serde.
+//! Errors name the offending field so clients can fix their request:
+//!
+//! ```text
+//! missing field 'selector'
+//! field 'quality' expected integer, got string
+//! field 'quality' out of range: 300 (expected u8)
+//! ```
+//!
+//! Keys are camelCase, matching the JSON protocol. Unknown keys are ignored.
+
+use crate::commands::{
+    AssertOperator, AssertProperty, CacheKind, MediaFeatures, ThemeSetting, WindowLayout,
+    WindowMatch, World,
+};
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Parses a command's `args` into `T`.
+///
+/// Missing or `null` args are treated as an empty object, so commands whose
+/// fields are all optional can be sent without `args`.
+///
+/// # Arguments
+///
+/// * `args` - The `args` value from the request, if any
+///
+/// # Returns
+///
+/// * `Ok(T)` - The parsed arguments
+/// * `Err(String)` - A message naming the missing or mistyped field
+pub fn parse_args<T: DeserializeOwned>(args: Option<&Value>) -> Result<T, String> {
+    let fields = match args {
+        None | Some(Value::Null) => Map::new(),
+        Some(Value::Object(fields)) => fields.clone(),
+        Some(other) => {
+            return Err(format!(
+                "args expected object, got {}",
+                json_type_name(other)
+            ))
+        }
+    };
+
+    T::deserialize(ArgsDeserializer { fields }).map_err(|e| e.0)
+}
+
+/// Name of a JSON value's type as used in error messages.
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Maps a serde "expected" description to a JSON type name.
+fn expected_type_name(expected: &str) -> &str {
+    match expected {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+        | "isize" => "integer",
+        "f32" | "f64" => "number",
+        "a string" | "a borrowed string" | "a character" => "string",
+        "a boolean" => "boolean",
+        "a sequence" => "array",
+        "a map" => "object",
+        other if other.starts_with("struct ") => "object",
+        other => other,
+    }
+}
+
+/// Maps a serde "unexpected" description (e.g. ``integer `1` ``) to a JSON
+/// type name.
+fn unexpected_type_name(unexpected: &str) -> &str {
+    match unexpected.split_whitespace().next().unwrap_or(unexpected) {
+        "integer" => "integer",
+        "floating" => "number",
+        "string" => "string",
+        "boolean" => "boolean",
+        "sequence" => "array",
+        "map" => "object",
+        "unit" => "null",
+        other => other,
+    }
+}
+
+/// Rewrites a serde_json error for `field` into a client-facing message.
+///
+/// The types are taken from the error rather than `value`, since the
+/// mismatch may be nested (e.g. a number inside an array of strings).
+fn field_error(field: &str, value: &Value, error: &serde_json::Error) -> String {
+    let message = error.to_string();
+
+    if let Some(detail) = message.strip_prefix("invalid type: ") {
+        if let Some((unexpected, expected)) = detail.rsplit_once(", expected ") {
+            return format!(
+                "field '{field}' expected {}, got {}",
+                expected_type_name(expected),
+                unexpected_type_name(unexpected)
+            );
+        }
+    }
+    if message.starts_with("invalid value:") {
+        if let Some((_, expected)) = message.rsplit_once(", expected ") {
+            return format!("field '{field}' out of range: {value} (expected {expected})");
+        }
+    }
+    if let Some(nested) = message.strip_prefix("missing field `") {
+        return format!(
+            "field '{field}' missing field '{}",
+            nested.replacen('`', "'", 1)
+        );
+    }
+
+    format!("field '{field}': {message}")
+}
+
+/// Error type carrying the final client-facing message.
+#[derive(Debug)]
+struct ArgsError(String);
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+impl de::Error for ArgsError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self(format!("missing field '{field}'"))
+    }
+}
+
+/// Deserializes an args object, remembering which field each error came from.
+struct ArgsDeserializer {
+    fields: Map<String, Value>,
+}
+
+impl<'de> de::Deserializer<'de> for ArgsDeserializer {
+    type Error = ArgsError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ArgsError> {
+        visitor.visit_map(FieldAccess {
+            fields: self.fields.into_iter(),
+            pending: None,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+struct FieldAccess {
+    fields: serde_json::map::IntoIter,
+    pending: Option<(String, Value)>,
+}
+
+impl<'de> MapAccess<'de> for FieldAccess {
+    type Error = ArgsError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, ArgsError> {
+        match self.fields.next() {
+            Some((key, value)) => {
+                let deserializer: de::value::StringDeserializer<ArgsError> =
+                    key.clone().into_deserializer();
+                let key_value = seed.deserialize(deserializer)?;
+                self.pending = Some((key, value));
+                Ok(Some(key_value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ArgsError> {
+        let (key, value) = self
+            .pending
+            .take()
+            .ok_or_else(|| ArgsError("value requested before key".to_string()))?;
+        seed.deserialize(value.clone())
+            .map_err(|e| ArgsError(field_error(&key, &value, &e)))
+    }
+}
+
+/// Arguments naming an optional target window.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowArgs {
+    pub window_label: Option<String>,
+}
+
+/// The object holding a request's window arguments: the forwarded `args`
+/// for `invoke_tauri`, the request's own `args` for every other command.
+fn window_args_mut(command: &mut Value) -> Option<&mut Map<String, Value>> {
+    let invoke = command.get("command").and_then(Value::as_str) == Some("invoke_tauri");
+    let args = command.get_mut("args")?;
+    let args = if invoke { args.get_mut("args")? } else { args };
+    args.as_object_mut()
+}
+
+/// The `windowLabel` argument of a request, if it has one.
+pub fn window_label_arg(command: &Value) -> Option<&str> {
+    let invoke = command.get("command").and_then(Value::as_str) == Some("invoke_tauri");
+    let args = command.get("args")?;
+    let args = if invoke { args.get("args")? } else { args };
+    args.get("windowLabel").and_then(Value::as_str)
+}
+
+/// Takes the `windowMatch` argument out of a request.
+///
+/// # Returns
+///
+/// * `Ok(Some(WindowMatch))` - The criteria, to be resolved to a label
+/// * `Ok(None)` - If the request doesn't use `windowMatch`
+/// * `Err(String)` - If the criteria are malformed or `windowLabel` is also
+///   given
+pub fn take_window_match(command: &mut Value) -> Result<Option<WindowMatch>, String> {
+    let args = match window_args_mut(command) {
+        Some(args) => args,
+        None => return Ok(None),
+    };
+    let criteria = match args.remove("windowMatch") {
+        None | Some(Value::Null) => return Ok(None),
+        Some(criteria) => criteria,
+    };
+    if args
+        .get("windowLabel")
+        .is_some_and(|label| !label.is_null())
+    {
+        return Err("Pass either windowLabel or windowMatch, not both".to_string());
+    }
+
+    parse_args(Some(&criteria))
+        .map(Some)
+        .map_err(|e| format!("windowMatch: {e}"))
+}
+
+/// Sets the `windowLabel` argument of a request, e.g. to the window a
+/// `windowMatch` resolved to.
+pub fn set_window_label(command: &mut Value, label: &str) {
+    if let Some(args) = window_args_mut(command) {
+        args.insert("windowLabel".to_string(), Value::from(label));
+    }
+}
+
+/// Fills in the `windowLabel` argument of a request that doesn't name a
+/// window, e.g. with the connection's default window. Requests without
+/// `args` get an `args` object holding only the label.
+pub fn default_window_label(command: &mut Value, label: &str) {
+    let invoke = command.get("command").and_then(Value::as_str) == Some("invoke_tauri");
+    let Some(request) = command.as_object_mut() else {
+        return;
+    };
+    let mut args = request.entry("args").or_insert(Value::Null);
+    if invoke {
+        if args.is_null() {
+            *args = Value::Object(Map::new());
+        }
+        args = match args.as_object_mut() {
+            Some(invoke_args) => invoke_args.entry("args").or_insert(Value::Null),
+            None => return,
+        };
+    }
+    if args.is_null() {
+        *args = Value::Object(Map::new());
+    }
+    if let Some(args) = args.as_object_mut() {
+        if args.get("windowLabel").map_or(true, Value::is_null) {
+            args.insert("windowLabel".to_string(), Value::from(label));
+        }
+    }
+}
+
+/// Arguments for `set_default_window`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultWindowArgs {
+    pub window_label: String,
+}
+
+/// Arguments for commands that act on one element, found by `selector` or
+/// by a `handleId` from `query_element_handle`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorArgs {
+    pub selector: Option<String>,
+    pub handle_id: Option<String>,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `element_from_point`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElementFromPointArgs {
+    pub x: f64,
+    pub y: f64,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `query_element_handle`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryElementHandleArgs {
+    pub selector: String,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `invoke_tauri`.
+#[derive(Debug, Deserialize)]
+pub struct InvokeTauriArgs {
+    /// Full command name, e.g. `plugin:mcp-bridge|get_window_info`
+    pub command: String,
+    /// Arguments forwarded to the command
+    pub args: Option<Value>,
+}
+
+/// Arguments for `set_window_flags`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetWindowFlagsArgs {
+    pub window_label: Option<String>,
+    pub always_on_top: Option<bool>,
+    pub decorations: Option<bool>,
+    pub resizable: Option<bool>,
+    pub fullscreen: Option<bool>,
+}
+
+/// Arguments for `dispatch_dom_event`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DispatchDomEventArgs {
+    pub selector: Option<String>,
+    pub handle_id: Option<String>,
+    pub event_type: String,
+    pub bubbles: Option<bool>,
+    pub detail: Option<Value>,
+}
+
+/// Arguments for `drag_and_drop`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DragAndDropArgs {
+    pub source_selector: String,
+    pub target_selector: String,
+    pub mode: Option<String>,
+}
+
+/// Arguments for `assert_element`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertElementArgs {
+    pub selector: String,
+    pub property: AssertProperty,
+    pub attribute_name: Option<String>,
+    pub expected: Value,
+    pub operator: Option<AssertOperator>,
+}
+
+/// Arguments for `freeze_time`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FreezeTimeArgs {
+    pub at: Option<f64>,
+}
+
+/// Arguments for `step_time`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepTimeArgs {
+    pub ms: u64,
+}
+
+/// Arguments for `set_user_agent`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetUserAgentArgs {
+    pub user_agent: String,
+}
+
+/// Arguments for `emulate_media`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmulateMediaArgs {
+    pub features: Option<MediaFeatures>,
+    pub reset: Option<bool>,
+}
+
+/// Arguments for `set_theme`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetThemeArgs {
+    pub theme: ThemeSetting,
+}
+
+/// Arguments for `clear_cache`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearCacheArgs {
+    pub kind: Option<CacheKind>,
+}
+
+/// Arguments for `get_computed_style`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComputedStyleArgs {
+    pub selector: String,
+    pub properties: Option<Vec<String>>,
+}
+
+/// Arguments for `reset_window`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetWindowArgs {
+    pub clear_cache: Option<bool>,
+    pub clear_storage: Option<bool>,
+    pub clear_cookies: Option<bool>,
+}
+
+/// Arguments for `set_input_value`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetInputValueArgs {
+    pub selector: Option<String>,
+    pub handle_id: Option<String>,
+    pub value: Value,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `fill_form`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillFormArgs {
+    pub fields: Map<String, Value>,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `read_form`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadFormArgs {
+    pub form_selector: String,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `list_resources`.
+#[derive(Debug, Deserialize)]
+pub struct ListResourcesArgs {
+    pub glob: Option<String>,
+}
+
+/// Arguments for `read_resource`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceArgs {
+    pub path: String,
+    pub max_bytes: Option<usize>,
+}
+
+/// Arguments for `emit_event`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmitEventArgs {
+    pub event_name: String,
+    #[serde(default)]
+    pub payload: Value,
+}
+
+/// Arguments for `hello`.
+#[derive(Debug, Deserialize)]
+pub struct HelloArgs {
+    #[serde(default)]
+    pub features: Vec<String>,
+    pub format: Option<String>,
+}
+
+/// Arguments for `identify`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentifyArgs {
+    pub name: String,
+}
+
+/// Arguments for `subscribe` and `unsubscribe`.
+#[derive(Debug, Deserialize)]
+pub struct TopicsArgs {
+    pub topics: Vec<String>,
+}
+
+/// Arguments for `get_bridge_logs`.
+#[derive(Debug, Deserialize)]
+pub struct BridgeLogsArgs {
+    pub limit: Option<usize>,
+}
+
+/// Arguments for `abort_all`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortAllArgs {
+    pub stop_ipc_monitor: Option<bool>,
+    pub stop_screencasts: Option<bool>,
+}
+
+/// Arguments for `wait_for_event`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForEventArgs {
+    pub event_name: String,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Arguments for `wait_for_ready`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForReadyArgs {
+    pub state: String,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Arguments for `wait_for_network_idle`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForNetworkIdleArgs {
+    pub idle_ms: Option<u64>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Arguments for `observe_mutations`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObserveMutationsArgs {
+    pub duration_ms: u64,
+    pub quiet_ms: Option<u64>,
+}
+
+/// Arguments for `execute_js`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteJsArgs {
+    pub script: String,
+    pub window_label: Option<String>,
+    pub max_result_bytes: Option<usize>,
+    pub world: Option<World>,
+}
+
+/// Arguments for `execute_js_all`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteJsAllArgs {
+    pub script: String,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Arguments for `execute_js_file`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteJsFileArgs {
+    pub path: String,
+    pub window_label: Option<String>,
+    /// Exposed to the script as `args`
+    pub args: Option<Value>,
+}
+
+/// Arguments for `execute_js_if`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecuteJsIfArgs {
+    pub condition: String,
+    pub script: String,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `capture_native_screenshot`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotArgs {
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+    pub window_label: Option<String>,
+    pub background: Option<String>,
+    pub settle_ms: Option<u64>,
+    pub canvas_selector: Option<String>,
+    /// `"inline"` (default) or `"url"`, see [`crate::capture_store`]
+    pub delivery: Option<String>,
+    /// Send only the region changed since the previous `diff` capture
+    pub diff: Option<bool>,
+    /// `"rgb"` (default) or `"grayscale"`
+    pub color_mode: Option<String>,
+    pub max_colors: Option<u16>,
+    /// Scrollable element to capture whole, stitched from several captures
+    pub scroll_container_selector: Option<String>,
+    /// CSS pixels of sticky content to drop from each later slice
+    pub scroll_overlap: Option<u32>,
+}
+
+/// Arguments for `capture_window_with_chrome`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureWindowArgs {
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+}
+
+/// Arguments for `compare_screenshot`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareScreenshotArgs {
+    pub baseline_path: String,
+    pub threshold: Option<f64>,
+    pub max_diff_ratio: Option<f64>,
+    pub diff_image: Option<bool>,
+}
+
+/// Arguments for `start_screencast`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartScreencastArgs {
+    pub fps: Option<f64>,
+    pub format: Option<String>,
+    pub quality: Option<u8>,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `stop_screencast`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopScreencastArgs {
+    pub stream_id: Option<String>,
+}
+
+/// Arguments for `get_console_history`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleHistoryArgs {
+    pub levels: Option<Vec<String>>,
+    pub limit: Option<usize>,
+}
+
+/// Arguments for `get_failed_requests`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedRequestsArgs {
+    pub since: Option<u64>,
+}
+
+/// Arguments for `set_timeout`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTimeoutArgs {
+    /// `None` goes back to the global command timeout
+    pub default_ms: Option<u64>,
+}
+
+/// Arguments for `set_trace`.
+#[derive(Debug, Deserialize)]
+pub struct SetTraceArgs {
+    pub enabled: bool,
+}
+
+/// Arguments for `get_readable_text`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadableTextArgs {
+    pub max_bytes: Option<usize>,
+}
+
+/// Arguments for `run_macro`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunMacroArgs {
+    pub name: String,
+    pub params: Option<Map<String, Value>>,
+}
+
+/// Arguments for `watch_js`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchJsArgs {
+    pub expression: String,
+    pub interval_ms: Option<u64>,
+    pub every_tick: Option<bool>,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `stop_watch`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopWatchArgs {
+    pub watch_id: String,
+}
+
+/// Arguments for `register_script`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterScriptArgs {
+    pub id: String,
+    /// `"inline"` or `"url"`
+    #[serde(rename = "type")]
+    pub script_type: String,
+    pub content: String,
+    pub window_label: Option<String>,
+}
+
+/// Arguments for `remove_script`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveScriptArgs {
+    pub id: String,
+    pub window_label: Option<String>,
+    #[serde(default)]
+    pub all_windows: bool,
+}
+
+/// Arguments for `clear_scripts`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClearScriptsArgs {
+    pub window_label: Option<String>,
+    #[serde(default)]
+    pub all_windows: bool,
+}
+
+/// Arguments for `add_mock`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddMockArgs {
+    pub id: Option<String>,
+    pub url_pattern: String,
+    pub method: Option<String>,
+    pub status: u16,
+    pub body: Option<Value>,
+    pub headers: Option<BTreeMap<String, String>>,
+}
+
+/// Arguments for `set_layout`.
+#[derive(Debug, Deserialize)]
+pub struct SetLayoutArgs {
+    pub layout: Vec<WindowLayout>,
+}
+
+/// Arguments for `remove_mock`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveMockArgs {
+    pub id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_screenshot_args_parse() {
+        let args: ScreenshotArgs = parse_args(Some(&json!({
+            "format": "jpeg",
+            "quality": 80,
+            "windowLabel": "main",
+            "unknown": true
+        })))
+        .unwrap();
+
+        assert_eq!(args.format.as_deref(), Some("jpeg"));
+        assert_eq!(args.quality, Some(80));
+        assert_eq!(args.window_label.as_deref(), Some("main"));
+        assert_eq!(args.settle_ms, None);
+    }
+
+    #[test]
+    fn test_wrong_type_names_the_field() {
+        let err = parse_args::<ScreenshotArgs>(Some(&json!({ "quality": "high" }))).unwrap_err();
+        assert_eq!(err, "field 'quality' expected integer, got string");
+
+        let err = parse_args::<ScreenshotArgs>(Some(&json!({ "quality": 300 }))).unwrap_err();
+        assert_eq!(err, "field 'quality' out of range: 300 (expected u8)");
+
+        let err = parse_args::<ExecuteJsArgs>(Some(&json!({ "script": 42 }))).unwrap_err();
+        assert_eq!(err, "field 'script' expected string, got integer");
+
+        let err = parse_args::<RemoveScriptArgs>(Some(&json!({ "id": "a", "allWindows": "yes" })))
+            .unwrap_err();
+        assert_eq!(err, "field 'allWindows' expected boolean, got string");
+    }
+
+    #[test]
+    fn test_missing_field_is_reported() {
+        let err = parse_args::<ExecuteJsArgs>(Some(&json!({ "windowLabel": "main" }))).unwrap_err();
+        assert_eq!(err, "missing field 'script'");
+
+        let err = parse_args::<ExecuteJsArgs>(None).unwrap_err();
+        assert_eq!(err, "missing field 'script'");
+    }
+
+    #[test]
+    fn test_optional_args_may_be_omitted() {
+        let args: ClearScriptsArgs = parse_args(None).unwrap();
+        assert!(!args.all_windows);
+        assert_eq!(args.window_label, None);
+
+        let args: ClearScriptsArgs = parse_args(Some(&Value::Null)).unwrap();
+        assert!(!args.all_windows);
+    }
+
+    #[test]
+    fn test_window_match_is_replaced_by_label() {
+        let mut command = json!({
+            "command": "capture_native_screenshot",
+            "args": { "windowMatch": { "urlContains": "/editor" } }
+        });
+        let criteria = take_window_match(&mut command).unwrap().unwrap();
+        assert_eq!(criteria.url_contains.as_deref(), Some("/editor"));
+
+        set_window_label(&mut command, "doc-1");
+        assert_eq!(command["args"], json!({ "windowLabel": "doc-1" }));
+
+        // invoke_tauri carries window arguments in the forwarded args
+        let mut command = json!({
+            "command": "invoke_tauri",
+            "args": {
+                "command": "plugin:mcp-bridge|get_window_info",
+                "args": { "windowMatch": { "titleContains": "Editor" } }
+            }
+        });
+        assert!(take_window_match(&mut command).unwrap().is_some());
+        set_window_label(&mut command, "doc-1");
+        assert_eq!(command["args"]["args"]["windowLabel"], "doc-1");
+
+        let mut command = json!({ "command": "list_windows" });
+        assert_eq!(take_window_match(&mut command), Ok(None));
+    }
+
+    #[test]
+    fn test_window_match_conflicts_with_label() {
+        let mut command = json!({
+            "command": "execute_js",
+            "args": { "windowLabel": "main", "windowMatch": { "label": "main" } }
+        });
+        assert_eq!(
+            take_window_match(&mut command),
+            Err("Pass either windowLabel or windowMatch, not both".to_string())
+        );
+
+        let mut command = json!({
+            "command": "execute_js",
+            "args": { "windowMatch": { "urlContains": 1 } }
+        });
+        assert_eq!(
+            take_window_match(&mut command),
+            Err("windowMatch: field 'urlContains' expected string, got integer".to_string())
+        );
+    }
+
+    #[test]
+    fn test_default_window_label_only_fills_missing_labels() {
+        let mut command = json!({ "command": "get_dom" });
+        default_window_label(&mut command, "settings");
+        assert_eq!(command["args"], json!({ "windowLabel": "settings" }));
+
+        let mut command = json!({
+            "command": "execute_js",
+            "args": { "script": "1", "windowLabel": "main" }
+        });
+        default_window_label(&mut command, "settings");
+        assert_eq!(command["args"]["windowLabel"], "main");
+
+        let mut command = json!({
+            "command": "invoke_tauri",
+            "args": { "command": "plugin:mcp-bridge|get_window_info", "args": null }
+        });
+        default_window_label(&mut command, "settings");
+        assert_eq!(
+            command["args"]["args"],
+            json!({ "windowLabel": "settings" })
+        );
+    }
+
+    #[test]
+    fn test_non_object_args_are_rejected() {
+        let err = parse_args::<WindowArgs>(Some(&json!(["main"]))).unwrap_err();
+        assert_eq!(err, "args expected object, got array");
+    }
+
+    #[test]
+    fn test_array_elements_report_their_field() {
+        let err = parse_args::<TopicsArgs>(Some(&json!({ "topics": [1] }))).unwrap_err();
+        assert_eq!(err, "field 'topics' expected string, got integer");
+
+        let err = parse_args::<TopicsArgs>(Some(&json!({ "topics": "navigation" }))).unwrap_err();
+        assert_eq!(err, "field 'topics' expected array, got string");
+    }
+}
@@ -0,0 +1,176 @@
+//! Recovering a stuck bridge without restarting the app.
+
+use crate::abort::AbortSignal;
+use crate::commands::ScriptExecutor;
+use crate::monitor::{IPCMonitorState, MonitorState};
+use serde::Serialize;
+use tauri::{command, State};
+
+/// What `abort_all` cancelled and cleared.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortSummary {
+    /// Operations (script executions and waits) that were cancelled
+    pub operations_cancelled: usize,
+    /// Script results still awaited from the webview that were discarded
+    pub pending_results_cleared: usize,
+    /// Screencasts that were stopped, on any connection
+    pub screencasts_stopped: usize,
+    /// Whether a running IPC monitor was stopped
+    pub ipc_monitor_stopped: bool,
+}
+
+/// Cancels everything in flight and resets the bridge's transient state.
+///
+/// A panic button for automation runs that got stuck: every script
+/// execution, `wait_for_event` and `wait_for_ready` in progress, on any
+/// connection, fails with `"Cancelled by abort_all"`, and script results
+/// still expected from the webview are discarded. Commands sent afterwards
+/// run normally.
+///
+/// # Arguments
+///
+/// * `stop_ipc_monitor` - Also stop IPC monitoring (default false). Captured
+///   events are kept.
+/// * `stop_screencasts` - Also stop every screencast, which ends with a
+///   `screencast_error` frame (default false)
+/// * `signal` - The bridge's abort signal
+/// * `executor_state` - Shared script executor state
+/// * `monitor` - Shared state for the IPC monitor
+///
+/// # Returns
+///
+/// * `Ok(AbortSummary)` - `{ operationsCancelled, pendingResultsCleared,
+///   screencastsStopped, ipcMonitorStopped }`
+/// * `Err(String)` - If the monitor lock fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const summary = await invoke('plugin:mcp-bridge|abort_all', {
+///   stopIpcMonitor: true,
+///   stopScreencasts: true
+/// });
+/// ```
+#[command]
+pub async fn abort_all(
+    stop_ipc_monitor: Option<bool>,
+    stop_screencasts: Option<bool>,
+    signal: State<'_, AbortSignal>,
+    executor_state: State<'_, ScriptExecutor>,
+    monitor: State<'_, IPCMonitorState>,
+) -> Result<AbortSummary, String> {
+    let ipc_monitor_stopped = if stop_ipc_monitor.unwrap_or(false) {
+        let mut mon = monitor.lock().map_err(|e| format!("Lock error: {e}"))?;
+        let running = mon.state() != MonitorState::Stopped;
+        mon.stop();
+        running
+    } else {
+        false
+    };
+
+    Ok(abort(
+        &signal,
+        &executor_state,
+        stop_screencasts.unwrap_or(false),
+        ipc_monitor_stopped,
+    )
+    .await)
+}
+
+/// Fires the abort signal and clears pending script results.
+async fn abort(
+    signal: &AbortSignal,
+    executor: &ScriptExecutor,
+    stop_screencasts: bool,
+    ipc_monitor_stopped: bool,
+) -> AbortSummary {
+    AbortSummary {
+        operations_cancelled: signal.abort_operations(),
+        pending_results_cleared: executor.abort_pending().await,
+        screencasts_stopped: if stop_screencasts {
+            signal.abort_streams()
+        } else {
+            0
+        },
+        ipc_monitor_stopped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abort::ABORTED;
+    use serde_json::Value;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn test_long_operations_resolve_with_cancelled_error() {
+        let signal = AbortSignal::new();
+        let executor = ScriptExecutor::new();
+
+        // Two waits that would otherwise run for a minute
+        let waits: Vec<_> = (0..2)
+            .map(|_| {
+                let signal = signal.clone();
+                tokio::spawn(async move {
+                    signal
+                        .run(async {
+                            tokio::time::sleep(Duration::from_secs(60)).await;
+                            Ok(Value::Null)
+                        })
+                        .await
+                })
+            })
+            .collect();
+        // A script whose result never arrives
+        let (tx, rx) = oneshot::channel();
+        executor
+            .pending_results
+            .lock()
+            .await
+            .insert("exec-1".to_string(), tx);
+        while signal.operations_in_flight() < 2 {
+            tokio::task::yield_now().await;
+        }
+        let _stream = signal.stream_token();
+
+        let summary = abort(&signal, &executor, false, false).await;
+
+        assert_eq!(
+            summary,
+            AbortSummary {
+                operations_cancelled: 2,
+                pending_results_cleared: 1,
+                screencasts_stopped: 0,
+                ipc_monitor_stopped: false,
+            }
+        );
+        for wait in waits {
+            let result = tokio::time::timeout(Duration::from_secs(1), wait)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(result, Err(ABORTED.to_string()));
+        }
+        let script = rx.await.unwrap();
+        assert_eq!(script["success"], false);
+        assert_eq!(script["error"], ABORTED);
+    }
+
+    #[tokio::test]
+    async fn test_screencasts_stop_only_when_asked() {
+        let signal = AbortSignal::new();
+        let stream = signal.stream_token();
+
+        abort(&signal, &ScriptExecutor::new(), false, false).await;
+        assert!(!stream.is_aborted());
+
+        let summary = abort(&signal, &ScriptExecutor::new(), true, false).await;
+        assert_eq!(summary.screencasts_stopped, 1);
+        assert!(stream.is_aborted());
+    }
+}
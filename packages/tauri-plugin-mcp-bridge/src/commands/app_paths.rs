@@ -0,0 +1,103 @@
+//! Application directory lookup.
+
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::{command, AppHandle, Manager, Runtime};
+
+/// Directories the app reads from and writes to.
+///
+/// Each path is `None` (`null` in JSON) when it can't be resolved on the
+/// current platform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppPaths {
+    /// Per-app data directory, e.g. `~/.local/share/<identifier>`
+    pub app_data_dir: Option<String>,
+    /// Per-app config directory, e.g. `~/.config/<identifier>`
+    pub app_config_dir: Option<String>,
+    /// Per-app cache directory, e.g. `~/.cache/<identifier>`
+    pub app_cache_dir: Option<String>,
+    /// Per-app log directory
+    pub app_log_dir: Option<String>,
+    /// Directory the app's bundled resources are read from
+    pub resource_dir: Option<String>,
+}
+
+/// Reports where the app keeps its data, config, cache, logs and resources.
+///
+/// Paths are resolved with Tauri's path API for the app's identifier, so
+/// they match what the app's own `path` calls return. Complements
+/// [`get_backend_state`](super::get_backend_state) when debugging storage.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+///
+/// # Returns
+///
+/// * `Ok(AppPaths)` - `{ appDataDir, appConfigDir, appCacheDir, appLogDir,
+///   resourceDir }` as absolute paths, with `null` for any that fail to
+///   resolve
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { appDataDir, appLogDir } = await invoke('plugin:mcp-bridge|get_app_paths');
+/// ```
+#[command]
+pub async fn get_app_paths<R: Runtime>(app: AppHandle<R>) -> Result<AppPaths, String> {
+    let path = app.path();
+
+    Ok(AppPaths {
+        app_data_dir: absolute_path(path.app_data_dir()),
+        app_config_dir: absolute_path(path.app_config_dir()),
+        app_cache_dir: absolute_path(path.app_cache_dir()),
+        app_log_dir: absolute_path(path.app_log_dir()),
+        resource_dir: absolute_path(path.resource_dir()),
+    })
+}
+
+/// Keeps a resolved path only if it is absolute and non-empty.
+fn absolute_path<E>(resolved: Result<PathBuf, E>) -> Option<String> {
+    resolved
+        .ok()
+        .filter(|path| path.is_absolute())
+        .map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolved_paths_are_absolute_and_non_empty() {
+        let data_dir = std::env::temp_dir().join("com.example.app");
+        let resolved: Result<PathBuf, ()> = Ok(data_dir.clone());
+
+        let path = absolute_path(resolved).unwrap();
+
+        assert!(!path.is_empty());
+        assert!(PathBuf::from(&path).is_absolute());
+        assert_eq!(PathBuf::from(path), data_dir);
+    }
+
+    #[test]
+    fn test_unresolvable_paths_are_null() {
+        assert_eq!(absolute_path(Err::<PathBuf, _>("unknown path")), None);
+        assert_eq!(absolute_path(Ok::<_, ()>(PathBuf::new())), None);
+        assert_eq!(absolute_path(Ok::<_, ()>(PathBuf::from("relative"))), None);
+
+        let paths = AppPaths {
+            app_data_dir: None,
+            app_config_dir: None,
+            app_cache_dir: None,
+            app_log_dir: None,
+            resource_dir: Some("/opt/app/resources".to_string()),
+        };
+        let json = serde_json::to_value(paths).unwrap();
+        assert!(json["appDataDir"].is_null());
+        assert_eq!(json["resourceDir"], "/opt/app/resources");
+    }
+}
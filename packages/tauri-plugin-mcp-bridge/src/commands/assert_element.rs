@@ -0,0 +1,227 @@
+//! Assertions on an element's text, value, attributes or match count.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::script_registry::js_string_literal;
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// What `assert_element` reads from the page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssertProperty {
+    /// The element's rendered text, trimmed
+    Text,
+    /// The form field's `value`
+    Value,
+    /// The attribute named by `attributeName`, or `null` when absent
+    Attribute,
+    /// How many elements match the selector
+    Count,
+}
+
+/// How `assert_element` compares the actual value with `expected`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AssertOperator {
+    /// Equal
+    #[default]
+    Eq,
+    /// The actual value contains `expected` as a substring
+    Contains,
+    /// `expected` is a JavaScript regular expression matching the actual value
+    Matches,
+}
+
+/// Checks a condition on the first element matching a CSS selector.
+///
+/// Text, value and attribute assertions fail with an error when nothing
+/// matches the selector, so a typo isn't mistaken for a failed assertion;
+/// assert `count` `eq` `0` to check that an element is absent.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `property` - `"text"`, `"value"`, `"attribute"` or `"count"`
+/// * `attribute_name` - The attribute to read, required for `"attribute"`
+/// * `expected` - The expected value; a regular expression for `"matches"`
+/// * `operator` - `"eq"` (default), `"contains"` or `"matches"`; `"count"`
+///   only supports `"eq"`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ passed, actual, expected }`
+/// * `Err(String)` - If the arguments don't fit together, no element matches,
+///   or `expected` isn't a valid regular expression
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { passed, actual } = await invoke('plugin:mcp-bridge|assert_element', {
+///   selector: '#status',
+///   property: 'text',
+///   operator: 'contains',
+///   expected: 'Saved'
+/// });
+/// ```
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn assert_element<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: String,
+    property: AssertProperty,
+    attribute_name: Option<String>,
+    expected: Value,
+    operator: Option<AssertOperator>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let script = assert_script(
+        &selector,
+        property,
+        attribute_name.as_deref(),
+        &expected,
+        operator.unwrap_or_default(),
+    )?;
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Builds the script reading `property` and comparing it with `expected`.
+fn assert_script(
+    selector: &str,
+    property: AssertProperty,
+    attribute_name: Option<&str>,
+    expected: &Value,
+    operator: AssertOperator,
+) -> Result<String, String> {
+    let read = match property {
+        AssertProperty::Count => {
+            if operator != AssertOperator::Eq {
+                return Err("count assertions only support the eq operator".to_string());
+            }
+            if !expected.is_u64() {
+                return Err(format!(
+                    "Expected count must be a non-negative integer, got {expected}"
+                ));
+            }
+            "document.querySelectorAll(selector).length".to_string()
+        }
+        AssertProperty::Text => "(el.innerText ?? el.textContent ?? '').trim()".to_string(),
+        AssertProperty::Value => "('value' in el ? String(el.value) : null)".to_string(),
+        AssertProperty::Attribute => {
+            let name = attribute_name
+                .ok_or_else(|| "attributeName is required for attribute assertions".to_string())?;
+            format!("el.getAttribute({})", js_string_literal(name))
+        }
+    };
+    let find = if property == AssertProperty::Count {
+        ""
+    } else {
+        "const el = document.querySelector(selector);\n\
+         if (!el) { throw new Error('No element matches selector: ' + selector); }\n"
+    };
+    let compare = match operator {
+        AssertOperator::Eq if property == AssertProperty::Count => "actual === expected",
+        AssertOperator::Eq => "actual !== null && actual === String(expected)",
+        AssertOperator::Contains => "actual !== null && actual.includes(String(expected))",
+        AssertOperator::Matches => "actual !== null && new RegExp(String(expected)).test(actual)",
+    };
+
+    let expected = serde_json::to_string(expected).map_err(|e| e.to_string())?;
+    Ok(format!(
+        r#"const selector = {selector};
+const expected = {expected};
+{find}const actual = {read};
+return {{ passed: {compare}, actual, expected }};"#,
+        selector = js_string_literal(selector),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_each_operator_builds_its_comparison() {
+        let eq = assert_script(
+            "#status",
+            AssertProperty::Text,
+            None,
+            &json!("Saved"),
+            AssertOperator::Eq,
+        )
+        .unwrap();
+        assert!(eq.starts_with("const selector = \"#status\";\nconst expected = \"Saved\";"));
+        assert!(eq.contains("No element matches selector: "));
+        assert!(eq.contains("passed: actual !== null && actual === String(expected)"));
+
+        let contains = assert_script(
+            "a",
+            AssertProperty::Attribute,
+            Some("href"),
+            &json!("/docs"),
+            AssertOperator::Contains,
+        )
+        .unwrap();
+        assert!(contains.contains(r#"const actual = el.getAttribute("href");"#));
+        assert!(contains.contains("actual.includes(String(expected))"));
+
+        let matches = assert_script(
+            "#email",
+            AssertProperty::Value,
+            None,
+            &json!("^\\w+@example\\.com$"),
+            AssertOperator::Matches,
+        )
+        .unwrap();
+        assert!(matches.contains(r#"const expected = "^\\w+@example\\.com$";"#));
+        assert!(matches.contains("new RegExp(String(expected)).test(actual)"));
+    }
+
+    #[test]
+    fn test_count_needs_no_element() {
+        let absent = assert_script(
+            ".error",
+            AssertProperty::Count,
+            None,
+            &json!(0),
+            AssertOperator::Eq,
+        )
+        .unwrap();
+        assert!(!absent.contains("throw"));
+        assert!(absent.contains("const actual = document.querySelectorAll(selector).length;"));
+        assert!(absent.contains("passed: actual === expected"));
+
+        assert!(assert_script(
+            ".error",
+            AssertProperty::Count,
+            None,
+            &json!(0),
+            AssertOperator::Contains
+        )
+        .unwrap_err()
+        .contains("only support the eq operator"));
+        assert!(assert_script(
+            ".error",
+            AssertProperty::Count,
+            None,
+            &json!("2"),
+            AssertOperator::Eq
+        )
+        .is_err());
+        assert_eq!(
+            assert_script(
+                "a",
+                AssertProperty::Attribute,
+                None,
+                &json!("x"),
+                AssertOperator::Eq
+            ),
+            Err("attributeName is required for attribute assertions".to_string())
+        );
+    }
+}
@@ -1,5 +1,7 @@
 //! Backend state retrieval.
 
+use crate::commands::list_windows::accessible_windows;
+use crate::commands::{execute_js, ScriptExecutor};
 use crate::monitor::current_timestamp;
 use serde_json::Value;
 use tauri::{command, AppHandle, Manager, Runtime};
@@ -15,15 +17,16 @@ use tauri::{command, AppHandle, Manager, Runtime};
 ///   - `app`: Application metadata (name, identifier, version)
 ///   - `tauri`: Tauri framework version
 ///   - `environment`: Runtime environment info (debug mode, OS, arch)
-///   - `windows`: List of window labels and their states
+///   - `webview`: Webview engine and version (`version` is `null` if unknown)
+///   - `windows`: List of window labels and their states, without windows
+///     restricted by the window allowlist or denylist
 ///   - `timestamp`: Current timestamp in milliseconds
 #[command]
 pub async fn get_backend_state<R: Runtime>(app: AppHandle<R>) -> Result<Value, String> {
     let config = app.config();
 
     // Get window information
-    let windows: Vec<Value> = app
-        .webview_windows()
+    let windows: Vec<Value> = accessible_windows(&app)
         .iter()
         .map(|(label, window)| {
             let is_focused = window.is_focused().unwrap_or(false);
@@ -39,6 +42,8 @@ pub async fn get_backend_state<R: Runtime>(app: AppHandle<R>) -> Result<Value, S
         })
         .collect();
 
+    let webview = webview_info(&app).await;
+
     Ok(serde_json::json!({
         "app": {
             "name": config.product_name.clone().unwrap_or_else(|| "Unknown".to_string()),
@@ -54,8 +59,137 @@ pub async fn get_backend_state<R: Runtime>(app: AppHandle<R>) -> Result<Value, S
             "arch": std::env::consts::ARCH,
             "family": std::env::consts::FAMILY,
         },
+        "webview": webview,
         "windows": windows,
         "window_count": windows.len(),
         "timestamp": current_timestamp(),
     }))
 }
+
+/// Webview engine used on the current platform.
+fn webview_engine() -> &'static str {
+    if cfg!(windows) {
+        "webview2"
+    } else if cfg!(target_vendor = "apple") {
+        "wkwebview"
+    } else if cfg!(target_os = "android") {
+        "android-webview"
+    } else {
+        "webkitgtk"
+    }
+}
+
+/// Describes the webview engine and its version.
+///
+/// The version is parsed from `navigator.userAgent` of an open window. When no
+/// window can report it, falls back to the version reported by the runtime,
+/// and to `null` if that is unavailable too.
+async fn webview_info<R: Runtime>(app: &AppHandle<R>) -> Value {
+    let engine = webview_engine();
+
+    let mut version = None;
+    let mut windows = accessible_windows(app);
+    if let (Some(window), Some(executor)) = (
+        windows
+            .remove("main")
+            .or_else(|| windows.into_values().next()),
+        app.try_state::<ScriptExecutor>(),
+    ) {
+        if let Ok(result) = execute_js(
+            window,
+            "navigator.userAgent".to_string(),
+            executor,
+            None,
+            Some(false),
+            None,
+        )
+        .await
+        {
+            version = result
+                .get("data")
+                .and_then(|v| v.as_str())
+                .and_then(|ua| parse_webview_version(engine, ua));
+        }
+    }
+    let version = version.or_else(|| tauri::webview_version().ok());
+
+    serde_json::json!({
+        "engine": engine,
+        "version": version,
+    })
+}
+
+/// Extracts the engine version from a user agent string.
+fn parse_webview_version(engine: &str, user_agent: &str) -> Option<String> {
+    let token = match engine {
+        "webview2" => "Edg/",
+        "android-webview" => "Chrome/",
+        _ => "AppleWebKit/",
+    };
+
+    let start = user_agent.find(token)? + token.len();
+    let version: String = user_agent[start..]
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+
+    (!version.is_empty()).then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_webview2_version() {
+        let ua = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.91";
+        assert_eq!(
+            parse_webview_version("webview2", ua).as_deref(),
+            Some("120.0.2210.91")
+        );
+    }
+
+    #[test]
+    fn test_parse_webkit_version() {
+        let ua = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko)";
+        assert_eq!(
+            parse_webview_version("wkwebview", ua).as_deref(),
+            Some("605.1.15")
+        );
+        assert_eq!(
+            parse_webview_version("webkitgtk", ua).as_deref(),
+            Some("605.1.15")
+        );
+    }
+
+    #[test]
+    fn test_unknown_version_is_none() {
+        assert_eq!(parse_webview_version("webview2", "Mozilla/5.0"), None);
+        assert_eq!(parse_webview_version("wkwebview", "AppleWebKit/ (x)"), None);
+    }
+
+    #[tokio::test]
+    async fn test_state_leaves_out_restricted_windows() {
+        let app = tauri::test::mock_app();
+        for label in ["main", "settings", "payment"] {
+            tauri::WebviewWindowBuilder::new(app.handle(), label, Default::default())
+                .build()
+                .unwrap();
+        }
+        app.manage(crate::config::Config {
+            window_denylist: vec!["payment".to_string()],
+            ..crate::config::Config::default()
+        });
+
+        let state = get_backend_state(app.handle().clone()).await.unwrap();
+        let mut labels: Vec<&str> = state["windows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|window| window["label"].as_str().unwrap())
+            .collect();
+        labels.sort();
+        assert_eq!(labels, ["main", "settings"]);
+        assert_eq!(state["window_count"], 2);
+    }
+}
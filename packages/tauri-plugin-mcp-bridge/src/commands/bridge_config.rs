@@ -0,0 +1,36 @@
+//! Reporting the bridge's effective configuration.
+
+use crate::config::Config;
+use serde_json::Value;
+use tauri::{command, AppHandle, Manager, Runtime};
+
+/// Returns the configuration the bridge is running with.
+///
+/// The result uses the config file keys, so it can be saved as a
+/// `mcp-bridge.json` to reproduce the setup elsewhere. Secrets are replaced
+/// with `"***"`; see [`Config::redacted`].
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The configuration, e.g. `{ bind_address, base_port,
+///   port_scan_attempts, max_result_bytes, js_sandbox, ... }`
+/// * `Err(String)` - If the plugin hasn't finished setting up
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const config = await invoke('plugin:mcp-bridge|get_config');
+/// console.log(config.bind_address, config.max_result_bytes);
+/// ```
+#[command]
+pub async fn get_config<R: Runtime>(app: AppHandle<R>) -> Result<Value, String> {
+    app.try_state::<Config>()
+        .map(|config| config.redacted())
+        .ok_or_else(|| "Bridge configuration is not available".to_string())
+}
@@ -0,0 +1,31 @@
+//! Recent internal bridge logs.
+
+use crate::logging::recent_logs;
+use serde_json::Value;
+use tauri::command;
+
+/// Returns the most recent internal bridge log lines.
+///
+/// Lets clients inspect bridge diagnostics without access to the host's
+/// stdout or filesystem. Only the last few hundred lines are kept in memory.
+///
+/// # Arguments
+///
+/// * `limit` - Maximum number of lines to return (default: all retained lines)
+///
+/// # Returns
+///
+/// * `Ok(Value)` - Array of `{ level, scope, message, timestamp }`, oldest first
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const logs = await invoke('plugin:mcp-bridge|get_bridge_logs', { limit: 50 });
+/// ```
+#[command]
+pub async fn get_bridge_logs(limit: Option<usize>) -> Result<Value, String> {
+    serde_json::to_value(recent_logs(limit))
+        .map_err(|e| format!("Failed to serialize bridge logs: {e}"))
+}
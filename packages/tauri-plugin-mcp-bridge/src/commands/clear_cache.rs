@@ -0,0 +1,352 @@
+//! Clearing a webview's HTTP cache without touching storage or cookies.
+//!
+//! | Platform | API | `disk` | `memory` | `all` |
+//! |----------|-----|--------|----------|-------|
+//! | Windows | `ICoreWebView2Profile2::ClearBrowsingData` | Disk cache | Not supported | Disk cache |
+//! | macOS, iOS | `WKWebsiteDataStore.removeDataOfTypes` | Disk cache | Memory cache | Both |
+//! | Android | `WebView.clearCache` | Both | Memory cache | Both |
+//! | Linux | Not supported | - | - | - |
+//!
+//! WebView2 keeps no separately clearable memory cache, and Android can only
+//! drop its disk cache together with the memory cache, so the response lists
+//! what was actually cleared.
+
+use serde::{Deserialize, Serialize};
+use tauri::{command, Runtime, WebviewWindow};
+
+/// How long to wait for the webview to finish clearing.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+))]
+const NATIVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Which cache `clear_cache` empties.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheKind {
+    Disk,
+    Memory,
+    #[default]
+    All,
+}
+
+/// Native cache-clearing API of a webview engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CacheApi {
+    WebView2,
+    WebKit,
+    AndroidWebView,
+}
+
+impl CacheApi {
+    /// The API of the platform the plugin runs on.
+    fn current() -> Option<Self> {
+        if cfg!(target_os = "windows") {
+            Some(Self::WebView2)
+        } else if cfg!(any(target_os = "macos", target_os = "ios")) {
+            Some(Self::WebKit)
+        } else if cfg!(target_os = "android") {
+            Some(Self::AndroidWebView)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::WebView2 => "ICoreWebView2Profile2::ClearBrowsingData",
+            Self::WebKit => "WKWebsiteDataStore.removeDataOfTypes",
+            Self::AndroidWebView => "WebView.clearCache",
+        }
+    }
+
+    /// Native data types cleared for `kind`.
+    fn data_types(self, kind: CacheKind) -> Result<Vec<&'static str>, String> {
+        match (self, kind) {
+            (Self::WebView2, CacheKind::Memory) => Err(
+                "WebView2 can't clear its memory cache on its own; use kind \"disk\" or \"all\""
+                    .to_string(),
+            ),
+            (Self::WebView2, _) => Ok(vec!["DISK_CACHE"]),
+            (Self::WebKit, CacheKind::Disk) => Ok(vec!["WKWebsiteDataTypeDiskCache"]),
+            (Self::WebKit, CacheKind::Memory) => Ok(vec!["WKWebsiteDataTypeMemoryCache"]),
+            (Self::WebKit, CacheKind::All) => Ok(vec![
+                "WKWebsiteDataTypeDiskCache",
+                "WKWebsiteDataTypeMemoryCache",
+            ]),
+            (Self::AndroidWebView, CacheKind::Memory) => Ok(vec!["memory"]),
+            (Self::AndroidWebView, _) => Ok(vec!["memory", "disk"]),
+        }
+    }
+}
+
+/// Result of `clear_cache`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheClearReport {
+    pub window_label: String,
+    /// The requested kind
+    pub kind: CacheKind,
+    /// Native data types that were cleared
+    pub cleared: Vec<String>,
+    /// The native API used
+    pub api: String,
+}
+
+/// Clears a window's HTTP cache through the native webview, keeping
+/// storage and cookies.
+///
+/// Unlike `reset_window`, the page isn't reloaded; the next requests for
+/// cached assets go to the network.
+///
+/// # Arguments
+///
+/// * `window` - The window whose cache to clear
+/// * `kind` - `"disk"`, `"memory"` or `"all"` (default)
+///
+/// # Returns
+///
+/// * `Ok(CacheClearReport)` - `{ windowLabel, kind, cleared, api }`
+/// * `Err(String)` - If the platform can't clear that kind of cache, or the
+///   webview reported a failure
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { cleared } = await invoke('plugin:mcp-bridge|clear_cache', { kind: 'disk' });
+/// ```
+#[command]
+pub async fn clear_cache<R: Runtime>(
+    window: WebviewWindow<R>,
+    kind: Option<CacheKind>,
+) -> Result<CacheClearReport, String> {
+    let kind = kind.unwrap_or_default();
+    let api = CacheApi::current().ok_or_else(|| {
+        format!(
+            "Clearing the HTTP cache is not supported on {}",
+            std::env::consts::OS
+        )
+    })?;
+    let data_types = api.data_types(kind)?;
+
+    clear_native_cache(&window, data_types.clone())?;
+
+    Ok(CacheClearReport {
+        window_label: window.label().to_string(),
+        kind,
+        cleared: data_types.into_iter().map(str::to_string).collect(),
+        api: api.name().to_string(),
+    })
+}
+
+/// Waits for the webview thread to report the outcome of clearing.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+))]
+fn await_native(rx: std::sync::mpsc::Receiver<Result<(), String>>) -> Result<(), String> {
+    rx.recv_timeout(NATIVE_TIMEOUT)
+        .map_err(|_| "Timed out clearing the cache".to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn clear_native_cache<R: Runtime>(
+    window: &WebviewWindow<R>,
+    data_types: Vec<&'static str>,
+) -> Result<(), String> {
+    use webview2_com::ClearBrowsingDataCompletedHandler;
+    use webview2_com::Microsoft::Web::WebView2::Win32::{
+        ICoreWebView2Profile2, ICoreWebView2_13, COREWEBVIEW2_BROWSING_DATA_KINDS,
+        COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE,
+    };
+    use windows::core::Interface;
+
+    let kinds = data_types
+        .iter()
+        .map(|data_type| match *data_type {
+            "DISK_CACHE" => COREWEBVIEW2_BROWSING_DATA_KINDS_DISK_CACHE.0,
+            _ => 0,
+        })
+        .fold(0, |kinds, kind| kinds | kind);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            let controller = webview.controller();
+            let started = unsafe {
+                crate::webview2::core_webview2(&controller).and_then(|core_webview| {
+                    let profile: ICoreWebView2Profile2 = core_webview
+                        .cast::<ICoreWebView2_13>()
+                        .and_then(|webview| webview.Profile())
+                        .and_then(|profile| profile.cast())
+                        .map_err(|e| format!("WebView2 profile unavailable: {e}"))?;
+                    let tx = tx.clone();
+                    let handler =
+                        ClearBrowsingDataCompletedHandler::create(Box::new(move |result| {
+                            let _ = tx.send(
+                                result.map_err(|e| format!("Failed to clear the cache: {e}")),
+                            );
+                            Ok(())
+                        }));
+                    profile
+                        .ClearBrowsingData(COREWEBVIEW2_BROWSING_DATA_KINDS(kinds), &handler)
+                        .map_err(|e| format!("Failed to clear the cache: {e}"))
+                })
+            };
+            if let Err(e) = started {
+                let _ = tx.send(Err(e));
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn clear_native_cache<R: Runtime>(
+    window: &WebviewWindow<R>,
+    data_types: Vec<&'static str>,
+) -> Result<(), String> {
+    use block2::RcBlock;
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::{NSDate, NSSet, NSString};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            // Untyped, as WKWebView's typed bindings are macOS only
+            let wkwebview: *mut AnyObject = webview.inner().cast();
+            // The WKWebsiteDataType constants are equal to their names
+            let types = NSSet::from_vec(
+                data_types
+                    .iter()
+                    .map(|data_type| NSString::from_str(data_type))
+                    .collect(),
+            );
+            let done = RcBlock::new(move || {
+                let _ = tx.send(Ok(()));
+            });
+            unsafe {
+                let since = NSDate::distantPast();
+                let configuration: *mut AnyObject = objc2::msg_send![wkwebview, configuration];
+                let store: *mut AnyObject = objc2::msg_send![configuration, websiteDataStore];
+                let _: () = objc2::msg_send![
+                    store,
+                    removeDataOfTypes: &*types,
+                    modifiedSince: &*since,
+                    completionHandler: &*done
+                ];
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(target_os = "android")]
+fn clear_native_cache<R: Runtime>(
+    window: &WebviewWindow<R>,
+    data_types: Vec<&'static str>,
+) -> Result<(), String> {
+    use jni::objects::JValue;
+
+    let include_disk_files = data_types.contains(&"disk");
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            webview
+                .jni_handle()
+                .exec(move |env, _activity, webview_obj| {
+                    let result = env
+                        .call_method(
+                            webview_obj,
+                            "clearCache",
+                            "(Z)V",
+                            &[JValue::Bool(include_disk_files.into())],
+                        )
+                        .map(|_| ())
+                        .map_err(|e| format!("Failed to clear the cache: {e}"));
+                    let _ = tx.send(result);
+                });
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+)))]
+fn clear_native_cache<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _data_types: Vec<&'static str>,
+) -> Result<(), String> {
+    Err(format!(
+        "Clearing the HTTP cache is not supported on {}",
+        std::env::consts::OS
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinds_map_to_native_data_types() {
+        assert_eq!(
+            CacheApi::WebKit.data_types(CacheKind::All),
+            Ok(vec![
+                "WKWebsiteDataTypeDiskCache",
+                "WKWebsiteDataTypeMemoryCache"
+            ])
+        );
+        assert_eq!(
+            CacheApi::WebKit.data_types(CacheKind::Memory),
+            Ok(vec!["WKWebsiteDataTypeMemoryCache"])
+        );
+        assert_eq!(
+            CacheApi::WebView2.data_types(CacheKind::Disk),
+            Ok(vec!["DISK_CACHE"])
+        );
+        assert!(CacheApi::WebView2
+            .data_types(CacheKind::Memory)
+            .unwrap_err()
+            .contains("memory cache"));
+        // Android drops its disk cache only together with the memory cache
+        assert_eq!(
+            CacheApi::AndroidWebView.data_types(CacheKind::Disk),
+            Ok(vec!["memory", "disk"])
+        );
+    }
+
+    #[test]
+    fn test_kind_argument_parsing() {
+        let kind: CacheKind = serde_json::from_value(serde_json::json!("disk")).unwrap();
+        assert_eq!(kind, CacheKind::Disk);
+        assert_eq!(CacheKind::default(), CacheKind::All);
+        assert!(serde_json::from_value::<CacheKind>(serde_json::json!("cookies")).is_err());
+
+        let report = CacheClearReport {
+            window_label: "main".to_string(),
+            kind,
+            cleared: vec!["WKWebsiteDataTypeDiskCache".to_string()],
+            api: CacheApi::WebKit.name().to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "windowLabel": "main",
+                "kind": "disk",
+                "cleared": ["WKWebsiteDataTypeDiskCache"],
+                "api": "WKWebsiteDataStore.removeDataOfTypes"
+            })
+        );
+    }
+}
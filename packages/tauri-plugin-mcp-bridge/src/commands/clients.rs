@@ -0,0 +1,31 @@
+//! Listing of connected WebSocket clients.
+
+use crate::clients::{ClientInfo, ClientRegistry};
+use tauri::{command, State};
+
+/// Lists the clients connected to the bridge's WebSocket server.
+///
+/// Clients that sent `identify` are listed with their `name`, which makes it
+/// possible to tell agents apart when several drive the same app.
+///
+/// # Arguments
+///
+/// * `clients` - Shared client registry
+///
+/// # Returns
+///
+/// * `Ok(Vec<ClientInfo>)` - `{ id, address, name, connectedAt, requests }`
+///   per connection, oldest first
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const clients = await invoke('plugin:mcp-bridge|get_clients');
+/// console.log(clients.map((c) => c.name ?? `client-${c.id}`));
+/// ```
+#[command]
+pub async fn get_clients(clients: State<'_, ClientRegistry>) -> Result<Vec<ClientInfo>, String> {
+    Ok(clients.list())
+}
@@ -0,0 +1,128 @@
+//! Computed CSS styles for visual debugging.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::script_registry::js_string_literal;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Properties returned when the caller doesn't ask for specific ones.
+///
+/// Covers layout, visibility, colors and typography, which explain most
+/// "why does it look like that" questions without sending every property.
+pub const DEFAULT_STYLE_PROPERTIES: &[&str] = &[
+    "display",
+    "visibility",
+    "opacity",
+    "position",
+    "z-index",
+    "width",
+    "height",
+    "margin",
+    "padding",
+    "overflow",
+    "color",
+    "background-color",
+    "background-image",
+    "font-family",
+    "font-size",
+    "font-weight",
+    "line-height",
+    "pointer-events",
+    "transform",
+];
+
+/// Reads computed CSS properties of the first element matching a selector.
+///
+/// Values come from `getComputedStyle`, so they reflect stylesheets,
+/// inheritance and inline styles, resolved the way the engine renders them
+/// (e.g. colors as `rgb(...)`, lengths in `px`). Property names may be
+/// given in CSS (`background-color`) or camelCase (`backgroundColor`) form
+/// and are returned as given. Custom properties (`--accent`) are supported.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `properties` - Properties to read. Defaults to
+///   [`DEFAULT_STYLE_PROPERTIES`].
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - Object mapping each property to its computed value, or
+///   `null` for properties the engine doesn't know and unset custom
+///   properties
+/// * `Err(String)` - If no element matches the selector or the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const style = await invoke('plugin:mcp-bridge|get_computed_style', {
+///   selector: '#submit',
+///   properties: ['display', 'backgroundColor', '--accent']
+/// });
+/// ```
+#[command]
+pub async fn get_computed_style<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: String,
+    properties: Option<Vec<String>>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let script = computed_style_script(&selector, properties.as_deref());
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Builds the script reading `properties` of the element matched by
+/// `selector`.
+fn computed_style_script(selector: &str, properties: Option<&[String]>) -> String {
+    let selector = js_string_literal(selector);
+    let properties: Vec<String> = match properties {
+        Some(properties) => properties.iter().map(|p| js_string_literal(p)).collect(),
+        None => DEFAULT_STYLE_PROPERTIES
+            .iter()
+            .map(|p| js_string_literal(p))
+            .collect(),
+    };
+    let properties = properties.join(",");
+
+    format!(
+        r#"const selector = {selector};
+const properties = [{properties}];
+const el = document.querySelector(selector);
+if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+const style = window.getComputedStyle(el);
+const result = {{}};
+for (const name of properties) {{
+    const css = name.startsWith('--') ? name : name.replace(/[A-Z]/g, (c) => '-' + c.toLowerCase());
+    const value = style.getPropertyValue(css);
+    result[name] = value === '' ? null : value.trim();
+}}
+return result;"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_properties_are_used_when_omitted() {
+        let script = computed_style_script("#app", None);
+
+        assert!(script.starts_with(r##"const selector = "#app";"##));
+        assert!(script.contains(r#"const properties = ["display","visibility","#));
+        assert!(script.contains("No element matches selector: "));
+    }
+
+    #[test]
+    fn test_requested_properties_are_embedded_as_literals() {
+        let properties = vec!["backgroundColor".to_string(), "--x</script>".to_string()];
+        let script = computed_style_script("p", Some(&properties));
+
+        assert!(script.contains(r#"const properties = ["backgroundColor","--x<\/script>"];"#));
+        assert!(!script.contains("\"display\""));
+    }
+}
@@ -0,0 +1,78 @@
+//! Reading and recording a window's console history.
+
+use crate::console_history::{validate_level, ConsoleEntry, ConsoleHistory};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Returns the console entries a window's pages logged, oldest first.
+///
+/// `bridge.js` records every `console.log`, `info`, `warn` and `error` call
+/// from page load on, including calls made before any client connected. The
+/// last 500 entries per window are kept across reloads.
+///
+/// # Arguments
+///
+/// * `window` - The window whose history to read
+/// * `history` - Shared console history
+/// * `levels` - Only return these levels (`"log"`, `"info"`, `"warn"`,
+///   `"error"`). All levels when omitted.
+/// * `limit` - Return at most this many of the latest matching entries
+///
+/// # Returns
+///
+/// * `Ok(Vec<ConsoleEntry>)` - `{ level, message, timestamp }` per entry
+/// * `Err(String)` - If a level is unknown
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const errors = await invoke('plugin:mcp-bridge|get_console_history', {
+///   levels: ['error'],
+///   limit: 20
+/// });
+/// ```
+#[command]
+pub async fn get_console_history<R: Runtime>(
+    window: WebviewWindow<R>,
+    history: State<'_, ConsoleHistory>,
+    levels: Option<Vec<String>>,
+    limit: Option<usize>,
+) -> Result<Vec<ConsoleEntry>, String> {
+    if let Some(levels) = &levels {
+        levels.iter().try_for_each(|level| validate_level(level))?;
+    }
+    Ok(history.entries(window.label(), levels.as_deref(), limit))
+}
+
+/// Records a console call reported by `bridge.js`.
+///
+/// # Arguments
+///
+/// * `window` - The window that logged
+/// * `history` - Shared console history
+/// * `level` - `"log"`, `"info"`, `"warn"` or `"error"`
+/// * `message` - The stringified arguments
+/// * `timestamp` - When the call was made, in milliseconds since the epoch
+///
+/// # Returns
+///
+/// * `Ok(())` - The entry was recorded
+/// * `Err(String)` - If the level is unknown
+#[command]
+pub fn report_console_entry<R: Runtime>(
+    window: WebviewWindow<R>,
+    history: State<'_, ConsoleHistory>,
+    level: String,
+    message: String,
+    timestamp: u64,
+) -> Result<(), String> {
+    history.record(
+        window.label(),
+        ConsoleEntry {
+            level,
+            message,
+            timestamp,
+        },
+    )
+}
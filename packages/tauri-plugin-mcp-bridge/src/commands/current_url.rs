@@ -0,0 +1,55 @@
+//! The URL a window's page reports for itself.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::navigation::NavigationTracker;
+use serde_json::Value;
+use tauri::{command, Manager, Runtime, State, WebviewWindow};
+
+/// Returns the URL of a window as the page sees it (`location.href`).
+///
+/// Unlike the webview's native URL, this follows client-side route changes
+/// made with `history.pushState`, `history.replaceState` or the URL hash.
+/// When the URL changed since it was last known, a navigation event with
+/// `"clientSide": true` is broadcast, and `list_windows` reports the new URL
+/// until the window loads another page.
+///
+/// # Arguments
+///
+/// * `window` - The window to read
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(String)` - The page's `location.href`
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const url = await invoke('plugin:mcp-bridge|get_current_url');
+/// ```
+#[command]
+pub async fn get_current_url<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<String, String> {
+    let data = dom::evaluate(
+        window.clone(),
+        "return location.href".to_string(),
+        executor_state,
+    )
+    .await?;
+
+    let url = match data {
+        Value::String(url) => url,
+        other => return Err(format!("Unexpected location.href: {other}")),
+    };
+
+    if let Some(tracker) = window.try_state::<NavigationTracker>() {
+        tracker.url_observed(window.label(), &url);
+    }
+
+    Ok(url)
+}
@@ -0,0 +1,62 @@
+//! Shared plumbing for commands that inspect the DOM through `execute_js`.
+
+use crate::commands::execute_js::execute_js_in_held_window;
+use crate::commands::execute_js_all::script_outcome;
+use crate::commands::{execute_js, ScriptExecutor};
+use serde_json::Value;
+use tauri::{Runtime, State, WebviewWindow};
+
+/// Defines `selectorFor(node)`, which builds a CSS selector matching only
+/// `node`, or its parent element for text nodes. It walks up to the nearest
+/// ancestor with a unique id, using `:nth-of-type` among same-tag siblings.
+pub(crate) const SELECTOR_FOR_JS: &str = r#"const selectorFor = (node) => {
+    let el = node && node.nodeType === 1 ? node : node && node.parentElement;
+    const parts = [];
+    for (; el && el.nodeType === 1; el = el.parentElement) {
+        if (el.id && document.querySelectorAll('#' + CSS.escape(el.id)).length === 1) {
+            parts.unshift('#' + CSS.escape(el.id));
+            break;
+        }
+        const parent = el.parentElement;
+        if (!parent) { parts.unshift(el.localName); break; }
+        const siblings = Array.from(parent.children).filter((c) => c.localName === el.localName);
+        parts.unshift(siblings.length > 1
+            ? el.localName + ':nth-of-type(' + (siblings.indexOf(el) + 1) + ')'
+            : el.localName);
+    }
+    return parts.length ? parts.join(' > ') : undefined;
+};"#;
+
+/// Defines `describeElement(el)`, which summarizes an element as
+/// `{ tagName, id, classes, text, selector }`. `text` is the first 100
+/// characters of its whitespace-collapsed text. Needs [`SELECTOR_FOR_JS`].
+pub(crate) const DESCRIBE_ELEMENT_JS: &str = r#"const describeElement = (el) => ({
+    tagName: el.localName,
+    id: el.id || null,
+    classes: Array.from(el.classList),
+    text: (el.textContent || '').replace(/\s+/g, ' ').trim().slice(0, 100),
+    selector: selectorFor(el)
+});"#;
+
+/// Runs a generated inspection script and returns its data.
+///
+/// Errors thrown by the script (e.g. "No element matches selector") are
+/// returned as `Err` with the script's message.
+pub(crate) async fn evaluate<R: Runtime>(
+    window: WebviewWindow<R>,
+    script: String,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let result = execute_js(window, script, executor_state, None, Some(false), None).await?;
+    script_outcome(result).map(|data| data.unwrap_or(Value::Null))
+}
+
+/// Runs a generated inspection script like [`evaluate`], in a window whose
+/// [`WindowQueue`](crate::window_queue::WindowQueue) slot the caller holds.
+pub(crate) async fn evaluate_in_held_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    script: String,
+) -> Result<Value, String> {
+    let result = execute_js_in_held_window(window, script).await?;
+    script_outcome(result).map(|data| data.unwrap_or(Value::Null))
+}
@@ -0,0 +1,136 @@
+//! Dispatching arbitrary DOM events on elements.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::element_handles::ElementTarget;
+use crate::script_registry::js_string_literal;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Dispatches a DOM event on the first element matching a selector, or on
+/// an element handle.
+///
+/// With `detail`, a `CustomEvent` carrying it is dispatched. Otherwise the
+/// event is built with the constructor browsers use for that type, e.g.
+/// `MouseEvent` for `click`, `DragEvent` for `dragstart` or `KeyboardEvent`
+/// for `keydown`, falling back to a plain `Event` for unknown types. Events
+/// are cancelable, so the result tells whether a listener called
+/// `preventDefault()`.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `event_type` - Event type, e.g. `"dragstart"` or `"app:refresh"`
+/// * `bubbles` - Whether the event bubbles (default true)
+/// * `detail` - Payload for a `CustomEvent`, available as `event.detail`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The boolean returned by `dispatchEvent`: `false` when a
+///   listener canceled the event
+/// * `Err(String)` - If no element matches the selector or the handle is
+///   stale
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const notCanceled = await invoke('plugin:mcp-bridge|dispatch_dom_event', {
+///   selector: '#editor',
+///   eventType: 'app:refresh',
+///   detail: { source: 'agent' }
+/// });
+/// ```
+#[command]
+pub async fn dispatch_dom_event<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    event_type: String,
+    bubbles: Option<bool>,
+    detail: Option<Value>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    let script = dispatch_script(&target, &event_type, bubbles.unwrap_or(true), detail);
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Builds the script dispatching `event_type` on the element of `target`.
+fn dispatch_script(
+    target: &ElementTarget,
+    event_type: &str,
+    bubbles: bool,
+    detail: Option<Value>,
+) -> String {
+    let lookup = target.require_js();
+    let event_type = js_string_literal(event_type);
+    let detail = detail.map_or_else(|| "undefined".to_string(), |detail| detail.to_string());
+    format!(
+        r#"{lookup}
+const type = {event_type};
+const detail = {detail};
+const init = {{ bubbles: {bubbles}, cancelable: true, composed: true }};
+const typed = [
+    [/^(click|dblclick|auxclick|contextmenu|mouse(down|up|over|out|enter|leave|move))$/, 'MouseEvent'],
+    [/^(pointer|gotpointercapture|lostpointercapture)/, 'PointerEvent'],
+    [/^(drag|drop$)/, 'DragEvent'],
+    [/^key/, 'KeyboardEvent'],
+    [/^(focus|blur)/, 'FocusEvent'],
+    [/^(beforeinput|input)$/, 'InputEvent'],
+    [/^wheel$/, 'WheelEvent'],
+    [/^touch/, 'TouchEvent'],
+    [/^composition/, 'CompositionEvent'],
+    [/^animation/, 'AnimationEvent'],
+    [/^transition/, 'TransitionEvent']
+];
+let event;
+if (detail !== undefined) {{
+    event = new CustomEvent(type, Object.assign(init, {{ detail }}));
+}} else {{
+    const match = typed.find(([pattern]) => pattern.test(type));
+    const EventType = (match && typeof window[match[1]] === 'function') ? window[match[1]] : Event;
+    event = new EventType(type, init);
+}}
+return el.dispatchEvent(event);"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn selector(selector: &str) -> ElementTarget {
+        ElementTarget::Selector(selector.to_string())
+    }
+
+    #[test]
+    fn test_custom_event_carries_detail() {
+        let script = dispatch_script(
+            &selector("#editor"),
+            "app:refresh",
+            true,
+            Some(json!({ "n": 1 })),
+        );
+
+        assert!(script.starts_with(r##"const selector = "#editor";"##));
+        assert!(script.contains(r#"const type = "app:refresh";"#));
+        assert!(script.contains(r#"const detail = {"n":1};"#));
+        assert!(script.contains("new CustomEvent(type, "));
+        // The listener's verdict is the command's result
+        assert!(script.ends_with("return el.dispatchEvent(event);"));
+    }
+
+    #[test]
+    fn test_typed_event_without_detail() {
+        let script = dispatch_script(&selector("li"), "dragstart", false, None);
+
+        assert!(script.contains("const detail = undefined;"));
+        assert!(script.contains("bubbles: false, cancelable: true"));
+        assert!(script.contains("'DragEvent'"));
+    }
+}
@@ -0,0 +1,192 @@
+//! Simulated drag-and-drop between two elements.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::script_registry::js_string_literal;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Intermediate pointer positions between source and target in pointer mode.
+const POINTER_MOVE_STEPS: u32 = 10;
+
+/// Drags one element onto another.
+///
+/// The strategy is chosen with `mode`:
+///
+/// * `"html5"` - Dispatches the HTML5 sequence `dragstart`, `drag`,
+///   `dragenter` and `dragover`, then `drop` on the target if a `dragover`
+///   listener accepted it with `preventDefault()` (`dragleave` otherwise),
+///   and finally `dragend`. All events share one `DataTransfer`, so data set
+///   in `dragstart` can be read in `drop`.
+/// * `"pointer"` - For libraries built on mouse or pointer events: presses
+///   the pointer at the source's center, moves it to the target's center in
+///   small steps (with a frame between each) and releases it there.
+/// * `"auto"` (default) - HTML5 first, then pointer if the target didn't
+///   accept the drop.
+///
+/// Coordinates are the centers of the elements' bounding boxes, in CSS
+/// pixels relative to the viewport.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the elements
+/// * `source_selector` - CSS selector of the element to drag
+/// * `target_selector` - CSS selector of the element to drop onto
+/// * `mode` - `"auto"`, `"html5"` or `"pointer"`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ dropped, method, source: { x, y }, target: { x, y } }`.
+///   `dropped` is whether the target accepted the HTML5 drop, or, in pointer
+///   mode, whether the pointer was released over the target. `method` is
+///   the strategy that produced the result.
+/// * `Err(String)` - If the mode is unknown or a selector matches nothing
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { dropped } = await invoke('plugin:mcp-bridge|drag_and_drop', {
+///   sourceSelector: '#card-1',
+///   targetSelector: '#done-column'
+/// });
+/// ```
+#[command]
+pub async fn drag_and_drop<R: Runtime>(
+    window: WebviewWindow<R>,
+    source_selector: String,
+    target_selector: String,
+    mode: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let mode = mode.unwrap_or_else(|| "auto".to_string());
+    if !matches!(mode.as_str(), "auto" | "html5" | "pointer") {
+        return Err(format!(
+            "Unknown drag mode '{mode}' (expected \"auto\", \"html5\" or \"pointer\")"
+        ));
+    }
+
+    let script = drag_script(&source_selector, &target_selector, &mode);
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Builds the script dragging the element matched by `source_selector` onto
+/// the one matched by `target_selector`.
+fn drag_script(source_selector: &str, target_selector: &str, mode: &str) -> String {
+    let source_selector = js_string_literal(source_selector);
+    let target_selector = js_string_literal(target_selector);
+    let mode = js_string_literal(mode);
+    format!(
+        r#"const find = (selector) => {{
+    const el = document.querySelector(selector);
+    if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+    return el;
+}};
+const source = find({source_selector});
+const target = find({target_selector});
+const mode = {mode};
+const center = (el) => {{
+    const rect = el.getBoundingClientRect();
+    return {{ x: rect.left + rect.width / 2, y: rect.top + rect.height / 2 }};
+}};
+const from = center(source);
+const to = center(target);
+const init = (point, extra) => Object.assign({{
+    bubbles: true, cancelable: true, composed: true,
+    clientX: point.x, clientY: point.y, screenX: point.x, screenY: point.y
+}}, extra);
+
+const html5 = () => {{
+    let dataTransfer = null;
+    try {{ dataTransfer = new DataTransfer(); }} catch (e) {{}}
+    const fire = (el, type, point) => el.dispatchEvent(new DragEvent(type, init(point, {{ dataTransfer }})));
+    if (!fire(source, 'dragstart', from)) {{
+        return false;
+    }}
+    fire(source, 'drag', from);
+    fire(target, 'dragenter', to);
+    // A dropzone accepts by canceling dragover
+    const accepted = !fire(target, 'dragover', to);
+    fire(target, accepted ? 'drop' : 'dragleave', to);
+    fire(source, 'dragend', to);
+    return accepted;
+}};
+
+const pointer = async () => {{
+    const frame = () => new Promise((resolve) => setTimeout(resolve, 16));
+    const at = (point) => document.elementFromPoint(point.x, point.y) || document.body;
+    const fire = (el, pointerType, mouseType, point, buttons) => {{
+        const extra = {{ button: 0, buttons, pointerId: 1, pointerType: 'mouse', isPrimary: true }};
+        if (typeof PointerEvent === 'function') {{
+            el.dispatchEvent(new PointerEvent(pointerType, init(point, extra)));
+        }}
+        el.dispatchEvent(new MouseEvent(mouseType, init(point, extra)));
+    }};
+    fire(source, 'pointerdown', 'mousedown', from, 1);
+    for (let step = 1; step <= {POINTER_MOVE_STEPS}; step++) {{
+        await frame();
+        const point = {{
+            x: from.x + (to.x - from.x) * step / {POINTER_MOVE_STEPS},
+            y: from.y + (to.y - from.y) * step / {POINTER_MOVE_STEPS}
+        }};
+        fire(at(point), 'pointermove', 'mousemove', point, 1);
+    }}
+    await frame();
+    const released = at(to);
+    fire(released, 'pointerup', 'mouseup', to, 0);
+    return target === released || target.contains(released);
+}};
+
+let dropped = false;
+let method = mode;
+if (mode !== 'pointer') {{
+    dropped = html5();
+    method = 'html5';
+}}
+if (mode === 'pointer' || (mode === 'auto' && !dropped)) {{
+    dropped = await pointer();
+    method = 'pointer';
+}}
+return {{ dropped, method, source: from, target: to }};"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html5_sequence_drops_only_when_accepted() {
+        let script = drag_script("#card-1", "#done", "html5");
+
+        assert!(script.contains(r##"const source = find("#card-1");"##));
+        assert!(script.contains(r##"const target = find("#done");"##));
+        assert!(script.contains("dataTransfer = new DataTransfer()"));
+        let order: Vec<usize> = [
+            "'dragstart'",
+            "'drag'",
+            "'dragenter'",
+            "'dragover'",
+            "'drop'",
+            "'dragend'",
+        ]
+        .iter()
+        .map(|event| script.find(event).unwrap())
+        .collect();
+        assert!(order.windows(2).all(|pair| pair[0] < pair[1]), "{order:?}");
+        assert!(script.contains("const accepted = !fire(target, 'dragover', to);"));
+    }
+
+    #[test]
+    fn test_pointer_fallback_moves_between_centers() {
+        let script = drag_script("li:first-child", "ul.done", "auto");
+
+        // Pointer mode awaits frames, so the script runs through the async path
+        assert!(crate::commands::execute_js::is_async_script(&script));
+        assert!(script.contains("(mode === 'auto' && !dropped)"));
+        assert!(script.contains("fire(source, 'pointerdown', 'mousedown', from, 1);"));
+        assert!(script.contains("step <= 10;"));
+        assert!(script.contains("fire(released, 'pointerup', 'mouseup', to, 0);"));
+    }
+}
@@ -0,0 +1,92 @@
+//! Element geometry for coordinate-based automation.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::element_handles::ElementTarget;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Measures the box of the first element matching a CSS selector, or of an
+/// element handle.
+///
+/// Coordinates come from `getBoundingClientRect` and are in CSS pixels
+/// relative to the viewport. Multiply by `devicePixelRatio` (or use
+/// `devicePixels`) to map them onto screenshot pixels; add `scrollX` /
+/// `scrollY` for document coordinates.
+///
+/// Only available on Windows: the other webviews run the measuring script
+/// without handing its value back.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ x, y, width, height, top, right, bottom, left,
+///   devicePixelRatio, devicePixels: { x, y, width, height }, scrollX, scrollY,
+///   inViewport }`. `inViewport` is true when any part of the element is
+///   within the visible viewport.
+/// * `Err(String)` - If no element matches the selector, the handle is stale,
+///   the script fails or the platform isn't Windows
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const box = await invoke('plugin:mcp-bridge|get_element_box', {
+///   selector: '#submit'
+/// });
+/// ```
+#[command]
+pub async fn get_element_box<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    if cfg!(not(windows)) {
+        return Err("get_element_box is only available on Windows".to_string());
+    }
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, element_box_script(&target), executor_state).await
+}
+
+/// Builds the script measuring the element of `target`.
+fn element_box_script(target: &ElementTarget) -> String {
+    format!(
+        r#"{lookup}
+const r = el.getBoundingClientRect();
+const dpr = window.devicePixelRatio || 1;
+const vw = window.innerWidth || document.documentElement.clientWidth;
+const vh = window.innerHeight || document.documentElement.clientHeight;
+return {{
+    x: r.x, y: r.y, width: r.width, height: r.height,
+    top: r.top, right: r.right, bottom: r.bottom, left: r.left,
+    devicePixelRatio: dpr,
+    devicePixels: {{ x: r.x * dpr, y: r.y * dpr, width: r.width * dpr, height: r.height * dpr }},
+    scrollX: window.scrollX, scrollY: window.scrollY,
+    inViewport: r.width > 0 && r.height > 0 && r.bottom > 0 && r.right > 0 && r.top < vh && r.left < vw
+}};"#,
+        lookup = target.require_js()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selector_is_embedded_as_literal() {
+        let script = element_box_script(&ElementTarget::Selector(
+            r#"a[title="x"]</script>"#.to_string(),
+        ));
+
+        assert!(script.starts_with(r#"const selector = "a[title=\"x\"]<\/script>";"#));
+        assert!(script.contains("No element matches selector: "));
+        assert!(script.contains("inViewport:"));
+    }
+}
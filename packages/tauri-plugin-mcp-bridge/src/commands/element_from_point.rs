@@ -0,0 +1,85 @@
+//! Resolving viewport coordinates to the element under them.
+
+use crate::commands::dom::{self, DESCRIBE_ELEMENT_JS, SELECTOR_FOR_JS};
+use crate::commands::ScriptExecutor;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Returns the topmost element at a point of the viewport.
+///
+/// The inverse of `get_element_box`: coordinates are CSS pixels relative to
+/// the viewport, so divide screenshot pixels by `devicePixelRatio` first.
+/// Elements with `pointer-events: none` are skipped, as they are for clicks.
+///
+/// # Arguments
+///
+/// * `window` - The window to look in
+/// * `x` - Distance from the viewport's left edge, in CSS pixels
+/// * `y` - Distance from the viewport's top edge, in CSS pixels
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ tagName, id, classes, text, selector }`, where `text` is
+///   the first 100 characters of the element's whitespace-collapsed text and
+///   `selector` is a CSS selector matching only this element. `null` if the
+///   point is outside the viewport or nothing is there.
+/// * `Err(String)` - If a coordinate isn't a finite number or the script
+///   fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const hit = await invoke('plugin:mcp-bridge|element_from_point', { x: 120, y: 48 });
+/// // { tagName: 'button', id: 'save', classes: ['primary'], text: 'Save', selector: '#save' }
+/// ```
+#[command]
+pub async fn element_from_point<R: Runtime>(
+    window: WebviewWindow<R>,
+    x: f64,
+    y: f64,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let script = element_from_point_script(x, y)?;
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Builds the script describing the element at `(x, y)`.
+fn element_from_point_script(x: f64, y: f64) -> Result<String, String> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(format!(
+            "Coordinates must be finite numbers, got ({x}, {y})"
+        ));
+    }
+    Ok(format!(
+        r#"{SELECTOR_FOR_JS}
+{DESCRIBE_ELEMENT_JS}
+const el = document.elementFromPoint({x}, {y});
+return el ? describeElement(el) : null;"#
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coordinates_are_embedded() {
+        let script = element_from_point_script(120.5, 48.0).unwrap();
+
+        assert!(script.contains("document.elementFromPoint(120.5, 48);"));
+        assert!(script.contains("const selectorFor = (node) => {"));
+        assert!(script.ends_with("return el ? describeElement(el) : null;"));
+    }
+
+    #[test]
+    fn test_non_finite_coordinates_are_rejected() {
+        assert_eq!(
+            element_from_point_script(f64::NAN, 10.0).unwrap_err(),
+            "Coordinates must be finite numbers, got (NaN, 10)"
+        );
+        assert!(element_from_point_script(0.0, f64::INFINITY).is_err());
+    }
+}
@@ -1,13 +1,24 @@
 //! Event emission.
 
+use crate::config::Config;
+use crate::event_schema;
 use serde_json::Value;
-use tauri::{command, AppHandle, Emitter, Runtime};
+use tauri::{command, AppHandle, Emitter, Manager, Runtime};
 
 /// Emits a custom event to the application.
 ///
-/// Triggers a named event with a JSON payload that can be listened to by
-/// event handlers in the frontend or backend. Useful for testing event
-/// handling and triggering custom application behavior.
+/// Triggers a named event with a JSON payload, delivered like the app's own
+/// `app.emit` calls: to `listen` handlers in every webview and to Rust
+/// handlers registered with `app.listen` / `app.listen_any`. Rust handlers
+/// run before this returns. Useful for testing event handling, including
+/// backend handlers without a frontend.
+///
+/// There is no way to reach only backend handlers: Tauri delivers events to
+/// webview listeners registered without a target for every emit.
+///
+/// If a schema was registered for `event_name` with
+/// [`Builder::register_event_schema`](crate::Builder::register_event_schema),
+/// the payload is validated first and nothing is emitted on mismatch.
 ///
 /// # Arguments
 ///
@@ -18,7 +29,7 @@ use tauri::{command, AppHandle, Emitter, Runtime};
 /// # Returns
 ///
 /// * `Ok(String)` - Success message
-/// * `Err(String)` - Error message if emission fails
+/// * `Err(String)` - Error message if validation or emission fails
 ///
 /// # Examples
 ///
@@ -42,7 +53,65 @@ pub async fn emit_event<R: Runtime>(
     event_name: String,
     payload: Value,
 ) -> Result<String, String> {
+    if let Some(config) = app.try_state::<Config>() {
+        if let Some(schema) = config.event_schemas.get(&event_name) {
+            event_schema::validate(schema, &payload).map_err(|e| {
+                format!("Payload for event '{event_name}' does not match its schema: {e}")
+            })?;
+        }
+    }
+
     app.emit(&event_name, payload)
         .map_err(|e| format!("Failed to emit event: {e}"))?;
     Ok(format!("Event '{event_name}' emitted successfully"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::mpsc;
+    use tauri::Listener;
+
+    #[tokio::test]
+    async fn test_emit_event_reaches_backend_listeners() {
+        let app = tauri::test::mock_app();
+        let (tx, rx) = mpsc::channel();
+        app.handle().listen("backend-sync", move |event| {
+            tx.send(event.payload().to_string()).unwrap();
+        });
+
+        emit_event(
+            app.handle().clone(),
+            "backend-sync".to_string(),
+            json!({ "rows": 3 }),
+        )
+        .await
+        .unwrap();
+
+        let payload: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(payload["rows"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_payload_is_not_emitted() {
+        let app = tauri::test::mock_app();
+        let mut config = Config::default();
+        config.event_schemas.insert(
+            "backend-sync".to_string(),
+            json!({ "type": "object", "required": ["rows"] }),
+        );
+        app.manage(config);
+        let (tx, rx) = mpsc::channel::<()>();
+        app.handle().listen("backend-sync", move |_| {
+            tx.send(()).unwrap();
+        });
+
+        let err = emit_event(app.handle().clone(), "backend-sync".to_string(), json!({}))
+            .await
+            .unwrap_err();
+
+        assert!(err.starts_with("Payload for event 'backend-sync' does not match its schema"));
+        assert!(rx.try_recv().is_err());
+    }
+}
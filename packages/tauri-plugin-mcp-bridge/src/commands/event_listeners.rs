@@ -0,0 +1,83 @@
+//! Listing of the frontend's Tauri event listeners.
+
+use crate::event_listeners::{EventListenerCount, EventListenerRegistry};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Lists the event listeners registered by the app's frontend.
+///
+/// Covers listeners registered with `listen`/`once` from a webview after the
+/// bridge script loaded. Listeners registered in Rust are not included.
+///
+/// # Arguments
+///
+/// * `listeners` - Shared event listener registry
+/// * `window_label` - Only list listeners of this window. Lists all windows
+///   when omitted.
+///
+/// # Returns
+///
+/// * `Ok(Vec<EventListenerCount>)` - `{ event, count }` per event, sorted by
+///   event name
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const listeners = await invoke('plugin:mcp-bridge|list_event_listeners');
+/// // [{ event: 'download-progress', count: 2 }]
+/// ```
+#[command]
+pub async fn list_event_listeners(
+    listeners: State<'_, EventListenerRegistry>,
+    window_label: Option<String>,
+) -> Result<Vec<EventListenerCount>, String> {
+    Ok(listeners.counts(window_label.as_deref()))
+}
+
+/// Records a listener change reported by `bridge.js`.
+///
+/// Synchronous so that reports are applied in the order the page sent them,
+/// which keeps a `reset` on page load ahead of the new page's listeners.
+///
+/// # Arguments
+///
+/// * `window` - The window that registered or removed the listener
+/// * `listeners` - Shared event listener registry
+/// * `action` - `"listen"`, `"unlisten"` or `"reset"` (the page reloaded and
+///   all its listeners are gone)
+/// * `event` - Event name, for `listen` and `unlisten`
+/// * `id` - Listener id returned by `plugin:event|listen`
+///
+/// # Returns
+///
+/// * `Ok(())` - The change was recorded
+/// * `Err(String)` - If the action is unknown or `event`/`id` is missing
+#[command]
+pub fn track_event_listener<R: Runtime>(
+    window: WebviewWindow<R>,
+    listeners: State<'_, EventListenerRegistry>,
+    action: String,
+    event: Option<String>,
+    id: Option<u64>,
+) -> Result<(), String> {
+    let label = window.label();
+    let listener = || match (&event, id) {
+        (Some(event), Some(id)) => Ok((event.as_str(), id)),
+        _ => Err(format!("'{action}' requires event and id")),
+    };
+
+    match action.as_str() {
+        "listen" => {
+            let (event, id) = listener()?;
+            listeners.listen(label, event, id);
+        }
+        "unlisten" => {
+            let (event, id) = listener()?;
+            listeners.unlisten(label, event, id);
+        }
+        "reset" => listeners.clear_window(label),
+        other => return Err(format!("Unknown listener action: {other}")),
+    }
+    Ok(())
+}
@@ -4,18 +4,151 @@
 //! For async scripts, uses a polling mechanism with global variables.
 
 use crate::commands::ScriptExecutor;
-use crate::logging::{mcp_log_error, mcp_log_info};
+use crate::config::{Config, DEFAULT_MAX_RESULT_BYTES};
+#[cfg(windows)]
+use crate::logging::mcp_log_error;
+use crate::logging::mcp_log_info;
+use crate::window_queue::WindowQueue;
+use serde::Deserialize;
 use serde_json::Value;
 use std::sync::{Arc, Mutex};
-use tauri::{command, Runtime, State, WebviewWindow};
+use tauri::{command, Manager, Runtime, State, WebviewWindow};
 use tokio::sync::oneshot;
 
+/// JavaScript world an `execute_js` script runs in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum World {
+    /// The page's own world, sharing its globals
+    #[default]
+    Main,
+    /// A world that shares the DOM but not the page's JS globals
+    Isolated,
+}
+
 /// Executes JavaScript code in the webview context and returns the result.
+///
+/// Results larger than `max_result_bytes` (after `JSON.stringify`) are replaced
+/// with `{ success: false, error: "result_too_large", sizeBytes }`. When not
+/// given, the limit comes from the plugin [`Config`].
+///
+/// Binary results (`ArrayBuffer`, typed arrays, and `Blob`s from async
+/// scripts) are returned as `{ success: true, resultType: "binary", encoding:
+/// "base64", data }` instead of being mangled by `JSON.stringify`.
+///
+/// Execution can be cancelled with `abort_all`, which fails it with
+/// [`ABORTED`](crate::abort::ABORTED).
+///
+/// With `unwrap`, a successful result is returned as the bare value instead
+/// of `{ success: true, data }`; errors and binary results keep the envelope.
+/// When not given, it comes from the plugin [`Config`] (`unwrap_results`).
+/// Callers inside the plugin pass `Some(false)` to always get the envelope.
+///
+/// With `world: "isolated"`, the script shares the page's DOM but not its JS
+/// globals, so it can neither clobber nor be clobbered by page scripts:
+///
+/// | Engine | Isolation |
+/// |--------|-----------|
+/// | WebView2 (Windows) | DevTools `Page.createIsolatedWorld`, a separate JS context like an extension content script |
+/// | WKWebView, WebKitGTK, Android | Closure-scoped approximation, see [`isolate_script`] |
+///
+/// WebView2 falls back to the approximation when the DevTools call fails.
+/// Nothing set in an isolated world survives the call.
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const title = await invoke('plugin:mcp-bridge|execute_js', {
+///   script: 'document.title',
+///   unwrap: true
+/// });
+/// ```
 #[command]
 pub async fn execute_js<R: Runtime>(
     window: WebviewWindow<R>,
     script: String,
     _executor_state: State<'_, ScriptExecutor>,
+    max_result_bytes: Option<usize>,
+    unwrap: Option<bool>,
+    world: Option<World>,
+) -> Result<Value, String> {
+    let unwrap = unwrap.unwrap_or_else(|| {
+        window
+            .try_state::<Config>()
+            .is_some_and(|config| config.unwrap_results)
+    });
+    let result = crate::abort::cancellable(
+        &window,
+        execute_script(
+            window.clone(),
+            script,
+            max_result_bytes,
+            world.unwrap_or_default(),
+        ),
+    )
+    .await?;
+    Ok(unwrap_result(result, unwrap))
+}
+
+/// Replaces a successful `{ success: true, data }` result with its bare
+/// `data` when `unwrap` is set.
+///
+/// Failed results keep the envelope so errors stay recognizable, and binary
+/// results keep it because `data` alone doesn't say it is base64.
+pub fn unwrap_result(result: Value, unwrap: bool) -> Value {
+    let unwrappable = unwrap
+        && result.get("success").and_then(Value::as_bool) == Some(true)
+        && result.get("resultType").is_none();
+    if !unwrappable {
+        return result;
+    }
+    match result {
+        Value::Object(mut envelope) => envelope.remove("data").unwrap_or(Value::Null),
+        other => other,
+    }
+}
+
+/// Runs a script like [`execute_js`] in a window whose [`WindowQueue`] slot
+/// the caller already holds, returning the `{ success, data }` envelope.
+///
+/// The queue isn't reentrant, so commands that hold a window across several
+/// steps, such as screenshot captures, run their scripts through this rather
+/// than [`execute_js`], which would wait for the slot forever.
+pub(crate) async fn execute_js_in_held_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    script: String,
+) -> Result<Value, String> {
+    crate::abort::cancellable(
+        window,
+        run_script(window.clone(), script, None, World::Main),
+    )
+    .await
+}
+
+/// Runs a script for [`execute_js`].
+async fn execute_script<R: Runtime>(
+    window: WebviewWindow<R>,
+    script: String,
+    max_result_bytes: Option<usize>,
+    world: World,
+) -> Result<Value, String> {
+    // Hold the window for the whole execution when per-window serialization is on
+    let _window_guard = match window.try_state::<WindowQueue>() {
+        Some(queue) => queue.acquire(window.label()).await,
+        None => None,
+    };
+
+    run_script(window, script, max_result_bytes, world).await
+}
+
+/// Runs a script without taking the window's [`WindowQueue`] slot.
+async fn run_script<R: Runtime>(
+    window: WebviewWindow<R>,
+    script: String,
+    max_result_bytes: Option<usize>,
+    world: World,
 ) -> Result<Value, String> {
     mcp_log_info(
         "EXECUTE_JS",
@@ -25,43 +158,31 @@ pub async fn execute_js<R: Runtime>(
         ),
     );
 
-    // Detect if script needs async handling
-    let needs_async = script.contains("await ") || script.contains(".then(");
+    let config = window.try_state::<Config>();
+    let max_result_bytes = max_result_bytes.unwrap_or_else(|| {
+        config
+            .as_ref()
+            .map(|config| config.max_result_bytes)
+            .unwrap_or(DEFAULT_MAX_RESULT_BYTES)
+    });
+    let sandboxed = config.map(|config| config.js_sandbox).unwrap_or(false);
+    let size_guard = result_size_guard(max_result_bytes);
 
-    // Prepare the script with appropriate wrapping
-    let (wrapped_script, exec_id) = if needs_async {
-        // For async scripts, store result in a global variable and poll
-        let exec_id = uuid::Uuid::new_v4().to_string().replace("-", "");
-        let prepared = prepare_script(&script);
-        let script = format!(
-            r#"(async function() {{
-                try {{
-                    const __fn = async () => {{ {prepared} }};
-                    const __result = await __fn();
-                    window.__mcp_result_{exec_id} = JSON.stringify({{ success: true, data: __result !== undefined ? __result : null }});
-                }} catch (e) {{
-                    window.__mcp_result_{exec_id} = JSON.stringify({{ success: false, error: e.message || String(e) }});
-                }}
-            }})(); window.__mcp_result_{exec_id} || '{{"pending":true}}'"#
-        );
-        (script, Some(exec_id))
-    } else {
-        // For sync scripts, execute directly with a SYNC wrapper (not async IIFE)
-        // This ensures the result is returned directly, not as a Promise
-        let prepared = prepare_script(&script);
-        let script = format!(
-            r#"(function() {{
-                try {{
-                    const __fn = function() {{ {prepared} }};
-                    const __result = __fn();
-                    return JSON.stringify({{ success: true, data: __result !== undefined ? __result : null }});
-                }} catch (e) {{
-                    return JSON.stringify({{ success: false, error: e.message || String(e) }});
-                }}
-            }})()"#
-        );
-        (script, None)
-    };
+    #[cfg(windows)]
+    if world == World::Isolated {
+        let prepared = prepare_body(&script, sandboxed, false, true);
+        let expression = isolated_world_expression(&prepared, &size_guard);
+        match execute_in_isolated_world(&window, expression).await {
+            Ok(result) => return Ok(result),
+            Err(e) => mcp_log_error(
+                "EXECUTE_JS",
+                &format!("Isolated world unavailable, isolating with a closure instead: {e}"),
+            ),
+        }
+    }
+    let isolated = world == World::Isolated;
+
+    let (wrapped_script, exec_id) = wrap_script(&script, sandboxed, isolated, &size_guard);
 
     // Create channel for result
     let (tx, rx) = oneshot::channel::<String>();
@@ -81,18 +202,19 @@ pub async fn execute_js<R: Runtime>(
             let controller = webview.controller();
 
             unsafe {
-                let core_webview2: ICoreWebView2 = match controller.CoreWebView2() {
-                    Ok(wv) => wv,
-                    Err(e) => {
-                        if let Some(tx) = tx_clone.lock().unwrap().take() {
-                            let _ = tx.send(format!(
-                                r#"{{"success":false,"error":"CoreWebView2 failed: {}"}}"#,
-                                e
-                            ));
+                let core_webview2: ICoreWebView2 =
+                    match crate::webview2::core_webview2(&controller) {
+                        Ok(wv) => wv,
+                        Err(e) => {
+                            if let Some(tx) = tx_clone.lock().unwrap().take() {
+                                let _ = tx.send(
+                                    serde_json::json!({ "success": false, "error": e })
+                                        .to_string(),
+                                );
+                            }
+                            return;
                         }
-                        return;
-                    }
-                };
+                    };
 
                 // Use ExecuteScript with callback handler
                 let handler = ExecuteScriptCompletedHandler::create(Box::new(
@@ -185,7 +307,9 @@ pub async fn execute_js<R: Runtime>(
 
     #[cfg(not(windows))]
     {
-        // For non-Windows platforms, use eval fallback
+        // For non-Windows platforms, use eval fallback; async results are
+        // only polled for through WebView2
+        let _ = exec_id;
         if let Err(e) = window.eval(&wrapped_script) {
             return Ok(serde_json::json!({
                 "success": false,
@@ -227,6 +351,169 @@ pub async fn execute_js<R: Runtime>(
     }
 }
 
+/// Wraps a script the way [`execute_js`] evaluates it in the page.
+///
+/// # Returns
+///
+/// The wrapped script, and for async scripts the id of the global their
+/// result is stored under until it is polled
+pub(crate) fn wrap_script(
+    script: &str,
+    sandboxed: bool,
+    isolated: bool,
+    size_guard: &str,
+) -> (String, Option<String>) {
+    // Prepare the script with appropriate wrapping
+    if is_async_script(script) {
+        // For async scripts, store result in a global variable and poll
+        let exec_id = uuid::Uuid::new_v4().to_string().replace("-", "");
+        let prepared = prepare_body(script, sandboxed, isolated, true);
+        (
+            async_wrapper(&exec_id, &prepared, size_guard),
+            Some(exec_id),
+        )
+    } else {
+        // For sync scripts, execute directly with a SYNC wrapper (not async IIFE)
+        // This ensures the result is returned directly, not as a Promise
+        let prepared = prepare_body(script, sandboxed, isolated, false);
+        (sync_wrapper(&prepared, size_guard), None)
+    }
+}
+
+/// Wraps a prepared sync script so it evaluates to its serialized result.
+///
+/// The wrapper is a plain function call rather than an async IIFE, so the
+/// result is returned directly instead of as a promise.
+fn sync_wrapper(prepared: &str, size_guard: &str) -> String {
+    format!(
+        r#"(function() {{
+                {size_guard}
+                {RESULT_PAYLOAD_HELPER}
+                try {{
+                    const __fn = function() {{ {prepared} }};
+                    const __result = __fn();
+                    return __mcp_guard(JSON.stringify(__mcp_payload(__result)));
+                }} catch (e) {{
+                    return JSON.stringify({{ success: false, error: e.message || String(e) }});
+                }}
+            }})()"#
+    )
+}
+
+/// How long an async result stays in its `window.__mcp_result_<id>` global
+/// before the page deletes it, in case nothing reads it.
+///
+/// Longer than the 5 s polling waits for a result, so only results whose
+/// execution timed out or was cancelled are removed this way.
+const ASYNC_RESULT_TTL_MS: u64 = 10_000;
+
+/// Wraps a prepared async script so its result lands in
+/// `window.__mcp_result_<exec_id>` for polling.
+///
+/// The global is written in a `finally` block, so success and error paths
+/// alike schedule its deletion after [`ASYNC_RESULT_TTL_MS`]. Pollers delete
+/// it as soon as they read it; the timer covers executions nobody reads any
+/// more because they timed out or were cancelled. Results available right
+/// away are taken by the wrapper's last expression, which deletes them too.
+fn async_wrapper(exec_id: &str, prepared: &str, size_guard: &str) -> String {
+    format!(
+        r#"(async function() {{
+                {size_guard}
+                {RESULT_PAYLOAD_HELPER}
+                let __json;
+                try {{
+                    const __fn = async () => {{ {prepared} }};
+                    const __result = await __fn();
+                    const __value = typeof Blob !== 'undefined' && __result instanceof Blob ? await __result.arrayBuffer() : __result;
+                    __json = __mcp_guard(JSON.stringify(__mcp_payload(__value)));
+                }} catch (e) {{
+                    __json = JSON.stringify({{ success: false, error: e.message || String(e) }});
+                }} finally {{
+                    window.__mcp_result_{exec_id} = __json;
+                    setTimeout(function() {{ delete window.__mcp_result_{exec_id}; }}, {ASYNC_RESULT_TTL_MS});
+                }}
+            }})(); (function() {{
+                const __ready = window.__mcp_result_{exec_id};
+                if (__ready === undefined) {{ return '{{"pending":true}}'; }}
+                delete window.__mcp_result_{exec_id};
+                return __ready;
+            }})()"#
+    )
+}
+
+/// Wraps a prepared script as a DevTools `Runtime.evaluate` expression that
+/// resolves to the serialized result.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn isolated_world_expression(prepared: &str, size_guard: &str) -> String {
+    format!(
+        r#"(async function() {{
+                {size_guard}
+                {RESULT_PAYLOAD_HELPER}
+                try {{
+                    const __fn = async () => {{ {prepared} }};
+                    const __result = await __fn();
+                    const __value = typeof Blob !== 'undefined' && __result instanceof Blob ? await __result.arrayBuffer() : __result;
+                    return __mcp_guard(JSON.stringify(__mcp_payload(__value)));
+                }} catch (e) {{
+                    return JSON.stringify({{ success: false, error: e.message || String(e) }});
+                }}
+            }})()"#
+    )
+}
+
+/// Name of the DevTools isolated world `execute_js` creates.
+#[cfg(windows)]
+const ISOLATED_WORLD_NAME: &str = "mcp-bridge";
+
+/// Evaluates an [`isolated_world_expression`] in a new isolated world of
+/// the window's main frame.
+#[cfg(windows)]
+async fn execute_in_isolated_world<R: Runtime>(
+    window: &WebviewWindow<R>,
+    expression: String,
+) -> Result<Value, String> {
+    use crate::webview2::call_devtools_method;
+    use serde_json::json;
+
+    let tree = call_devtools_method(window, "Page.getFrameTree", json!({})).await?;
+    let frame_id = tree["frameTree"]["frame"]["id"]
+        .as_str()
+        .ok_or("Page.getFrameTree returned no main frame")?
+        .to_string();
+    let world = call_devtools_method(
+        window,
+        "Page.createIsolatedWorld",
+        json!({ "frameId": frame_id, "worldName": ISOLATED_WORLD_NAME }),
+    )
+    .await?;
+    let context_id = world["executionContextId"]
+        .as_i64()
+        .ok_or("Page.createIsolatedWorld returned no executionContextId")?;
+
+    let evaluated = call_devtools_method(
+        window,
+        "Runtime.evaluate",
+        json!({
+            "expression": expression,
+            "contextId": context_id,
+            "returnByValue": true,
+            "awaitPromise": true
+        }),
+    )
+    .await?;
+    if let Some(details) = evaluated.get("exceptionDetails") {
+        let error = details["exception"]["description"]
+            .as_str()
+            .or_else(|| details["text"].as_str())
+            .unwrap_or("Script failed");
+        return Ok(json!({ "success": false, "error": error }));
+    }
+    let serialized = evaluated["result"]["value"]
+        .as_str()
+        .ok_or("Runtime.evaluate returned no result")?;
+    serde_json::from_str(serialized).map_err(|e| format!("Failed to parse result: {e}"))
+}
+
 /// Poll for async script result
 #[cfg(windows)]
 async fn poll_async_result<R: Runtime>(
@@ -255,7 +542,7 @@ async fn poll_async_result<R: Runtime>(
             let controller = webview.controller();
 
             unsafe {
-                if let Ok(core_webview2) = controller.CoreWebView2() {
+                if let Ok(core_webview2) = crate::webview2::core_webview2(&controller) {
                     let handler = ExecuteScriptCompletedHandler::create(Box::new(
                         move |error_code, result| {
                             if let Some(tx) = tx_clone.lock().unwrap().take() {
@@ -319,8 +606,169 @@ async fn poll_async_result<R: Runtime>(
     }))
 }
 
+/// Builds the JS helper that replaces oversized serialized results.
+///
+/// Defines `__mcp_guard(json)`, which returns `json` unchanged when it fits in
+/// `max_bytes` (UTF-8) and a `result_too_large` error payload otherwise.
+pub(crate) fn result_size_guard(max_bytes: usize) -> String {
+    format!(
+        r#"const __mcp_guard = function(json) {{
+                    const size = typeof TextEncoder !== 'undefined' ? new TextEncoder().encode(json).length : json.length;
+                    return size > {max_bytes} ? JSON.stringify({{ success: false, error: 'result_too_large', sizeBytes: size }}) : json;
+                }};"#
+    )
+}
+
+/// JS helper building the success payload for a script result.
+///
+/// Defines `__mcp_payload(result)`. `ArrayBuffer`s and typed arrays become
+/// `{ resultType: "binary", encoding: "base64", data }`, since
+/// `JSON.stringify` would turn them into `{}` or an object keyed by index.
+/// A `Blob` can only be read asynchronously, so async wrappers read it first
+/// and sync scripts returning one fail with a hint to await it.
+const RESULT_PAYLOAD_HELPER: &str = r#"const __mcp_payload = function(result) {
+                    if (result instanceof ArrayBuffer || ArrayBuffer.isView(result)) {
+                        const bytes = result instanceof ArrayBuffer ? new Uint8Array(result) : new Uint8Array(result.buffer, result.byteOffset, result.byteLength);
+                        let binary = '';
+                        for (let i = 0; i < bytes.length; i += 0x8000) {
+                            binary += String.fromCharCode.apply(null, bytes.subarray(i, i + 0x8000));
+                        }
+                        return { success: true, resultType: 'binary', encoding: 'base64', data: btoa(binary) };
+                    }
+                    if (typeof Blob !== 'undefined' && result instanceof Blob) {
+                        throw new Error('Returning a Blob requires an async script, e.g. return await blob.arrayBuffer()');
+                    }
+                    return { success: true, data: result !== undefined ? result : null };
+                };"#;
+
+/// Decodes the bytes of a binary script result.
+///
+/// # Returns
+///
+/// * `None` - If `result` isn't a binary result
+/// * `Some(Ok(Vec<u8>))` - The decoded bytes
+/// * `Some(Err(String))` - If `data` isn't valid base64
+pub fn decode_binary_result(result: &Value) -> Option<Result<Vec<u8>, String>> {
+    use base64::Engine as _;
+
+    if result.get("resultType").and_then(Value::as_str) != Some("binary") {
+        return None;
+    }
+    let data = result
+        .get("data")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    Some(
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_err(|e| format!("Invalid binary result: {e}")),
+    )
+}
+
+/// Globals shadowed when `execute_js` runs in sandboxed mode.
+const SANDBOX_BLOCKED_GLOBALS: &[&str] = &[
+    "fetch",
+    "XMLHttpRequest",
+    "WebSocket",
+    "localStorage",
+    "sessionStorage",
+    "eval",
+    "Function",
+];
+
+/// Prepares the function body that runs the user script, sandboxing and
+/// isolating it if requested.
+fn prepare_body(script: &str, sandboxed: bool, isolated: bool, is_async: bool) -> String {
+    let mut prepared = prepare_script(script);
+    if sandboxed {
+        prepared = sandbox_script(&prepared, is_async);
+    }
+    if isolated {
+        prepared = isolate_script(&prepared, is_async);
+    }
+    prepared
+}
+
+/// Defines the scope object of [`isolate_script`].
+///
+/// Reads fall through to `window`, with its native functions bound to it so
+/// `setTimeout(...)` still works; writes and deletes only touch a private
+/// object. `window`, `self` and `globalThis` resolve to the scope itself.
+const ISOLATED_SCOPE_JS: &str = r#"const __mcp_world = (function() {
+                        const own = Object.create(null);
+                        const bound = new Map();
+                        const aliases = ['window', 'self', 'globalThis', 'frames'];
+                        const scope = new Proxy(own, {
+                            has: function(_, key) { return typeof key === 'string' && key !== '__mcp_world'; },
+                            get: function(_, key) {
+                                if (key === Symbol.unscopables) { return undefined; }
+                                if (key in own) { return own[key]; }
+                                if (aliases.includes(key)) { return scope; }
+                                const value = window[key];
+                                if (typeof value === 'function' && !('prototype' in value)) {
+                                    if (!bound.has(value)) { bound.set(value, value.bind(window)); }
+                                    return bound.get(value);
+                                }
+                                return value;
+                            },
+                            set: function(_, key, value) { own[key] = value; return true; },
+                            deleteProperty: function(_, key) { return delete own[key]; }
+                        });
+                        return scope;
+                    })();"#;
+
+/// Wraps a prepared script so it shares the DOM but not the page's globals.
+///
+/// A closure-scoped approximation of an isolated world: the script runs in
+/// a `with` block over the [`ISOLATED_SCOPE_JS`] proxy, so its globals,
+/// undeclared assignments and `window.x = ...` writes stay in a private
+/// object that is dropped after the call. Changes to the DOM are shared, and
+/// built-ins the page patched (`document.querySelector`, ...) are still the
+/// patched ones.
+fn isolate_script(prepared: &str, is_async: bool) -> String {
+    let async_kw = if is_async { "async " } else { "" };
+
+    format!(
+        r#"{ISOLATED_SCOPE_JS}
+                    with (__mcp_world) {{
+                        return ({async_kw}function() {{ {prepared} }}).call(__mcp_world);
+                    }}"#
+    )
+}
+
+/// Wraps a prepared script so the blocked globals resolve to throwing stand-ins.
+///
+/// The script runs inside a function nested in a scope that declares the
+/// blocked globals as `const`s, so unqualified references (`fetch(...)`,
+/// `localStorage.x`) hit a proxy that throws on any use. The script's own
+/// function is left alone: it may declare the same names or opt into strict
+/// mode, which parameters named `eval` would forbid. This is best-effort:
+/// qualified access such as `window.fetch` still reaches the original.
+fn sandbox_script(prepared: &str, is_async: bool) -> String {
+    let blocked = SANDBOX_BLOCKED_GLOBALS
+        .iter()
+        .map(|name| format!("{name} = __mcp_block('{name}')"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let async_kw = if is_async { "async " } else { "" };
+
+    format!(
+        r#"const __mcp_block = function(name) {{
+                        const deny = function() {{ throw new Error(name + ' is not available in sandboxed execute_js'); }};
+                        return new Proxy(deny, {{ get: deny, set: deny, has: deny, apply: deny, construct: deny }});
+                    }};
+                    const {blocked};
+                    return ({async_kw}function() {{ {prepared} }}).call(this);"#
+    )
+}
+
+/// Whether a script awaits anything and must run in an async wrapper.
+pub(crate) fn is_async_script(script: &str) -> bool {
+    script.contains("await ") || script.contains(".then(")
+}
+
 /// Prepare script by adding return statement if needed.
-fn prepare_script(script: &str) -> String {
+pub(crate) fn prepare_script(script: &str) -> String {
     let trimmed = script.trim();
     let needs_return = !trimmed.starts_with("return ");
 
@@ -359,3 +807,206 @@ fn prepare_script(script: &str) -> String {
         script.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node_eval::{node_available, run_in_page};
+
+    #[test]
+    fn test_async_wrapper_cleans_up_its_global_on_every_path() {
+        let script = async_wrapper("abc123", "return await load();", "");
+        let global = "window.__mcp_result_abc123";
+
+        // Success and error both reach the finally block that writes the
+        // result and schedules its deletion
+        let finally = script.find("} finally {").unwrap();
+        assert!(script.find("__json = __mcp_guard(").unwrap() < finally);
+        let error = script.find("__json = JSON.stringify({ success: false");
+        assert!(error.unwrap() < finally);
+        assert_eq!(script.matches(&format!("{global} = __json;")).count(), 1);
+        assert!(script[finally..].contains(&format!(
+            "setTimeout(function() {{ delete {global}; }}, {ASYNC_RESULT_TTL_MS});"
+        )));
+
+        // A result taken right away doesn't wait for the timer
+        let taken = script.rfind(&format!("const __ready = {global};")).unwrap();
+        assert!(script[taken..].contains(&format!("delete {global};")));
+        const _: () = assert!(ASYNC_RESULT_TTL_MS > 5_000);
+    }
+
+    #[test]
+    fn test_result_size_guard_replaces_oversized_results() {
+        if !node_available() {
+            return;
+        }
+        let run = |script: &str| {
+            let wrapped = sync_wrapper(
+                &prepare_body(script, false, false, false),
+                &result_size_guard(1024),
+            );
+            run_in_page(&wrapped).json()
+        };
+
+        // The size is that of the serialized `{"success":true,"data":...}`
+        assert_eq!(
+            run("'x'.repeat(2048)"),
+            serde_json::json!({ "success": false, "error": "result_too_large", "sizeBytes": 2074 })
+        );
+        // Sizes count UTF-8 bytes, not UTF-16 code units
+        assert_eq!(run("'é'.repeat(600)")["sizeBytes"], 1226);
+        // Exactly at the limit still fits
+        assert_eq!(
+            run("'x'.repeat(998)"),
+            serde_json::json!({ "success": true, "data": "x".repeat(998) })
+        );
+        assert_eq!(run("'x'.repeat(999)")["sizeBytes"], 1025);
+    }
+
+    #[test]
+    fn test_binary_result_round_trip() {
+        // What __mcp_payload produces for `new Uint8Array([0, 1, 127, 128, 255])`
+        let result = serde_json::json!({
+            "success": true,
+            "resultType": "binary",
+            "encoding": "base64",
+            "data": "AAF/gP8="
+        });
+
+        assert_eq!(
+            decode_binary_result(&result),
+            Some(Ok(vec![0, 1, 127, 128, 255]))
+        );
+        assert_eq!(
+            decode_binary_result(&serde_json::json!({ "success": true, "data": "AAF/gP8=" })),
+            None
+        );
+        assert!(RESULT_PAYLOAD_HELPER.contains("ArrayBuffer.isView(result)"));
+    }
+
+    #[test]
+    fn test_unwrap_returns_bare_objects_and_primitives() {
+        let object = serde_json::json!({ "success": true, "data": { "count": 3 } });
+        let number = serde_json::json!({ "success": true, "data": 42 });
+        let undefined = serde_json::json!({ "success": true });
+
+        assert_eq!(
+            unwrap_result(object.clone(), true),
+            serde_json::json!({ "count": 3 })
+        );
+        assert_eq!(unwrap_result(number.clone(), true), serde_json::json!(42));
+        assert_eq!(unwrap_result(undefined, true), Value::Null);
+
+        // The default keeps the envelope
+        assert_eq!(unwrap_result(object.clone(), false), object);
+        assert_eq!(unwrap_result(number.clone(), false), number);
+    }
+
+    #[test]
+    fn test_unwrap_keeps_envelope_for_errors_and_binary() {
+        let error = serde_json::json!({ "success": false, "error": "boom" });
+        let too_large =
+            serde_json::json!({ "success": false, "error": "result_too_large", "sizeBytes": 9 });
+        let binary = serde_json::json!({
+            "success": true,
+            "resultType": "binary",
+            "encoding": "base64",
+            "data": "AAF/gP8="
+        });
+
+        for result in [error, too_large, binary] {
+            assert_eq!(unwrap_result(result.clone(), true), result);
+        }
+    }
+
+    #[test]
+    fn test_prepare_body_unsandboxed_is_plain_script() {
+        assert_eq!(
+            prepare_body("document.title", false, false, false),
+            "return document.title"
+        );
+    }
+
+    /// Runs `script` like `execute_js` would, sandboxed if asked, and
+    /// returns its result envelope.
+    fn run_sandboxed(script: &str, sandboxed: bool) -> Value {
+        let prepared = prepare_body(script, sandboxed, false, is_async_script(script));
+        let guard = result_size_guard(DEFAULT_MAX_RESULT_BYTES);
+        run_in_page(&isolated_world_expression(&prepared, &guard)).json()
+    }
+
+    #[test]
+    fn test_sandbox_blocks_network_and_storage_globals() {
+        if !node_available() {
+            return;
+        }
+        for script in [
+            "fetch('/x')",
+            "new XMLHttpRequest()",
+            "new WebSocket('ws://localhost')",
+            "localStorage.getItem('key')",
+            "sessionStorage.getItem('key')",
+            "eval('1 + 1')",
+            "Function('return 1')()",
+            "await fetch('/x')",
+        ] {
+            let result = run_sandboxed(script, true);
+            assert_eq!(result["success"], false, "{script}");
+            let error = result["error"].as_str().unwrap();
+            assert!(
+                error.ends_with("is not available in sandboxed execute_js"),
+                "{script}: {error}"
+            );
+        }
+
+        // The stand-ins only exist in sandboxed mode
+        assert_eq!(
+            run_sandboxed("fetch('/x')", false),
+            serde_json::json!({ "success": true, "data": "fetch called" })
+        );
+    }
+
+    #[test]
+    fn test_sandbox_keeps_the_dom_and_valid_scripts_working() {
+        if !node_available() {
+            return;
+        }
+        for (script, expected) in [
+            ("document.querySelector('h1').textContent", "Hello"),
+            ("await Promise.resolve(document.title)", "Test page"),
+            // Strict scripts and scripts reusing a blocked name still parse
+            ("'use strict'; return document.title;", "Test page"),
+            ("const fetch = () => 'own'; return fetch();", "own"),
+            ("let Function = 'shadowed'; return Function;", "shadowed"),
+        ] {
+            assert_eq!(
+                run_sandboxed(script, true),
+                serde_json::json!({ "success": true, "data": expected }),
+                "{script}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_isolated_scripts_keep_globals_to_themselves() {
+        let body = prepare_body(
+            "window.leaked = 1; var declared = 2; undeclared = 3;",
+            false,
+            true,
+            false,
+        );
+
+        assert!(body.starts_with(ISOLATED_SCOPE_JS));
+        assert!(body.contains("with (__mcp_world) {"));
+        assert!(body.contains(
+            "return (function() { window.leaked = 1; var declared = 2; undeclared = 3; }).call(__mcp_world);"
+        ));
+        // Writes land in the scope's own object, never on window
+        assert!(ISOLATED_SCOPE_JS
+            .contains("set: function(_, key, value) { own[key] = value; return true; }"));
+        assert!(!ISOLATED_SCOPE_JS.contains("window[key] ="));
+
+        let sandboxed = prepare_body("await fetch('/x')", true, true, true);
+        assert!(sandboxed.contains("return (async function() { const __mcp_block"));
+    }
+}
@@ -0,0 +1,184 @@
+//! JavaScript execution across every open window.
+
+use crate::batch::BatchResult;
+use crate::commands::{check_window_access, execute_js, ScriptExecutor};
+use futures_util::future::join_all;
+use serde_json::Value;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager, Runtime, State, WebviewWindow};
+
+/// Default time to wait for each window before reporting a timeout.
+const DEFAULT_EXECUTE_ALL_TIMEOUT_MS: u64 = 5_000;
+
+/// Executes the same script in every open webview window.
+///
+/// The script runs in all windows concurrently through the regular
+/// [`execute_js`] path. Each window reports its own outcome, so a window that
+/// throws or times out does not hide the results of the others. Windows
+/// restricted by the window allowlist or denylist are skipped.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `script` - JavaScript code to execute in each window
+/// * `executor_state` - Shared script executor state
+/// * `timeout_ms` - Maximum time to wait per window in milliseconds (default 5000)
+///
+/// # Returns
+///
+/// * `Ok(Value)` - A batch result with one `{ label, success, data?, error? }`
+///   entry per window, ordered by label
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { results } = await invoke('plugin:mcp-bridge|execute_js_all', {
+///   script: 'window.__APP_READY__ === true',
+///   timeoutMs: 2000
+/// });
+/// ```
+#[command]
+pub async fn execute_js_all<R: Runtime>(
+    app: AppHandle<R>,
+    script: String,
+    executor_state: State<'_, ScriptExecutor>,
+    timeout_ms: Option<u64>,
+) -> Result<Value, String> {
+    let mut windows: Vec<(String, WebviewWindow<R>)> = app
+        .webview_windows()
+        .into_iter()
+        .filter(|(label, _)| check_window_access(&app, label).is_ok())
+        .collect();
+    windows.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_EXECUTE_ALL_TIMEOUT_MS));
+
+    let batch = run_in_each(windows, timeout, |window| {
+        execute_js(
+            window,
+            script.clone(),
+            executor_state.clone(),
+            None,
+            Some(false),
+            None,
+        )
+    })
+    .await;
+
+    Ok(batch.to_value())
+}
+
+/// Runs `execute` for every labeled target concurrently and collects the
+/// outcomes in input order.
+async fn run_in_each<T, F, Fut>(
+    targets: Vec<(String, T)>,
+    timeout: Duration,
+    execute: F,
+) -> BatchResult
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let (labels, runs): (Vec<String>, Vec<_>) = targets
+        .into_iter()
+        .map(|(label, target)| (label, tokio::time::timeout(timeout, execute(target))))
+        .unzip();
+
+    let outcomes = join_all(runs).await;
+
+    let mut batch = BatchResult::new();
+    for (label, outcome) in labels.iter().zip(outcomes) {
+        let outcome = match outcome {
+            Ok(Ok(result)) => script_outcome(result),
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err(format!(
+                "Script execution timed out after {}ms",
+                timeout.as_millis()
+            )),
+        };
+        batch.push_labeled(label, outcome);
+    }
+    batch
+}
+
+/// Converts an `execute_js` result (`{ success, data, error }`) into a batch outcome.
+pub(crate) fn script_outcome(result: Value) -> Result<Option<Value>, String> {
+    if result
+        .get("success")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true)
+    {
+        Ok(result.get("data").cloned())
+    } else {
+        Err(result
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("Script execution failed")
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_two_windows_return_different_values() {
+        let windows = vec![
+            (
+                "main".to_string(),
+                json!({ "success": true, "data": "dark" }),
+            ),
+            (
+                "settings".to_string(),
+                json!({ "success": true, "data": "light" }),
+            ),
+        ];
+
+        let batch = run_in_each(windows, Duration::from_secs(1), |result| async move {
+            Ok(result)
+        })
+        .await;
+
+        let value = batch.to_value();
+        assert_eq!(value["successCount"], 2);
+        assert_eq!(value["results"][0]["label"], "main");
+        assert_eq!(value["results"][0]["data"], "dark");
+        assert_eq!(value["results"][1]["label"], "settings");
+        assert_eq!(value["results"][1]["data"], "light");
+    }
+
+    #[tokio::test]
+    async fn test_failures_do_not_abort_other_windows() {
+        let windows = vec![
+            ("broken".to_string(), 0u64),
+            ("main".to_string(), 1),
+            ("slow".to_string(), 2),
+        ];
+
+        let batch = run_in_each(windows, Duration::from_millis(50), |kind| async move {
+            match kind {
+                0 => Ok(json!({ "success": false, "error": "x is not defined" })),
+                1 => Ok(json!({ "success": true, "data": 42 })),
+                _ => {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(json!({ "success": true, "data": null }))
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(batch.success_count, 1);
+        assert_eq!(batch.failure_count, 2);
+        assert_eq!(batch.results[0].error.as_deref(), Some("x is not defined"));
+        assert_eq!(batch.results[1].data, Some(json!(42)));
+        assert_eq!(
+            batch.results[2].error.as_deref(),
+            Some("Script execution timed out after 50ms")
+        );
+    }
+}
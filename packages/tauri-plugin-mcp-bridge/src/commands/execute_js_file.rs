@@ -0,0 +1,216 @@
+//! JavaScript execution from script files on the backend filesystem.
+
+use crate::commands::{execute_js, ScriptExecutor};
+use crate::config::Config;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tauri::{command, Manager, Runtime, State, WebviewWindow};
+
+/// Executes a JavaScript file from the backend filesystem in a webview.
+///
+/// The file is read on the Rust side and run through the regular
+/// [`execute_js`] pipeline. Its contents are treated as a function body: use
+/// `return` to produce a result and `await` for async work. The optional
+/// `args` value is available to the script as `args`.
+///
+/// Only `.js` files inside the directory configured with
+/// [`Builder::scripts_dir`](crate::Builder::scripts_dir) can be executed:
+/// relative paths are resolved against it and files outside it are
+/// rejected. Without a scripts directory the command is disabled, so the
+/// page can't use it to read arbitrary files.
+///
+/// # Arguments
+///
+/// * `window` - The Tauri window to execute the script in
+/// * `path` - Path to the script file
+/// * `args` - Optional JSON value exposed to the script as `args`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The script result, as returned by [`execute_js`]
+/// * `Err(String)` - If no scripts directory is configured, the path is
+///   rejected, the file cannot be read, or execution fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// // scripts/count-rows.js: return document.querySelectorAll(args.selector).length;
+/// const result = await invoke('plugin:mcp-bridge|execute_js_file', {
+///   path: 'count-rows.js',
+///   args: { selector: 'tr' }
+/// });
+/// ```
+#[command]
+pub async fn execute_js_file<R: Runtime>(
+    window: WebviewWindow<R>,
+    path: String,
+    args: Option<Value>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let scripts_dir = window
+        .try_state::<Config>()
+        .and_then(|config| config.scripts_dir.clone());
+
+    let script = load_script_file(&path, scripts_dir.as_deref(), args.as_ref())?;
+
+    execute_js(window, script, executor_state, None, Some(false), None).await
+}
+
+/// Reads a script file and prepends the `args` binding.
+///
+/// Fails when `scripts_dir` is `None`, since there is nothing to confine
+/// the path to.
+pub fn load_script_file(
+    path: &str,
+    scripts_dir: Option<&Path>,
+    args: Option<&Value>,
+) -> Result<String, String> {
+    let scripts_dir = scripts_dir.ok_or_else(|| {
+        "execute_js_file is disabled: configure a scripts directory with Builder::scripts_dir"
+            .to_string()
+    })?;
+    let resolved = resolve_script_path(path, scripts_dir)?;
+    let source = std::fs::read_to_string(&resolved)
+        .map_err(|e| format!("Failed to read script file {}: {e}", resolved.display()))?;
+    let args = args.cloned().unwrap_or(Value::Null);
+
+    Ok(format!("const args = {args};\n{source}"))
+}
+
+/// Resolves and validates a script path.
+///
+/// Symlinks and `..` components are resolved before the checks, so a path
+/// cannot escape the scripts directory or point at a non-`.js` file.
+pub fn resolve_script_path(path: &str, scripts_dir: &Path) -> Result<PathBuf, String> {
+    let dir = scripts_dir.canonicalize().map_err(|e| {
+        format!(
+            "Scripts directory {} is not accessible: {e}",
+            scripts_dir.display()
+        )
+    })?;
+    let resolved = dir
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Script file {path} not found: {e}"))?;
+    if !resolved.starts_with(&dir) {
+        return Err(format!(
+            "Script file {path} is outside the scripts directory {}",
+            dir.display()
+        ));
+    }
+
+    if resolved.extension().and_then(|ext| ext.to_str()) != Some("js") {
+        return Err(format!("Script file {path} must have a .js extension"));
+    }
+
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::execute_js::{result_size_guard, wrap_script};
+    use crate::config::DEFAULT_MAX_RESULT_BYTES;
+    use crate::node_eval::{node_available, run_in_page};
+    use std::fs;
+
+    fn temp_scripts_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mcp-scripts-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_load_known_file_with_args() {
+        let dir = temp_scripts_dir();
+        fs::write(dir.join("sum.js"), "return args.a + args.b;").unwrap();
+
+        let script = load_script_file(
+            "sum.js",
+            Some(&dir),
+            Some(&serde_json::json!({ "a": 2, "b": 3 })),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            script.unwrap(),
+            "const args = {\"a\":2,\"b\":3};\nreturn args.a + args.b;"
+        );
+    }
+
+    #[test]
+    fn test_loaded_script_runs_in_the_page() {
+        if !node_available() {
+            return;
+        }
+        let dir = temp_scripts_dir();
+        fs::create_dir_all(dir.join("pages")).unwrap();
+        fs::write(
+            dir.join("pages").join("heading.js"),
+            "const heading = document.querySelector(args.selector);\n\
+             return { text: heading.textContent, total: args.a + args.b };",
+        )
+        .unwrap();
+
+        let script = load_script_file(
+            "pages/heading.js",
+            Some(&dir),
+            Some(&serde_json::json!({ "selector": "h1", "a": 2, "b": 3 })),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (wrapped, exec_id) = wrap_script(
+            &script.unwrap(),
+            false,
+            false,
+            &result_size_guard(DEFAULT_MAX_RESULT_BYTES),
+        );
+        assert_eq!(exec_id, None);
+        assert_eq!(
+            run_in_page(&wrapped).json(),
+            serde_json::json!({ "success": true, "data": { "text": "Hello", "total": 5 } })
+        );
+    }
+
+    #[test]
+    fn test_paths_outside_scripts_dir_are_rejected() {
+        let root = temp_scripts_dir();
+        let scripts = root.join("scripts");
+        fs::create_dir_all(&scripts).unwrap();
+        fs::write(root.join("secret.js"), "return 1;").unwrap();
+
+        let err = resolve_script_path("../secret.js", &scripts).unwrap_err();
+        let absolute = root.join("secret.js");
+        let absolute_err = resolve_script_path(absolute.to_str().unwrap(), &scripts).unwrap_err();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(err.contains("outside the scripts directory"));
+        assert!(absolute_err.contains("outside the scripts directory"));
+    }
+
+    #[test]
+    fn test_scripts_need_a_scripts_dir() {
+        let dir = temp_scripts_dir();
+        let script = dir.join("anywhere.js");
+        fs::write(&script, "return document.cookie;").unwrap();
+
+        let err = load_script_file(script.to_str().unwrap(), None, None).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.starts_with("execute_js_file is disabled"));
+    }
+
+    #[test]
+    fn test_non_js_files_are_rejected() {
+        let dir = temp_scripts_dir();
+        fs::write(dir.join("notes.txt"), "hello").unwrap();
+
+        let err = resolve_script_path("notes.txt", &dir).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.contains("must have a .js extension"));
+    }
+}
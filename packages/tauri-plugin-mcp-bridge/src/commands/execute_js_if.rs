@@ -0,0 +1,108 @@
+//! Conditional JavaScript execution.
+
+use crate::commands::execute_js::{is_async_script, prepare_script};
+use crate::commands::{execute_js, ScriptExecutor};
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Runs a script only if a condition evaluates to `true`.
+///
+/// Both parts are sent to the webview as one script, so nothing can change
+/// the page between the check and the run, and only one round trip is
+/// made. `condition` and `script` follow the [`execute_js`] rules: a single
+/// expression is returned as is, statements need an explicit `return`, and
+/// `await` is allowed in either.
+///
+/// # Arguments
+///
+/// * `window` - The Tauri window to execute the script in
+/// * `condition` - JavaScript that must return a boolean
+/// * `script` - JavaScript to run when the condition is `true`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The [`execute_js`] result, whose `data` is
+///   `{ conditionMet, result? }`. `result` is omitted when the condition is
+///   false or the script returns nothing. A condition that returns anything
+///   other than a boolean fails the call.
+/// * `Err(String)` - If execution fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const result = await invoke('plugin:mcp-bridge|execute_js_if', {
+///   condition: '!window.__initialized',
+///   script: 'window.__initialized = true; return setup();'
+/// });
+/// ```
+#[command]
+pub async fn execute_js_if<R: Runtime>(
+    window: WebviewWindow<R>,
+    condition: String,
+    script: String,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    execute_js(
+        window,
+        conditional_script(&condition, &script),
+        executor_state,
+        None,
+        Some(false),
+        None,
+    )
+    .await
+}
+
+/// Combines `condition` and `script` into a single `execute_js` body.
+fn conditional_script(condition: &str, script: &str) -> String {
+    let (async_kw, await_kw) = if is_async_script(condition) || is_async_script(script) {
+        ("async ", "await ")
+    } else {
+        ("", "")
+    };
+    let condition = prepare_script(condition);
+    let script = prepare_script(script);
+
+    format!(
+        r#"const __mcp_condition = {await_kw}({async_kw}() => {{ {condition} }})();
+if (typeof __mcp_condition !== 'boolean') {{
+    throw new Error('condition must return a boolean, got ' + typeof __mcp_condition);
+}}
+if (!__mcp_condition) {{
+    return {{ conditionMet: false }};
+}}
+return {{ conditionMet: true, result: {await_kw}({async_kw}() => {{ {script} }})() }};"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_false_condition_skips_script() {
+        let body = conditional_script("window.__initialized === true", "window.ran = true");
+
+        assert!(body.starts_with(
+            "const __mcp_condition = (() => { return window.__initialized === true })();"
+        ));
+        // The early return comes before the script is ever invoked
+        let skip = body.find("return { conditionMet: false };").unwrap();
+        let run = body.find("window.ran = true").unwrap();
+        assert!(skip < run);
+        // The combined body is already a statement list, so execute_js keeps it as is
+        assert_eq!(prepare_script(&body), body);
+    }
+
+    #[test]
+    fn test_await_in_either_part_makes_both_async() {
+        let body = conditional_script("await isReady()", "document.title");
+
+        assert!(is_async_script(&body));
+        assert!(body.contains("await (async () => { return await isReady() })()"));
+        assert!(body.contains("result: await (async () => { return document.title })()"));
+    }
+}
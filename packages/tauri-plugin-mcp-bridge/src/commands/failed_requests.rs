@@ -0,0 +1,72 @@
+//! Reading and recording a window's failed resource loads.
+
+use crate::failed_requests::{FailedRequest, FailedRequests};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Returns the resource loads and requests that failed in a window, oldest
+/// first.
+///
+/// `bridge.js` records, from document start, elements (images, scripts,
+/// stylesheets, media, frames) that fire `error` while loading, and
+/// `fetch`/XHR calls that fail or get a 4xx/5xx response. The last 200
+/// failures per window are kept across reloads.
+///
+/// # Arguments
+///
+/// * `window` - The window whose failures to read
+/// * `requests` - Shared failed-request history
+/// * `since` - Only return failures at or after this time, in milliseconds
+///   since the epoch
+///
+/// # Returns
+///
+/// * `Vec<FailedRequest>` - `{ url, type, status, timestamp }` per failure.
+///   `status` is `null` for element loads and network errors.
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const failures = await invoke('plugin:mcp-bridge|get_failed_requests', {
+///   since: Date.now() - 60_000
+/// });
+/// ```
+#[command]
+pub async fn get_failed_requests<R: Runtime>(
+    window: WebviewWindow<R>,
+    requests: State<'_, FailedRequests>,
+    since: Option<u64>,
+) -> Result<Vec<FailedRequest>, String> {
+    Ok(requests.entries(window.label(), since))
+}
+
+/// Records a failed load reported by `bridge.js`.
+///
+/// # Arguments
+///
+/// * `window` - The window the load failed in
+/// * `requests` - Shared failed-request history
+/// * `url` - The URL that failed
+/// * `resource_type` - `"fetch"`, `"xhr"` or the element's tag
+/// * `status` - The HTTP status, when the page could see one
+/// * `timestamp` - When it failed, in milliseconds since the epoch
+#[command]
+pub fn report_failed_request<R: Runtime>(
+    window: WebviewWindow<R>,
+    requests: State<'_, FailedRequests>,
+    url: String,
+    resource_type: String,
+    status: Option<u16>,
+    timestamp: u64,
+) {
+    requests.record(
+        window.label(),
+        FailedRequest {
+            url,
+            kind: resource_type,
+            status,
+            timestamp,
+        },
+    );
+}
@@ -0,0 +1,145 @@
+//! Reading and moving keyboard focus.
+
+use crate::commands::dom::{self, DESCRIBE_ELEMENT_JS, SELECTOR_FOR_JS};
+use crate::commands::input_value::READ_VALUE_JS;
+use crate::commands::ScriptExecutor;
+use crate::element_handles::ElementTarget;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Defines `activeElement()`, which describes the focused element like
+/// `describeElement`, plus the `value` (and `checked`) of form fields, or
+/// returns `null` when focus is on the page itself. Focus inside open shadow
+/// roots is followed down to the focused element.
+const ACTIVE_ELEMENT_JS: &str = r#"const activeElement = () => {
+    let el = document.activeElement;
+    while (el && el.shadowRoot && el.shadowRoot.activeElement) {
+        el = el.shadowRoot.activeElement;
+    }
+    if (!el || el === document.body || el === document.documentElement) {
+        return null;
+    }
+    const info = describeElement(el);
+    if (el instanceof HTMLInputElement || el instanceof HTMLSelectElement
+        || el instanceof HTMLTextAreaElement || el.isContentEditable) {
+        const field = readValue(el, info.selector);
+        info.value = field.value;
+        if ('checked' in field) { info.checked = field.checked; }
+    }
+    return info;
+};"#;
+
+/// Returns the element that has keyboard focus.
+///
+/// # Arguments
+///
+/// * `window` - The window to inspect
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ tagName, id, classes, text, selector, value?, checked? }`,
+///   with `value` for form fields and contenteditable elements. `null` when
+///   nothing in the page has focus, i.e. focus is on `<body>`.
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const active = await invoke('plugin:mcp-bridge|get_active_element');
+/// // { tagName: 'input', id: 'email', classes: [], text: '', selector: '#email', value: 'a@b.c' }
+/// ```
+#[command]
+pub async fn get_active_element<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, active_element_script(), executor_state).await
+}
+
+/// Moves keyboard focus to an element.
+///
+/// Elements that can't take focus, such as disabled inputs or a `<div>`
+/// without `tabindex`, leave focus where it was and report `focused: false`.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ focused, activeElement }`, where `activeElement` is
+///   what [`get_active_element`] returns afterwards
+/// * `Err(String)` - If no element matches, the handle is stale or the
+///   script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { focused } = await invoke('plugin:mcp-bridge|focus_element', {
+///   selector: '#email'
+/// });
+/// ```
+#[command]
+pub async fn focus_element<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, focus_element_script(&target), executor_state).await
+}
+
+/// Shared prologue defining `activeElement`.
+fn active_element_js() -> String {
+    format!("{SELECTOR_FOR_JS}\n{DESCRIBE_ELEMENT_JS}\n{READ_VALUE_JS}\n{ACTIVE_ELEMENT_JS}\n")
+}
+
+/// Builds the script describing the focused element.
+fn active_element_script() -> String {
+    format!("{}return activeElement();", active_element_js())
+}
+
+/// Builds the script focusing the element of `target`.
+fn focus_element_script(target: &ElementTarget) -> String {
+    format!(
+        r#"{lookup}
+{prologue}el.focus();
+return {{ focused: el.matches(':focus') || el.contains(document.activeElement), activeElement: activeElement() }};"#,
+        lookup = target.require_js(),
+        prologue = active_element_js()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_active_element_reports_field_values() {
+        let script = active_element_script();
+
+        assert!(script.contains("const describeElement = (el) => ({"));
+        assert!(script.contains("const readValue = (el, selector) => {"));
+        assert!(script.contains("el === document.body"));
+        assert!(script.ends_with("return activeElement();"));
+    }
+
+    #[test]
+    fn test_focus_reads_focus_back() {
+        let script = focus_element_script(&ElementTarget::Selector("#email".to_string()));
+
+        assert!(script.starts_with(r##"const selector = "#email";"##));
+        let focus = script.find("el.focus();").unwrap();
+        let read = script.rfind("activeElement: activeElement()").unwrap();
+        assert!(focus < read);
+    }
+}
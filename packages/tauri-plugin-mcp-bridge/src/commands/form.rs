@@ -0,0 +1,190 @@
+//! Filling and reading whole forms in one round trip.
+//!
+//! Each field is written with the same logic as
+//! [`set_input_value`](super::set_input_value), so framework-controlled
+//! inputs, checkboxes, radios and selects behave the same as when set one by
+//! one.
+
+use crate::commands::input_value::{READ_VALUE_JS, WRITE_VALUE_JS};
+use crate::commands::{dom, ScriptExecutor};
+use crate::element_handles::ElementTarget;
+use serde_json::{Map, Value};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Sets several form fields at once.
+///
+/// Every field is attempted even if an earlier one fails, and each reports
+/// its own outcome. Fields are filled in the order of their selectors as
+/// sorted strings, so set fields that depend on each other (e.g. a select
+/// that reveals another input) with separate calls.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the form
+/// * `fields` - Map of CSS selector to value, with the values
+///   `set_input_value` accepts
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ results, successCount, failureCount }`, where `results`
+///   maps each selector to `{ success: true, value }`, `value` being the
+///   field as read back by `get_input_value`, or `{ success: false, error }`
+/// * `Err(String)` - If `fields` is empty or the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { failureCount } = await invoke('plugin:mcp-bridge|fill_form', {
+///   fields: { '#email': 'user@example.com', '#plan': 'pro', '#terms': true }
+/// });
+/// ```
+#[command]
+pub async fn fill_form<R: Runtime>(
+    window: WebviewWindow<R>,
+    fields: Map<String, Value>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let script = fill_form_script(fields)?;
+    dom::evaluate(window, script, executor_state).await
+}
+
+/// Reads the values of every named field in a form.
+///
+/// `form_selector` usually matches a `<form>`, whose `elements` are read,
+/// including fields associated with it through their `form` attribute. Any
+/// other element works too, reading the inputs, selects and textareas inside
+/// it. Buttons, file inputs and fields without a `name` are skipped.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the form
+/// * `form_selector` - CSS selector of the form or container
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - Object mapping field names to values. A radio group maps
+///   to the checked radio's value or `null`; a lone checkbox to its checked
+///   state; several checkboxes sharing a name, and `<select multiple>`, to
+///   arrays of the checked or selected values
+/// * `Err(String)` - If no element matches or the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const values = await invoke('plugin:mcp-bridge|read_form', {
+///   formSelector: '#signup'
+/// });
+/// // { email: 'user@example.com', plan: 'pro', terms: true }
+/// ```
+#[command]
+pub async fn read_form<R: Runtime>(
+    window: WebviewWindow<R>,
+    form_selector: String,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, read_form_script(form_selector), executor_state).await
+}
+
+/// Builds the script writing each of `fields`.
+fn fill_form_script(fields: Map<String, Value>) -> Result<String, String> {
+    if fields.is_empty() {
+        return Err("fields must name at least one field".to_string());
+    }
+    Ok(format!(
+        r#"{READ_VALUE_JS}
+{WRITE_VALUE_JS}
+const fields = {fields};
+const results = {{}};
+let successCount = 0;
+for (const [selector, value] of Object.entries(fields)) {{
+    try {{
+        const el = document.querySelector(selector);
+        if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+        writeValue(el, value, selector);
+        results[selector] = {{ success: true, value: readValue(el, selector) }};
+        successCount++;
+    }} catch (e) {{
+        results[selector] = {{ success: false, error: e && e.message ? e.message : String(e) }};
+    }}
+}}
+return {{ results, successCount, failureCount: Object.keys(fields).length - successCount }};"#,
+        fields = Value::Object(fields)
+    ))
+}
+
+/// Builds the script reading the named fields inside `form_selector`.
+fn read_form_script(form_selector: String) -> String {
+    format!(
+        r#"{lookup}
+const controls = el instanceof HTMLFormElement
+    ? Array.from(el.elements)
+    : Array.from(el.querySelectorAll('input, select, textarea'));
+const skipped = ['button', 'submit', 'reset', 'image', 'file'];
+const fields = controls.filter((c) => c.name
+    && (c instanceof HTMLInputElement || c instanceof HTMLSelectElement || c instanceof HTMLTextAreaElement)
+    && !skipped.includes(c.type));
+const checkboxes = {{}};
+fields.forEach((c) => {{
+    if (c.type === 'checkbox') {{ checkboxes[c.name] = (checkboxes[c.name] || 0) + 1; }}
+}});
+const values = {{}};
+for (const c of fields) {{
+    if (c.type === 'radio') {{
+        if (c.checked || !(c.name in values)) {{ values[c.name] = c.checked ? c.value : null; }}
+    }} else if (c.type === 'checkbox' && checkboxes[c.name] > 1) {{
+        values[c.name] = values[c.name] || [];
+        if (c.checked) {{ values[c.name].push(c.value); }}
+    }} else if (c.type === 'checkbox') {{
+        values[c.name] = c.checked;
+    }} else if (c instanceof HTMLSelectElement && c.multiple) {{
+        values[c.name] = Array.from(c.selectedOptions, (o) => o.value);
+    }} else {{
+        values[c.name] = c.value;
+    }}
+}}
+return values;"#,
+        lookup = ElementTarget::Selector(form_selector).require_js()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_fill_form_writes_each_field_with_set_input_logic() {
+        let fields = json!({ "#email": "user@example.com", "#terms": true });
+        let script = fill_form_script(fields.as_object().unwrap().clone()).unwrap();
+
+        assert!(script.contains("const writeValue = (el, value, selector) => {"));
+        assert!(script.contains(r##"const fields = {"#email":"user@example.com","#terms":true};"##));
+        // A failing field is reported without stopping the rest
+        let write = script.find("writeValue(el, value, selector);").unwrap();
+        let catch = script.find("} catch (e) {").unwrap();
+        assert!(script[..write].contains("try {"));
+        assert!(write < catch);
+
+        assert_eq!(
+            fill_form_script(Map::new()).unwrap_err(),
+            "fields must name at least one field"
+        );
+    }
+
+    #[test]
+    fn test_read_form_groups_radios_and_checkboxes() {
+        let script = read_form_script("#signup".to_string());
+
+        assert!(script.starts_with(r##"const selector = "#signup";"##));
+        assert!(script.contains("No element matches selector: "));
+        assert!(script.contains("values[c.name] = c.checked ? c.value : null;"));
+        assert!(script.contains("checkboxes[c.name] > 1"));
+        assert!(script.ends_with("return values;"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Diagnostics for globals the plugin leaves on a page.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Prefix of the per-execution globals scripts put on `window`, such as the
+/// `__mcp_result_<id>` slots of async `execute_js` calls.
+///
+/// The bridge's own `__MCP_*` globals live as long as the page and aren't
+/// counted.
+pub const INJECTED_GLOBAL_PREFIX: &str = "__mcp_";
+
+/// Per-execution globals currently on a page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InjectedGlobals {
+    pub count: usize,
+    /// The globals' names, sorted
+    pub names: Vec<String>,
+}
+
+/// Counts the per-execution globals left on a window's page.
+///
+/// Every `execute_js` wrapper removes its globals once its result is read,
+/// or shortly after when the execution timed out or was cancelled, so on an
+/// idle page the count returns to its baseline (normally 0). Meant for tests
+/// that check long sessions don't accumulate state.
+///
+/// # Arguments
+///
+/// * `window` - The window to inspect
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(InjectedGlobals)` - `{ count, names }`
+/// * `Err(String)` - If the page can't be evaluated
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { count } = await invoke('plugin:mcp-bridge|get_injected_globals_count');
+/// ```
+#[command]
+pub async fn get_injected_globals_count<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<InjectedGlobals, String> {
+    let data = dom::evaluate(window, injected_globals_script(), executor_state).await?;
+    serde_json::from_value(data).map_err(|e| format!("Unexpected globals report: {e}"))
+}
+
+/// Builds the script listing `window` properties with [`INJECTED_GLOBAL_PREFIX`].
+fn injected_globals_script() -> String {
+    format!(
+        r#"const names = Object.getOwnPropertyNames(window)
+    .filter((name) => name.startsWith('{INJECTED_GLOBAL_PREFIX}'))
+    .sort();
+return {{ count: names.length, names }};"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_counts_only_per_execution_globals() {
+        let script = injected_globals_script();
+
+        assert!(script.contains("name.startsWith('__mcp_')"));
+        // Sync, so the count itself doesn't leave a result slot behind
+        assert!(!crate::commands::execute_js::is_async_script(&script));
+        assert!(!script.contains("window.__mcp_"));
+    }
+
+    #[test]
+    fn test_report_parses_from_script_result() {
+        let report: InjectedGlobals = serde_json::from_value(serde_json::json!({
+            "count": 1,
+            "names": ["__mcp_result_abc"]
+        }))
+        .unwrap();
+
+        assert_eq!(report.count, 1);
+        assert_eq!(report.names, ["__mcp_result_abc"]);
+    }
+}
@@ -0,0 +1,216 @@
+//! Reading and writing form field values without keystroke simulation.
+//!
+//! Frameworks such as React track an input's last known value and ignore
+//! `input` events when the DOM value matches it. Assigning `el.value`
+//! directly updates that tracker too, so the change is swallowed. These
+//! commands write through the native prototype setter instead, then dispatch
+//! the `input` and `change` events a real edit would produce.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::element_handles::ElementTarget;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Defines `readValue(el, selector)`, which reads the current value of a form
+/// field as `{ type, value, checked? }`.
+pub(crate) const READ_VALUE_JS: &str = r#"const readValue = (el, selector) => {
+    if (el instanceof HTMLInputElement && (el.type === 'checkbox' || el.type === 'radio')) {
+        return { type: el.type, value: el.value, checked: el.checked };
+    }
+    if (el instanceof HTMLSelectElement) {
+        return el.multiple
+            ? { type: el.type, value: Array.from(el.selectedOptions, (o) => o.value) }
+            : { type: el.type, value: el.value };
+    }
+    if (el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement) {
+        return { type: el.type, value: el.value };
+    }
+    if (el.isContentEditable) {
+        return { type: 'contenteditable', value: el.innerText };
+    }
+    throw new Error('Element is not a form field or contenteditable: ' + selector);
+};"#;
+
+/// Defines `writeValue(el, value, selector)`, which sets a form field the way
+/// a user edit would. `selector` only labels errors.
+pub(crate) const WRITE_VALUE_JS: &str = r#"const writeValue = (el, value, selector) => {
+    const fire = (type) => el.dispatchEvent(new Event(type, { bubbles: true }));
+    const setNative = (proto, prop, v) => {
+        const descriptor = Object.getOwnPropertyDescriptor(proto, prop);
+        if (descriptor && descriptor.set) { descriptor.set.call(el, v); } else { el[prop] = v; }
+    };
+    if (el instanceof HTMLInputElement && (el.type === 'checkbox' || el.type === 'radio')) {
+        const checked = typeof value === 'string' ? value === 'true' : Boolean(value);
+        if (el.checked !== checked && (checked || el.type === 'checkbox')) { el.click(); }
+        if (el.checked !== checked) {
+            setNative(HTMLInputElement.prototype, 'checked', checked);
+            fire('input');
+            fire('change');
+        }
+    } else if (el instanceof HTMLSelectElement) {
+        const values = (Array.isArray(value) ? value : [value]).map(String);
+        if (!el.multiple && values.length !== 1) {
+            throw new Error('Expected a single value for <select>: ' + selector);
+        }
+        const options = Array.from(el.options);
+        const missing = values.filter((v) => !options.some((o) => o.value === v));
+        if (missing.length) { throw new Error('No option with value ' + JSON.stringify(missing[0]) + ' in ' + selector); }
+        options.forEach((o) => { o.selected = values.includes(o.value); });
+        fire('input');
+        fire('change');
+    } else if (el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement) {
+        const proto = el instanceof HTMLTextAreaElement ? HTMLTextAreaElement.prototype : HTMLInputElement.prototype;
+        el.focus();
+        setNative(proto, 'value', value == null ? '' : String(value));
+        fire('input');
+        fire('change');
+    } else if (el.isContentEditable) {
+        el.focus();
+        el.textContent = value == null ? '' : String(value);
+        el.dispatchEvent(new InputEvent('input', { bubbles: true, inputType: 'insertText' }));
+    } else {
+        throw new Error('Element is not a form field or contenteditable: ' + selector);
+    }
+};"#;
+
+/// Sets the value of a form field the way a user edit would.
+///
+/// Text inputs and textareas are written through the native value setter
+/// so framework-controlled inputs (React, Vue, Svelte) pick up the change.
+/// Other elements are handled specially:
+///
+/// * Checkboxes and radios - `value` is the desired checked state; the
+///   element is clicked when it needs to change
+/// * `<select>` - `value` is an option value, or an array of values for
+///   `<select multiple>`
+/// * `contenteditable` - `value` replaces the element's text
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `value` - The value to set
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The field's value after the update, as returned by
+///   [`get_input_value`]
+/// * `Err(String)` - If no element matches, the handle is stale, the element
+///   isn't a form field,
+///   or a `<select>` has no option with the requested value
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|set_input_value', {
+///   selector: '#email',
+///   value: 'user@example.com'
+/// });
+/// await invoke('plugin:mcp-bridge|set_input_value', {
+///   selector: '#subscribe',
+///   value: true
+/// });
+/// ```
+#[command]
+pub async fn set_input_value<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    value: Value,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, set_value_script(&target, &value), executor_state).await
+}
+
+/// Reads the value of a form field.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ type, value, checked? }`. `value` is an array for
+///   `<select multiple>`; `checked` is only present for checkboxes and radios.
+/// * `Err(String)` - If no element matches, the handle is stale or the
+///   element isn't a form field
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { value } = await invoke('plugin:mcp-bridge|get_input_value', {
+///   selector: '#email'
+/// });
+/// ```
+#[command]
+pub async fn get_input_value<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, get_value_script(&target), executor_state).await
+}
+
+/// Shared prologue: resolves `el` from `target` and defines `readValue`.
+fn find_element_js(target: &ElementTarget) -> String {
+    format!("{}\n{READ_VALUE_JS}\n", target.require_js())
+}
+
+/// Builds the script reading the field of `target`.
+fn get_value_script(target: &ElementTarget) -> String {
+    format!("{}return readValue(el, selector);", find_element_js(target))
+}
+
+/// Builds the script setting the field of `target` to `value`.
+fn set_value_script(target: &ElementTarget, value: &Value) -> String {
+    format!(
+        "{prologue}{WRITE_VALUE_JS}\nwriteValue(el, {value}, selector);\nreturn readValue(el, selector);",
+        prologue = find_element_js(target),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn selector(selector: &str) -> ElementTarget {
+        ElementTarget::Selector(selector.to_string())
+    }
+
+    #[test]
+    fn test_controlled_input_is_written_through_native_setter() {
+        let script = set_value_script(&selector("#name"), &json!("Ada"));
+
+        assert!(script.starts_with(r##"const selector = "#name";"##));
+        assert!(script.contains(r#"writeValue(el, "Ada", selector);"#));
+        // The prototype setter bypasses React's value tracker on the instance
+        assert!(script.contains("setNative(proto, 'value', "));
+        let set = script.find("setNative(proto, 'value', ").unwrap();
+        let input = script[set..].find("fire('input')").unwrap();
+        let change = script[set..].find("fire('change')").unwrap();
+        assert!(input < change);
+    }
+
+    #[test]
+    fn test_values_are_embedded_as_json() {
+        let script = set_value_script(&selector("select"), &json!(["a", "</script>"]));
+        assert!(script.contains(r#"writeValue(el, ["a","</script>"], selector);"#));
+
+        let script = get_value_script(&selector(r#"input[name="x"]"#));
+        assert!(script.starts_with(r#"const selector = "input[name=\"x\"]";"#));
+        assert!(script.ends_with("return readValue(el, selector);"));
+    }
+}
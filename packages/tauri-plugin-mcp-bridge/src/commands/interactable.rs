@@ -0,0 +1,110 @@
+//! Pre-interaction checks for elements.
+
+use crate::commands::{dom, ScriptExecutor};
+use crate::element_handles::ElementTarget;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Reports whether the first element matching a selector, or an element
+/// handle, can be clicked.
+///
+/// An element is clickable when it exists, is visible, is at least partly
+/// inside the viewport, is enabled, and is the topmost element at its center
+/// point (or contains it). Checking this first avoids blind clicks that land
+/// on an overlay, a disabled button or an element scrolled out of view.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ exists, visible, inViewport, enabled, occluded }`.
+///   `visible` is false for elements with no size, `display: none`,
+///   `visibility: hidden` or `opacity: 0` (on the element or an ancestor).
+///   `enabled` is false for disabled form controls (including those in a
+///   disabled `<fieldset>`) and `aria-disabled="true"`. `occluded` is true
+///   when `document.elementFromPoint` at the element's center returns an
+///   unrelated element, and `null` when the center is outside the viewport.
+///   When no element matches, or the handle is stale, `exists` is false and
+///   the other fields are false or `null`.
+/// * `Err(String)` - If the script fails, e.g. because the selector is invalid
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const state = await invoke('plugin:mcp-bridge|is_interactable', {
+///   selector: '#submit'
+/// });
+/// if (state.visible && state.enabled && !state.occluded) {
+///   // safe to click
+/// }
+/// ```
+#[command]
+pub async fn is_interactable<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, interactable_script(&target), executor_state).await
+}
+
+/// Builds the script checking the element of `target`.
+fn interactable_script(target: &ElementTarget) -> String {
+    format!(
+        r#"{lookup}
+if (!el) {{
+    return {{ exists: false, visible: false, inViewport: false, enabled: false, occluded: null }};
+}}
+const r = el.getBoundingClientRect();
+const vw = window.innerWidth || document.documentElement.clientWidth;
+const vh = window.innerHeight || document.documentElement.clientHeight;
+let visible = r.width > 0 && r.height > 0;
+for (let node = el; visible && node && node.nodeType === 1; node = node.parentElement) {{
+    const style = window.getComputedStyle(node);
+    if (style.display === 'none' || parseFloat(style.opacity) === 0) {{ visible = false; }}
+    if (node === el && style.visibility !== 'visible') {{ visible = false; }}
+}}
+const inViewport = r.width > 0 && r.height > 0 && r.bottom > 0 && r.right > 0 && r.top < vh && r.left < vw;
+const enabled = !el.matches(':disabled') && el.getAttribute('aria-disabled') !== 'true';
+const cx = r.left + r.width / 2;
+const cy = r.top + r.height / 2;
+let occluded = null;
+if (cx >= 0 && cy >= 0 && cx < vw && cy < vh) {{
+    const top = document.elementFromPoint(cx, cy);
+    occluded = !top || (top !== el && !el.contains(top));
+}}
+return {{ exists: true, visible, inViewport, enabled, occluded }};"#,
+        lookup = target.find_js()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlay_is_detected_with_element_from_point() {
+        let script = interactable_script(&ElementTarget::Selector("#behind-overlay".to_string()));
+
+        assert!(script.starts_with(r##"const selector = "#behind-overlay";"##));
+        // An overlay at the center point is neither the element nor inside it
+        assert!(script.contains("document.elementFromPoint(cx, cy)"));
+        assert!(script.contains("occluded = !top || (top !== el && !el.contains(top));"));
+    }
+
+    #[test]
+    fn test_missing_element_is_reported_not_thrown() {
+        let script = interactable_script(&ElementTarget::Handle("conn1.ab12cd.1".to_string()));
+
+        assert!(script.contains("exists: false"));
+        assert!(!script.contains("throw"));
+    }
+}
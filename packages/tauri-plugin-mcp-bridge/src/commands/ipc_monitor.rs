@@ -1,7 +1,8 @@
 //! IPC monitoring commands.
 
-use crate::monitor::{IPCEvent, IPCMonitorState};
-use tauri::{command, State};
+use crate::monitor::{current_timestamp, IPCEvent, IPCMonitorState};
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
 
 /// Starts IPC monitoring to capture Tauri command calls.
 ///
@@ -12,6 +13,7 @@ use tauri::{command, State};
 /// # Arguments
 ///
 /// * `monitor` - Shared state for the IPC monitor
+/// * `window_label` - Only capture calls from this window (default: all windows)
 ///
 /// # Returns
 ///
@@ -25,6 +27,9 @@ use tauri::{command, State};
 ///
 /// await invoke('plugin:mcp-bridge|start_ipc_monitor');
 /// // Now all IPC calls will be captured
+///
+/// // Or only calls made from the "settings" window
+/// await invoke('plugin:mcp-bridge|start_ipc_monitor', { windowLabel: 'settings' });
 /// ```
 ///
 /// # See Also
@@ -32,10 +37,17 @@ use tauri::{command, State};
 /// * [`stop_ipc_monitor`] - Stop monitoring
 /// * [`get_ipc_events`] - Retrieve captured events
 #[command]
-pub async fn start_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<String, String> {
+pub async fn start_ipc_monitor(
+    monitor: State<'_, IPCMonitorState>,
+    window_label: Option<String>,
+) -> Result<String, String> {
     let mut mon = monitor.lock().map_err(|e| format!("Lock error: {e}"))?;
-    mon.start();
-    Ok("IPC monitoring started".to_string())
+    let message = match &window_label {
+        Some(label) => format!("IPC monitoring started for window '{label}'"),
+        None => "IPC monitoring started".to_string(),
+    };
+    mon.start_scoped(window_label);
+    Ok(message)
 }
 
 /// Stops IPC monitoring.
@@ -73,6 +85,69 @@ pub async fn stop_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<Str
     Ok("IPC monitoring stopped".to_string())
 }
 
+/// Pauses IPC capture without clearing captured events.
+///
+/// Calls made while paused are not recorded. Unlike [`stop_ipc_monitor`]
+/// followed by [`start_ipc_monitor`], resuming keeps the events captured so
+/// far, so noisy operations can be left out of a recording.
+///
+/// # Arguments
+///
+/// * `monitor` - Shared state for the IPC monitor
+///
+/// # Returns
+///
+/// * `Ok(String)` - Success message
+/// * `Err(String)` - If monitoring isn't running or the monitor lock fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|pause_ipc_monitor');
+/// await warmUpCaches(); // not captured
+/// await invoke('plugin:mcp-bridge|resume_ipc_monitor');
+/// ```
+///
+/// # See Also
+///
+/// * [`resume_ipc_monitor`] - Resume capture
+#[command]
+pub async fn pause_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<String, String> {
+    let mut mon = monitor.lock().map_err(|e| format!("Lock error: {e}"))?;
+    mon.pause()?;
+    Ok("IPC monitoring paused".to_string())
+}
+
+/// Resumes IPC capture after [`pause_ipc_monitor`].
+///
+/// Events captured before the pause are kept, and a window scope set by
+/// [`start_ipc_monitor`] still applies.
+///
+/// # Arguments
+///
+/// * `monitor` - Shared state for the IPC monitor
+///
+/// # Returns
+///
+/// * `Ok(String)` - Success message
+/// * `Err(String)` - If monitoring isn't running or the monitor lock fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|resume_ipc_monitor');
+/// ```
+#[command]
+pub async fn resume_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<String, String> {
+    let mut mon = monitor.lock().map_err(|e| format!("Lock error: {e}"))?;
+    mon.resume()?;
+    Ok("IPC monitoring resumed".to_string())
+}
+
 /// Retrieves all captured IPC events.
 ///
 /// Returns a list of all IPC events captured since monitoring was started.
@@ -82,6 +157,7 @@ pub async fn stop_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<Str
 /// # Arguments
 ///
 /// * `monitor` - Shared state for the IPC monitor
+/// * `window_label` - Only return events from this window (default: all windows)
 ///
 /// # Returns
 ///
@@ -108,7 +184,82 @@ pub async fn stop_ipc_monitor(monitor: State<'_, IPCMonitorState>) -> Result<Str
 /// * [`start_ipc_monitor`] - Start monitoring
 /// * [`stop_ipc_monitor`] - Stop monitoring
 #[command]
-pub async fn get_ipc_events(monitor: State<'_, IPCMonitorState>) -> Result<Vec<IPCEvent>, String> {
-    let mon = monitor.lock().map_err(|e| format!("Lock error: {e}"))?;
-    Ok(mon.get_events())
+pub async fn get_ipc_events(
+    monitor: State<'_, IPCMonitorState>,
+    window_label: Option<String>,
+) -> Result<Vec<IPCEvent>, String> {
+    // Hold the lock only while taking the snapshot so live capture isn't blocked
+    let snapshot = monitor
+        .lock()
+        .map_err(|e| format!("Lock error: {e}"))?
+        .snapshot_for_window(window_label.as_deref());
+
+    Ok(snapshot.iter().map(|event| (**event).clone()).collect())
+}
+
+/// Records an IPC event reported by the app's frontend.
+///
+/// Lets client-side glue registered with
+/// [`Builder::additional_init_script`](crate::Builder::additional_init_script)
+/// feed the monitor with calls the plugin can't observe itself. The event is
+/// attributed to the calling window and is dropped unless monitoring is
+/// active for it.
+///
+/// # Arguments
+///
+/// * `window` - The window that reported the call
+/// * `monitor` - Shared state for the IPC monitor
+/// * `command` - Name of the invoked command
+/// * `args` - Arguments passed to the command
+/// * `result` - The command's result, if it succeeded
+/// * `error` - The error message, if it failed
+/// * `stack` - Stack trace of the error, if the frontend has one
+/// * `duration_ms` - How long the call took
+///
+/// # Returns
+///
+/// * `Ok(())` - The event was recorded or ignored
+/// * `Err(String)` - Error message if the monitor lock fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|report_ipc_event', {
+///   command: 'greet',
+///   args: { name: 'World' },
+///   result: 'Hello, World!',
+///   durationMs: 1.5
+/// });
+/// ```
+#[command]
+#[allow(clippy::too_many_arguments)]
+pub async fn report_ipc_event<R: Runtime>(
+    window: WebviewWindow<R>,
+    monitor: State<'_, IPCMonitorState>,
+    command: String,
+    args: Option<Value>,
+    result: Option<Value>,
+    error: Option<String>,
+    stack: Option<String>,
+    duration_ms: Option<f64>,
+) -> Result<(), String> {
+    let event = IPCEvent {
+        timestamp: current_timestamp(),
+        command,
+        args: args.unwrap_or(Value::Null),
+        result,
+        error,
+        duration_ms,
+        window_label: Some(window.label().to_string()),
+        source: None,
+        stack,
+    };
+
+    monitor
+        .lock()
+        .map_err(|e| format!("Lock error: {e}"))?
+        .add_event(event);
+    Ok(())
 }
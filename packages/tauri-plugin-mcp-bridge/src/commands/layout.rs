@@ -0,0 +1,437 @@
+//! Snapshotting and restoring the geometry and state of every window.
+
+use crate::batch::BatchResult;
+use crate::commands::check_window_access;
+use crate::commands::window_flags::{FlagControl, WindowFlag};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use tauri::{
+    command, AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, Url, WebviewUrl,
+    WebviewWindow, WebviewWindowBuilder,
+};
+
+/// Geometry and state of one window.
+///
+/// Coordinates and sizes are physical pixels: `x`/`y` locate the outer
+/// window, including its title bar, and `width`/`height` are the size of the
+/// content area, as `set_position` and `set_size` take them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowLayout {
+    pub label: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    #[serde(default)]
+    pub minimized: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    #[serde(default)]
+    pub always_on_top: bool,
+    /// URL to open when the window has to be created. Absolute URLs are
+    /// loaded as they are, anything else as a path of the app's frontend.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Windows whose geometry and state can be read and written.
+pub trait LayoutControl: FlagControl {
+    /// Returns the outer position and inner size.
+    fn geometry(&self) -> tauri::Result<(PhysicalPosition<i32>, PhysicalSize<u32>)>;
+
+    /// Moves the outer window to `position` and resizes the content to `size`.
+    fn set_geometry(
+        &self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+    ) -> tauri::Result<()>;
+
+    /// Returns whether the window is `(maximized, minimized)`.
+    fn window_state(&self) -> tauri::Result<(bool, bool)>;
+
+    /// Maximizes or restores the window.
+    fn set_maximized(&self, maximized: bool) -> tauri::Result<()>;
+
+    /// Minimizes or restores the window.
+    fn set_minimized(&self, minimized: bool) -> tauri::Result<()>;
+}
+
+impl<R: Runtime> LayoutControl for WebviewWindow<R> {
+    fn geometry(&self) -> tauri::Result<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+        Ok((self.outer_position()?, self.inner_size()?))
+    }
+
+    fn set_geometry(
+        &self,
+        position: PhysicalPosition<i32>,
+        size: PhysicalSize<u32>,
+    ) -> tauri::Result<()> {
+        self.set_position(position)?;
+        self.set_size(size)
+    }
+
+    fn window_state(&self) -> tauri::Result<(bool, bool)> {
+        Ok((self.is_maximized()?, self.is_minimized()?))
+    }
+
+    fn set_maximized(&self, maximized: bool) -> tauri::Result<()> {
+        if maximized {
+            self.maximize()
+        } else {
+            self.unmaximize()
+        }
+    }
+
+    fn set_minimized(&self, minimized: bool) -> tauri::Result<()> {
+        if minimized {
+            self.minimize()
+        } else {
+            self.unminimize()
+        }
+    }
+}
+
+/// Reads the layout of a window.
+pub fn read_layout(window: &impl LayoutControl, label: &str) -> Result<WindowLayout, String> {
+    let (position, size) = window
+        .geometry()
+        .map_err(|e| format!("Failed to get geometry: {e}"))?;
+    let (maximized, minimized) = window
+        .window_state()
+        .map_err(|e| format!("Failed to get window state: {e}"))?;
+    let flag = |flag: WindowFlag| {
+        window
+            .flag(flag)
+            .map_err(|e| format!("Failed to get window flags: {e}"))
+    };
+
+    Ok(WindowLayout {
+        label: label.to_string(),
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+        minimized,
+        fullscreen: flag(WindowFlag::Fullscreen)?,
+        always_on_top: flag(WindowFlag::AlwaysOnTop)?,
+        url: None,
+    })
+}
+
+/// Applies `layout` to a window and returns the layout it ends up with.
+///
+/// The window is first brought back to its normal state, as geometry set
+/// while maximized, minimized or fullscreen is ignored or lost on some
+/// platforms, then moved and resized, and finally put into the requested
+/// state.
+pub fn apply_layout(
+    window: &impl LayoutControl,
+    layout: &WindowLayout,
+) -> Result<WindowLayout, String> {
+    let set_flag = |flag: WindowFlag, value: bool| {
+        window
+            .set_flag(flag, value)
+            .map_err(|e| format!("Failed to set window flags: {e}"))
+    };
+    let set_state = |maximized: Option<bool>, minimized: Option<bool>| {
+        if let Some(maximized) = maximized {
+            window
+                .set_maximized(maximized)
+                .map_err(|e| format!("Failed to set maximized: {e}"))?;
+        }
+        if let Some(minimized) = minimized {
+            window
+                .set_minimized(minimized)
+                .map_err(|e| format!("Failed to set minimized: {e}"))?;
+        }
+        Ok::<(), String>(())
+    };
+
+    let current = read_layout(window, &layout.label)?;
+    if current.fullscreen {
+        set_flag(WindowFlag::Fullscreen, false)?;
+    }
+    set_state(
+        current.maximized.then_some(false),
+        current.minimized.then_some(false),
+    )?;
+
+    window
+        .set_geometry(
+            PhysicalPosition::new(layout.x, layout.y),
+            PhysicalSize::new(layout.width, layout.height),
+        )
+        .map_err(|e| format!("Failed to set geometry: {e}"))?;
+
+    set_state(
+        layout.maximized.then_some(true),
+        layout.minimized.then_some(true),
+    )?;
+    if layout.fullscreen {
+        set_flag(WindowFlag::Fullscreen, true)?;
+    }
+    set_flag(WindowFlag::AlwaysOnTop, layout.always_on_top)?;
+
+    read_layout(window, &layout.label)
+}
+
+/// Returns the layout of every window.
+///
+/// Windows excluded by the window allowlist or denylist are left out.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+///
+/// # Returns
+///
+/// * `Ok(Vec<WindowLayout>)` - One `{ label, x, y, width, height, maximized,
+///   minimized, fullscreen, alwaysOnTop, url }` per window, sorted by label
+/// * `Err(String)` - If a window's state can't be read
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const layout = await invoke('plugin:mcp-bridge|get_layout');
+/// // ... rearrange windows ...
+/// await invoke('plugin:mcp-bridge|set_layout', { layout });
+/// ```
+#[command]
+pub async fn get_layout<R: Runtime>(app: AppHandle<R>) -> Result<Vec<WindowLayout>, String> {
+    let mut windows: Vec<(String, WebviewWindow<R>)> = app.webview_windows().into_iter().collect();
+    windows.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut layouts = Vec::new();
+    for (label, window) in windows {
+        if check_window_access(&app, &label).is_err() {
+            continue;
+        }
+        let mut layout =
+            read_layout(&window, &label).map_err(|e| format!("Window '{label}': {e}"))?;
+        layout.url = window.url().ok().map(|url| url.to_string());
+        layouts.push(layout);
+    }
+    Ok(layouts)
+}
+
+/// Applies a layout from [`get_layout`] to the app's windows.
+///
+/// Existing windows are moved, resized and put into the recorded state.
+/// Windows that no longer exist are created from their `url`, and fail if
+/// they have none. Windows missing from `layout` are left alone.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `layout` - The windows to apply, as returned by `get_layout`
+///
+/// # Returns
+///
+/// * `Value` - `{ results, successCount, failureCount }`, with one result per
+///   entry of `layout` whose `data` is `{ created, layout }`, `layout` being
+///   the window's layout after applying
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { failureCount } = await invoke('plugin:mcp-bridge|set_layout', {
+///   layout: [{ label: 'main', x: 0, y: 0, width: 1280, height: 800 }]
+/// });
+/// ```
+#[command]
+pub async fn set_layout<R: Runtime>(app: AppHandle<R>, layout: Vec<WindowLayout>) -> Value {
+    let mut batch = BatchResult::new();
+
+    for entry in &layout {
+        let outcome = apply_window_layout(&app, entry).and_then(|(created, applied)| {
+            let applied = serde_json::to_value(applied)
+                .map_err(|e| format!("Failed to serialize layout: {e}"))?;
+            Ok(Some(
+                serde_json::json!({ "created": created, "layout": applied }),
+            ))
+        });
+        batch.push_labeled(&entry.label, outcome);
+    }
+
+    batch.to_value()
+}
+
+/// Applies one entry of a layout, creating its window if needed.
+fn apply_window_layout<R: Runtime>(
+    app: &AppHandle<R>,
+    layout: &WindowLayout,
+) -> Result<(bool, WindowLayout), String> {
+    check_window_access(app, &layout.label)?;
+
+    let (window, created) = match app.get_webview_window(&layout.label) {
+        Some(window) => (window, false),
+        None => {
+            let url = layout.url.as_deref().ok_or_else(|| {
+                format!(
+                    "Window '{}' does not exist and the layout has no url to create it from",
+                    layout.label
+                )
+            })?;
+            let window = WebviewWindowBuilder::new(app, &layout.label, webview_url(url))
+                .build()
+                .map_err(|e| format!("Failed to create window '{}': {e}", layout.label))?;
+            (window, true)
+        }
+    };
+
+    let applied = apply_layout(&window, layout)?;
+    Ok((created, applied))
+}
+
+/// Parses a layout URL: absolute URLs load as they are, anything else as a
+/// path of the app's frontend.
+fn webview_url(url: &str) -> WebviewUrl {
+    match Url::parse(url) {
+        Ok(url) => WebviewUrl::External(url),
+        Err(_) => WebviewUrl::App(PathBuf::from(url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// In-memory window recording its layout.
+    struct FakeWindow {
+        layout: RefCell<WindowLayout>,
+    }
+
+    impl FakeWindow {
+        fn new() -> Self {
+            Self {
+                layout: RefCell::new(WindowLayout {
+                    label: "main".to_string(),
+                    x: 100,
+                    y: 80,
+                    width: 1280,
+                    height: 800,
+                    maximized: false,
+                    minimized: false,
+                    fullscreen: false,
+                    always_on_top: false,
+                    url: None,
+                }),
+            }
+        }
+    }
+
+    impl FlagControl for FakeWindow {
+        fn flag(&self, flag: WindowFlag) -> tauri::Result<bool> {
+            let layout = self.layout.borrow();
+            Ok(match flag {
+                WindowFlag::AlwaysOnTop => layout.always_on_top,
+                WindowFlag::Fullscreen => layout.fullscreen,
+                WindowFlag::Decorations | WindowFlag::Resizable => true,
+            })
+        }
+
+        fn set_flag(&self, flag: WindowFlag, value: bool) -> tauri::Result<()> {
+            let mut layout = self.layout.borrow_mut();
+            match flag {
+                WindowFlag::AlwaysOnTop => layout.always_on_top = value,
+                WindowFlag::Fullscreen => layout.fullscreen = value,
+                WindowFlag::Decorations | WindowFlag::Resizable => {}
+            }
+            Ok(())
+        }
+    }
+
+    impl LayoutControl for FakeWindow {
+        fn geometry(&self) -> tauri::Result<(PhysicalPosition<i32>, PhysicalSize<u32>)> {
+            let layout = self.layout.borrow();
+            Ok((
+                PhysicalPosition::new(layout.x, layout.y),
+                PhysicalSize::new(layout.width, layout.height),
+            ))
+        }
+
+        fn set_geometry(
+            &self,
+            position: PhysicalPosition<i32>,
+            size: PhysicalSize<u32>,
+        ) -> tauri::Result<()> {
+            let mut layout = self.layout.borrow_mut();
+            // Like real windows, maximized ones ignore geometry changes
+            if !layout.maximized && !layout.fullscreen {
+                (layout.x, layout.y) = (position.x, position.y);
+                (layout.width, layout.height) = (size.width, size.height);
+            }
+            Ok(())
+        }
+
+        fn window_state(&self) -> tauri::Result<(bool, bool)> {
+            let layout = self.layout.borrow();
+            Ok((layout.maximized, layout.minimized))
+        }
+
+        fn set_maximized(&self, maximized: bool) -> tauri::Result<()> {
+            self.layout.borrow_mut().maximized = maximized;
+            Ok(())
+        }
+
+        fn set_minimized(&self, minimized: bool) -> tauri::Result<()> {
+            self.layout.borrow_mut().minimized = minimized;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_snapshot_restores_modified_layout() {
+        let window = FakeWindow::new();
+        let snapshot = read_layout(&window, "main").unwrap();
+
+        let modified = WindowLayout {
+            x: 0,
+            y: 0,
+            width: 640,
+            height: 480,
+            maximized: true,
+            always_on_top: true,
+            ..snapshot.clone()
+        };
+        assert_eq!(apply_layout(&window, &modified).unwrap(), modified);
+
+        // Restoring un-maximizes first so the old geometry sticks
+        assert_eq!(apply_layout(&window, &snapshot).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_layout_json_defaults_states() {
+        let layout: WindowLayout = serde_json::from_value(serde_json::json!({
+            "label": "settings",
+            "x": 10,
+            "y": 20,
+            "width": 400,
+            "height": 300,
+            "url": "settings.html"
+        }))
+        .unwrap();
+
+        assert!(!layout.maximized && !layout.minimized && !layout.fullscreen);
+        assert!(!layout.always_on_top);
+        assert!(matches!(webview_url("settings.html"), WebviewUrl::App(_)));
+        assert!(matches!(
+            webview_url("https://example.com/"),
+            WebviewUrl::External(_)
+        ));
+
+        let json = serde_json::to_value(FakeWindow::new().layout.into_inner()).unwrap();
+        assert_eq!(json["alwaysOnTop"], false);
+        assert!(json.get("url").is_none());
+    }
+}
@@ -1,8 +1,11 @@
 //! Window listing and discovery.
 
-use serde::Serialize;
+use crate::config::Config;
+use crate::navigation::NavigationTracker;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tauri::{command, AppHandle, Manager, Runtime};
+use std::collections::HashMap;
+use tauri::{command, AppHandle, Manager, Runtime, WebviewWindow};
 
 /// Information about a webview window.
 #[derive(Debug, Clone, Serialize)]
@@ -12,7 +15,8 @@ pub struct WindowInfo {
     pub label: String,
     /// The window title (if available)
     pub title: Option<String>,
-    /// The current URL loaded in the webview (if available)
+    /// The current URL loaded in the webview (if available), including
+    /// client-side route changes seen by `get_current_url`
     pub url: Option<String>,
     /// Whether this window currently has focus
     pub focused: bool,
@@ -22,10 +26,11 @@ pub struct WindowInfo {
     pub is_main: bool,
 }
 
-/// Lists all open webview windows in the application.
+/// Lists the open webview windows commands may target.
 ///
 /// Returns detailed information about each window including its label, title,
-/// URL, focus state, and visibility.
+/// URL, focus state, and visibility. Windows restricted by the window
+/// allowlist or denylist are left out.
 ///
 /// # Arguments
 ///
@@ -46,12 +51,12 @@ pub struct WindowInfo {
 /// ```
 #[command]
 pub async fn list_windows<R: Runtime>(app: AppHandle<R>) -> Result<Value, String> {
-    let windows = app.webview_windows();
+    let windows = accessible_windows(&app);
     let mut window_list: Vec<WindowInfo> = Vec::new();
 
     for (label, window) in windows.iter() {
         let title = window.title().ok();
-        let url = window.url().ok().map(|u| u.to_string());
+        let url = window_url(window);
         let focused = window.is_focused().unwrap_or(false);
         let visible = window.is_visible().unwrap_or(false);
         let is_main = label == "main";
@@ -80,6 +85,162 @@ pub async fn list_windows<R: Runtime>(app: AppHandle<R>) -> Result<Value, String
     serde_json::to_value(&window_list).map_err(|e| format!("Failed to serialize windows: {e}"))
 }
 
+/// Returns the label of the window that currently has focus.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+///
+/// # Returns
+///
+/// * `Ok(Some(String))` - Label of the focused window
+/// * `Ok(None)` - If no window of the app has focus
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const label = await invoke('plugin:mcp-bridge|get_active_window');
+/// ```
+#[command]
+pub async fn get_active_window<R: Runtime>(app: AppHandle<R>) -> Result<Option<String>, String> {
+    Ok(active_window_label(&app))
+}
+
+/// Brings a window to the front and gives it focus.
+///
+/// Minimized or hidden windows are restored first.
+///
+/// # Arguments
+///
+/// * `window` - The window to activate
+///
+/// # Returns
+///
+/// * `Ok(String)` - Label of the activated window
+/// * `Err(String)` - Error message if the window can't be focused
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|set_active_window', { windowLabel: 'settings' });
+/// ```
+#[command]
+pub async fn set_active_window<R: Runtime>(window: WebviewWindow<R>) -> Result<String, String> {
+    if window.is_minimized().unwrap_or(false) {
+        window
+            .unminimize()
+            .map_err(|e| format!("Failed to unminimize window: {e}"))?;
+    }
+    window
+        .show()
+        .map_err(|e| format!("Failed to show window: {e}"))?;
+    window
+        .set_focus()
+        .map_err(|e| format!("Failed to focus window: {e}"))?;
+    Ok(window.label().to_string())
+}
+
+/// Returns the best known URL of a window.
+///
+/// Prefers the URL last read by `get_current_url`, which follows client-side
+/// route changes, while the window is still on the page it was read on.
+fn window_url<R: Runtime>(window: &WebviewWindow<R>) -> Option<String> {
+    let native = window.url().ok().map(|u| u.to_string());
+    match window.try_state::<NavigationTracker>() {
+        Some(tracker) => tracker.current_url(window.label(), native),
+        None => native,
+    }
+}
+
+/// Label of the focused window, if any.
+fn active_window_label<R: Runtime>(app: &AppHandle<R>) -> Option<String> {
+    focused_label(
+        app.webview_windows()
+            .iter()
+            .map(|(label, window)| (label.as_str(), window.is_focused().unwrap_or(false))),
+    )
+}
+
+/// Picks the focused window from `(label, focused)` pairs.
+///
+/// Ties (which the OS shouldn't produce) go to the lowest label so the
+/// choice doesn't depend on map iteration order.
+fn focused_label<'a>(windows: impl IntoIterator<Item = (&'a str, bool)>) -> Option<String> {
+    windows
+        .into_iter()
+        .filter(|(_, focused)| *focused)
+        .map(|(label, _)| label)
+        .min()
+        .map(str::to_string)
+}
+
+/// Label used when a command doesn't name a window.
+///
+/// This is `"main"`, or the focused window when
+/// [`Builder::default_to_active_window`](crate::Builder::default_to_active_window)
+/// is set and some window has focus.
+fn default_label(default_to_active: bool, active: impl FnOnce() -> Option<String>) -> String {
+    default_to_active
+        .then(active)
+        .flatten()
+        .unwrap_or_else(|| "main".to_string())
+}
+
+/// Applies the configured default-window strategy to an optional label.
+fn target_label<R: Runtime>(app: &AppHandle<R>, label: Option<String>) -> String {
+    label.unwrap_or_else(|| {
+        let default_to_active = app
+            .try_state::<crate::config::Config>()
+            .map(|config| config.default_to_active_window)
+            .unwrap_or(false);
+        default_label(default_to_active, || active_window_label(app))
+    })
+}
+
+/// Error for a window restricted by the window allowlist or denylist.
+fn access_denied(label: &str) -> String {
+    format!("access_denied: Window '{label}' is restricted by the bridge configuration")
+}
+
+/// Checks `label` against the configured window allowlist and denylist.
+fn window_access(config: Option<&Config>, label: &str) -> Result<(), String> {
+    if config.map_or(true, |config| config.allows_window(label)) {
+        Ok(())
+    } else {
+        Err(access_denied(label))
+    }
+}
+
+/// Checks whether commands may target the window with `label`.
+///
+/// See [`Builder::window_allowlist`](crate::Builder::window_allowlist) and
+/// [`Builder::window_denylist`](crate::Builder::window_denylist).
+///
+/// # Returns
+///
+/// * `Ok(())` - If the window may be targeted
+/// * `Err(String)` - An `access_denied` error otherwise
+pub fn check_window_access<R: Runtime, M: Manager<R>>(
+    manager: &M,
+    label: &str,
+) -> Result<(), String> {
+    let config = manager.try_state::<Config>();
+    window_access(config.as_deref(), label)
+}
+
+/// Returns the open webview windows commands may target, leaving out those
+/// [`check_window_access`] rejects.
+pub fn accessible_windows<R: Runtime>(app: &AppHandle<R>) -> HashMap<String, WebviewWindow<R>> {
+    app.webview_windows()
+        .into_iter()
+        .filter(|(label, _)| check_window_access(app, label).is_ok())
+        .collect()
+}
+
 /// Context about which window was used for an operation.
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -98,18 +259,19 @@ pub struct ResolvedWindow<R: Runtime> {
     pub context: WindowContext,
 }
 
-/// Resolves a window by label, defaulting to "main" if not specified.
-/// Returns both the window and context about the resolution.
+/// Resolves a window by label, defaulting to "main" (or the active window,
+/// see [`Builder::default_to_active_window`](crate::Builder::default_to_active_window))
+/// if not specified. Returns both the window and context about the resolution.
 ///
 /// # Arguments
 ///
 /// * `app` - The Tauri application handle
-/// * `label` - Optional window label (defaults to "main")
+/// * `label` - Optional window label (defaults as described above)
 ///
 /// # Returns
 ///
 /// * `Ok(ResolvedWindow)` - The resolved window with context
-/// * `Err(String)` - Error if window not found
+/// * `Err(String)` - Error if window not found or access to it is denied
 pub fn resolve_window_with_context<R: Runtime>(
     app: &AppHandle<R>,
     label: Option<String>,
@@ -117,7 +279,8 @@ pub fn resolve_window_with_context<R: Runtime>(
     let windows = app.webview_windows();
     let total_windows = windows.len();
     let explicit_label = label.is_some();
-    let target_label = label.unwrap_or_else(|| "main".to_string());
+    let target_label = target_label(app, label);
+    check_window_access(app, &target_label)?;
 
     let window = app
         .get_webview_window(&target_label)
@@ -125,10 +288,15 @@ pub fn resolve_window_with_context<R: Runtime>(
 
     let warning = if !explicit_label && total_windows > 1 {
         Some(format!(
-            "Multiple windows detected ({total_windows} total). Defaulting to 'main' window. \
+            "Multiple windows detected ({total_windows} total). Defaulting to '{target_label}' window. \
              Use windowId parameter to target a specific window. \
              Available windows: {}",
-            windows.keys().cloned().collect::<Vec<_>>().join(", ")
+            windows
+                .keys()
+                .filter(|label| check_window_access(app, label).is_ok())
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
         ))
     } else {
         None
@@ -144,23 +312,353 @@ pub fn resolve_window_with_context<R: Runtime>(
     })
 }
 
-/// Resolves a window by label, defaulting to "main" if not specified.
+/// Criteria for finding a window when its label isn't known in advance.
+///
+/// Every given criterion must match. Substring matches ignore case.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowMatch {
+    /// Exact window label
+    pub label: Option<String>,
+    /// Substring of the window's current URL
+    pub url_contains: Option<String>,
+    /// Substring of the window's title
+    pub title_contains: Option<String>,
+}
+
+impl WindowMatch {
+    fn matches(&self, candidate: &WindowCandidate) -> bool {
+        let contains = |haystack: &Option<String>, needle: &Option<String>| match needle {
+            Some(needle) => haystack
+                .as_ref()
+                .is_some_and(|h| h.to_lowercase().contains(&needle.to_lowercase())),
+            None => true,
+        };
+
+        self.label.as_ref().map_or(true, |l| *l == candidate.label)
+            && contains(&candidate.url, &self.url_contains)
+            && contains(&candidate.title, &self.title_contains)
+    }
+}
+
+/// The window properties [`WindowMatch`] looks at.
+#[derive(Debug, Clone)]
+struct WindowCandidate {
+    label: String,
+    url: Option<String>,
+    title: Option<String>,
+    focused: bool,
+}
+
+/// Picks the window for `criteria` among `candidates`.
+///
+/// When several match, the focused window wins, then `"main"`, then the
+/// lowest label, and a warning lists the others.
+fn pick_window(
+    mut candidates: Vec<WindowCandidate>,
+    criteria: &WindowMatch,
+) -> Result<(String, Option<String>), String> {
+    if *criteria == WindowMatch::default() {
+        return Err(
+            "windowMatch needs at least one of label, urlContains or titleContains".to_string(),
+        );
+    }
+
+    candidates.retain(|candidate| criteria.matches(candidate));
+    candidates.sort_by(|a, b| {
+        b.focused
+            .cmp(&a.focused)
+            .then_with(|| (b.label == "main").cmp(&(a.label == "main")))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+
+    let mut labels = candidates.into_iter().map(|candidate| candidate.label);
+    let label = labels
+        .next()
+        .ok_or_else(|| format!("No window matches {criteria:?}"))?;
+    let others: Vec<String> = labels.collect();
+    let warning = (!others.is_empty()).then(|| {
+        format!(
+            "{} windows match; using '{label}'. Also matching: {}. \
+             Narrow windowMatch or use windowLabel to pick another.",
+            others.len() + 1,
+            others.join(", ")
+        )
+    });
+
+    Ok((label, warning))
+}
+
+/// Like [`pick_window`], but only among the windows `allows` accepts.
+///
+/// Fails with `access_denied` when no allowed window matches but a
+/// restricted one does.
+fn pick_allowed_window(
+    candidates: Vec<WindowCandidate>,
+    criteria: &WindowMatch,
+    allows: impl Fn(&str) -> bool,
+) -> Result<(String, Option<String>), String> {
+    let (allowed, restricted): (Vec<_>, Vec<_>) = candidates
+        .into_iter()
+        .partition(|candidate| allows(&candidate.label));
+
+    pick_window(allowed, criteria).map_err(|e| match pick_window(restricted, criteria) {
+        Ok((label, _)) => access_denied(&label),
+        Err(_) => e,
+    })
+}
+
+/// Resolves the window matching `criteria`, with context about the choice.
+///
+/// Unlike [`resolve_window_with_context`], the window is found by its URL
+/// or title, for apps whose window labels are generated at runtime.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `criteria` - What the window must match
+///
+/// # Returns
+///
+/// * `Ok(ResolvedWindow)` - The best match. `context.warning` is set when
+///   several windows matched.
+/// * `Err(String)` - If `criteria` is empty or no window matches
+pub fn resolve_window_matching<R: Runtime>(
+    app: &AppHandle<R>,
+    criteria: &WindowMatch,
+) -> Result<ResolvedWindow<R>, String> {
+    let windows = app.webview_windows();
+    let candidates = windows
+        .iter()
+        .map(|(label, window)| WindowCandidate {
+            label: label.clone(),
+            url: window_url(window),
+            title: window.title().ok(),
+            focused: window.is_focused().unwrap_or(false),
+        })
+        .collect();
+    let config = app.try_state::<Config>();
+    let (label, warning) = pick_allowed_window(candidates, criteria, |label| {
+        window_access(config.as_deref(), label).is_ok()
+    })?;
+
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("Window '{label}' not found"))?;
+
+    Ok(ResolvedWindow {
+        window,
+        context: WindowContext {
+            window_label: label,
+            total_windows: windows.len(),
+            warning,
+        },
+    })
+}
+
+/// Resolves a window by label, defaulting like [`resolve_window_with_context`].
 /// Simple version without context (for backward compatibility).
 ///
 /// # Arguments
 ///
 /// * `app` - The Tauri application handle
-/// * `label` - Optional window label (defaults to "main")
+/// * `label` - Optional window label
 ///
 /// # Returns
 ///
 /// * `Ok(WebviewWindow)` - The resolved window
-/// * `Err(String)` - Error if window not found
+/// * `Err(String)` - Error if window not found or access to it is denied
 pub fn resolve_window<R: Runtime>(
     app: &AppHandle<R>,
     label: Option<String>,
 ) -> Result<tauri::WebviewWindow<R>, String> {
-    let label = label.unwrap_or_else(|| "main".to_string());
+    let label = target_label(app, label);
+    check_window_access(app, &label)?;
     app.get_webview_window(&label)
         .ok_or_else(|| format!("Window '{label}' not found"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(label: &str, url: &str, title: &str, focused: bool) -> WindowCandidate {
+        WindowCandidate {
+            label: label.to_string(),
+            url: Some(url.to_string()),
+            title: Some(title.to_string()),
+            focused,
+        }
+    }
+
+    fn candidates() -> Vec<WindowCandidate> {
+        vec![
+            candidate("main", "http://localhost:1420/", "Dashboard", false),
+            candidate(
+                "doc-2",
+                "http://localhost:1420/editor?doc=2",
+                "Report - Editor",
+                false,
+            ),
+            candidate(
+                "doc-1",
+                "http://localhost:1420/editor?doc=1",
+                "Notes - Editor",
+                false,
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_url_substring_picks_lowest_label_among_matches() {
+        let criteria = WindowMatch {
+            url_contains: Some("/editor".to_string()),
+            ..WindowMatch::default()
+        };
+
+        let (label, warning) = pick_window(candidates(), &criteria).unwrap();
+
+        assert_eq!(label, "doc-1");
+        let warning = warning.unwrap();
+        assert!(warning.contains("2 windows match"), "{warning}");
+        assert!(warning.contains("doc-2"), "{warning}");
+    }
+
+    #[test]
+    fn test_title_substring_prefers_focused_window() {
+        let mut windows = candidates();
+        windows[1].focused = true;
+        let criteria = WindowMatch {
+            title_contains: Some("editor".to_string()),
+            ..WindowMatch::default()
+        };
+
+        let (label, warning) = pick_window(windows, &criteria).unwrap();
+
+        assert_eq!(label, "doc-2");
+        assert!(warning.unwrap().contains("doc-1"));
+    }
+
+    #[test]
+    fn test_criteria_combine_and_single_match_has_no_warning() {
+        let criteria = WindowMatch {
+            url_contains: Some("doc=2".to_string()),
+            title_contains: Some("Report".to_string()),
+            ..WindowMatch::default()
+        };
+        assert_eq!(
+            pick_window(candidates(), &criteria).unwrap(),
+            ("doc-2".to_string(), None)
+        );
+
+        let criteria = WindowMatch {
+            url_contains: Some("doc=2".to_string()),
+            title_contains: Some("Notes".to_string()),
+            ..WindowMatch::default()
+        };
+        assert!(pick_window(candidates(), &criteria).is_err());
+        assert!(pick_window(candidates(), &WindowMatch::default()).is_err());
+    }
+
+    #[test]
+    fn test_main_strategy_ignores_focus() {
+        assert_eq!(
+            default_label(false, || Some("settings".to_string())),
+            "main"
+        );
+        assert_eq!(default_label(false, || None), "main");
+    }
+
+    #[test]
+    fn test_active_strategy_prefers_focused_window() {
+        let windows = [("main", false), ("settings", true), ("about", false)];
+        assert_eq!(default_label(true, || focused_label(windows)), "settings");
+
+        // Nothing focused (e.g. the app is in the background)
+        let windows = [("main", false), ("settings", false)];
+        assert_eq!(focused_label(windows), None);
+        assert_eq!(default_label(true, || focused_label(windows)), "main");
+    }
+
+    #[test]
+    fn test_restricted_windows_are_denied_by_label() {
+        let mut config = Config {
+            window_allowlist: Some(vec!["main".to_string(), "doc-1".to_string()]),
+            window_denylist: vec!["doc-1".to_string()],
+            ..Config::default()
+        };
+
+        assert_eq!(window_access(Some(&config), "main"), Ok(()));
+        for label in ["doc-1", "doc-2"] {
+            let err = window_access(Some(&config), label).unwrap_err();
+            assert!(err.starts_with("access_denied:"), "{err}");
+        }
+
+        // Without configuration, or with an empty denylist, everything resolves
+        assert_eq!(window_access(None, "doc-2"), Ok(()));
+        config.window_allowlist = None;
+        config.window_denylist.clear();
+        assert_eq!(window_access(Some(&config), "doc-1"), Ok(()));
+    }
+
+    #[test]
+    fn test_matching_skips_and_rejects_restricted_windows() {
+        let allows = |label: &str| label != "doc-1";
+        let editor = WindowMatch {
+            url_contains: Some("/editor".to_string()),
+            ..WindowMatch::default()
+        };
+        // doc-1 would win, but it's restricted
+        assert_eq!(
+            pick_allowed_window(candidates(), &editor, allows).unwrap(),
+            ("doc-2".to_string(), None)
+        );
+
+        // doc-1 exists and is the only match
+        let notes = WindowMatch {
+            title_contains: Some("Notes".to_string()),
+            ..WindowMatch::default()
+        };
+        let err = pick_allowed_window(candidates(), &notes, allows).unwrap_err();
+        assert_eq!(err, access_denied("doc-1"));
+
+        // Nothing matching at all is still reported as such
+        let missing = WindowMatch {
+            label: Some("missing".to_string()),
+            ..WindowMatch::default()
+        };
+        let err = pick_allowed_window(candidates(), &missing, allows).unwrap_err();
+        assert!(err.starts_with("No window matches"), "{err}");
+    }
+
+    /// A mock app with `main`, `settings` and `payment` windows, whose
+    /// config denies `payment`.
+    fn app_with_denied_window() -> tauri::App<tauri::test::MockRuntime> {
+        let app = tauri::test::mock_app();
+        for label in ["main", "settings", "payment"] {
+            tauri::WebviewWindowBuilder::new(app.handle(), label, Default::default())
+                .build()
+                .unwrap();
+        }
+        app.manage(Config {
+            window_denylist: vec!["payment".to_string()],
+            ..Config::default()
+        });
+        app
+    }
+
+    #[tokio::test]
+    async fn test_list_leaves_out_restricted_windows() {
+        let app = app_with_denied_window();
+
+        let windows = list_windows(app.handle().clone()).await.unwrap();
+        let labels: Vec<&str> = windows
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|window| window["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, ["main", "settings"]);
+        assert!(!windows.to_string().contains("payment"));
+    }
+}
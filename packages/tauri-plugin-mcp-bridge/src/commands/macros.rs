@@ -0,0 +1,214 @@
+//! Running the macros registered with `Builder::register_macro`.
+
+use crate::commands::screenshot::capture_frame;
+use crate::commands::{dom, ScriptExecutor};
+use crate::config::Config;
+use crate::macros::{run_steps, MacroReport, Step};
+use crate::screenshot::FrameEncoder;
+use crate::script_registry::js_string_literal;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Manager, Runtime, WebviewWindow};
+
+/// How long a `wait` step waits for its selector by default.
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 5000;
+
+/// How often a `wait` step checks for its selector.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A registered macro, as listed by `list_macros`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MacroInfo {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// Lists the registered macros, sorted by name.
+///
+/// # Arguments
+///
+/// * `app` - The app handle
+///
+/// # Returns
+///
+/// * `Vec<MacroInfo>` - `{ name, steps }` per macro
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const macros = await invoke('plugin:mcp-bridge|list_macros');
+/// ```
+#[command]
+pub async fn list_macros<R: Runtime>(app: AppHandle<R>) -> Vec<MacroInfo> {
+    let Some(config) = app.try_state::<Config>() else {
+        return Vec::new();
+    };
+    let mut macros: Vec<MacroInfo> = config
+        .macros
+        .iter()
+        .map(|(name, steps)| MacroInfo {
+            name: name.clone(),
+            steps: steps.clone(),
+        })
+        .collect();
+    macros.sort_by(|a, b| a.name.cmp(&b.name));
+    macros
+}
+
+/// Runs a registered macro against a window.
+///
+/// The steps run one after the other, each once the previous one finished.
+/// The first failing step ends the macro; its error is in the report and the
+/// remaining steps are skipped.
+///
+/// # Arguments
+///
+/// * `window` - The window to run the steps in
+/// * `name` - Name the macro was registered under
+/// * `params` - Values for the steps' `{{param}}` placeholders
+///
+/// # Returns
+///
+/// * `Ok(MacroReport)` - `{ name, completed, steps }`, with
+///   `{ index, type, success, data?, error?, durationMs }` per step that ran
+/// * `Err(String)` - If no macro has that name, or a placeholder has no
+///   parameter
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const report = await invoke('plugin:mcp-bridge|run_macro', {
+///   name: 'open-item',
+///   params: { id: 42 }
+/// });
+/// ```
+#[command]
+pub async fn run_macro<R: Runtime>(
+    window: WebviewWindow<R>,
+    name: String,
+    params: Option<Map<String, Value>>,
+) -> Result<MacroReport, String> {
+    let steps = window
+        .try_state::<Config>()
+        .and_then(|config| config.macros.get(&name).cloned())
+        .ok_or_else(|| format!("Unknown macro: {name}"))?;
+
+    run_steps(&name, &steps, &params.unwrap_or_default(), |step| {
+        run_step(&window, step)
+    })
+    .await
+}
+
+/// Runs one step in `window`.
+async fn run_step<R: Runtime>(window: &WebviewWindow<R>, step: Step) -> Result<Value, String> {
+    match step {
+        Step::Scroll { selector, x, y } => {
+            evaluate(window, scroll_script(selector.as_deref(), x, y)).await
+        }
+        Step::Click { selector } => evaluate(window, click_script(&selector)).await,
+        Step::Wait {
+            ms,
+            selector,
+            timeout_ms,
+        } => {
+            let started = Instant::now();
+            if let Some(ms) = ms {
+                tokio::time::sleep(Duration::from_millis(ms)).await;
+            }
+            if let Some(selector) = selector {
+                let timeout_ms = timeout_ms.unwrap_or(DEFAULT_WAIT_TIMEOUT_MS);
+                wait_for_selector(window, &selector, Duration::from_millis(timeout_ms)).await?;
+            }
+            Ok(json!({ "waitedMs": started.elapsed().as_millis() as u64 }))
+        }
+        Step::Screenshot { format, quality } => {
+            let format = format.unwrap_or_else(|| "png".to_string());
+            let image = capture_frame(window, &format, quality, &mut FrameEncoder::new()).await?;
+            serde_json::to_value(image).map_err(|e| e.to_string())
+        }
+    }
+}
+
+async fn evaluate<R: Runtime>(window: &WebviewWindow<R>, script: String) -> Result<Value, String> {
+    dom::evaluate(window.clone(), script, window.state::<ScriptExecutor>()).await
+}
+
+/// Polls until an element matches `selector`.
+async fn wait_for_selector<R: Runtime>(
+    window: &WebviewWindow<R>,
+    selector: &str,
+    timeout: Duration,
+) -> Result<(), String> {
+    let script = format!(
+        "return document.querySelector({}) !== null;",
+        js_string_literal(selector)
+    );
+    let deadline = Instant::now() + timeout;
+    loop {
+        if evaluate(window, script.clone()).await? == Value::Bool(true) {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Timed out after {}ms waiting for selector: {selector}",
+                timeout.as_millis()
+            ));
+        }
+        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+    }
+}
+
+/// Builds the script scrolling `selector` into view, or the page to `x`, `y`.
+fn scroll_script(selector: Option<&str>, x: f64, y: f64) -> String {
+    let target = match selector {
+        Some(selector) => format!(
+            "const selector = {};
+const el = document.querySelector(selector);
+if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+el.scrollIntoView({{ block: 'center', inline: 'center' }});",
+            js_string_literal(selector)
+        ),
+        None => format!("window.scrollTo({x}, {y});"),
+    };
+    format!("{target}\nreturn {{ x: window.scrollX, y: window.scrollY }};")
+}
+
+/// Builds the script clicking the element matching `selector`.
+fn click_script(selector: &str) -> String {
+    format!(
+        "const selector = {};
+const el = document.querySelector(selector);
+if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+el.scrollIntoView({{ block: 'center', inline: 'center' }});
+el.click();
+return {{ clicked: selector }};",
+        js_string_literal(selector)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_script_targets_selector_or_position() {
+        let to_element = scroll_script(Some("#list 'a'"), 0.0, 0.0);
+        assert!(to_element.contains(&js_string_literal("#list 'a'")));
+        assert!(to_element.contains("scrollIntoView"));
+
+        let to_position = scroll_script(None, 0.0, 480.5);
+        assert!(to_position.starts_with("window.scrollTo(0, 480.5);"));
+    }
+
+    #[test]
+    fn test_click_script_fails_on_missing_element() {
+        let script = click_script("#save");
+        assert!(script.contains("throw new Error('No element matches selector: '"));
+        assert!(script.ends_with("return { clicked: selector };"));
+    }
+}
@@ -0,0 +1,411 @@
+//! Emulating CSS media features such as dark mode and print.
+//!
+//! | Platform | Method | Covers |
+//! |----------|--------|--------|
+//! | Windows | DevTools protocol `Emulation.setEmulatedMedia` | All stylesheets and `matchMedia`, across navigations |
+//! | macOS, iOS, Linux, Android | CSS rewriting (`css`) | Readable stylesheets and `matchMedia`, until the page reloads |
+//!
+//! The CSS fallback rewrites the media queries of every stylesheet the page
+//! can read, so a `(prefers-color-scheme: dark)` rule applies as if the user
+//! had chosen dark mode, and wraps `window.matchMedia` the same way.
+//! Cross-origin stylesheets can't be read and are left alone; stylesheets
+//! added after the call are not rewritten until `emulate_media` runs again.
+//! Windows falls back to CSS rewriting when the DevTools call fails.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Media features `emulate_media` can override.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaFeatures {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefers_color_scheme: Option<ColorScheme>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefers_reduced_motion: Option<ReducedMotion>,
+    /// Applies `print` styles instead of `screen` ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub print: Option<bool>,
+}
+
+/// Value of `prefers-color-scheme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorScheme {
+    Light,
+    Dark,
+}
+
+/// Value of `prefers-reduced-motion`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReducedMotion {
+    Reduce,
+    NoPreference,
+}
+
+impl MediaFeatures {
+    /// Whether no feature is set.
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// Emulated media features as CSS `(name, value)` pairs.
+    fn css_features(&self) -> Vec<(&'static str, &'static str)> {
+        let mut features = Vec::new();
+        if let Some(scheme) = self.prefers_color_scheme {
+            let value = match scheme {
+                ColorScheme::Light => "light",
+                ColorScheme::Dark => "dark",
+            };
+            features.push(("prefers-color-scheme", value));
+        }
+        if let Some(motion) = self.prefers_reduced_motion {
+            let value = match motion {
+                ReducedMotion::Reduce => "reduce",
+                ReducedMotion::NoPreference => "no-preference",
+            };
+            features.push(("prefers-reduced-motion", value));
+        }
+        features
+    }
+
+    /// Media queries that match once the emulation is applied, used to
+    /// confirm it took effect.
+    fn expected_queries(&self) -> Vec<String> {
+        let mut queries: Vec<String> = self
+            .css_features()
+            .into_iter()
+            .map(|(name, value)| format!("({name}: {value})"))
+            .collect();
+        if self.print == Some(true) {
+            queries.push("print".to_string());
+        }
+        queries
+    }
+
+    /// Parameters of the DevTools `Emulation.setEmulatedMedia` method.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn devtools_params(&self) -> Value {
+        let media = if self.print == Some(true) {
+            "print"
+        } else {
+            ""
+        };
+        let features: Vec<Value> = self
+            .css_features()
+            .into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect();
+        json!({ "media": media, "features": features })
+    }
+}
+
+/// How an emulation was applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaEmulationMethod {
+    /// The engine's DevTools protocol
+    Devtools,
+    /// Rewriting the page's stylesheets and `matchMedia`
+    Css,
+}
+
+/// Outcome of `emulate_media`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MediaEmulation {
+    /// Features now emulated; empty after a reset
+    pub applied: MediaFeatures,
+    pub method: MediaEmulationMethod,
+    /// Stylesheet media lists rewritten by the CSS fallback
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewritten_rules: Option<u32>,
+    /// Cross-origin stylesheets the CSS fallback couldn't read
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skipped_stylesheets: Option<u32>,
+    /// `matchMedia` result for each emulated feature, e.g.
+    /// `{ "(prefers-color-scheme: dark)": true }`
+    pub matches: Map<String, Value>,
+}
+
+/// What the page reports after applying or clearing an emulation.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageReport {
+    #[serde(default)]
+    rewritten_rules: u32,
+    #[serde(default)]
+    skipped_stylesheets: u32,
+    #[serde(default)]
+    matches: Map<String, Value>,
+}
+
+/// Emulates CSS media features in a window, or clears the emulation.
+///
+/// Only the features given are overridden; the others keep following the
+/// system. Each call replaces the previous emulation. See the module docs
+/// for what each platform supports.
+///
+/// # Arguments
+///
+/// * `window` - The window to emulate media in
+/// * `features` - `{ prefersColorScheme?, prefersReducedMotion?, print? }`
+/// * `reset` - Clears any emulation instead of applying `features`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(MediaEmulation)` - `{ applied, method, rewrittenRules?,
+///   skippedStylesheets?, matches }`
+/// * `Err(String)` - If neither `features` nor `reset` is given, or the page
+///   can't be evaluated
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { matches } = await invoke('plugin:mcp-bridge|emulate_media', {
+///   features: { prefersColorScheme: 'dark' }
+/// });
+/// console.log(matches['(prefers-color-scheme: dark)']); // true
+///
+/// await invoke('plugin:mcp-bridge|emulate_media', { reset: true });
+/// ```
+#[command]
+pub async fn emulate_media<R: Runtime>(
+    window: WebviewWindow<R>,
+    features: Option<MediaFeatures>,
+    reset: Option<bool>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<MediaEmulation, String> {
+    let features = if reset.unwrap_or(false) {
+        MediaFeatures::default()
+    } else {
+        match features {
+            Some(features) if !features.is_empty() => features,
+            _ => return Err("Pass features to emulate, or reset: true".to_string()),
+        }
+    };
+
+    let method = match set_devtools_media(&window, &features) {
+        Ok(()) => MediaEmulationMethod::Devtools,
+        Err(_) => MediaEmulationMethod::Css,
+    };
+    let script = emulation_script(&features, method == MediaEmulationMethod::Css);
+    let data = dom::evaluate(window, script, executor_state).await?;
+    let report: PageReport =
+        serde_json::from_value(data).map_err(|e| format!("Unexpected emulation report: {e}"))?;
+
+    let css = method == MediaEmulationMethod::Css;
+    Ok(MediaEmulation {
+        applied: features,
+        method,
+        rewritten_rules: css.then_some(report.rewritten_rules),
+        skipped_stylesheets: css.then_some(report.skipped_stylesheets),
+        matches: report.matches,
+    })
+}
+
+/// Clears any CSS emulation on the page, applies `features` with the CSS
+/// fallback when `rewrite` is set, and reports how the expected queries
+/// evaluate.
+fn emulation_script(features: &MediaFeatures, rewrite: bool) -> String {
+    let overrides: Map<String, Value> = features
+        .css_features()
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), Value::from(value)))
+        .collect();
+    let emulation = json!({
+        "rewrite": rewrite && !features.is_empty(),
+        "features": overrides,
+        "print": features.print == Some(true),
+        "queries": features.expected_queries(),
+    });
+    format!("const emulation = {emulation};\n{MEDIA_EMULATION_SCRIPT}")
+}
+
+const MEDIA_EMULATION_SCRIPT: &str = r#"const previous = window.__MCP_MEDIA__;
+if (previous) {
+    for (const [list, text] of previous.originals) {
+        try { list.mediaText = text; } catch (e) { /* the rule is gone */ }
+    }
+    window.matchMedia = previous.matchMedia;
+    delete window.__MCP_MEDIA__;
+}
+
+let rewrittenRules = 0;
+let skippedStylesheets = 0;
+if (emulation.rewrite) {
+    const TRUE = '(min-width: 0px)';
+    const FALSE = '(not (min-width: 0px))';
+    const rewriteFeatures = (text) => text.replace(
+        /\(\s*(prefers-color-scheme|prefers-reduced-motion)\s*(?::\s*([a-z-]+)\s*)?\)/gi,
+        (match, name, value) => {
+            const emulated = emulation.features[name.toLowerCase()];
+            if (emulated === undefined) return match;
+            if (value === undefined) return emulated === 'no-preference' ? FALSE : TRUE;
+            return value.toLowerCase() === emulated ? TRUE : FALSE;
+        }
+    );
+    // `print` rules match everywhere and `screen` rules nowhere
+    const rewriteTypes = (text) => emulation.print
+        ? text.replace(/(^|,)(\s*(?:not\s+|only\s+)?)(print|screen)\b/gi,
+            (match, start, prefix, type) => start + prefix + (type.toLowerCase() === 'print' ? 'all' : 'print'))
+        : text;
+    const rewrite = (text) => rewriteTypes(rewriteFeatures(text));
+
+    const originals = [];
+    const rewriteList = (list) => {
+        if (!list || !list.mediaText) return;
+        const text = list.mediaText;
+        const rewritten = rewrite(text);
+        if (rewritten === text) return;
+        list.mediaText = rewritten;
+        originals.push([list, text]);
+        rewrittenRules++;
+    };
+    const visitSheet = (sheet) => {
+        rewriteList(sheet.media);
+        let rules;
+        try {
+            rules = sheet.cssRules;
+        } catch (e) {
+            skippedStylesheets++;
+            return;
+        }
+        visitRules(rules);
+    };
+    const visitRules = (rules) => {
+        for (const rule of rules) {
+            if (rule.media) rewriteList(rule.media);
+            if (rule.styleSheet) visitSheet(rule.styleSheet);
+            if (rule.cssRules) visitRules(rule.cssRules);
+        }
+    };
+    for (const sheet of document.styleSheets) visitSheet(sheet);
+
+    const matchMedia = window.matchMedia;
+    window.matchMedia = (query) => matchMedia.call(window, rewrite(String(query)));
+    window.__MCP_MEDIA__ = { originals, matchMedia };
+}
+
+const matches = {};
+for (const query of emulation.queries) matches[query] = window.matchMedia(query).matches;
+return { rewrittenRules, skippedStylesheets, matches };"#;
+
+/// Applies `features` through the DevTools protocol, clearing emulation when
+/// empty.
+#[cfg(target_os = "windows")]
+fn set_devtools_media<R: Runtime>(
+    window: &WebviewWindow<R>,
+    features: &MediaFeatures,
+) -> Result<(), String> {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::HSTRING;
+
+    let params = features.devtools_params().to_string();
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            let controller = webview.controller();
+            let started = unsafe {
+                crate::webview2::core_webview2(&controller).and_then(|core_webview| {
+                    let tx = tx.clone();
+                    let handler = CallDevToolsProtocolMethodCompletedHandler::create(Box::new(
+                        move |result, _response| {
+                            let _ = tx
+                                .send(result.map_err(|e| format!("setEmulatedMedia failed: {e}")));
+                            Ok(())
+                        },
+                    ));
+                    core_webview
+                        .CallDevToolsProtocolMethod(
+                            &HSTRING::from("Emulation.setEmulatedMedia"),
+                            &HSTRING::from(params.as_str()),
+                            &handler,
+                        )
+                        .map_err(|e| format!("Failed to call the DevTools protocol: {e}"))
+                })
+            };
+            if let Err(e) = started {
+                let _ = tx.send(Err(e));
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    rx.recv_timeout(std::time::Duration::from_secs(5))
+        .map_err(|_| "Timed out emulating media".to_string())?
+}
+
+#[cfg(not(target_os = "windows"))]
+fn set_devtools_media<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _features: &MediaFeatures,
+) -> Result<(), String> {
+    Err(format!(
+        "The DevTools protocol is not available on {}",
+        std::env::consts::OS
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dark_mode_makes_color_scheme_query_match() {
+        let features: MediaFeatures =
+            serde_json::from_value(json!({ "prefersColorScheme": "dark" })).unwrap();
+
+        assert_eq!(
+            features.css_features(),
+            vec![("prefers-color-scheme", "dark")]
+        );
+        assert_eq!(
+            features.expected_queries(),
+            vec!["(prefers-color-scheme: dark)"]
+        );
+        assert_eq!(
+            features.devtools_params(),
+            json!({
+                "media": "",
+                "features": [{ "name": "prefers-color-scheme", "value": "dark" }]
+            })
+        );
+
+        let script = emulation_script(&features, true);
+        assert!(script.starts_with(
+            r#"const emulation = {"features":{"prefers-color-scheme":"dark"},"print":false,"queries":["(prefers-color-scheme: dark)"],"rewrite":true};"#
+        ));
+    }
+
+    #[test]
+    fn test_reset_clears_every_feature() {
+        let print: MediaFeatures = serde_json::from_value(json!({
+            "prefersReducedMotion": "no-preference",
+            "print": true
+        }))
+        .unwrap();
+        assert_eq!(
+            print.expected_queries(),
+            vec!["(prefers-reduced-motion: no-preference)", "print"]
+        );
+        assert_eq!(print.devtools_params()["media"], "print");
+
+        // Unknown keys are ignored, leaving nothing to emulate
+        let misspelled: MediaFeatures =
+            serde_json::from_value(json!({ "colorScheme": "dark" })).unwrap();
+        assert!(misspelled.is_empty());
+
+        let reset = MediaFeatures::default();
+        assert!(reset.is_empty());
+        assert_eq!(
+            reset.devtools_params(),
+            json!({ "media": "", "features": [] })
+        );
+        assert!(emulation_script(&reset, true).contains(r#""rewrite":false"#));
+    }
+}
@@ -0,0 +1,181 @@
+//! Reading a page's `<meta>` tags.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Key prefix of OpenGraph tags, e.g. `og:title`.
+const OPEN_GRAPH_PREFIX: &str = "og:";
+
+/// Key prefix of Twitter card tags, e.g. `twitter:card`.
+const TWITTER_PREFIX: &str = "twitter:";
+
+/// A single `<meta>` tag.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetaTag {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub property: Option<String>,
+    /// `null` when the tag has no `content` attribute
+    #[serde(default)]
+    pub content: Option<String>,
+}
+
+impl MetaTag {
+    /// The key the tag describes, preferring `property` as OpenGraph does.
+    fn key(&self) -> Option<&str> {
+        self.property.as_deref().or(self.name.as_deref())
+    }
+}
+
+/// A page's `<meta>` tags, with OpenGraph and Twitter card fields grouped.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaTags {
+    /// Every tag with a `name` or `property`, in document order
+    pub tags: Vec<MetaTag>,
+    /// `og:*` fields keyed without the prefix
+    pub open_graph: Map<String, Value>,
+    /// `twitter:*` fields keyed without the prefix
+    pub twitter: Map<String, Value>,
+}
+
+impl MetaTags {
+    /// Groups the OpenGraph and Twitter card fields of `tags`.
+    ///
+    /// Keys are matched case-insensitively. A key that appears more than
+    /// once, such as several `og:image` tags, maps to an array of its values
+    /// in document order. Tags without content are listed but not grouped.
+    pub fn from_tags(tags: Vec<MetaTag>) -> Self {
+        let mut open_graph = Map::new();
+        let mut twitter = Map::new();
+
+        for tag in &tags {
+            let (Some(key), Some(content)) = (tag.key(), tag.content.as_deref()) else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let (group, field) = if let Some(field) = key.strip_prefix(OPEN_GRAPH_PREFIX) {
+                (&mut open_graph, field)
+            } else if let Some(field) = key.strip_prefix(TWITTER_PREFIX) {
+                (&mut twitter, field)
+            } else {
+                continue;
+            };
+            if field.is_empty() {
+                continue;
+            }
+
+            let value = Value::String(content.to_string());
+            match group.get_mut(field) {
+                None => {
+                    group.insert(field.to_string(), value);
+                }
+                Some(Value::Array(values)) => values.push(value),
+                Some(existing) => *existing = Value::Array(vec![existing.take(), value]),
+            }
+        }
+
+        Self {
+            tags,
+            open_graph,
+            twitter,
+        }
+    }
+}
+
+/// Returns a window's `<meta>` tags.
+///
+/// Tags without a `name` or `property` (such as `charset`) are left out.
+///
+/// # Arguments
+///
+/// * `window` - The window to read
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(MetaTags)` - `{ tags, openGraph, twitter }`
+/// * `Err(String)` - If the page can't be evaluated
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { openGraph } = await invoke('plugin:mcp-bridge|get_meta_tags');
+/// console.log(openGraph.title, openGraph.image);
+/// ```
+#[command]
+pub async fn get_meta_tags<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<MetaTags, String> {
+    let data = dom::evaluate(window, META_TAGS_SCRIPT.to_string(), executor_state).await?;
+    let tags: Vec<MetaTag> =
+        serde_json::from_value(data).map_err(|e| format!("Unexpected meta tag list: {e}"))?;
+    Ok(MetaTags::from_tags(tags))
+}
+
+/// Lists `<meta>` tags with a `name` or `property` attribute.
+const META_TAGS_SCRIPT: &str = r#"return Array.from(document.querySelectorAll('meta[name], meta[property]'))
+    .map((meta) => ({
+        name: meta.getAttribute('name'),
+        property: meta.getAttribute('property'),
+        content: meta.getAttribute('content')
+    }));"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tags(value: Value) -> Vec<MetaTag> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_open_graph_and_twitter_fields_are_grouped() {
+        let meta = MetaTags::from_tags(tags(json!([
+            { "name": "description", "property": null, "content": "A page" },
+            { "name": null, "property": "og:title", "content": "Title" },
+            { "name": null, "property": "OG:Type", "content": "article" },
+            { "name": null, "property": "og:image:width", "content": "1200" },
+            { "name": "twitter:card", "property": null, "content": "summary" },
+            { "name": null, "property": "twitter:site", "content": "@tauri" }
+        ])));
+
+        assert_eq!(meta.tags.len(), 6);
+        assert_eq!(
+            Value::Object(meta.open_graph),
+            json!({ "title": "Title", "type": "article", "image:width": "1200" })
+        );
+        assert_eq!(
+            Value::Object(meta.twitter),
+            json!({ "card": "summary", "site": "@tauri" })
+        );
+    }
+
+    #[test]
+    fn test_duplicates_and_missing_content_are_tolerated() {
+        let meta = MetaTags::from_tags(tags(json!([
+            { "name": null, "property": "og:image", "content": "a.png" },
+            { "name": null, "property": "og:image", "content": "b.png" },
+            { "name": null, "property": "og:image", "content": "c.png" },
+            { "name": null, "property": "og:title", "content": null },
+            { "name": "robots", "property": null, "content": null }
+        ])));
+
+        assert_eq!(
+            Value::Object(meta.open_graph.clone()),
+            json!({ "image": ["a.png", "b.png", "c.png"] })
+        );
+        assert!(meta.twitter.is_empty());
+        assert_eq!(
+            serde_json::to_value(&meta).unwrap()["tags"][4],
+            json!({ "name": "robots", "content": null })
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Mock rules command for installing stubbed responses on page load.
+
+use crate::commands::check_window_access;
+use crate::mocks::{MockRegistry, MockRule};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Returns the mock rules for `bridge.js` to apply to the page's `fetch` and
+/// XHR calls, called at document start on every page load.
+///
+/// Windows restricted by the window allowlist or denylist get none.
+///
+/// # Arguments
+///
+/// * `window` - The window loading a page
+/// * `mocks` - Shared mock rules
+///
+/// # Returns
+///
+/// * `Vec<MockRule>` - The rules, in the order they are checked
+#[command]
+pub fn request_mocks<R: Runtime>(
+    window: WebviewWindow<R>,
+    mocks: State<'_, MockRegistry>,
+) -> Vec<MockRule> {
+    match check_window_access(&window, window.label()) {
+        Ok(()) => mocks.rules(),
+        Err(_) => Vec::new(),
+    }
+}
@@ -5,31 +5,124 @@
 //! when invoked from the frontend.
 
 // Individual command modules
+pub mod abort_all;
+pub mod app_paths;
+pub mod assert_element;
 pub mod backend_state;
+pub mod bridge_config;
+pub mod bridge_logs;
+pub mod clear_cache;
+pub mod clients;
+pub mod computed_style;
+pub mod console_history;
+pub mod current_url;
+pub(crate) mod dom;
+pub mod dom_event;
+pub mod drag_and_drop;
+pub mod element_box;
+pub mod element_from_point;
 pub mod emit_event;
+pub mod event_listeners;
 pub mod execute_command;
 pub mod execute_js;
+pub mod execute_js_all;
+pub mod execute_js_file;
+pub mod execute_js_if;
+pub mod failed_requests;
+pub mod focus;
+pub mod form;
+pub mod injected_globals;
+pub mod input_value;
+pub mod interactable;
+pub mod layout;
 pub mod ipc_monitor;
 pub mod list_windows;
+pub mod macros;
+pub mod media;
+pub mod meta_tags;
+pub mod mocks;
+pub mod mutations;
+pub mod network_idle;
+pub mod readable_text;
+pub mod ready_state;
+pub mod reset_window;
+pub mod resources;
 pub mod screenshot;
 pub mod script_executor;
 pub mod script_injection;
+pub mod scroll_info;
+pub mod selection;
+pub mod system_status;
+pub mod theme;
+pub mod user_agent;
+pub mod virtual_time;
+pub mod wait_for_event;
+pub mod web_vitals;
+pub mod window_flags;
 pub mod window_info;
 
 // Re-export types and commands for convenience
 pub use script_executor::ScriptExecutor;
 
 // Re-export command functions (needed for generate_handler! macro)
+pub use abort_all::{abort_all, AbortSummary};
+pub use assert_element::{assert_element, AssertOperator, AssertProperty};
 pub use backend_state::get_backend_state;
+pub use bridge_config::get_config;
+pub use bridge_logs::get_bridge_logs;
+pub use dom_event::dispatch_dom_event;
+pub use drag_and_drop::drag_and_drop;
+pub use element_box::get_element_box;
+pub use element_from_point::element_from_point;
 pub use emit_event::emit_event;
+pub use event_listeners::{list_event_listeners, track_event_listener};
 pub use execute_command::execute_command;
-pub use execute_js::execute_js;
-pub use ipc_monitor::{get_ipc_events, start_ipc_monitor, stop_ipc_monitor};
+pub use app_paths::{get_app_paths, AppPaths};
+pub use clear_cache::{clear_cache, CacheClearReport, CacheKind};
+pub use clients::get_clients;
+pub use computed_style::get_computed_style;
+pub use console_history::{get_console_history, report_console_entry};
+pub use current_url::get_current_url;
+pub use execute_js::{execute_js, World};
+pub use execute_js_all::execute_js_all;
+pub use execute_js_file::execute_js_file;
+pub use execute_js_if::execute_js_if;
+pub use failed_requests::{get_failed_requests, report_failed_request};
+pub use focus::{focus_element, get_active_element};
+pub use form::{fill_form, read_form};
+pub use injected_globals::{get_injected_globals_count, InjectedGlobals};
+pub use input_value::{get_input_value, set_input_value};
+pub use interactable::is_interactable;
+pub use layout::{get_layout, set_layout, WindowLayout};
+pub use ipc_monitor::{
+    get_ipc_events, pause_ipc_monitor, report_ipc_event, resume_ipc_monitor, start_ipc_monitor,
+    stop_ipc_monitor,
+};
 pub use list_windows::{
-    list_windows, resolve_window, resolve_window_with_context, ResolvedWindow, WindowContext,
-    WindowInfo,
+    check_window_access, get_active_window, list_windows, resolve_window, resolve_window_matching,
+    resolve_window_with_context, set_active_window, ResolvedWindow, WindowContext, WindowInfo,
+    WindowMatch,
 };
-pub use screenshot::capture_native_screenshot;
+pub use macros::{list_macros, run_macro, MacroInfo};
+pub use media::{emulate_media, MediaEmulation, MediaFeatures};
+pub use meta_tags::{get_meta_tags, MetaTag, MetaTags};
+pub use mocks::request_mocks;
+pub use mutations::{observe_mutations, MutationSummary};
+pub use network_idle::{wait_for_network_idle, NetworkIdleReport};
+pub use readable_text::get_readable_text;
+pub use ready_state::{get_ready_state, wait_for_ready, ReadyStateReport};
+pub use reset_window::{reset_window, ResetReport};
+pub use resources::{list_resources, read_resource};
+pub use screenshot::{capture_native_screenshot, capture_window_with_chrome, compare_screenshot};
 pub use script_executor::script_result;
 pub use script_injection::request_script_injection;
+pub use scroll_info::{get_scroll_info, ScrollInfo};
+pub use selection::{get_selection, set_selection};
+pub use system_status::get_system_status;
+pub use theme::{get_theme, set_theme, ThemeSetting, WindowTheme};
+pub use user_agent::{get_user_agent, set_user_agent, UserAgentInfo, UserAgentOverrides};
+pub use virtual_time::{freeze_time, step_time, unfreeze_time};
+pub use wait_for_event::wait_for_event;
+pub use web_vitals::{get_web_vitals, WebVitals};
+pub use window_flags::set_window_flags;
 pub use window_info::get_window_info;
@@ -0,0 +1,268 @@
+//! Summarizing how much a page's DOM changes over a time window.
+//!
+//! `observe_mutations` installs a `MutationObserver` on the document, waits
+//! on the Rust side, then collects the counts and disconnects the observer.
+//! The wait isn't bound by the script execution timeout, and the observer
+//! removes itself if it is never collected, e.g. when the wait is aborted.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Longest accepted observation window.
+pub const MAX_OBSERVE_DURATION_MS: u64 = 60_000;
+
+/// How long an uncollected observer stays installed past its window.
+const OBSERVER_TTL_MS: u64 = 10_000;
+
+/// Counts collected from the page.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MutationReport {
+    added_nodes: u64,
+    removed_nodes: u64,
+    attribute_changes: u64,
+    character_data_changes: u64,
+    elapsed_ms: f64,
+    /// Time from the start of the window to the last mutation
+    last_mutation_ms: Option<f64>,
+}
+
+/// DOM changes seen during `observe_mutations`.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MutationSummary {
+    /// Nodes inserted anywhere in the document
+    pub added_nodes: u64,
+    /// Nodes removed anywhere in the document
+    pub removed_nodes: u64,
+    pub attribute_changes: u64,
+    /// Text node content changes
+    pub character_data_changes: u64,
+    /// Whether nothing changed during the final `quietMs` of the window
+    pub stable: bool,
+    /// Time from the start of the window to the last mutation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_mutation_ms: Option<u64>,
+}
+
+impl MutationSummary {
+    fn new(report: MutationReport, quiet: Duration) -> Self {
+        let quiet_ms = quiet.as_millis() as f64;
+        let stable = report
+            .last_mutation_ms
+            .map_or(true, |last| report.elapsed_ms - last >= quiet_ms);
+        Self {
+            added_nodes: report.added_nodes,
+            removed_nodes: report.removed_nodes,
+            attribute_changes: report.attribute_changes,
+            character_data_changes: report.character_data_changes,
+            stable,
+            last_mutation_ms: report.last_mutation_ms.map(|last| last.round() as u64),
+        }
+    }
+}
+
+/// Observes a window's DOM for `duration_ms` and summarizes the changes.
+///
+/// Every node, attribute and text change in the document is counted. The
+/// page is `stable` when none happened during the final `quiet_ms` of the
+/// window, which makes repeated calls a way to wait until a page settles.
+///
+/// # Arguments
+///
+/// * `window` - The window to observe
+/// * `duration_ms` - Length of the observation window (at most 60000)
+/// * `quiet_ms` - Final part of the window that must be free of changes for
+///   the page to count as stable (default a quarter of `duration_ms`)
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(MutationSummary)` - `{ addedNodes, removedNodes, attributeChanges,
+///   characterDataChanges, stable, lastMutationMs? }`
+/// * `Err(String)` - If the durations are invalid, the page navigated away
+///   during the window, or the wait is cancelled with `abort_all`
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { stable } = await invoke('plugin:mcp-bridge|observe_mutations', {
+///   durationMs: 1000
+/// });
+/// ```
+#[command]
+pub async fn observe_mutations<R: Runtime>(
+    window: WebviewWindow<R>,
+    duration_ms: u64,
+    quiet_ms: Option<u64>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<MutationSummary, String> {
+    let duration = validate_duration(duration_ms)?;
+    let quiet = Duration::from_millis(quiet_ms.unwrap_or(duration_ms / 4));
+    if quiet > duration {
+        return Err(format!(
+            "quietMs ({}) must not exceed durationMs ({duration_ms})",
+            quiet.as_millis()
+        ));
+    }
+
+    let observer_id = uuid::Uuid::new_v4().simple().to_string();
+    dom::evaluate(
+        window.clone(),
+        install_script(&observer_id, duration_ms + OBSERVER_TTL_MS),
+        executor_state.clone(),
+    )
+    .await?;
+
+    let observe = async {
+        tokio::time::sleep(duration).await;
+        dom::evaluate(window.clone(), collect_script(&observer_id), executor_state).await
+    };
+    let data = crate::abort::cancellable(&window, observe).await?;
+    let report: MutationReport =
+        serde_json::from_value(data).map_err(|e| format!("Unexpected mutation report: {e}"))?;
+    Ok(MutationSummary::new(report, quiet))
+}
+
+/// Checks a requested observation window.
+fn validate_duration(duration_ms: u64) -> Result<Duration, String> {
+    match duration_ms {
+        0 => Err("durationMs must be greater than 0".to_string()),
+        ms if ms > MAX_OBSERVE_DURATION_MS => Err(format!(
+            "durationMs must be at most {MAX_OBSERVE_DURATION_MS}, got {ms}"
+        )),
+        ms => Ok(Duration::from_millis(ms)),
+    }
+}
+
+/// Name of the global holding an observer between install and collection.
+fn observer_global(observer_id: &str) -> String {
+    format!("__mcp_mutations_{observer_id}")
+}
+
+/// Starts counting mutations. The observer removes itself after `ttl_ms`.
+fn install_script(observer_id: &str, ttl_ms: u64) -> String {
+    let global = observer_global(observer_id);
+    format!(
+        r#"const counts = {{ addedNodes: 0, removedNodes: 0, attributeChanges: 0, characterDataChanges: 0 }};
+const start = performance.now();
+let last = null;
+const count = (records) => {{
+    for (const record of records) {{
+        if (record.type === 'childList') {{
+            counts.addedNodes += record.addedNodes.length;
+            counts.removedNodes += record.removedNodes.length;
+        }} else if (record.type === 'attributes') {{
+            counts.attributeChanges++;
+        }} else if (record.type === 'characterData') {{
+            counts.characterDataChanges++;
+        }}
+    }}
+    if (records.length) last = performance.now();
+}};
+const observer = new MutationObserver(count);
+observer.observe(document.documentElement, {{ childList: true, subtree: true, attributes: true, characterData: true }});
+const expiry = setTimeout(() => {{
+    observer.disconnect();
+    delete window.{global};
+}}, {ttl_ms});
+window.{global} = () => {{
+    clearTimeout(expiry);
+    count(observer.takeRecords());
+    observer.disconnect();
+    delete window.{global};
+    return {{
+        ...counts,
+        elapsedMs: performance.now() - start,
+        lastMutationMs: last === null ? null : last - start
+    }};
+}};
+return true;"#
+    )
+}
+
+/// Stops an observer and returns its counts.
+fn collect_script(observer_id: &str) -> String {
+    let global = observer_global(observer_id);
+    format!(
+        r#"const collect = window.{global};
+if (typeof collect !== 'function') {{
+    throw new Error('Mutation observer is gone; the page navigated during the observation');
+}}
+return collect();"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(last_mutation_ms: Option<f64>) -> MutationReport {
+        MutationReport {
+            added_nodes: 12,
+            removed_nodes: 3,
+            attribute_changes: 5,
+            character_data_changes: 1,
+            elapsed_ms: 1000.0,
+            last_mutation_ms,
+        }
+    }
+
+    #[test]
+    fn test_page_that_mutates_then_settles_is_stable() {
+        let quiet = Duration::from_millis(250);
+
+        let settled = MutationSummary::new(report(Some(400.2)), quiet);
+        assert!(settled.stable);
+        assert_eq!(
+            serde_json::to_value(settled).unwrap(),
+            serde_json::json!({
+                "addedNodes": 12,
+                "removedNodes": 3,
+                "attributeChanges": 5,
+                "characterDataChanges": 1,
+                "stable": true,
+                "lastMutationMs": 400
+            })
+        );
+
+        let still_changing = MutationSummary::new(report(Some(900.0)), quiet);
+        assert!(!still_changing.stable);
+
+        let untouched = MutationSummary::new(
+            MutationReport {
+                added_nodes: 0,
+                ..report(None)
+            },
+            quiet,
+        );
+        assert!(untouched.stable);
+    }
+
+    #[test]
+    fn test_scripts_share_a_self_cleaning_global() {
+        let install = install_script("abc", 11_000);
+        let collect = collect_script("abc");
+
+        assert!(install.contains("window.__mcp_mutations_abc = () =>"));
+        assert!(install.contains("}, 11000);"));
+        assert_eq!(
+            install
+                .matches("delete window.__mcp_mutations_abc;")
+                .count(),
+            2
+        );
+        assert!(collect.contains("const collect = window.__mcp_mutations_abc;"));
+        // Both run synchronously, without a result slot of their own
+        assert!(!crate::commands::execute_js::is_async_script(&install));
+        assert!(!crate::commands::execute_js::is_async_script(&collect));
+
+        assert!(validate_duration(0).is_err());
+        assert!(validate_duration(MAX_OBSERVE_DURATION_MS + 1).is_err());
+        assert_eq!(validate_duration(500), Ok(Duration::from_millis(500)));
+    }
+}
@@ -0,0 +1,227 @@
+//! Waiting for a page's network traffic to settle.
+//!
+//! `bridge.js` wraps `fetch` and `XMLHttpRequest` before the page's own
+//! scripts run and counts the calls in flight in `window.__MCP_NETWORK__`.
+//! Requests the page makes by other means (images, WebSockets, service
+//! workers) aren't counted.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Default time without requests in flight that counts as idle.
+const DEFAULT_IDLE_MS: u64 = 500;
+
+/// Default time to wait for the network to go idle.
+const DEFAULT_NETWORK_IDLE_TIMEOUT_MS: u64 = 10_000;
+
+/// Time between checks of the page's request count while waiting.
+const NETWORK_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A page's request count at one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkActivity {
+    /// `fetch` and `XMLHttpRequest` calls in flight
+    pub pending: u32,
+    /// Time since the count last changed
+    pub quiet_ms: u64,
+}
+
+impl NetworkActivity {
+    fn is_idle_for(&self, idle: Duration) -> bool {
+        self.pending == 0 && u128::from(self.quiet_ms) >= idle.as_millis()
+    }
+}
+
+/// Outcome of `wait_for_network_idle`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkIdleReport {
+    /// Whether the network went idle before the timeout
+    pub idle: bool,
+    /// Requests still in flight when the timeout elapsed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pending_at_timeout: Option<u32>,
+    pub waited_ms: u64,
+}
+
+/// Waits until a window has had no `fetch` or `XMLHttpRequest` calls in
+/// flight for `idle_ms`.
+///
+/// The request count is checked every 50ms. Checks that fail while a new
+/// page is loading are retried. Timing out is not an error: the report says
+/// whether the network went idle and, if not, how many requests were still
+/// in flight.
+///
+/// # Arguments
+///
+/// * `window` - The window to wait on
+/// * `idle_ms` - Time without requests in flight that counts as idle
+///   (default 500)
+/// * `timeout_ms` - Maximum time to wait in milliseconds (default 10000)
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(NetworkIdleReport)` - `{ idle, pendingAtTimeout?, waitedMs }`
+/// * `Err(String)` - If the page never reported its request count, or the
+///   wait is cancelled with `abort_all`
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { idle } = await invoke('plugin:mcp-bridge|wait_for_network_idle', {
+///   idleMs: 500,
+///   timeoutMs: 5000
+/// });
+/// ```
+#[command]
+pub async fn wait_for_network_idle<R: Runtime>(
+    window: WebviewWindow<R>,
+    idle_ms: Option<u64>,
+    timeout_ms: Option<u64>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<NetworkIdleReport, String> {
+    let idle = Duration::from_millis(idle_ms.unwrap_or(DEFAULT_IDLE_MS));
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_NETWORK_IDLE_TIMEOUT_MS));
+    let wait = wait_until_idle(idle, timeout, NETWORK_POLL_INTERVAL, || {
+        network_activity(window.clone(), executor_state.clone())
+    });
+    crate::abort::cancellable(&window, wait).await
+}
+
+/// Reads the request count `bridge.js` keeps.
+async fn network_activity<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<NetworkActivity, String> {
+    let data = dom::evaluate(window, NETWORK_ACTIVITY_SCRIPT.to_string(), executor_state).await?;
+    serde_json::from_value(data).map_err(|_| "Network tracking is not installed".to_string())
+}
+
+const NETWORK_ACTIVITY_SCRIPT: &str = r#"const network = window.__MCP_NETWORK__;
+if (!network) return null;
+return { pending: network.pending, quietMs: Math.max(0, Date.now() - network.lastChange) };"#;
+
+/// Polls `check` until it reports no requests in flight for `idle`.
+async fn wait_until_idle<F, Fut>(
+    idle: Duration,
+    timeout: Duration,
+    interval: Duration,
+    mut check: F,
+) -> Result<NetworkIdleReport, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<NetworkActivity, String>>,
+{
+    let start = Instant::now();
+    let mut last = None;
+    let mut last_error = None;
+
+    loop {
+        // Failures are expected while a navigation swaps the document
+        match check().await {
+            Ok(activity) if activity.is_idle_for(idle) => {
+                return Ok(NetworkIdleReport {
+                    idle: true,
+                    pending_at_timeout: None,
+                    waited_ms: start.elapsed().as_millis() as u64,
+                });
+            }
+            Ok(activity) => last = Some(activity),
+            Err(e) => last_error = Some(e),
+        }
+
+        if start.elapsed() >= timeout {
+            return match (last, last_error) {
+                (Some(activity), _) => Ok(NetworkIdleReport {
+                    idle: false,
+                    pending_at_timeout: Some(activity.pending),
+                    waited_ms: start.elapsed().as_millis() as u64,
+                }),
+                (None, error) => Err(format!(
+                    "Timed out after {}ms waiting for network idle: {}",
+                    timeout.as_millis(),
+                    error.unwrap_or_else(|| "no response from the page".to_string())
+                )),
+            };
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A page that starts one fetch answered after `delay`.
+    fn page_with_delayed_fetch(
+        delay: Duration,
+    ) -> impl FnMut() -> std::future::Ready<Result<NetworkActivity, String>> {
+        let start = Instant::now();
+        move || {
+            let elapsed = start.elapsed();
+            let activity = match elapsed.checked_sub(delay) {
+                None => NetworkActivity {
+                    pending: 1,
+                    quiet_ms: elapsed.as_millis() as u64,
+                },
+                Some(since_done) => NetworkActivity {
+                    pending: 0,
+                    quiet_ms: since_done.as_millis() as u64,
+                },
+            };
+            std::future::ready(Ok(activity))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_resolves_once_delayed_fetch_completes() {
+        let report = wait_until_idle(
+            Duration::from_millis(30),
+            Duration::from_secs(2),
+            Duration::from_millis(5),
+            page_with_delayed_fetch(Duration::from_millis(60)),
+        )
+        .await
+        .unwrap();
+
+        assert!(report.idle);
+        // The fetch took 60ms and the page then had to stay quiet for 30ms
+        assert!(report.waited_ms >= 90, "waited {}ms", report.waited_ms);
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()["pendingAtTimeout"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[tokio::test]
+    async fn test_timeout_reports_requests_in_flight() {
+        let report = wait_until_idle(
+            Duration::from_millis(10),
+            Duration::from_millis(30),
+            Duration::from_millis(5),
+            page_with_delayed_fetch(Duration::from_secs(60)),
+        )
+        .await
+        .unwrap();
+
+        assert!(!report.idle);
+        assert_eq!(report.pending_at_timeout, Some(1));
+
+        let err = wait_until_idle(
+            Duration::from_millis(10),
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            || std::future::ready(Err("Network tracking is not installed".to_string())),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("waiting for network idle: Network tracking is not installed"));
+    }
+}
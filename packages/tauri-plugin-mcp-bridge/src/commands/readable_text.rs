@@ -0,0 +1,201 @@
+//! Extracting the readable text of a page for LLM context.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde_json::{json, Value};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Bytes of text returned when the caller doesn't pass `max_bytes`.
+pub const DEFAULT_READABLE_TEXT_BYTES: usize = 64 * 1024;
+
+/// Walks the main content of the page (`<main>` or `[role="main"]`, else
+/// `<body>`) and returns `{ title, url, text }`. Scripts, styles and hidden
+/// elements are skipped; headings, list items, links and code blocks are
+/// written as markdown. Blocks are separated by a blank line, the items of a
+/// list and the rows of a table by a newline.
+const READABLE_TEXT_JS: &str = r#"const SKIPPED = new Set(['script', 'style', 'noscript', 'template', 'svg', 'canvas',
+    'iframe', 'object', 'embed', 'head', 'select', 'input', 'textarea', 'button']);
+const BLOCKS = new Set(['address', 'article', 'aside', 'blockquote', 'details', 'dd', 'div', 'dl',
+    'dt', 'fieldset', 'figcaption', 'figure', 'footer', 'form', 'header', 'hr', 'main', 'nav',
+    'ol', 'p', 'section', 'summary', 'table', 'ul']);
+const blocks = [];
+let line = '';
+// Consecutive blocks of the same `group` (list items, table rows) are
+// separated by a single newline
+const flush = (group) => {
+    const text = line.replace(/ *\n */g, '\n').replace(/ {2,}/g, ' ').trim();
+    if (text) { blocks.push({ text, group: group || null }); }
+    line = '';
+};
+const isHidden = (el) => {
+    if (el.hidden || el.getAttribute('aria-hidden') === 'true') { return true; }
+    const style = getComputedStyle(el);
+    return style.display === 'none' || style.visibility === 'hidden' || style.visibility === 'collapse';
+};
+const walk = (node) => {
+    if (node.nodeType === Node.TEXT_NODE) {
+        line += node.data.replace(/\s+/g, ' ');
+        return;
+    }
+    if (node.nodeType !== Node.ELEMENT_NODE) { return; }
+    const tag = node.localName;
+    if (SKIPPED.has(tag) || isHidden(node)) { return; }
+    const children = () => { node.childNodes.forEach(walk); };
+    const heading = /^h([1-6])$/.exec(tag);
+    if (heading) {
+        flush();
+        line = '#'.repeat(Number(heading[1])) + ' ';
+        children();
+        flush();
+    } else if (tag === 'li') {
+        flush();
+        const list = node.parentElement;
+        line = list && list.localName === 'ol'
+            ? (Array.prototype.indexOf.call(list.children, node) + 1) + '. '
+            : '- ';
+        children();
+        flush(list);
+    } else if (tag === 'pre') {
+        flush();
+        const code = node.innerText.replace(/\n+$/, '');
+        if (code.trim()) { blocks.push({ text: '```\n' + code + '\n```', group: null }); }
+    } else if (tag === 'a' && node.href && !node.href.startsWith('javascript:')) {
+        const start = line.length;
+        children();
+        const label = line.slice(start).replace(/\s+/g, ' ').trim();
+        if (label) { line = line.slice(0, start) + ' [' + label + '](' + node.href + ')'; }
+    } else if (tag === 'br') {
+        line += '\n';
+    } else if (tag === 'tr') {
+        flush();
+        children();
+        flush(node.closest('table'));
+    } else if (tag === 'td' || tag === 'th') {
+        if (line.trim()) { line += ' | '; }
+        children();
+    } else if (BLOCKS.has(tag)) {
+        flush();
+        children();
+        flush();
+    } else {
+        children();
+    }
+};
+const root = document.querySelector('main, [role="main"]') || document.body;
+if (root) { walk(root); }
+flush();
+let text = '';
+blocks.forEach((block, i) => {
+    if (i > 0) { text += block.group && block.group === blocks[i - 1].group ? '\n' : '\n\n'; }
+    text += block.text;
+});
+return { title: document.title, url: location.href, text };"#;
+
+/// Returns the visible text of a page, roughly as a reader-mode extractor
+/// would, with basic markdown structure.
+///
+/// Much cheaper for an LLM than the page's HTML. Text of `<script>`,
+/// `<style>` and form controls is skipped, as are elements that are `hidden`,
+/// `aria-hidden="true"`, `display: none` or `visibility: hidden`. Headings
+/// become `#` lines, list items `-` or `1.` lines, links `[text](href)` and
+/// `<pre>` blocks fenced code. When the page has a `<main>` element, only its
+/// text is returned.
+///
+/// # Arguments
+///
+/// * `window` - The window to read the text of
+/// * `executor_state` - Shared script executor state
+/// * `max_bytes` - Longest text returned, in UTF-8 bytes (default 65536)
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ title, url, text, bytes, truncated }`. `bytes` is the
+///   size of the whole text; when it exceeds `max_bytes`, `text` is cut at a
+///   character boundary and `truncated` is true.
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { text, truncated } = await invoke('plugin:mcp-bridge|get_readable_text', {
+///   maxBytes: 16384
+/// });
+/// ```
+#[command]
+pub async fn get_readable_text<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+    max_bytes: Option<usize>,
+) -> Result<Value, String> {
+    let page = dom::evaluate(window, READABLE_TEXT_JS.to_string(), executor_state).await?;
+    Ok(readable_text_result(
+        page,
+        max_bytes.unwrap_or(DEFAULT_READABLE_TEXT_BYTES),
+    ))
+}
+
+/// Builds the command's result from the script's `{ title, url, text }`,
+/// cutting the text to `max_bytes`.
+fn readable_text_result(page: Value, max_bytes: usize) -> Value {
+    let mut text = page
+        .get("text")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let bytes = text.len();
+    let truncated = bytes > max_bytes;
+    if truncated {
+        let mut end = max_bytes;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+
+    json!({
+        "title": page.get("title").cloned().unwrap_or(Value::Null),
+        "url": page.get("url").cloned().unwrap_or(Value::Null),
+        "text": text,
+        "bytes": bytes,
+        "truncated": truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_skips_hidden_and_script_content() {
+        assert!(READABLE_TEXT_JS.contains("const SKIPPED = new Set(['script', 'style', 'noscript'"));
+        assert!(READABLE_TEXT_JS.contains("el.getAttribute('aria-hidden') === 'true'"));
+        assert!(READABLE_TEXT_JS.contains("style.display === 'none'"));
+        // Hidden elements are skipped before their children are visited
+        let skip = READABLE_TEXT_JS
+            .find("if (SKIPPED.has(tag) || isHidden(node)) { return; }")
+            .unwrap();
+        let children = READABLE_TEXT_JS
+            .find("node.childNodes.forEach(walk)")
+            .unwrap();
+        assert!(skip < children);
+    }
+
+    #[test]
+    fn test_text_is_truncated_at_a_char_boundary() {
+        let page =
+            json!({ "title": "Docs", "url": "http://localhost/", "text": "# Héllo\n\nworld" });
+
+        let whole = readable_text_result(page.clone(), 1024);
+        assert_eq!(whole["text"], "# Héllo\n\nworld");
+        assert_eq!(whole["bytes"], 15);
+        assert_eq!(whole["truncated"], false);
+
+        // "é" spans bytes 3..5, so cutting at 4 keeps "# H"
+        let cut = readable_text_result(page, 4);
+        assert_eq!(cut["text"], "# H");
+        assert_eq!(cut["bytes"], 15);
+        assert_eq!(cut["truncated"], true);
+        assert_eq!(cut["title"], "Docs");
+    }
+}
@@ -0,0 +1,251 @@
+//! Page load state of a window's document.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Default time to wait for a ready state before giving up.
+const DEFAULT_READY_TIMEOUT_MS: u64 = 10_000;
+
+/// Time between `document.readyState` checks while waiting.
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A value of `document.readyState`, in loading order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ReadyState {
+    Loading,
+    Interactive,
+    Complete,
+}
+
+impl ReadyState {
+    /// Parses a `document.readyState` value.
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "loading" => Ok(Self::Loading),
+            "interactive" => Ok(Self::Interactive),
+            "complete" => Ok(Self::Complete),
+            other => Err(format!(
+                "Unknown ready state '{other}' (expected \"loading\", \"interactive\" or \"complete\")"
+            )),
+        }
+    }
+
+    /// The `document.readyState` value.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Loading => "loading",
+            Self::Interactive => "interactive",
+            Self::Complete => "complete",
+        }
+    }
+}
+
+/// Result of `wait_for_ready`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadyStateReport {
+    /// The ready state when the wait ended
+    pub ready_state: String,
+    /// How long the wait took in milliseconds
+    pub waited_ms: u64,
+}
+
+/// Returns the `document.readyState` of a window.
+///
+/// # Arguments
+///
+/// * `window` - The window to check
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(String)` - `"loading"`, `"interactive"` or `"complete"`
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const state = await invoke('plugin:mcp-bridge|get_ready_state');
+/// ```
+#[command]
+pub async fn get_ready_state<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<String, String> {
+    let data = dom::evaluate(
+        window,
+        "return document.readyState".to_string(),
+        executor_state,
+    )
+    .await?;
+
+    match data {
+        Value::String(state) => Ok(state),
+        other => Err(format!("Unexpected readyState: {other}")),
+    }
+}
+
+/// Waits until a window's document has reached a ready state.
+///
+/// `document.readyState` is checked every 100ms, so the wait survives
+/// navigations and reloads that replace the document in the meantime.
+/// Checks that fail while a new page is loading are retried. A state later
+/// than the target also satisfies it, e.g. `complete` for `interactive`.
+///
+/// # Arguments
+///
+/// * `window` - The window to wait on
+/// * `state` - `"interactive"` or `"complete"`
+/// * `timeout_ms` - Maximum time to wait in milliseconds (default 10000)
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(ReadyStateReport)` - `{ readyState, waitedMs }` once the state is
+///   reached
+/// * `Err(String)` - If `state` is invalid, the timeout elapses first or the
+///   wait is cancelled with `abort_all`
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|wait_for_ready', {
+///   state: 'complete',
+///   timeoutMs: 5000
+/// });
+/// ```
+#[command]
+pub async fn wait_for_ready<R: Runtime>(
+    window: WebviewWindow<R>,
+    state: String,
+    timeout_ms: Option<u64>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<ReadyStateReport, String> {
+    let target = ReadyState::parse(&state)?;
+    if target == ReadyState::Loading {
+        return Err("Cannot wait for \"loading\"; use \"interactive\" or \"complete\"".to_string());
+    }
+
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(DEFAULT_READY_TIMEOUT_MS));
+    let wait = wait_until(target, timeout, READY_POLL_INTERVAL, || {
+        get_ready_state(window.clone(), executor_state.clone())
+    });
+    crate::abort::cancellable(&window, wait).await
+}
+
+/// Polls `check` until it reports `target` or a later state.
+async fn wait_until<F, Fut>(
+    target: ReadyState,
+    timeout: Duration,
+    interval: Duration,
+    mut check: F,
+) -> Result<ReadyStateReport, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let start = Instant::now();
+    let mut last = None;
+
+    loop {
+        // Failures are expected while a navigation swaps the document
+        if let Ok(current) = check().await {
+            if ReadyState::parse(&current).is_ok_and(|current| current >= target) {
+                return Ok(ReadyStateReport {
+                    ready_state: current,
+                    waited_ms: start.elapsed().as_millis() as u64,
+                });
+            }
+            last = Some(current);
+        }
+
+        if start.elapsed() >= timeout {
+            return Err(format!(
+                "Timed out after {}ms waiting for readyState '{}' (last seen: {})",
+                timeout.as_millis(),
+                target.as_str(),
+                last.as_deref().unwrap_or("unknown")
+            ));
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// A check reporting `states` in turn, then the last one forever.
+    fn page(states: &[&str]) -> impl FnMut() -> std::future::Ready<Result<String, String>> {
+        let mut states: VecDeque<String> = states.iter().map(|s| s.to_string()).collect();
+        move || {
+            let state = if states.len() > 1 {
+                states.pop_front()
+            } else {
+                states.front().cloned()
+            };
+            std::future::ready(state.ok_or_else(|| "no document".to_string()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_waits_for_complete_on_loading_page() {
+        let report = wait_until(
+            ReadyState::Complete,
+            Duration::from_secs(1),
+            Duration::from_millis(1),
+            page(&["loading", "interactive", "complete"]),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.ready_state, "complete");
+    }
+
+    #[tokio::test]
+    async fn test_later_state_satisfies_target() {
+        let report = wait_until(
+            ReadyState::Interactive,
+            Duration::from_secs(1),
+            Duration::from_millis(1),
+            page(&["complete"]),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(report.ready_state, "complete");
+    }
+
+    #[tokio::test]
+    async fn test_times_out_with_last_state() {
+        let err = wait_until(
+            ReadyState::Complete,
+            Duration::from_millis(20),
+            Duration::from_millis(5),
+            page(&["interactive"]),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.contains("waiting for readyState 'complete' (last seen: interactive)"));
+    }
+
+    #[test]
+    fn test_parse_ready_states() {
+        assert_eq!(
+            ReadyState::parse("interactive"),
+            Ok(ReadyState::Interactive)
+        );
+        assert!(ReadyState::Loading < ReadyState::Complete);
+        assert!(ReadyState::parse("done").is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Returning a window to a clean state between test scenarios.
+//!
+//! The webview only exposes one native clearing API, which wipes cache,
+//! cookies and storage together. A full reset uses it; a partial reset
+//! clears the requested parts individually:
+//!
+//! * Storage - `localStorage`, `sessionStorage` and IndexedDB, from the page
+//! * Cookies - through the native cookie store (http and https URLs), plus
+//!   `document.cookie` for cookies of custom schemes such as `tauri://`
+//! * Cache - Cache Storage from the page. The HTTP cache can't be cleared on
+//!   its own, which the response notes.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// What `reset_window` should clear.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetOptions {
+    pub clear_cache: bool,
+    pub clear_storage: bool,
+    pub clear_cookies: bool,
+}
+
+impl ResetOptions {
+    /// Resolves optional flags, each defaulting to `true`.
+    pub fn new(
+        clear_cache: Option<bool>,
+        clear_storage: Option<bool>,
+        clear_cookies: Option<bool>,
+    ) -> Self {
+        Self {
+            clear_cache: clear_cache.unwrap_or(true),
+            clear_storage: clear_storage.unwrap_or(true),
+            clear_cookies: clear_cookies.unwrap_or(true),
+        }
+    }
+
+    /// Whether everything is cleared, so the native API can be used.
+    fn is_full(&self) -> bool {
+        self.clear_cache && self.clear_storage && self.clear_cookies
+    }
+
+    /// Names of the parts to clear, as reported in [`ResetReport::cleared`].
+    fn parts(&self) -> Vec<String> {
+        [
+            (self.clear_cache, "cache"),
+            (self.clear_storage, "storage"),
+            (self.clear_cookies, "cookies"),
+        ]
+        .into_iter()
+        .filter(|(enabled, _)| *enabled)
+        .map(|(_, name)| name.to_string())
+        .collect()
+    }
+}
+
+/// Result of `reset_window`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResetReport {
+    /// Label of the reset window
+    pub window_label: String,
+    /// Parts that were cleared: `"cache"`, `"storage"` and/or `"cookies"`
+    pub cleared: Vec<String>,
+    /// Whether the webview's native clearing API was used
+    pub native: bool,
+    /// Anything that couldn't be cleared completely
+    pub notes: Vec<String>,
+}
+
+/// Clears a window's cache, storage and/or cookies, then reloads it.
+///
+/// Each flag defaults to `true`, so a call without options performs a full
+/// reset through the webview's native API. Parts that can't be cleared on
+/// this platform are listed in `notes` rather than failing the call.
+///
+/// # Arguments
+///
+/// * `window` - The window to reset
+/// * `clear_cache` - Clear Cache Storage (and the HTTP cache on a full reset)
+/// * `clear_storage` - Clear `localStorage`, `sessionStorage` and IndexedDB
+/// * `clear_cookies` - Delete the window's cookies
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(ResetReport)` - What was cleared, once the reload has been started
+/// * `Err(String)` - If clearing from the page or reloading fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// // Full reset
+/// await invoke('plugin:mcp-bridge|reset_window');
+///
+/// // Keep cookies (e.g. a login session), clear everything else
+/// const { cleared, notes } = await invoke('plugin:mcp-bridge|reset_window', {
+///   clearCookies: false
+/// });
+/// ```
+#[command]
+pub async fn reset_window<R: Runtime>(
+    window: WebviewWindow<R>,
+    clear_cache: Option<bool>,
+    clear_storage: Option<bool>,
+    clear_cookies: Option<bool>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<ResetReport, String> {
+    let options = ResetOptions::new(clear_cache, clear_storage, clear_cookies);
+    let mut report = ResetReport {
+        window_label: window.label().to_string(),
+        cleared: options.parts(),
+        native: false,
+        notes: Vec::new(),
+    };
+
+    if options.is_full() {
+        match window.clear_all_browsing_data() {
+            Ok(()) => report.native = true,
+            Err(e) => report.notes.push(format!(
+                "Native clearing failed ({e}); cleared from the page instead"
+            )),
+        }
+    }
+
+    if !report.native {
+        if options.clear_cookies {
+            if let Err(e) = delete_native_cookies(&window) {
+                report
+                    .notes
+                    .push(format!("Native cookie store not cleared: {e}"));
+            }
+        }
+
+        let script = clear_script(options);
+        if let Some(script) = script {
+            let data = dom::evaluate(window.clone(), script, executor_state).await?;
+            report.notes.extend(script_notes(&data));
+        }
+
+        if options.clear_cache {
+            report.notes.push(
+                "HTTP cache not cleared: the webview only clears it together with storage and cookies"
+                    .to_string(),
+            );
+        }
+    }
+
+    window
+        .reload()
+        .map_err(|e| format!("Failed to reload window: {e}"))?;
+
+    Ok(report)
+}
+
+/// Deletes every cookie in the window's native cookie store.
+fn delete_native_cookies<R: Runtime>(window: &WebviewWindow<R>) -> tauri::Result<()> {
+    for cookie in window.cookies()? {
+        window.delete_cookie(cookie)?;
+    }
+    Ok(())
+}
+
+/// Builds the script clearing the parts of `options` reachable from the
+/// page, or `None` if there is nothing to clear there.
+///
+/// The script returns `{ notes }` for parts it could only clear partially.
+fn clear_script(options: ResetOptions) -> Option<String> {
+    if !(options.clear_cache || options.clear_storage || options.clear_cookies) {
+        return None;
+    }
+
+    Some(format!(
+        r#"const clearCache = {cache};
+const clearStorage = {storage};
+const clearCookies = {cookies};
+const notes = [];
+if (clearStorage) {{
+    for (const name of ['localStorage', 'sessionStorage']) {{
+        try {{ window[name].clear(); }} catch (e) {{ notes.push(name + ' not cleared: ' + e.message); }}
+    }}
+    if (window.indexedDB && typeof indexedDB.databases === 'function') {{
+        const databases = await indexedDB.databases();
+        // A database the page still has open stays "blocked" until the reload closes it
+        await Promise.all(databases.map((db) => new Promise((resolve) => {{
+            const request = indexedDB.deleteDatabase(db.name);
+            request.onsuccess = request.onerror = request.onblocked = () => resolve();
+        }})));
+    }} else if (window.indexedDB) {{
+        notes.push('IndexedDB not cleared: this webview cannot list its databases');
+    }}
+}}
+if (clearCache && window.caches) {{
+    for (const key of await caches.keys()) {{ await caches.delete(key); }}
+}}
+if (clearCookies) {{
+    for (const cookie of document.cookie.split(';')) {{
+        const name = cookie.split('=')[0].trim();
+        if (name) {{ document.cookie = name + '=; expires=Thu, 01 Jan 1970 00:00:00 GMT; path=/'; }}
+    }}
+}}
+return {{ notes }};"#,
+        cache = options.clear_cache,
+        storage = options.clear_storage,
+        cookies = options.clear_cookies,
+    ))
+}
+
+/// Notes reported by [`clear_script`].
+fn script_notes(data: &Value) -> Vec<String> {
+    data.get("notes")
+        .and_then(Value::as_array)
+        .map(|notes| {
+            notes
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_options_default_to_full_reset() {
+        let options = ResetOptions::new(None, None, None);
+        assert!(options.is_full());
+        assert_eq!(options.parts(), vec!["cache", "storage", "cookies"]);
+
+        let options = ResetOptions::new(None, None, Some(false));
+        assert!(!options.is_full());
+        assert_eq!(options.parts(), vec!["cache", "storage"]);
+    }
+
+    #[test]
+    fn test_storage_is_emptied_by_partial_reset() {
+        let script = clear_script(ResetOptions::new(Some(false), Some(true), Some(false))).unwrap();
+
+        assert!(script.contains("const clearStorage = true;"));
+        assert!(script.contains("const clearCache = false;"));
+        assert!(script.contains("const clearCookies = false;"));
+        for api in ["localStorage", "sessionStorage", "indexedDB.deleteDatabase"] {
+            assert!(script.contains(api), "script doesn't clear {api}");
+        }
+    }
+
+    #[test]
+    fn test_nothing_to_clear_skips_script() {
+        assert_eq!(
+            clear_script(ResetOptions::new(Some(false), Some(false), Some(false))),
+            None
+        );
+        assert!(ResetOptions::new(Some(false), Some(false), Some(false))
+            .parts()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_script_notes_are_collected() {
+        let data = json!({ "notes": ["IndexedDB not cleared", 3] });
+        assert_eq!(script_notes(&data), vec!["IndexedDB not cleared"]);
+        assert!(script_notes(&Value::Null).is_empty());
+    }
+}
@@ -0,0 +1,341 @@
+//! Read-only access to bundled app resources.
+//!
+//! Both commands are confined to the app's resource directory as resolved by
+//! Tauri's path resolver; paths that escape it through `..`, absolute paths
+//! or symlinks are rejected.
+
+use serde_json::Value;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager, Runtime};
+
+/// Default maximum number of bytes returned by `read_resource` (1 MiB).
+const DEFAULT_MAX_RESOURCE_BYTES: usize = 1024 * 1024;
+
+/// Number of leading bytes inspected when classifying content as binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Lists files in the app's resource directory.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `glob` - Optional pattern matched against resource-relative paths.
+///   Supports `*` (within a path segment), `**` (across segments) and `?`
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ resourceDir, files }` where `files` holds sorted,
+///   `/`-separated paths relative to the resource directory
+/// * `Err(String)` - If the resource directory cannot be resolved or read
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { files } = await invoke('plugin:mcp-bridge|list_resources', {
+///   glob: 'icons/**/*.png'
+/// });
+/// ```
+#[command]
+pub async fn list_resources<R: Runtime>(
+    app: AppHandle<R>,
+    glob: Option<String>,
+) -> Result<Value, String> {
+    let resource_dir = resource_dir(&app)?;
+    let files = list_files(&resource_dir, glob.as_deref())?;
+
+    Ok(serde_json::json!({
+        "resourceDir": resource_dir.display().to_string(),
+        "files": files
+    }))
+}
+
+/// Reads a file from the app's resource directory.
+///
+/// Text files are returned as UTF-8; anything containing NUL bytes or invalid
+/// UTF-8 is treated as binary and returned base64-encoded.
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `path` - Path relative to the resource directory
+/// * `max_bytes` - Maximum number of bytes to return (default 1 MiB)
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ path, sizeBytes, truncated, binary, encoding, content }`
+///   where `encoding` is `"utf8"` or `"base64"`
+/// * `Err(String)` - If the path is outside the resource directory or cannot
+///   be read
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const file = await invoke('plugin:mcp-bridge|read_resource', {
+///   path: 'config/defaults.json',
+///   maxBytes: 4096
+/// });
+/// ```
+#[command]
+pub async fn read_resource<R: Runtime>(
+    app: AppHandle<R>,
+    path: String,
+    max_bytes: Option<usize>,
+) -> Result<Value, String> {
+    let resource_dir = resource_dir(&app)?;
+    read_file_within(
+        &resource_dir,
+        &path,
+        max_bytes.unwrap_or(DEFAULT_MAX_RESOURCE_BYTES),
+    )
+}
+
+fn resource_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    app.path()
+        .resource_dir()
+        .map_err(|e| format!("Failed to resolve resource directory: {e}"))
+}
+
+/// Recursively lists files under `dir` matching `glob`.
+fn list_files(dir: &Path, glob: Option<&str>) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = std::fs::read_dir(&current)
+            .map_err(|e| format!("Failed to read {}: {e}", current.display()))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Don't follow directory symlinks, they may point outside `dir`
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Some(relative) = relative_path(dir, &path) else {
+                continue;
+            };
+            if glob.map_or(true, |pattern| glob_matches(pattern, &relative)) {
+                files.push(relative);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Returns `path` relative to `base` with `/` separators.
+fn relative_path(base: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Reads up to `max_bytes` of `path`, which must resolve inside `dir`.
+fn read_file_within(dir: &Path, path: &str, max_bytes: usize) -> Result<Value, String> {
+    let dir = dir.canonicalize().map_err(|e| {
+        format!(
+            "Resource directory {} is not accessible: {e}",
+            dir.display()
+        )
+    })?;
+    let resolved = dir
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("Resource {path} not found: {e}"))?;
+    if !resolved.starts_with(&dir) {
+        return Err(format!("Resource {path} is outside the resource directory"));
+    }
+
+    // Only the bytes returned are read, however large the file is
+    let read_error = |e: std::io::Error| format!("Failed to read {path}: {e}");
+    let file = File::open(&resolved).map_err(read_error)?;
+    let size_bytes = file.metadata().map_err(read_error)?.len();
+    let mut bytes = Vec::with_capacity(size_bytes.min(max_bytes as u64) as usize);
+    file.take(max_bytes as u64)
+        .read_to_end(&mut bytes)
+        .map_err(read_error)?;
+    let truncated = size_bytes > max_bytes as u64;
+    let content = bytes.as_slice();
+
+    let (binary, encoding, content) = match text_content(content, truncated) {
+        Some(text) => (false, "utf8", text.to_string()),
+        None => {
+            use base64::Engine as _;
+            (
+                true,
+                "base64",
+                base64::engine::general_purpose::STANDARD.encode(content),
+            )
+        }
+    };
+
+    Ok(serde_json::json!({
+        "path": path,
+        "sizeBytes": size_bytes,
+        "truncated": truncated,
+        "binary": binary,
+        "encoding": encoding,
+        "content": content
+    }))
+}
+
+/// Returns the content as text, or `None` if it looks binary.
+///
+/// A multi-byte character cut off by truncation doesn't make the content
+/// binary; the partial character is dropped.
+fn text_content(bytes: &[u8], truncated: bool) -> Option<&str> {
+    let sniff = &bytes[..bytes.len().min(BINARY_SNIFF_BYTES)];
+    if sniff.contains(&0) {
+        return None;
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some(text),
+        Err(e) if truncated && e.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..e.valid_up_to()]).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+/// Matches a `/`-separated path against a glob pattern.
+fn glob_matches(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').collect();
+    match_segments(&pattern, &path)
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((segment, rest)) => match path.split_first() {
+            Some((name, path_rest)) => {
+                match_segment(segment.as_bytes(), name.as_bytes())
+                    && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| match_segment(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && match_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && match_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_resources() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("mcp-resources-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("icons/large")).unwrap();
+        fs::write(dir.join("index.html"), "<h1>hi</h1>").unwrap();
+        fs::write(dir.join("icons/app.png"), [0x89, b'P', b'N', b'G', 0, 1, 2]).unwrap();
+        fs::write(dir.join("icons/large/app.png"), [0x89, 0]).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        assert!(glob_matches("*.html", "index.html"));
+        assert!(!glob_matches("*.html", "pages/index.html"));
+        assert!(glob_matches("**/*.png", "icons/large/app.png"));
+        assert!(glob_matches("**/*.png", "app.png"));
+        assert!(glob_matches("icons/?pp.png", "icons/app.png"));
+        assert!(!glob_matches("icons/*.png", "icons/large/app.png"));
+    }
+
+    #[test]
+    fn test_list_files_filters_by_glob() {
+        let dir = temp_resources();
+
+        let all = list_files(&dir, None).unwrap();
+        let icons = list_files(&dir, Some("icons/**/*.png")).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            all,
+            vec!["icons/app.png", "icons/large/app.png", "index.html"]
+        );
+        assert_eq!(icons, vec!["icons/app.png", "icons/large/app.png"]);
+    }
+
+    #[test]
+    fn test_read_detects_text_and_binary() {
+        let dir = temp_resources();
+
+        let text = read_file_within(&dir, "index.html", 1024).unwrap();
+        let binary = read_file_within(&dir, "icons/app.png", 1024).unwrap();
+        let truncated = read_file_within(&dir, "index.html", 4).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(text["binary"], false);
+        assert_eq!(text["encoding"], "utf8");
+        assert_eq!(text["content"], "<h1>hi</h1>");
+
+        assert_eq!(binary["binary"], true);
+        assert_eq!(binary["encoding"], "base64");
+        assert_eq!(binary["content"], "iVBORwABAg==");
+
+        assert_eq!(truncated["truncated"], true);
+        assert_eq!(truncated["sizeBytes"], 11);
+        assert_eq!(truncated["content"], "<h1>");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_large_files_are_read_only_up_to_the_limit() {
+        let dir = temp_resources();
+        // Sparse, so it takes no disk space; reading it whole would need 8 GiB
+        let huge = File::create(dir.join("huge.bin")).unwrap();
+        huge.set_len(8 << 30).unwrap();
+
+        let read = read_file_within(&dir, "huge.bin", 6).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(read["truncated"], true);
+        assert_eq!(read["sizeBytes"], 8u64 << 30);
+        assert_eq!(read["content"], "AAAAAAAA");
+    }
+
+    #[test]
+    fn test_truncation_inside_multibyte_char_is_still_text() {
+        let bytes = "héllo".as_bytes();
+
+        assert_eq!(text_content(&bytes[..2], true), Some("h"));
+        assert_eq!(text_content(&bytes[..2], false), None);
+    }
+
+    #[test]
+    fn test_reads_outside_resource_dir_are_rejected() {
+        let dir = temp_resources();
+        let outside = dir.with_extension("secret");
+        fs::write(&outside, "secret").unwrap();
+
+        let name = outside.file_name().unwrap().to_str().unwrap();
+        let err = read_file_within(&dir.join("icons"), &format!("../../{name}"), 1024);
+        fs::remove_file(&outside).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(err.unwrap_err().contains("outside the resource directory"));
+    }
+}
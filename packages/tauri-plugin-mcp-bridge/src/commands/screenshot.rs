@@ -1,6 +1,16 @@
 //! Native screenshot capture.
 
-use tauri::{command, Runtime, WebviewWindow};
+use crate::commands::dom;
+use crate::config::Config;
+use crate::screenshot::{
+    compare_images, load_baseline, Background, CapturedImage, ColorDepth, CompareOptions,
+    FrameCache, FrameEncoder, ScreenshotComparison, WindowCapture,
+};
+use crate::script_registry::js_string_literal;
+use crate::window_queue::WindowQueue;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{command, Manager, Runtime, WebviewWindow};
 
 /// Native screenshot command using platform-specific APIs.
 ///
@@ -13,31 +23,658 @@ use tauri::{command, Runtime, WebviewWindow};
 ///
 /// **Note**: This captures only what's currently visible in the viewport.
 /// The agent should scroll content into view before taking screenshots if needed.
+/// Inner scroll containers such as chat panes and data grids can be captured
+/// whole with `scroll_container_selector`.
+///
+/// When `canvas_selector` is given, the viewport is not captured at all.
+/// The pixels of that `<canvas>` are read from the page with
+/// `canvas.toDataURL()` instead. This is the reliable way to capture WebGL
+/// content on platforms where native capture misses GPU layers (such as the
+/// Linux JavaScript fallback). The read happens inside a
+/// `requestAnimationFrame` callback, while the current frame is still in the
+/// drawing buffer, so WebGL contexts created without `preserveDrawingBuffer`
+/// don't come back blank. Limits:
+///
+/// - Canvases tainted by cross-origin images or video can't be read
+/// - Canvases handed to a worker with `transferControlToOffscreen()` can't be
+///   read from the page
+/// - The image has the canvas's backing-store size, not its on-screen size,
+///   and includes nothing drawn over the canvas by other elements
 ///
 /// # Arguments
 ///
 /// * `window` - The window to capture
-/// * `format` - Image format ("png" or "jpeg")
+/// * `format` - Image format ("png" or "jpeg"), or `"rgba"` for the raw,
+///   unencoded pixels
 /// * `quality` - JPEG quality (0-100), only used for JPEG format
+/// * `background` - `"transparent"` (default), `"white"` or `"#rrggbb"`; non-transparent
+///   values flatten the capture onto that color before encoding
+/// * `settle_ms` - Time to wait before capturing so in-flight rendering after a
+///   navigation or state change can finish (default 0). Adds latency equal to
+///   the settle time.
+/// * `canvas_selector` - CSS selector of a `<canvas>` to capture instead of
+///   the viewport
+/// * `diff` - Compare with the previous `diff` capture of the same window (or
+///   canvas) and send only the bounding box of the changed pixels. See
+///   [`FrameCache::diff`].
+/// * `color_mode` - `"rgb"` (default) or `"grayscale"`
+/// * `max_colors` - Quantize to at most this many colors (gray levels in
+///   grayscale mode) for smaller PNGs. See [`ColorDepth::new`].
+/// * `scroll_container_selector` - CSS selector of a scrollable element to
+///   capture whole instead of the viewport. It is scrolled through its
+///   `scrollHeight`, captured slice by slice and stitched into one image of
+///   its content box; see [`crate::screenshot::stitch_slices`]. The element
+///   is scrolled into view first, must fit inside the viewport, and gets its
+///   scroll position back afterwards.
+/// * `scroll_overlap` - CSS pixels at the top of the container hidden by
+///   sticky content, such as a table header, dropped from every slice after
+///   the first (default 0)
 ///
 /// # Returns
 ///
-/// * `Ok(String)` - Base64-encoded image data URL
-/// * `Err(String)` - Error message if capture fails
+/// * `Ok(CapturedImage)` - `{ dataUrl, warning?, monitor? }` with the
+///   base64-encoded image data URL. `warning` is set when the requested
+///   background or format could not be applied and the original PNG was
+///   returned instead. `monitor` holds `{ monitorName, monitorScaleFactor,
+///   monitorPosition }` for the display the window is on; see
+///   [`screenshot::window_monitor`](crate::screenshot::window_monitor) for how
+///   windows spanning several displays are handled. Canvas captures have no
+///   `monitor`. With `diff`, the image covers only `changedRegion`
+///   (`{ x, y, width, height }`), or the result is just `{ unchanged: true }`
+///   when nothing changed. Captures with reduced colors also report the
+///   applied `colorMode` and `maxColors`. `rgba` captures have no `dataUrl`;
+///   their pixels are in `rgba` as `{ width, height, stride, data }`, laid
+///   out as described on [`RgbaPixels`](crate::screenshot::RgbaPixels).
+/// * `Err(String)` - Error message if capture fails, if `diff` is combined
+///   with `rgba`, if `canvas_selector` is combined with
+///   `scroll_container_selector`, if the scroll container is missing or
+///   doesn't fit the viewport, or if the canvas is missing, tainted or
+///   transferred to an `OffscreenCanvas`. Captures taken before the webview
+///   has painted fail with a message starting `Webview not ready:` (after
+///   one automatic retry), which callers can retry.
 #[command]
+#[allow(clippy::too_many_arguments)]
 pub async fn capture_native_screenshot<R: Runtime>(
     window: WebviewWindow<R>,
     format: Option<String>,
     quality: Option<u8>,
-) -> Result<String, String> {
+    background: Option<String>,
+    settle_ms: Option<u64>,
+    canvas_selector: Option<String>,
+    diff: Option<bool>,
+    color_mode: Option<String>,
+    max_colors: Option<u16>,
+    scroll_container_selector: Option<String>,
+    scroll_overlap: Option<u32>,
+) -> Result<CapturedImage, String> {
+    // Use the screenshot module for viewport capture
+    use crate::screenshot;
+
     let format = format.unwrap_or_else(|| "png".to_string());
     let quality = quality.unwrap_or(90);
+    let background = match background {
+        Some(value) => screenshot::Background::parse(&value).map_err(|e| e.to_string())?,
+        None => screenshot::Background::Transparent,
+    };
+    let color_mode = match color_mode {
+        Some(value) => screenshot::ColorMode::parse(&value).map_err(|e| e.to_string())?,
+        None => screenshot::ColorMode::Rgb,
+    };
+    let color = ColorDepth::new(color_mode, max_colors).map_err(|e| e.to_string())?;
+    let diff = diff.unwrap_or(false);
+    if diff && format == "rgba" {
+        return Err("diff does not support format \"rgba\"".to_string());
+    }
+    if canvas_selector.is_some() && scroll_container_selector.is_some() {
+        return Err("Pass either canvasSelector or scrollContainerSelector, not both".to_string());
+    }
 
-    // Use the screenshot module for viewport capture
-    use crate::screenshot;
+    // Hold the window for the whole capture when per-window serialization is on
+    let _window_guard = match window.try_state::<WindowQueue>() {
+        Some(queue) => queue.acquire(window.label()).await,
+        None => None,
+    };
+
+    let mut encoder = FrameEncoder::new();
+    let capture = async {
+        match (&canvas_selector, &scroll_container_selector) {
+            (Some(selector), _) => {
+                capture_canvas(
+                    &window,
+                    selector,
+                    &format,
+                    quality,
+                    background,
+                    color,
+                    &mut encoder,
+                )
+                .await
+            }
+            (None, Some(selector)) => {
+                capture_scroll_container(
+                    &window,
+                    selector,
+                    scroll_overlap.unwrap_or(0),
+                    &format,
+                    quality,
+                    background,
+                    color,
+                    &mut encoder,
+                )
+                .await
+            }
+            (None, None) => screenshot::capture_viewport_screenshot(
+                &window,
+                &format,
+                quality,
+                background,
+                color,
+                &mut encoder,
+            )
+            .await
+            .map_err(|e| e.to_string()),
+        }
+    };
+
+    let image = after_settle(settle_ms.unwrap_or(0), capture).await?;
+    if !diff {
+        return Ok(image);
+    }
+
+    // Viewport, canvas and container frames of a window are compared separately
+    let key = match (&canvas_selector, &scroll_container_selector) {
+        (Some(selector), _) => format!("{}|{selector}", window.label()),
+        (None, Some(selector)) => format!("{}|scroll|{selector}", window.label()),
+        (None, None) => window.label().to_string(),
+    };
+    window
+        .state::<FrameCache>()
+        .diff(&key, image, &format, quality)
+}
+
+/// Captures the viewport and compares it with a baseline image on disk.
+///
+/// The capture is taken like a PNG [`capture_native_screenshot`] and
+/// compared pixel by pixel, so only the verdict crosses the connection. A
+/// pixel differs when any channel, alpha included, is further apart than
+/// `threshold` allows. The baseline must have the capture's size in device
+/// pixels, so record it at the same window size and scale factor.
+///
+/// # Arguments
+///
+/// * `window` - The window to capture
+/// * `baseline_path` - Path of the baseline PNG inside the directory set
+///   with [`Builder::baselines_dir`](crate::Builder::baselines_dir)
+/// * `threshold` - Per-channel tolerance as a fraction of the channel range,
+///   0-1 (default 0, exact pixels)
+/// * `max_diff_ratio` - Fraction of differing pixels, 0-1, that still counts
+///   as a match (default 0)
+/// * `diff_image` - Also return a PNG with the differing pixels in red over
+///   the faded baseline (default false)
+///
+/// # Returns
+///
+/// * `Ok(ScreenshotComparison)` - `{ match, diffPixelCount, diffRatio,
+///   diffImageDataUrl? }`
+/// * `Err(String)` - If no baselines directory is configured, an option is
+///   out of range, the baseline is outside the directory or can't be loaded,
+///   the capture fails, or the sizes differ
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const result = await invoke('plugin:mcp-bridge|compare_screenshot', {
+///   baselinePath: 'settings.png',
+///   threshold: 0.02,
+///   diffImage: true
+/// });
+/// if (!result.match) console.log(`${result.diffPixelCount} pixels changed`);
+/// ```
+#[command]
+pub async fn compare_screenshot<R: Runtime>(
+    window: WebviewWindow<R>,
+    baseline_path: String,
+    threshold: Option<f64>,
+    max_diff_ratio: Option<f64>,
+    diff_image: Option<bool>,
+) -> Result<ScreenshotComparison, String> {
+    let options = CompareOptions::new(threshold, max_diff_ratio, diff_image)?;
+    let baselines_dir = window
+        .try_state::<Config>()
+        .and_then(|config| config.baselines_dir.clone());
+    let baseline = load_baseline(&baseline_path, baselines_dir.as_deref())?;
+
+    let _window_guard = match window.try_state::<WindowQueue>() {
+        Some(queue) => queue.acquire(window.label()).await,
+        None => None,
+    };
+    let capture = crate::screenshot::capture_viewport_screenshot(
+        &window,
+        "png",
+        100,
+        Background::Transparent,
+        ColorDepth::default(),
+        &mut FrameEncoder::new(),
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let (_, bytes) = crate::screencast::decode_data_url(&capture.data_url)?;
+    let current = image::load_from_memory(&bytes)
+        .map_err(|e| format!("Failed to decode capture for comparison: {e}"))?
+        .to_rgba8();
+    compare_images(&baseline, &current, options)
+}
+
+/// Captures the whole native window, including its frame and titlebar.
+///
+/// Unlike [`capture_native_screenshot`], which captures the webview viewport,
+/// this shows the window as it appears on screen:
+/// - macOS: `CGWindowListCreateImage`, which needs the Screen Recording
+///   permission
+/// - Windows: `PrintWindow`, cropped to the visible frame
+/// - Linux, iOS and Android: no window capture; the viewport is captured and
+///   `method` is `"viewport"`
+///
+/// A borderless window has no chrome, so its capture equals the viewport
+/// capture.
+///
+/// # Arguments
+///
+/// * `window` - The window to capture
+/// * `format` - Image format ("png" or "jpeg")
+/// * `quality` - JPEG quality (0-100), only used for JPEG format
+///
+/// # Returns
+///
+/// * `Ok(WindowCapture)` - `{ dataUrl, width, height, chromeIncluded, method,
+///   monitor? }`, with the dimensions of the captured image in pixels.
+///   `chromeIncluded` is false for borderless windows and viewport captures.
+/// * `Err(String)` - Error message if capture fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { dataUrl, chromeIncluded } = await invoke('plugin:mcp-bridge|capture_window_with_chrome', {
+///   format: 'png'
+/// });
+/// ```
+#[command]
+pub async fn capture_window_with_chrome<R: Runtime>(
+    window: WebviewWindow<R>,
+    format: Option<String>,
+    quality: Option<u8>,
+) -> Result<WindowCapture, String> {
+    let _window_guard = match window.try_state::<WindowQueue>() {
+        Some(queue) => queue.acquire(window.label()).await,
+        None => None,
+    };
+
+    crate::screenshot::capture_window_screenshot(
+        &window,
+        format.as_deref().unwrap_or("png"),
+        quality.unwrap_or(90),
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Captures one screencast frame of a window's viewport.
+///
+/// `encoder` belongs to the stream, so JPEG frames reuse the buffers of the
+/// previous frame instead of allocating their own.
+pub(crate) async fn capture_frame<R: Runtime>(
+    window: &WebviewWindow<R>,
+    format: &str,
+    quality: Option<u8>,
+    encoder: &mut FrameEncoder,
+) -> Result<CapturedImage, String> {
+    let _window_guard = match window.try_state::<WindowQueue>() {
+        Some(queue) => queue.acquire(window.label()).await,
+        None => None,
+    };
+
+    crate::screenshot::capture_viewport_screenshot(
+        window,
+        format,
+        quality.unwrap_or(90),
+        Background::Transparent,
+        ColorDepth::default(),
+        encoder,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Reads the pixels of the `<canvas>` matching `selector` and encodes them.
+async fn capture_canvas<R: Runtime>(
+    window: &WebviewWindow<R>,
+    selector: &str,
+    format: &str,
+    quality: u8,
+    background: Background,
+    color: ColorDepth,
+    encoder: &mut FrameEncoder,
+) -> Result<CapturedImage, String> {
+    // The capture holds the window, so the script must not queue for it again
+    let result = dom::evaluate_in_held_window(window, canvas_script(selector)).await?;
+    let data_url = result
+        .as_str()
+        .ok_or_else(|| "Canvas capture returned no image".to_string())?;
+    let (_, png) = crate::screencast::decode_data_url(data_url)?;
+
+    Ok(crate::screenshot::encode_capture(
+        png, format, quality, background, color, encoder,
+    ))
+}
+
+/// Builds the script returning a PNG data URL of the canvas matching `selector`.
+fn canvas_script(selector: &str) -> String {
+    let selector = js_string_literal(selector);
+    format!(
+        r#"const selector = {selector};
+const canvas = document.querySelector(selector);
+if (!canvas) {{ throw new Error('No element matches selector: ' + selector); }}
+if (!(canvas instanceof HTMLCanvasElement)) {{ throw new Error('Element is not a <canvas>: ' + selector); }}
+if (!canvas.width || !canvas.height) {{ throw new Error('Canvas has no pixels: ' + selector); }}
+// Read during a frame so WebGL buffers without preserveDrawingBuffer still hold it;
+// the timeout covers hidden windows, where animation frames are paused
+return await new Promise((resolve, reject) => {{
+    let done = false;
+    let fallback;
+    const read = () => {{
+        if (done) {{ return; }}
+        done = true;
+        clearTimeout(fallback);
+        try {{
+            resolve(canvas.toDataURL('image/png'));
+        }} catch (e) {{
+            if (e && e.name === 'SecurityError') {{
+                reject(new Error('Canvas is tainted by cross-origin content and cannot be read: ' + selector));
+            }} else if (e && e.name === 'InvalidStateError') {{
+                reject(new Error('Canvas was transferred to an OffscreenCanvas and cannot be read from the page: ' + selector));
+            }} else {{
+                reject(e);
+            }}
+        }}
+    }};
+    requestAnimationFrame(read);
+    fallback = setTimeout(read, 1000);
+}});"#
+    )
+}
+
+/// Captures the whole content of the scroll container matching `selector` by
+/// stitching viewport captures taken at successive scroll offsets.
+#[allow(clippy::too_many_arguments)]
+async fn capture_scroll_container<R: Runtime>(
+    window: &WebviewWindow<R>,
+    selector: &str,
+    overlap: u32,
+    format: &str,
+    quality: u8,
+    background: Background,
+    color: ColorDepth,
+    encoder: &mut FrameEncoder,
+) -> Result<CapturedImage, String> {
+    // The capture holds the window, so its scripts must not queue for it again
+    let evaluate = |script: String| dom::evaluate_in_held_window(window, script);
+    let metrics = evaluate(container_metrics_script(selector)).await?;
+    let number = |key: &str| {
+        metrics[key]
+            .as_f64()
+            .ok_or_else(|| format!("Scroll container metrics have no {key}"))
+    };
+    let (left, top, width, height) = (
+        number("left")?,
+        number("top")?,
+        number("width")?,
+        number("height")?,
+    );
+    let (scroll_height, viewport_width) = (number("scrollHeight")?, number("viewportWidth")?);
+    let scroll_top = number("scrollTop")?;
+    let offsets = crate::screenshot::slice_offsets(scroll_height as u32, height as u32, overlap)?;
+
+    let mut slices = Vec::with_capacity(offsets.len());
+    let mut scale = 1.0;
+    let captured = async {
+        for offset in offsets {
+            let reached = evaluate(scroll_container_script(selector, offset)).await?;
+            let capture = crate::screenshot::capture_viewport_screenshot(
+                window,
+                "png",
+                100,
+                Background::Transparent,
+                ColorDepth::default(),
+                &mut FrameEncoder::new(),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+            let (_, bytes) = crate::screencast::decode_data_url(&capture.data_url)?;
+            let viewport = image::load_from_memory(&bytes)
+                .map_err(|e| format!("Failed to decode scroll container slice: {e}"))?
+                .to_rgba8();
+
+            // Image pixels per CSS pixel, from the capture itself
+            scale = f64::from(viewport.width()) / viewport_width.max(1.0);
+            let px = |css: f64| (css * scale).round().max(0.0) as u32;
+            let (x, y) = (px(left), px(top));
+            let slice = image::imageops::crop_imm(
+                &viewport,
+                x,
+                y,
+                px(width).min(viewport.width().saturating_sub(x)),
+                px(height).min(viewport.height().saturating_sub(y)),
+            )
+            .to_image();
+            slices.push((reached.as_u64().unwrap_or(u64::from(offset)) as u32, slice));
+        }
+        Ok::<(), String>(())
+    }
+    .await;
+
+    // Put the container back where the page had it, even after a failure
+    let restore = format!(
+        "const el = document.querySelector({});
+if (el) {{ el.scrollTop = {}; }}
+return null;",
+        js_string_literal(selector),
+        scroll_top
+    );
+    let _ = evaluate(restore).await;
+    captured?;
+
+    let stitched = crate::screenshot::stitch_slices(&slices, scale, scroll_height as u32, overlap)?;
+    let mut png = Vec::new();
+    stitched
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode stitched capture: {e}"))?;
+
+    let mut image =
+        crate::screenshot::encode_capture(png, format, quality, background, color, encoder);
+    image.monitor = crate::screenshot::window_monitor(window);
+    Ok(image)
+}
+
+/// Builds the script scrolling the container matching `selector` into view
+/// and measuring its content box in viewport CSS pixels.
+fn container_metrics_script(selector: &str) -> String {
+    let selector = js_string_literal(selector);
+    format!(
+        r#"const selector = {selector};
+const el = document.querySelector(selector);
+if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+el.scrollIntoView({{ block: 'nearest', inline: 'nearest' }});
+const rect = el.getBoundingClientRect();
+const left = rect.left + el.clientLeft;
+const top = rect.top + el.clientTop;
+if (top < 0 || left < 0 || top + el.clientHeight > window.innerHeight || left + el.clientWidth > window.innerWidth) {{
+    throw new Error('Scroll container does not fit inside the viewport: ' + selector);
+}}
+return {{
+    left, top,
+    width: el.clientWidth,
+    height: el.clientHeight,
+    scrollHeight: el.scrollHeight,
+    scrollTop: el.scrollTop,
+    viewportWidth: window.innerWidth
+}};"#
+    )
+}
+
+/// Builds the script scrolling the container matching `selector` to `offset`
+/// and returning the offset it reached once the next frame has painted.
+fn scroll_container_script(selector: &str, offset: u32) -> String {
+    let selector = js_string_literal(selector);
+    format!(
+        r#"const selector = {selector};
+const el = document.querySelector(selector);
+if (!el) {{ throw new Error('No element matches selector: ' + selector); }}
+el.scrollTop = {offset};
+// Two frames, so the scrolled content has been painted; the timeout covers
+// hidden windows, where animation frames are paused
+await new Promise((resolve) => {{
+    const fallback = setTimeout(resolve, 500);
+    requestAnimationFrame(() => requestAnimationFrame(() => {{ clearTimeout(fallback); resolve(); }}));
+}});
+return Math.round(el.scrollTop);"#
+    )
+}
+
+/// Waits `settle_ms` milliseconds before driving `capture`.
+async fn after_settle<F: Future>(settle_ms: u64, capture: F) -> F::Output {
+    if settle_ms > 0 {
+        tokio::time::sleep(Duration::from_millis(settle_ms)).await;
+    }
+    capture.await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::ScriptExecutor;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn test_capture_is_delayed_by_settle_time() {
+        let start = Instant::now();
+
+        let captured_at = after_settle(120, async { start.elapsed() }).await;
+
+        assert!(captured_at >= Duration::from_millis(120));
+        assert!(captured_at < Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_canvas_is_read_inside_an_animation_frame() {
+        let script = canvas_script("#scene");
+
+        assert!(script.starts_with(r##"const selector = "#scene";"##));
+        // Async, so execute_js awaits the animation frame
+        assert!(crate::commands::execute_js::is_async_script(&script));
+        let frame = script.find("requestAnimationFrame(read)").unwrap();
+        let read = script.find("canvas.toDataURL('image/png')").unwrap();
+        assert!(read < frame);
+        assert!(script.contains("e.name === 'SecurityError'"));
+        assert!(script.contains("e.name === 'InvalidStateError'"));
+    }
+
+    #[test]
+    fn test_scroll_container_slices_wait_for_paint() {
+        let metrics = container_metrics_script("#grid");
+        assert!(metrics.starts_with(r##"const selector = "#grid";"##));
+        assert!(metrics.contains("el.scrollIntoView("));
+        assert!(metrics.contains("scrollHeight: el.scrollHeight"));
+
+        let scroll = scroll_container_script("#grid", 260);
+        assert!(crate::commands::execute_js::is_async_script(&scroll));
+        let set = scroll.find("el.scrollTop = 260;").unwrap();
+        let frame = scroll.find("requestAnimationFrame(").unwrap();
+        assert!(set < frame);
+        assert!(scroll.ends_with("return Math.round(el.scrollTop);"));
+    }
+
+    /// A window whose `execute_js` and screenshot calls are serialized.
+    #[cfg(not(windows))]
+    fn serialized_window(
+        app: &tauri::App<tauri::test::MockRuntime>,
+    ) -> WebviewWindow<tauri::test::MockRuntime> {
+        app.manage(WindowQueue::new(true));
+        app.manage(ScriptExecutor::new());
+        tauri::WebviewWindowBuilder::new(app, "main", Default::default())
+            .build()
+            .unwrap()
+    }
+
+    // Off Windows scripts can't hand values back, so the captures fail, but
+    // only once their scripts ran
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_canvas_capture_with_serialized_windows() {
+        let app = tauri::test::mock_app();
+        let window = serialized_window(&app);
+
+        // The canvas script used to queue behind the capture holding its window
+        let capture = tokio::time::timeout(
+            Duration::from_secs(5),
+            capture_native_screenshot(
+                window,
+                None,
+                None,
+                None,
+                None,
+                Some("#scene".to_string()),
+                None,
+                None,
+                None,
+                None,
+                None,
+            ),
+        )
+        .await;
+
+        assert!(capture.expect("canvas capture deadlocked").is_err());
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_scroll_container_capture_with_serialized_windows() {
+        let app = tauri::test::mock_app();
+        let window = serialized_window(&app);
+
+        let capture = tokio::time::timeout(
+            Duration::from_secs(5),
+            capture_native_screenshot(
+                window,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some("#grid".to_string()),
+                None,
+            ),
+        )
+        .await;
+
+        assert!(capture
+            .expect("scroll container capture deadlocked")
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_zero_settle_captures_immediately() {
+        let start = Instant::now();
+
+        let captured_at = after_settle(0, async { start.elapsed() }).await;
 
-    match screenshot::capture_viewport_screenshot(&window, &format, quality).await {
-        Ok(data_url) => Ok(data_url),
-        Err(e) => Err(e.to_string()),
+        assert!(captured_at < Duration::from_millis(50));
     }
 }
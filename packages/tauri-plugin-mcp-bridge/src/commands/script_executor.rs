@@ -1,5 +1,6 @@
 //! Script executor state and result handling.
 
+use crate::abort::ABORTED;
 use crate::logging::mcp_log_info;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -58,6 +59,23 @@ impl ScriptExecutor {
             let _ = tx.send(result);
         }
     }
+
+    /// Fails every pending script result with [`ABORTED`].
+    ///
+    /// # Returns
+    ///
+    /// The number of pending results that were cleared.
+    pub async fn abort_pending(&self) -> usize {
+        let mut pending = self.pending_results.lock().await;
+        let count = pending.len();
+        for (_, tx) in pending.drain() {
+            let _ = tx.send(serde_json::json!({
+                "success": false,
+                "error": ABORTED
+            }));
+        }
+        count
+    }
 }
 
 impl Default for ScriptExecutor {
@@ -86,5 +104,3 @@ pub async fn script_result<R: Runtime>(
 
     Ok(())
 }
-
-
@@ -1,55 +1,40 @@
 //! Script injection command for re-injecting registered scripts on page load.
 
-use crate::script_registry::{ScriptEntry, ScriptType, SharedScriptRegistry};
+use crate::commands::check_window_access;
+use crate::script_registry::SharedScriptRegistry;
 use tauri::{command, Runtime, State, WebviewWindow};
 
 /// Request script injection - called by bridge.js when a page loads.
 /// This command retrieves all registered scripts and injects them into the webview.
+/// Windows restricted by the window allowlist or denylist get none.
 #[command]
 pub async fn request_script_injection<R: Runtime>(
     window: WebviewWindow<R>,
     registry: State<'_, SharedScriptRegistry>,
 ) -> Result<serde_json::Value, String> {
-    let scripts: Vec<ScriptEntry> = {
+    check_window_access(&window, window.label())?;
+
+    let (inject_script, script_ids) = {
         let reg = registry
             .lock()
             .map_err(|e| format!("Failed to lock registry: {e}"))?;
-        reg.get_all().iter().map(|e| (*e).clone()).collect()
+        let ids: Vec<String> = reg.get_all().iter().map(|e| e.id.clone()).collect();
+        (reg.page_load_script(), ids)
     };
 
-    if scripts.is_empty() {
+    let Some(inject_script) = inject_script else {
         return Ok(serde_json::json!({
             "injected": 0,
             "message": "No scripts registered"
         }));
-    }
-
-    // Build the injection script
-    let scripts_json: Vec<serde_json::Value> = scripts
-        .iter()
-        .map(|entry| {
-            serde_json::json!({
-                "id": entry.id,
-                "type": match entry.script_type {
-                    ScriptType::Inline => "inline",
-                    ScriptType::Url => "url",
-                },
-                "content": entry.content
-            })
-        })
-        .collect();
-
-    let inject_script = format!(
-        "if (window.__MCP_INJECT_SCRIPTS__) {{ window.__MCP_INJECT_SCRIPTS__({}); }}",
-        serde_json::to_string(&scripts_json).unwrap_or_else(|_| "[]".to_string())
-    );
+    };
 
     window
         .eval(&inject_script)
         .map_err(|e| format!("Failed to inject scripts: {e}"))?;
 
     Ok(serde_json::json!({
-        "injected": scripts.len(),
-        "scriptIds": scripts.iter().map(|s| s.id.clone()).collect::<Vec<_>>()
+        "injected": script_ids.len(),
+        "scriptIds": script_ids
     }))
 }
@@ -0,0 +1,151 @@
+//! The page's scroll position and scrollable extent.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Distance in CSS pixels from an edge still reported as being at it, as
+/// fractional device pixel ratios can leave the last scroll a fraction short.
+const EDGE_TOLERANCE: f64 = 1.0;
+
+/// Reads the document's scroll metrics.
+const SCROLL_METRICS_JS: &str = r#"const root = document.scrollingElement || document.documentElement;
+return {
+    scrollX: window.scrollX,
+    scrollY: window.scrollY,
+    scrollWidth: root.scrollWidth,
+    scrollHeight: root.scrollHeight,
+    clientWidth: root.clientWidth,
+    clientHeight: root.clientHeight
+};"#;
+
+/// Scroll metrics as measured in the page.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScrollMetrics {
+    scroll_x: f64,
+    scroll_y: f64,
+    scroll_width: f64,
+    scroll_height: f64,
+    client_width: f64,
+    client_height: f64,
+}
+
+/// Result of `get_scroll_info`, in CSS pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScrollInfo {
+    pub scroll_x: f64,
+    pub scroll_y: f64,
+    /// Width of the whole document
+    pub scroll_width: f64,
+    /// Height of the whole document
+    pub scroll_height: f64,
+    /// Width of the viewport, without scrollbars
+    pub client_width: f64,
+    /// Height of the viewport, without scrollbars
+    pub client_height: f64,
+    pub at_top: bool,
+    pub at_bottom: bool,
+    pub at_left: bool,
+    pub at_right: bool,
+}
+
+impl From<ScrollMetrics> for ScrollInfo {
+    fn from(m: ScrollMetrics) -> Self {
+        let max_x = (m.scroll_width - m.client_width).max(0.0);
+        let max_y = (m.scroll_height - m.client_height).max(0.0);
+        Self {
+            scroll_x: m.scroll_x,
+            scroll_y: m.scroll_y,
+            scroll_width: m.scroll_width,
+            scroll_height: m.scroll_height,
+            client_width: m.client_width,
+            client_height: m.client_height,
+            at_top: m.scroll_y <= EDGE_TOLERANCE,
+            at_bottom: m.scroll_y >= max_y - EDGE_TOLERANCE,
+            at_left: m.scroll_x <= EDGE_TOLERANCE,
+            at_right: m.scroll_x >= max_x - EDGE_TOLERANCE,
+        }
+    }
+}
+
+/// Returns a window's scroll position and the size of its document.
+///
+/// Tiling a full page takes `ceil(scrollHeight / clientHeight)` viewport
+/// screenshots. The `at*` flags tell whether the page can scroll further in
+/// each direction; a page that doesn't scroll is at every edge.
+///
+/// # Arguments
+///
+/// * `window` - The window to measure
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(ScrollInfo)` - `{ scrollX, scrollY, scrollWidth, scrollHeight,
+///   clientWidth, clientHeight, atTop, atBottom, atLeft, atRight }`
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { scrollHeight, clientHeight } = await invoke('plugin:mcp-bridge|get_scroll_info');
+/// const screenshots = Math.ceil(scrollHeight / clientHeight);
+/// ```
+#[command]
+pub async fn get_scroll_info<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<ScrollInfo, String> {
+    let data = dom::evaluate(window, SCROLL_METRICS_JS.to_string(), executor_state).await?;
+    let metrics: ScrollMetrics =
+        serde_json::from_value(data).map_err(|e| format!("Unexpected scroll metrics: {e}"))?;
+    Ok(metrics.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tall_page(scroll_y: f64) -> ScrollInfo {
+        serde_json::from_value::<ScrollMetrics>(serde_json::json!({
+            "scrollX": 0,
+            "scrollY": scroll_y,
+            "scrollWidth": 1024,
+            "scrollHeight": 3000,
+            "clientWidth": 1024,
+            "clientHeight": 768
+        }))
+        .unwrap()
+        .into()
+    }
+
+    #[test]
+    fn test_page_taller_than_viewport() {
+        let top = tall_page(0.0);
+        assert_eq!((top.scroll_height, top.client_height), (3000.0, 768.0));
+        assert!(top.at_top && !top.at_bottom);
+        // Doesn't scroll sideways
+        assert!(top.at_left && top.at_right);
+
+        let middle = tall_page(1000.0);
+        assert!(!middle.at_top && !middle.at_bottom);
+
+        // 3000 - 768, less a fraction from rounding
+        let bottom = tall_page(2231.5);
+        assert!(!bottom.at_top && bottom.at_bottom);
+    }
+
+    #[test]
+    fn test_scroll_info_serializes_camel_case() {
+        let value = serde_json::to_value(tall_page(0.0)).unwrap();
+
+        assert_eq!(value["scrollHeight"], 3000.0);
+        assert_eq!(value["clientWidth"], 1024.0);
+        assert_eq!(value["atBottom"], false);
+        assert!(SCROLL_METRICS_JS.contains("document.scrollingElement"));
+    }
+}
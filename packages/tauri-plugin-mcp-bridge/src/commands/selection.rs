@@ -0,0 +1,160 @@
+//! Reading and setting the page's text selection.
+
+use crate::commands::dom::{self, SELECTOR_FOR_JS};
+use crate::commands::ScriptExecutor;
+use crate::element_handles::ElementTarget;
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Defines `readSelection`, which reports the current selection as
+/// `{ text, isCollapsed, anchorSelector?, rangeCount }`. Needs
+/// [`SELECTOR_FOR_JS`].
+const READ_SELECTION_JS: &str = r#"const readSelection = () => {
+    const field = document.activeElement;
+    // Text selected inside a form field isn't part of the document selection
+    if ((field instanceof HTMLInputElement || field instanceof HTMLTextAreaElement)
+        && typeof field.selectionStart === 'number' && field.selectionStart !== field.selectionEnd) {
+        return {
+            text: field.value.slice(field.selectionStart, field.selectionEnd),
+            isCollapsed: false,
+            anchorSelector: selectorFor(field),
+            rangeCount: 1
+        };
+    }
+    const selection = window.getSelection();
+    if (!selection || selection.rangeCount === 0) {
+        return { text: '', isCollapsed: true, rangeCount: 0 };
+    }
+    return {
+        text: selection.toString(),
+        isCollapsed: selection.isCollapsed,
+        anchorSelector: selectorFor(selection.anchorNode),
+        rangeCount: selection.rangeCount
+    };
+};"#;
+
+/// Returns the current text selection of a window.
+///
+/// Text selected inside an `<input>` or `<textarea>` is reported too, even
+/// though browsers keep it out of `window.getSelection()`.
+///
+/// # Arguments
+///
+/// * `window` - The window to read the selection from
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ text, isCollapsed, anchorSelector?, rangeCount }`.
+///   `anchorSelector` is a CSS selector for the element the selection starts
+///   in. With nothing selected, `text` is empty and `isCollapsed` is true.
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { text } = await invoke('plugin:mcp-bridge|get_selection');
+/// ```
+#[command]
+pub async fn get_selection<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, get_selection_script(), executor_state).await
+}
+
+/// Selects the contents of an element, as a user dragging across it would.
+///
+/// The contents of `<input>` and `<textarea>` elements are selected with
+/// `select()`, which also focuses them; any other element has its contents
+/// selected with a DOM range, replacing the current selection.
+///
+/// # Arguments
+///
+/// * `window` - The window containing the element
+/// * `selector` - CSS selector of the element
+/// * `handle_id` - Handle from `query_element_handle`, instead of `selector`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The new selection, as returned by [`get_selection`]
+/// * `Err(String)` - If no element matches the selector or the handle is
+///   stale
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const selection = await invoke('plugin:mcp-bridge|set_selection', {
+///   selector: 'blockquote'
+/// });
+/// // { text: 'Quoted text', isCollapsed: false, anchorSelector: 'html > body > blockquote', rangeCount: 1 }
+/// ```
+#[command]
+pub async fn set_selection<R: Runtime>(
+    window: WebviewWindow<R>,
+    selector: Option<String>,
+    handle_id: Option<String>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    let target = ElementTarget::new(selector, handle_id)?;
+    dom::evaluate(window, set_selection_script(&target), executor_state).await
+}
+
+/// Builds the script reading the current selection.
+fn get_selection_script() -> String {
+    format!("{SELECTOR_FOR_JS}\n{READ_SELECTION_JS}\nreturn readSelection();")
+}
+
+/// Builds the script selecting the contents of the element of `target`.
+fn set_selection_script(target: &ElementTarget) -> String {
+    format!(
+        r#"{lookup}
+{SELECTOR_FOR_JS}
+{READ_SELECTION_JS}
+if (el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement) {{
+    el.focus();
+    el.select();
+}} else {{
+    if (document.activeElement && document.activeElement !== document.body) {{
+        document.activeElement.blur();
+    }}
+    const range = document.createRange();
+    range.selectNodeContents(el);
+    const selection = window.getSelection();
+    selection.removeAllRanges();
+    selection.addRange(range);
+}}
+return readSelection();"#,
+        lookup = target.require_js()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_selected_element_is_read_back() {
+        let script = set_selection_script(&ElementTarget::Selector("#quote".to_string()));
+
+        assert!(script.starts_with(r##"const selector = "#quote";"##));
+        assert!(script.contains("range.selectNodeContents(el);"));
+        // The selection just made is what the command returns
+        let select = script.find("selection.addRange(range);").unwrap();
+        let read = script.rfind("return readSelection();").unwrap();
+        assert!(select < read);
+    }
+
+    #[test]
+    fn test_empty_selection_returns_empty_text() {
+        let script = get_selection_script();
+
+        assert!(script.contains("return { text: '', isCollapsed: true, rangeCount: 0 };"));
+        assert!(!script.contains("throw"));
+    }
+}
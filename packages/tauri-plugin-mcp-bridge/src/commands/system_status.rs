@@ -0,0 +1,168 @@
+//! Host system status.
+//!
+//! Requires the `system-status` feature. Field availability per platform:
+//!
+//! | Field              | Linux | macOS | Windows | Android / iOS |
+//! |--------------------|-------|-------|---------|---------------|
+//! | `memoryUsedBytes`  | yes   | yes   | yes     | yes           |
+//! | `memoryTotalBytes` | yes   | yes   | yes     | yes           |
+//! | `cpuCount`         | yes   | yes   | yes     | yes           |
+//! | `batteryPercent`   | yes   | no    | no      | no            |
+//! | `charging`         | yes   | no    | no      | no            |
+//! | `online`           | yes   | no    | no      | no            |
+//!
+//! On Linux, battery fields come from `/sys/class/power_supply` and are
+//! omitted on machines without a battery. `online` is true when any
+//! non-loopback interface in `/sys/class/net` is up.
+
+use serde_json::Value;
+use tauri::command;
+
+/// Retrieves live host metrics.
+///
+/// Complements [`get_backend_state`](super::get_backend_state) with
+/// environmental context such as memory pressure and battery level. Fields
+/// that are not available on the current platform are omitted rather than
+/// reported with placeholder values.
+///
+/// # Returns
+///
+/// * `Ok(Value)` - JSON object containing:
+///   - `memoryUsedBytes`, `memoryTotalBytes`: Physical memory usage
+///   - `cpuCount`: Number of logical CPUs
+///   - `batteryPercent` (optional): Battery charge from 0 to 100
+///   - `charging` (optional): Whether the battery is charging
+///   - `online` (optional): Whether a network interface is up
+/// * `Err(String)` - If the plugin was built without the `system-status` feature
+#[command]
+pub async fn get_system_status() -> Result<Value, String> {
+    #[cfg(feature = "system-status")]
+    {
+        serde_json::to_value(status::collect())
+            .map_err(|e| format!("Failed to serialize system status: {e}"))
+    }
+
+    #[cfg(not(feature = "system-status"))]
+    {
+        Err("get_system_status requires the `system-status` feature".to_string())
+    }
+}
+
+#[cfg(feature = "system-status")]
+mod status {
+    use serde::Serialize;
+
+    /// Snapshot of host metrics.
+    #[derive(Debug, Clone, Default, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SystemStatus {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub battery_percent: Option<u8>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub charging: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub online: Option<bool>,
+        pub memory_used_bytes: u64,
+        pub memory_total_bytes: u64,
+        pub cpu_count: usize,
+    }
+
+    /// Gathers the metrics available on this platform.
+    pub fn collect() -> SystemStatus {
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+
+        #[cfg(target_os = "linux")]
+        let (battery, online) = (linux::battery(), linux::online());
+        #[cfg(not(target_os = "linux"))]
+        let (battery, online): (Option<(u8, bool)>, Option<bool>) = (None, None);
+
+        SystemStatus {
+            battery_percent: battery.map(|(percent, _)| percent),
+            charging: battery.map(|(_, charging)| charging),
+            online,
+            memory_used_bytes: system.used_memory(),
+            memory_total_bytes: system.total_memory(),
+            cpu_count: std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1),
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    mod linux {
+        use std::fs;
+        use std::path::Path;
+
+        /// Reads the first battery under `/sys/class/power_supply`.
+        pub fn battery() -> Option<(u8, bool)> {
+            for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+                let path = entry.path();
+                if read_trimmed(&path.join("type")).as_deref() != Some("Battery") {
+                    continue;
+                }
+                let capacity = read_trimmed(&path.join("capacity"))?;
+                let status = read_trimmed(&path.join("status")).unwrap_or_default();
+                return parse_battery(&capacity, &status);
+            }
+            None
+        }
+
+        /// Returns whether any non-loopback interface is up, or `None` if
+        /// interface state cannot be read.
+        pub fn online() -> Option<bool> {
+            let interfaces = fs::read_dir("/sys/class/net").ok()?;
+            Some(interfaces.flatten().any(|entry| {
+                entry.file_name() != "lo"
+                    && read_trimmed(&entry.path().join("operstate")).as_deref() == Some("up")
+            }))
+        }
+
+        /// Parses `capacity` and `status` power supply attributes.
+        pub fn parse_battery(capacity: &str, status: &str) -> Option<(u8, bool)> {
+            let percent = capacity.trim().parse::<u8>().ok()?.min(100);
+            Some((percent, status.trim() == "Charging"))
+        }
+
+        fn read_trimmed(path: &Path) -> Option<String> {
+            fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        #[cfg(target_os = "linux")]
+        fn test_parse_battery() {
+            assert_eq!(linux::parse_battery("87\n", "Charging\n"), Some((87, true)));
+            assert_eq!(linux::parse_battery("100", "Full"), Some((100, false)));
+            assert_eq!(linux::parse_battery("n/a", "Discharging"), None);
+        }
+
+        #[test]
+        fn test_unavailable_fields_are_omitted() {
+            let status = SystemStatus {
+                memory_used_bytes: 1,
+                memory_total_bytes: 2,
+                cpu_count: 4,
+                ..SystemStatus::default()
+            };
+            let value = serde_json::to_value(&status).unwrap();
+
+            assert_eq!(value["cpuCount"], 4);
+            assert!(value.get("batteryPercent").is_none());
+            assert!(value.get("charging").is_none());
+            assert!(value.get("online").is_none());
+        }
+
+        #[test]
+        fn test_collect_reports_memory_and_cpus() {
+            let status = collect();
+
+            assert!(status.cpu_count >= 1);
+            assert!(status.memory_total_bytes >= status.memory_used_bytes);
+        }
+    }
+}
@@ -0,0 +1,295 @@
+//! Reading and switching a window's light/dark theme.
+//!
+//! Unlike [`emulate_media`](super::emulate_media), which only changes what
+//! the page's `prefers-color-scheme` queries see, `set_theme` drives Tauri's
+//! own window theme, so native chrome such as the title bar follows too.
+//!
+//! | Platform | Method | Covers |
+//! |----------|--------|--------|
+//! | Windows | Tauri window theme (`native`) | The window's title bar and the page's `prefers-color-scheme` |
+//! | macOS | Tauri window theme (`native`) | Every window, app-wide, and the page's `prefers-color-scheme` |
+//! | Linux | Tauri window theme (`native`) | The app-wide GTK theme variant; whether the page follows depends on the WebKitGTK version |
+//! | iOS, Android | CSS media emulation (`media`) | The page's `prefers-color-scheme` only, until it reloads |
+
+use crate::commands::media::ColorScheme;
+use crate::commands::ScriptExecutor;
+use serde::{Deserialize, Serialize};
+use tauri::{command, Runtime, State, Theme, WebviewWindow};
+
+/// Theme requested with `set_theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeSetting {
+    Light,
+    Dark,
+    /// Follow the system theme
+    Auto,
+}
+
+impl ThemeSetting {
+    /// The theme to pass to Tauri; `None` follows the system.
+    #[cfg_attr(any(target_os = "android", target_os = "ios"), allow(dead_code))]
+    fn native(self) -> Option<Theme> {
+        match self {
+            Self::Light => Some(Theme::Light),
+            Self::Dark => Some(Theme::Dark),
+            Self::Auto => None,
+        }
+    }
+
+    /// The color scheme to emulate; `None` clears the emulation.
+    #[cfg_attr(not(any(target_os = "android", target_os = "ios")), allow(dead_code))]
+    fn color_scheme(self) -> Option<ColorScheme> {
+        match self {
+            Self::Light => Some(ColorScheme::Light),
+            Self::Dark => Some(ColorScheme::Dark),
+            Self::Auto => None,
+        }
+    }
+}
+
+/// How the theme was read or applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeMethod {
+    /// Tauri's window theme
+    Native,
+    /// The page's `prefers-color-scheme`, through media emulation
+    Media,
+}
+
+/// Outcome of `get_theme` and `set_theme`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowTheme {
+    /// The effective theme; `auto` resolves to the system's
+    pub theme: ColorScheme,
+    pub method: ThemeMethod,
+}
+
+/// Windows whose native theme can be read and written.
+pub trait ThemeControl {
+    /// Returns the window's effective theme.
+    fn native_theme(&self) -> tauri::Result<Theme>;
+
+    /// Sets the window's theme, or follows the system with `None`.
+    fn set_native_theme(&self, theme: Option<Theme>) -> tauri::Result<()>;
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+impl<R: Runtime> ThemeControl for WebviewWindow<R> {
+    fn native_theme(&self) -> tauri::Result<Theme> {
+        self.theme()
+    }
+
+    fn set_native_theme(&self, theme: Option<Theme>) -> tauri::Result<()> {
+        self.set_theme(theme)
+    }
+}
+
+/// Reads the window's effective theme.
+#[cfg_attr(any(target_os = "android", target_os = "ios"), allow(dead_code))]
+fn read_native_theme(window: &impl ThemeControl) -> Result<ColorScheme, String> {
+    match window.native_theme() {
+        Ok(Theme::Dark) => Ok(ColorScheme::Dark),
+        Ok(_) => Ok(ColorScheme::Light),
+        Err(e) => Err(format!("Failed to get theme: {e}")),
+    }
+}
+
+/// Applies `setting` and reads back the effective theme.
+#[cfg_attr(any(target_os = "android", target_os = "ios"), allow(dead_code))]
+fn apply_native_theme(
+    window: &impl ThemeControl,
+    setting: ThemeSetting,
+) -> Result<ColorScheme, String> {
+    window
+        .set_native_theme(setting.native())
+        .map_err(|e| format!("Failed to set theme: {e}"))?;
+    read_native_theme(window)
+}
+
+/// Gets a window's effective theme.
+///
+/// # Arguments
+///
+/// * `window` - The window to inspect
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(WindowTheme)` - `{ theme: "light" | "dark", method }`
+/// * `Err(String)` - If the theme can't be read
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { theme } = await invoke('plugin:mcp-bridge|get_theme');
+/// ```
+#[command]
+pub async fn get_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    read_theme(window, executor_state).await
+}
+
+/// Switches a window to a light or dark theme, or back to the system's.
+///
+/// Where Tauri has no window theme (iOS and Android), the page's
+/// `prefers-color-scheme` is emulated instead, replacing any
+/// `emulate_media` override. See the module docs for what each platform
+/// covers.
+///
+/// # Arguments
+///
+/// * `window` - The window to update
+/// * `theme` - `"light"`, `"dark"` or `"auto"`
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(WindowTheme)` - The effective theme afterwards, and how it was set
+/// * `Err(String)` - If the theme can't be applied
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|set_theme', { theme: 'dark' });
+/// await invoke('plugin:mcp-bridge|set_theme', { theme: 'auto' });
+/// ```
+#[command]
+pub async fn set_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    theme: ThemeSetting,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    apply_theme(window, theme, executor_state).await
+}
+
+/// Reads Tauri's window theme.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+async fn read_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    _executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    Ok(WindowTheme {
+        theme: read_native_theme(&window)?,
+        method: ThemeMethod::Native,
+    })
+}
+
+/// Sets Tauri's window theme.
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+async fn apply_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    setting: ThemeSetting,
+    _executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    Ok(WindowTheme {
+        theme: apply_native_theme(&window, setting)?,
+        method: ThemeMethod::Native,
+    })
+}
+
+/// Reads the page's `prefers-color-scheme`.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+async fn read_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    let script =
+        "return window.matchMedia('(prefers-color-scheme: dark)').matches ? 'dark' : 'light';";
+    let data = crate::commands::dom::evaluate(window, script.to_string(), executor_state).await?;
+    Ok(WindowTheme {
+        theme: serde_json::from_value(data).map_err(|e| format!("Unexpected theme report: {e}"))?,
+        method: ThemeMethod::Media,
+    })
+}
+
+/// Emulates `setting` as the page's `prefers-color-scheme`.
+#[cfg(any(target_os = "android", target_os = "ios"))]
+async fn apply_theme<R: Runtime>(
+    window: WebviewWindow<R>,
+    setting: ThemeSetting,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<WindowTheme, String> {
+    use crate::commands::media::{emulate_media, MediaFeatures};
+
+    let features = MediaFeatures {
+        prefers_color_scheme: setting.color_scheme(),
+        ..MediaFeatures::default()
+    };
+    let reset = features.is_empty();
+    emulate_media(
+        window.clone(),
+        Some(features),
+        Some(reset),
+        executor_state.clone(),
+    )
+    .await?;
+    read_theme(window, executor_state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// In-memory window following a light system theme.
+    struct FakeWindow {
+        theme: Cell<Option<Theme>>,
+    }
+
+    impl ThemeControl for FakeWindow {
+        fn native_theme(&self) -> tauri::Result<Theme> {
+            Ok(self.theme.get().unwrap_or(Theme::Light))
+        }
+
+        fn set_native_theme(&self, theme: Option<Theme>) -> tauri::Result<()> {
+            self.theme.set(theme);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_set_dark_reads_back_dark() {
+        let window = FakeWindow {
+            theme: Cell::new(None),
+        };
+        assert_eq!(read_native_theme(&window), Ok(ColorScheme::Light));
+
+        let theme = apply_native_theme(&window, ThemeSetting::Dark).unwrap();
+        assert_eq!(theme, ColorScheme::Dark);
+        assert_eq!(read_native_theme(&window), Ok(ColorScheme::Dark));
+        assert_eq!(
+            serde_json::to_value(WindowTheme {
+                theme,
+                method: ThemeMethod::Native
+            })
+            .unwrap(),
+            serde_json::json!({ "theme": "dark", "method": "native" })
+        );
+
+        // Auto hands the theme back to the system
+        let theme = apply_native_theme(&window, ThemeSetting::Auto).unwrap();
+        assert_eq!(window.theme.get(), None);
+        assert_eq!(theme, ColorScheme::Light);
+    }
+
+    #[test]
+    fn test_theme_settings_map_to_both_methods() {
+        let setting: ThemeSetting = serde_json::from_str(r#""auto""#).unwrap();
+        assert_eq!(setting, ThemeSetting::Auto);
+        assert!(serde_json::from_str::<ThemeSetting>(r#""sepia""#).is_err());
+
+        assert_eq!(ThemeSetting::Dark.native(), Some(Theme::Dark));
+        assert_eq!(ThemeSetting::Auto.native(), None);
+        assert_eq!(ThemeSetting::Light.color_scheme(), Some(ColorScheme::Light));
+        assert_eq!(ThemeSetting::Auto.color_scheme(), None);
+    }
+}
@@ -0,0 +1,369 @@
+//! Reading and overriding a window's user agent.
+//!
+//! The override goes through the native webview, so it applies to HTTP
+//! requests as well as `navigator.userAgent`:
+//!
+//! | Platform | API | Takes effect |
+//! |----------|-----|--------------|
+//! | Windows | `ICoreWebView2Settings2::SetUserAgent` | Next navigation |
+//! | macOS, iOS | `WKWebView.customUserAgent` | Next navigation |
+//! | Android | `WebSettings.setUserAgentString` | Next navigation |
+//! | Linux | Not supported | - |
+//!
+//! Pages read the new value only once they load again, so responses report
+//! whether a reload is still needed.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::{command, Manager, Runtime, State, WebviewWindow};
+
+/// How long to wait for the webview thread to apply an override.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+))]
+const NATIVE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A window's user agent as the page sees it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserAgentInfo {
+    /// `navigator.userAgent` of the loaded page
+    pub user_agent: String,
+    /// The override set with `set_user_agent`, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub override_user_agent: Option<String>,
+    /// Whether the page must be reloaded to pick up the override
+    pub reload_required: bool,
+}
+
+impl UserAgentInfo {
+    fn new(page_user_agent: String, override_user_agent: Option<String>) -> Self {
+        let reload_required = override_user_agent
+            .as_ref()
+            .is_some_and(|user_agent| *user_agent != page_user_agent);
+        Self {
+            user_agent: page_user_agent,
+            override_user_agent,
+            reload_required,
+        }
+    }
+}
+
+/// User agent overrides set per window.
+///
+/// Cheap to clone; clones share the same overrides. The plugin manages one as
+/// Tauri state.
+#[derive(Clone, Default)]
+pub struct UserAgentOverrides {
+    overrides: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl UserAgentOverrides {
+    /// Creates an empty set of overrides.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the override applied to a window.
+    pub fn set(&self, window_label: &str, user_agent: &str) {
+        self.overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(window_label.to_string(), user_agent.to_string());
+    }
+
+    /// Returns the override applied to a window, if any.
+    pub fn get(&self, window_label: &str) -> Option<String> {
+        self.overrides
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(window_label)
+            .cloned()
+    }
+}
+
+/// Returns the user agent the page in a window reports.
+///
+/// # Arguments
+///
+/// * `window` - The window to read
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(UserAgentInfo)` - `{ userAgent, overrideUserAgent?, reloadRequired }`
+/// * `Err(String)` - If the page can't be evaluated
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { userAgent } = await invoke('plugin:mcp-bridge|get_user_agent');
+/// ```
+#[command]
+pub async fn get_user_agent<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<UserAgentInfo, String> {
+    let override_user_agent = window
+        .try_state::<UserAgentOverrides>()
+        .and_then(|overrides| overrides.get(window.label()));
+    let page_user_agent = page_user_agent(window, executor_state).await?;
+    Ok(UserAgentInfo::new(page_user_agent, override_user_agent))
+}
+
+/// Overrides a window's user agent through the native webview.
+///
+/// The override lasts for the life of the webview and applies from the next
+/// navigation on; reload the page for it to see the new value. Linux is not
+/// supported, since WebKitGTK settings aren't reachable from the plugin.
+///
+/// # Arguments
+///
+/// * `window` - The window to change
+/// * `user_agent` - The new user agent string
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(UserAgentInfo)` - The user agent the page currently reports, and
+///   whether a reload is needed for it to report the override
+/// * `Err(String)` - If `user_agent` is empty, the platform can't change it at
+///   runtime, or the webview rejected it
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { reloadRequired } = await invoke('plugin:mcp-bridge|set_user_agent', {
+///   userAgent: 'Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X)'
+/// });
+/// ```
+#[command]
+pub async fn set_user_agent<R: Runtime>(
+    window: WebviewWindow<R>,
+    user_agent: String,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<UserAgentInfo, String> {
+    if user_agent.trim().is_empty() {
+        return Err("userAgent must not be empty".to_string());
+    }
+
+    set_native_user_agent(&window, user_agent.clone())?;
+    if let Some(overrides) = window.try_state::<UserAgentOverrides>() {
+        overrides.set(window.label(), &user_agent);
+    }
+
+    let page_user_agent = page_user_agent(window, executor_state).await?;
+    Ok(UserAgentInfo::new(page_user_agent, Some(user_agent)))
+}
+
+/// Reads `navigator.userAgent` from the page.
+async fn page_user_agent<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<String, String> {
+    let value = dom::evaluate(
+        window,
+        "return navigator.userAgent;".to_string(),
+        executor_state,
+    )
+    .await?;
+    value
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "navigator.userAgent is not a string".to_string())
+}
+
+/// Waits for the webview thread to report the outcome of an override.
+#[cfg(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+))]
+fn await_native(rx: std::sync::mpsc::Receiver<Result<(), String>>) -> Result<(), String> {
+    rx.recv_timeout(NATIVE_TIMEOUT)
+        .map_err(|_| "Timed out setting the user agent".to_string())?
+}
+
+#[cfg(target_os = "windows")]
+fn set_native_user_agent<R: Runtime>(
+    window: &WebviewWindow<R>,
+    user_agent: String,
+) -> Result<(), String> {
+    use webview2_com::Microsoft::Web::WebView2::Win32::ICoreWebView2Settings2;
+    use windows::core::{Interface, HSTRING};
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            let controller = webview.controller();
+            let result = unsafe {
+                crate::webview2::core_webview2(&controller).and_then(|core_webview| {
+                    let settings: ICoreWebView2Settings2 = core_webview
+                        .Settings()
+                        .and_then(|settings| settings.cast())
+                        .map_err(|e| format!("WebView2 settings unavailable: {e}"))?;
+                    settings
+                        .SetUserAgent(&HSTRING::from(user_agent.as_str()))
+                        .map_err(|e| format!("Failed to set user agent: {e}"))
+                })
+            };
+            let _ = tx.send(result);
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(target_os = "macos")]
+fn set_native_user_agent<R: Runtime>(
+    window: &WebviewWindow<R>,
+    user_agent: String,
+) -> Result<(), String> {
+    use objc2_foundation::NSString;
+    use objc2_web_kit::WKWebView;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            unsafe {
+                let wkwebview: &WKWebView = &*(webview.inner() as *const _ as *const WKWebView);
+                wkwebview.setCustomUserAgent(Some(&NSString::from_str(&user_agent)));
+            }
+            let _ = tx.send(Ok(()));
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(target_os = "ios")]
+fn set_native_user_agent<R: Runtime>(
+    window: &WebviewWindow<R>,
+    user_agent: String,
+) -> Result<(), String> {
+    use objc2::runtime::AnyObject;
+    use objc2_foundation::NSString;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            // WKWebView's typed bindings are macOS only, as in the iOS screenshot code
+            let wkwebview: *mut AnyObject = webview.inner().cast();
+            let user_agent = NSString::from_str(&user_agent);
+            unsafe {
+                let _: () = objc2::msg_send![wkwebview, setCustomUserAgent: &*user_agent];
+            }
+            let _ = tx.send(Ok(()));
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(target_os = "android")]
+fn set_native_user_agent<R: Runtime>(
+    window: &WebviewWindow<R>,
+    user_agent: String,
+) -> Result<(), String> {
+    use jni::objects::JValue;
+
+    let (tx, rx) = std::sync::mpsc::channel();
+    window
+        .with_webview(move |webview| {
+            webview
+                .jni_handle()
+                .exec(move |env, _activity, webview_obj| {
+                    let result = (|| {
+                        let settings = env
+                            .call_method(
+                                webview_obj,
+                                "getSettings",
+                                "()Landroid/webkit/WebSettings;",
+                                &[],
+                            )
+                            .and_then(|settings| settings.l())
+                            .map_err(|e| format!("Failed to get WebSettings: {e}"))?;
+                        let user_agent = env
+                            .new_string(&user_agent)
+                            .map_err(|e| format!("Failed to create string: {e}"))?;
+                        env.call_method(
+                            &settings,
+                            "setUserAgentString",
+                            "(Ljava/lang/String;)V",
+                            &[JValue::Object(&user_agent)],
+                        )
+                        .map_err(|e| format!("Failed to set user agent: {e}"))?;
+                        Ok(())
+                    })();
+                    let _ = tx.send(result);
+                });
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+    await_native(rx)
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "android"
+)))]
+fn set_native_user_agent<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _user_agent: String,
+) -> Result<(), String> {
+    Err(format!(
+        "Changing the user agent at runtime is not supported on {}",
+        std::env::consts::OS
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOBILE: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X)";
+
+    #[test]
+    fn test_set_user_agent_is_read_back() {
+        let overrides = UserAgentOverrides::new();
+        assert_eq!(overrides.get("main"), None);
+
+        overrides.set("main", MOBILE);
+        overrides.clone().set("other", "Other/1.0");
+
+        assert_eq!(overrides.get("main").as_deref(), Some(MOBILE));
+        assert_eq!(overrides.get("other").as_deref(), Some("Other/1.0"));
+    }
+
+    #[test]
+    fn test_reload_is_required_until_the_page_reports_the_override() {
+        let before_reload = UserAgentInfo::new("Desktop/1.0".to_string(), Some(MOBILE.into()));
+        assert!(before_reload.reload_required);
+
+        let after_reload = UserAgentInfo::new(MOBILE.to_string(), Some(MOBILE.into()));
+        assert!(!after_reload.reload_required);
+        assert_eq!(
+            serde_json::to_value(&after_reload).unwrap(),
+            serde_json::json!({
+                "userAgent": MOBILE,
+                "overrideUserAgent": MOBILE,
+                "reloadRequired": false
+            })
+        );
+
+        let untouched = UserAgentInfo::new("Desktop/1.0".to_string(), None);
+        assert!(!untouched.reload_required);
+        assert_eq!(
+            serde_json::to_value(&untouched).unwrap(),
+            serde_json::json!({ "userAgent": "Desktop/1.0", "reloadRequired": false })
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Freezing the page's clock and timers for deterministic screenshots.
+//!
+//! `freeze_time` replaces `Date`, `performance.now`, `setTimeout`,
+//! `setInterval` and `requestAnimationFrame` (and their `clear`/`cancel`
+//! counterparts) with a virtual clock that only moves when `step_time`
+//! advances it. Timers due within a step fire in order, and animation frames
+//! run every 16 ms of virtual time.
+//!
+//! This is intrusive and meant for tests only: the page sees a clock that
+//! doesn't move, and code that captured the original functions before the
+//! freeze keeps using real time. CSS animations and transitions aren't
+//! affected; emulate `prefersReducedMotion` with `emulate_media` for those.
+//! A navigation unfreezes the page, as the new document starts with the
+//! original functions.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde_json::Value;
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Installs the virtual clock, unless one is already installed.
+const FREEZE_SCRIPT: &str = r#"if (window.__MCP_CLOCK__) {
+    const clock = window.__MCP_CLOCK__;
+    return { frozen: true, alreadyFrozen: true, now: clock.start + clock.elapsed, pendingTimers: clock.timers.size };
+}
+const originals = {
+    Date: window.Date,
+    performanceNow: performance.now,
+    setTimeout: window.setTimeout,
+    clearTimeout: window.clearTimeout,
+    setInterval: window.setInterval,
+    clearInterval: window.clearInterval,
+    requestAnimationFrame: window.requestAnimationFrame,
+    cancelAnimationFrame: window.cancelAnimationFrame
+};
+const clock = {
+    start: at === null ? originals.Date.now() : at,
+    performanceStart: originals.performanceNow.call(performance),
+    elapsed: 0,
+    timers: new Map(),
+    nextId: 1,
+    originals
+};
+const RealDate = originals.Date;
+const now = () => clock.start + clock.elapsed;
+function FrozenDate(...args) {
+    if (!new.target) return new RealDate(now()).toString();
+    return args.length ? new RealDate(...args) : new RealDate(now());
+}
+FrozenDate.prototype = RealDate.prototype;
+FrozenDate.now = now;
+FrozenDate.parse = RealDate.parse;
+FrozenDate.UTC = RealDate.UTC;
+
+const schedule = (kind, callback, delay, args) => {
+    const id = clock.nextId++;
+    const fn = typeof callback === 'function' ? callback : () => (0, eval)(String(callback));
+    const wait = Math.max(0, Number(delay) || 0);
+    clock.timers.set(id, { kind, fn, args, due: clock.elapsed + wait, interval: kind === 'interval' ? Math.max(1, wait) : null });
+    return id;
+};
+const cancel = (id) => { clock.timers.delete(id); };
+
+window.Date = FrozenDate;
+performance.now = () => clock.performanceStart + clock.elapsed;
+window.setTimeout = (callback, delay, ...args) => schedule('timeout', callback, delay, args);
+window.setInterval = (callback, delay, ...args) => schedule('interval', callback, delay, args);
+window.requestAnimationFrame = (callback) => {
+    const id = schedule('frame', callback, 0, []);
+    const timer = clock.timers.get(id);
+    timer.due = (Math.floor(clock.elapsed / 16) + 1) * 16;
+    return id;
+};
+window.clearTimeout = cancel;
+window.clearInterval = cancel;
+window.cancelAnimationFrame = cancel;
+window.__MCP_CLOCK__ = clock;
+return { frozen: true, alreadyFrozen: false, now: now(), pendingTimers: 0 };"#;
+
+/// Advances the virtual clock by `ms`, firing the timers that come due.
+const STEP_SCRIPT: &str = r#"const clock = window.__MCP_CLOCK__;
+if (!clock) { throw new Error('Time is not frozen; call freeze_time first'); }
+const target = clock.elapsed + ms;
+let fired = 0;
+const errors = [];
+// Timers scheduling timers at 0 ms could otherwise loop forever
+while (fired < 10000) {
+    let nextId = null;
+    let next = null;
+    for (const [id, timer] of clock.timers) {
+        if (timer.due <= target && (!next || timer.due < next.due)) {
+            nextId = id;
+            next = timer;
+        }
+    }
+    if (!next) break;
+    clock.elapsed = Math.max(clock.elapsed, next.due);
+    if (next.interval === null) {
+        clock.timers.delete(nextId);
+    } else {
+        next.due += next.interval;
+    }
+    fired++;
+    try {
+        if (next.kind === 'frame') next.fn(performance.now());
+        else next.fn(...next.args);
+    } catch (e) {
+        errors.push(String(e && e.message || e));
+    }
+}
+clock.elapsed = Math.max(clock.elapsed, target);
+return { now: clock.start + clock.elapsed, elapsed: clock.elapsed, fired, pendingTimers: clock.timers.size, errors };"#;
+
+/// Restores the real clock, handing pending timers to the real functions.
+const UNFREEZE_SCRIPT: &str = r#"const clock = window.__MCP_CLOCK__;
+if (!clock) return { frozen: false, resumedTimers: 0 };
+const originals = clock.originals;
+window.Date = originals.Date;
+performance.now = originals.performanceNow;
+window.setTimeout = originals.setTimeout;
+window.clearTimeout = originals.clearTimeout;
+window.setInterval = originals.setInterval;
+window.clearInterval = originals.clearInterval;
+window.requestAnimationFrame = originals.requestAnimationFrame;
+window.cancelAnimationFrame = originals.cancelAnimationFrame;
+delete window.__MCP_CLOCK__;
+
+for (const timer of clock.timers.values()) {
+    const remaining = Math.max(0, timer.due - clock.elapsed);
+    if (timer.kind === 'frame') {
+        originals.requestAnimationFrame.call(window, timer.fn);
+    } else if (timer.kind === 'interval') {
+        originals.setTimeout.call(window, () => {
+            timer.fn(...timer.args);
+            originals.setInterval.call(window, timer.fn, timer.interval, ...timer.args);
+        }, remaining);
+    } else {
+        originals.setTimeout.call(window, timer.fn, remaining, ...timer.args);
+    }
+}
+return { frozen: false, resumedTimers: clock.timers.size };"#;
+
+/// Freezes a window's clock and timers.
+///
+/// Afterwards `Date.now()`, `new Date()` and `performance.now()` stay
+/// constant, and timer and animation frame callbacks only run when
+/// [`step_time`] advances the clock. Freezing a frozen window changes
+/// nothing. Use only during testing, see the [module docs](self).
+///
+/// # Arguments
+///
+/// * `window` - The window to freeze
+/// * `at` - Milliseconds since the epoch to freeze `Date.now()` at; defaults
+///   to the current time
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ frozen: true, alreadyFrozen, now, pendingTimers }`
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|freeze_time', { at: Date.parse('2024-01-01T00:00:00Z') });
+/// ```
+#[command]
+pub async fn freeze_time<R: Runtime>(
+    window: WebviewWindow<R>,
+    at: Option<f64>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, freeze_script(at), executor_state).await
+}
+
+/// Advances a frozen window's clock, running the timers and animation
+/// frames that come due in order.
+///
+/// # Arguments
+///
+/// * `window` - The frozen window
+/// * `ms` - Milliseconds to advance
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ now, elapsed, fired, pendingTimers, errors }`, where
+///   `errors` lists the messages of callbacks that threw
+/// * `Err(String)` - If the window isn't frozen
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// // Let a 300 ms fade finish before the screenshot
+/// await invoke('plugin:mcp-bridge|step_time', { ms: 300 });
+/// ```
+#[command]
+pub async fn step_time<R: Runtime>(
+    window: WebviewWindow<R>,
+    ms: u64,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, step_script(ms), executor_state).await
+}
+
+/// Restores a window's real clock and timers.
+///
+/// Timers still pending on the virtual clock are handed to the real timer
+/// functions with their remaining delay.
+///
+/// # Arguments
+///
+/// * `window` - The window to unfreeze
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ frozen: false, resumedTimers }`; unfreezing a window
+///   that isn't frozen resumes nothing
+/// * `Err(String)` - If the script fails
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// await invoke('plugin:mcp-bridge|unfreeze_time');
+/// ```
+#[command]
+pub async fn unfreeze_time<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<Value, String> {
+    dom::evaluate(window, UNFREEZE_SCRIPT.to_string(), executor_state).await
+}
+
+/// Builds the script freezing the clock at `at`, or at the current time.
+fn freeze_script(at: Option<f64>) -> String {
+    let at = at.map_or("null".to_string(), |at| at.to_string());
+    format!("const at = {at};\n{FREEZE_SCRIPT}")
+}
+
+/// Builds the script advancing the clock by `ms`.
+fn step_script(ms: u64) -> String {
+    format!("const ms = {ms};\n{STEP_SCRIPT}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freeze_keeps_date_now_constant() {
+        let script = freeze_script(Some(1_704_067_200_000.0));
+
+        assert!(script.starts_with("const at = 1704067200000;\n"));
+        // Only step_time moves the clock
+        assert!(script.contains("const now = () => clock.start + clock.elapsed;"));
+        assert!(script.contains("FrozenDate.now = now;"));
+        assert!(script.contains("performance.now = () => clock.performanceStart + clock.elapsed;"));
+        assert!(!STEP_SCRIPT.contains("originals.Date.now()"));
+        assert!(freeze_script(None).starts_with("const at = null;\n"));
+    }
+
+    #[test]
+    fn test_step_then_unfreeze_restores_originals() {
+        let step = step_script(250);
+        assert!(step.starts_with("const ms = 250;\n"));
+        assert!(step.contains("call freeze_time first"));
+
+        for name in [
+            "Date",
+            "setTimeout",
+            "clearTimeout",
+            "setInterval",
+            "clearInterval",
+            "requestAnimationFrame",
+            "cancelAnimationFrame",
+        ] {
+            assert!(FREEZE_SCRIPT.contains(&format!("{name}: window.{name}")));
+            assert!(UNFREEZE_SCRIPT.contains(&format!("window.{name} = originals.{name};")));
+        }
+        assert!(UNFREEZE_SCRIPT.contains("delete window.__MCP_CLOCK__;"));
+    }
+}
@@ -0,0 +1,121 @@
+//! Waiting for application events.
+
+use serde_json::Value;
+use std::time::Duration;
+use tauri::{command, AppHandle, Listener, Runtime};
+use tokio::sync::oneshot;
+
+/// Default time to wait for an event before giving up.
+const DEFAULT_WAIT_TIMEOUT_MS: u64 = 10_000;
+
+/// Waits for the application to emit a specific event.
+///
+/// The counterpart to [`emit_event`](super::emit_event::emit_event): registers a
+/// one-shot listener and resolves with the event payload when it fires, so
+/// automation can synchronize with the app lifecycle (e.g. "data-loaded").
+///
+/// # Arguments
+///
+/// * `app` - The Tauri application handle
+/// * `event_name` - Name of the event to wait for
+/// * `timeout_ms` - Maximum time to wait in milliseconds (default 10000)
+///
+/// # Returns
+///
+/// * `Ok(Value)` - `{ eventName, payload }` where `payload` is the parsed JSON payload
+/// * `Err(String)` - Error message if the event did not fire in time, or the
+///   wait was cancelled with `abort_all`
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { payload } = await invoke('plugin:mcp-bridge|wait_for_event', {
+///   eventName: 'data-loaded',
+///   timeoutMs: 5000
+/// });
+/// ```
+#[command]
+pub async fn wait_for_event<R: Runtime>(
+    app: AppHandle<R>,
+    event_name: String,
+    timeout_ms: Option<u64>,
+) -> Result<Value, String> {
+    let (tx, rx) = oneshot::channel::<String>();
+
+    let event_id = app.once(event_name.clone(), move |event| {
+        let _ = tx.send(event.payload().to_string());
+    });
+
+    let timeout_ms = timeout_ms.unwrap_or(DEFAULT_WAIT_TIMEOUT_MS);
+    let wait = async { Ok(tokio::time::timeout(Duration::from_millis(timeout_ms), rx).await) };
+
+    match crate::abort::cancellable(&app, wait).await {
+        Ok(Ok(Ok(raw_payload))) => Ok(serde_json::json!({
+            "eventName": event_name,
+            "payload": parse_payload(&raw_payload),
+        })),
+        Ok(Ok(Err(_))) => Err(format!("Listener for event '{event_name}' was dropped")),
+        Ok(Err(_)) => {
+            app.unlisten(event_id);
+            Err(format!(
+                "Timed out after {timeout_ms}ms waiting for event '{event_name}'"
+            ))
+        }
+        Err(aborted) => {
+            app.unlisten(event_id);
+            Err(aborted)
+        }
+    }
+}
+
+/// Parses an event payload as JSON, falling back to the raw string.
+fn parse_payload(raw: &str) -> Value {
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::Emitter;
+
+    #[tokio::test]
+    async fn test_wait_for_event_returns_payload() {
+        let app = tauri::test::mock_app();
+        let handle = app.handle().clone();
+
+        let waiter = tokio::spawn(wait_for_event(
+            handle.clone(),
+            "data-loaded".to_string(),
+            Some(2000),
+        ));
+
+        // Let the listener register before emitting
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle
+            .emit("data-loaded", serde_json::json!({ "rows": 3 }))
+            .unwrap();
+
+        let result = waiter.await.unwrap().unwrap();
+        assert_eq!(result["eventName"], "data-loaded");
+        assert_eq!(result["payload"]["rows"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_event_times_out() {
+        let app = tauri::test::mock_app();
+
+        let err = wait_for_event(app.handle().clone(), "never".to_string(), Some(20))
+            .await
+            .unwrap_err();
+
+        assert!(err.contains("Timed out after 20ms"));
+    }
+
+    #[test]
+    fn test_parse_payload_falls_back_to_string() {
+        assert_eq!(parse_payload(r#"{"a":1}"#)["a"], 1);
+        assert_eq!(parse_payload("not json"), Value::String("not json".into()));
+    }
+}
@@ -0,0 +1,142 @@
+//! Core web vitals of a page.
+//!
+//! `bridge.js` registers `PerformanceObserver`s for `layout-shift`,
+//! `largest-contentful-paint`, `first-input` and `event` entries before the
+//! page's own scripts run, and keeps the metrics in
+//! `window.__MCP_WEB_VITALS__`. WebKit webviews (macOS, iOS, Linux) don't
+//! report every entry type; metrics whose entry type is unsupported stay
+//! `null` and the type is listed in `unsupported`.
+
+use crate::commands::{dom, ScriptExecutor};
+use serde::{Deserialize, Serialize};
+use tauri::{command, Runtime, State, WebviewWindow};
+
+/// Reads the metrics recorded by `bridge.js`.
+const WEB_VITALS_JS: &str = r#"const vitals = window.__MCP_WEB_VITALS__;
+if (!vitals) { throw new Error('Web vitals are not being recorded; is the bridge script injected?'); }
+return vitals;"#;
+
+/// Metrics as recorded in the page.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct RecordedVitals {
+    cls: Option<f64>,
+    lcp: Option<f64>,
+    fid: Option<f64>,
+    inp: Option<f64>,
+    #[serde(default)]
+    unsupported: Vec<String>,
+}
+
+/// Result of `get_web_vitals`. Metrics not available yet are `null`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebVitals {
+    /// Cumulative layout shift: the largest burst of unexpected layout
+    /// shifts. 0 on a page that hasn't shifted.
+    pub cls: Option<f64>,
+    /// Largest contentful paint, in milliseconds since navigation start
+    pub lcp_ms: Option<f64>,
+    /// First input delay, once the user has interacted with the page
+    pub fid_ms: Option<f64>,
+    /// Interaction to next paint: the slowest interaction so far
+    pub inp_ms: Option<f64>,
+    /// Performance entry types the webview doesn't report
+    pub unsupported: Vec<String>,
+}
+
+impl From<RecordedVitals> for WebVitals {
+    fn from(recorded: RecordedVitals) -> Self {
+        Self {
+            cls: recorded.cls,
+            lcp_ms: recorded.lcp,
+            fid_ms: recorded.fid,
+            inp_ms: recorded.inp,
+            unsupported: recorded.unsupported,
+        }
+    }
+}
+
+/// Returns a window's core web vitals: layout shift, largest contentful
+/// paint, first input delay and interaction to next paint.
+///
+/// The metrics come from observers installed at document start, so shifts
+/// and paints during the first load are included. LCP is final once the
+/// user interacts with the page; FID and INP need an interaction first.
+///
+/// # Arguments
+///
+/// * `window` - The window to measure
+/// * `executor_state` - Shared script executor state
+///
+/// # Returns
+///
+/// * `Ok(WebVitals)` - `{ cls, lcpMs, fidMs, inpMs, unsupported }`, with
+///   `null` for metrics not available yet
+/// * `Err(String)` - If the bridge script isn't recording the page
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const { cls, lcpMs } = await invoke('plugin:mcp-bridge|get_web_vitals');
+/// ```
+#[command]
+pub async fn get_web_vitals<R: Runtime>(
+    window: WebviewWindow<R>,
+    executor_state: State<'_, ScriptExecutor>,
+) -> Result<WebVitals, String> {
+    let data = dom::evaluate(window, WEB_VITALS_JS.to_string(), executor_state).await?;
+    let recorded: RecordedVitals =
+        serde_json::from_value(data).map_err(|e| format!("Unexpected web vitals: {e}"))?;
+    Ok(recorded.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn vitals(recorded: serde_json::Value) -> serde_json::Value {
+        let recorded: RecordedVitals = serde_json::from_value(recorded).unwrap();
+        serde_json::to_value(WebVitals::from(recorded)).unwrap()
+    }
+
+    #[test]
+    fn test_lcp_is_reported_after_load() {
+        let loaded = vitals(json!({
+            "cls": 0.04,
+            "lcp": 812.5,
+            "fid": null,
+            "inp": null,
+            "unsupported": []
+        }));
+
+        assert_eq!(loaded["lcpMs"], 812.5);
+        assert_eq!(loaded["cls"], 0.04);
+        // No interaction yet
+        assert!(loaded["fidMs"].is_null() && loaded["inpMs"].is_null());
+
+        // The observer is installed before the page's scripts run
+        let bridge = include_str!("../bridge.js");
+        let installed = bridge.find("   trackWebVitals();").unwrap();
+        assert!(installed < bridge.find("   waitForTauri(function()").unwrap());
+        assert!(bridge.contains("observe('largest-contentful-paint'"));
+    }
+
+    #[test]
+    fn test_unsupported_metrics_stay_null() {
+        let webkit = vitals(json!({
+            "cls": null,
+            "lcp": null,
+            "fid": 3,
+            "inp": null,
+            "unsupported": ["layout-shift", "largest-contentful-paint", "event"]
+        }));
+
+        assert!(webkit["cls"].is_null() && webkit["lcpMs"].is_null());
+        assert_eq!(webkit["fidMs"], 3.0);
+        assert_eq!(webkit["unsupported"][1], "largest-contentful-paint");
+        assert!(WEB_VITALS_JS.contains("window.__MCP_WEB_VITALS__"));
+    }
+}
@@ -0,0 +1,235 @@
+//! Window chrome flags (always-on-top, decorations, resizable, fullscreen).
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{command, Runtime, WebviewWindow};
+
+/// A window flag that can be queried and toggled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowFlag {
+    AlwaysOnTop,
+    Decorations,
+    Resizable,
+    Fullscreen,
+}
+
+impl WindowFlag {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::AlwaysOnTop => "always-on-top",
+            Self::Decorations => "decorations",
+            Self::Resizable => "resizable",
+            Self::Fullscreen => "fullscreen",
+        }
+    }
+}
+
+/// Current state of all window flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowFlagStates {
+    pub always_on_top: bool,
+    pub decorations: bool,
+    pub resizable: bool,
+    pub fullscreen: bool,
+}
+
+/// Windows whose flags can be read and written.
+pub trait FlagControl {
+    /// Returns the current value of `flag`.
+    fn flag(&self, flag: WindowFlag) -> tauri::Result<bool>;
+
+    /// Sets `flag` to `value`.
+    fn set_flag(&self, flag: WindowFlag, value: bool) -> tauri::Result<()>;
+}
+
+impl<R: Runtime> FlagControl for WebviewWindow<R> {
+    fn flag(&self, flag: WindowFlag) -> tauri::Result<bool> {
+        match flag {
+            WindowFlag::AlwaysOnTop => self.is_always_on_top(),
+            WindowFlag::Decorations => self.is_decorated(),
+            WindowFlag::Resizable => self.is_resizable(),
+            WindowFlag::Fullscreen => self.is_fullscreen(),
+        }
+    }
+
+    fn set_flag(&self, flag: WindowFlag, value: bool) -> tauri::Result<()> {
+        match flag {
+            WindowFlag::AlwaysOnTop => self.set_always_on_top(value),
+            WindowFlag::Decorations => self.set_decorations(value),
+            WindowFlag::Resizable => self.set_resizable(value),
+            WindowFlag::Fullscreen => self.set_fullscreen(value),
+        }
+    }
+}
+
+/// Reads the current state of all window flags.
+pub fn read_flag_states(window: &impl FlagControl) -> Result<WindowFlagStates, String> {
+    let read = |flag: WindowFlag| {
+        window
+            .flag(flag)
+            .map_err(|e| format!("Failed to get {}: {e}", flag.name()))
+    };
+
+    Ok(WindowFlagStates {
+        always_on_top: read(WindowFlag::AlwaysOnTop)?,
+        decorations: read(WindowFlag::Decorations)?,
+        resizable: read(WindowFlag::Resizable)?,
+        fullscreen: read(WindowFlag::Fullscreen)?,
+    })
+}
+
+/// Applies each requested flag and returns the resulting states.
+fn apply_flags(
+    window: &impl FlagControl,
+    requested: &[(WindowFlag, Option<bool>)],
+) -> Result<WindowFlagStates, String> {
+    for (flag, value) in requested {
+        if let Some(value) = value {
+            window
+                .set_flag(*flag, *value)
+                .map_err(|e| format!("Failed to set {}: {e}", flag.name()))?;
+        }
+    }
+
+    read_flag_states(window)
+}
+
+/// Sets window chrome flags.
+///
+/// Only the flags present in the request are changed; omitted flags keep
+/// their current value.
+///
+/// # Arguments
+///
+/// * `window` - The window to update
+/// * `always_on_top` - Keep the window above other windows
+/// * `decorations` - Show native title bar and borders
+/// * `resizable` - Allow the user to resize the window
+/// * `fullscreen` - Make the window fullscreen
+///
+/// # Returns
+///
+/// * `Ok(Value)` - Resulting `{ alwaysOnTop, decorations, resizable, fullscreen }`
+/// * `Err(String)` - Error message if a flag could not be applied
+///
+/// # Examples
+///
+/// ```typescript
+/// import { invoke } from '@tauri-apps/api/core';
+///
+/// const flags = await invoke('plugin:mcp-bridge|set_window_flags', {
+///   alwaysOnTop: true,
+///   decorations: false
+/// });
+/// ```
+#[command]
+pub async fn set_window_flags<R: Runtime>(
+    window: WebviewWindow<R>,
+    always_on_top: Option<bool>,
+    decorations: Option<bool>,
+    resizable: Option<bool>,
+    fullscreen: Option<bool>,
+) -> Result<Value, String> {
+    let states = apply_flags(
+        &window,
+        &[
+            (WindowFlag::AlwaysOnTop, always_on_top),
+            (WindowFlag::Decorations, decorations),
+            (WindowFlag::Resizable, resizable),
+            (WindowFlag::Fullscreen, fullscreen),
+        ],
+    )?;
+
+    serde_json::to_value(states).map_err(|e| format!("Failed to serialize window flags: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// In-memory window recording flag changes.
+    struct FakeWindow {
+        states: RefCell<WindowFlagStates>,
+        writes: RefCell<Vec<WindowFlag>>,
+    }
+
+    impl FakeWindow {
+        fn new() -> Self {
+            Self {
+                states: RefCell::new(WindowFlagStates {
+                    always_on_top: false,
+                    decorations: true,
+                    resizable: true,
+                    fullscreen: false,
+                }),
+                writes: RefCell::new(Vec::new()),
+            }
+        }
+    }
+
+    impl FlagControl for FakeWindow {
+        fn flag(&self, flag: WindowFlag) -> tauri::Result<bool> {
+            let states = self.states.borrow();
+            Ok(match flag {
+                WindowFlag::AlwaysOnTop => states.always_on_top,
+                WindowFlag::Decorations => states.decorations,
+                WindowFlag::Resizable => states.resizable,
+                WindowFlag::Fullscreen => states.fullscreen,
+            })
+        }
+
+        fn set_flag(&self, flag: WindowFlag, value: bool) -> tauri::Result<()> {
+            self.writes.borrow_mut().push(flag);
+            let mut states = self.states.borrow_mut();
+            match flag {
+                WindowFlag::AlwaysOnTop => states.always_on_top = value,
+                WindowFlag::Decorations => states.decorations = value,
+                WindowFlag::Resizable => states.resizable = value,
+                WindowFlag::Fullscreen => states.fullscreen = value,
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_toggle_always_on_top_is_reflected() {
+        let window = FakeWindow::new();
+
+        let states = apply_flags(&window, &[(WindowFlag::AlwaysOnTop, Some(true))]).unwrap();
+        assert!(states.always_on_top);
+
+        let states = apply_flags(&window, &[(WindowFlag::AlwaysOnTop, Some(false))]).unwrap();
+        assert!(!states.always_on_top);
+    }
+
+    #[test]
+    fn test_only_present_flags_are_applied() {
+        let window = FakeWindow::new();
+
+        let states = apply_flags(
+            &window,
+            &[
+                (WindowFlag::AlwaysOnTop, None),
+                (WindowFlag::Decorations, Some(false)),
+                (WindowFlag::Resizable, None),
+                (WindowFlag::Fullscreen, None),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(*window.writes.borrow(), vec![WindowFlag::Decorations]);
+        assert!(!states.decorations);
+        assert!(states.resizable);
+        assert_eq!(
+            serde_json::to_value(states).unwrap(),
+            serde_json::json!({
+                "alwaysOnTop": false,
+                "decorations": false,
+                "resizable": true,
+                "fullscreen": false
+            })
+        );
+    }
+}
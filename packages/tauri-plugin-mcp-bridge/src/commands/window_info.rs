@@ -1,5 +1,6 @@
 //! Window information retrieval.
 
+use crate::commands::window_flags::read_flag_states;
 use serde_json::Value;
 use tauri::{command, Runtime, WebviewWindow};
 
@@ -22,6 +23,7 @@ use tauri::{command, Runtime, WebviewWindow};
 ///   - `title`: Window title string
 ///   - `focused`: Whether the window has focus
 ///   - `visible`: Whether the window is visible
+///   - `alwaysOnTop`, `decorations`, `resizable`, `fullscreen`: Window flags
 /// * `Err(String)` - Error message if retrieval fails
 ///
 /// # Examples
@@ -49,6 +51,7 @@ pub async fn get_window_info<R: Runtime>(window: WebviewWindow<R>) -> Result<Val
     let is_visible = window
         .is_visible()
         .map_err(|e| format!("Failed to get visibility: {e}"))?;
+    let flags = read_flag_states(&window)?;
 
     Ok(serde_json::json!({
         "width": size.width,
@@ -58,5 +61,9 @@ pub async fn get_window_info<R: Runtime>(window: WebviewWindow<R>) -> Result<Val
         "title": title,
         "focused": is_focused,
         "visible": is_visible,
+        "alwaysOnTop": flags.always_on_top,
+        "decorations": flags.decorations,
+        "resizable": flags.resizable,
+        "fullscreen": flags.fullscreen,
     }))
 }
@@ -2,9 +2,111 @@
 //!
 //! This module provides configuration options for customizing the plugin behavior,
 //! including the WebSocket server bind address and port.
+//!
+//! Configuration can also be loaded from a TOML or JSON file with
+//! [`Config::from_file`] or [`Builder::from_file`]. Keys match the builder
+//! method names; omitted keys keep their defaults:
+//!
+//! ```toml
+//! bind_address = "127.0.0.1"
+//! port = 9225
+//! base_port = 9223
+//! port_scan_attempts = 100
+//! port_lock_dir = "/tmp/mcp-bridge-ports"
+//! max_result_bytes = 1048576
+//! js_sandbox = true
+//! scripts_dir = "automation/scripts"
+//! baselines_dir = "tests/baselines"
+//! log_file = "logs/mcp-bridge.log"
+//! log_file_max_bytes = 1048576
+//! window_denylist = ["payment"]
+//!
+//! [event_schemas.user-updated]
+//! type = "object"
+//! required = ["id"]
+//! ```
+
+use crate::intercept::{InterceptDecision, RequestInterceptor};
+use crate::macros::Step;
+use crate::pipeline::WsRequest;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Default first port tried when auto-selecting a port.
+pub const DEFAULT_BASE_PORT: u16 = 9223;
+
+/// Default number of consecutive ports tried when auto-selecting a port.
+pub const DEFAULT_PORT_SCAN_ATTEMPTS: u16 = 100;
+
+/// Default upper bound for a serialized `execute_js` result (10 MiB).
+pub const DEFAULT_MAX_RESULT_BYTES: usize = 10 * 1024 * 1024;
+
+/// Default size above which `execute_js` results are sent in chunks (1 MiB).
+pub const DEFAULT_CHUNK_THRESHOLD_BYTES: usize = 1024 * 1024;
+
+/// Commands unauthenticated clients may call by default, see
+/// [`Builder::read_only_commands`].
+pub const DEFAULT_READ_ONLY_COMMANDS: &[&str] =
+    &["get_system_status", "list_windows", "get_backend_state"];
+
+/// Default maximum number of registered scripts.
+pub const DEFAULT_MAX_SCRIPTS: usize = 100;
+
+/// Default maximum combined size of registered scripts (5 MiB).
+pub const DEFAULT_MAX_SCRIPT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Default idle time before the first TCP keepalive probe.
+pub const DEFAULT_TCP_KEEPALIVE_IDLE: Duration = Duration::from_secs(30);
+
+/// Default time between unanswered TCP keepalive probes.
+pub const DEFAULT_TCP_KEEPALIVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Default longest time a WebSocket command may run.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Default number of commands a WebSocket connection runs at once.
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 1;
+
+/// Value shown in place of secrets by [`Config::redacted`].
+pub const REDACTED: &str = "***";
+
+/// Parts of a key that mark its value as a secret.
+const SECRET_KEY_MARKERS: &[&str] = &["token", "secret", "password"];
+
+/// Errors that can occur while loading a configuration file.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The file could not be read
+    #[error("Failed to read config file {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    /// The file extension is not `.toml` or `.json`
+    #[error("Unsupported config file format: {0} (expected .toml or .json)")]
+    UnsupportedFormat(String),
+
+    /// The file contents are not a valid configuration
+    #[error("Invalid config file: {0}")]
+    Parse(String),
+
+    /// The auth token could not be loaded from the OS keychain
+    #[cfg(feature = "keyring")]
+    #[error("Failed to load auth token from keychain entry {service}/{account}: {message}")]
+    Keyring {
+        service: String,
+        account: String,
+        message: String,
+    },
+}
 
 /// Configuration for the MCP Bridge plugin.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct Config {
     /// The address to bind the WebSocket server to.
     /// Default: "0.0.0.0" (all interfaces, for remote device support)
@@ -13,8 +115,184 @@ pub struct Config {
 
     /// Optional explicit port for the WebSocket server.
     /// When `Some(port)`, the server will use exactly this port and fail if unavailable.
-    /// When `None`, the server auto-selects from the range 9223-9322.
+    /// When `None`, the server auto-selects from the range starting at
+    /// `base_port`.
     pub port: Option<u16>,
+
+    /// First port tried when auto-selecting a port.
+    /// Default: 9223
+    pub base_port: u16,
+
+    /// Number of consecutive ports tried when auto-selecting a port.
+    /// Default: 100 (9223-9322)
+    pub port_scan_attempts: u16,
+
+    /// Directory where instances claim auto-selected ports with lock files,
+    /// so instances starting together don't pick the same port.
+    /// Default: None (no claims)
+    pub port_lock_dir: Option<PathBuf>,
+
+    /// Waits for [`McpBridgeHandle::activate`](crate::McpBridgeHandle::activate)
+    /// before binding a port, instead of starting the server during setup.
+    /// Default: false
+    pub lazy_start: bool,
+
+    /// Token clients must send in an `authenticate` request before any
+    /// other command. Shown as `"***"` by `get_config`.
+    /// Default: None (no authentication)
+    pub auth_token: Option<String>,
+
+    /// Commands connections may call before authenticating, when
+    /// `auth_token` is set. Others get an `unauthorized` error.
+    /// Default: `get_system_status`, `list_windows`, `get_backend_state`
+    pub read_only_commands: Vec<String>,
+
+    /// Namespace WebSocket commands are sent under, as in `mcp.execute_js`.
+    /// Commands outside it are ignored.
+    /// Default: None (unqualified commands)
+    pub command_namespace: Option<String>,
+
+    /// Whether connections also answer MCP JSON-RPC messages, see
+    /// [`mcp`](crate::mcp).
+    /// Default: false
+    pub mcp_mode: bool,
+
+    /// Maximum size in bytes of a serialized `execute_js` result.
+    /// Larger results are replaced with a `result_too_large` error.
+    /// Can be overridden per request with the `maxResultBytes` argument.
+    /// Default: 10 MiB
+    pub max_result_bytes: usize,
+
+    /// Serialized `execute_js` results larger than this many bytes are sent
+    /// over WebSocket as a series of chunk frames of at most this size.
+    /// `0` sends every result in a single frame.
+    /// Default: 1 MiB
+    pub chunk_threshold_bytes: usize,
+
+    /// JSON schemas for event payloads, keyed by event name.
+    /// `emit_event` validates payloads of registered events before emitting;
+    /// unregistered events pass through unvalidated.
+    pub event_schemas: HashMap<String, Value>,
+
+    /// Macros `run_macro` can run, keyed by name. See [`macros`](crate::macros).
+    /// Default: empty
+    pub macros: HashMap<String, Vec<Step>>,
+
+    /// Makes the `execute_js` command return successful results as the bare
+    /// value instead of `{ success: true, data }`. Can be overridden per
+    /// request with the `unwrap` argument.
+    /// Default: false
+    pub unwrap_results: bool,
+
+    /// Runs `execute_js` scripts with network, storage and dynamic code
+    /// globals shadowed to throw. Best-effort defense-in-depth, not a
+    /// security boundary.
+    /// Default: false
+    pub js_sandbox: bool,
+
+    /// Directory `execute_js_file` is restricted to. Relative script paths
+    /// are resolved against it.
+    /// Default: None (`execute_js_file` is disabled)
+    pub scripts_dir: Option<PathBuf>,
+
+    /// Directory `compare_screenshot` loads baselines from. Baseline paths
+    /// are resolved against it.
+    /// Default: None (`compare_screenshot` is disabled)
+    pub baselines_dir: Option<PathBuf>,
+
+    /// Runs webview operations (`execute_js`, screenshots) on the same window
+    /// one at a time. Different windows still run in parallel.
+    /// Default: false
+    pub serialize_per_window: bool,
+
+    /// Closes connections that send no message for this long. Stored in
+    /// config files as `idle_timeout_ms`.
+    /// Default: None (disabled)
+    #[serde(rename = "idle_timeout_ms", with = "optional_millis")]
+    pub idle_timeout: Option<Duration>,
+
+    /// Longest a WebSocket command may run before it fails with a timeout
+    /// error. Requests can ask for less with `timeoutMs`, and connections
+    /// with `set_timeout`, but never for more. Stored in config files as
+    /// `command_timeout_ms`.
+    /// Default: 60 seconds
+    #[serde(rename = "command_timeout_ms", with = "millis")]
+    pub command_timeout: Duration,
+
+    /// Most commands one WebSocket connection runs at once. Further requests
+    /// wait until one of them is answered; 0 counts as 1.
+    /// Default: 1 (one at a time, answered in order)
+    pub max_concurrent_requests: usize,
+
+    /// Sets `TCP_NODELAY` on accepted connections, so small frames aren't
+    /// delayed by Nagle's algorithm.
+    /// Default: true
+    pub tcp_nodelay: bool,
+
+    /// Enables TCP keepalive on accepted connections, so the OS resets
+    /// connections to vanished peers.
+    /// Default: true
+    pub tcp_keepalive: bool,
+
+    /// Idle time before the first keepalive probe. Stored in config files as
+    /// `tcp_keepalive_idle_ms`.
+    /// Default: 30 seconds
+    #[serde(rename = "tcp_keepalive_idle_ms", with = "millis")]
+    pub tcp_keepalive_idle: Duration,
+
+    /// Time between unanswered keepalive probes. Stored in config files as
+    /// `tcp_keepalive_interval_ms`.
+    /// Default: 10 seconds
+    #[serde(rename = "tcp_keepalive_interval_ms", with = "millis")]
+    pub tcp_keepalive_interval: Duration,
+
+    /// Scripts injected into every webview after `bridge.js`, in order.
+    /// Default: empty
+    pub additional_init_scripts: Vec<String>,
+
+    /// Targets the focused window, instead of `"main"`, when a command
+    /// doesn't name one. Falls back to `"main"` if no window has focus.
+    /// Default: false
+    pub default_to_active_window: bool,
+
+    /// Maximum number of scripts `register_script` accepts.
+    /// Default: 100
+    pub max_scripts: usize,
+
+    /// Maximum combined size in bytes of registered script contents.
+    /// Default: 5 MiB
+    pub max_script_bytes: usize,
+
+    /// File the bridge's `[MCP]` log lines are also appended to, one JSON
+    /// object per line.
+    /// Default: None (stdout/stderr only)
+    pub log_file: Option<PathBuf>,
+
+    /// Size in bytes at which `log_file` is rotated to `<log_file>.1`.
+    /// Default: None (never rotated)
+    pub log_file_max_bytes: Option<u64>,
+
+    /// Broadcasts a `metrics` frame to connected clients at this interval,
+    /// raised to [`MIN_METRICS_INTERVAL`](crate::metrics::MIN_METRICS_INTERVAL)
+    /// if shorter. Stored in config files as `metrics_interval_ms`.
+    /// Default: None (disabled)
+    #[serde(rename = "metrics_interval_ms", with = "optional_millis")]
+    pub metrics_interval: Option<Duration>,
+
+    /// Labels of the only windows commands may target.
+    /// Default: None (every window)
+    pub window_allowlist: Option<Vec<String>>,
+
+    /// Labels of windows commands may never target. Takes precedence over
+    /// `window_allowlist`.
+    /// Default: empty
+    pub window_denylist: Vec<String>,
+
+    /// Decides on every authorized request before it is dispatched. Set with
+    /// [`Builder::request_interceptor`]; not read from config files.
+    /// Default: None
+    #[serde(skip)]
+    pub request_interceptor: Option<RequestInterceptor>,
 }
 
 impl Default for Config {
@@ -22,6 +300,43 @@ impl Default for Config {
         Self {
             bind_address: "0.0.0.0".to_string(),
             port: None,
+            base_port: DEFAULT_BASE_PORT,
+            port_scan_attempts: DEFAULT_PORT_SCAN_ATTEMPTS,
+            port_lock_dir: None,
+            lazy_start: false,
+            auth_token: None,
+            read_only_commands: DEFAULT_READ_ONLY_COMMANDS
+                .iter()
+                .map(|command| command.to_string())
+                .collect(),
+            command_namespace: None,
+            mcp_mode: false,
+            max_result_bytes: DEFAULT_MAX_RESULT_BYTES,
+            chunk_threshold_bytes: DEFAULT_CHUNK_THRESHOLD_BYTES,
+            event_schemas: HashMap::new(),
+            macros: HashMap::new(),
+            unwrap_results: false,
+            js_sandbox: false,
+            scripts_dir: None,
+            baselines_dir: None,
+            serialize_per_window: false,
+            idle_timeout: None,
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            tcp_nodelay: true,
+            tcp_keepalive: true,
+            tcp_keepalive_idle: DEFAULT_TCP_KEEPALIVE_IDLE,
+            tcp_keepalive_interval: DEFAULT_TCP_KEEPALIVE_INTERVAL,
+            additional_init_scripts: Vec::new(),
+            default_to_active_window: false,
+            max_scripts: DEFAULT_MAX_SCRIPTS,
+            max_script_bytes: DEFAULT_MAX_SCRIPT_BYTES,
+            log_file: None,
+            log_file_max_bytes: None,
+            metrics_interval: None,
+            window_allowlist: None,
+            window_denylist: Vec::new(),
+            request_interceptor: None,
         }
     }
 }
@@ -31,17 +346,106 @@ impl Config {
     pub fn new(bind_address: &str) -> Self {
         Self {
             bind_address: bind_address.to_string(),
-            port: None,
+            ..Self::default()
         }
     }
 
     /// Creates a configuration that binds to localhost only.
     pub fn localhost_only() -> Self {
-        Self {
-            bind_address: "127.0.0.1".to_string(),
-            port: None,
+        Self::new("127.0.0.1")
+    }
+
+    /// Whether commands may target the window with `label`, according to
+    /// `window_allowlist` and `window_denylist`.
+    pub fn allows_window(&self, label: &str) -> bool {
+        let denied = self.window_denylist.iter().any(|l| l == label);
+        let allowed = self
+            .window_allowlist
+            .as_ref()
+            .map_or(true, |list| list.iter().any(|l| l == label));
+        allowed && !denied
+    }
+
+    /// The configuration in config file form, with secrets replaced by
+    /// [`REDACTED`].
+    ///
+    /// Keys containing `token`, `secret` or `password` are masked whenever
+    /// they are set, so the result can be shared or logged safely.
+    pub fn redacted(&self) -> Value {
+        let mut value = serde_json::to_value(self).unwrap_or(Value::Null);
+        if let Value::Object(fields) = &mut value {
+            redact_secrets(fields);
+        }
+        value
+    }
+
+    /// The complete init script: `bridge.js` followed by any additional
+    /// scripts, each separated by a newline.
+    pub(crate) fn init_script(&self) -> String {
+        let mut script = include_str!("bridge.js").to_string();
+        for extra in &self.additional_init_scripts {
+            script.push('\n');
+            script.push_str(extra);
+        }
+        script
+    }
+
+    /// Loads a configuration from a TOML or JSON file.
+    ///
+    /// The format is chosen from the file extension. Keys that are not
+    /// present in the file keep their default values; unknown keys are
+    /// rejected so typos don't go unnoticed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a `.toml` or `.json` file
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Config)` - The loaded configuration
+    /// * `Err(ConfigError)` - If the file cannot be read or parsed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tauri_plugin_mcp_bridge::Config;
+    ///
+    /// let config = Config::from_file("mcp-bridge.toml").expect("valid config");
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        // Check the format before touching the filesystem
+        if extension != "toml" && extension != "json" {
+            return Err(ConfigError::UnsupportedFormat(path.display().to_string()));
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+        if extension == "toml" {
+            Self::from_toml_str(&contents)
+        } else {
+            Self::from_json_str(&contents)
         }
     }
+
+    /// Parses a configuration from TOML text.
+    pub fn from_toml_str(contents: &str) -> Result<Self, ConfigError> {
+        toml::from_str(contents).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
+
+    /// Parses a configuration from JSON text.
+    pub fn from_json_str(contents: &str) -> Result<Self, ConfigError> {
+        serde_json::from_str(contents).map_err(|e| ConfigError::Parse(e.to_string()))
+    }
 }
 
 /// Builder for creating a configured MCP Bridge plugin.
@@ -65,7 +469,7 @@ impl Config {
 ///     .build();
 /// ```
 pub struct Builder {
-    config: Config,
+    pub(crate) config: Config,
 }
 
 impl Default for Builder {
@@ -82,6 +486,31 @@ impl Builder {
         }
     }
 
+    /// Creates a builder starting from a TOML or JSON configuration file.
+    ///
+    /// Builder calls made afterwards override values from the file, which in
+    /// turn override the defaults. See [`Config::from_file`] for the format.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path to a `.toml` or `.json` file
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Port from the file is replaced by the explicit call
+    /// let builder = Builder::from_file("mcp-bridge.toml")
+    ///     .expect("valid config")
+    ///     .port(9230);
+    /// ```
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Ok(Self {
+            config: Config::from_file(path)?,
+        })
+    }
+
     /// Sets the bind address for the WebSocket server.
     ///
     /// # Arguments
@@ -104,7 +533,8 @@ impl Builder {
     ///
     /// When set, the plugin will use exactly this port and fail if it's
     /// unavailable (strict mode). When not set, the plugin auto-selects
-    /// from the range 9223-9322.
+    /// from the range configured with [`port_range`](Self::port_range)
+    /// (9223-9322 by default).
     ///
     /// # Arguments
     ///
@@ -123,8 +553,1185 @@ impl Builder {
         self
     }
 
-    /// Builds the plugin with the configured options.
-    pub fn build<R: tauri::Runtime>(self) -> tauri::plugin::TauriPlugin<R> {
-        crate::init_with_config(self.config)
+    /// Sets the range of ports tried when auto-selecting a port.
+    ///
+    /// Ports `base_port` through `base_port + attempts - 1` are tried in
+    /// order and the first free one is used. If none is free, plugin setup
+    /// fails instead of falling back to a port that can't be bound. Has no
+    /// effect when an explicit [`port`](Self::port) is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `base_port` - First port to try (default 9223)
+    /// * `attempts` - Number of consecutive ports to try (default 100)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Try ports 10000-10499
+    /// let builder = Builder::new().port_range(10000, 500);
+    /// ```
+    pub fn port_range(mut self, base_port: u16, attempts: u16) -> Self {
+        self.config.base_port = base_port;
+        self.config.port_scan_attempts = attempts;
+        self
+    }
+
+    /// Sets how many consecutive ports are tried when auto-selecting a port,
+    /// keeping the configured base port.
+    ///
+    /// # Arguments
+    ///
+    /// * `attempts` - Number of consecutive ports to try (default 100)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Try ports 9223-9722
+    /// let builder = Builder::new().port_scan_attempts(500);
+    /// ```
+    pub fn port_scan_attempts(mut self, attempts: u16) -> Self {
+        self.config.port_scan_attempts = attempts;
+        self
+    }
+
+    /// Coordinates auto-selected ports with other instances through a shared
+    /// lock directory.
+    ///
+    /// Each instance scanning for a port claims it with a
+    /// `mcp-bridge-<port>.lock` file holding its process ID before binding,
+    /// and skips ports claimed by running instances. The claim is held until
+    /// the app exits or a lazily started bridge is deactivated; claims left
+    /// by crashed instances are reclaimed. Explicit ports are not claimed.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory shared by the instances, created if missing
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().port_lock_dir(std::env::temp_dir().join("mcp-bridge-ports"));
+    /// ```
+    pub fn port_lock_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.port_lock_dir = Some(dir.into());
+        self
+    }
+
+    /// Defers binding a port until the app activates the bridge.
+    ///
+    /// The server then starts only when [`McpBridgeHandle::activate`] is
+    /// called, or when the app emits the `mcp-bridge:activate` event, so an
+    /// app that ships with the plugin doesn't listen on a port until
+    /// automation is wanted. [`McpBridgeHandle::deactivate`] (or the
+    /// `mcp-bridge:deactivate` event) closes the connections and releases
+    /// the port again. The port is chosen at each activation, following
+    /// [`port`](Self::port) or [`port_range`](Self::port_range); an
+    /// unavailable explicit port fails the activation instead of panicking.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to wait for activation
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().lazy_start(true);
+    /// ```
+    ///
+    /// [`McpBridgeHandle::activate`]: crate::McpBridgeHandle::activate
+    /// [`McpBridgeHandle::deactivate`]: crate::McpBridgeHandle::deactivate
+    pub fn lazy_start(mut self, enabled: bool) -> Self {
+        self.config.lazy_start = enabled;
+        self
+    }
+
+    /// Requires clients to authenticate with `token`.
+    ///
+    /// Each connection must send `{ command: "authenticate", args: { token } }`
+    /// before any command other than `hello` and the
+    /// [`read_only_commands`](Self::read_only_commands), which otherwise get
+    /// an `unauthorized` error. A wrong token closes the connection with
+    /// `4001 unauthorized`. The `capabilities` frame reports `auth: true`.
+    /// See [`crate::auth`].
+    ///
+    /// # Arguments
+    ///
+    /// * `token` - The shared secret clients must present
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().auth_token("s3cret");
+    /// ```
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.config.auth_token = Some(token.into());
+        self
+    }
+
+    /// Sets the commands connections may call without authenticating.
+    ///
+    /// Only applies with an [`auth_token`](Self::auth_token). Lets monitoring
+    /// dashboards read state without the token while everything else needs
+    /// it. Commands sent through `invoke_tauri` are named without the
+    /// `plugin:mcp-bridge|` prefix. Defaults to [`DEFAULT_READ_ONLY_COMMANDS`];
+    /// pass an empty list to require the token for everything.
+    ///
+    /// # Arguments
+    ///
+    /// * `commands` - Names of the read-only commands
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new()
+    ///     .auth_token("s3cret")
+    ///     .read_only_commands(&["list_windows", "get_window_info"]);
+    /// ```
+    pub fn read_only_commands(mut self, commands: &[&str]) -> Self {
+        self.config.read_only_commands = commands.iter().map(|c| c.to_string()).collect();
+        self
+    }
+
+    /// Expects WebSocket commands under `namespace`, as in `mcp.execute_js`.
+    ///
+    /// For channels the bridge shares with other consumers: commands outside
+    /// the namespace are ignored without a response, so another consumer can
+    /// answer them. The `capabilities` frame reports the namespace as
+    /// `commandNamespace`.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The prefix, without the trailing `.`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Clients send { "command": "mcp.list_windows" }
+    /// let builder = Builder::new().command_namespace("mcp");
+    /// ```
+    pub fn command_namespace(mut self, namespace: &str) -> Self {
+        self.config.command_namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Lets connections speak a minimal MCP tool protocol.
+    ///
+    /// Besides bridge requests, connections then answer the JSON-RPC
+    /// `initialize`, `tools/list` and `tools/call` methods, exposing the
+    /// bridge's commands as MCP tools, so a thin proxy suffices between the
+    /// bridge and an LLM. See the [`mcp`](crate::mcp) module for the details.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to answer MCP messages
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().mcp_mode(true);
+    /// ```
+    pub fn mcp_mode(mut self, enabled: bool) -> Self {
+        self.config.mcp_mode = enabled;
+        self
+    }
+
+    /// Requires clients to authenticate with a token stored in the OS
+    /// keychain (macOS Keychain, Windows Credential Manager, or the Secret
+    /// Service on Linux).
+    ///
+    /// The token is read once, when this is called, and then enforced as
+    /// with [`auth_token`](Self::auth_token). Requires the `keyring` feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `service` - Service name of the keychain entry
+    /// * `account` - Account (user) name of the keychain entry
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Builder)` - With the token configured
+    /// * `Err(ConfigError::Keyring)` - If the entry is missing or the
+    ///   keychain can't be read
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new()
+    ///     .auth_token_from_keyring("my-app", "mcp-bridge")
+    ///     .expect("auth token in keychain");
+    /// ```
+    #[cfg(feature = "keyring")]
+    pub fn auth_token_from_keyring(
+        self,
+        service: &str,
+        account: &str,
+    ) -> Result<Self, ConfigError> {
+        let entry = keyring::Entry::new(service, account)
+            .map_err(|e| keyring_error(service, account, e))?;
+        self.auth_token_from_entry(&entry, service, account)
+    }
+
+    /// Loads the auth token from a keychain entry.
+    #[cfg(feature = "keyring")]
+    fn auth_token_from_entry(
+        self,
+        entry: &keyring::Entry,
+        service: &str,
+        account: &str,
+    ) -> Result<Self, ConfigError> {
+        let token = entry
+            .get_password()
+            .map_err(|e| keyring_error(service, account, e))?;
+        Ok(self.auth_token(token))
+    }
+
+    /// Sets the maximum size of a serialized `execute_js` result.
+    ///
+    /// Scripts returning more than this many bytes (after `JSON.stringify`)
+    /// get a `result_too_large` error instead of the payload. Clients can
+    /// override the limit per request with the `maxResultBytes` argument.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The maximum result size in bytes
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Limit results to 1 MiB
+    /// let builder = Builder::new().max_result_bytes(1024 * 1024);
+    /// ```
+    pub fn max_result_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_result_bytes = bytes;
+        self
+    }
+
+    /// Sets the size above which WebSocket `execute_js` results are chunked.
+    ///
+    /// A larger result is announced with `{ chunked: true, totalBytes,
+    /// chunkCount, resultId }` and followed by `chunk` frames carrying up to
+    /// `bytes` of its JSON text each, keeping every frame small. `0` turns
+    /// chunking off.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The threshold and chunk size in bytes
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Send results over 256 KiB in 256 KiB chunks
+    /// let builder = Builder::new().chunk_threshold_bytes(256 * 1024);
+    /// ```
+    pub fn chunk_threshold_bytes(mut self, bytes: usize) -> Self {
+        self.config.chunk_threshold_bytes = bytes;
+        self
+    }
+
+    /// Makes `execute_js` return successful results without the envelope.
+    ///
+    /// By default the `execute_js` command resolves to `{ success: true,
+    /// data }` and `{ success: false, error }`. With this enabled a
+    /// successful result is the bare `data` value, for integrations that
+    /// have their own error channel. Errors and binary results keep the
+    /// envelope. Clients can override it per request with the `unwrap`
+    /// argument.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to unwrap successful results
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().unwrap_results(true);
+    /// ```
+    pub fn unwrap_results(mut self, enabled: bool) -> Self {
+        self.config.unwrap_results = enabled;
+        self
+    }
+
+    /// Registers a JSON schema for an event's payload.
+    ///
+    /// `emit_event` validates payloads for this event name against the schema
+    /// and returns a validation error instead of emitting on mismatch. See
+    /// [`event_schema`](crate::event_schema) for the supported keywords.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The event name
+    /// * `schema` - JSON schema the payload must match
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use serde_json::json;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().register_event_schema(
+    ///     "user-updated",
+    ///     json!({
+    ///         "type": "object",
+    ///         "required": ["id"],
+    ///         "properties": { "id": { "type": "integer" } }
+    ///     }),
+    /// );
+    /// ```
+    pub fn register_event_schema(mut self, name: &str, schema: Value) -> Self {
+        self.config.event_schemas.insert(name.to_string(), schema);
+        self
+    }
+
+    /// Registers a macro: a named sequence of typed steps agents can run
+    /// with `run_macro`, without sending raw JavaScript.
+    ///
+    /// Together with [`auth_token`](Self::auth_token), listing `run_macro`
+    /// and `list_macros` in [`read_only_commands`](Self::read_only_commands)
+    /// lets unauthenticated agents run the vetted macros while `execute_js`
+    /// stays behind the token. Registering a name again replaces the macro.
+    /// See [`macros`](crate::macros).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name `run_macro` is called with
+    /// * `steps` - The steps, run in order
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::macros::Step;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().register_macro(
+    ///     "open-item",
+    ///     vec![
+    ///         Step::Click { selector: "#item-{{id}}".into() },
+    ///         Step::Screenshot { format: None, quality: None },
+    ///     ],
+    /// );
+    /// ```
+    pub fn register_macro(mut self, name: &str, steps: Vec<Step>) -> Self {
+        self.config.macros.insert(name.to_string(), steps);
+        self
+    }
+
+    /// Enables sandboxed evaluation for `execute_js`.
+    ///
+    /// Scripts run in a scope where `fetch`, `XMLHttpRequest`, `WebSocket`,
+    /// `localStorage`, `sessionStorage`, `eval` and `Function` are shadowed
+    /// by values that throw when used. Read-only DOM APIs such as
+    /// `document.querySelector` keep working.
+    ///
+    /// This reduces the blast radius of a misbehaving agent but is **not** a
+    /// security boundary: scripts can still reach the originals through
+    /// `window.fetch`, constructors of existing functions and similar paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to sandbox `execute_js` scripts
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().js_sandbox(true);
+    /// ```
+    pub fn js_sandbox(mut self, enabled: bool) -> Self {
+        self.config.js_sandbox = enabled;
+        self
+    }
+
+    /// Enables `execute_js_file` for scripts inside a directory.
+    ///
+    /// Relative paths sent by clients are resolved against this directory,
+    /// and paths that resolve outside of it (via `..`, absolute paths or
+    /// symlinks) are rejected. Without it, `execute_js_file` fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory containing automation scripts
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().scripts_dir("automation/scripts");
+    /// ```
+    pub fn scripts_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.scripts_dir = Some(dir.into());
+        self
+    }
+
+    /// Enables `compare_screenshot` for baselines inside a directory.
+    ///
+    /// Baseline paths sent by clients are resolved against this directory,
+    /// and paths that resolve outside of it (via `..`, absolute paths or
+    /// symlinks) are rejected. Without it, `compare_screenshot` fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory containing baseline PNGs
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().baselines_dir("tests/baselines");
+    /// ```
+    pub fn baselines_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.config.baselines_dir = Some(dir.into());
+        self
+    }
+
+    /// Serializes webview operations per window.
+    ///
+    /// Concurrent `execute_js` and screenshot calls against the same window
+    /// can interleave and race on shared page globals. With this enabled they
+    /// queue behind each other, while calls to different windows still run in
+    /// parallel. This trades throughput on a single window for correctness.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to queue operations per window
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().serialize_per_window(true);
+    /// ```
+    pub fn serialize_per_window(mut self, enabled: bool) -> Self {
+        self.config.serialize_per_window = enabled;
+        self
+    }
+
+    /// Closes connections that stay idle for longer than `timeout`.
+    ///
+    /// The timer restarts whenever the client sends a text or binary
+    /// message. WebSocket ping/pong frames don't count, so a client whose
+    /// network stack is alive but which stopped issuing requests is still
+    /// closed, with close code `4408`. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - How long a connection may go without sending a message
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().idle_timeout(Duration::from_secs(300));
+    /// ```
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.config.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the longest a WebSocket command may run.
+    ///
+    /// A command still running after this long is answered with a timeout
+    /// error; it is not cancelled inside the webview. Each request can pass a
+    /// shorter `timeoutMs`, and each connection can lower its own default
+    /// with `set_timeout`. Defaults to 60 seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The cap on every command's run time
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().command_timeout(Duration::from_secs(20));
+    /// ```
+    pub fn command_timeout(mut self, timeout: Duration) -> Self {
+        self.config.command_timeout = timeout;
+        self
+    }
+
+    /// Sets how many commands one connection may run at once.
+    ///
+    /// By default a connection runs its commands one at a time and answers
+    /// them in order. With a higher limit, slow commands such as screenshots
+    /// or `wait_for_event` no longer hold up the requests behind them, and
+    /// responses may arrive out of order; clients match them by `id`.
+    /// Requests beyond the limit wait until a running command is answered.
+    /// Connection-level commands like `subscribe` or `set_timeout` still run
+    /// alone, once the commands before them are answered.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The most commands running at once per connection; 0 counts
+    ///   as 1
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().max_concurrent_requests(4);
+    /// ```
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.config.max_concurrent_requests = max;
+        self
+    }
+
+    /// Sets whether accepted connections use `TCP_NODELAY`.
+    ///
+    /// On by default, so command frames and responses go out immediately
+    /// instead of waiting for Nagle's algorithm to coalesce them.
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to set `TCP_NODELAY`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().tcp_nodelay(false);
+    /// ```
+    pub fn tcp_nodelay(mut self, enabled: bool) -> Self {
+        self.config.tcp_nodelay = enabled;
+        self
+    }
+
+    /// Sets the TCP keepalive timing of accepted connections.
+    ///
+    /// Keepalive is on by default, probing after 30 seconds of silence and
+    /// every 10 seconds after that. A peer that answers no probe has its
+    /// connection reset by the OS, which also ends the WebSocket connection.
+    /// Pass `None` to turn keepalive off.
+    ///
+    /// # Arguments
+    ///
+    /// * `timing` - `(idle, interval)`: the idle time before the first probe
+    ///   and the time between unanswered probes, or `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder =
+    ///     Builder::new().tcp_keepalive(Some((Duration::from_secs(60), Duration::from_secs(15))));
+    /// ```
+    pub fn tcp_keepalive(mut self, timing: Option<(Duration, Duration)>) -> Self {
+        self.config.tcp_keepalive = timing.is_some();
+        if let Some((idle, interval)) = timing {
+            self.config.tcp_keepalive_idle = idle;
+            self.config.tcp_keepalive_interval = interval;
+        }
+        self
+    }
+
+    /// Makes commands without a window label target the focused window.
+    ///
+    /// By default such commands target the window labelled `"main"`. With
+    /// this enabled they act on whatever window is in front, falling back to
+    /// `"main"` when none of the app's windows has focus (for example while
+    /// another application is active).
+    ///
+    /// # Arguments
+    ///
+    /// * `enabled` - Whether to default to the active window
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().default_to_active_window(true);
+    /// ```
+    pub fn default_to_active_window(mut self, enabled: bool) -> Self {
+        self.config.default_to_active_window = enabled;
+        self
+    }
+
+    /// Sets the maximum number of registered scripts.
+    ///
+    /// Registered scripts are re-injected on every page load, so a client
+    /// registering scripts in a loop would slow down each navigation.
+    /// `register_script` fails once the limit is reached; replacing a script
+    /// with the same ID is still allowed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of scripts
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().max_scripts(20);
+    /// ```
+    pub fn max_scripts(mut self, max: usize) -> Self {
+        self.config.max_scripts = max;
+        self
+    }
+
+    /// Sets the maximum combined size of registered script contents.
+    ///
+    /// `register_script` fails when a script would push the total past this
+    /// limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The maximum total size in bytes
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Allow up to 1 MiB of scripts
+    /// let builder = Builder::new().max_script_bytes(1024 * 1024);
+    /// ```
+    pub fn max_script_bytes(mut self, bytes: usize) -> Self {
+        self.config.max_script_bytes = bytes;
+        self
+    }
+
+    /// Mirrors the bridge's internal logs to a file.
+    ///
+    /// Useful for field debugging where stdout is lost. Every line passed to
+    /// the console is also appended to `path` as a JSON object with
+    /// `timestamp`, `level`, `scope` and `message`. If the file can't be
+    /// written, a warning is printed once and logging continues on stdout
+    /// only.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File to append to; created if missing
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().log_file("logs/mcp-bridge.log");
+    /// ```
+    pub fn log_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config.log_file = Some(path.into());
+        self
+    }
+
+    /// Rotates the log file once it would grow past a size.
+    ///
+    /// The full file is renamed to `<log_file>.1`, replacing the previous
+    /// backup, and a new file is started. Has no effect without
+    /// [`log_file`](Self::log_file).
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The maximum size of the log file in bytes
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// // Keep at most 2 MiB of logs across the file and its backup
+    /// let builder = Builder::new()
+    ///     .log_file("logs/mcp-bridge.log")
+    ///     .log_file_max_bytes(1024 * 1024);
+    /// ```
+    pub fn log_file_max_bytes(mut self, bytes: u64) -> Self {
+        self.config.log_file_max_bytes = Some(bytes);
+        self
+    }
+
+    /// Broadcasts server health metrics at a fixed interval.
+    ///
+    /// Every `interval`, connected clients receive a push frame for
+    /// monitoring dashboards:
+    ///
+    /// ```json
+    /// { "type": "metrics", "connectedClients": 2, "ipcBufferLen": 118, "eventsSentTotal": 5320, "uptimeMs": 93000 }
+    /// ```
+    ///
+    /// No frames are sent while no client is connected. Disabled by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between metrics frames, raised to
+    ///   [`MIN_METRICS_INTERVAL`](crate::metrics::MIN_METRICS_INTERVAL) if shorter
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().metrics_interval(Duration::from_secs(5));
+    /// ```
+    pub fn metrics_interval(mut self, interval: Duration) -> Self {
+        self.config.metrics_interval = Some(interval);
+        self
+    }
+
+    /// Restricts commands to the windows with these labels.
+    ///
+    /// Any other window is rejected with an `access_denied` error, whether it
+    /// is targeted by label, by `windowMatch` or as the default window, and
+    /// is skipped by commands that run in every window. Use this to keep
+    /// automation away from sensitive surfaces such as payment popups.
+    /// Calling it again replaces the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - Labels of the windows commands may target
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().window_allowlist(&["main", "settings"]);
+    /// ```
+    pub fn window_allowlist(mut self, labels: &[&str]) -> Self {
+        self.config.window_allowlist = Some(labels.iter().map(|l| l.to_string()).collect());
+        self
+    }
+
+    /// Forbids commands from targeting the windows with these labels.
+    ///
+    /// Restricted windows are handled as described in
+    /// [`window_allowlist`](Self::window_allowlist). A label on both lists is
+    /// denied. Calling it again replaces the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `labels` - Labels of the windows commands may not target
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().window_denylist(&["payment"]);
+    /// ```
+    pub fn window_denylist(mut self, labels: &[&str]) -> Self {
+        self.config.window_denylist = labels.iter().map(|l| l.to_string()).collect();
+        self
+    }
+
+    /// Appends a script to inject into every webview after `bridge.js`.
+    ///
+    /// Use this to extend the client-side shim without forking the crate,
+    /// e.g. to report the app's own IPC traffic to the monitor. Scripts run
+    /// in the order they were added, after `bridge.js` has installed these
+    /// globals:
+    ///
+    /// * `window.__MCP_CONSOLE_LOGS__` - The latest 500 console entries
+    ///   (`{ level, message, timestamp }`)
+    /// * `window.__MCP_BRIDGE_READY__` - Set once Tauri IPC is available
+    /// * `window.__MCP_INJECT_SCRIPTS__(scripts)`, `__MCP_REMOVE_SCRIPT__(id)`,
+    ///   `__MCP_CLEAR_SCRIPTS__()` - The registered script pipeline
+    /// * `__mcp_exec_request` / `__mcp_exec_response` and
+    ///   `__mcp_script_result` DOM events - The `execute_js` round trip
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - JavaScript source to inject
+    ///
+    /// # Examples
+    ///
+    /// Report every app `invoke` to the IPC monitor:
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().additional_init_script(
+    ///     r#"
+    ///     (function () {
+    ///       const internals = window.__TAURI_INTERNALS__;
+    ///       const invoke = internals.invoke;
+    ///       internals.invoke = async function (cmd, args, options) {
+    ///         if (cmd.startsWith('plugin:mcp-bridge|')) return invoke(cmd, args, options);
+    ///         const start = performance.now();
+    ///         const report = (outcome) => invoke('plugin:mcp-bridge|report_ipc_event', {
+    ///           command: cmd, args: args ?? {}, durationMs: performance.now() - start, ...outcome
+    ///         }).catch(() => {});
+    ///         try {
+    ///           const result = await invoke(cmd, args, options);
+    ///           report({ result });
+    ///           return result;
+    ///         } catch (error) {
+    ///           report({ error: String(error), stack: error?.stack });
+    ///           throw error;
+    ///         }
+    ///       };
+    ///     })();
+    ///     "#,
+    /// );
+    /// ```
+    pub fn additional_init_script(mut self, script: impl Into<String>) -> Self {
+        self.config.additional_init_scripts.push(script.into());
+        self
+    }
+
+    /// Sets a hook that sees every request before it is dispatched.
+    ///
+    /// The interceptor runs after authentication, on the request as sent.
+    /// It may rewrite the request in place, for example to redact secrets
+    /// from its arguments, and then returns [`InterceptDecision::Allow`] to
+    /// dispatch it or [`InterceptDecision::Deny`] to refuse it. A denied
+    /// request is logged and answered with `"Request denied: <reason>"`; the
+    /// connection stays open. A `batch` is seen as one request, with its
+    /// steps in `args.commands`. Calling it again replaces the interceptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `interceptor` - Called with each request
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::intercept::InterceptDecision;
+    /// use tauri_plugin_mcp_bridge::Builder;
+    ///
+    /// let builder = Builder::new().request_interceptor(|request| {
+    ///     let script = request
+    ///         .args()
+    ///         .and_then(|args| args.get("script"))
+    ///         .and_then(|script| script.as_str())
+    ///         .unwrap_or("");
+    ///     if request.command == "execute_js" && script.contains("localStorage") {
+    ///         return InterceptDecision::Deny("scripts may not read localStorage".into());
+    ///     }
+    ///     InterceptDecision::Allow
+    /// });
+    /// ```
+    pub fn request_interceptor(
+        mut self,
+        interceptor: impl Fn(&mut WsRequest) -> InterceptDecision + Send + Sync + 'static,
+    ) -> Self {
+        self.config.request_interceptor = Some(RequestInterceptor::new(interceptor));
+        self
+    }
+
+    /// Builds the plugin with the configured options.
+    pub fn build<R: tauri::Runtime>(self) -> tauri::plugin::TauriPlugin<R> {
+        crate::init_with_config(self.config)
+    }
+
+    /// Builds the plugin and a handle to its server, see
+    /// [`init_with_handle`](crate::init_with_handle).
+    pub fn build_with_handle<R: tauri::Runtime>(
+        self,
+    ) -> (tauri::plugin::TauriPlugin<R>, crate::McpBridgeHandle) {
+        crate::init_with_handle(self.config)
+    }
+}
+
+/// Masks the set values of secret keys.
+///
+/// Only top-level keys are checked; nested values such as event schemas
+/// describe data rather than hold it.
+fn redact_secrets(fields: &mut serde_json::Map<String, Value>) {
+    for (key, value) in fields.iter_mut() {
+        let key = key.to_ascii_lowercase();
+        if !value.is_null() && SECRET_KEY_MARKERS.iter().any(|m| key.contains(m)) {
+            *value = Value::String(REDACTED.to_string());
+        }
+    }
+}
+
+/// Serializes an optional [`Duration`] as whole milliseconds.
+mod optional_millis {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        value.map(|d| d.as_millis() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_millis))
+    }
+}
+
+mod millis {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(value: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        (value.as_millis() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// Describes a keychain failure, spelling out how to fix a missing entry.
+#[cfg(feature = "keyring")]
+fn keyring_error(service: &str, account: &str, error: keyring::Error) -> ConfigError {
+    let message = match error {
+        keyring::Error::NoEntry => format!(
+            "no token is stored; add one for service \"{service}\" and account \"{account}\""
+        ),
+        e => e.to_string(),
+    };
+    ConfigError::Keyring {
+        service: service.to_string(),
+        account: account.to_string(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sample_config() -> Config {
+        let mut config = Config {
+            bind_address: "127.0.0.1".to_string(),
+            port: Some(9225),
+            max_result_bytes: 1024,
+            js_sandbox: true,
+            ..Config::default()
+        };
+        config.event_schemas.insert(
+            "user-updated".to_string(),
+            json!({ "type": "object", "required": ["id"] }),
+        );
+        config.macros.insert(
+            "open-item".to_string(),
+            vec![
+                Step::Click {
+                    selector: "#item-{{id}}".to_string(),
+                },
+                Step::Wait {
+                    ms: Some(250),
+                    selector: None,
+                    timeout_ms: None,
+                },
+            ],
+        );
+        config
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let text = toml::to_string(&sample_config()).unwrap();
+        assert_eq!(Config::from_toml_str(&text).unwrap(), sample_config());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let text = serde_json::to_string(&sample_config()).unwrap();
+        assert_eq!(Config::from_json_str(&text).unwrap(), sample_config());
+    }
+
+    #[test]
+    fn test_missing_keys_use_defaults() {
+        let config = Config::from_toml_str("port = 9300").unwrap();
+
+        assert_eq!(config.port, Some(9300));
+        assert_eq!(config.bind_address, "0.0.0.0");
+        assert_eq!(config.max_result_bytes, DEFAULT_MAX_RESULT_BYTES);
+    }
+
+    #[test]
+    fn test_idle_timeout_is_read_as_milliseconds() {
+        let config = Config::from_toml_str("idle_timeout_ms = 1500").unwrap();
+        assert_eq!(config.idle_timeout, Some(Duration::from_millis(1500)));
+
+        let text = toml::to_string(&config).unwrap();
+        assert!(text.contains("idle_timeout_ms = 1500"));
+        assert_eq!(Config::default().idle_timeout, None);
+    }
+
+    #[test]
+    fn test_command_timeout_is_read_as_milliseconds() {
+        let config = Config::from_toml_str("command_timeout_ms = 15000").unwrap();
+        assert_eq!(config.command_timeout, Duration::from_secs(15));
+        assert_eq!(Config::default().command_timeout, DEFAULT_COMMAND_TIMEOUT);
+    }
+
+    #[test]
+    fn test_max_concurrent_requests() {
+        let config = Config::from_toml_str("max_concurrent_requests = 4").unwrap();
+        assert_eq!(config.max_concurrent_requests, 4);
+        assert_eq!(Config::default().max_concurrent_requests, 1);
+    }
+
+    #[test]
+    fn test_tcp_keepalive_is_read_as_milliseconds() {
+        let config = Config::from_toml_str(
+            "tcp_keepalive_idle_ms = 60000\ntcp_keepalive_interval_ms = 5000\ntcp_nodelay = false",
+        )
+        .unwrap();
+        assert_eq!(config.tcp_keepalive_idle, Duration::from_secs(60));
+        assert_eq!(config.tcp_keepalive_interval, Duration::from_secs(5));
+        assert!(config.tcp_keepalive && !config.tcp_nodelay);
+
+        let disabled = Builder::new().tcp_keepalive(None).config;
+        assert!(!disabled.tcp_keepalive);
+        assert_eq!(disabled.tcp_keepalive_idle, DEFAULT_TCP_KEEPALIVE_IDLE);
+    }
+
+    #[test]
+    fn test_unknown_keys_are_rejected() {
+        let err = Config::from_json_str(r#"{ "prot": 9300 }"#).unwrap_err();
+        assert!(matches!(err, ConfigError::Parse(_)));
+    }
+
+    #[test]
+    fn test_builder_calls_override_file_values() {
+        let path = std::env::temp_dir().join(format!("mcp-bridge-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{ "bind_address": "127.0.0.1", "port": 9225 }"#).unwrap();
+
+        let builder = Builder::from_file(&path).unwrap().port(9230);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(builder.config.bind_address, "127.0.0.1");
+        assert_eq!(builder.config.port, Some(9230));
+    }
+
+    #[test]
+    fn test_unsupported_extension() {
+        let err = Config::from_file("mcp-bridge.yaml").unwrap_err();
+        assert!(matches!(err, ConfigError::UnsupportedFormat(_)));
+    }
+
+    #[test]
+    fn test_additional_init_scripts_follow_bridge_in_order() {
+        let builder = Builder::new()
+            .additional_init_script("window.first = 1;")
+            .additional_init_script("window.second = 2;");
+
+        let script = builder.config.init_script();
+        let bridge = script.find("__MCP_BRIDGE_READY__").unwrap();
+        let first = script.find("window.first = 1;").unwrap();
+        let second = script.find("window.second = 2;").unwrap();
+
+        assert!(bridge < first && first < second);
+        assert_eq!(Config::default().init_script(), include_str!("bridge.js"));
+    }
+
+    #[test]
+    fn test_window_denylist_takes_precedence() {
+        assert!(Config::default().allows_window("payment"));
+
+        let config = Builder::new()
+            .window_allowlist(&["main", "payment"])
+            .window_denylist(&["payment"])
+            .config;
+        assert!(config.allows_window("main"));
+        assert!(!config.allows_window("payment"));
+        assert!(!config.allows_window("settings"));
+
+        let config = Config::from_toml_str(r#"window_denylist = ["payment"]"#).unwrap();
+        assert!(config.allows_window("settings"));
+        assert!(!config.allows_window("payment"));
+    }
+
+    #[test]
+    fn test_redacted_config_matches_builder_inputs() {
+        let config = Builder::new()
+            .bind_address("127.0.0.1")
+            .port_range(10000, 50)
+            .max_result_bytes(2048)
+            .chunk_threshold_bytes(512)
+            .js_sandbox(true)
+            .window_denylist(&["payment"])
+            .config;
+
+        let exported = config.redacted();
+        assert_eq!(exported["bind_address"], "127.0.0.1");
+        assert_eq!(exported["base_port"], 10000);
+        assert_eq!(exported["port_scan_attempts"], 50);
+        assert_eq!(exported["max_result_bytes"], 2048);
+        assert_eq!(exported["chunk_threshold_bytes"], 512);
+        assert_eq!(exported["js_sandbox"], true);
+        assert_eq!(exported["window_denylist"], json!(["payment"]));
+        assert_eq!(
+            Config::from_json_str(&exported.to_string()).unwrap(),
+            config
+        );
+
+        let mut fields = json!({ "auth_token": "hunter2", "tls_password": null, "port": 9225 });
+        redact_secrets(fields.as_object_mut().unwrap());
+        assert_eq!(
+            fields,
+            json!({ "auth_token": REDACTED, "tls_password": null, "port": 9225 })
+        );
+    }
+
+    #[cfg(feature = "keyring")]
+    #[test]
+    fn test_keyring_token_is_enforced() {
+        use crate::auth::{AuthGate, AuthOutcome};
+
+        keyring::set_default_credential_builder(keyring::mock::default_credential_builder());
+        let entry = keyring::Entry::new("my-app", "mcp-bridge").unwrap();
+
+        let missing = Builder::new()
+            .auth_token_from_entry(&entry, "my-app", "mcp-bridge")
+            .err()
+            .unwrap();
+        assert!(missing
+            .to_string()
+            .contains("no token is stored; add one for service \"my-app\""));
+
+        entry.set_password("from-keychain").unwrap();
+        let config = Builder::new()
+            .auth_token_from_entry(&entry, "my-app", "mcp-bridge")
+            .unwrap()
+            .config;
+        assert_eq!(config.auth_token.as_deref(), Some("from-keychain"));
+
+        let mut gate = AuthGate::new(config.auth_token);
+        assert_eq!(gate.check("execute_js", None), AuthOutcome::Denied);
+        assert_eq!(
+            gate.check("authenticate", Some("from-keychain")),
+            AuthOutcome::Authenticated
+        );
+        assert_eq!(gate.check("execute_js", None), AuthOutcome::Allowed);
     }
 }
@@ -0,0 +1,203 @@
+//! Ongoing console history of each window.
+//!
+//! `bridge.js` wraps `console.log`, `info`, `warn` and `error` for the whole
+//! life of the page and reports every call through `report_console_entry`.
+//! The last [`CONSOLE_HISTORY_CAPACITY`] entries of each window are kept, and
+//! survive reloads, so `get_console_history` can show what a page logged
+//! before the client connected. Every entry is also broadcast to connected
+//! clients:
+//!
+//! ```json
+//! { "type": "console", "windowLabel": "main", "level": "error", "message": "Save failed", "timestamp": 1700000000000 }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Entries kept per window; older ones are dropped first.
+pub const CONSOLE_HISTORY_CAPACITY: usize = 500;
+
+/// Console levels that are recorded.
+pub const CONSOLE_LEVELS: [&str; 4] = ["log", "info", "warn", "error"];
+
+/// One console call.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsoleEntry {
+    /// `"log"`, `"info"`, `"warn"` or `"error"`
+    pub level: String,
+    /// The arguments, stringified and joined with spaces
+    pub message: String,
+    /// Milliseconds since the Unix epoch, from the page's clock
+    pub timestamp: u64,
+}
+
+/// Broadcast frame for a new console entry.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConsoleFrame<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    window_label: &'a str,
+    #[serde(flatten)]
+    entry: &'a ConsoleEntry,
+}
+
+/// Checks that `level` is one of [`CONSOLE_LEVELS`].
+pub fn validate_level(level: &str) -> Result<(), String> {
+    if CONSOLE_LEVELS.contains(&level) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unknown console level '{level}' (expected log, info, warn or error)"
+        ))
+    }
+}
+
+/// Shared console history of every window.
+///
+/// Cheap to clone; clones share the same history. The plugin manages one as
+/// Tauri state.
+#[derive(Clone)]
+pub struct ConsoleHistory {
+    entries: Arc<Mutex<HashMap<String, VecDeque<ConsoleEntry>>>>,
+    capacity: usize,
+    events: broadcast::Sender<String>,
+}
+
+impl ConsoleHistory {
+    /// Creates an empty history that broadcasts new entries on `events`.
+    pub fn new(events: broadcast::Sender<String>) -> Self {
+        Self::with_capacity(events, CONSOLE_HISTORY_CAPACITY)
+    }
+
+    /// Creates an empty history keeping `capacity` entries per window.
+    pub fn with_capacity(events: broadcast::Sender<String>, capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+            events,
+        }
+    }
+
+    /// Records a console call of `window_label` and broadcasts it.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The entry was recorded
+    /// * `Err(String)` - If the level isn't one of [`CONSOLE_LEVELS`]
+    pub fn record(&self, window_label: &str, entry: ConsoleEntry) -> Result<(), String> {
+        validate_level(&entry.level)?;
+
+        let frame = ConsoleFrame {
+            kind: "console",
+            window_label,
+            entry: &entry,
+        };
+        if let Ok(frame) = serde_json::to_string(&frame) {
+            // No receivers just means no client is connected
+            let _ = self.events.send(frame);
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let window = entries.entry(window_label.to_string()).or_default();
+        if window.len() == self.capacity {
+            window.pop_front();
+        }
+        window.push_back(entry);
+        Ok(())
+    }
+
+    /// Returns the most recent entries of a window, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_label` - The window
+    /// * `levels` - Only return entries of these levels. All levels when `None`.
+    /// * `limit` - Return at most this many of the latest matching entries
+    pub fn entries(
+        &self,
+        window_label: &str,
+        levels: Option<&[String]>,
+        limit: Option<usize>,
+    ) -> Vec<ConsoleEntry> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(window) = entries.get(window_label) else {
+            return Vec::new();
+        };
+
+        let mut matching: Vec<ConsoleEntry> = window
+            .iter()
+            .rev()
+            .filter(|entry| levels.map_or(true, |levels| levels.contains(&entry.level)))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+        matching.reverse();
+        matching
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn entry(level: &str, message: &str) -> ConsoleEntry {
+        ConsoleEntry {
+            level: level.to_string(),
+            message: message.to_string(),
+            timestamp: 1_700_000_000_000,
+        }
+    }
+
+    #[test]
+    fn test_console_error_is_retrievable_and_broadcast() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let history = ConsoleHistory::new(tx);
+
+        history.record("main", entry("log", "booting")).unwrap();
+        history
+            .record("main", entry("error", "Save failed: 500"))
+            .unwrap();
+        history
+            .record("other", entry("error", "elsewhere"))
+            .unwrap();
+
+        let errors = history.entries("main", Some(&["error".to_string()]), None);
+        assert_eq!(errors, vec![entry("error", "Save failed: 500")]);
+        assert_eq!(history.entries("main", None, None).len(), 2);
+        assert!(history.entries("missing", None, None).is_empty());
+
+        rx.try_recv().unwrap();
+        let frame: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["type"], "console");
+        assert_eq!(frame["windowLabel"], "main");
+        assert_eq!(frame["level"], "error");
+        assert_eq!(frame["message"], "Save failed: 500");
+
+        assert!(history.record("main", entry("trace", "x")).is_err());
+    }
+
+    #[test]
+    fn test_history_is_capped_and_limit_keeps_latest() {
+        let (tx, _rx) = broadcast::channel(8);
+        let history = ConsoleHistory::with_capacity(tx, 3);
+        for i in 0..5 {
+            history
+                .record("main", entry("info", &i.to_string()))
+                .unwrap();
+        }
+
+        let messages = |entries: Vec<ConsoleEntry>| -> Vec<String> {
+            entries.into_iter().map(|e| e.message).collect()
+        };
+        assert_eq!(
+            messages(history.entries("main", None, None)),
+            ["2", "3", "4"]
+        );
+        assert_eq!(messages(history.entries("main", None, Some(2))), ["3", "4"]);
+    }
+}
@@ -2,8 +2,16 @@
 //!
 //! This module provides a lightweight mechanism for multiple Tauri apps
 //! to coexist on the same machine by finding available ports dynamically.
+//!
+//! Probing a port and binding it for good are separate steps, so instances
+//! starting at the same moment can both probe the same free port. With a
+//! shared lock directory, each instance first claims the port with a
+//! [`PortLock`] file and skips ports another running instance has claimed.
 
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
 use std::net::TcpListener;
+use std::path::{Path, PathBuf};
 
 use crate::logging::mcp_log_error;
 
@@ -12,24 +20,266 @@ use crate::logging::mcp_log_error;
 /// # Arguments
 ///
 /// * `bind_address` - The address to bind to (e.g., "0.0.0.0" or "127.0.0.1")
+/// * `base_port` - First port to try
+/// * `attempts` - Number of consecutive ports to try. The scan stops early at
+///   port 65535.
+///
+/// # Returns
+///
+/// * `Ok(u16)` - The first available port in the range
+/// * `Err(String)` - If every port in the range is in use
+pub fn find_available_port(
+    bind_address: &str,
+    base_port: u16,
+    attempts: u16,
+) -> Result<u16, String> {
+    let listener = bind_available_port(bind_address, base_port, attempts)?;
+    listener
+        .local_addr()
+        .map(|addr| addr.port())
+        .map_err(|e| format!("MCP Bridge: Failed to read bound address: {e}"))
+}
+
+/// Binds the first available port in a range.
+///
+/// Unlike [`find_available_port`], the port stays bound, so nothing else can
+/// take it before the server starts listening.
+///
+/// # Arguments
+///
+/// * `bind_address` - The address to bind to (e.g., "0.0.0.0" or "127.0.0.1")
+/// * `base_port` - First port to try
+/// * `attempts` - Number of consecutive ports to try. The scan stops early at
+///   port 65535.
 ///
 /// # Returns
 ///
-/// An available port number in the range 9223-9322, or 9223 if none are available.
-pub fn find_available_port(bind_address: &str) -> u16 {
-    let base_port = 9223;
-    let max_attempts = 100;
+/// * `Ok(TcpListener)` - A listener on the first available port in the range
+/// * `Err(String)` - If every port in the range is in use
+pub fn bind_available_port(
+    bind_address: &str,
+    base_port: u16,
+    attempts: u16,
+) -> Result<TcpListener, String> {
+    scan(bind_address, base_port, attempts, None).map(|(listener, _)| listener)
+}
 
-    for offset in 0..max_attempts {
-        let port = base_port + offset;
-        if is_port_available(bind_address, port) {
-            return port;
+/// Binds the first available port in a range that no other instance using
+/// `lock_dir` has claimed, and claims it.
+///
+/// # Arguments
+///
+/// * `bind_address` - The address to bind to (e.g., "0.0.0.0" or "127.0.0.1")
+/// * `base_port` - First port to try
+/// * `attempts` - Number of consecutive ports to try. The scan stops early at
+///   port 65535.
+/// * `lock_dir` - Directory shared by the instances, created if missing
+///
+/// # Returns
+///
+/// * `Ok((TcpListener, PortLock))` - A listener on the port and its claim,
+///   which should be kept until the server stops
+/// * `Err(String)` - If every port in the range is in use or claimed, or the
+///   lock directory can't be written
+pub fn bind_available_port_locked(
+    bind_address: &str,
+    base_port: u16,
+    attempts: u16,
+    lock_dir: &Path,
+) -> Result<(TcpListener, PortLock), String> {
+    let (listener, lock) = scan(bind_address, base_port, attempts, Some(lock_dir))?;
+    let lock = lock.ok_or_else(|| "MCP Bridge: Port scan returned no lock".to_string())?;
+    Ok((listener, lock))
+}
+
+/// Finds an available port that no other instance using `lock_dir` has
+/// claimed, and claims it.
+///
+/// Like [`find_available_port`], the port is released for the server to bind
+/// later, but it stays claimed until the returned lock is dropped or
+/// released.
+///
+/// # Returns
+///
+/// * `Ok((u16, PortLock))` - The port and its claim
+/// * `Err(String)` - If every port in the range is in use or claimed, or the
+///   lock directory can't be written
+pub fn claim_available_port(
+    bind_address: &str,
+    base_port: u16,
+    attempts: u16,
+    lock_dir: &Path,
+) -> Result<(u16, PortLock), String> {
+    let (_, lock) = bind_available_port_locked(bind_address, base_port, attempts, lock_dir)?;
+    Ok((lock.port(), lock))
+}
+
+/// Binds the first port of a range that is free and, with a lock directory,
+/// not claimed by another instance.
+fn scan(
+    bind_address: &str,
+    base_port: u16,
+    attempts: u16,
+    lock_dir: Option<&Path>,
+) -> Result<(TcpListener, Option<PortLock>), String> {
+    let mut last_port = None;
+
+    for offset in 0..attempts {
+        let Some(port) = base_port.checked_add(offset) else {
+            break;
+        };
+        last_port = Some(port);
+
+        let lock = match lock_dir {
+            Some(dir) => match PortLock::acquire(dir, port)? {
+                Some(lock) => Some(lock),
+                None => continue,
+            },
+            None => None,
+        };
+        // A lock on a port we can't bind is released as it drops
+        if let Ok(listener) = TcpListener::bind(format!("{bind_address}:{port}")) {
+            return Ok((listener, lock));
         }
     }
 
-    // If no ports in the range are available, use default
-    // (The app will need to handle port conflicts)
-    base_port
+    let range = match last_port {
+        Some(last) if last != base_port => format!("{base_port}-{last}"),
+        Some(_) => base_port.to_string(),
+        None => "(empty range)".to_string(),
+    };
+    Err(format!(
+        "MCP Bridge: No available port in {range} on {bind_address}. \
+         Increase the range with Builder::port_range or Builder::port_scan_attempts, \
+         or set an explicit port."
+    ))
+}
+
+/// Advisory claim on a port, held as a `mcp-bridge-<port>.lock` file holding
+/// the owner's process ID.
+///
+/// The file is removed when the lock is released or dropped. A lock left
+/// behind by a process that has exited, for example after a crash, is
+/// reclaimed by the next instance that wants the port.
+#[derive(Debug)]
+pub struct PortLock {
+    port: u16,
+    path: PathBuf,
+}
+
+impl PortLock {
+    /// Claims `port` in `dir`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Some(PortLock))` - The claim
+    /// * `Ok(None)` - If a running process holds the port
+    /// * `Err(String)` - If the lock file can't be created
+    pub fn acquire(dir: &Path, port: u16) -> Result<Option<Self>, String> {
+        fs::create_dir_all(dir).map_err(|e| {
+            format!(
+                "MCP Bridge: Failed to create port lock directory {}: {e}",
+                dir.display()
+            )
+        })?;
+        let path = dir.join(format!("mcp-bridge-{port}.lock"));
+
+        // A second attempt follows reclaiming a stale lock
+        for _ in 0..2 {
+            match create_lock_file(&path) {
+                Ok(()) => return Ok(Some(Self { port, path })),
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(format!(
+                        "MCP Bridge: Failed to create port lock {}: {e}",
+                        path.display()
+                    ))
+                }
+            }
+
+            // Unreadable or half-written locks count as held
+            let owner = fs::read_to_string(&path)
+                .ok()
+                .and_then(|pid| pid.trim().parse::<u32>().ok());
+            match owner {
+                Some(pid) if !process_alive(pid) => {
+                    mcp_log_error(
+                        "DISCOVERY",
+                        &format!("Reclaiming port {port} from exited process {pid}"),
+                    );
+                    let _ = fs::remove_file(&path);
+                }
+                _ => return Ok(None),
+            }
+        }
+        Ok(None)
+    }
+
+    /// The claimed port.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Gives up the claim. Safe to call more than once.
+    pub fn release(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Drop for PortLock {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Creates the lock file, failing with `AlreadyExists` if it exists.
+fn create_lock_file(path: &Path) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).create_new(true).open(path)?;
+    if let Err(e) = writeln!(file, "{}", std::process::id()) {
+        let _ = fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(())
+}
+
+/// Returns whether a process with `pid` is running.
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks that the process exists; EPERM means it does but
+    // belongs to another user
+    let exists = unsafe { libc::kill(pid, 0) } == 0;
+    exists || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Returns whether a process with `pid` is running.
+#[cfg(windows)]
+fn process_alive(pid: u32) -> bool {
+    use windows::Win32::Foundation::{CloseHandle, ERROR_INVALID_PARAMETER, STILL_ACTIVE};
+    use windows::Win32::System::Threading::{
+        GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+
+    unsafe {
+        let handle = match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(handle) => handle,
+            // Access denied and similar errors still mean the process exists
+            Err(e) => return e.code() != ERROR_INVALID_PARAMETER.to_hresult(),
+        };
+        let mut code = 0u32;
+        let alive = GetExitCodeProcess(handle, &mut code).is_err() || code == STILL_ACTIVE.0 as u32;
+        let _ = CloseHandle(handle);
+        alive
+    }
+}
+
+/// Returns whether a process with `pid` is running. Without a way to check,
+/// every lock is treated as held.
+#[cfg(not(any(unix, windows)))]
+fn process_alive(_pid: u32) -> bool {
+    true
 }
 
 /// Uses an explicit port, panicking if unavailable (strict mode).
@@ -75,17 +325,103 @@ pub fn is_port_available(bind_address: &str, port: u16) -> bool {
 mod tests {
     use super::*;
 
+    use crate::config::{DEFAULT_BASE_PORT, DEFAULT_PORT_SCAN_ATTEMPTS};
+
+    /// Returns a port the OS just reported as free.
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
     #[test]
     fn test_port_availability_all_interfaces() {
-        let port = find_available_port("0.0.0.0");
+        let port =
+            find_available_port("0.0.0.0", DEFAULT_BASE_PORT, DEFAULT_PORT_SCAN_ATTEMPTS).unwrap();
         assert!(port >= 9223);
         assert!(port < 9323);
     }
 
     #[test]
     fn test_port_availability_localhost() {
-        let port = find_available_port("127.0.0.1");
+        let port = find_available_port("127.0.0.1", DEFAULT_BASE_PORT, DEFAULT_PORT_SCAN_ATTEMPTS)
+            .unwrap();
         assert!(port >= 9223);
         assert!(port < 9323);
     }
+
+    #[test]
+    fn test_custom_base_port_is_tried_first() {
+        let base = free_port();
+        assert_eq!(find_available_port("127.0.0.1", base, 1), Ok(base));
+    }
+
+    #[test]
+    fn test_exhausted_range_is_an_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let taken = listener.local_addr().unwrap().port();
+
+        let err = find_available_port("127.0.0.1", taken, 1).unwrap_err();
+        assert!(err.contains(&format!("No available port in {taken} on 127.0.0.1")));
+
+        assert!(find_available_port("127.0.0.1", taken, 0).is_err());
+    }
+
+    #[test]
+    fn test_scan_skips_ports_in_use() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let taken = listener.local_addr().unwrap().port();
+
+        if let Ok(port) = find_available_port("127.0.0.1", taken, 2) {
+            assert_eq!(port, taken + 1);
+        }
+    }
+
+    fn lock_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("mcp-port-locks-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_instances_sharing_a_lock_dir_claim_distinct_ports() {
+        let dir = lock_dir();
+        let base = free_port();
+
+        // Both instances see the same free ports; the lock keeps them apart
+        let (first, first_lock) = claim_available_port("127.0.0.1", base, 10, &dir).unwrap();
+        let (second, second_lock) = claim_available_port("127.0.0.1", base, 10, &dir).unwrap();
+        assert_ne!(first, second);
+        assert!(dir.join(format!("mcp-bridge-{first}.lock")).exists());
+
+        // A released port can be claimed again
+        drop(first_lock);
+        let (third, _third_lock) = claim_available_port("127.0.0.1", first, 1, &dir).unwrap();
+        assert_eq!(third, first);
+
+        second_lock.release();
+        assert!(!dir.join(format!("mcp-bridge-{second}.lock")).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_lock_of_exited_process_is_reclaimed() {
+        let dir = lock_dir();
+        let port = free_port();
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("mcp-bridge-{port}.lock"));
+
+        // No process has this ID
+        fs::write(&path, "999999999\n").unwrap();
+        let lock = PortLock::acquire(&dir, port).unwrap().unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap().trim(),
+            std::process::id().to_string()
+        );
+
+        // A running process keeps its claim
+        assert!(PortLock::acquire(&dir, port).unwrap().is_none());
+        drop(lock);
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
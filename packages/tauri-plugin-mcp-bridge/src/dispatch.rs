@@ -0,0 +1,2020 @@
+//! The dispatch stage: runs a request's command and builds its response.
+//!
+//! Commands are looked up in a table, see [`handler`]. Each handler returns
+//! a [`Reply`], which [`Reply::into_body`] turns into the response body, so
+//! every command answers errors the same way, through
+//! [`error_body`](crate::pipeline::error_body).
+//!
+//! Connection-level commands (`hello`, `subscribe`, `identify`, screencasts,
+//! watches, ...) get the [`ConnState`]; everything else only gets the app and
+//! the request's arguments, so it can run alongside the connection, see
+//! [`serve`].
+
+use crate::abort::{AbortSignal, AbortToken, ABORTED};
+use crate::batch::BatchResult;
+use crate::capture_store::{delivery_by_url, CaptureStore};
+use crate::chunked;
+use crate::command_args::{
+    default_window_label, parse_args, set_window_label, window_label_arg, AbortAllArgs,
+    AddMockArgs, AssertElementArgs, BridgeLogsArgs, CaptureWindowArgs, ClearCacheArgs,
+    ClearScriptsArgs, CompareScreenshotArgs, ComputedStyleArgs, ConsoleHistoryArgs,
+    DefaultWindowArgs, DispatchDomEventArgs, DragAndDropArgs, ElementFromPointArgs, EmitEventArgs,
+    EmulateMediaArgs, ExecuteJsAllArgs, ExecuteJsArgs, ExecuteJsFileArgs, ExecuteJsIfArgs,
+    FailedRequestsArgs, FillFormArgs, FreezeTimeArgs, HelloArgs, IdentifyArgs, InvokeTauriArgs,
+    ListResourcesArgs, ObserveMutationsArgs, QueryElementHandleArgs, ReadFormArgs,
+    ReadResourceArgs, ReadableTextArgs, RegisterScriptArgs, RemoveMockArgs, RemoveScriptArgs,
+    ResetWindowArgs, RunMacroArgs, ScreenshotArgs, SelectorArgs, SetInputValueArgs, SetLayoutArgs,
+    SetThemeArgs, SetTimeoutArgs, SetTraceArgs, SetUserAgentArgs, SetWindowFlagsArgs,
+    StartScreencastArgs, StepTimeArgs, StopScreencastArgs, StopWatchArgs, WaitForEventArgs,
+    WaitForNetworkIdleArgs, WaitForReadyArgs, WatchJsArgs, WindowArgs,
+};
+use crate::commands::{
+    self, check_window_access, resolve_window_matching, resolve_window_with_context, WindowContext,
+};
+use crate::config::{Config, DEFAULT_CHUNK_THRESHOLD_BYTES};
+use crate::element_handles::query_handle_script;
+use crate::mocks::{MockRegistry, MockRule};
+use crate::pipeline::{
+    connection_timeout, error_body, qualified_command, ConnState, RequestSlots, WsRequest,
+    WsResponse,
+};
+use crate::response_format::ResponseFormat;
+use crate::screencast::{clamp_fps, decode_data_url};
+use crate::script_registry::{ScriptEntry, ScriptType, SharedScriptRegistry};
+use crate::subscriptions::topics_arg;
+use crate::watch::{clamp_interval, watch_script};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+use tokio::sync::OwnedSemaphorePermit;
+
+/// A handler's pending [`Reply`].
+pub(crate) type ReplyFuture<'a> = Pin<Box<dyn Future<Output = Reply> + Send + 'a>>;
+
+/// Runs a connection-level command.
+pub(crate) type ConnHandler<R> = for<'a> fn(ConnCall<'a, R>) -> ReplyFuture<'a>;
+
+/// Runs a command that only needs the app.
+pub(crate) type AppHandler<R> = fn(AppCall<R>) -> ReplyFuture<'static>;
+
+/// How a command is run, see [`handler`].
+pub(crate) enum Handler<R: Runtime> {
+    /// Reads or changes the state of the connection the request arrived on
+    Connection(ConnHandler<R>),
+    /// Only talks to the app
+    App(AppHandler<R>),
+}
+
+/// A request as a connection-level handler gets it.
+pub(crate) struct ConnCall<'a, R: Runtime> {
+    pub app: &'a AppHandle<R>,
+    pub conn: &'a mut ConnState,
+    /// The request's `args`
+    pub args: Option<&'a Value>,
+}
+
+/// A request as an app handler gets it. It owns what it needs, so the
+/// handler doesn't hold on to the connection.
+pub(crate) struct AppCall<R: Runtime> {
+    pub app: AppHandle<R>,
+    /// The request's `args`
+    pub args: Option<Value>,
+    /// The address the client reached us on, for download URLs
+    pub local_addr: SocketAddr,
+}
+
+/// What a command answers, before the request id is added.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Reply {
+    /// `data` on success, else `error`
+    result: Result<Value, String>,
+    /// Fields sent next to `data` or `error`, such as `windowContext`
+    fields: Map<String, Value>,
+    /// Wire format a `hello` switched to
+    format: Option<ResponseFormat>,
+}
+
+impl Reply {
+    /// A successful reply carrying `data`.
+    pub fn ok(data: impl Serialize) -> Self {
+        Self::from(Ok(data))
+    }
+
+    /// A failed reply carrying `error`.
+    pub fn err(error: impl Into<String>) -> Self {
+        Self::from(Err::<Value, _>(error.into()))
+    }
+
+    /// Adds a field next to `data` or `error`.
+    pub fn with(mut self, key: &str, value: impl Serialize) -> Self {
+        self.fields.insert(key.to_string(), data(value));
+        self
+    }
+
+    /// The response body for request `id`: `{ id, success, data | error }`
+    /// plus the reply's other fields.
+    pub fn into_body(self, id: &str) -> Value {
+        let mut body = match self.result {
+            Ok(data) => serde_json::json!({
+                "id": id,
+                "success": true,
+                "data": data
+            }),
+            Err(e) => error_body(id, e),
+        };
+        if let Value::Object(body) = &mut body {
+            body.extend(self.fields);
+        }
+        body
+    }
+}
+
+impl<T: Serialize> From<Result<T, String>> for Reply {
+    fn from(result: Result<T, String>) -> Self {
+        Self {
+            result: result.map(data),
+            fields: Map::new(),
+            format: None,
+        }
+    }
+}
+
+/// A command's result as JSON.
+fn data(value: impl Serialize) -> Value {
+    serde_json::json!(value)
+}
+
+/// Looks up how to run a command.
+///
+/// # Returns
+///
+/// The command's handler, or `None` for unknown commands
+pub(crate) fn handler<R: Runtime>(command: &str) -> Option<Handler<R>> {
+    use Handler::{App, Connection};
+
+    let handler: Handler<R> = match command {
+        "authenticate" => Connection(|call| Box::pin(authenticate(call))),
+        "hello" => Connection(|call| Box::pin(hello(call))),
+        "subscribe" => Connection(|call| Box::pin(subscribe(call, true))),
+        "unsubscribe" => Connection(|call| Box::pin(subscribe(call, false))),
+        "identify" => Connection(|call| Box::pin(identify(call))),
+        "set_default_window" => Connection(|call| Box::pin(set_default_window(call))),
+        "clear_default_window" => Connection(|call| Box::pin(clear_default_window(call))),
+        "set_timeout" => Connection(|call| Box::pin(set_timeout(call))),
+        "set_trace" => Connection(|call| Box::pin(set_trace(call))),
+        "get_clients" => Connection(|call| Box::pin(get_clients(call))),
+        "start_screencast" => Connection(|call| Box::pin(start_screencast(call))),
+        "stop_screencast" => Connection(|call| Box::pin(stop_screencast(call))),
+        "watch_js" => Connection(|call| Box::pin(watch_js(call))),
+        "stop_watch" => Connection(|call| Box::pin(stop_watch(call))),
+        "query_element_handle" => Connection(|call| Box::pin(query_element_handle(call))),
+        "invoke_tauri" => App(|call| Box::pin(invoke_tauri(call))),
+        "get_bridge_logs" => App(|call| Box::pin(get_bridge_logs(call))),
+        "wait_for_event" => App(|call| Box::pin(wait_for_event(call))),
+        "abort_all" => App(|call| Box::pin(abort_all(call))),
+        "list_windows" => App(|call| Box::pin(list_windows(call))),
+        "get_active_window" => App(|call| Box::pin(get_active_window(call))),
+        "get_layout" => App(|call| Box::pin(get_layout(call))),
+        "set_layout" => App(|call| Box::pin(set_layout(call))),
+        "execute_js" => App(|call| Box::pin(execute_js(call))),
+        "execute_js_all" => App(|call| Box::pin(execute_js_all(call))),
+        "execute_js_file" => App(|call| Box::pin(execute_js_file(call))),
+        "execute_js_if" => App(|call| Box::pin(execute_js_if(call))),
+        "capture_native_screenshot" => App(|call| Box::pin(capture_native_screenshot(call))),
+        "register_script" => App(|call| Box::pin(register_script(call))),
+        "remove_script" => App(|call| Box::pin(remove_script(call))),
+        "clear_scripts" => App(|call| Box::pin(clear_scripts(call))),
+        "get_scripts" => App(|call| Box::pin(get_scripts(call))),
+        "add_mock" => App(|call| Box::pin(add_mock(call))),
+        "remove_mock" => App(|call| Box::pin(remove_mock(call))),
+        "clear_mocks" => App(|call| Box::pin(clear_mocks(call))),
+        "get_mocks" => App(|call| Box::pin(get_mocks(call))),
+        _ => return None,
+    };
+    Some(handler)
+}
+
+/// Waits for the request slots `request` runs in: one for app commands,
+/// all of them for anything else, see [`serve`].
+///
+/// # Arguments
+///
+/// * `request` - The parsed, authorized request
+/// * `slots` - The connection's [`RequestSlots`]
+///
+/// # Returns
+///
+/// The permit to pass to [`serve`]
+pub(crate) async fn reserve<R: Runtime>(
+    request: &WsRequest,
+    slots: &RequestSlots,
+) -> OwnedSemaphorePermit {
+    match handler::<R>(&request.command) {
+        Some(Handler::App(_)) => slots.acquire().await,
+        _ => slots.acquire_all().await,
+    }
+}
+
+/// Runs a request in the slots [`reserve`] got it and queues its response.
+///
+/// App commands run alongside the connection loop, holding their slot until
+/// they are answered, so the loop can read further requests meanwhile.
+/// Everything else holds every slot, so connection-level commands find the
+/// connection as the requests before them left it.
+///
+/// # Arguments
+///
+/// * `request` - The parsed, authorized request
+/// * `slot` - The permit from [`reserve`]
+/// * `conn` - State of the connection the request arrived on
+/// * `app` - The Tauri application handle
+/// * `complete` - Applied to the response before it is queued, e.g. to
+///   wrap it for MCP clients
+pub(crate) async fn serve<R: Runtime>(
+    request: WsRequest,
+    slot: OwnedSemaphorePermit,
+    conn: &mut ConnState,
+    app: &AppHandle<R>,
+    complete: impl FnOnce(WsResponse) -> WsResponse + Send + 'static,
+) {
+    match begin_with_timeout(request, conn, app).await {
+        Dispatched::Answered(response) => conn.respond(complete(response)),
+        Dispatched::Running(running) => {
+            let responder = conn.responder();
+            tauri::async_runtime::spawn(async move {
+                responder.respond(&complete(running.await));
+                // Freed only once queued, so with a single slot responses
+                // keep the order of their requests
+                drop(slot);
+            });
+        }
+    }
+}
+
+/// A request [`begin`] started.
+pub(crate) enum Dispatched {
+    /// Connection-level commands, and requests that failed before their
+    /// command ran
+    Answered(WsResponse),
+    /// An app command, answered when the future completes
+    Running(Pin<Box<dyn Future<Output = WsResponse> + Send>>),
+}
+
+/// Runs [`begin`], answering with a timeout error if the command takes
+/// longer than [`ConnState::request_timeout`].
+pub(crate) async fn begin_with_timeout<R: Runtime>(
+    request: WsRequest,
+    conn: &mut ConnState,
+    app: &AppHandle<R>,
+) -> Dispatched {
+    let timeout = conn.request_timeout(&request);
+    let deadline = tokio::time::Instant::now() + timeout;
+    let (id, command) = (request.id.clone(), request.command.clone());
+    let timed_out = move || {
+        WsResponse::error(
+            &id,
+            format!(
+                "Command '{command}' timed out after {}ms",
+                timeout.as_millis()
+            ),
+        )
+    };
+
+    match tokio::time::timeout_at(deadline, begin(request, conn, app)).await {
+        Ok(Dispatched::Running(running)) => Dispatched::Running(Box::pin(async move {
+            tokio::time::timeout_at(deadline, running)
+                .await
+                .unwrap_or_else(|_| timed_out())
+        })),
+        Ok(answered) => answered,
+        Err(_) => Dispatched::Answered(timed_out()),
+    }
+}
+
+/// Starts a request's command.
+///
+/// Connection-level commands run to completion here, as they need `conn`.
+/// App commands only get their future built, to run wherever the caller
+/// likes.
+///
+/// # Arguments
+///
+/// * `request` - The parsed, authorized request
+/// * `conn` - State of the connection the request arrived on
+/// * `app` - The Tauri application handle
+///
+/// # Returns
+///
+/// The response, with the wire format a `hello` negotiated, or the future
+/// producing it
+pub(crate) async fn begin<R: Runtime>(
+    request: WsRequest,
+    conn: &mut ConnState,
+    app: &AppHandle<R>,
+) -> Dispatched {
+    let WsRequest {
+        id,
+        command: cmd_name,
+        body: mut command,
+        window_match,
+    } = request;
+
+    // Resolve `windowMatch` to the `windowLabel` every command understands
+    let matched_window = window_match.and_then(|criteria| {
+        criteria
+            .map(|criteria| {
+                let resolved = resolve_window_matching(app, &criteria)?;
+                set_window_label(&mut command, &resolved.context.window_label);
+                Ok(resolved.context)
+            })
+            .transpose()
+    });
+
+    // Commands without a window target the connection's default window
+    if let Some(label) = conn.default_window.as_deref() {
+        if cmd_name != "set_default_window" {
+            default_window_label(&mut command, label);
+        }
+    }
+
+    let handler = match (handler::<R>(&cmd_name), &matched_window) {
+        (_, Err(e)) => Err(e.clone()),
+        (None, Ok(_)) => Err(format!(
+            "Unknown command: {}",
+            qualified_command(conn.namespace.as_deref(), &cmd_name)
+        )),
+        (Some(handler), Ok(_)) => check_invoked_handle(&cmd_name, &command, conn).map(|()| handler),
+    };
+
+    let window_label = window_label_arg(&command).map(str::to_string);
+    let handle = app.clone();
+    let respond = move |reply: Reply| {
+        let mut response = finish(reply, &id, &cmd_name, &handle);
+        if let Ok(Some(context)) = matched_window {
+            add_window_match_warning(&mut response.body, context);
+        }
+        response.window_label = window_label;
+        response
+    };
+
+    let args = command.get("args");
+    match handler {
+        Err(e) => Dispatched::Answered(respond(Reply::err(e))),
+        Ok(Handler::Connection(handler)) => {
+            let reply = handler(ConnCall { app, conn, args }).await;
+            Dispatched::Answered(respond(reply))
+        }
+        Ok(Handler::App(handler)) => {
+            let running = handler(AppCall {
+                app: app.clone(),
+                args: args.cloned(),
+                local_addr: conn.local_addr,
+            });
+            Dispatched::Running(Box::pin(async move { respond(running.await) }))
+        }
+    }
+}
+
+/// Element handles only resolve on the connection that created them, so the
+/// one an `invoke_tauri` passes must be the connection's own.
+fn check_invoked_handle(cmd_name: &str, command: &Value, conn: &ConnState) -> Result<(), String> {
+    let handle_id = command
+        .pointer("/args/args/handleId")
+        .and_then(Value::as_str);
+    match handle_id {
+        Some(handle_id) if cmd_name == "invoke_tauri" => conn.element_handles.check(handle_id),
+        _ => Ok(()),
+    }
+}
+
+/// Turns a command's reply into the response to request `id`.
+fn finish<R: Runtime>(reply: Reply, id: &str, cmd_name: &str, app: &AppHandle<R>) -> WsResponse {
+    let format = reply.format;
+    let mut body = reply.into_body(id);
+
+    // Oversized script results go out in chunks
+    let chunks = if cmd_name == "execute_js" {
+        let threshold = app
+            .try_state::<Config>()
+            .map_or(DEFAULT_CHUNK_THRESHOLD_BYTES, |config| {
+                config.chunk_threshold_bytes
+            });
+        chunked::split(&mut body, threshold)
+    } else {
+        Vec::new()
+    };
+
+    WsResponse {
+        body,
+        format,
+        chunks,
+        window_label: None,
+    }
+}
+
+/// `authorize` has already checked the token.
+async fn authenticate<R: Runtime>(_call: ConnCall<'_, R>) -> Reply {
+    Reply::ok(serde_json::json!({ "authenticated": true }))
+}
+
+/// Optional client hello listing desired features and wire format.
+async fn hello<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    let hello = parse_args::<HelloArgs>(call.args).and_then(|hello| {
+        let requested_format = match hello.format.as_deref() {
+            Some(name) => Some(ResponseFormat::parse(name)?),
+            None => None,
+        };
+        Ok((hello.features, requested_format))
+    });
+
+    match hello {
+        Ok((requested, requested_format)) => {
+            let mut data = call.conn.capabilities.negotiate(&requested);
+            data["format"] = requested_format.unwrap_or(call.conn.format).as_str().into();
+            // Applied after the response is sent
+            let mut reply = Reply::ok(data);
+            reply.format = requested_format;
+            reply
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn subscribe<R: Runtime>(call: ConnCall<'_, R>, subscribe: bool) -> Reply {
+    match topics_arg(call.args) {
+        Ok(topics) => {
+            let topics_tx = &call.conn.topics;
+            topics_tx.send_modify(|filter| {
+                if subscribe {
+                    filter.subscribe(topics);
+                } else {
+                    filter.unsubscribe(topics.iter().map(String::as_str));
+                }
+            });
+            Reply::ok(serde_json::json!({ "topics": topics_tx.borrow().topics() }))
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// Attaches a human-readable name to this connection.
+async fn identify<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    parse_args::<IdentifyArgs>(call.args)
+        .and_then(|args| call.conn.client.identify(&args.name))
+        .map_or_else(Reply::err, Reply::ok)
+}
+
+/// Targets a window by default for the rest of this connection.
+async fn set_default_window<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    match parse_args::<DefaultWindowArgs>(call.args).and_then(|args| {
+        commands::resolve_window(call.app, Some(args.window_label.clone()))?;
+        Ok(args.window_label)
+    }) {
+        Ok(label) => {
+            call.conn.default_window = Some(label.clone());
+            Reply::ok(serde_json::json!({ "windowLabel": label }))
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn clear_default_window<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    call.conn.default_window = None;
+    Reply::ok(serde_json::json!({ "windowLabel": null }))
+}
+
+/// Default run time of this connection's requests without `timeoutMs`.
+async fn set_timeout<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    let command_timeout = call.conn.command_timeout;
+    match parse_args::<SetTimeoutArgs>(call.args)
+        .and_then(|args| connection_timeout(args.default_ms, command_timeout))
+    {
+        Ok(timeout) => {
+            call.conn.default_timeout = timeout;
+            Reply::ok(serde_json::json!({
+                "defaultMs": timeout.unwrap_or(command_timeout).as_millis() as u64,
+                "maxMs": command_timeout.as_millis() as u64
+            }))
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// Takes effect from the next request on this connection.
+async fn set_trace<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    match parse_args::<SetTraceArgs>(call.args) {
+        Ok(args) => {
+            call.conn.trace = args.enabled;
+            Reply::ok(serde_json::json!({ "enabled": args.enabled }))
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn get_clients<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    Reply::ok(serde_json::json!({
+        "clients": call.conn.clients.list(),
+        "self": call.conn.client.id()
+    }))
+}
+
+/// Starts streaming frames to this connection.
+async fn start_screencast<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    let ConnCall { app, conn, args } = call;
+    let (resolved, fps, args) = match parse_args::<StartScreencastArgs>(args).and_then(|args| {
+        let fps = clamp_fps(args.fps)?;
+        resolve_window_with_context(app, args.window_label.clone())
+            .map(|resolved| (resolved, fps, args))
+    }) {
+        Ok(started) => started,
+        Err(e) => return Reply::err(e),
+    };
+
+    let format = args.format.unwrap_or_else(|| "jpeg".to_string());
+    let quality = args.quality;
+    let window = resolved.window.clone();
+    let shown = resolved.window.clone();
+    let capture_format = format.clone();
+    // Ends the stream when `abort_all` stops screencasts
+    let abort = app
+        .try_state::<AbortSignal>()
+        .map(|signal| signal.stream_token());
+    // Frames of the stream share one set of encoding buffers
+    let encoder = Arc::new(tokio::sync::Mutex::new(
+        crate::screenshot::FrameEncoder::new(),
+    ));
+    let stream_id = conn.screencasts.start(
+        fps,
+        window.label().to_string(),
+        conn.responses.clone(),
+        // Platforms that can't tell count as visible
+        move || shown.is_visible().unwrap_or(true) && !shown.is_minimized().unwrap_or(false),
+        move || {
+            let window = window.clone();
+            let format = capture_format.clone();
+            let encoder = encoder.clone();
+            let aborted = abort.as_ref().is_some_and(AbortToken::is_aborted);
+            async move {
+                if aborted {
+                    return Err(ABORTED.to_string());
+                }
+                let mut encoder = encoder.lock().await;
+                let image = crate::commands::screenshot::capture_frame(
+                    &window,
+                    &format,
+                    quality,
+                    &mut encoder,
+                )
+                .await?;
+                decode_data_url(&image.data_url)
+            }
+        },
+    );
+    Reply::ok(serde_json::json!({
+        "streamId": stream_id,
+        "fps": fps,
+        "format": format
+    }))
+    .with("windowContext", resolved.context)
+}
+
+/// Stops one stream, or all of this connection's streams.
+async fn stop_screencast<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    match parse_args::<StopScreencastArgs>(call.args)
+        .and_then(|args| call.conn.screencasts.stop(args.stream_id.as_deref()))
+    {
+        Ok(stopped) => Reply::ok(serde_json::json!({ "stopped": stopped })),
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// Pushes a JavaScript value to this connection as it changes.
+async fn watch_js<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    let ConnCall { app, conn, args } = call;
+    match parse_args::<WatchJsArgs>(args).and_then(|args| {
+        let interval_ms = clamp_interval(args.interval_ms)?;
+        resolve_window_with_context(app, args.window_label.clone())
+            .map(|resolved| (resolved, interval_ms, args))
+    }) {
+        Ok((resolved, interval_ms, args)) => {
+            let window = resolved.window.clone();
+            let script = watch_script(&args.expression);
+            let watch_id = conn.watches.start(
+                interval_ms,
+                args.every_tick.unwrap_or(false),
+                conn.responses.clone(),
+                move || {
+                    let window = window.clone();
+                    let script = script.clone();
+                    async move {
+                        let executor = window.state::<commands::ScriptExecutor>();
+                        commands::dom::evaluate(window.clone(), script, executor).await
+                    }
+                },
+            );
+            Reply::ok(serde_json::json!({
+                "watchId": watch_id,
+                "intervalMs": interval_ms
+            }))
+            .with("windowContext", resolved.context)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn stop_watch<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    parse_args::<StopWatchArgs>(call.args)
+        .and_then(|args| call.conn.watches.stop(&args.watch_id))
+        .map_or_else(Reply::err, |()| Reply::ok(Value::Null))
+}
+
+/// Registers the element under a handle owned by this connection.
+async fn query_element_handle<R: Runtime>(call: ConnCall<'_, R>) -> Reply {
+    let ConnCall { app, conn, args } = call;
+    let (resolved, args) = match parse_args::<QueryElementHandleArgs>(args).and_then(|args| {
+        resolve_window_with_context(app, args.window_label.clone()).map(|resolved| (resolved, args))
+    }) {
+        Ok(resolved) => resolved,
+        Err(e) => return Reply::err(e),
+    };
+
+    let window = resolved.window.clone();
+    let script = query_handle_script(&args.selector, conn.element_handles.owner());
+    let executor = window.state::<commands::ScriptExecutor>();
+    match commands::dom::evaluate(window.clone(), script, executor).await {
+        Ok(data) => {
+            conn.element_handles.record(window.label());
+            Reply::ok(data).with("windowContext", resolved.context)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// Runs one of the plugin's Tauri commands.
+async fn invoke_tauri<R: Runtime>(call: AppCall<R>) -> Reply {
+    let invoked = parse_args::<InvokeTauriArgs>(call.args.as_ref()).and_then(|invoke| {
+        // Optional window_label for window targeting
+        let window: WindowArgs = parse_args(invoke.args.as_ref())?;
+        Ok((invoke, window.window_label))
+    });
+    match invoked {
+        Ok((invoke, window_label)) => Reply::from(
+            invoke_plugin_command(
+                &call.app,
+                &invoke.command,
+                invoke.args.as_ref(),
+                window_label,
+            )
+            .await,
+        ),
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// The table of commands `invoke_tauri` runs.
+async fn invoke_plugin_command<R: Runtime>(
+    app: &AppHandle<R>,
+    tauri_cmd: &str,
+    args: Option<&Value>,
+    window_label: Option<String>,
+) -> Result<Value, String> {
+    let window = || commands::resolve_window(app, window_label.clone());
+
+    match tauri_cmd {
+        "plugin:mcp-bridge|set_active_window" => {
+            commands::set_active_window(window()?).await.map(data)
+        }
+        "plugin:mcp-bridge|reset_window" => {
+            let args: ResetWindowArgs = parse_args(args)?;
+            commands::reset_window(
+                window()?,
+                args.clear_cache,
+                args.clear_storage,
+                args.clear_cookies,
+                app.state(),
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|get_window_info" => commands::get_window_info(window()?).await.map(data),
+        "plugin:mcp-bridge|set_window_flags" => {
+            let flags: SetWindowFlagsArgs = parse_args(args)?;
+            commands::set_window_flags(
+                window()?,
+                flags.always_on_top,
+                flags.decorations,
+                flags.resizable,
+                flags.fullscreen,
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|get_backend_state" => {
+            commands::get_backend_state(app.clone()).await.map(data)
+        }
+        "plugin:mcp-bridge|get_app_paths" => commands::get_app_paths(app.clone()).await.map(data),
+        "plugin:mcp-bridge|get_config" => commands::get_config(app.clone()).await.map(data),
+        "plugin:mcp-bridge|get_system_status" => commands::get_system_status().await.map(data),
+        "plugin:mcp-bridge|element_from_point" => {
+            let args: ElementFromPointArgs = parse_args(args)?;
+            commands::element_from_point(window()?, args.x, args.y, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_element_box" => {
+            let args: SelectorArgs = parse_args(args)?;
+            commands::get_element_box(window()?, args.selector, args.handle_id, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|is_interactable" => {
+            let args: SelectorArgs = parse_args(args)?;
+            commands::is_interactable(window()?, args.selector, args.handle_id, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|focus_element" => {
+            let args: SelectorArgs = parse_args(args)?;
+            commands::focus_element(window()?, args.selector, args.handle_id, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_active_element" => {
+            commands::get_active_element(window()?, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_current_url" => commands::get_current_url(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|get_user_agent" => commands::get_user_agent(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|set_user_agent" => {
+            let args: SetUserAgentArgs = parse_args(args)?;
+            commands::set_user_agent(window()?, args.user_agent, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_injected_globals_count" => {
+            commands::get_injected_globals_count(window()?, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_meta_tags" => commands::get_meta_tags(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|get_ready_state" => commands::get_ready_state(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|wait_for_ready" => {
+            let args: WaitForReadyArgs = parse_args(args)?;
+            commands::wait_for_ready(window()?, args.state, args.timeout_ms, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|wait_for_network_idle" => {
+            let args: WaitForNetworkIdleArgs = parse_args(args)?;
+            commands::wait_for_network_idle(window()?, args.idle_ms, args.timeout_ms, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|observe_mutations" => {
+            let args: ObserveMutationsArgs = parse_args(args)?;
+            commands::observe_mutations(window()?, args.duration_ms, args.quiet_ms, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|emulate_media" => {
+            let args: EmulateMediaArgs = parse_args(args)?;
+            commands::emulate_media(window()?, args.features, args.reset, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_theme" => {
+            commands::get_theme(window()?, app.state()).await.map(data)
+        }
+        "plugin:mcp-bridge|set_theme" => {
+            let args: SetThemeArgs = parse_args(args)?;
+            commands::set_theme(window()?, args.theme, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|clear_cache" => {
+            let args: ClearCacheArgs = parse_args(args)?;
+            commands::clear_cache(window()?, args.kind).await.map(data)
+        }
+        "plugin:mcp-bridge|assert_element" => {
+            let args: AssertElementArgs = parse_args(args)?;
+            commands::assert_element(
+                window()?,
+                args.selector,
+                args.property,
+                args.attribute_name,
+                args.expected,
+                args.operator,
+                app.state(),
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|freeze_time" => {
+            let args: FreezeTimeArgs = parse_args(args)?;
+            commands::freeze_time(window()?, args.at, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|step_time" => {
+            let args: StepTimeArgs = parse_args(args)?;
+            commands::step_time(window()?, args.ms, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|unfreeze_time" => commands::unfreeze_time(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|capture_window_with_chrome" => {
+            let args: CaptureWindowArgs = parse_args(args)?;
+            commands::capture_window_with_chrome(window()?, args.format, args.quality)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|compare_screenshot" => {
+            let args: CompareScreenshotArgs = parse_args(args)?;
+            commands::compare_screenshot(
+                window()?,
+                args.baseline_path,
+                args.threshold,
+                args.max_diff_ratio,
+                args.diff_image,
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|get_web_vitals" => commands::get_web_vitals(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|list_macros" => Ok(data(commands::list_macros(app.clone()).await)),
+        "plugin:mcp-bridge|run_macro" => {
+            let args: RunMacroArgs = parse_args(args)?;
+            commands::run_macro(window()?, args.name, args.params)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_console_history" => {
+            let args: ConsoleHistoryArgs = parse_args(args)?;
+            commands::get_console_history(window()?, app.state(), args.levels, args.limit)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_failed_requests" => {
+            let args: FailedRequestsArgs = parse_args(args)?;
+            commands::get_failed_requests(window()?, app.state(), args.since)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_readable_text" => {
+            let args: ReadableTextArgs = parse_args(args)?;
+            commands::get_readable_text(window()?, app.state(), args.max_bytes)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_selection" => commands::get_selection(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|get_scroll_info" => commands::get_scroll_info(window()?, app.state())
+            .await
+            .map(data),
+        "plugin:mcp-bridge|set_selection" => {
+            let args: SelectorArgs = parse_args(args)?;
+            commands::set_selection(window()?, args.selector, args.handle_id, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|dispatch_dom_event" => {
+            let args: DispatchDomEventArgs = parse_args(args)?;
+            commands::dispatch_dom_event(
+                window()?,
+                args.selector,
+                args.handle_id,
+                args.event_type,
+                args.bubbles,
+                args.detail,
+                app.state(),
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|drag_and_drop" => {
+            let args: DragAndDropArgs = parse_args(args)?;
+            commands::drag_and_drop(
+                window()?,
+                args.source_selector,
+                args.target_selector,
+                args.mode,
+                app.state(),
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|get_computed_style" => {
+            let args: ComputedStyleArgs = parse_args(args)?;
+            commands::get_computed_style(window()?, args.selector, args.properties, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|get_input_value" => {
+            let args: SelectorArgs = parse_args(args)?;
+            commands::get_input_value(window()?, args.selector, args.handle_id, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|set_input_value" => {
+            let args: SetInputValueArgs = parse_args(args)?;
+            commands::set_input_value(
+                window()?,
+                args.selector,
+                args.handle_id,
+                args.value,
+                app.state(),
+            )
+            .await
+            .map(data)
+        }
+        "plugin:mcp-bridge|fill_form" => {
+            let args: FillFormArgs = parse_args(args)?;
+            commands::fill_form(window()?, args.fields, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|read_form" => {
+            let args: ReadFormArgs = parse_args(args)?;
+            commands::read_form(window()?, args.form_selector, app.state())
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|list_resources" => {
+            let args: ListResourcesArgs = parse_args(args)?;
+            commands::list_resources(app.clone(), args.glob)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|read_resource" => {
+            let args: ReadResourceArgs = parse_args(args)?;
+            commands::read_resource(app.clone(), args.path, args.max_bytes)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|start_ipc_monitor" => {
+            commands::start_ipc_monitor(app.state(), window_label)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|stop_ipc_monitor" => {
+            commands::stop_ipc_monitor(app.state()).await.map(data)
+        }
+        "plugin:mcp-bridge|pause_ipc_monitor" => {
+            commands::pause_ipc_monitor(app.state()).await.map(data)
+        }
+        "plugin:mcp-bridge|resume_ipc_monitor" => {
+            commands::resume_ipc_monitor(app.state()).await.map(data)
+        }
+        "plugin:mcp-bridge|get_ipc_events" => commands::get_ipc_events(app.state(), window_label)
+            .await
+            .map(data),
+        "plugin:mcp-bridge|list_event_listeners" => {
+            commands::list_event_listeners(app.state(), window_label)
+                .await
+                .map(data)
+        }
+        "plugin:mcp-bridge|emit_event" => {
+            let args: EmitEventArgs = parse_args(args)?;
+            commands::emit_event(app.clone(), args.event_name, args.payload)
+                .await
+                .map(data)
+        }
+        _ => Err(format!("Unsupported Tauri command: {tauri_cmd}")),
+    }
+}
+
+async fn get_bridge_logs<R: Runtime>(call: AppCall<R>) -> Reply {
+    match parse_args::<BridgeLogsArgs>(call.args.as_ref()) {
+        Ok(args) => commands::get_bridge_logs(args.limit).await.into(),
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn wait_for_event<R: Runtime>(call: AppCall<R>) -> Reply {
+    match parse_args::<WaitForEventArgs>(call.args.as_ref()) {
+        Ok(args) => commands::wait_for_event(call.app, args.event_name, args.timeout_ms)
+            .await
+            .into(),
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// Cancels everything in flight, on every connection.
+async fn abort_all<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<AbortAllArgs>(call.args.as_ref()) {
+        Ok(args) => commands::abort_all(
+            args.stop_ipc_monitor,
+            args.stop_screencasts,
+            app.state(),
+            app.state(),
+            app.state(),
+        )
+        .await
+        .into(),
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn list_windows<R: Runtime>(call: AppCall<R>) -> Reply {
+    commands::list_windows(call.app).await.into()
+}
+
+async fn get_active_window<R: Runtime>(call: AppCall<R>) -> Reply {
+    commands::get_active_window(call.app).await.into()
+}
+
+async fn get_layout<R: Runtime>(call: AppCall<R>) -> Reply {
+    commands::get_layout(call.app).await.into()
+}
+
+/// Per-window results; windows missing from the layout are untouched.
+async fn set_layout<R: Runtime>(call: AppCall<R>) -> Reply {
+    match parse_args::<SetLayoutArgs>(call.args.as_ref()) {
+        Ok(args) => Reply::ok(commands::set_layout(call.app, args.layout).await),
+        Err(e) => Reply::err(e),
+    }
+}
+
+/// The reply to a script run in a resolved window, passing on how the
+/// script itself fared.
+fn script_reply(result: Result<Value, String>, context: WindowContext) -> Reply {
+    let reply = match result {
+        Ok(result) if result.get("success").and_then(Value::as_bool) == Some(false) => {
+            let error = result.get("error").and_then(Value::as_str);
+            Reply::err(error.unwrap_or("Unknown error"))
+        }
+        Ok(result) => {
+            let data = result.get("data").cloned().unwrap_or(Value::Null);
+            let mut reply = Reply::ok(data);
+            for key in ["sizeBytes", "resultType", "encoding"] {
+                if let Some(value) = result.get(key) {
+                    reply = reply.with(key, value);
+                }
+            }
+            reply
+        }
+        Err(e) => Reply::err(e),
+    };
+    reply.with("windowContext", context)
+}
+
+async fn execute_js<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<ExecuteJsArgs>(call.args.as_ref()).and_then(|args| {
+        resolve_window_with_context(app, args.window_label.clone()).map(|resolved| (resolved, args))
+    }) {
+        Ok((resolved, args)) => {
+            let result = commands::execute_js(
+                resolved.window.clone(),
+                args.script,
+                app.state::<commands::ScriptExecutor>(),
+                args.max_result_bytes,
+                Some(false),
+                args.world,
+            )
+            .await;
+            script_reply(result, resolved.context)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn execute_js_all<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<ExecuteJsAllArgs>(call.args.as_ref()) {
+        Ok(args) => commands::execute_js_all(
+            app.clone(),
+            args.script,
+            app.state::<commands::ScriptExecutor>(),
+            args.timeout_ms,
+        )
+        .await
+        .into(),
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn execute_js_file<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<ExecuteJsFileArgs>(call.args.as_ref()).and_then(|args| {
+        resolve_window_with_context(app, args.window_label.clone()).map(|resolved| (resolved, args))
+    }) {
+        Ok((resolved, args)) => {
+            let result = commands::execute_js_file(
+                resolved.window.clone(),
+                args.path,
+                args.args,
+                app.state::<commands::ScriptExecutor>(),
+            )
+            .await;
+            script_reply(result, resolved.context)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn execute_js_if<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<ExecuteJsIfArgs>(call.args.as_ref()).and_then(|args| {
+        resolve_window_with_context(app, args.window_label.clone()).map(|resolved| (resolved, args))
+    }) {
+        Ok((resolved, args)) => {
+            let result = commands::execute_js_if(
+                resolved.window.clone(),
+                args.condition,
+                args.script,
+                app.state::<commands::ScriptExecutor>(),
+            )
+            .await;
+            script_reply(result, resolved.context)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn capture_native_screenshot<R: Runtime>(call: AppCall<R>) -> Reply {
+    let AppCall {
+        app,
+        args,
+        local_addr,
+    } = call;
+    let (resolved, by_url, args) =
+        match parse_args::<ScreenshotArgs>(args.as_ref()).and_then(|args| {
+            let by_url = delivery_by_url(args.delivery.as_deref())?;
+            resolve_window_with_context(&app, args.window_label.clone())
+                .map(|resolved| (resolved, by_url, args))
+        }) {
+            Ok(resolved) => resolved,
+            Err(e) => return Reply::err(e),
+        };
+
+    let captured = commands::capture_native_screenshot(
+        resolved.window,
+        args.format,
+        args.quality,
+        args.background,
+        args.settle_ms,
+        args.canvas_selector,
+        args.diff,
+        args.color_mode,
+        args.max_colors,
+        args.scroll_container_selector,
+        args.scroll_overlap,
+    )
+    .await
+    .and_then(|image| {
+        if image.unchanged {
+            return Ok((serde_json::json!({ "unchanged": true }), image));
+        }
+        if let Some(pixels) = &image.rgba {
+            if by_url {
+                return Err("delivery \"url\" does not support format \"rgba\"".to_string());
+            }
+            return Ok((serde_json::json!(pixels.data), image));
+        }
+        let mut data = if by_url {
+            // Keep the bytes for download instead of sending them
+            let (format, bytes) = decode_data_url(&image.data_url)?;
+            let stored = app.state::<CaptureStore>().insert(&format, bytes)?;
+            let mut data = serde_json::json!(stored);
+            data["url"] =
+                serde_json::json!(format!("http://{local_addr}/screenshot/{}", stored.id));
+            data
+        } else {
+            serde_json::json!(image.data_url)
+        };
+        // Diffed frames only cover the changed region
+        if let Some(region) = image.changed_region {
+            if !by_url {
+                data = serde_json::json!({ "dataUrl": data });
+            }
+            data["unchanged"] = serde_json::json!(false);
+            data["changedRegion"] = serde_json::json!(region);
+        }
+        Ok((data, image))
+    });
+
+    let (data, image) = match captured {
+        Ok(captured) => captured,
+        Err(e) => return Reply::err(e).with("windowContext", resolved.context),
+    };
+    let mut reply = Reply::ok(data).with("windowContext", resolved.context);
+    // Raw pixels travel like binary script results
+    if image.rgba.is_some() {
+        reply = reply
+            .with("resultType", "binary")
+            .with("encoding", "base64");
+    }
+    if let Some(warning) = image.warning {
+        reply = reply.with("warning", warning);
+    }
+    let mut metadata = match image.monitor {
+        Some(monitor) => serde_json::json!(monitor),
+        None => serde_json::json!({}),
+    };
+    if let Some(pixels) = image.rgba {
+        metadata["pixelFormat"] = serde_json::json!("rgba8");
+        metadata["width"] = serde_json::json!(pixels.width);
+        metadata["height"] = serde_json::json!(pixels.height);
+        metadata["stride"] = serde_json::json!(pixels.stride);
+    }
+    let color = image.color.unwrap_or_default();
+    metadata["colorMode"] = serde_json::json!(color.color_mode);
+    if let Some(max_colors) = color.max_colors {
+        metadata["maxColors"] = serde_json::json!(max_colors);
+    }
+    reply.with("metadata", metadata)
+}
+
+async fn register_script<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    let args = match parse_args::<RegisterScriptArgs>(call.args.as_ref()) {
+        Ok(args) => args,
+        Err(e) => return Reply::err(e),
+    };
+    let script_type = match args.script_type.as_str() {
+        "url" => ScriptType::Url,
+        _ => ScriptType::Inline,
+    };
+
+    let entry = ScriptEntry {
+        id: args.id.clone(),
+        script_type,
+        content: args.content,
+    };
+
+    // Add to registry, unless it's full
+    let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
+    let added = registry.lock().unwrap().add(entry.clone());
+
+    // Inject the script into the webview
+    match added.and_then(|()| inject_script_to_webview(app, &entry, args.window_label)) {
+        Ok(result) => Reply::ok(serde_json::json!({ "registered": true, "scriptId": args.id }))
+            .with("windowContext", result.window_context),
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn remove_script<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    let args = match parse_args::<RemoveScriptArgs>(call.args.as_ref()) {
+        Ok(args) => args,
+        Err(e) => return Reply::err(e),
+    };
+    let script_id = args.id.as_str();
+    let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
+    let removed = {
+        let mut reg = registry.lock().unwrap();
+        reg.remove(script_id).is_some()
+    };
+
+    // Remove from DOM
+    if args.all_windows {
+        let batch = remove_script_from_all_windows(app, script_id);
+        let mut data = batch.to_value();
+        data["removed"] = serde_json::json!(removed);
+        data["scriptId"] = serde_json::json!(script_id);
+        return Reply::ok(data);
+    }
+    let data = serde_json::json!({ "removed": removed, "scriptId": script_id });
+    match remove_script_from_webview(app, script_id, args.window_label) {
+        Ok(result) => Reply::ok(data).with("windowContext", result.window_context),
+        Err(e) => {
+            eprintln!("Failed to remove script from DOM: {e}");
+            Reply::ok(data).with(
+                "error",
+                format!("Script removed from registry but DOM removal failed: {e}"),
+            )
+        }
+    }
+}
+
+async fn clear_scripts<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    let args = match parse_args::<ClearScriptsArgs>(call.args.as_ref()) {
+        Ok(args) => args,
+        Err(e) => return Reply::err(e),
+    };
+    let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
+    let count = {
+        let mut reg = registry.lock().unwrap();
+        let count = reg.len();
+        reg.clear();
+        count
+    };
+
+    // Clear from DOM
+    if args.all_windows {
+        let batch = clear_scripts_from_all_windows(app);
+        let mut data = batch.to_value();
+        data["cleared"] = serde_json::json!(count);
+        return Reply::ok(data);
+    }
+    let data = serde_json::json!({ "cleared": count });
+    match clear_scripts_from_webview(app, args.window_label) {
+        Ok(result) => Reply::ok(data).with("windowContext", result.window_context),
+        Err(e) => {
+            eprintln!("Failed to clear scripts from DOM: {e}");
+            Reply::ok(data).with(
+                "error",
+                format!("Scripts cleared from registry but DOM clear failed: {e}"),
+            )
+        }
+    }
+}
+
+async fn get_scripts<R: Runtime>(call: AppCall<R>) -> Reply {
+    let registry: tauri::State<'_, SharedScriptRegistry> = call.app.state();
+    let (scripts, usage): (Vec<Value>, _) = {
+        let reg = registry.lock().unwrap();
+        let scripts = reg
+            .get_all()
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "id": entry.id,
+                    "type": match entry.script_type {
+                        ScriptType::Inline => "inline",
+                        ScriptType::Url => "url",
+                    },
+                    "content": entry.content
+                })
+            })
+            .collect();
+        (scripts, reg.usage())
+    };
+
+    Reply::ok(serde_json::json!({
+        "scripts": scripts,
+        "count": usage.count,
+        "maxScripts": usage.max_scripts,
+        "totalBytes": usage.total_bytes,
+        "maxTotalBytes": usage.max_total_bytes
+    }))
+}
+
+/// Stubs matching fetch/XHR calls in every window.
+async fn add_mock<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    let mocks: tauri::State<'_, MockRegistry> = app.state();
+    let added = parse_args::<AddMockArgs>(call.args.as_ref()).and_then(|args| {
+        MockRule::new(
+            args.id,
+            args.url_pattern,
+            args.method,
+            args.status,
+            args.body,
+            args.headers,
+        )
+        .and_then(|rule| mocks.add(rule))
+    });
+    match added {
+        Ok(rule) => {
+            let mut data = apply_mocks_to_all_windows(app, &mocks).to_value();
+            data["mock"] = serde_json::json!(rule);
+            Reply::ok(data)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn remove_mock<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    match parse_args::<RemoveMockArgs>(call.args.as_ref()) {
+        Ok(args) => {
+            let mocks: tauri::State<'_, MockRegistry> = app.state();
+            let removed = mocks.remove(&args.id);
+            let mut data = apply_mocks_to_all_windows(app, &mocks).to_value();
+            data["removed"] = serde_json::json!(removed);
+            data["mockId"] = serde_json::json!(args.id);
+            Reply::ok(data)
+        }
+        Err(e) => Reply::err(e),
+    }
+}
+
+async fn clear_mocks<R: Runtime>(call: AppCall<R>) -> Reply {
+    let app = &call.app;
+    let mocks: tauri::State<'_, MockRegistry> = app.state();
+    let count = mocks.clear();
+    let mut data = apply_mocks_to_all_windows(app, &mocks).to_value();
+    data["cleared"] = serde_json::json!(count);
+    Reply::ok(data)
+}
+
+async fn get_mocks<R: Runtime>(call: AppCall<R>) -> Reply {
+    let mocks: tauri::State<'_, MockRegistry> = call.app.state();
+    let rules = mocks.rules();
+    Reply::ok(serde_json::json!({ "mocks": rules, "count": rules.len() }))
+}
+
+/// Result of a script operation with window context.
+struct ScriptOperationResult {
+    window_context: WindowContext,
+}
+
+/// Reports an ambiguous `windowMatch` in a response's `windowContext`,
+/// adding one for commands that don't report their window otherwise.
+fn add_window_match_warning(response: &mut serde_json::Value, context: WindowContext) {
+    let Some(warning) = context.warning.clone() else {
+        return;
+    };
+    match response.get_mut("windowContext") {
+        Some(serde_json::Value::Object(existing)) => {
+            existing.insert("warning".to_string(), warning.into());
+        }
+        _ => {
+            response["windowContext"] = serde_json::json!(context);
+        }
+    }
+}
+
+/// Injects a script into a specific webview window.
+fn inject_script_to_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    entry: &ScriptEntry,
+) -> Result<(), String> {
+    let script = entry.injection_script();
+
+    window
+        .eval(&script)
+        .map_err(|e| format!("Failed to inject script: {e}"))
+}
+
+/// Injects a script into the webview DOM.
+/// If a script with the same ID already exists, it is removed first.
+/// Returns window context for the response.
+fn inject_script_to_webview<R: Runtime>(
+    app: &AppHandle<R>,
+    entry: &ScriptEntry,
+    window_label: Option<String>,
+) -> Result<ScriptOperationResult, String> {
+    let resolved = resolve_window_with_context(app, window_label)?;
+
+    inject_script_to_window(&resolved.window, entry)?;
+
+    Ok(ScriptOperationResult {
+        window_context: resolved.context,
+    })
+}
+
+/// Removes a script from a specific window's DOM.
+fn remove_script_from_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+    script_id: &str,
+) -> Result<(), String> {
+    let script = format!(
+        r#"
+        (function() {{
+            var script = document.querySelector('script[data-mcp-script-id="{script_id}"]');
+            if (script) {{
+                script.remove();
+            }}
+        }})();
+        "#
+    );
+
+    window
+        .eval(&script)
+        .map_err(|e| format!("Failed to remove script: {e}"))
+}
+
+/// Removes a script from the webview DOM by ID.
+/// Returns window context for the response.
+fn remove_script_from_webview<R: Runtime>(
+    app: &AppHandle<R>,
+    script_id: &str,
+    window_label: Option<String>,
+) -> Result<ScriptOperationResult, String> {
+    let resolved = resolve_window_with_context(app, window_label)?;
+
+    remove_script_from_window(&resolved.window, script_id)?;
+
+    Ok(ScriptOperationResult {
+        window_context: resolved.context,
+    })
+}
+
+/// Removes a script from the DOM of every open window.
+/// A failure in one window does not prevent removal from the others.
+fn remove_script_from_all_windows<R: Runtime>(app: &AppHandle<R>, script_id: &str) -> BatchResult {
+    let mut batch = BatchResult::new();
+
+    for (label, window) in sorted_webview_windows(app) {
+        batch.push_labeled(
+            &label,
+            remove_script_from_window(&window, script_id).map(|_| None),
+        );
+    }
+
+    batch
+}
+
+/// Clears all MCP-managed scripts from a specific window's DOM.
+fn clear_scripts_from_window<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
+    let script = r#"
+        (function() {
+            var scripts = document.querySelectorAll('script[data-mcp-script-id]');
+            scripts.forEach(function(s) { s.remove(); });
+        })();
+    "#;
+
+    window
+        .eval(script)
+        .map_err(|e| format!("Failed to clear scripts: {e}"))
+}
+
+/// Clears all MCP-managed scripts from the webview DOM.
+/// Returns window context for the response.
+fn clear_scripts_from_webview<R: Runtime>(
+    app: &AppHandle<R>,
+    window_label: Option<String>,
+) -> Result<ScriptOperationResult, String> {
+    let resolved = resolve_window_with_context(app, window_label)?;
+
+    clear_scripts_from_window(&resolved.window)?;
+
+    Ok(ScriptOperationResult {
+        window_context: resolved.context,
+    })
+}
+
+/// Clears all MCP-managed scripts from the DOM of every open window.
+/// A failure in one window does not prevent clearing the others.
+fn clear_scripts_from_all_windows<R: Runtime>(app: &AppHandle<R>) -> BatchResult {
+    let mut batch = BatchResult::new();
+
+    for (label, window) in sorted_webview_windows(app) {
+        batch.push_labeled(&label, clear_scripts_from_window(&window).map(|_| None));
+    }
+
+    batch
+}
+
+/// Pushes the current mock rules to every open window, skipping restricted
+/// ones. Pages loaded later fetch them through `request_mocks`.
+fn apply_mocks_to_all_windows<R: Runtime>(app: &AppHandle<R>, mocks: &MockRegistry) -> BatchResult {
+    let script = mocks.apply_script();
+    let mut batch = BatchResult::new();
+
+    for (label, window) in sorted_webview_windows(app) {
+        if check_window_access(app, &label).is_err() {
+            continue;
+        }
+        let applied = window
+            .eval(&script)
+            .map(|()| None)
+            .map_err(|e| format!("Failed to apply mocks: {e}"));
+        batch.push_labeled(&label, applied);
+    }
+
+    batch
+}
+
+/// Returns all webview windows sorted by label for deterministic ordering.
+fn sorted_webview_windows<R: Runtime>(app: &AppHandle<R>) -> Vec<(String, WebviewWindow<R>)> {
+    let mut windows: Vec<(String, WebviewWindow<R>)> = app.webview_windows().into_iter().collect();
+    windows.sort_by(|a, b| a.0.cmp(&b.0));
+    windows
+}
+
+/// Injects all registered scripts into the webview.
+/// Called when a page loads to re-inject persistent scripts.
+///
+/// Each script is injected independently; the returned batch result reports
+/// the outcome per script ID so one broken script does not block the rest.
+pub fn inject_all_scripts<R: Runtime>(
+    app: &AppHandle<R>,
+    window_label: Option<String>,
+) -> Result<BatchResult, String> {
+    let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
+    let scripts: Vec<ScriptEntry> = {
+        let reg = registry.lock().unwrap();
+        reg.get_all().iter().map(|e| (*e).clone()).collect()
+    };
+
+    let resolved = resolve_window_with_context(app, window_label)?;
+
+    let mut batch = BatchResult::new();
+    for entry in &scripts {
+        batch.push_labeled(
+            &entry.id,
+            inject_script_to_window(&resolved.window, entry).map(|_| None),
+        );
+    }
+
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::AuthGate;
+    use crate::capabilities::Capabilities;
+    use crate::clients::ClientRegistry;
+    use crate::pipeline::{parse_request, route, RequestSlots};
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn conn_state() -> ConnState {
+        let address = "127.0.0.1:50000".parse().unwrap();
+        let local_addr = "127.0.0.1:9223".parse().unwrap();
+        let (conn, _responses) = ConnState::new(
+            ClientRegistry::new(),
+            address,
+            local_addr,
+            Capabilities::from_config(&Config::default()),
+            AuthGate::new(None),
+        );
+        conn
+    }
+
+    #[test]
+    fn test_handler_table_tells_connection_commands_apart() {
+        type Mock = tauri::test::MockRuntime;
+        for command in ["authenticate", "hello", "set_default_window", "watch_js"] {
+            assert!(
+                matches!(handler::<Mock>(command), Some(Handler::Connection(_))),
+                "{command}"
+            );
+        }
+        for command in ["invoke_tauri", "execute_js", "capture_native_screenshot"] {
+            assert!(
+                matches!(handler::<Mock>(command), Some(Handler::App(_))),
+                "{command}"
+            );
+        }
+        assert!(handler::<Mock>("teleport").is_none());
+    }
+
+    #[test]
+    fn test_replies_become_response_bodies() {
+        let ok = Reply::ok([1, 2]).with("windowContext", json!({ "windowLabel": "main" }));
+        assert_eq!(
+            ok.into_body("1"),
+            json!({
+                "id": "1",
+                "success": true,
+                "data": [1, 2],
+                "windowContext": { "windowLabel": "main" }
+            })
+        );
+
+        let failed = Reply::from(Err::<Value, _>("boom".to_string()));
+        assert_eq!(failed.into_body("2"), error_body("2", "boom"));
+        assert_eq!(
+            error_body("2", "boom"),
+            json!({ "id": "2", "success": false, "error": "boom" })
+        );
+    }
+
+    #[test]
+    fn test_script_replies_pass_on_how_the_script_fared() {
+        let context = || WindowContext {
+            window_label: "main".to_string(),
+            total_windows: 1,
+            warning: None,
+        };
+
+        let ran = script_reply(
+            Ok(json!({ "success": true, "data": "x", "sizeBytes": 3 })),
+            context(),
+        );
+        let body = ran.into_body("1");
+        assert_eq!(
+            (&body["data"], &body["sizeBytes"]),
+            (&json!("x"), &json!(3))
+        );
+        assert_eq!(body["windowContext"]["windowLabel"], "main");
+
+        let threw = script_reply(
+            Ok(json!({ "success": false, "error": "ReferenceError: x" })),
+            context(),
+        );
+        let body = threw.into_body("2");
+        assert_eq!(
+            (&body["success"], &body["error"]),
+            (&json!(false), &json!("ReferenceError: x"))
+        );
+        assert_eq!(body["windowContext"]["windowLabel"], "main");
+    }
+
+    async fn run(
+        conn: &mut ConnState,
+        app: &AppHandle<tauri::test::MockRuntime>,
+        text: &str,
+    ) -> WsResponse {
+        answered(begin(parse_request(text).unwrap(), conn, app).await).await
+    }
+
+    async fn send(
+        conn: &mut ConnState,
+        app: &AppHandle<tauri::test::MockRuntime>,
+        text: &str,
+    ) -> WsResponse {
+        answered(begin_with_timeout(parse_request(text).unwrap(), conn, app).await).await
+    }
+
+    async fn answered(dispatched: Dispatched) -> WsResponse {
+        match dispatched {
+            Dispatched::Answered(response) => response,
+            Dispatched::Running(running) => running.await,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_connection_commands() {
+        let app = tauri::test::mock_app();
+        let mut conn = conn_state();
+
+        let hello = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "1", "command": "hello", "args": { "features": ["batchResults", "teleport"], "format": "json-pretty" } }"#,
+        )
+        .await;
+        assert_eq!(hello.body["data"]["accepted"], json!(["batchResults"]));
+        assert_eq!(hello.body["data"]["unsupported"], json!(["teleport"]));
+        assert_eq!(hello.format, Some(ResponseFormat::JsonPretty));
+
+        let subscribed = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "2", "command": "subscribe", "args": { "topics": ["navigation"] } }"#,
+        )
+        .await;
+        assert_eq!(subscribed.body["data"]["topics"], json!(["navigation"]));
+        assert!(conn
+            .topics
+            .borrow()
+            .topics()
+            .contains(&"navigation".to_string()));
+
+        let clients = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "3", "command": "get_clients" }"#,
+        )
+        .await;
+        assert_eq!(clients.body["data"]["self"], conn.client.id());
+        assert_eq!(clients.format, None);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_reports_errors_with_the_request_id() {
+        let app = tauri::test::mock_app();
+        let mut conn = conn_state();
+
+        let unknown = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "9", "command": "teleport" }"#,
+        )
+        .await;
+        assert_eq!(
+            unknown.body,
+            json!({ "id": "9", "success": false, "error": "Unknown command: teleport" })
+        );
+
+        let bad_match = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "10", "command": "execute_js", "args": { "script": "1", "windowLabel": "main", "windowMatch": { "titleContains": "Doc" } } }"#,
+        )
+        .await;
+        assert_eq!(bad_match.body["id"], "10");
+        assert_eq!(bad_match.body["success"], false);
+        assert_eq!(
+            bad_match.body["error"],
+            "Pass either windowLabel or windowMatch, not both"
+        );
+
+        let unsupported = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "11", "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|teleport" } }"#,
+        )
+        .await;
+        assert_eq!(
+            unsupported.body,
+            error_body(
+                "11",
+                "Unsupported Tauri command: plugin:mcp-bridge|teleport"
+            )
+        );
+
+        // Handles of other connections are refused before the command runs
+        let foreign = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "12", "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|focus_element", "args": { "handleId": "conn999.1" } } }"#,
+        )
+        .await;
+        assert_eq!(foreign.body["id"], "12");
+        assert!(foreign.body["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Unknown element handle: conn999.1"));
+    }
+
+    #[tokio::test]
+    async fn test_default_window_targets_later_commands() {
+        let app = tauri::test::mock_app();
+        let mut conn = conn_state();
+        let window_info = r#"{ "id": "2", "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|get_window_info" } }"#;
+
+        let missing = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "1", "command": "set_default_window", "args": { "windowLabel": "settings" } }"#,
+        )
+        .await;
+        assert_eq!(missing.body["error"], "Window 'settings' not found");
+        assert_eq!(conn.default_window, None);
+
+        // Without a default, commands fall back to "main", which doesn't exist
+        let fallback = run(&mut conn, app.handle(), window_info).await;
+        assert_eq!(fallback.body["error"], "Window 'main' not found");
+
+        tauri::WebviewWindowBuilder::new(app.handle(), "settings", Default::default())
+            .build()
+            .unwrap();
+        let set = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "3", "command": "set_default_window", "args": { "windowLabel": "settings" } }"#,
+        )
+        .await;
+        assert_eq!(set.body["data"]["windowLabel"], "settings");
+        assert_eq!(conn.default_window.as_deref(), Some("settings"));
+
+        let targeted = run(&mut conn, app.handle(), window_info).await;
+        assert_eq!(targeted.body["success"], true, "{}", targeted.body);
+
+        // An explicit label still wins
+        let explicit = run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "4", "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|get_window_info", "args": { "windowLabel": "main" } } }"#,
+        )
+        .await;
+        assert_eq!(explicit.body["error"], "Window 'main' not found");
+
+        // Other connections keep the "main" fallback
+        let mut other = conn_state();
+        let unaffected = run(&mut other, app.handle(), window_info).await;
+        assert_eq!(unaffected.body["error"], "Window 'main' not found");
+
+        run(
+            &mut conn,
+            app.handle(),
+            r#"{ "id": "5", "command": "clear_default_window" }"#,
+        )
+        .await;
+        assert_eq!(conn.default_window, None);
+        let cleared = run(&mut conn, app.handle(), window_info).await;
+        assert_eq!(cleared.body["error"], "Window 'main' not found");
+    }
+
+    #[tokio::test]
+    async fn test_connection_timeout_ends_slow_commands() {
+        let mock = tauri::test::mock_app();
+        let app = mock.handle();
+        let mut conn = conn_state();
+        conn.command_timeout = Duration::from_secs(5);
+
+        let capped = send(
+            &mut conn,
+            app,
+            r#"{ "id": "1", "command": "set_timeout", "args": { "defaultMs": 60000 } }"#,
+        )
+        .await;
+        assert_eq!(
+            capped.body["error"],
+            "defaultMs 60000 exceeds the maximum command timeout of 5000ms"
+        );
+
+        let set = send(
+            &mut conn,
+            app,
+            r#"{ "id": "2", "command": "set_timeout", "args": { "defaultMs": 50 } }"#,
+        )
+        .await;
+        assert_eq!(set.body["data"], json!({ "defaultMs": 50, "maxMs": 5000 }));
+
+        // wait_for_event would otherwise wait 10 seconds for an event that never comes
+        let started = std::time::Instant::now();
+        let waited = send(
+            &mut conn,
+            app,
+            r#"{ "id": "3", "command": "wait_for_event", "args": { "eventName": "never-emitted" } }"#,
+        )
+        .await;
+        assert_eq!(
+            waited.body,
+            json!({ "id": "3", "success": false, "error": "Command 'wait_for_event' timed out after 50ms" })
+        );
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_requests_beyond_the_limit_wait_for_a_free_slot() {
+        use tauri::Emitter;
+
+        let app = tauri::test::mock_app();
+        let handle = app.handle().clone();
+        let (mut conn, mut responses) = ConnState::new(
+            ClientRegistry::new(),
+            "127.0.0.1:50000".parse().unwrap(),
+            "127.0.0.1:9223".parse().unwrap(),
+            Capabilities::from_config(&Config::default()),
+            AuthGate::new(None),
+        );
+        conn.request_slots = RequestSlots::new(2);
+        let wait = |id: &str| {
+            let text = format!(
+                r#"{{ "id": "{id}", "command": "wait_for_event", "args": {{ "eventName": "go-{id}", "timeoutMs": 5000 }} }}"#
+            );
+            parse_request(&text).unwrap()
+        };
+        let id = |frame: Message| {
+            let body: Value = serde_json::from_str(frame.to_text().unwrap()).unwrap();
+            assert_eq!(body["success"], true, "{body}");
+            body["id"].as_str().unwrap().to_string()
+        };
+
+        for request in [wait("1"), wait("2")] {
+            let slot = reserve::<tauri::test::MockRuntime>(&request, &conn.request_slots).await;
+            serve(request, slot, &mut conn, &handle, |response| response).await;
+        }
+
+        // Both slots are taken, so the third request waits
+        let third = wait("3");
+        let slot = {
+            let waiting = reserve::<tauri::test::MockRuntime>(&third, &conn.request_slots);
+            tokio::pin!(waiting);
+            let waited = tokio::time::timeout(Duration::from_millis(100), &mut waiting).await;
+            assert!(waited.is_err());
+
+            handle.emit("go-1", json!(1)).unwrap();
+            tokio::time::timeout(Duration::from_secs(5), waiting)
+                .await
+                .expect("the third request gets a slot once the first is answered")
+        };
+        serve(third, slot, &mut conn, &handle, |response| response).await;
+        assert_eq!(id(responses.try_recv().unwrap()), "1");
+
+        // The third is answered while the second still runs
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        handle.emit("go-3", json!(3)).unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(5), responses.recv()).await;
+        assert_eq!(id(next.unwrap().unwrap()), "3");
+
+        handle.emit("go-2", json!(2)).unwrap();
+        let next = tokio::time::timeout(Duration::from_secs(5), responses.recv()).await;
+        assert_eq!(id(next.unwrap().unwrap()), "2");
+    }
+
+    #[tokio::test]
+    async fn test_namespaced_routing() {
+        let app = tauri::test::mock_app();
+        let mut conn = conn_state();
+        conn.namespace = Some("mcp".to_string());
+        let route_text = |text: &str, conn: &ConnState| {
+            route(parse_request(text).unwrap(), conn.namespace.as_deref())
+        };
+
+        let request = route_text(r#"{ "id": "1", "command": "mcp.get_clients" }"#, &conn).unwrap();
+        let clients = answered(begin(request, &mut conn, app.handle()).await).await;
+        assert_eq!(clients.body["data"]["self"], conn.client.id());
+
+        // Unqualified and foreign commands are left to other consumers
+        let plain = route_text(r#"{ "id": "2", "command": "get_clients" }"#, &conn);
+        assert!(matches!(plain, Err(e) if e.response("2").is_none() && e.close_reason().is_none()));
+        assert!(route_text(r#"{ "id": "3", "command": "other.get_clients" }"#, &conn).is_err());
+
+        let request = route_text(r#"{ "id": "4", "command": "mcp.teleport" }"#, &conn).unwrap();
+        let unknown = answered(begin(request, &mut conn, app.handle()).await).await;
+        assert_eq!(unknown.body["error"], "Unknown command: mcp.teleport");
+    }
+}
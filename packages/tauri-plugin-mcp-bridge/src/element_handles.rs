@@ -0,0 +1,196 @@
+//! Stable handles to elements, for repeated operations on one element.
+//!
+//! Re-querying a selector for every command is slow and breaks when the DOM
+//! changes around the element. A client can instead resolve the selector
+//! once:
+//!
+//! ```json
+//! { "id": "1", "command": "query_element_handle", "args": { "selector": "#save" } }
+//! ```
+//!
+//! and receive `{ handleId, tagName }`. Element commands (`get_element_box`,
+//! `is_interactable`, `get_input_value`, `set_selection`,
+//! `dispatch_dom_event`) then accept `handleId` in place of `selector`.
+//!
+//! `bridge.js` keeps the handles in a registry of weak references, so a
+//! handle never keeps its element alive. A handle stops resolving once its
+//! element is garbage-collected, leaves the document, or the page reloads.
+//! Handles belong to the connection that created them: other connections
+//! can't use them, and they are released when the connection closes.
+
+use crate::script_registry::js_string_literal;
+use std::collections::BTreeSet;
+
+/// How a command finds its element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ElementTarget {
+    /// The first element matching a CSS selector
+    Selector(String),
+    /// An element handle from `query_element_handle`
+    Handle(String),
+}
+
+impl ElementTarget {
+    /// Builds the target from a command's `selector` and `handleId`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(ElementTarget)` - The target
+    /// * `Err(String)` - If neither or both are given
+    pub fn new(selector: Option<String>, handle_id: Option<String>) -> Result<Self, String> {
+        match (selector, handle_id) {
+            (Some(selector), None) => Ok(Self::Selector(selector)),
+            (None, Some(handle_id)) => Ok(Self::Handle(handle_id)),
+            (Some(_), Some(_)) => Err("Pass either selector or handleId, not both".to_string()),
+            (None, None) => Err("Either selector or handleId is required".to_string()),
+        }
+    }
+
+    /// Script prologue declaring `selector`, the target as shown in errors,
+    /// and `el`, the element or `null`.
+    pub fn find_js(&self) -> String {
+        match self {
+            Self::Selector(selector) => format!(
+                "const selector = {};\nconst el = document.querySelector(selector);",
+                js_string_literal(selector)
+            ),
+            Self::Handle(handle_id) => format!(
+                "const selector = {};\nconst el = window.__MCP_HANDLES__ ? window.__MCP_HANDLES__.get(selector) : null;",
+                js_string_literal(handle_id)
+            ),
+        }
+    }
+
+    /// Like [`find_js`](Self::find_js), but throws when there is no element.
+    pub fn require_js(&self) -> String {
+        let error = match self {
+            Self::Selector(_) => "No element matches selector: ",
+            Self::Handle(_) => "Element handle no longer refers to an element in the page: ",
+        };
+        format!(
+            "{}\nif (!el) {{ throw new Error('{error}' + selector); }}",
+            self.find_js()
+        )
+    }
+}
+
+/// Builds the script registering the element matched by `selector` under a
+/// new handle owned by `owner`, returning `{ handleId, tagName }`.
+pub fn query_handle_script(selector: &str, owner: &str) -> String {
+    format!(
+        "{}
+if (!window.__MCP_HANDLES__) {{ throw new Error('The MCP bridge script is not loaded in this window'); }}
+return {{ handleId: window.__MCP_HANDLES__.put(el, {}), tagName: el.localName }};",
+        ElementTarget::Selector(selector.to_string()).require_js(),
+        js_string_literal(owner)
+    )
+}
+
+/// Builds the script dropping every handle of `owner` in a window.
+pub fn release_script(owner: &str) -> String {
+    format!(
+        "window.__MCP_HANDLES__ && window.__MCP_HANDLES__.release({});",
+        js_string_literal(owner)
+    )
+}
+
+/// Element handles created on one connection.
+///
+/// Handle ids start with the connection's owner tag, which is how other
+/// connections' handles are told apart. The windows are tracked so the
+/// handles can be released there when the connection closes.
+#[derive(Debug, Clone, Default)]
+pub struct ElementHandles {
+    owner: String,
+    windows: BTreeSet<String>,
+}
+
+impl ElementHandles {
+    /// Creates the handle set of connection `client_id`.
+    pub fn new(client_id: u64) -> Self {
+        Self {
+            owner: format!("conn{client_id}"),
+            windows: BTreeSet::new(),
+        }
+    }
+
+    /// The tag the connection's handle ids start with.
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    /// Notes that a handle was created in `window_label`.
+    pub fn record(&mut self, window_label: &str) {
+        self.windows.insert(window_label.to_string());
+    }
+
+    /// Windows the connection created handles in.
+    pub fn windows(&self) -> impl Iterator<Item = &str> {
+        self.windows.iter().map(String::as_str)
+    }
+
+    /// Checks that `handle_id` was issued to this connection.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If it was
+    /// * `Err(String)` - If it belongs to another connection or isn't a
+    ///   handle id
+    pub fn check(&self, handle_id: &str) -> Result<(), String> {
+        let owned = handle_id
+            .strip_prefix(self.owner.as_str())
+            .is_some_and(|rest| rest.starts_with('.'));
+        if owned {
+            Ok(())
+        } else {
+            Err(format!(
+                "Unknown element handle: {handle_id}. Handles only work on the connection that created them"
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_targets_resolve_selectors_and_handles() {
+        let by_selector = ElementTarget::new(Some("#save".to_string()), None).unwrap();
+        assert_eq!(
+            by_selector.require_js(),
+            "const selector = \"#save\";\nconst el = document.querySelector(selector);\nif (!el) { throw new Error('No element matches selector: ' + selector); }"
+        );
+
+        let by_handle = ElementTarget::new(None, Some("conn1.ab12cd.3".to_string())).unwrap();
+        assert!(by_handle
+            .find_js()
+            .contains("window.__MCP_HANDLES__.get(selector)"));
+        assert!(by_handle
+            .require_js()
+            .contains("Element handle no longer refers to an element in the page: "));
+
+        assert!(ElementTarget::new(None, None).is_err());
+        assert!(ElementTarget::new(Some("a".to_string()), Some("conn1.x.1".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_handles_are_scoped_to_their_connection() {
+        let mut handles = ElementHandles::new(3);
+        handles.record("main");
+        handles.record("settings");
+        handles.record("main");
+
+        assert!(handles.check("conn3.ab12cd.1").is_ok());
+        assert!(handles.check("conn31.ab12cd.1").is_err());
+        assert!(handles.check("conn4.ab12cd.1").is_err());
+        assert_eq!(handles.windows().collect::<Vec<_>>(), ["main", "settings"]);
+
+        let query = query_handle_script("#save", handles.owner());
+        assert!(query.contains("window.__MCP_HANDLES__.put(el, \"conn3\")"));
+        assert_eq!(
+            release_script(handles.owner()),
+            "window.__MCP_HANDLES__ && window.__MCP_HANDLES__.release(\"conn3\");"
+        );
+    }
+}
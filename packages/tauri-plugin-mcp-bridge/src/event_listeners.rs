@@ -0,0 +1,150 @@
+//! Tracking of the frontend's Tauri event listeners.
+//!
+//! Tauri doesn't expose which listeners exist, so `bridge.js` wraps the IPC
+//! calls behind `listen`/`once`/`unlisten` (`plugin:event|listen` and
+//! `plugin:event|unlisten`) and reports them here through
+//! `track_event_listener`. `list_event_listeners` then shows the app's event
+//! surface:
+//!
+//! ```json
+//! [{ "event": "download-progress", "count": 2 }, { "event": "tauri://focus", "count": 1 }]
+//! ```
+//!
+//! Only listeners registered from a webview after the bridge script loaded
+//! are seen; listeners registered in Rust with `Listener::listen` are not.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+/// Number of listeners for one event, as reported by `list_event_listeners`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventListenerCount {
+    /// Event name
+    pub event: String,
+    /// Number of active listeners
+    pub count: usize,
+}
+
+/// Listener ids by window label, then event name.
+type Listeners = BTreeMap<String, BTreeMap<String, BTreeSet<u64>>>;
+
+/// Shared record of the frontend's event listeners.
+///
+/// Cheap to clone; clones share the same listeners. The plugin manages one
+/// as Tauri state for the tracking and listing commands.
+#[derive(Clone, Default)]
+pub struct EventListenerRegistry {
+    listeners: Arc<Mutex<Listeners>>,
+}
+
+impl EventListenerRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a listener registered by `window`.
+    pub fn listen(&self, window: &str, event: &str, id: u64) {
+        self.listeners
+            .lock()
+            .unwrap()
+            .entry(window.to_string())
+            .or_default()
+            .entry(event.to_string())
+            .or_default()
+            .insert(id);
+    }
+
+    /// Removes a listener of `window`. Unknown listeners are ignored.
+    pub fn unlisten(&self, window: &str, event: &str, id: u64) {
+        let mut listeners = self.listeners.lock().unwrap();
+        if let Some(events) = listeners.get_mut(window) {
+            if let Some(ids) = events.get_mut(event) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    events.remove(event);
+                }
+            }
+        }
+    }
+
+    /// Forgets every listener of `window`, e.g. when its page reloads.
+    pub fn clear_window(&self, window: &str) {
+        self.listeners.lock().unwrap().remove(window);
+    }
+
+    /// Counts listeners per event, sorted by event name.
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Only count listeners of this window. Counts all windows
+    ///   when `None`.
+    pub fn counts(&self, window: Option<&str>) -> Vec<EventListenerCount> {
+        let listeners = self.listeners.lock().unwrap();
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for (label, events) in listeners.iter() {
+            if window.is_some_and(|window| window != label) {
+                continue;
+            }
+            for (event, ids) in events {
+                *counts.entry(event).or_default() += ids.len();
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|(event, count)| EventListenerCount {
+                event: event.to_string(),
+                count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn count(event: &str, count: usize) -> EventListenerCount {
+        EventListenerCount {
+            event: event.to_string(),
+            count,
+        }
+    }
+
+    #[test]
+    fn test_registered_listener_is_listed() {
+        let registry = EventListenerRegistry::new();
+        registry.listen("main", "download-progress", 1);
+        registry.listen("main", "download-progress", 2);
+        registry.listen("settings", "download-progress", 1);
+        registry.listen("settings", "theme-changed", 2);
+
+        assert_eq!(
+            registry.counts(None),
+            vec![count("download-progress", 3), count("theme-changed", 1)]
+        );
+        assert_eq!(
+            registry.counts(Some("main")),
+            vec![count("download-progress", 2)]
+        );
+    }
+
+    #[test]
+    fn test_unlisten_and_reload_remove_listeners() {
+        let registry = EventListenerRegistry::new();
+        registry.listen("main", "ready", 1);
+        registry.listen("main", "tick", 2);
+        registry.listen("other", "tick", 2);
+
+        registry.unlisten("main", "ready", 1);
+        registry.unlisten("main", "ready", 99);
+        assert_eq!(registry.counts(None), vec![count("tick", 2)]);
+
+        registry.clear_window("main");
+        assert_eq!(registry.counts(Some("main")), vec![]);
+        assert_eq!(registry.counts(None), vec![count("tick", 1)]);
+    }
+}
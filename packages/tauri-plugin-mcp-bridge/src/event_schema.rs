@@ -0,0 +1,248 @@
+//! Payload validation for emitted events.
+//!
+//! Event schemas are registered at build time with
+//! [`Builder::register_event_schema`](crate::Builder::register_event_schema).
+//! `emit_event` validates payloads for registered event names before emitting
+//! so malformed payloads fail loudly instead of being silently ignored by
+//! listeners. Events without a registered schema pass through unvalidated.
+//!
+//! Only a practical subset of JSON Schema is supported:
+//! `type` (string or array of strings), `enum`, `const`, `properties`,
+//! `required`, `additionalProperties` (boolean), `items`, `minimum`,
+//! `maximum`, `minLength`, `maxLength`, `minItems` and `maxItems`.
+//! Unknown keywords are ignored.
+
+use serde_json::Value;
+
+/// Validates `payload` against `schema`.
+///
+/// # Returns
+///
+/// * `Ok(())` - The payload matches the schema
+/// * `Err(String)` - Description of the first mismatch, prefixed with its JSON path
+///
+/// # Examples
+///
+/// ```rust
+/// use serde_json::json;
+/// use tauri_plugin_mcp_bridge::event_schema::validate;
+///
+/// let schema = json!({
+///     "type": "object",
+///     "required": ["id"],
+///     "properties": { "id": { "type": "integer" } }
+/// });
+///
+/// assert!(validate(&schema, &json!({ "id": 1 })).is_ok());
+/// assert!(validate(&schema, &json!({ "id": "1" })).is_err());
+/// ```
+pub fn validate(schema: &Value, payload: &Value) -> Result<(), String> {
+    validate_at("$", schema, payload)
+}
+
+fn validate_at(path: &str, schema: &Value, value: &Value) -> Result<(), String> {
+    let Some(schema) = schema.as_object() else {
+        // `true` / `{}`-like schemas accept anything
+        return Ok(());
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|t| matches_type(t, value)) {
+            return Err(format!(
+                "{path}: expected {}, got {}",
+                types.join(" or "),
+                type_name(value)
+            ));
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(|v| v.as_array()) {
+        if !allowed.contains(value) {
+            return Err(format!(
+                "{path}: value {value} is not one of {}",
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            return Err(format!("{path}: expected {constant}, got {value}"));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(required) = schema.get("required").and_then(|v| v.as_array()) {
+                for key in required.iter().filter_map(|k| k.as_str()) {
+                    if !map.contains_key(key) {
+                        return Err(format!("{path}: missing required property '{key}'"));
+                    }
+                }
+            }
+
+            let properties = schema.get("properties").and_then(|v| v.as_object());
+            for (key, child) in map {
+                let child_path = format!("{path}.{key}");
+                match properties.and_then(|p| p.get(key)) {
+                    Some(child_schema) => validate_at(&child_path, child_schema, child)?,
+                    None => {
+                        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                            return Err(format!("{child_path}: unexpected property"));
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(path, "minItems", schema, items.len(), |len, min| len >= min)?;
+            check_bound(path, "maxItems", schema, items.len(), |len, max| len <= max)?;
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(&format!("{path}[{index}]"), item_schema, item)?;
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count();
+            check_bound(path, "minLength", schema, len, |len, min| len >= min)?;
+            check_bound(path, "maxLength", schema, len, |len, max| len <= max)?;
+        }
+        Value::Number(n) => {
+            if let (Some(min), Some(n)) =
+                (schema.get("minimum").and_then(|v| v.as_f64()), n.as_f64())
+            {
+                if n < min {
+                    return Err(format!("{path}: {n} is less than minimum {min}"));
+                }
+            }
+            if let (Some(max), Some(n)) =
+                (schema.get("maximum").and_then(|v| v.as_f64()), n.as_f64())
+            {
+                if n > max {
+                    return Err(format!("{path}: {n} is greater than maximum {max}"));
+                }
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn check_bound(
+    path: &str,
+    keyword: &str,
+    schema: &serde_json::Map<String, Value>,
+    actual: usize,
+    ok: impl Fn(usize, usize) -> bool,
+) -> Result<(), String> {
+    match schema.get(keyword).and_then(|v| v.as_u64()) {
+        Some(bound) if !ok(actual, bound as usize) => Err(format!(
+            "{path}: {keyword} {bound} not satisfied (got {actual})"
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "null" => value.is_null(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn user_schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["name", "role"],
+            "additionalProperties": false,
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "role": { "enum": ["admin", "viewer"] },
+                "age": { "type": "integer", "minimum": 0 },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            }
+        })
+    }
+
+    #[test]
+    fn test_valid_payload() {
+        let payload = json!({ "name": "Ada", "role": "admin", "age": 36, "tags": ["x"] });
+        assert!(validate(&user_schema(), &payload).is_ok());
+    }
+
+    #[test]
+    fn test_violations_report_path() {
+        let schema = user_schema();
+
+        let err = validate(&schema, &json!({ "name": "Ada" })).unwrap_err();
+        assert_eq!(err, "$: missing required property 'role'");
+
+        let err = validate(&schema, &json!({ "name": 5, "role": "admin" })).unwrap_err();
+        assert_eq!(err, "$.name: expected string, got integer");
+
+        let err = validate(&schema, &json!({ "name": "Ada", "role": "owner" })).unwrap_err();
+        assert!(err.starts_with("$.role: value \"owner\" is not one of"));
+
+        let err = validate(
+            &schema,
+            &json!({ "name": "Ada", "role": "admin", "tags": [1] }),
+        )
+        .unwrap_err();
+        assert_eq!(err, "$.tags[0]: expected string, got integer");
+
+        let err = validate(
+            &schema,
+            &json!({ "name": "Ada", "role": "admin", "extra": true }),
+        )
+        .unwrap_err();
+        assert_eq!(err, "$.extra: unexpected property");
+
+        let err = validate(
+            &schema,
+            &json!({ "name": "Ada", "role": "admin", "age": -1 }),
+        )
+        .unwrap_err();
+        assert_eq!(err, "$.age: -1 is less than minimum 0");
+    }
+
+    #[test]
+    fn test_type_arrays_and_permissive_schemas() {
+        let schema = json!({ "type": ["string", "null"] });
+        assert!(validate(&schema, &json!(null)).is_ok());
+        assert!(validate(&schema, &json!("x")).is_ok());
+        assert!(validate(&schema, &json!(1)).is_err());
+
+        assert!(validate(&json!(true), &json!({ "anything": 1 })).is_ok());
+        assert!(validate(&json!({}), &json!([1, 2])).is_ok());
+    }
+}
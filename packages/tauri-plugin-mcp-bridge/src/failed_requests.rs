@@ -0,0 +1,190 @@
+//! Failed resource loads and requests of each window.
+//!
+//! `bridge.js` listens, from document start, for `error` events of resource
+//! elements (images, scripts, stylesheets, media, frames) and for `fetch`
+//! and `XMLHttpRequest` calls that fail or answer with a 4xx/5xx status, and
+//! reports each through `report_failed_request`. The last
+//! [`FAILED_REQUESTS_CAPACITY`] failures of each window are kept across
+//! reloads for `get_failed_requests`. Every failure is also broadcast to
+//! connected clients:
+//!
+//! ```json
+//! { "type": "failedRequest", "windowLabel": "main", "url": "http://localhost:1420/logo.png", "resourceType": "img", "status": null, "timestamp": 1700000000000 }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+
+/// Failures kept per window; older ones are dropped first.
+pub const FAILED_REQUESTS_CAPACITY: usize = 200;
+
+/// One failed load or request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedRequest {
+    /// The absolute URL that failed
+    pub url: String,
+    /// `"fetch"`, `"xhr"`, or the lowercase tag of the element that failed
+    /// to load, such as `"img"`, `"script"` or `"link"`
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// HTTP status of failed `fetch`/XHR responses. `None` for network
+    /// errors and element loads, whose status the page can't see.
+    pub status: Option<u16>,
+    /// Milliseconds since the Unix epoch, from the page's clock
+    pub timestamp: u64,
+}
+
+/// Broadcast frame for a new failure.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FailedRequestFrame<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    window_label: &'a str,
+    url: &'a str,
+    resource_type: &'a str,
+    status: Option<u16>,
+    timestamp: u64,
+}
+
+/// Shared failed-request history of every window.
+///
+/// Cheap to clone; clones share the same history. The plugin manages one as
+/// Tauri state.
+#[derive(Clone)]
+pub struct FailedRequests {
+    entries: Arc<Mutex<HashMap<String, VecDeque<FailedRequest>>>>,
+    capacity: usize,
+    events: broadcast::Sender<String>,
+}
+
+impl FailedRequests {
+    /// Creates an empty history that broadcasts new failures on `events`.
+    pub fn new(events: broadcast::Sender<String>) -> Self {
+        Self::with_capacity(events, FAILED_REQUESTS_CAPACITY)
+    }
+
+    /// Creates an empty history keeping `capacity` failures per window.
+    pub fn with_capacity(events: broadcast::Sender<String>, capacity: usize) -> Self {
+        Self {
+            entries: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+            events,
+        }
+    }
+
+    /// Records a failure of `window_label` and broadcasts it.
+    pub fn record(&self, window_label: &str, failure: FailedRequest) {
+        let frame = FailedRequestFrame {
+            kind: "failedRequest",
+            window_label,
+            url: &failure.url,
+            resource_type: &failure.kind,
+            status: failure.status,
+            timestamp: failure.timestamp,
+        };
+        if let Ok(frame) = serde_json::to_string(&frame) {
+            // No receivers just means no client is connected
+            let _ = self.events.send(frame);
+        }
+
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let window = entries.entry(window_label.to_string()).or_default();
+        if window.len() == self.capacity {
+            window.pop_front();
+        }
+        window.push_back(failure);
+    }
+
+    /// Returns the failures of a window, oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_label` - The window
+    /// * `since` - Only return failures at or after this time, in
+    ///   milliseconds since the epoch. All kept failures when `None`.
+    pub fn entries(&self, window_label: &str, since: Option<u64>) -> Vec<FailedRequest> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let Some(window) = entries.get(window_label) else {
+            return Vec::new();
+        };
+
+        window
+            .iter()
+            .filter(|failure| since.map_or(true, |since| failure.timestamp >= since))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn failure(url: &str, kind: &str, status: Option<u16>, timestamp: u64) -> FailedRequest {
+        FailedRequest {
+            url: url.to_string(),
+            kind: kind.to_string(),
+            status,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_failed_image_load_is_retrievable_and_broadcast() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let requests = FailedRequests::new(tx);
+        let missing = failure("http://localhost:1420/missing.png", "img", None, 1_000);
+
+        requests.record("main", missing.clone());
+        requests.record("other", failure("http://x/api", "fetch", Some(500), 1_000));
+
+        assert_eq!(requests.entries("main", None), vec![missing]);
+        assert!(requests.entries("missing", None).is_empty());
+
+        let frame: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["type"], "failedRequest");
+        assert_eq!(frame["windowLabel"], "main");
+        assert_eq!(frame["resourceType"], "img");
+        assert_eq!(frame["url"], "http://localhost:1420/missing.png");
+        assert!(frame["status"].is_null());
+
+        // Reported entries use `type`, as returned by get_failed_requests
+        let reported: FailedRequest = serde_json::from_value(serde_json::json!({
+            "url": "http://x/app.css",
+            "type": "link",
+            "status": null,
+            "timestamp": 5
+        }))
+        .unwrap();
+        assert_eq!(reported.kind, "link");
+    }
+
+    #[test]
+    fn test_since_filters_and_history_is_capped() {
+        let (tx, _rx) = broadcast::channel(8);
+        let requests = FailedRequests::with_capacity(tx, 3);
+        for i in 0..5 {
+            requests.record(
+                "main",
+                failure(&format!("http://x/{i}"), "xhr", Some(404), i * 10),
+            );
+        }
+
+        let urls = |entries: Vec<FailedRequest>| -> Vec<String> {
+            entries.into_iter().map(|e| e.url).collect()
+        };
+        assert_eq!(
+            urls(requests.entries("main", None)),
+            ["http://x/2", "http://x/3", "http://x/4"]
+        );
+        assert_eq!(
+            urls(requests.entries("main", Some(30))),
+            ["http://x/3", "http://x/4"]
+        );
+    }
+}
@@ -0,0 +1,273 @@
+//! Plain HTTP requests on the WebSocket port.
+//!
+//! Every accepted connection is peeked at before the WebSocket handshake.
+//! Requests that don't ask for a WebSocket upgrade are answered here
+//! instead, which lets clients download large payloads without pushing them
+//! through the command channel:
+//!
+//! | Request | Response |
+//! |---------|----------|
+//! | `GET /screenshot/{id}` | The capture stored under `id` (see [`capture_store`](crate::capture_store)), or `404` once it expired |
+//! | Anything else | `404` (`405` for methods other than `GET`/`HEAD`) |
+//!
+//! Each response closes the connection.
+
+use crate::capture_store::CaptureStore;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Largest request head looked at. Longer heads are left to the WebSocket
+/// handshake, which rejects them.
+const MAX_HEAD_BYTES: usize = 8 * 1024;
+
+/// How long a client may take to send its request head.
+const HEAD_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The parts of an HTTP request head needed for routing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestHead {
+    pub method: String,
+    pub path: String,
+    /// Whether the request asks for a WebSocket upgrade
+    pub upgrade: bool,
+    /// Length of the head in bytes, including the blank line ending it
+    pub len: usize,
+}
+
+/// An HTTP response to write back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: String,
+    pub body: Arc<Vec<u8>>,
+    /// Send headers only (`HEAD` requests)
+    pub head_only: bool,
+}
+
+impl Response {
+    fn text(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8".to_string(),
+            body: Arc::new(message.as_bytes().to_vec()),
+            head_only: false,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Error",
+        }
+    }
+
+    /// Serializes the status line and headers.
+    fn head(&self) -> String {
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+            self.status,
+            self.reason(),
+            self.content_type,
+            self.body.len()
+        )
+    }
+}
+
+/// Parses an HTTP request head from the start of `buf`.
+///
+/// Returns `None` until the blank line ending the head has arrived, or if
+/// the request line is malformed.
+pub fn parse_head(buf: &[u8]) -> Option<RequestHead> {
+    let end = buf.windows(4).position(|w| w == b"\r\n\r\n")? + 4;
+    let head = std::str::from_utf8(&buf[..end]).ok()?;
+    let mut lines = head.split("\r\n");
+
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    if !request_line.next()?.starts_with("HTTP/") {
+        return None;
+    }
+
+    let upgrade = lines.any(|line| {
+        line.split_once(':').is_some_and(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("upgrade")
+                && value.trim().eq_ignore_ascii_case("websocket")
+        })
+    });
+
+    Some(RequestHead {
+        method,
+        path,
+        upgrade,
+        len: end,
+    })
+}
+
+/// Waits for the request head of a new connection without consuming it.
+///
+/// Returns `None` if the client sends something that isn't an HTTP request
+/// head within [`HEAD_TIMEOUT`], so the caller can fall back to the
+/// WebSocket handshake and its error handling.
+pub async fn peek_head(stream: &TcpStream) -> Option<RequestHead> {
+    let peek = async {
+        let mut buf = vec![0u8; MAX_HEAD_BYTES];
+        loop {
+            let read = stream.peek(&mut buf).await.ok()?;
+            if read == 0 {
+                return None;
+            }
+            if let Some(head) = parse_head(&buf[..read]) {
+                return Some(head);
+            }
+            if read == buf.len() {
+                return None;
+            }
+            // Peeking returns immediately while data is buffered, so wait
+            // for more to arrive before looking again
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    };
+    tokio::time::timeout(HEAD_TIMEOUT, peek)
+        .await
+        .ok()
+        .flatten()
+}
+
+/// Routes a plain HTTP request.
+pub fn route(head: &RequestHead, captures: &CaptureStore) -> Response {
+    let head_only = match head.method.as_str() {
+        "GET" => false,
+        "HEAD" => true,
+        _ => return Response::text(405, "Only GET and HEAD are supported"),
+    };
+
+    // Ignore any query string
+    let path = head.path.split('?').next().unwrap_or_default();
+    let response = match path.strip_prefix("/screenshot/") {
+        Some(id) => match captures.get(id) {
+            Some((content_type, body)) => Response {
+                status: 200,
+                content_type,
+                body,
+                head_only: false,
+            },
+            None => Response::text(404, "Screenshot not found or expired"),
+        },
+        None => Response::text(404, "Not found"),
+    };
+
+    Response {
+        head_only,
+        ..response
+    }
+}
+
+/// Answers a plain HTTP request on `stream` and closes it.
+pub async fn serve(
+    mut stream: TcpStream,
+    head: RequestHead,
+    captures: &CaptureStore,
+) -> std::io::Result<()> {
+    // Consume the head that was only peeked at so far
+    let mut consumed = vec![0u8; head.len];
+    stream.read_exact(&mut consumed).await?;
+
+    let response = route(&head, captures);
+    stream.write_all(response.head().as_bytes()).await?;
+    if !response.head_only {
+        stream.write_all(&response.body).await?;
+    }
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn get(path: &str) -> RequestHead {
+        parse_head(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").as_bytes()).unwrap()
+    }
+
+    fn png() -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image::RgbaImage::new(2, 2)
+            .write_to(
+                &mut std::io::Cursor::new(&mut bytes),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_parse_head_detects_websocket_upgrade() {
+        let request = b"GET / HTTP/1.1\r\nHost: localhost\r\nUpgrade: WebSocket\r\nConnection: Upgrade\r\n\r\n";
+        let head = parse_head(request).unwrap();
+
+        assert!(head.upgrade);
+        assert_eq!(head.len, request.len());
+        assert!(!get("/screenshot/abc").upgrade);
+
+        // Incomplete or garbage input isn't a request head
+        assert_eq!(parse_head(b"GET / HTTP/1.1\r\nHost: x\r\n"), None);
+        assert_eq!(parse_head(b"\x00\x01\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_routes_screenshots_by_id() {
+        let captures = CaptureStore::new();
+        let reference = captures.insert("png", png()).unwrap();
+
+        let response = route(&get(&format!("/screenshot/{}", reference.id)), &captures);
+        assert_eq!(response.status, 200);
+        assert_eq!(response.content_type, "image/png");
+        assert_eq!(*response.body, png());
+
+        assert_eq!(route(&get("/screenshot/expired"), &captures).status, 404);
+        assert_eq!(route(&get("/other"), &captures).status, 404);
+
+        let post = RequestHead {
+            method: "POST".to_string(),
+            ..get("/screenshot/x")
+        };
+        assert_eq!(route(&post, &captures).status, 405);
+    }
+
+    #[tokio::test]
+    async fn test_screenshot_download_over_tcp() {
+        let captures = CaptureStore::new();
+        let reference = captures.insert("png", png()).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let request = format!(
+            "GET /screenshot/{} HTTP/1.1\r\nHost: {addr}\r\n\r\n",
+            reference.id
+        );
+        let client = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(addr).await.unwrap();
+            stream.write_all(request.as_bytes()).await.unwrap();
+            let mut response = Vec::new();
+            stream.read_to_end(&mut response).await.unwrap();
+            response
+        });
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let head = peek_head(&stream).await.unwrap();
+        assert!(!head.upgrade);
+        serve(stream, head, &captures).await.unwrap();
+
+        let response = client.await.unwrap();
+        let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap() + 4;
+        let headers = String::from_utf8_lossy(&response[..split]);
+        assert!(headers.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(headers.contains("Content-Type: image/png\r\n"));
+        assert_eq!(response[split..], png()[..]);
+    }
+}
@@ -0,0 +1,89 @@
+//! Application-level inactivity timeout for client connections.
+//!
+//! WebSocket ping/pong only proves the peer's network stack is alive; an agent
+//! that crashed mid-session can leave its connection open indefinitely. When
+//! [`Builder::idle_timeout`](crate::Builder::idle_timeout) is set, a connection
+//! that sends no text or binary message within the window is closed with
+//! [`CloseReason::IdleTimeout`](crate::close_reason::CloseReason::IdleTimeout).
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Tracks the deadline for the next inbound message on one connection.
+#[derive(Debug)]
+pub struct IdleTimer {
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+}
+
+impl IdleTimer {
+    /// Starts a timer; `None` disables it.
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// Restarts the window after a message was received.
+    pub fn reset(&mut self) {
+        if let Some(timeout) = self.timeout {
+            self.deadline = Some(Instant::now() + timeout);
+        }
+    }
+
+    /// Completes once the connection has been idle for the whole window.
+    /// Never completes when the timer is disabled.
+    pub async fn expired(&self) {
+        match self.deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(40);
+
+    #[tokio::test]
+    async fn test_idle_connection_times_out_after_configured_duration() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<&str>();
+        let mut timer = IdleTimer::new(Some(TIMEOUT));
+        let started = Instant::now();
+
+        // Two messages keep the connection alive past one window, then it idles
+        tokio::spawn(async move {
+            for _ in 0..2 {
+                tokio::time::sleep(TIMEOUT / 2).await;
+                let _ = tx.send("ping");
+            }
+            tokio::time::sleep(TIMEOUT * 10).await;
+        });
+
+        let mut received = 0;
+        loop {
+            tokio::select! {
+                Some(_) = rx.recv() => {
+                    received += 1;
+                    timer.reset();
+                }
+                _ = timer.expired() => break,
+            }
+        }
+
+        assert_eq!(received, 2);
+        assert!(started.elapsed() >= TIMEOUT + TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn test_disabled_timer_never_expires() {
+        let timer = IdleTimer::new(None);
+
+        assert!(tokio::time::timeout(TIMEOUT, timer.expired())
+            .await
+            .is_err());
+    }
+}
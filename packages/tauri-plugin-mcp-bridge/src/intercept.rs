@@ -0,0 +1,54 @@
+//! Operator hook that sees every request before it is dispatched.
+//!
+//! An app registers an interceptor with
+//! [`Builder::request_interceptor`](crate::Builder::request_interceptor) to
+//! enforce its own policy on top of authentication, for example forbidding
+//! scripts that touch `localStorage`, or redacting secrets from arguments
+//! before they are logged and run. The interceptor receives each authorized
+//! request and may rewrite it in place; its [`InterceptDecision`] then either
+//! lets the request through or refuses it with a reason the client sees.
+
+use crate::pipeline::WsRequest;
+use std::fmt;
+use std::sync::Arc;
+
+/// What an interceptor decided about a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterceptDecision {
+    /// Dispatch the request, including any changes made to it
+    Allow,
+    /// Refuse the request; the reason is logged and sent in the error
+    /// response
+    Deny(String),
+}
+
+/// The function deciding on requests, shared by every connection.
+#[derive(Clone)]
+pub struct RequestInterceptor(Arc<dyn Fn(&mut WsRequest) -> InterceptDecision + Send + Sync>);
+
+impl RequestInterceptor {
+    /// Wraps `interceptor` for use by the server.
+    pub fn new(
+        interceptor: impl Fn(&mut WsRequest) -> InterceptDecision + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(interceptor))
+    }
+
+    /// Runs the interceptor on `request`.
+    pub fn decide(&self, request: &mut WsRequest) -> InterceptDecision {
+        (self.0)(request)
+    }
+}
+
+impl fmt::Debug for RequestInterceptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RequestInterceptor(..)")
+    }
+}
+
+// Closures can't be compared, so a config equals its clones only
+impl PartialEq for RequestInterceptor {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
@@ -4,24 +4,72 @@
 //! enabling deep inspection and interaction with Tauri's IPC layer, backend state, and
 //! window management.
 
+pub mod abort;
+pub mod auth;
+pub mod batch;
+pub mod bridge_handle;
+pub mod capabilities;
+pub mod capture_store;
+pub mod chunked;
+pub mod clients;
+pub mod close_reason;
+pub mod command_args;
 pub mod commands;
 pub mod config;
+pub mod console_history;
 pub mod discovery;
+mod dispatch;
+pub mod element_handles;
+pub mod event_listeners;
+pub mod event_schema;
+pub mod failed_requests;
+pub mod http_fallback;
+pub mod intercept;
+mod idle_timeout;
 mod logging;
+pub mod macros;
+pub mod mcp;
+pub mod metrics;
+pub mod mocks;
 pub mod monitor;
+pub mod navigation;
+#[cfg(test)]
+mod node_eval;
+pub mod outbound;
+pub mod pipeline;
+pub mod response_format;
+pub mod screencast;
 pub mod screenshot;
 pub mod script_registry;
+pub mod subscriptions;
+mod tcp_options;
+pub mod watch;
+#[cfg(windows)]
+mod webview2;
 pub mod websocket;
+pub mod window_queue;
 
-pub use config::{Builder, Config};
+pub use bridge_handle::{BridgeStatus, McpBridgeHandle};
+pub use config::{Builder, Config, ConfigError};
 
-use commands::ScriptExecutor;
-use discovery::{find_available_port, use_explicit_port_or_fail};
+use abort::AbortSignal;
+use bridge_handle::{BridgeServer, PortChoice, ACTIVATE_EVENT, DEACTIVATE_EVENT};
+use commands::{ScriptExecutor, UserAgentOverrides};
+use console_history::ConsoleHistory;
+use discovery::{claim_available_port, find_available_port, use_explicit_port_or_fail, PortLock};
+use event_listeners::EventListenerRegistry;
+use failed_requests::FailedRequests;
 use logging::{mcp_log_error, mcp_log_info};
 use monitor::IPCMonitor;
-use script_registry::create_shared_registry;
+use navigation::NavigationTracker;
+use script_registry::{create_shared_registry_with_limits, SharedScriptRegistry};
 use std::sync::{Arc, Mutex};
-use tauri::{plugin::Builder as PluginBuilder, plugin::TauriPlugin, Manager, Runtime};
+use tauri::{
+    plugin::Builder as PluginBuilder, plugin::TauriPlugin, webview::PageLoadEvent, Listener,
+    Manager, RunEvent, Runtime,
+};
+use websocket::ShutdownHandle;
+use window_queue::WindowQueue;
 
 /// Initializes the MCP Bridge plugin.
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
@@ -30,39 +78,214 @@ pub fn init<R: Runtime>() -> TauriPlugin<R> {
 
 /// Initializes the MCP Bridge plugin with custom configuration.
 pub fn init_with_config<R: Runtime>(config: Config) -> TauriPlugin<R> {
+    init_with_handle(config).0
+}
+
+/// Initializes the MCP Bridge plugin and returns a handle to its server.
+///
+/// The handle broadcasts frames to connected clients, reports the port and
+/// status of the server, and stops it. It's the same [`McpBridgeHandle`] the
+/// plugin manages as Tauri state, available before the app is built.
+///
+/// The port is chosen and bound during the plugin's setup, which runs while
+/// the app is built and before the app's own setup hook. Until then
+/// [`McpBridgeHandle::port`] returns `None`; [`McpBridgeHandle::ready`]
+/// waits for it.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let (plugin, bridge) = tauri_plugin_mcp_bridge::init_with_handle(Config::default());
+///
+/// tauri::Builder::default()
+///     .plugin(plugin)
+///     .setup(move |_app| {
+///         println!("MCP Bridge port: {:?}", bridge.port());
+///         Ok(())
+///     })
+/// ```
+pub fn init_with_handle<R: Runtime>(config: Config) -> (TauriPlugin<R>, McpBridgeHandle) {
+    let handle = McpBridgeHandle::pending();
+    let bridge = handle.clone();
+
+    if let Some(path) = &config.log_file {
+        logging::set_log_file(path.clone(), config.log_file_max_bytes);
+    }
+
     let bind_address = config.bind_address.clone();
     let explicit_port = config.port;
+    let (base_port, port_scan_attempts) = (config.base_port, config.port_scan_attempts);
+    let port_lock_dir = config.port_lock_dir.clone();
+    let lazy_start = config.lazy_start;
+    let metrics_interval = config.metrics_interval;
+    let tcp_options = tcp_options::TcpOptions::from_config(&config);
+    let init_script = config.init_script();
 
-    PluginBuilder::<R>::new("mcp-bridge")
+    let plugin = PluginBuilder::<R>::new("mcp-bridge")
         .invoke_handler(tauri::generate_handler![
+            commands::abort_all::abort_all,
             commands::execute_command::execute_command,
             commands::window_info::get_window_info,
+            commands::window_flags::set_window_flags,
             commands::backend_state::get_backend_state,
+            commands::app_paths::get_app_paths,
+            commands::system_status::get_system_status,
+            commands::bridge_config::get_config,
+            commands::bridge_logs::get_bridge_logs,
+            commands::clients::get_clients,
             commands::emit_event::emit_event,
+            commands::event_listeners::list_event_listeners,
+            commands::event_listeners::track_event_listener,
             commands::ipc_monitor::start_ipc_monitor,
             commands::ipc_monitor::stop_ipc_monitor,
+            commands::ipc_monitor::pause_ipc_monitor,
+            commands::ipc_monitor::resume_ipc_monitor,
             commands::ipc_monitor::get_ipc_events,
+            commands::ipc_monitor::report_ipc_event,
             commands::execute_js::execute_js,
+            commands::execute_js_all::execute_js_all,
+            commands::execute_js_file::execute_js_file,
+            commands::execute_js_if::execute_js_if,
+            commands::injected_globals::get_injected_globals_count,
+            commands::meta_tags::get_meta_tags,
+            commands::network_idle::wait_for_network_idle,
+            commands::mutations::observe_mutations,
             commands::script_executor::script_result,
             commands::screenshot::capture_native_screenshot,
+            commands::screenshot::capture_window_with_chrome,
+            commands::screenshot::compare_screenshot,
             commands::list_windows::list_windows,
+            commands::list_windows::get_active_window,
+            commands::list_windows::set_active_window,
+            commands::reset_window::reset_window,
+            commands::element_box::get_element_box,
+            commands::element_from_point::element_from_point,
+            commands::computed_style::get_computed_style,
+            commands::console_history::get_console_history,
+            commands::console_history::report_console_entry,
+            commands::failed_requests::get_failed_requests,
+            commands::failed_requests::report_failed_request,
+            commands::dom_event::dispatch_dom_event,
+            commands::drag_and_drop::drag_and_drop,
+            commands::input_value::get_input_value,
+            commands::input_value::set_input_value,
+            commands::focus::focus_element,
+            commands::focus::get_active_element,
+            commands::form::fill_form,
+            commands::form::read_form,
+            commands::interactable::is_interactable,
+            commands::layout::get_layout,
+            commands::layout::set_layout,
+            commands::current_url::get_current_url,
+            commands::ready_state::get_ready_state,
+            commands::ready_state::wait_for_ready,
+            commands::resources::list_resources,
+            commands::resources::read_resource,
             commands::script_injection::request_script_injection,
+            commands::mocks::request_mocks,
+            commands::readable_text::get_readable_text,
+            commands::selection::get_selection,
+            commands::selection::set_selection,
+            commands::user_agent::get_user_agent,
+            commands::user_agent::set_user_agent,
+            commands::media::emulate_media,
+            commands::theme::get_theme,
+            commands::theme::set_theme,
+            commands::clear_cache::clear_cache,
+            commands::scroll_info::get_scroll_info,
+            commands::assert_element::assert_element,
+            commands::virtual_time::freeze_time,
+            commands::virtual_time::step_time,
+            commands::virtual_time::unfreeze_time,
+            commands::web_vitals::get_web_vitals,
+            commands::macros::list_macros,
+            commands::macros::run_macro,
+            commands::wait_for_event::wait_for_event,
         ])
-        .js_init_script(include_str!("bridge.js").to_string())
+        .js_init_script(init_script)
+        .on_page_load(|webview, payload| {
+            // Broadcast URL transitions once the new page has loaded
+            if payload.event() == PageLoadEvent::Finished {
+                if let Some(tracker) = webview.try_state::<NavigationTracker>() {
+                    tracker.page_loaded(webview.label(), payload.url().as_str());
+                }
+
+                // Inject registered scripts from Rust as well, so windows
+                // created after registration get them even when bridge.js
+                // can't request them (no `window.__TAURI__` global).
+                // Restricted windows are left alone.
+                let allowed = commands::check_window_access(webview, webview.label()).is_ok();
+                let registry = webview.try_state::<SharedScriptRegistry>().filter(|_| allowed);
+                if let Some(registry) = registry {
+                    let script = registry
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .page_load_script();
+                    if let Some(script) = script {
+                        if let Err(e) = webview.eval(&script) {
+                            mcp_log_error(
+                                "SCRIPTS",
+                                &format!(
+                                    "Failed to inject scripts into '{}': {e}",
+                                    webview.label()
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        })
+        .on_event(|app, event| {
+            // Tell connected clients the app is going away
+            if let RunEvent::Exit = event {
+                if let Some(handle) = app.try_state::<ShutdownHandle>() {
+                    handle.shutdown();
+                }
+                if let Some(handle) = app.try_state::<McpBridgeHandle>() {
+                    handle.shutdown();
+                }
+                // Let the next instance have the port
+                if let Some(lock) = app.try_state::<PortLock>() {
+                    lock.release();
+                }
+            }
+        })
         .setup(move |app, _api| {
+            // Per-window webview locks (no-ops unless enabled)
+            app.manage(WindowQueue::new(config.serialize_per_window));
+
+            // Initialize script registry for persistent script injection
+            app.manage(create_shared_registry_with_limits(
+                config.max_scripts,
+                config.max_script_bytes,
+            ));
+
+            // Make configuration available to command handlers
+            app.manage(config);
+
             // Initialize script executor state
             app.manage(ScriptExecutor::new());
 
+            // Cancellation for in-flight operations, fired by `abort_all`
+            app.manage(AbortSignal::new());
+
             // Initialize IPC monitor state
             let monitor = Arc::new(Mutex::new(IPCMonitor::new()));
             app.manage(monitor.clone());
 
-            // Initialize script registry for persistent script injection
-            let script_registry = create_shared_registry();
-            app.manage(script_registry);
+            // Frontend event listeners, reported by bridge.js
+            app.manage(EventListenerRegistry::new());
+
+            // User agent overrides, recorded by `set_user_agent`
+            app.manage(UserAgentOverrides::new());
 
-            // Determine port: use explicit port (strict mode) or find available port
+            // Stubbed fetch/XHR responses, applied by bridge.js
+            app.manage(mocks::MockRegistry::new());
+
+            // Determine port: use explicit port (strict mode) or find available port.
+            // With lazy start it is chosen on each activation instead.
             let port = match explicit_port {
+                _ if lazy_start => 0,
                 Some(p) => {
                     mcp_log_info(
                         "PLUGIN",
@@ -71,10 +294,30 @@ pub fn init_with_config<R: Runtime>(config: Config) -> TauriPlugin<R> {
                     use_explicit_port_or_fail(&bind_address, p)
                 }
                 None => {
-                    let p = find_available_port(&bind_address);
+                    // With a lock directory the port stays claimed until exit
+                    let p = match &port_lock_dir {
+                        Some(dir) => claim_available_port(
+                            &bind_address,
+                            base_port,
+                            port_scan_attempts,
+                            dir,
+                        )
+                        .map(|(p, lock)| {
+                            app.manage(lock);
+                            p
+                        }),
+                        None => find_available_port(&bind_address, base_port, port_scan_attempts),
+                    }
+                    .map_err(|e| {
+                        mcp_log_error("PLUGIN", &e);
+                        e
+                    })?;
                     mcp_log_info(
                         "PLUGIN",
-                        &format!("Auto-selected port {} from range 9223-9322", p),
+                        &format!(
+                            "Auto-selected port {} from range starting at {}",
+                            p, base_port
+                        ),
                     );
                     p
                 }
@@ -91,14 +334,88 @@ pub fn init_with_config<R: Runtime>(config: Config) -> TauriPlugin<R> {
 
             // Start WebSocket server in background
             let app_handle = app.clone();
-            let (ws_server, _event_rx) =
+            let (mut ws_server, _event_rx) =
                 websocket::WebSocketServer::new(port, &bind_address, app_handle);
+            if let Some(interval) = metrics_interval {
+                ws_server.set_metrics_interval(interval);
+            }
+            ws_server.set_tcp_options(tcp_options);
 
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = ws_server.start().await {
-                    mcp_log_error("PLUGIN", &format!("WebSocket server error: {e}"));
-                }
-            });
+            // Track per-window URLs so page loads can be broadcast as navigations
+            app.manage(NavigationTracker::new(ws_server.event_sender()));
+
+            // Keep each window's console output and stream it to clients
+            app.manage(ConsoleHistory::new(ws_server.event_sender()));
+
+            // Keep each window's failed loads and stream them to clients
+            app.manage(FailedRequests::new(ws_server.event_sender()));
+
+            // Push failed IPC calls to clients as soon as they are captured
+            monitor
+                .lock()
+                .unwrap()
+                .set_error_sink(ws_server.event_sender());
+            app.manage(ws_server.shutdown_handle());
+            app.manage(ws_server.clients());
+            app.manage(ws_server.captures());
+            app.manage(screenshot::FrameCache::new());
+
+            // Eagerly started servers keep the port picked above
+            let ports = match explicit_port {
+                _ if !lazy_start => PortChoice::Explicit(port),
+                Some(p) => PortChoice::Explicit(p),
+                None => PortChoice::Scan {
+                    base_port,
+                    attempts: port_scan_attempts,
+                },
+            };
+            let events = ws_server.event_sender();
+            let server = Arc::new(ws_server);
+            let server = BridgeServer::new(
+                &bind_address,
+                ports,
+                events,
+                move |listener, shutdown_tx| {
+                    let server = server.clone();
+                    async move {
+                        let result = match tokio::net::TcpListener::from_std(listener) {
+                            Ok(listener) => server.serve(listener, shutdown_tx).await,
+                            Err(e) => Err(e.into()),
+                        };
+                        if let Err(e) = result {
+                            mcp_log_error("PLUGIN", &format!("WebSocket server error: {e}"));
+                        }
+                    }
+                },
+            )
+            .with_port_lock_dir(port_lock_dir.clone());
+            if let Err(e) = handle.attach(server, !lazy_start) {
+                mcp_log_error("PLUGIN", &format!("WebSocket server error: {e}"));
+            }
+            app.manage(handle.clone());
+
+            if lazy_start {
+                let activator = handle.clone();
+                app.listen_any(ACTIVATE_EVENT, move |_| {
+                    if let Err(e) = activator.activate() {
+                        mcp_log_error("PLUGIN", &e);
+                    }
+                });
+                let deactivator = handle.clone();
+                app.listen_any(DEACTIVATE_EVENT, move |_| {
+                    let handle = deactivator.clone();
+                    tauri::async_runtime::spawn(async move { handle.deactivate().await });
+                });
+
+                mcp_log_info(
+                    "PLUGIN",
+                    &format!(
+                        "MCP Bridge plugin initialized for '{}' ({}), waiting for activation",
+                        app_name, identifier
+                    ),
+                );
+                return Ok(());
+            }
 
             mcp_log_info(
                 "PLUGIN",
@@ -110,5 +427,7 @@ pub fn init_with_config<R: Runtime>(config: Config) -> TauriPlugin<R> {
 
             Ok(())
         })
-        .build()
+        .build();
+
+    (plugin, bridge)
 }
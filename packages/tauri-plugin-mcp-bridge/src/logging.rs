@@ -1,7 +1,277 @@
+//! Internal bridge logging.
+//!
+//! Log lines are written to stdout/stderr and also kept in a bounded
+//! in-memory ring so remote clients can pull recent diagnostics with
+//! `get_bridge_logs`.
+//!
+//! With [`Builder::log_file`](crate::Builder::log_file) set, each line is
+//! also appended to a file as JSON, for field debugging where stdout is lost:
+//!
+//! ```json
+//! {"level":"info","scope":"PLUGIN","message":"Auto-selected port 9223","timestamp":1700000000000}
+//! ```
+
+use crate::monitor::current_timestamp;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Maximum number of log lines kept in memory.
+const LOG_RING_CAPACITY: usize = 500;
+
+/// A single internal log line.
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    /// `"info"` or `"error"`
+    pub level: &'static str,
+    /// Subsystem that emitted the line (e.g. `"PLUGIN"`)
+    pub scope: String,
+    /// Log message
+    pub message: String,
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+}
+
+/// Bounded buffer that drops the oldest entry when full.
+struct LogRing {
+    entries: VecDeque<LogEntry>,
+    capacity: usize,
+}
+
+impl LogRing {
+    fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    fn recent(&self, limit: Option<usize>) -> Vec<LogEntry> {
+        let len = self.entries.len();
+        let limit = limit.unwrap_or(len).min(len);
+        self.entries.iter().skip(len - limit).cloned().collect()
+    }
+}
+
+/// Log file that is rotated once it would grow past `max_bytes`.
+///
+/// On rotation the current file is renamed to `<path>.1`, replacing any
+/// previous backup, and a new file is started.
+struct LogFile {
+    path: PathBuf,
+    max_bytes: Option<u64>,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf, max_bytes: Option<u64>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            file,
+            size,
+        })
+    }
+
+    fn write(&mut self, entry: &LogEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry).map_err(io::Error::from)?;
+        line.push(b'\n');
+
+        let len = line.len() as u64;
+        // A line larger than the limit still goes into a file of its own
+        if self
+            .max_bytes
+            .is_some_and(|max| self.size > 0 && self.size + len > max)
+        {
+            self.rotate()?;
+        }
+
+        self.file.write_all(&line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        std::fs::rename(&self.path, backup_path(&self.path))?;
+        *self = Self::open(self.path.clone(), self.max_bytes)?;
+        Ok(())
+    }
+}
+
+/// Path the log file is moved to on rotation.
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".1");
+    PathBuf::from(backup)
+}
+
+fn log_ring() -> &'static Mutex<LogRing> {
+    static RING: OnceLock<Mutex<LogRing>> = OnceLock::new();
+    RING.get_or_init(|| Mutex::new(LogRing::new(LOG_RING_CAPACITY)))
+}
+
+fn log_file() -> &'static Mutex<Option<LogFile>> {
+    static FILE: OnceLock<Mutex<Option<LogFile>>> = OnceLock::new();
+    FILE.get_or_init(|| Mutex::new(None))
+}
+
+/// Starts mirroring log lines to a file.
+///
+/// # Arguments
+///
+/// * `path` - File to append to; created if missing
+/// * `max_bytes` - Rotate the file once it would grow past this size
+///
+/// If the file can't be opened, logging continues on stdout only.
+pub(crate) fn set_log_file(path: PathBuf, max_bytes: Option<u64>) {
+    let file = LogFile::open(path.clone(), max_bytes)
+        .map_err(|e| warn_file_disabled(&path, &e))
+        .ok();
+    *log_file().lock().unwrap_or_else(|e| e.into_inner()) = file;
+}
+
+/// Reports that the log file was given up on. Goes straight to stderr, as
+/// logging it would try the file again.
+fn warn_file_disabled(path: &Path, error: &io::Error) {
+    eprintln!(
+        "[MCP][LOG][ERROR] Failed to write log file {}: {error}; logging to stdout only",
+        path.display()
+    );
+}
+
+fn record(level: &'static str, scope: &str, msg: &str) {
+    let entry = LogEntry {
+        level,
+        scope: scope.to_string(),
+        message: msg.to_string(),
+        timestamp: current_timestamp(),
+    };
+
+    {
+        let mut file = log_file().lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(log) = file.as_mut() {
+            // Give up on the file after the first failure so the warning
+            // is printed once
+            if let Err(e) = log.write(&entry) {
+                warn_file_disabled(&log.path, &e);
+                *file = None;
+            }
+        }
+    }
+
+    log_ring()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(entry);
+}
+
 pub fn mcp_log_info(scope: &str, msg: &str) {
     println!("[MCP][{scope}][INFO] {msg}");
+    record("info", scope, msg);
 }
 
 pub fn mcp_log_error(scope: &str, msg: &str) {
     eprintln!("[MCP][{scope}][ERROR] {msg}");
+    record("error", scope, msg);
+}
+
+/// Returns up to `limit` of the most recent log lines, oldest first.
+pub fn recent_logs(limit: Option<usize>) -> Vec<LogEntry> {
+    log_ring()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .recent(limit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_logs_are_retrievable() {
+        mcp_log_info("LOG_TEST", "first line");
+        mcp_log_error("LOG_TEST", "second line");
+
+        let ours: Vec<LogEntry> = recent_logs(None)
+            .into_iter()
+            .filter(|entry| entry.scope == "LOG_TEST")
+            .collect();
+
+        assert_eq!(ours.len(), 2);
+        assert_eq!(ours[0].level, "info");
+        assert_eq!(ours[0].message, "first line");
+        assert_eq!(ours[1].level, "error");
+        assert_eq!(ours[1].message, "second line");
+        assert!(ours[0].timestamp > 0);
+    }
+
+    #[test]
+    fn test_ring_is_bounded_and_limit_applies() {
+        let mut ring = LogRing::new(3);
+        for i in 0..5 {
+            ring.push(LogEntry {
+                level: "info",
+                scope: "TEST".to_string(),
+                message: format!("line {i}"),
+                timestamp: 0,
+            });
+        }
+
+        let all = ring.recent(None);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0].message, "line 2");
+        assert_eq!(all[2].message, "line 4");
+
+        let last = ring.recent(Some(1));
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].message, "line 4");
+        assert!(ring.recent(Some(0)).is_empty());
+        assert_eq!(ring.recent(Some(10)).len(), 3);
+    }
+
+    #[test]
+    fn test_log_file_is_written_and_rotated() {
+        let dir = std::env::temp_dir().join(format!("mcp-logs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bridge.log");
+
+        let entry = |i: usize| LogEntry {
+            level: "info",
+            scope: "TEST".to_string(),
+            message: format!("line {i}"),
+            timestamp: 1,
+        };
+        let line_len = serde_json::to_vec(&entry(0)).unwrap().len() as u64 + 1;
+
+        // Room for three lines per file
+        let mut log = LogFile::open(path.clone(), Some(line_len * 3)).unwrap();
+        for i in 0..3 {
+            log.write(&entry(i)).unwrap();
+        }
+        assert!(!backup_path(&path).exists());
+
+        log.write(&entry(3)).unwrap();
+        let current = std::fs::read_to_string(&path).unwrap();
+        let backup = std::fs::read_to_string(backup_path(&path)).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(backup.lines().count(), 3);
+        assert!(backup
+            .starts_with(r#"{"level":"info","scope":"TEST","message":"line 0","timestamp":1}"#));
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains(r#""message":"line 3""#));
+    }
 }
@@ -0,0 +1,326 @@
+//! Named automation macros registered at build time.
+//!
+//! Instead of handing agents raw `execute_js`, operators can register vetted
+//! sequences of typed [`Step`]s with
+//! [`Builder::register_macro`](crate::Builder::register_macro) (or under
+//! `[macros]` in a config file). Agents list them with `list_macros` and run
+//! them with `run_macro { name, params?, windowLabel? }`; the steps run on
+//! the server, one after the other, against one window.
+//!
+//! String fields of a step may contain `{{param}}` placeholders, filled from
+//! `run_macro`'s `params` before the first step runs, as in these
+//! `invoke_tauri` arguments:
+//!
+//! ```json
+//! { "command": "plugin:mcp-bridge|run_macro", "args": { "name": "open-item", "params": { "id": 42 } } }
+//! ```
+//!
+//! Running stops at the first failing step. The report lists the result of
+//! every step that ran:
+//!
+//! ```json
+//! { "name": "open-item", "completed": true, "steps": [
+//!   { "index": 0, "type": "click", "success": true, "data": { "clicked": "#item-42" }, "durationMs": 4 },
+//!   { "index": 1, "type": "wait", "success": true, "data": { "waitedMs": 310 }, "durationMs": 310 }
+//! ] }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::time::Instant;
+
+/// One step of a macro.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Step {
+    /// Scrolls the element matching `selector` into view, or the page to
+    /// `x`, `y` when there is no selector
+    Scroll {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default)]
+        x: f64,
+        #[serde(default)]
+        y: f64,
+    },
+    /// Clicks the element matching `selector`
+    Click { selector: String },
+    /// Waits `ms` milliseconds, then until an element matches `selector`,
+    /// for at most `timeoutMs` (default 5000). Either may be omitted.
+    Wait {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        ms: Option<u64>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        selector: Option<String>,
+        #[serde(default, rename = "timeoutMs", skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+    },
+    /// Captures the viewport; `format` is `"png"` (default) or `"jpeg"`
+    Screenshot {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        quality: Option<u8>,
+    },
+}
+
+impl Step {
+    /// The step's `type`, as reported in results.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Scroll { .. } => "scroll",
+            Self::Click { .. } => "click",
+            Self::Wait { .. } => "wait",
+            Self::Screenshot { .. } => "screenshot",
+        }
+    }
+
+    /// Returns the step with its placeholders filled from `params`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Step)` - The step, ready to run
+    /// * `Err(String)` - If a placeholder names a missing parameter, or a
+    ///   `wait` step has neither `ms` nor `selector`
+    pub fn with_params(&self, params: &Map<String, Value>) -> Result<Self, String> {
+        let fill_opt = |text: &Option<String>| text.as_deref().map(|t| fill(t, params)).transpose();
+        Ok(match self {
+            Self::Scroll { selector, x, y } => Self::Scroll {
+                selector: fill_opt(selector)?,
+                x: *x,
+                y: *y,
+            },
+            Self::Click { selector } => Self::Click {
+                selector: fill(selector, params)?,
+            },
+            Self::Wait {
+                ms: None,
+                selector: None,
+                ..
+            } => return Err("A wait step needs ms or selector".to_string()),
+            Self::Wait {
+                ms,
+                selector,
+                timeout_ms,
+            } => Self::Wait {
+                ms: *ms,
+                selector: fill_opt(selector)?,
+                timeout_ms: *timeout_ms,
+            },
+            Self::Screenshot { format, quality } => Self::Screenshot {
+                format: fill_opt(format)?,
+                quality: *quality,
+            },
+        })
+    }
+}
+
+/// Replaces the `{{name}}` placeholders in `text` with `params`. Strings
+/// are inserted as is, other values as JSON.
+fn fill(text: &str, params: &Map<String, Value>) -> Result<String, String> {
+    let mut filled = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        filled.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("Unclosed placeholder in \"{text}\""))?;
+        let name = after[..end].trim();
+        match params.get(name) {
+            Some(Value::String(value)) => filled.push_str(value),
+            Some(value) => filled.push_str(&value.to_string()),
+            None => return Err(format!("Missing macro parameter: {name}")),
+        }
+        rest = &after[end + 2..];
+    }
+    filled.push_str(rest);
+    Ok(filled)
+}
+
+/// The outcome of one step.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepResult {
+    pub index: usize,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Result of `run_macro`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MacroReport {
+    pub name: String,
+    /// Whether every step succeeded
+    pub completed: bool,
+    /// Results of the steps that ran, in order
+    pub steps: Vec<StepResult>,
+}
+
+/// Runs a macro's steps in order, stopping at the first failure.
+///
+/// # Arguments
+///
+/// * `name` - The macro's name, for the report
+/// * `steps` - The registered steps
+/// * `params` - Values for the steps' placeholders
+/// * `run` - Runs one step and returns its data
+///
+/// # Returns
+///
+/// * `Ok(MacroReport)` - Once every step ran or one failed
+/// * `Err(String)` - If the placeholders can't be filled; no step runs then
+pub async fn run_steps<F, Fut>(
+    name: &str,
+    steps: &[Step],
+    params: &Map<String, Value>,
+    mut run: F,
+) -> Result<MacroReport, String>
+where
+    F: FnMut(Step) -> Fut,
+    Fut: Future<Output = Result<Value, String>>,
+{
+    let steps = steps
+        .iter()
+        .map(|step| step.with_params(params))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = Vec::with_capacity(steps.len());
+    for (index, step) in steps.into_iter().enumerate() {
+        let kind = step.kind();
+        let started = Instant::now();
+        let outcome = run(step).await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        let success = outcome.is_ok();
+        let (data, error) = match outcome {
+            Ok(data) => (Some(data), None),
+            Err(e) => (None, Some(e)),
+        };
+        results.push(StepResult {
+            index,
+            kind,
+            success,
+            data,
+            error,
+            duration_ms,
+        });
+        if !success {
+            break;
+        }
+    }
+
+    Ok(MacroReport {
+        name: name.to_string(),
+        completed: results.iter().all(|result| result.success),
+        steps: results,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Builder;
+    use serde_json::json;
+
+    fn params(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_two_step_macro_runs_end_to_end() {
+        let config = Builder::new()
+            .register_macro(
+                "open-item",
+                vec![
+                    Step::Click {
+                        selector: "#item-{{id}}".to_string(),
+                    },
+                    Step::Wait {
+                        ms: None,
+                        selector: Some(".detail[data-id=\"{{ id }}\"]".to_string()),
+                        timeout_ms: Some(2000),
+                    },
+                ],
+            )
+            .config;
+        let steps = &config.macros["open-item"];
+
+        let mut ran = Vec::new();
+        let report = run_steps("open-item", steps, &params(json!({ "id": 42 })), |step| {
+            ran.push(step.clone());
+            async move {
+                match step {
+                    Step::Click { selector } => Ok(json!({ "clicked": selector })),
+                    Step::Wait { .. } => Ok(json!({ "waitedMs": 0 })),
+                    other => Err(format!("unexpected {}", other.kind())),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert!(report.completed);
+        assert_eq!(report.steps.len(), 2);
+        assert_eq!(report.steps[0].data, Some(json!({ "clicked": "#item-42" })));
+        assert_eq!(
+            ran[1],
+            Step::Wait {
+                ms: None,
+                selector: Some(".detail[data-id=\"42\"]".to_string()),
+                timeout_ms: Some(2000),
+            }
+        );
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["steps"][1]["type"], "wait");
+        assert!(value["steps"][0].get("error").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_failures_stop_the_macro() {
+        let steps: Vec<Step> = serde_json::from_value(json!([
+            { "type": "click", "selector": "#missing" },
+            { "type": "screenshot" }
+        ]))
+        .unwrap();
+
+        let report = run_steps("broken", &steps, &Map::new(), |_| async {
+            Err::<Value, _>("No element matches selector: #missing".to_string())
+        })
+        .await
+        .unwrap();
+        assert!(!report.completed);
+        assert_eq!(report.steps.len(), 1);
+        assert_eq!(
+            report.steps[0].error.as_deref(),
+            Some("No element matches selector: #missing")
+        );
+
+        // Missing parameters are reported before anything runs
+        let err = run_steps(
+            "x",
+            &[Step::Click {
+                selector: "#{{id}}".to_string(),
+            }],
+            &Map::new(),
+            |_| async { Ok(Value::Null) },
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "Missing macro parameter: id");
+        assert!(Step::Wait {
+            ms: None,
+            selector: None,
+            timeout_ms: None
+        }
+        .with_params(&Map::new())
+        .is_err());
+    }
+}
@@ -0,0 +1,877 @@
+//! A minimal MCP tool protocol adapter.
+//!
+//! With [`Builder::mcp_mode`](crate::Builder::mcp_mode) on, a connection also
+//! accepts JSON-RPC 2.0 messages and answers the MCP methods a tool client
+//! needs, so a thin proxy can forward an LLM's MCP traffic to the bridge
+//! unchanged:
+//!
+//! | Method       | Answer                                                       |
+//! |--------------|--------------------------------------------------------------|
+//! | `initialize` | Protocol version, `tools` capability and server info         |
+//! | `ping`       | An empty result                                              |
+//! | `tools/list` | The bridge commands in [`TOOLS`] with their input schemas    |
+//! | `tools/call` | Runs the command and returns its result as `content`         |
+//!
+//! A `tools/call` goes through the usual [`pipeline`](crate::pipeline):
+//! authorization, dispatch and logging apply as for a bridge request. A
+//! command error becomes a result with `isError: true`, as MCP expects for
+//! tool failures; only malformed calls get a JSON-RPC error. Notifications,
+//! such as `notifications/initialized`, get no reply.
+//!
+//! Frames without `"jsonrpc": "2.0"` are bridge requests, so MCP and bridge
+//! clients can share a port. Bridge clients authenticate with the
+//! `authenticate` command before calling tools.
+
+use crate::command_args::take_window_match;
+use crate::pipeline::{WsRequest, WsResponse};
+use serde_json::{json, Map, Value};
+
+/// MCP protocol version answered when the client asks for one we don't know.
+pub const MCP_PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// MCP protocol versions a client may pick in `initialize`.
+const SUPPORTED_VERSIONS: &[&str] = &["2024-11-05", "2025-03-26", MCP_PROTOCOL_VERSION];
+
+/// JSON-RPC error code for an unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for invalid method parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// How a tool call reaches its command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolRoute {
+    /// A WebSocket command, as in `{ "command": "execute_js" }`
+    Bridge,
+    /// A plugin command, run through `invoke_tauri` as
+    /// `plugin:mcp-bridge|<name>`
+    Plugin,
+}
+
+/// JSON Schema type of a tool parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    StringArray,
+    /// One of the listed strings
+    Enum(&'static [&'static str]),
+    /// Any JSON value
+    Any,
+}
+
+/// A tool parameter, an argument of the command.
+#[derive(Debug, Clone, Copy)]
+pub struct ToolParam {
+    /// The camelCase argument name
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub required: bool,
+    pub description: &'static str,
+}
+
+/// A bridge command exposed as an MCP tool.
+#[derive(Debug, Clone, Copy)]
+pub struct Tool {
+    /// Tool name, which is also the command name
+    pub name: &'static str,
+    pub description: &'static str,
+    pub route: ToolRoute,
+    /// Whether the command targets a window, adding a `windowLabel`
+    /// parameter
+    pub window: bool,
+    pub params: &'static [ToolParam],
+}
+
+const fn required(name: &'static str, kind: ParamKind, description: &'static str) -> ToolParam {
+    ToolParam {
+        name,
+        kind,
+        required: true,
+        description,
+    }
+}
+
+const fn optional(name: &'static str, kind: ParamKind, description: &'static str) -> ToolParam {
+    ToolParam {
+        name,
+        kind,
+        required: false,
+        description,
+    }
+}
+
+const IMAGE_FORMAT: ParamKind = ParamKind::Enum(&["png", "jpeg"]);
+
+/// The commands listed by `tools/list`.
+pub const TOOLS: &[Tool] = &[
+    Tool {
+        name: "execute_js",
+        description: "Run JavaScript in a webview and return its result",
+        route: ToolRoute::Bridge,
+        window: true,
+        params: &[
+            required(
+                "script",
+                ParamKind::String,
+                "JavaScript to run; `return` a value to get it back",
+            ),
+            optional(
+                "maxResultBytes",
+                ParamKind::Integer,
+                "Largest serialized result to accept",
+            ),
+            optional(
+                "world",
+                ParamKind::Enum(&["main", "isolated"]),
+                "Run in the page's JS world (default) or one isolated from its globals",
+            ),
+        ],
+    },
+    Tool {
+        name: "capture_native_screenshot",
+        description: "Capture the visible viewport of a webview as an image",
+        route: ToolRoute::Bridge,
+        window: true,
+        params: &[
+            optional("format", IMAGE_FORMAT, "Image format, png by default"),
+            optional("quality", ParamKind::Integer, "JPEG quality, 0-100"),
+            optional(
+                "background",
+                ParamKind::String,
+                "\"transparent\", \"white\" or \"#rrggbb\"",
+            ),
+            optional(
+                "scrollContainerSelector",
+                ParamKind::String,
+                "Scrollable element to capture whole instead of the viewport",
+            ),
+            optional(
+                "scrollOverlap",
+                ParamKind::Integer,
+                "CSS pixels of sticky header in the scroll container to trim",
+            ),
+        ],
+    },
+    Tool {
+        name: "capture_window_with_chrome",
+        description: "Capture the whole native window, including its titlebar and frame",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("format", IMAGE_FORMAT, "Image format, png by default"),
+            optional("quality", ParamKind::Integer, "JPEG quality, 0-100"),
+        ],
+    },
+    Tool {
+        name: "compare_screenshot",
+        description: "Capture the viewport and compare it with a baseline PNG on disk",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            required(
+                "baselinePath",
+                ParamKind::String,
+                "Path of the baseline PNG in the app's baselines directory",
+            ),
+            optional(
+                "threshold",
+                ParamKind::Number,
+                "Per-channel color tolerance, 0-1 (default 0)",
+            ),
+            optional(
+                "maxDiffRatio",
+                ParamKind::Number,
+                "Fraction of differing pixels that still matches, 0-1 (default 0)",
+            ),
+            optional(
+                "diffImage",
+                ParamKind::Boolean,
+                "Also return an image highlighting the changed pixels",
+            ),
+        ],
+    },
+    Tool {
+        name: "list_windows",
+        description: "List the app's webview windows",
+        route: ToolRoute::Bridge,
+        window: false,
+        params: &[],
+    },
+    Tool {
+        name: "get_layout",
+        description: "Snapshot the position, size and state of every window",
+        route: ToolRoute::Bridge,
+        window: false,
+        params: &[],
+    },
+    Tool {
+        name: "set_layout",
+        description: "Restore a get_layout snapshot, creating windows that were closed",
+        route: ToolRoute::Bridge,
+        window: false,
+        params: &[required(
+            "layout",
+            ParamKind::Any,
+            "Array of window layouts, as returned by get_layout",
+        )],
+    },
+    Tool {
+        name: "get_window_info",
+        description: "Get a window's size, position, title and state",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[],
+    },
+    Tool {
+        name: "get_current_url",
+        description: "Get the URL a webview is showing",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[],
+    },
+    Tool {
+        name: "get_backend_state",
+        description: "Get the app's name, version and environment",
+        route: ToolRoute::Plugin,
+        window: false,
+        params: &[],
+    },
+    Tool {
+        name: "get_system_status",
+        description: "Get the bridge's health and resource usage",
+        route: ToolRoute::Plugin,
+        window: false,
+        params: &[],
+    },
+    Tool {
+        name: "get_element_box",
+        description:
+            "Get the position and size of the element matching a CSS selector (Windows only)",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+        ],
+    },
+    Tool {
+        name: "element_from_point",
+        description: "Get the topmost element at viewport coordinates, with a selector for it",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            required(
+                "x",
+                ParamKind::Number,
+                "CSS pixels from the viewport's left edge",
+            ),
+            required(
+                "y",
+                ParamKind::Number,
+                "CSS pixels from the viewport's top edge",
+            ),
+        ],
+    },
+    Tool {
+        name: "is_interactable",
+        description: "Check whether the element matching a CSS selector can be clicked",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+        ],
+    },
+    Tool {
+        name: "get_computed_style",
+        description: "Get computed CSS properties of the element matching a selector",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            required("selector", ParamKind::String, "CSS selector"),
+            optional(
+                "properties",
+                ParamKind::StringArray,
+                "Properties to read; all when omitted",
+            ),
+        ],
+    },
+    Tool {
+        name: "get_input_value",
+        description: "Get the value of a form field",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector of the field"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+        ],
+    },
+    Tool {
+        name: "set_input_value",
+        description: "Set the value of a form field, firing input and change events",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector of the field"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+            required(
+                "value",
+                ParamKind::Any,
+                "Text, a checkbox state, or the values of a multi-select",
+            ),
+        ],
+    },
+    Tool {
+        name: "fill_form",
+        description: "Set several form fields at once, reporting each field's outcome",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[required(
+            "fields",
+            ParamKind::Any,
+            "Object mapping CSS selectors to the values set_input_value accepts",
+        )],
+    },
+    Tool {
+        name: "read_form",
+        description: "Read the values of every named field in a form",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[required(
+            "formSelector",
+            ParamKind::String,
+            "CSS selector of the form or a container of fields",
+        )],
+    },
+    Tool {
+        name: "get_active_element",
+        description: "Describe the focused element, with its value if it is a form field",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[],
+    },
+    Tool {
+        name: "focus_element",
+        description: "Move keyboard focus to the element matching a CSS selector",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+        ],
+    },
+    Tool {
+        name: "dispatch_dom_event",
+        description: "Dispatch a DOM event on the element matching a CSS selector",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional("selector", ParamKind::String, "CSS selector"),
+            optional(
+                "handleId",
+                ParamKind::String,
+                "Handle from query_element_handle, instead of selector",
+            ),
+            required(
+                "eventType",
+                ParamKind::String,
+                "Event type, such as \"click\"",
+            ),
+            optional("bubbles", ParamKind::Boolean, "Whether the event bubbles"),
+        ],
+    },
+    Tool {
+        name: "assert_element",
+        description: "Check an element's text, value, attribute or match count",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            required("selector", ParamKind::String, "CSS selector"),
+            required(
+                "property",
+                ParamKind::Enum(&["text", "value", "attribute", "count"]),
+                "What to check",
+            ),
+            optional(
+                "attributeName",
+                ParamKind::String,
+                "Attribute to read for \"attribute\"",
+            ),
+            required(
+                "expected",
+                ParamKind::Any,
+                "Expected value; a regular expression for \"matches\"",
+            ),
+            optional(
+                "operator",
+                ParamKind::Enum(&["eq", "contains", "matches"]),
+                "Comparison, eq by default",
+            ),
+        ],
+    },
+    Tool {
+        name: "get_scroll_info",
+        description: "Get the page's scroll position and document size",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[],
+    },
+    Tool {
+        name: "get_web_vitals",
+        description: "Get the page's layout shift, largest contentful paint and input delays",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[],
+    },
+    Tool {
+        name: "list_macros",
+        description: "List the macros the app registered, with their steps",
+        route: ToolRoute::Plugin,
+        window: false,
+        params: &[],
+    },
+    Tool {
+        name: "run_macro",
+        description: "Run a registered macro of scroll, click, wait and screenshot steps",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            required("name", ParamKind::String, "Macro name"),
+            optional(
+                "params",
+                ParamKind::Any,
+                "Values for the macro's {{param}} placeholders",
+            ),
+        ],
+    },
+    Tool {
+        name: "get_console_history",
+        description: "Get recent console messages of a webview",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[
+            optional(
+                "levels",
+                ParamKind::StringArray,
+                "Levels to include, such as \"error\"",
+            ),
+            optional("limit", ParamKind::Integer, "Most recent entries to return"),
+        ],
+    },
+    Tool {
+        name: "get_failed_requests",
+        description: "Get resource loads and fetch/XHR calls that failed in a webview",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[optional(
+            "since",
+            ParamKind::Integer,
+            "Only failures at or after this time, in milliseconds since the epoch",
+        )],
+    },
+    Tool {
+        name: "get_readable_text",
+        description:
+            "Get the visible text of a webview as markdown, without scripts or hidden elements",
+        route: ToolRoute::Plugin,
+        window: true,
+        params: &[optional(
+            "maxBytes",
+            ParamKind::Integer,
+            "Longest text returned, in bytes (default 65536)",
+        )],
+    },
+    Tool {
+        name: "query_element_handle",
+        description:
+            "Get a handle to the element matching a CSS selector, for reuse in later calls",
+        route: ToolRoute::Bridge,
+        window: true,
+        params: &[required("selector", ParamKind::String, "CSS selector")],
+    },
+    Tool {
+        name: "wait_for_event",
+        description: "Wait for the app to emit a Tauri event",
+        route: ToolRoute::Bridge,
+        window: false,
+        params: &[
+            required("eventName", ParamKind::String, "Event name"),
+            optional("timeoutMs", ParamKind::Integer, "How long to wait"),
+        ],
+    },
+    Tool {
+        name: "get_bridge_logs",
+        description: "Get the bridge's recent log entries",
+        route: ToolRoute::Bridge,
+        window: false,
+        params: &[optional(
+            "limit",
+            ParamKind::Integer,
+            "Most recent entries to return",
+        )],
+    },
+];
+
+impl ParamKind {
+    /// The JSON Schema of a parameter of this kind.
+    fn schema(self, description: &str) -> Value {
+        let mut schema = match self {
+            Self::String => json!({ "type": "string" }),
+            Self::Integer => json!({ "type": "integer" }),
+            Self::Number => json!({ "type": "number" }),
+            Self::Boolean => json!({ "type": "boolean" }),
+            Self::StringArray => json!({ "type": "array", "items": { "type": "string" } }),
+            Self::Enum(values) => json!({ "type": "string", "enum": values }),
+            Self::Any => json!({}),
+        };
+        schema["description"] = json!(description);
+        schema
+    }
+}
+
+impl Tool {
+    /// The tool's definition as listed by `tools/list`.
+    pub fn definition(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for param in self.params {
+            properties.insert(param.name.to_string(), param.kind.schema(param.description));
+            if param.required {
+                required.push(param.name);
+            }
+        }
+        if self.window {
+            properties.insert(
+                "windowLabel".to_string(),
+                ParamKind::String.schema("Window to target; the active window by default"),
+            );
+        }
+
+        json!({
+            "name": self.name,
+            "description": self.description,
+            "inputSchema": {
+                "type": "object",
+                "properties": properties,
+                "required": required
+            }
+        })
+    }
+
+    /// The bridge request running the tool with `arguments`.
+    fn request_body(&self, id: &str, arguments: Value) -> Value {
+        match self.route {
+            ToolRoute::Bridge => json!({ "id": id, "command": self.name, "args": arguments }),
+            ToolRoute::Plugin => json!({
+                "id": id,
+                "command": "invoke_tauri",
+                "args": {
+                    "command": format!("plugin:mcp-bridge|{}", self.name),
+                    "args": arguments
+                }
+            }),
+        }
+    }
+}
+
+/// The definitions of all tools, for `tools/list`.
+pub fn tool_definitions() -> Vec<Value> {
+    TOOLS.iter().map(Tool::definition).collect()
+}
+
+/// A JSON-RPC message received in MCP mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpMessage {
+    /// Answered without running a command
+    Reply(Value),
+    /// A `tools/call`, run as `request`; its result is sent to `rpc_id`
+    Call { rpc_id: Value, request: WsRequest },
+    /// A notification or a client's response, which gets no reply
+    Notification,
+}
+
+/// Parses a frame as an MCP message.
+///
+/// # Returns
+///
+/// * `Some(McpMessage)` - If the frame is a JSON-RPC 2.0 message
+/// * `None` - If it isn't, so it is handled as a bridge request
+pub fn parse(text: &str) -> Option<McpMessage> {
+    let body: Value = serde_json::from_str(text).ok()?;
+    if body.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return None;
+    }
+    let (Some(method), Some(rpc_id)) = (
+        body.get("method").and_then(Value::as_str),
+        body.get("id").cloned(),
+    ) else {
+        return Some(McpMessage::Notification);
+    };
+    let params = body.get("params");
+
+    Some(match method {
+        "initialize" => McpMessage::Reply(rpc_result(&rpc_id, initialize_result(params))),
+        "ping" => McpMessage::Reply(rpc_result(&rpc_id, json!({}))),
+        "tools/list" => {
+            McpMessage::Reply(rpc_result(&rpc_id, json!({ "tools": tool_definitions() })))
+        }
+        "tools/call" => match call_request(&rpc_id, params) {
+            Ok(request) => McpMessage::Call { rpc_id, request },
+            Err(e) => McpMessage::Reply(rpc_error(&rpc_id, INVALID_PARAMS, &e)),
+        },
+        _ => McpMessage::Reply(rpc_error(
+            &rpc_id,
+            METHOD_NOT_FOUND,
+            &format!("Method not found: {method}"),
+        )),
+    })
+}
+
+/// Converts the response to a `tools/call` into its JSON-RPC result.
+///
+/// The command's data is sent as JSON text content, or as image content
+/// when it is a screenshot data URL. Chunks of an oversized result are
+/// joined back together, as MCP clients don't read chunk frames.
+pub fn tool_result(rpc_id: &Value, response: &WsResponse) -> Value {
+    let body = &response.body;
+    let success = body
+        .get("success")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let content = if !success {
+        let error = match body.get("error") {
+            Some(Value::String(error)) => error.clone(),
+            Some(error) => error.to_string(),
+            None => "Command failed".to_string(),
+        };
+        json!({ "type": "text", "text": error })
+    } else if !response.chunks.is_empty() {
+        let text: String = response
+            .chunks
+            .iter()
+            .filter_map(|chunk| chunk.get("data").and_then(Value::as_str))
+            .collect();
+        json!({ "type": "text", "text": text })
+    } else {
+        let data = body.get("data").unwrap_or(&Value::Null);
+        image_content(data).unwrap_or_else(|| json!({ "type": "text", "text": data.to_string() }))
+    };
+
+    rpc_result(rpc_id, json!({ "content": [content], "isError": !success }))
+}
+
+/// Image content for a data URL result, or a result carrying one as
+/// `dataUrl`.
+fn image_content(data: &Value) -> Option<Value> {
+    let url = data
+        .as_str()
+        .or_else(|| data.get("dataUrl").and_then(Value::as_str))?;
+    let (mime_type, base64) = url.strip_prefix("data:")?.split_once(";base64,")?;
+    if !mime_type.starts_with("image/") {
+        return None;
+    }
+    Some(json!({ "type": "image", "data": base64, "mimeType": mime_type }))
+}
+
+/// The `initialize` result, agreeing to the client's protocol version when
+/// it is one we support.
+fn initialize_result(params: Option<&Value>) -> Value {
+    let requested = params
+        .and_then(|params| params.get("protocolVersion"))
+        .and_then(Value::as_str);
+    let version = requested
+        .filter(|version| SUPPORTED_VERSIONS.contains(version))
+        .unwrap_or(MCP_PROTOCOL_VERSION);
+
+    json!({
+        "protocolVersion": version,
+        "capabilities": { "tools": { "listChanged": false } },
+        "serverInfo": {
+            "name": "tauri-plugin-mcp-bridge",
+            "version": env!("CARGO_PKG_VERSION")
+        }
+    })
+}
+
+/// Builds the bridge request a `tools/call` runs.
+fn call_request(rpc_id: &Value, params: Option<&Value>) -> Result<WsRequest, String> {
+    let name = params
+        .and_then(|params| params.get("name"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| "tools/call requires a tool name".to_string())?;
+    let tool = TOOLS
+        .iter()
+        .find(|tool| tool.name == name)
+        .ok_or_else(|| format!("Unknown tool: {name}"))?;
+    let arguments = match params.and_then(|params| params.get("arguments")) {
+        None | Some(Value::Null) => json!({}),
+        Some(arguments) if arguments.is_object() => arguments.clone(),
+        Some(_) => return Err("Tool arguments must be an object".to_string()),
+    };
+
+    let id = match rpc_id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    };
+    let mut body = tool.request_body(&id, arguments);
+    let window_match = take_window_match(&mut body);
+    Ok(WsRequest {
+        id,
+        command: body["command"].as_str().unwrap_or(name).to_string(),
+        body,
+        window_match,
+    })
+}
+
+fn rpc_result(rpc_id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": rpc_id, "result": result })
+}
+
+fn rpc_error(rpc_id: &Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": rpc_id,
+        "error": { "code": code, "message": message }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(text: &str) -> Value {
+        match parse(text) {
+            Some(McpMessage::Reply(reply)) => reply,
+            other => panic!("expected a reply, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_tools_list_returns_bridge_commands() {
+        let reply = reply(r#"{ "jsonrpc": "2.0", "id": 1, "method": "tools/list" }"#);
+        assert_eq!(reply["id"], 1);
+        let tools = reply["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), TOOLS.len());
+
+        let execute_js = tools.iter().find(|t| t["name"] == "execute_js").unwrap();
+        assert_eq!(execute_js["inputSchema"]["type"], "object");
+        assert_eq!(
+            execute_js["inputSchema"]["properties"]["script"]["type"],
+            "string"
+        );
+        assert_eq!(
+            execute_js["inputSchema"]["properties"]["windowLabel"]["type"],
+            "string"
+        );
+        assert_eq!(execute_js["inputSchema"]["required"], json!(["script"]));
+
+        let assert_element = tools
+            .iter()
+            .find(|t| t["name"] == "assert_element")
+            .unwrap();
+        let properties = &assert_element["inputSchema"]["properties"];
+        assert_eq!(
+            properties["operator"]["enum"],
+            json!(["eq", "contains", "matches"])
+        );
+        assert!(properties["expected"].get("type").is_none());
+
+        let list_windows = tools.iter().find(|t| t["name"] == "list_windows").unwrap();
+        assert_eq!(list_windows["inputSchema"]["properties"], json!({}));
+    }
+
+    #[test]
+    fn test_initialize_and_unknown_methods() {
+        let init = reply(
+            r#"{ "jsonrpc": "2.0", "id": "a", "method": "initialize", "params": { "protocolVersion": "2024-11-05" } }"#,
+        );
+        assert_eq!(init["result"]["protocolVersion"], "2024-11-05");
+        assert!(init["result"]["capabilities"]["tools"].is_object());
+        assert_eq!(
+            init["result"]["serverInfo"]["name"],
+            "tauri-plugin-mcp-bridge"
+        );
+
+        let future = reply(
+            r#"{ "jsonrpc": "2.0", "id": 2, "method": "initialize", "params": { "protocolVersion": "2999-01-01" } }"#,
+        );
+        assert_eq!(future["result"]["protocolVersion"], MCP_PROTOCOL_VERSION);
+
+        let unknown = reply(r#"{ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }"#);
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        assert_eq!(
+            parse(r#"{ "jsonrpc": "2.0", "method": "notifications/initialized" }"#),
+            Some(McpMessage::Notification)
+        );
+        // Bridge requests aren't MCP messages
+        assert_eq!(parse(r#"{ "id": "1", "command": "list_windows" }"#), None);
+    }
+
+    #[test]
+    fn test_tools_call_runs_the_command() {
+        let Some(McpMessage::Call { rpc_id, request }) = parse(
+            r##"{ "jsonrpc": "2.0", "id": 7, "method": "tools/call", "params": { "name": "get_element_box", "arguments": { "selector": "#save" } } }"##,
+        ) else {
+            panic!("expected a call");
+        };
+        assert_eq!(rpc_id, 7);
+        assert_eq!(
+            (request.id.as_str(), request.command.as_str()),
+            ("7", "invoke_tauri")
+        );
+        assert_eq!(
+            request.args(),
+            Some(&json!({
+                "command": "plugin:mcp-bridge|get_element_box",
+                "args": { "selector": "#save" }
+            }))
+        );
+
+        let unknown = reply(
+            r#"{ "jsonrpc": "2.0", "id": 8, "method": "tools/call", "params": { "name": "teleport" } }"#,
+        );
+        assert_eq!(unknown["error"]["code"], INVALID_PARAMS);
+
+        let ok = WsResponse::new(json!({ "id": "7", "success": true, "data": { "x": 1 } }));
+        let result = tool_result(&rpc_id, &ok);
+        assert_eq!(result["result"]["isError"], false);
+        assert_eq!(result["result"]["content"][0]["text"], r#"{"x":1}"#);
+
+        let failed = WsResponse::new(json!({ "id": "7", "success": false, "error": "No element" }));
+        let result = tool_result(&rpc_id, &failed);
+        assert_eq!(result["result"]["isError"], true);
+        assert_eq!(result["result"]["content"][0]["text"], "No element");
+
+        let image = WsResponse::new(
+            json!({ "id": "7", "success": true, "data": "data:image/png;base64,AAAA" }),
+        );
+        assert_eq!(
+            tool_result(&rpc_id, &image)["result"]["content"][0],
+            json!({ "type": "image", "data": "AAAA", "mimeType": "image/png" })
+        );
+    }
+}
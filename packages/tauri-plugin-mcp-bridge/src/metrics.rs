@@ -0,0 +1,226 @@
+//! Periodic server health metrics for monitoring dashboards.
+//!
+//! With [`Builder::metrics_interval`](crate::Builder::metrics_interval) set,
+//! the server broadcasts a frame like this at that interval:
+//!
+//! ```json
+//! {
+//!   "type": "metrics",
+//!   "connectedClients": 2,
+//!   "ipcBufferLen": 118,
+//!   "eventsSentTotal": 5320,
+//!   "uptimeMs": 93000
+//! }
+//! ```
+//!
+//! `eventsSentTotal` counts broadcast frames delivered to clients, summed over
+//! connections. Nothing is sent while no client is connected, and the task
+//! stops when the server shuts down. Clients that only want metrics can
+//! `subscribe` to the `metrics` topic.
+//!
+//! The interval is bounded below by [`MIN_METRICS_INTERVAL`], so a zero
+//! interval from a builder or config file can't spin the task.
+
+use crate::clients::ClientRegistry;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{broadcast, watch};
+use tokio::time::MissedTickBehavior;
+
+/// Shortest time between metrics frames.
+pub const MIN_METRICS_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A `metrics` broadcast frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsFrame {
+    /// Always `"metrics"`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Number of open WebSocket connections
+    pub connected_clients: usize,
+    /// Number of IPC events held by the monitor
+    pub ipc_buffer_len: usize,
+    /// Broadcast frames delivered to clients since the server started
+    pub events_sent_total: u64,
+    /// Milliseconds since the server started
+    pub uptime_ms: u64,
+}
+
+/// Counters shared by the server's connections.
+///
+/// Cheap to clone; clones share the same counters.
+#[derive(Clone)]
+pub struct ServerMetrics {
+    started_at: Instant,
+    events_sent: Arc<AtomicU64>,
+}
+
+impl Default for ServerMetrics {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            events_sent: Arc::new(AtomicU64::new(0)),
+        }
+    }
+}
+
+impl ServerMetrics {
+    /// Starts counting from now.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a broadcast frame delivered to one client.
+    pub fn record_event_sent(&self) {
+        self.events_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Builds a frame from the counters and the given live values.
+    pub fn frame(&self, connected_clients: usize, ipc_buffer_len: usize) -> MetricsFrame {
+        MetricsFrame {
+            kind: "metrics",
+            connected_clients,
+            ipc_buffer_len,
+            events_sent_total: self.events_sent.load(Ordering::Relaxed),
+            uptime_ms: self.started_at.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Broadcasts a metrics frame every `interval` until the server shuts down.
+///
+/// Ticks are skipped while no client is connected.
+///
+/// # Arguments
+///
+/// * `interval` - Time between frames, at least [`MIN_METRICS_INTERVAL`]
+/// * `metrics` - The server's counters
+/// * `clients` - Registry of open connections
+/// * `ipc_buffer_len` - Returns the current number of buffered IPC events
+/// * `event_tx` - Broadcast sender shared with the connections
+/// * `shutdown_rx` - Flips to `true` when the server is shutting down
+pub async fn broadcast_metrics<F: Fn() -> usize>(
+    interval: Duration,
+    metrics: ServerMetrics,
+    clients: ClientRegistry,
+    ipc_buffer_len: F,
+    event_tx: broadcast::Sender<String>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) {
+    let interval = interval.max(MIN_METRICS_INTERVAL);
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            _ = shutdown_rx.wait_for(|shutting_down| *shutting_down) => return,
+        }
+
+        let connected = clients.len();
+        if connected == 0 {
+            continue;
+        }
+        if let Ok(frame) = serde_json::to_string(&metrics.frame(connected, ipc_buffer_len())) {
+            let _ = event_tx.send(frame);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_metrics_frames_arrive_at_interval() {
+        let clients = ClientRegistry::new();
+        let _client = clients.register("127.0.0.1:50000".parse().unwrap());
+        let metrics = ServerMetrics::new();
+        metrics.record_event_sent();
+        let (event_tx, mut event_rx) = broadcast::channel(16);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(broadcast_metrics(
+            Duration::from_millis(50),
+            metrics,
+            clients,
+            || 7,
+            event_tx,
+            shutdown_rx,
+        ));
+
+        let start = Instant::now();
+        for _ in 0..3 {
+            let frame: Value = serde_json::from_str(&event_rx.recv().await.unwrap()).unwrap();
+            assert_eq!(frame["type"], "metrics");
+            assert_eq!(frame["connectedClients"], 1);
+            assert_eq!(frame["ipcBufferLen"], 7);
+            assert_eq!(frame["eventsSentTotal"], 1);
+        }
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(140), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(1000), "{elapsed:?}");
+
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_zero_interval_is_raised_to_the_minimum() {
+        let clients = ClientRegistry::new();
+        let _client = clients.register("127.0.0.1:50000".parse().unwrap());
+        let (event_tx, mut event_rx) = broadcast::channel(16);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(broadcast_metrics(
+            Duration::ZERO,
+            ServerMetrics::new(),
+            clients,
+            || 0,
+            event_tx,
+            shutdown_rx,
+        ));
+
+        let start = Instant::now();
+        let frame = tokio::time::timeout(Duration::from_secs(1), event_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        let frame: Value = serde_json::from_str(&frame).unwrap();
+        assert_eq!(frame["type"], "metrics");
+        assert!(start.elapsed() >= MIN_METRICS_INTERVAL);
+
+        shutdown_tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_no_frames_without_clients() {
+        let (event_tx, mut event_rx) = broadcast::channel(16);
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
+        let task = tokio::spawn(broadcast_metrics(
+            Duration::from_millis(10),
+            ServerMetrics::new(),
+            ClientRegistry::new(),
+            || 0,
+            event_tx,
+            shutdown_rx,
+        ));
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        shutdown_tx.send(true).unwrap();
+        task.await.unwrap();
+        assert!(event_rx.try_recv().is_err());
+    }
+}
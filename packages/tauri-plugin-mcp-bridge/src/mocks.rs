@@ -0,0 +1,250 @@
+//! Canned responses for the page's `fetch` and `XMLHttpRequest` calls.
+//!
+//! Rules added with `add_mock` live in an app-wide [`MockRegistry`], like
+//! registered scripts: they outlive the connection that added them and apply
+//! to every window until `remove_mock` or `clear_mocks`. `bridge.js` asks for
+//! the rules with `request_mocks` at document start, before the page's own
+//! scripts run, so they hold across navigations and reloads. Requests made
+//! before the answer arrives wait for it.
+//!
+//! Only `fetch` and `XMLHttpRequest` are intercepted. Resources the browser
+//! loads itself, such as `<img>`, `<script>` and `<link>` tags, CSS
+//! `url()`s, navigations, WebSockets and requests from workers, still go to
+//! the network.
+
+use crate::script_registry::js_string_literal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Most rules the registry holds, as each page load checks every rule.
+pub const MAX_MOCKS: usize = 100;
+
+/// A canned response for requests matching a URL pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MockRule {
+    pub id: String,
+    /// Glob matched against the absolute request URL; `*` matches any run of
+    /// characters
+    pub url_pattern: String,
+    /// Upper-case method the rule is limited to; `None` matches every method
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub method: Option<String>,
+    pub status: u16,
+    pub body: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl MockRule {
+    /// Builds a rule from `add_mock` arguments.
+    ///
+    /// A `body` that isn't a string is sent as JSON, with a
+    /// `content-type: application/json` header unless one is given.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MockRule)` - The rule, with an empty `id` if none was given
+    /// * `Err(String)` - If the pattern is empty or the status isn't one a
+    ///   `Response` can have
+    pub fn new(
+        id: Option<String>,
+        url_pattern: String,
+        method: Option<String>,
+        status: u16,
+        body: Option<Value>,
+        headers: Option<BTreeMap<String, String>>,
+    ) -> Result<Self, String> {
+        if url_pattern.trim().is_empty() {
+            return Err("urlPattern must not be empty".to_string());
+        }
+        if !(200..=599).contains(&status) {
+            return Err(format!("status must be between 200 and 599, got {status}"));
+        }
+
+        let mut headers = headers.unwrap_or_default();
+        let body = match body {
+            None | Some(Value::Null) => String::new(),
+            Some(Value::String(text)) => text,
+            Some(json) => {
+                let has_type = headers
+                    .keys()
+                    .any(|name| name.eq_ignore_ascii_case("content-type"));
+                if !has_type {
+                    headers.insert("content-type".to_string(), "application/json".to_string());
+                }
+                json.to_string()
+            }
+        };
+        Ok(Self {
+            id: id.unwrap_or_default(),
+            url_pattern,
+            method: method.map(|method| method.to_ascii_uppercase()),
+            status,
+            body,
+            headers,
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+struct MockRules {
+    rules: Vec<MockRule>,
+    next_id: u64,
+}
+
+/// Mock rules shared by every connection and window.
+///
+/// Cheap to clone; clones share the same rules. The plugin manages one as
+/// Tauri state.
+#[derive(Debug, Clone, Default)]
+pub struct MockRegistry {
+    inner: Arc<Mutex<MockRules>>,
+}
+
+impl MockRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule, replacing the rule with the same id if there is one.
+    /// Rules are checked in the order they were first added.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(MockRule)` - The stored rule, with a generated `mock-<n>` id if
+    ///   it had none
+    /// * `Err(String)` - If the registry already holds [`MAX_MOCKS`] rules
+    pub fn add(&self, mut rule: MockRule) -> Result<MockRule, String> {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if rule.id.is_empty() {
+            inner.next_id += 1;
+            rule.id = format!("mock-{}", inner.next_id);
+        }
+        if let Some(existing) = inner.rules.iter_mut().find(|r| r.id == rule.id) {
+            *existing = rule.clone();
+            return Ok(rule);
+        }
+        if inner.rules.len() >= MAX_MOCKS {
+            return Err(format!(
+                "Too many mocks ({MAX_MOCKS}); remove some with remove_mock or clear_mocks"
+            ));
+        }
+        inner.rules.push(rule.clone());
+        Ok(rule)
+    }
+
+    /// Removes the rule with `id`, returning whether there was one.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        let before = inner.rules.len();
+        inner.rules.retain(|rule| rule.id != id);
+        inner.rules.len() != before
+    }
+
+    /// Removes every rule, returning how many there were.
+    pub fn clear(&self) -> usize {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        std::mem::take(&mut inner.rules).len()
+    }
+
+    /// The rules, in the order they are checked.
+    pub fn rules(&self) -> Vec<MockRule> {
+        self.inner
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .rules
+            .clone()
+    }
+
+    /// Builds the script replacing the rules `bridge.js` applies in an open
+    /// page.
+    pub fn apply_script(&self) -> String {
+        let rules = serde_json::to_string(&self.rules()).unwrap_or_else(|_| "[]".to_string());
+        format!(
+            "window.__MCP_MOCKS__ && window.__MCP_MOCKS__.set(JSON.parse({}));",
+            js_string_literal(&rules)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rule(id: Option<&str>, pattern: &str) -> MockRule {
+        MockRule::new(
+            id.map(str::to_string),
+            pattern.to_string(),
+            Some("get".to_string()),
+            200,
+            Some(json!({ "users": [] })),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules_are_validated_and_json_bodies_typed() {
+        let users = rule(None, "*/api/users*");
+        assert_eq!(users.method.as_deref(), Some("GET"));
+        assert_eq!(users.body, r#"{"users":[]}"#);
+        assert_eq!(users.headers["content-type"], "application/json");
+
+        let text = MockRule::new(
+            None,
+            "*/health".to_string(),
+            None,
+            503,
+            Some(json!("down")),
+            Some(BTreeMap::from([(
+                "Content-Type".to_string(),
+                "text/plain".to_string(),
+            )])),
+        )
+        .unwrap();
+        assert_eq!(text.body, "down");
+        assert_eq!(text.headers.len(), 1);
+
+        let err = MockRule::new(None, "*".to_string(), None, 101, None, None).unwrap_err();
+        assert_eq!(err, "status must be between 200 and 599, got 101");
+        assert!(MockRule::new(None, " ".to_string(), None, 200, None, None).is_err());
+    }
+
+    #[test]
+    fn test_registry_adds_replaces_and_removes_rules() {
+        let registry = MockRegistry::new();
+        let first = registry.add(rule(None, "*/a")).unwrap();
+        let named = registry.add(rule(Some("users"), "*/users")).unwrap();
+        assert_eq!((first.id.as_str(), named.id.as_str()), ("mock-1", "users"));
+
+        // The same id replaces the rule in place
+        registry.add(rule(Some("users"), "*/people")).unwrap();
+        let patterns: Vec<_> = registry
+            .rules()
+            .into_iter()
+            .map(|rule| rule.url_pattern)
+            .collect();
+        assert_eq!(patterns, ["*/a", "*/people"]);
+
+        let script = registry.apply_script();
+        assert!(
+            script.starts_with("window.__MCP_MOCKS__ && window.__MCP_MOCKS__.set(JSON.parse(\"[{")
+        );
+        assert!(script.contains(r#"\"urlPattern\":\"*/people\""#));
+
+        assert!(registry.remove("mock-1"));
+        assert!(!registry.remove("mock-1"));
+        assert_eq!(registry.clear(), 1);
+        assert!(registry.rules().is_empty());
+
+        for _ in 0..MAX_MOCKS {
+            registry.add(rule(None, "*")).unwrap();
+        }
+        assert!(registry.add(rule(None, "*")).is_err());
+    }
+}
@@ -2,9 +2,17 @@
 //!
 //! This module provides functionality to monitor and capture Tauri IPC events,
 //! including command invocations, arguments, results, and timing information.
+//!
+//! Failed calls are also pushed to connected clients as they are captured,
+//! as an `ipc_error` frame carrying the full event:
+//!
+//! ```json
+//! { "type": "ipc_error", "event": { "command": "save", "args": { ... }, "error": "disk full", "source": "app", ... } }
+//! ```
 
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
 
 /// Represents a captured IPC event.
 ///
@@ -20,6 +28,10 @@ use std::sync::{Arc, Mutex};
 /// * `result` - Optional JSON result returned by the command
 /// * `error` - Optional error message if the command failed
 /// * `duration_ms` - Optional execution duration in milliseconds
+/// * `window_label` - Label of the window that issued the call, when known
+/// * `source` - Origin of a failed command: `"plugin:<name>"` for plugin
+///   commands, `"app"` otherwise. Set automatically for errored events.
+/// * `stack` - Optional stack trace reported alongside the error
 ///
 /// # Examples
 ///
@@ -34,6 +46,9 @@ use std::sync::{Arc, Mutex};
 ///     result: Some(json!({"message": "Hello, World!"})),
 ///     error: None,
 ///     duration_ms: Some(5.2),
+///     window_label: Some("main".to_string()),
+///     source: None,
+///     stack: None,
 /// };
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,18 +59,88 @@ pub struct IPCEvent {
     pub result: Option<serde_json::Value>,
     pub error: Option<String>,
     pub duration_ms: Option<f64>,
+    #[serde(
+        rename = "windowLabel",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub window_label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stack: Option<String>,
+}
+
+impl IPCEvent {
+    /// Returns where a command is implemented, judging by its name.
+    ///
+    /// Plugin commands are invoked as `plugin:<name>|<command>`; anything else
+    /// is one of the app's own commands.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::monitor::IPCEvent;
+    ///
+    /// assert_eq!(IPCEvent::command_source("plugin:fs|read_file"), "plugin:fs");
+    /// assert_eq!(IPCEvent::command_source("greet"), "app");
+    /// ```
+    pub fn command_source(command: &str) -> String {
+        match command
+            .strip_prefix("plugin:")
+            .and_then(|rest| rest.split_once('|'))
+        {
+            Some((plugin, _)) => format!("plugin:{plugin}"),
+            None => "app".to_string(),
+        }
+    }
+}
+
+/// Capture state of an [`IPCMonitor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MonitorState {
+    /// Not capturing; captured events stay readable
+    Stopped,
+    /// Capturing new events
+    Capturing,
+    /// Temporarily not capturing; captured events are kept
+    Paused,
 }
 
 /// IPC monitor for capturing Tauri command invocations.
 ///
 /// The monitor can be enabled or disabled and maintains a list of captured
-/// events. When enabled, it records all IPC events that occur. Events are
-/// cleared when monitoring is restarted.
+/// events. When enabled, it records all IPC events that occur, or only those
+/// from one window when started with [`start_scoped`](IPCMonitor::start_scoped).
+/// Events are cleared when monitoring is restarted.
+///
+/// Capture moves between three [`MonitorState`]s. Captured events can be
+/// read in every state; only `start` clears them:
+///
+/// ```text
+///             start (clears)           pause
+///   Stopped ───────────────► Capturing ──────► Paused
+///      ▲                       │   ▲              │
+///      │          stop         │   └── resume ────┤
+///      └───────────────────────┴──────────────────┘
+///                                    stop
+/// ```
+///
+/// `start` from any state clears the events and begins capturing again.
+///
+/// Captured events whose `error` is set are additionally broadcast right away
+/// to the sink installed with [`set_error_sink`](IPCMonitor::set_error_sink),
+/// so clients learn of failures without polling. Their `args` are kept whole.
 ///
 /// # Thread Safety
 ///
 /// This struct is typically wrapped in `Arc<Mutex<IPCMonitor>>` to allow
-/// safe concurrent access from multiple threads.
+/// safe concurrent access from multiple threads. Events are stored behind
+/// `Arc` so readers can take a [`snapshot`](IPCMonitor::snapshot) with only
+/// reference-count bumps while holding the lock, and do the expensive deep
+/// clone and serialization after releasing it. This keeps `add_event` on the
+/// app's IPC hot path from waiting on a large readout.
 ///
 /// # Examples
 ///
@@ -70,7 +155,10 @@ pub struct IPCEvent {
 /// ```
 pub struct IPCMonitor {
     pub enabled: bool,
-    pub events: Vec<IPCEvent>,
+    paused: bool,
+    scope: Option<String>,
+    events: Vec<Arc<IPCEvent>>,
+    error_sink: Option<broadcast::Sender<String>>,
 }
 
 impl Default for IPCMonitor {
@@ -97,10 +185,21 @@ impl IPCMonitor {
     pub fn new() -> Self {
         Self {
             enabled: false,
+            paused: false,
+            scope: None,
             events: Vec::new(),
+            error_sink: None,
         }
     }
 
+    /// Sets the channel errored events are broadcast on as they are captured.
+    ///
+    /// Each message is a JSON `{ "type": "ipc_error", "event": IPCEvent }`
+    /// frame, ready to forward to WebSocket clients.
+    pub fn set_error_sink(&mut self, sink: broadcast::Sender<String>) {
+        self.error_sink = Some(sink);
+    }
+
     /// Starts IPC monitoring and clears previous events.
     ///
     /// Enables the monitor and clears any previously captured events.
@@ -116,10 +215,35 @@ impl IPCMonitor {
     /// assert!(monitor.enabled);
     /// ```
     pub fn start(&mut self) {
+        self.start_scoped(None);
+    }
+
+    /// Starts IPC monitoring for a single window and clears previous events.
+    ///
+    /// When `window_label` is `Some`, only events whose `window_label` matches
+    /// are captured; `None` captures events from all windows.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::monitor::IPCMonitor;
+    ///
+    /// let mut monitor = IPCMonitor::new();
+    /// monitor.start_scoped(Some("settings".to_string()));
+    /// assert_eq!(monitor.scope(), Some("settings"));
+    /// ```
+    pub fn start_scoped(&mut self, window_label: Option<String>) {
         self.enabled = true;
+        self.paused = false;
+        self.scope = window_label;
         self.events.clear();
     }
 
+    /// Returns the window label capture is scoped to, if any.
+    pub fn scope(&self) -> Option<&str> {
+        self.scope.as_deref()
+    }
+
     /// Stops IPC monitoring.
     ///
     /// Disables the monitor, preventing new events from being captured.
@@ -137,12 +261,75 @@ impl IPCMonitor {
     /// ```
     pub fn stop(&mut self) {
         self.enabled = false;
+        self.paused = false;
+    }
+
+    /// Pauses capture without clearing captured events.
+    ///
+    /// Events arriving while paused are dropped, including the `ipc_error`
+    /// broadcast for failed calls. Pausing an already paused monitor does
+    /// nothing.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The monitor is paused
+    /// * `Err(String)` - If the monitor is stopped
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use tauri_plugin_mcp_bridge::monitor::{IPCMonitor, MonitorState};
+    ///
+    /// let mut monitor = IPCMonitor::new();
+    /// assert!(monitor.pause().is_err());
+    ///
+    /// monitor.start();
+    /// monitor.pause().unwrap();
+    /// assert_eq!(monitor.state(), MonitorState::Paused);
+    /// ```
+    pub fn pause(&mut self) -> Result<(), String> {
+        if !self.enabled {
+            return Err("IPC monitor is not running; start it with start_ipc_monitor".to_string());
+        }
+        self.paused = true;
+        Ok(())
+    }
+
+    /// Resumes capture after [`pause`](IPCMonitor::pause), keeping the
+    /// events captured so far and the window scope. Resuming a monitor that
+    /// is capturing does nothing.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The monitor is capturing
+    /// * `Err(String)` - If the monitor is stopped
+    pub fn resume(&mut self) -> Result<(), String> {
+        if !self.enabled {
+            return Err("IPC monitor is not running; start it with start_ipc_monitor".to_string());
+        }
+        self.paused = false;
+        Ok(())
+    }
+
+    /// Returns the current capture state.
+    pub fn state(&self) -> MonitorState {
+        match (self.enabled, self.paused) {
+            (false, _) => MonitorState::Stopped,
+            (true, false) => MonitorState::Capturing,
+            (true, true) => MonitorState::Paused,
+        }
     }
 
     /// Adds an IPC event to the monitor if monitoring is enabled.
     ///
-    /// Events are only added when the monitor is enabled. If disabled,
-    /// the event is silently ignored.
+    /// Events are only added when the monitor is capturing (enabled and not
+    /// paused) and, for scoped
+    /// monitoring, when the event comes from the monitored window. Other
+    /// events are silently ignored. Interceptors should set `window_label`
+    /// to the label of the webview that issued the call.
+    ///
+    /// Errored events get their `source` filled in from the command name when
+    /// unset, and are broadcast to the error sink before this returns.
     ///
     /// # Arguments
     ///
@@ -164,19 +351,86 @@ impl IPCMonitor {
     ///     result: None,
     ///     error: None,
     ///     duration_ms: None,
+    ///     window_label: None,
+    ///     source: None,
+    ///     stack: None,
     /// };
     ///
     /// monitor.add_event(event);
     /// assert_eq!(monitor.get_events().len(), 1);
     /// ```
-    pub fn add_event(&mut self, event: IPCEvent) {
-        if self.enabled {
-            self.events.push(event);
+    pub fn add_event(&mut self, mut event: IPCEvent) {
+        if self.state() != MonitorState::Capturing {
+            return;
+        }
+        if let Some(scope) = &self.scope {
+            if event.window_label.as_ref() != Some(scope) {
+                return;
+            }
+        }
+        if event.error.is_some() {
+            if event.source.is_none() {
+                event.source = Some(IPCEvent::command_source(&event.command));
+            }
+            if let Some(sink) = &self.error_sink {
+                let frame = serde_json::json!({ "type": "ipc_error", "event": &event });
+                // No receivers just means no client is connected
+                let _ = sink.send(frame.to_string());
+            }
+        }
+        self.events.push(Arc::new(event));
+    }
+
+    /// Returns shared handles to all captured events.
+    ///
+    /// This only bumps reference counts, so it is cheap enough to call while
+    /// holding the monitor lock. Clone or serialize the events after the
+    /// lock has been released.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::sync::{Arc, Mutex};
+    /// use tauri_plugin_mcp_bridge::monitor::IPCMonitor;
+    ///
+    /// let monitor = Arc::new(Mutex::new(IPCMonitor::new()));
+    /// let snapshot = monitor.lock().unwrap().snapshot();
+    /// // Lock released here; serialize `snapshot` without blocking writers
+    /// assert!(snapshot.is_empty());
+    /// ```
+    pub fn snapshot(&self) -> Vec<Arc<IPCEvent>> {
+        self.events.clone()
+    }
+
+    /// Like [`snapshot`](IPCMonitor::snapshot), limited to events from one
+    /// window when `window_label` is `Some`.
+    pub fn snapshot_for_window(&self, window_label: Option<&str>) -> Vec<Arc<IPCEvent>> {
+        match window_label {
+            Some(label) => self
+                .events
+                .iter()
+                .filter(|event| event.window_label.as_deref() == Some(label))
+                .cloned()
+                .collect(),
+            None => self.snapshot(),
         }
     }
 
+    /// Returns the number of captured events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns true if no events have been captured.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
     /// Returns a copy of all captured events.
     ///
+    /// Prefer [`snapshot`](IPCMonitor::snapshot) when the monitor is shared,
+    /// since this deep-clones every event.
+    ///
     /// # Returns
     ///
     /// A vector containing clones of all captured IPC events.
@@ -191,7 +445,7 @@ impl IPCMonitor {
     /// assert_eq!(events.len(), 0);
     /// ```
     pub fn get_events(&self) -> Vec<IPCEvent> {
-        self.events.clone()
+        self.events.iter().map(|event| (**event).clone()).collect()
     }
 }
 
@@ -222,3 +476,207 @@ pub fn current_timestamp() -> u64 {
         .unwrap()
         .as_millis() as u64
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::thread;
+
+    fn make_event(index: u64) -> IPCEvent {
+        IPCEvent {
+            timestamp: index,
+            command: format!("cmd_{index}"),
+            args: json!({ "payload": "x".repeat(256) }),
+            result: None,
+            error: None,
+            duration_ms: None,
+            window_label: None,
+            source: None,
+            stack: None,
+        }
+    }
+
+    fn make_window_event(index: u64, window_label: &str) -> IPCEvent {
+        IPCEvent {
+            window_label: Some(window_label.to_string()),
+            ..make_event(index)
+        }
+    }
+
+    #[test]
+    fn test_events_from_two_windows_filter_to_one() {
+        let mut monitor = IPCMonitor::new();
+        monitor.start();
+        monitor.add_event(make_window_event(1, "main"));
+        monitor.add_event(make_window_event(2, "settings"));
+        monitor.add_event(make_window_event(3, "main"));
+
+        assert_eq!(monitor.snapshot_for_window(None).len(), 3);
+
+        let main: Vec<u64> = monitor
+            .snapshot_for_window(Some("main"))
+            .iter()
+            .map(|event| event.timestamp)
+            .collect();
+        assert_eq!(main, vec![1, 3]);
+
+        let serialized = serde_json::to_value(monitor.get_events()).unwrap();
+        assert_eq!(serialized[1]["windowLabel"], "settings");
+    }
+
+    #[test]
+    fn test_errored_event_is_broadcast_with_full_args() {
+        let (sink, mut rx) = broadcast::channel(16);
+        let mut monitor = IPCMonitor::new();
+        monitor.set_error_sink(sink);
+        monitor.start();
+
+        let args = json!({ "path": "/tmp/out.txt", "contents": "y".repeat(64 * 1024) });
+        monitor.add_event(make_event(1));
+        monitor.add_event(IPCEvent {
+            command: "plugin:fs|write_file".to_string(),
+            args: args.clone(),
+            error: Some("permission denied".to_string()),
+            stack: Some("Error: permission denied\n    at save (app.js:10:5)".to_string()),
+            ..make_event(2)
+        });
+
+        // Only the failure is broadcast, and immediately
+        let frame: serde_json::Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert!(rx.try_recv().is_err());
+        assert_eq!(frame["type"], "ipc_error");
+        assert_eq!(frame["event"]["args"], args);
+        assert_eq!(frame["event"]["error"], "permission denied");
+        assert_eq!(frame["event"]["source"], "plugin:fs");
+        assert!(frame["event"]["stack"]
+            .as_str()
+            .unwrap()
+            .contains("app.js:10:5"));
+
+        let events = monitor.get_events();
+        assert_eq!(events[1].args, args);
+        assert_eq!(events[0].source, None);
+    }
+
+    #[test]
+    fn test_scoped_monitor_only_captures_its_window() {
+        let mut monitor = IPCMonitor::new();
+        monitor.start_scoped(Some("settings".to_string()));
+        monitor.add_event(make_window_event(1, "main"));
+        monitor.add_event(make_window_event(2, "settings"));
+        monitor.add_event(make_event(3));
+
+        let events = monitor.get_events();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].window_label.as_deref(), Some("settings"));
+
+        // Restarting without a scope captures every window again
+        monitor.start();
+        monitor.add_event(make_window_event(4, "main"));
+        assert_eq!(monitor.scope(), None);
+        assert_eq!(monitor.len(), 1);
+    }
+
+    #[test]
+    fn test_pause_keeps_events_and_resume_continues_capture() {
+        let (sink, mut errors) = broadcast::channel(8);
+        let mut monitor = IPCMonitor::new();
+        monitor.set_error_sink(sink);
+        monitor.start();
+        monitor.add_event(make_event(1));
+
+        monitor.pause().unwrap();
+        monitor.add_event(make_event(2));
+        monitor.add_event(IPCEvent {
+            error: Some("ignored".to_string()),
+            ..make_event(3)
+        });
+        assert_eq!(monitor.len(), 1);
+        assert!(errors.try_recv().is_err());
+
+        monitor.resume().unwrap();
+        assert_eq!(monitor.state(), MonitorState::Capturing);
+        monitor.add_event(make_event(4));
+
+        let commands: Vec<String> = monitor
+            .get_events()
+            .into_iter()
+            .map(|e| e.command)
+            .collect();
+        assert_eq!(commands, vec!["cmd_1", "cmd_4"]);
+    }
+
+    #[test]
+    fn test_pause_requires_running_monitor() {
+        let mut monitor = IPCMonitor::new();
+        assert!(monitor.pause().is_err());
+        assert!(monitor.resume().is_err());
+
+        monitor.start();
+        monitor.pause().unwrap();
+        monitor.stop();
+        assert_eq!(monitor.state(), MonitorState::Stopped);
+
+        // Starting again clears the pause along with the events
+        monitor.start();
+        assert_eq!(monitor.state(), MonitorState::Capturing);
+    }
+
+    #[test]
+    fn test_snapshot_shares_events_without_deep_clone() {
+        let mut monitor = IPCMonitor::new();
+        monitor.start();
+        monitor.add_event(make_event(1));
+
+        let first = monitor.snapshot();
+        let second = monitor.snapshot();
+
+        assert!(Arc::ptr_eq(&first[0], &second[0]));
+        assert_eq!(monitor.get_events()[0].command, "cmd_1");
+    }
+
+    #[test]
+    fn test_concurrent_capture_and_readout() {
+        const EVENTS: u64 = 5_000;
+
+        let monitor: IPCMonitorState = Arc::new(Mutex::new(IPCMonitor::new()));
+        monitor.lock().unwrap().start();
+
+        let writer = {
+            let monitor = monitor.clone();
+            thread::spawn(move || {
+                for i in 0..EVENTS {
+                    monitor.lock().unwrap().add_event(make_event(i));
+                }
+            })
+        };
+
+        let reader = {
+            let monitor = monitor.clone();
+            thread::spawn(move || {
+                let mut last_len = 0;
+                while last_len < EVENTS as usize {
+                    // Only reference counts are bumped under the lock...
+                    let snapshot = monitor.lock().unwrap().snapshot();
+                    // ...serialization happens after it is released
+                    let events: Vec<&IPCEvent> = snapshot.iter().map(|e| e.as_ref()).collect();
+                    let serialized = serde_json::to_string(&events).unwrap();
+                    assert!(!serialized.is_empty());
+                    assert!(snapshot.len() >= last_len);
+                    last_len = snapshot.len();
+                }
+            })
+        };
+
+        writer.join().unwrap();
+        reader.join().unwrap();
+
+        let events = monitor.lock().unwrap().snapshot();
+        assert_eq!(events.len(), EVENTS as usize);
+        assert!(events
+            .iter()
+            .enumerate()
+            .all(|(i, event)| event.timestamp == i as u64));
+    }
+}
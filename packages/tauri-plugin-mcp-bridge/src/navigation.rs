@@ -0,0 +1,216 @@
+//! Navigation tracking for broadcast `navigation` events.
+//!
+//! Every finished page load is broadcast to connected clients as:
+//!
+//! ```json
+//! {
+//!   "type": "navigation",
+//!   "windowLabel": "main",
+//!   "fromUrl": "http://localhost:1420/",
+//!   "toUrl": "http://localhost:1420/settings",
+//!   "timestamp": 1700000000000
+//! }
+//! ```
+//!
+//! `fromUrl` is `null` on the first load of a window. Client-side route
+//! changes through `history.pushState` or the URL hash don't reach the page
+//! load hook. They are reported, with `"clientSide": true`, when
+//! `get_current_url` reads a URL that differs from the last one known.
+
+use crate::monitor::current_timestamp;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast;
+
+/// A URL transition in a single window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NavigationEvent {
+    /// Always `"navigation"`
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// Label of the window that navigated
+    pub window_label: String,
+    /// URL before the navigation, `None` on the first load
+    pub from_url: Option<String>,
+    /// URL after the navigation
+    pub to_url: String,
+    /// Set for client-side route changes found by `get_current_url`
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub client_side: bool,
+    /// Unix timestamp in milliseconds
+    pub timestamp: u64,
+}
+
+/// The URLs known for one window.
+struct WindowUrls {
+    /// URL of the last finished page load
+    loaded: String,
+    /// Latest URL, including client-side route changes since the load
+    current: String,
+}
+
+/// Remembers the last URL of each window and broadcasts transitions.
+pub struct NavigationTracker {
+    urls: Mutex<HashMap<String, WindowUrls>>,
+    events: broadcast::Sender<String>,
+}
+
+impl NavigationTracker {
+    /// Creates a tracker that broadcasts on `events`.
+    pub fn new(events: broadcast::Sender<String>) -> Self {
+        Self {
+            urls: Mutex::new(HashMap::new()),
+            events,
+        }
+    }
+
+    /// Records that `window_label` finished loading `url` and broadcasts the
+    /// resulting transition.
+    pub fn page_loaded(&self, window_label: &str, url: &str) -> NavigationEvent {
+        let previous = self.urls.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            window_label.to_string(),
+            WindowUrls {
+                loaded: url.to_string(),
+                current: url.to_string(),
+            },
+        );
+
+        self.broadcast(window_label, previous.map(|urls| urls.current), url, false)
+    }
+
+    /// Records the URL a window's page reports for itself (`location.href`),
+    /// and broadcasts a client-side navigation when it changed.
+    ///
+    /// Ignored until the window's first page load has been recorded.
+    ///
+    /// # Returns
+    ///
+    /// The broadcast event, or `None` if the URL is unchanged or unknown.
+    pub fn url_observed(&self, window_label: &str, url: &str) -> Option<NavigationEvent> {
+        let from_url = {
+            let mut urls = self.urls.lock().unwrap_or_else(|e| e.into_inner());
+            let known = urls.get_mut(window_label)?;
+            if known.current == url {
+                return None;
+            }
+            std::mem::replace(&mut known.current, url.to_string())
+        };
+
+        Some(self.broadcast(window_label, Some(from_url), url, true))
+    }
+
+    /// Returns the best known URL of a window.
+    ///
+    /// The native URL can miss client-side route changes, so the URL last
+    /// reported by the page is preferred while the native URL still matches
+    /// the page load it was reported on. Once the window has loaded another
+    /// page, the native URL is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_label` - The window
+    /// * `native_url` - The URL reported by the webview
+    pub fn current_url(&self, window_label: &str, native_url: Option<String>) -> Option<String> {
+        let urls = self.urls.lock().unwrap_or_else(|e| e.into_inner());
+        match urls.get(window_label) {
+            Some(known) if native_url.as_deref() == Some(known.loaded.as_str()) => {
+                Some(known.current.clone())
+            }
+            _ => native_url,
+        }
+    }
+
+    /// Broadcasts a URL transition to connected clients.
+    fn broadcast(
+        &self,
+        window_label: &str,
+        from_url: Option<String>,
+        to_url: &str,
+        client_side: bool,
+    ) -> NavigationEvent {
+        let event = NavigationEvent {
+            kind: "navigation",
+            window_label: window_label.to_string(),
+            from_url,
+            to_url: to_url.to_string(),
+            client_side,
+            timestamp: current_timestamp(),
+        };
+
+        if let Ok(frame) = serde_json::to_string(&event) {
+            // No receivers just means no client is connected
+            let _ = self.events.send(frame);
+        }
+
+        event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    #[test]
+    fn test_navigation_frames_carry_url_transition() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let tracker = NavigationTracker::new(tx);
+
+        tracker.page_loaded("main", "http://localhost/");
+        tracker.page_loaded("other", "http://localhost/other");
+        tracker.page_loaded("main", "http://localhost/settings");
+
+        let first: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(first["type"], "navigation");
+        assert_eq!(first["windowLabel"], "main");
+        assert!(first["fromUrl"].is_null());
+        assert_eq!(first["toUrl"], "http://localhost/");
+        assert!(first["timestamp"].as_u64().unwrap() > 0);
+
+        let other: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert!(other["fromUrl"].is_null());
+
+        let second: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(second["windowLabel"], "main");
+        assert_eq!(second["fromUrl"], "http://localhost/");
+        assert_eq!(second["toUrl"], "http://localhost/settings");
+        assert!(second.get("clientSide").is_none());
+    }
+
+    #[test]
+    fn test_pushed_url_replaces_stale_native_url() {
+        let (tx, mut rx) = broadcast::channel(8);
+        let tracker = NavigationTracker::new(tx);
+        let loaded = "http://localhost/app".to_string();
+        tracker.page_loaded("main", &loaded);
+        rx.try_recv().unwrap();
+
+        // `history.pushState` moved the page, the webview still reports the load URL
+        let pushed = "http://localhost/app/users?page=2#top";
+        let event = tracker.url_observed("main", pushed).unwrap();
+        assert!(event.client_side);
+        assert_eq!(event.from_url.as_deref(), Some("http://localhost/app"));
+        assert_eq!(
+            tracker.current_url("main", Some(loaded.clone())).as_deref(),
+            Some(pushed)
+        );
+        let frame: Value = serde_json::from_str(&rx.try_recv().unwrap()).unwrap();
+        assert_eq!(frame["clientSide"], true);
+        assert_eq!(frame["toUrl"], pushed);
+
+        // Reading the same URL again is not a navigation
+        assert!(tracker.url_observed("main", pushed).is_none());
+        assert!(rx.try_recv().is_err());
+
+        // A later page load makes the native URL authoritative again
+        let next = "http://localhost/login".to_string();
+        assert_eq!(
+            tracker.current_url("main", Some(next.clone())),
+            Some(next.clone())
+        );
+        let reloaded = tracker.page_loaded("main", &next);
+        assert_eq!(reloaded.from_url.as_deref(), Some(pushed));
+    }
+}
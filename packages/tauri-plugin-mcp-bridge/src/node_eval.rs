@@ -0,0 +1,110 @@
+//! Runs generated page scripts under Node.js, so tests can check what the
+//! scripts do instead of what they contain.
+//!
+//! Scripts run as classic scripts in a fresh `vm` context standing in for a
+//! webview: `window` is the context itself, `document.querySelector` finds
+//! an `h1` and nothing else, and the network and storage globals are
+//! harmless stubs. Needs `node` on the `PATH`; tests check
+//! [`node_available`] first and pass without running when it is missing.
+
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+/// Reads a script from stdin, evaluates it in the stand-in page and prints
+/// `{ value }` once it settled.
+const DRIVER: &str = r#"
+const vm = require('vm');
+let source = '';
+process.stdin.setEncoding('utf8');
+process.stdin.on('data', (chunk) => { source += chunk; });
+process.stdin.on('end', async () => {
+  const stub = (name) => function () { return name + ' called'; };
+  const context = {
+    console, setTimeout, clearTimeout, TextEncoder, btoa, Blob,
+    requestAnimationFrame: (callback) => setTimeout(callback, 0),
+    document: {
+      title: 'Test page',
+      querySelector: (selector) => (selector === 'h1' ? { tagName: 'H1', textContent: 'Hello' } : null),
+    },
+    fetch: stub('fetch'),
+    XMLHttpRequest: stub('XMLHttpRequest'),
+    WebSocket: stub('WebSocket'),
+    localStorage: { getItem: stub('getItem') },
+    sessionStorage: { getItem: stub('getItem') },
+  };
+  context.window = context;
+  vm.createContext(context);
+  let value;
+  try {
+    value = await vm.runInContext(source, context);
+  } catch (e) {
+    value = { thrown: String(e) };
+  }
+  // Let callbacks scheduled by the script run
+  await new Promise((resolve) => setTimeout(resolve, 20));
+  process.stdout.write(JSON.stringify({ value: value === undefined ? null : value }));
+});
+"#;
+
+/// What a script did in the stand-in page.
+#[derive(Debug)]
+pub(crate) struct PageRun {
+    /// The script's completion value, awaited if it is a promise. A script
+    /// that throws while being evaluated yields `{ "thrown": message }`.
+    pub value: Value,
+}
+
+impl PageRun {
+    /// Parses the completion value, which the `execute_js` wrappers leave as
+    /// a JSON string.
+    pub fn json(&self) -> Value {
+        let text = self
+            .value
+            .as_str()
+            .unwrap_or_else(|| panic!("script did not produce a JSON string: {}", self.value));
+        serde_json::from_str(text).unwrap()
+    }
+}
+
+/// Returns whether `node` can be run, noting once on stderr when it can't.
+///
+/// Tests using [`run_in_page`] return early when this is false, so
+/// `cargo test` works on machines without Node.js.
+pub(crate) fn node_available() -> bool {
+    static AVAILABLE: OnceLock<bool> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| {
+        let available = Command::new("node")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        if !available {
+            eprintln!("node is not on the PATH; skipping tests that run page scripts");
+        }
+        available
+    })
+}
+
+/// Evaluates `script` in the stand-in page.
+pub(crate) fn run_in_page(script: &str) -> PageRun {
+    let mut node = Command::new("node")
+        .args(["-e", DRIVER])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("these tests run page scripts with Node.js, which must be on the PATH");
+    node.stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+    let output = node.wait_with_output().unwrap();
+    assert!(output.status.success(), "node failed on:\n{script}");
+
+    let mut run: Value = serde_json::from_slice(&output.stdout).unwrap();
+    PageRun {
+        value: run["value"].take(),
+    }
+}
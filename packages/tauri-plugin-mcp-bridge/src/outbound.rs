@@ -0,0 +1,328 @@
+//! Backpressure for frames sent to a client.
+//!
+//! Every connection has its own send task, so one client can't block
+//! another's writes. A client that stops reading still holds on to server
+//! resources, though: its socket buffer fills, writes stall, and its queue of
+//! pending broadcasts grows. Two limits stop that:
+//!
+//! - Each write must complete within [`WRITE_TIMEOUT`]
+//! - At most [`OUTBOUND_QUEUE_CAPACITY`] broadcast events, and as many
+//!   responses and streamed frames in the client's [`Outbox`], may be
+//!   waiting for the client; falling further behind means it can't keep up
+//!
+//! A client that hits any limit is disconnected with
+//! [`CloseReason::SlowConsumer`](crate::close_reason::CloseReason::SlowConsumer).
+
+use futures_util::{Sink, SinkExt};
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{
+    self,
+    error::{TryRecvError, TrySendError},
+};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Longest a single frame write may take before the client is dropped.
+pub const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Broadcast events, and frames in the [`Outbox`], buffered per client
+/// before it counts as too slow.
+///
+/// A power of two, since the broadcast channel rounds its capacity up to one.
+pub const OUTBOUND_QUEUE_CAPACITY: usize = 128;
+
+/// Why a frame could not be delivered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutboundError {
+    /// The write didn't complete in time; the client isn't reading
+    Timeout,
+    /// The client fell too far behind on broadcasts
+    Lagged(u64),
+    /// The client's [`Outbox`] was full
+    QueueFull,
+    /// The connection failed
+    Failed(String),
+}
+
+impl OutboundError {
+    /// Whether the client should be closed as a slow consumer.
+    pub fn is_slow_consumer(&self) -> bool {
+        matches!(self, Self::Timeout | Self::Lagged(_) | Self::QueueFull)
+    }
+}
+
+impl Display for OutboundError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "write timed out after {WRITE_TIMEOUT:?}"),
+            Self::Lagged(skipped) => write!(f, "fell {skipped} broadcast events behind"),
+            Self::QueueFull => write!(
+                f,
+                "left {OUTBOUND_QUEUE_CAPACITY} responses and frames unread"
+            ),
+            Self::Failed(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+/// Sends `frame`, giving up after `timeout`.
+pub async fn send_frame<S>(
+    sink: &mut S,
+    frame: Message,
+    timeout: Duration,
+) -> Result<(), OutboundError>
+where
+    S: Sink<Message> + Unpin,
+    S::Error: Display,
+{
+    match tokio::time::timeout(timeout, sink.send(frame)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(e)) => Err(OutboundError::Failed(e.to_string())),
+        Err(_) => Err(OutboundError::Timeout),
+    }
+}
+
+impl From<RecvError> for OutboundError {
+    /// An overflowed queue means the client is too slow; a closed channel
+    /// means the server is going away.
+    fn from(error: RecvError) -> Self {
+        match error {
+            RecvError::Lagged(skipped) => Self::Lagged(skipped),
+            RecvError::Closed => Self::Failed("broadcast channel closed".to_string()),
+        }
+    }
+}
+
+/// Creates a client's queue of responses and streamed frames.
+///
+/// # Returns
+///
+/// The [`Outbox`] producers queue frames in, and the [`OutboxReceiver`] the
+/// connection's send task reads them from
+pub fn outbox() -> (Outbox, OutboxReceiver) {
+    let (frames, frames_rx) = mpsc::channel(OUTBOUND_QUEUE_CAPACITY);
+    let overflow = Arc::new(Overflow::default());
+    (
+        Outbox {
+            frames,
+            overflow: overflow.clone(),
+        },
+        OutboxReceiver {
+            frames: frames_rx,
+            overflow,
+        },
+    )
+}
+
+/// Set once a frame didn't fit in an [`Outbox`].
+#[derive(Debug, Default)]
+struct Overflow {
+    overflowed: AtomicBool,
+    notify: Notify,
+}
+
+/// Queues responses and streamed frames for a client.
+///
+/// Holds at most [`OUTBOUND_QUEUE_CAPACITY`] frames. A frame that doesn't fit
+/// isn't waited for; the receiver stops instead, so the client is closed as
+/// a slow consumer.
+#[derive(Debug, Clone)]
+pub struct Outbox {
+    frames: mpsc::Sender<Message>,
+    overflow: Arc<Overflow>,
+}
+
+impl Outbox {
+    /// Queues `frame` for the client.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The frame is queued
+    /// * `Err(OutboundError::QueueFull)` - The queue is full
+    /// * `Err(OutboundError::Failed)` - The connection is gone
+    pub fn send(&self, frame: Message) -> Result<(), OutboundError> {
+        self.frames.try_send(frame).map_err(|e| match e {
+            TrySendError::Full(_) => {
+                self.overflow.overflowed.store(true, Ordering::SeqCst);
+                self.overflow.notify.notify_one();
+                OutboundError::QueueFull
+            }
+            TrySendError::Closed(_) => OutboundError::Failed("connection closed".to_string()),
+        })
+    }
+}
+
+/// Receiving end of an [`Outbox`].
+#[derive(Debug)]
+pub struct OutboxReceiver {
+    frames: mpsc::Receiver<Message>,
+    overflow: Arc<Overflow>,
+}
+
+impl OutboxReceiver {
+    /// Waits for the next frame.
+    ///
+    /// # Returns
+    ///
+    /// The next frame, or `None` once every [`Outbox`] was dropped or one of
+    /// them [`overflowed`](Self::overflowed)
+    pub async fn recv(&mut self) -> Option<Message> {
+        if self.overflowed() {
+            return None;
+        }
+        tokio::select! {
+            biased;
+            () = self.overflow.notify.notified() => None,
+            frame = self.frames.recv() => frame,
+        }
+    }
+
+    /// Takes the next frame if one is queued.
+    pub fn try_recv(&mut self) -> Result<Message, TryRecvError> {
+        self.frames.try_recv()
+    }
+
+    /// Whether a frame didn't fit in the queue.
+    pub fn overflowed(&self) -> bool {
+        self.overflow.overflowed.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::broadcast;
+    use tokio_tungstenite::WebSocketStream;
+
+    /// A server socket whose client completes the handshake, then never
+    /// reads another byte.
+    async fn never_reading_client() -> (WebSocketStream<TcpStream>, tokio::task::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+
+        let client = tokio::spawn(async move {
+            let (stream, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            drop(stream);
+        });
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let server = tokio_tungstenite::accept_async(socket).await.unwrap();
+        (server, client)
+    }
+
+    #[tokio::test]
+    async fn test_client_that_never_reads_is_dropped() {
+        let (mut server, client) = never_reading_client().await;
+
+        let payload = vec![0u8; 256 * 1024];
+        let mut outcome = Ok(());
+        for _ in 0..1024 {
+            outcome = send_frame(
+                &mut server,
+                Message::Binary(payload.clone().into()),
+                Duration::from_millis(200),
+            )
+            .await;
+            if outcome.is_err() {
+                break;
+            }
+        }
+
+        assert_eq!(outcome, Err(OutboundError::Timeout));
+        assert!(outcome.unwrap_err().is_slow_consumer());
+        client.abort();
+    }
+
+    #[tokio::test]
+    async fn test_client_that_never_reads_a_stream_is_dropped() {
+        let (mut server, client) = never_reading_client().await;
+        let (outbox, mut frames) = outbox();
+
+        // Queues frames like a screencast, far faster than they are written
+        let producer = tokio::spawn(async move {
+            let payload = vec![0u8; 256 * 1024];
+            loop {
+                if let Err(e) = outbox.send(Message::Binary(payload.clone().into())) {
+                    break e;
+                }
+                tokio::task::yield_now().await;
+            }
+        });
+
+        // What the connection's send task does with the outbox
+        let outcome = loop {
+            let Some(frame) = frames.recv().await else {
+                break if frames.overflowed() {
+                    Err(OutboundError::QueueFull)
+                } else {
+                    Ok(())
+                };
+            };
+            if let Err(e) = send_frame(&mut server, frame, Duration::from_millis(200)).await {
+                break Err(e);
+            }
+        };
+
+        // The queue filled up instead of growing
+        assert_eq!(producer.await.unwrap(), OutboundError::QueueFull);
+        assert!(outcome.unwrap_err().is_slow_consumer());
+        client.abort();
+    }
+
+    #[test]
+    fn test_full_outbox_counts_as_slow() {
+        let (outbox, mut frames) = outbox();
+        for _ in 0..OUTBOUND_QUEUE_CAPACITY {
+            outbox.send(Message::Text("frame".into())).unwrap();
+        }
+
+        let error = outbox.send(Message::Text("frame".into())).unwrap_err();
+
+        assert_eq!(error, OutboundError::QueueFull);
+        assert!(error.is_slow_consumer());
+        assert!(frames.overflowed());
+        let next = futures_util::FutureExt::now_or_never(frames.recv());
+        assert_eq!(next, Some(None));
+    }
+
+    #[tokio::test]
+    async fn test_reading_client_receives_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = tokio::spawn(async move {
+            let stream = TcpStream::connect(addr).await.unwrap();
+            let (mut ws, _) = tokio_tungstenite::client_async(format!("ws://{addr}"), stream)
+                .await
+                .unwrap();
+            futures_util::StreamExt::next(&mut ws).await
+        });
+
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut server = tokio_tungstenite::accept_async(socket).await.unwrap();
+        let sent = send_frame(&mut server, Message::Text("hi".into()), WRITE_TIMEOUT).await;
+
+        assert_eq!(sent, Ok(()));
+        let received = client.await.unwrap().unwrap().unwrap();
+        assert_eq!(received.into_text().unwrap().as_str(), "hi");
+    }
+
+    #[tokio::test]
+    async fn test_overflowing_queue_counts_as_slow() {
+        let (tx, mut rx) = broadcast::channel(OUTBOUND_QUEUE_CAPACITY);
+        for i in 0..OUTBOUND_QUEUE_CAPACITY + 5 {
+            tx.send(i).unwrap();
+        }
+
+        let error = OutboundError::from(rx.recv().await.unwrap_err());
+
+        assert_eq!(error, OutboundError::Lagged(5));
+        assert!(error.is_slow_consumer());
+        assert!(!OutboundError::Failed("reset".to_string()).is_slow_consumer());
+    }
+}
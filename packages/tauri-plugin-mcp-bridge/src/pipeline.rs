@@ -0,0 +1,874 @@
+//! The stages a WebSocket request goes through.
+//!
+//! Each text frame is handled as
+//!
+//! 1. [`parse_request`] - JSON text to a [`WsRequest`]
+//! 2. [`route`] - strips the command namespace, dropping commands meant for
+//!    other consumers of the connection
+//! 3. [`authorize`] - checks the request against the connection's
+//!    [`AuthGate`]
+//! 4. [`intercept`] - runs the operator's
+//!    [`RequestInterceptor`], which may rewrite or refuse the request
+//! 5. [`serve`](crate::dispatch::serve) - waits for one of the connection's
+//!    [`RequestSlots`], then runs the command within
+//!    [`ConnState::request_timeout`], producing a [`WsResponse`]
+//! 6. [`ConnState::respond`] or a [`Responder`] - encodes the response in
+//!    the connection's wire format and queues it for sending
+//!
+//! Connections that turned on tracing with `set_trace` also get a `debug`
+//! field in each response, see [`RequestTrace`].
+//!
+//! The connection loop in [`websocket`](crate::websocket) only reads frames
+//! and wires the stages together, so each stage can be tested without a
+//! socket.
+
+use crate::auth::{self, AuthGate, AuthOutcome};
+use crate::capabilities::Capabilities;
+use crate::clients::{ClientHandle, ClientRegistry};
+use crate::close_reason::CloseReason;
+use crate::command_args::take_window_match;
+use crate::commands::WindowMatch;
+use crate::config::{DEFAULT_COMMAND_TIMEOUT, DEFAULT_MAX_CONCURRENT_REQUESTS};
+use crate::element_handles::ElementHandles;
+use crate::intercept::{InterceptDecision, RequestInterceptor};
+use crate::outbound::{outbox, Outbox, OutboxReceiver};
+use crate::response_format::ResponseFormat;
+use crate::screencast::Screencasts;
+use crate::subscriptions::TopicFilter;
+use crate::watch::Watches;
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+
+/// A parsed request frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WsRequest {
+    /// The request id echoed in the response; empty when missing
+    pub id: String,
+    /// The command name; `"unknown"` when missing
+    pub command: String,
+    /// The whole request, with `windowMatch` taken out of its arguments
+    pub body: Value,
+    /// The `windowMatch` criteria, or why they are malformed
+    pub window_match: Result<Option<WindowMatch>, String>,
+}
+
+impl WsRequest {
+    /// The request's `args`, if any.
+    pub fn args(&self) -> Option<&Value> {
+        self.body.get("args")
+    }
+
+    /// The `timeoutMs` the request carries next to `command`, if any.
+    pub fn timeout_ms(&self) -> Option<u64> {
+        self.body.get("timeoutMs").and_then(Value::as_u64)
+    }
+}
+
+/// Why a request frame wasn't dispatched.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum WsError {
+    /// The frame isn't a JSON object; it gets a parse error response with
+    /// an empty id
+    #[error("Failed to parse command: {0}")]
+    Malformed(String),
+
+    /// The command is outside the bridge's namespace; it is dropped without
+    /// a response
+    #[error("Ignoring command outside the namespace: {0}")]
+    Foreign(String),
+
+    /// The connection must authenticate to run the command; it gets an
+    /// `unauthorized` error response
+    #[error("Denied unauthenticated {0}")]
+    Unauthenticated(String),
+
+    /// The connection presented a wrong token; it is closed
+    #[error("Rejected unauthenticated {0}")]
+    Unauthorized(String),
+
+    /// The request interceptor refused the command; it gets an error
+    /// response carrying the reason
+    #[error("Interceptor denied {command}: {reason}")]
+    Denied { command: String, reason: String },
+}
+
+impl WsError {
+    /// The Close frame reason the error ends the connection with, if any.
+    pub fn close_reason(&self) -> Option<CloseReason> {
+        match self {
+            Self::Malformed(_)
+            | Self::Foreign(_)
+            | Self::Unauthenticated(_)
+            | Self::Denied { .. } => None,
+            Self::Unauthorized(_) => Some(CloseReason::Unauthorized),
+        }
+    }
+
+    /// The error response sent for a request that isn't dispatched, if any.
+    pub fn response(&self, id: &str) -> Option<WsResponse> {
+        match self {
+            Self::Malformed(_) => Some(WsResponse::error(
+                id,
+                "Failed to parse request: expected a JSON object",
+            )),
+            Self::Unauthenticated(_) => Some(WsResponse::error(id, "unauthorized")),
+            Self::Denied { reason, .. } => {
+                Some(WsResponse::error(id, format!("Request denied: {reason}")))
+            }
+            Self::Foreign(_) | Self::Unauthorized(_) => None,
+        }
+    }
+}
+
+/// The response to a dispatched request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WsResponse {
+    /// `{ id, success, data | error, ... }`
+    pub body: Value,
+    /// Wire format a `hello` switched to, applied after this response is sent
+    pub format: Option<ResponseFormat>,
+    /// Frames sent right after `body`, such as the
+    /// [`chunks`](crate::chunked) of an oversized result
+    pub chunks: Vec<Value>,
+    /// The window the request named, matched or defaulted to, if any
+    pub window_label: Option<String>,
+}
+
+impl WsResponse {
+    /// A response that leaves the wire format unchanged.
+    pub fn new(body: Value) -> Self {
+        Self {
+            body,
+            format: None,
+            chunks: Vec::new(),
+            window_label: None,
+        }
+    }
+
+    /// An error response to request `id`, see [`error_body`].
+    pub fn error(id: &str, error: impl Into<String>) -> Self {
+        Self::new(error_body(id, error))
+    }
+}
+
+/// `{ id, success: false, error }`, the body of every error response.
+pub fn error_body(id: &str, error: impl Into<String>) -> Value {
+    serde_json::json!({
+        "id": id,
+        "success": false,
+        "error": error.into()
+    })
+}
+
+/// A traced request as it was dispatched, with the time it spent in each
+/// stage, echoed in the `debug` field of its response.
+#[derive(Debug, Clone)]
+pub struct RequestTrace {
+    command: String,
+    args: Option<Value>,
+    received: Instant,
+    admitted: Instant,
+}
+
+impl RequestTrace {
+    /// Adds `debug` to a dispatched request's response.
+    ///
+    /// `debug` is `{ command, args, windowLabel, timing }`, where `command`
+    /// and `args` are the request as dispatched, after the namespace was
+    /// stripped and the interceptor ran, and `windowLabel` is the window the
+    /// request targeted, or `null` for commands without one. `timing` holds
+    /// `admitMs` (parsing, authorization and interception), `dispatchMs`
+    /// (waiting for a free request slot and running the command) and
+    /// `totalMs`, with microsecond precision.
+    pub fn annotate(&self, response: &mut WsResponse) {
+        let dispatched = Instant::now();
+        let window_label = response.window_label.clone().or_else(|| {
+            response
+                .body
+                .pointer("/windowContext/windowLabel")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+        if let Some(body) = response.body.as_object_mut() {
+            body.insert(
+                "debug".to_string(),
+                serde_json::json!({
+                    "command": self.command,
+                    "args": self.args,
+                    "windowLabel": window_label,
+                    "timing": {
+                        "admitMs": millis(self.admitted - self.received),
+                        "dispatchMs": millis(dispatched - self.admitted),
+                        "totalMs": millis(dispatched - self.received)
+                    }
+                }),
+            );
+        }
+    }
+}
+
+/// A duration in milliseconds, rounded to the microsecond.
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+/// Limits how many commands a connection runs at once, see
+/// [`Config::max_concurrent_requests`](crate::config::Config::max_concurrent_requests).
+///
+/// Slots are handed out in the order they are asked for, so a request never
+/// overtakes one that started waiting before it.
+#[derive(Debug, Clone)]
+pub struct RequestSlots {
+    semaphore: Arc<Semaphore>,
+    size: u32,
+}
+
+impl RequestSlots {
+    /// Creates `max` slots; 0 counts as 1.
+    pub fn new(max: usize) -> Self {
+        let size = max.clamp(1, u32::MAX as usize) as u32;
+        Self {
+            semaphore: Arc::new(Semaphore::new(size as usize)),
+            size,
+        }
+    }
+
+    /// Waits for a free slot, held until the permit is dropped.
+    pub async fn acquire(&self) -> OwnedSemaphorePermit {
+        self.semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("request slots are never closed")
+    }
+
+    /// Waits until every running command is answered, then holds all slots
+    /// until the permit is dropped.
+    pub async fn acquire_all(&self) -> OwnedSemaphorePermit {
+        self.semaphore
+            .clone()
+            .acquire_many_owned(self.size)
+            .await
+            .expect("request slots are never closed")
+    }
+}
+
+/// Queues responses for a connection from outside its loop, for commands
+/// that run alongside it.
+#[derive(Clone)]
+pub struct Responder {
+    format: ResponseFormat,
+    responses: Outbox,
+}
+
+impl Responder {
+    /// Queues a response and the frames following it.
+    pub fn respond(&self, response: &WsResponse) {
+        for frame in std::iter::once(&response.body).chain(&response.chunks) {
+            let _ = self.responses.send(self.format.encode_response(frame));
+        }
+    }
+}
+
+/// Per-connection state shared by the stages.
+pub struct ConnState {
+    /// The connection's entry in the client registry
+    pub client: ClientHandle,
+    /// Every connected client, for `get_clients`
+    pub clients: ClientRegistry,
+    /// Features announced in the `capabilities` frame
+    pub capabilities: Capabilities,
+    /// Authentication state, from
+    /// [`Config::auth_token`](crate::config::Config::auth_token) and
+    /// [`Config::read_only_commands`](crate::config::Config::read_only_commands)
+    pub auth: AuthGate,
+    /// The address the client reached us on, for download URLs
+    pub local_addr: SocketAddr,
+    /// Current wire format
+    pub format: ResponseFormat,
+    /// Publishes the wire format to the send task, for broadcasts
+    pub format_tx: watch::Sender<ResponseFormat>,
+    /// Broadcast topics the connection subscribed to
+    pub topics: watch::Sender<TopicFilter>,
+    /// Frames queued for the send task
+    pub responses: Outbox,
+    /// Screencasts started on this connection, stopped when it is dropped
+    pub screencasts: Screencasts,
+    /// Watches started on this connection, stopped when it is dropped
+    pub watches: Watches,
+    /// Element handles created on this connection, released when it closes
+    pub element_handles: ElementHandles,
+    /// Prefix the bridge's commands carry, as in `mcp.execute_js`
+    pub namespace: Option<String>,
+    /// Whether MCP JSON-RPC messages are answered, see [`crate::mcp`]
+    pub mcp_mode: bool,
+    /// Window targeted by commands without `windowLabel`, set with
+    /// `set_default_window`. `None` falls back to `"main"`.
+    pub default_window: Option<String>,
+    /// Longest any command may run, from
+    /// [`Config::command_timeout`](crate::config::Config::command_timeout)
+    pub command_timeout: Duration,
+    /// Run time of requests without `timeoutMs`, set with `set_timeout`.
+    /// `None` falls back to `command_timeout`.
+    pub default_timeout: Option<Duration>,
+    /// Hook deciding on each request, from
+    /// [`Config::request_interceptor`](crate::config::Config::request_interceptor)
+    pub interceptor: Option<RequestInterceptor>,
+    /// Commands allowed to run at once, from
+    /// [`Config::max_concurrent_requests`](crate::config::Config::max_concurrent_requests)
+    pub request_slots: RequestSlots,
+    /// Whether inbound frames are logged and responses carry a `debug`
+    /// field, set with `set_trace`
+    pub trace: bool,
+}
+
+impl ConnState {
+    /// Creates the state of a new connection, registering it in `clients`.
+    ///
+    /// # Returns
+    ///
+    /// The state, and the receiving end of its outgoing frame queue
+    pub fn new(
+        clients: ClientRegistry,
+        address: SocketAddr,
+        local_addr: SocketAddr,
+        capabilities: Capabilities,
+        auth: AuthGate,
+    ) -> (Self, OutboxReceiver) {
+        let (responses, responses_rx) = outbox();
+        let format = ResponseFormat::default();
+        let client = clients.register(address);
+        let element_handles = ElementHandles::new(client.id());
+        let state = Self {
+            client,
+            clients,
+            capabilities,
+            auth,
+            local_addr,
+            format,
+            format_tx: watch::channel(format).0,
+            topics: watch::channel(TopicFilter::default()).0,
+            responses,
+            screencasts: Screencasts::default(),
+            watches: Watches::default(),
+            element_handles,
+            namespace: None,
+            mcp_mode: false,
+            default_window: None,
+            command_timeout: DEFAULT_COMMAND_TIMEOUT,
+            default_timeout: None,
+            interceptor: None,
+            request_slots: RequestSlots::new(DEFAULT_MAX_CONCURRENT_REQUESTS),
+            trace: false,
+        };
+        (state, responses_rx)
+    }
+
+    /// How long `request` may run: its own `timeoutMs`, else the
+    /// connection's default, never more than `command_timeout`.
+    ///
+    /// Requests passing `timeoutMs` in their `args`, such as
+    /// `wait_for_event`, bound their own wait, so only the cap applies to
+    /// them.
+    pub fn request_timeout(&self, request: &WsRequest) -> Duration {
+        let own_wait = request
+            .args()
+            .and_then(|args| args.get("timeoutMs"))
+            .is_some_and(|ms| !ms.is_null());
+        let timeout = match request.timeout_ms() {
+            Some(ms) => Duration::from_millis(ms),
+            None if own_wait => self.command_timeout,
+            None => self.default_timeout.unwrap_or(self.command_timeout),
+        };
+        timeout.min(self.command_timeout)
+    }
+
+    /// Starts tracing an admitted request, if the connection turned tracing
+    /// on.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request about to be dispatched
+    /// * `received` - When its frame arrived
+    ///
+    /// # Returns
+    ///
+    /// The trace to [`annotate`](RequestTrace::annotate) its response with,
+    /// or `None` when tracing is off
+    pub fn trace_request(&self, request: &WsRequest, received: Instant) -> Option<RequestTrace> {
+        self.trace.then(|| RequestTrace {
+            command: request.command.clone(),
+            args: request.args().cloned(),
+            received,
+            admitted: Instant::now(),
+        })
+    }
+
+    /// A [`Responder`] sending in the current wire format.
+    pub fn responder(&self) -> Responder {
+        Responder {
+            format: self.format,
+            responses: self.responses.clone(),
+        }
+    }
+
+    /// Queues a response and the frames following it in the current wire
+    /// format, then switches to the format it negotiated, if any.
+    pub fn respond(&mut self, response: WsResponse) {
+        self.responder().respond(&response);
+
+        if let Some(format) = response.format {
+            self.format = format;
+            self.format_tx.send_replace(format);
+        }
+    }
+}
+
+/// Checks the default run time a connection asks for with `set_timeout`.
+///
+/// # Arguments
+///
+/// * `default_ms` - The requested default in milliseconds, or `None` to go
+///   back to `command_timeout`
+/// * `command_timeout` - The global cap
+///
+/// # Returns
+///
+/// * `Ok(Option<Duration>)` - The connection's new default
+/// * `Err(String)` - If `default_ms` is 0 or exceeds `command_timeout`
+pub fn connection_timeout(
+    default_ms: Option<u64>,
+    command_timeout: Duration,
+) -> Result<Option<Duration>, String> {
+    let max_ms = command_timeout.as_millis();
+    match default_ms {
+        Some(0) => Err("defaultMs must be greater than 0".to_string()),
+        Some(ms) if u128::from(ms) > max_ms => Err(format!(
+            "defaultMs {ms} exceeds the maximum command timeout of {max_ms}ms"
+        )),
+        default_ms => Ok(default_ms.map(Duration::from_millis)),
+    }
+}
+
+/// Parses a request frame.
+///
+/// # Returns
+///
+/// * `Ok(WsRequest)` - The request; a malformed `windowMatch` is kept in
+///   [`WsRequest::window_match`] so it can be reported to the client
+/// * `Err(WsError::Malformed)` - If the text isn't a JSON object
+pub fn parse_request(text: &str) -> Result<WsRequest, WsError> {
+    let mut body: Value =
+        serde_json::from_str(text).map_err(|_| WsError::Malformed(text.into()))?;
+    if !body.is_object() {
+        return Err(WsError::Malformed(text.to_string()));
+    }
+
+    let window_match = take_window_match(&mut body);
+    let id = body.get("id").and_then(Value::as_str).unwrap_or("");
+    let command = body
+        .get("command")
+        .and_then(Value::as_str)
+        .unwrap_or("unknown");
+    Ok(WsRequest {
+        id: id.to_string(),
+        command: command.to_string(),
+        body,
+        window_match,
+    })
+}
+
+/// Strips the command namespace from a request.
+///
+/// # Arguments
+///
+/// * `request` - The parsed request
+/// * `namespace` - The namespace the bridge's commands are sent under, if any
+///
+/// # Returns
+///
+/// * `Ok(WsRequest)` - The request, with `command` unqualified
+/// * `Err(WsError::Foreign)` - If the command isn't in the namespace
+pub fn route(mut request: WsRequest, namespace: Option<&str>) -> Result<WsRequest, WsError> {
+    let Some(namespace) = namespace else {
+        return Ok(request);
+    };
+    match request
+        .command
+        .strip_prefix(namespace)
+        .and_then(|rest| rest.strip_prefix('.'))
+    {
+        Some(command) if !command.is_empty() => {
+            request.command = command.to_string();
+            Ok(request)
+        }
+        _ => Err(WsError::Foreign(request.command)),
+    }
+}
+
+/// The name a client sends `command` under.
+pub fn qualified_command(namespace: Option<&str>, command: &str) -> String {
+    match namespace {
+        Some(namespace) => format!("{namespace}.{command}"),
+        None => command.to_string(),
+    }
+}
+
+/// Checks that the connection may run a request, recording a successful
+/// `authenticate`.
+///
+/// # Returns
+///
+/// * `Ok(())` - Dispatch the request
+/// * `Err(WsError::Unauthenticated)` - Respond with an `unauthorized` error
+/// * `Err(WsError::Unauthorized)` - Close the connection
+pub fn authorize(request: &WsRequest, conn: &mut ConnState) -> Result<(), WsError> {
+    let token = auth::token_arg(&request.body);
+    let command = auth::gated_command(&request.command, &request.body);
+    match conn.auth.check(command, token) {
+        AuthOutcome::Allowed | AuthOutcome::Authenticated => Ok(()),
+        AuthOutcome::Denied => Err(WsError::Unauthenticated(command.to_string())),
+        AuthOutcome::Rejected => Err(WsError::Unauthorized(request.command.clone())),
+    }
+}
+
+/// Lets the connection's [`RequestInterceptor`], if any, rewrite and decide
+/// on an authorized request.
+///
+/// # Returns
+///
+/// * `Ok(())` - Dispatch the request as the interceptor left it
+/// * `Err(WsError::Denied)` - Respond with the interceptor's reason
+pub fn intercept(request: &mut WsRequest, conn: &ConnState) -> Result<(), WsError> {
+    let Some(interceptor) = &conn.interceptor else {
+        return Ok(());
+    };
+    match interceptor.decide(request) {
+        InterceptDecision::Allow => Ok(()),
+        InterceptDecision::Deny(reason) => Err(WsError::Denied {
+            command: request.command.clone(),
+            reason,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use serde_json::json;
+    use tokio_tungstenite::tungstenite::Message;
+
+    fn conn_state(token: Option<&str>) -> (ConnState, OutboxReceiver) {
+        let address = "127.0.0.1:50000".parse().unwrap();
+        let local_addr = "127.0.0.1:9223".parse().unwrap();
+        let config = Config::default();
+        ConnState::new(
+            ClientRegistry::new(),
+            address,
+            local_addr,
+            Capabilities::from_config(&config),
+            AuthGate::new(token.map(str::to_string)).with_read_only(config.read_only_commands),
+        )
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let request = parse_request(
+            r#"{ "id": "7", "command": "execute_js", "args": { "script": "1", "windowMatch": { "titleContains": "Doc" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(request.id, "7");
+        assert_eq!(request.command, "execute_js");
+        assert_eq!(request.args(), Some(&json!({ "script": "1" })));
+        assert_eq!(
+            request.window_match,
+            Ok(Some(WindowMatch {
+                title_contains: Some("Doc".to_string()),
+                ..WindowMatch::default()
+            }))
+        );
+
+        let bad_match = parse_request(
+            r#"{ "id": "8", "command": "execute_js", "args": { "windowLabel": "main", "windowMatch": { "titleContains": "Doc" } } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            bad_match.window_match.unwrap_err(),
+            "Pass either windowLabel or windowMatch, not both"
+        );
+
+        let bare = parse_request("{}").unwrap();
+        assert_eq!((bare.id.as_str(), bare.command.as_str()), ("", "unknown"));
+        assert_eq!(bare.window_match, Ok(None));
+
+        assert_eq!(
+            parse_request("not json"),
+            Err(WsError::Malformed("not json".to_string()))
+        );
+        // The client still hears about the frame it sent
+        assert_eq!(
+            parse_request("not json")
+                .unwrap_err()
+                .response("")
+                .unwrap()
+                .body,
+            json!({
+                "id": "",
+                "success": false,
+                "error": "Failed to parse request: expected a JSON object"
+            })
+        );
+        assert!(parse_request("[1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_route_strips_the_namespace() {
+        let request = |text: &str| parse_request(text).unwrap();
+        let execute_js = request(r#"{ "id": "1", "command": "mcp.execute_js" }"#);
+
+        // Without a namespace nothing changes
+        assert_eq!(route(execute_js.clone(), None), Ok(execute_js.clone()));
+        assert_eq!(
+            route(request(r#"{ "command": "execute_js" }"#), None)
+                .unwrap()
+                .command,
+            "execute_js"
+        );
+
+        let routed = route(execute_js, Some("mcp")).unwrap();
+        assert_eq!(
+            (routed.id.as_str(), routed.command.as_str()),
+            ("1", "execute_js")
+        );
+        for foreign in [
+            "execute_js",
+            "other.execute_js",
+            "mcpx.execute_js",
+            "mcp.",
+            "mcp",
+        ] {
+            let text = format!(r#"{{ "command": "{foreign}" }}"#);
+            assert_eq!(
+                route(request(&text), Some("mcp")),
+                Err(WsError::Foreign(foreign.to_string()))
+            );
+        }
+        assert_eq!(qualified_command(Some("mcp"), "teleport"), "mcp.teleport");
+        assert_eq!(qualified_command(None, "teleport"), "teleport");
+    }
+
+    #[test]
+    fn test_authorize_grades_unauthenticated_access() {
+        let request = |text: &str| parse_request(text).unwrap();
+        let (mut conn, _rx) = conn_state(Some("s3cret"));
+        let execute_js =
+            request(r#"{ "id": "2", "command": "execute_js", "args": { "script": "1" } }"#);
+
+        assert_eq!(
+            authorize(&request(r#"{ "command": "hello" }"#), &mut conn),
+            Ok(())
+        );
+        // Reads work without the token
+        assert_eq!(
+            authorize(&request(r#"{ "command": "list_windows" }"#), &mut conn),
+            Ok(())
+        );
+        let backend_state = request(
+            r#"{ "command": "invoke_tauri", "args": { "command": "plugin:mcp-bridge|get_backend_state" } }"#,
+        );
+        assert_eq!(authorize(&backend_state, &mut conn), Ok(()));
+
+        // Writes are refused, but the connection stays open
+        let err = authorize(&execute_js, &mut conn).unwrap_err();
+        assert_eq!(err, WsError::Unauthenticated("execute_js".to_string()));
+        assert_eq!(err.close_reason(), None);
+        assert_eq!(
+            err.response("2").unwrap().body,
+            json!({ "id": "2", "success": false, "error": "unauthorized" })
+        );
+
+        // A wrong token closes it
+        let wrong = request(r#"{ "command": "authenticate", "args": { "token": "guess" } }"#);
+        let err = authorize(&wrong, &mut conn).unwrap_err();
+        assert_eq!(err.close_reason(), Some(CloseReason::Unauthorized));
+
+        let login = request(r#"{ "command": "authenticate", "args": { "token": "s3cret" } }"#);
+        assert_eq!(authorize(&login, &mut conn), Ok(()));
+        assert_eq!(authorize(&execute_js, &mut conn), Ok(()));
+
+        let (mut open, _rx) = conn_state(None);
+        assert_eq!(authorize(&execute_js, &mut open), Ok(()));
+    }
+
+    #[test]
+    fn test_intercept_denies_or_rewrites_requests() {
+        let request = |text: &str| parse_request(text).unwrap();
+        let (mut conn, _rx) = conn_state(None);
+        let mut forbidden = request(
+            r#"{ "id": "5", "command": "execute_js", "args": { "script": "localStorage.getItem('jwt')" } }"#,
+        );
+
+        // Without an interceptor everything passes
+        assert_eq!(intercept(&mut forbidden, &conn), Ok(()));
+
+        conn.interceptor = Some(RequestInterceptor::new(|request| {
+            let Some(args) = request.body.get_mut("args") else {
+                return InterceptDecision::Allow;
+            };
+            if request.command == "set_input_value" {
+                args["value"] = json!("[redacted]");
+            }
+            let script = args.get("script").and_then(Value::as_str).unwrap_or("");
+            if request.command == "execute_js" && script.contains("localStorage") {
+                return InterceptDecision::Deny("scripts may not read localStorage".into());
+            }
+            InterceptDecision::Allow
+        }));
+
+        let err = intercept(&mut forbidden, &conn).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Interceptor denied execute_js: scripts may not read localStorage"
+        );
+        assert_eq!(err.close_reason(), None);
+        assert_eq!(
+            err.response("5").unwrap().body,
+            json!({
+                "id": "5",
+                "success": false,
+                "error": "Request denied: scripts may not read localStorage"
+            })
+        );
+
+        let mut allowed = request(
+            r#"{ "id": "6", "command": "execute_js", "args": { "script": "document.title" } }"#,
+        );
+        assert_eq!(intercept(&mut allowed, &conn), Ok(()));
+
+        let mut login = request(
+            r#"{ "id": "7", "command": "set_input_value", "args": { "selector": "input[type=password]", "value": "hunter2" } }"#,
+        );
+        assert_eq!(intercept(&mut login, &conn), Ok(()));
+        assert_eq!(
+            login.args(),
+            Some(&json!({ "selector": "input[type=password]", "value": "[redacted]" }))
+        );
+    }
+
+    #[test]
+    fn test_connection_timeout_applies_under_the_global_cap() {
+        let (mut conn, _responses) = conn_state(None);
+        conn.command_timeout = Duration::from_secs(10);
+        let request = |text: &str| parse_request(text).unwrap();
+        let plain = request(r#"{ "id": "1", "command": "get_dom" }"#);
+        assert_eq!(conn.request_timeout(&plain), Duration::from_secs(10));
+
+        assert_eq!(
+            connection_timeout(Some(30_000), conn.command_timeout),
+            Err("defaultMs 30000 exceeds the maximum command timeout of 10000ms".to_string())
+        );
+        assert!(connection_timeout(Some(0), conn.command_timeout).is_err());
+
+        conn.default_timeout = connection_timeout(Some(2_000), conn.command_timeout).unwrap();
+        assert_eq!(conn.request_timeout(&plain), Duration::from_secs(2));
+
+        // A request's own timeoutMs wins, but is capped too
+        let shorter = request(r#"{ "id": "2", "command": "get_dom", "timeoutMs": 500 }"#);
+        assert_eq!(conn.request_timeout(&shorter), Duration::from_millis(500));
+        let longer = request(r#"{ "id": "3", "command": "get_dom", "timeoutMs": 60000 }"#);
+        assert_eq!(conn.request_timeout(&longer), Duration::from_secs(10));
+
+        // Commands with their own wait only get the cap
+        let wait = request(
+            r#"{ "id": "4", "command": "wait_for_event", "args": { "eventName": "x", "timeoutMs": 5000 } }"#,
+        );
+        assert_eq!(conn.request_timeout(&wait), Duration::from_secs(10));
+
+        conn.default_timeout = connection_timeout(None, conn.command_timeout).unwrap();
+        assert_eq!(conn.request_timeout(&plain), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn test_debug_field_only_when_tracing() {
+        let (mut conn, _rx) = conn_state(None);
+        let request = parse_request(
+            r#"{ "id": "1", "command": "get_dom", "args": { "windowLabel": "settings" } }"#,
+        )
+        .unwrap();
+        let received = Instant::now();
+
+        assert!(conn.trace_request(&request, received).is_none());
+
+        conn.trace = true;
+        let trace = conn.trace_request(&request, received).unwrap();
+        let mut response = WsResponse::new(json!({ "id": "1", "success": true, "data": "<html>" }));
+        response.window_label = Some("settings".to_string());
+        trace.annotate(&mut response);
+
+        let debug = &response.body["debug"];
+        assert_eq!(debug["command"], "get_dom");
+        assert_eq!(debug["args"], json!({ "windowLabel": "settings" }));
+        assert_eq!(debug["windowLabel"], "settings");
+        let timing = debug["timing"].as_object().unwrap();
+        for stage in ["admitMs", "dispatchMs", "totalMs"] {
+            assert!(timing[stage].as_f64().unwrap() >= 0.0, "{stage}");
+        }
+        assert!(timing["totalMs"].as_f64() >= timing["dispatchMs"].as_f64());
+        assert_eq!(response.body["data"], "<html>");
+    }
+
+    #[test]
+    fn test_respond_switches_format_after_sending() {
+        let (mut conn, mut rx) = conn_state(None);
+        let broadcasts = conn.format_tx.subscribe();
+
+        conn.respond(WsResponse {
+            body: json!({ "id": "1", "success": true }),
+            format: Some(ResponseFormat::JsonPretty),
+            chunks: Vec::new(),
+            window_label: None,
+        });
+        conn.respond(WsResponse::new(json!({ "id": "2", "success": true })));
+
+        // The negotiating response still uses the old format
+        let Message::Text(first) = rx.try_recv().unwrap() else {
+            panic!("expected a text frame");
+        };
+        assert_eq!(first.as_str(), r#"{"id":"1","success":true}"#);
+        let Message::Text(second) = rx.try_recv().unwrap() else {
+            panic!("expected a text frame");
+        };
+        assert!(second.as_str().contains("\n  \"id\": \"2\""));
+        assert_eq!(*broadcasts.borrow(), ResponseFormat::JsonPretty);
+    }
+
+    #[test]
+    fn test_respond_sends_chunks_after_the_response() {
+        let (mut conn, mut rx) = conn_state(None);
+        let mut body = json!({ "id": "1", "success": true, "data": "x".repeat(100) });
+        let chunks = crate::chunked::split(&mut body, 40);
+
+        conn.respond(WsResponse {
+            body,
+            format: None,
+            chunks,
+            window_label: None,
+        });
+
+        let mut frames = Vec::new();
+        while let Ok(Message::Text(text)) = rx.try_recv() {
+            frames.push(serde_json::from_str::<Value>(text.as_str()).unwrap());
+        }
+        assert_eq!(frames[0]["chunked"], true);
+        assert_eq!(frames[0]["chunkCount"], 3);
+        let data: String = frames[1..]
+            .iter()
+            .map(|chunk| chunk["data"].as_str().unwrap())
+            .collect();
+        assert_eq!(data, serde_json::to_string(&"x".repeat(100)).unwrap());
+    }
+}
@@ -0,0 +1,269 @@
+//! Wire formats for frames sent to WebSocket clients.
+//!
+//! Each connection starts out with compact JSON text frames. Clients can switch
+//! to another format by passing `format` in their `hello` command:
+//!
+//! | Format        | Frame type | Notes                                          |
+//! |---------------|------------|------------------------------------------------|
+//! | `json`        | text       | Default                                        |
+//! | `json-pretty` | text       | Indented JSON, for debugging over the wire     |
+//! | `msgpack`     | binary     | MessagePack; requires the `msgpack` feature    |
+//!
+//! The `hello` response itself is still sent in the previous format; every
+//! later frame on the connection, including broadcast events, uses the
+//! negotiated one. Requests are always JSON text.
+//!
+//! Binary script results (`resultType: "binary"`) carry base64 `data` in the
+//! JSON formats. MessagePack sends the raw bytes as a `bin` value instead,
+//! with `encoding: "raw"`.
+
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Serialization format for outgoing frames on a connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// Compact JSON text frames
+    #[default]
+    Json,
+    /// Indented JSON text frames
+    JsonPretty,
+    /// MessagePack binary frames
+    #[cfg(feature = "msgpack")]
+    MsgPack,
+}
+
+impl ResponseFormat {
+    /// Parses a format name as sent by clients.
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "json" => Ok(Self::Json),
+            "json-pretty" => Ok(Self::JsonPretty),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Ok(Self::MsgPack),
+            #[cfg(not(feature = "msgpack"))]
+            "msgpack" => {
+                Err("Response format 'msgpack' requires the `msgpack` feature".to_string())
+            }
+            other => Err(format!(
+                "Unsupported response format '{other}'. Supported: {}",
+                Self::supported().join(", ")
+            )),
+        }
+    }
+
+    /// Returns the client-facing name of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::JsonPretty => "json-pretty",
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => "msgpack",
+        }
+    }
+
+    /// Names of all formats available in this build.
+    pub fn supported() -> Vec<&'static str> {
+        let mut formats = vec!["json", "json-pretty"];
+        if cfg!(feature = "msgpack") {
+            formats.push("msgpack");
+        }
+        formats
+    }
+
+    /// Encodes a response value as a WebSocket frame.
+    pub fn encode(&self, value: &Value) -> Message {
+        match self {
+            Self::Json => Message::Text(value.to_string().into()),
+            Self::JsonPretty => Message::Text(
+                serde_json::to_string_pretty(value)
+                    .unwrap_or_else(|_| value.to_string())
+                    .into(),
+            ),
+            #[cfg(feature = "msgpack")]
+            Self::MsgPack => match rmp_serde::to_vec_named(value) {
+                Ok(bytes) => Message::Binary(bytes.into()),
+                Err(e) => Message::Text(
+                    serde_json::json!({
+                        "success": false,
+                        "error": format!("Failed to encode msgpack frame: {e}")
+                    })
+                    .to_string()
+                    .into(),
+                ),
+            },
+        }
+    }
+
+    /// Encodes a response, sending binary results as raw bytes where the
+    /// format allows.
+    pub fn encode_response(&self, value: &Value) -> Message {
+        #[cfg(feature = "msgpack")]
+        if *self == Self::MsgPack {
+            if let Some(Ok(bytes)) = crate::commands::execute_js::decode_binary_result(value) {
+                if let Some(fields) = value.as_object() {
+                    let response = RawBinaryResponse {
+                        fields,
+                        bytes: &bytes,
+                    };
+                    if let Ok(frame) = rmp_serde::to_vec_named(&response) {
+                        return Message::Binary(frame.into());
+                    }
+                }
+            }
+        }
+
+        self.encode(value)
+    }
+
+    /// Encodes an already serialized JSON frame (e.g. a broadcast event).
+    pub fn encode_json_text(&self, json: String) -> Message {
+        if *self == Self::Json {
+            return Message::Text(json.into());
+        }
+        match serde_json::from_str::<Value>(&json) {
+            Ok(value) => self.encode(&value),
+            Err(_) => Message::Text(json.into()),
+        }
+    }
+}
+
+/// A binary result response with `data` serialized as bytes.
+#[cfg(feature = "msgpack")]
+struct RawBinaryResponse<'a> {
+    fields: &'a serde_json::Map<String, Value>,
+    bytes: &'a [u8],
+}
+
+#[cfg(feature = "msgpack")]
+impl serde::Serialize for RawBinaryResponse<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        struct Bytes<'a>(&'a [u8]);
+        impl serde::Serialize for Bytes<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_bytes(self.0)
+            }
+        }
+
+        let mut map = serializer.serialize_map(Some(self.fields.len()))?;
+        for (key, value) in self.fields {
+            match key.as_str() {
+                "data" => map.serialize_entry(key, &Bytes(self.bytes))?,
+                "encoding" => map.serialize_entry(key, "raw")?,
+                _ => map.serialize_entry(key, value)?,
+            }
+        }
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_response() -> Value {
+        serde_json::json!({
+            "id": "req-1",
+            "success": true,
+            "data": { "title": "Home", "count": 3, "tags": ["a", "b"], "ratio": 0.5 }
+        })
+    }
+
+    fn decode(message: Message) -> Value {
+        match message {
+            Message::Text(text) => serde_json::from_str(text.as_str()).unwrap(),
+            #[cfg(feature = "msgpack")]
+            Message::Binary(bytes) => rmp_serde::from_slice(&bytes).unwrap(),
+            other => panic!("unexpected frame: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let message = ResponseFormat::Json.encode(&sample_response());
+
+        assert!(!message.to_text().unwrap().contains('\n'));
+        assert_eq!(decode(message), sample_response());
+    }
+
+    #[test]
+    fn test_json_pretty_round_trip() {
+        let message = ResponseFormat::JsonPretty.encode(&sample_response());
+
+        assert!(message.to_text().unwrap().contains("\n  \"id\": \"req-1\""));
+        assert_eq!(decode(message), sample_response());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_round_trip() {
+        let message = ResponseFormat::MsgPack.encode(&sample_response());
+
+        assert!(message.is_binary());
+        assert_eq!(decode(message), sample_response());
+    }
+
+    fn binary_response() -> Value {
+        serde_json::json!({
+            "id": "req-2",
+            "success": true,
+            "resultType": "binary",
+            "encoding": "base64",
+            "data": "AAF/gP8="
+        })
+    }
+
+    #[test]
+    fn test_json_keeps_binary_results_base64() {
+        let message = ResponseFormat::Json.encode_response(&binary_response());
+
+        assert_eq!(decode(message), binary_response());
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn test_msgpack_sends_binary_results_as_raw_bytes() {
+        let frame = ResponseFormat::MsgPack
+            .encode_response(&binary_response())
+            .into_data();
+
+        // bin 8 with length 5, followed by the decoded bytes
+        let raw = [0xc4, 5, 0, 1, 127, 128, 255];
+        assert!(frame.windows(raw.len()).any(|w| w == raw));
+        assert!(frame.windows(3).any(|w| w == b"raw"));
+        assert!(!frame.windows(8).any(|w| w == b"AAF/gP8="));
+    }
+
+    #[test]
+    fn test_encode_json_text_reencodes_broadcasts() {
+        let event = sample_response().to_string();
+
+        let message = ResponseFormat::JsonPretty.encode_json_text(event.clone());
+        assert_eq!(decode(message), sample_response());
+
+        let message = ResponseFormat::Json.encode_json_text(event.clone());
+        assert_eq!(message.to_text().unwrap(), event);
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        assert_eq!(ResponseFormat::parse("json"), Ok(ResponseFormat::Json));
+        assert_eq!(
+            ResponseFormat::parse("json-pretty"),
+            Ok(ResponseFormat::JsonPretty)
+        );
+        assert_eq!(
+            ResponseFormat::parse("json-pretty").unwrap().as_str(),
+            "json-pretty"
+        );
+        assert!(ResponseFormat::parse("xml")
+            .unwrap_err()
+            .starts_with("Unsupported response format 'xml'"));
+        assert_eq!(
+            ResponseFormat::parse("msgpack").is_ok(),
+            cfg!(feature = "msgpack")
+        );
+    }
+}
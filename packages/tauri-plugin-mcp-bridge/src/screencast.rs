@@ -0,0 +1,418 @@
+//! Continuous screenshot streams ("screencasts") over a WebSocket connection.
+//!
+//! A client starts a stream with:
+//!
+//! ```json
+//! { "id": "1", "command": "start_screencast", "args": { "fps": 5, "format": "jpeg" } }
+//! ```
+//!
+//! and receives `{ streamId, fps, format }`. Frames are then pushed to that
+//! connection only, as binary WebSocket messages with this envelope:
+//!
+//! ```text
+//! +----------------------+---------------------------+---------------------+
+//! | header length (u32,  | JSON header (UTF-8)       | image bytes         |
+//! | big-endian, 4 bytes) |                           | (PNG or JPEG)       |
+//! +----------------------+---------------------------+---------------------+
+//! ```
+//!
+//! The header is `{ type: "screencast_frame", streamId, seq, timestamp,
+//! format, windowLabel }`, with `seq` counting up from 0 per stream. If a
+//! capture fails, a text frame `{ type: "screencast_error", streamId, error }`
+//! is sent and the stream stops.
+//!
+//! While the window is hidden or minimized no frames are captured. The
+//! stream sends `{ type: "screencast", streamId, state: "paused", reason:
+//! "hidden" }` when it pauses and `{ type: "screencast", streamId, state:
+//! "resumed" }` once the window is visible again; `seq` carries on where it
+//! left off.
+//!
+//! Streams end on `stop_screencast`, or automatically when the connection
+//! closes. Frame rate is capped at [`MAX_SCREENCAST_FPS`]; a slow capture
+//! skips ticks rather than bursting to catch up.
+
+use crate::outbound::Outbox;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Highest accepted frame rate.
+pub const MAX_SCREENCAST_FPS: f64 = 10.0;
+
+/// Frame rate used when the client doesn't ask for one.
+pub const DEFAULT_SCREENCAST_FPS: f64 = 2.0;
+
+/// Header preceding the image bytes of each binary frame.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameHeader {
+    #[serde(rename = "type")]
+    pub frame_type: &'static str,
+    pub stream_id: String,
+    pub seq: u64,
+    pub timestamp: u64,
+    pub format: String,
+    pub window_label: String,
+}
+
+/// Validates a requested frame rate, applying the default and the cap.
+pub fn clamp_fps(fps: Option<f64>) -> Result<f64, String> {
+    match fps {
+        None => Ok(DEFAULT_SCREENCAST_FPS),
+        Some(fps) if fps.is_finite() && fps > 0.0 => Ok(fps.min(MAX_SCREENCAST_FPS)),
+        Some(fps) => Err(format!("fps must be greater than 0, got {fps}")),
+    }
+}
+
+/// Builds a binary frame: length-prefixed JSON header followed by `image`.
+pub fn encode_frame(header: &FrameHeader, image: &[u8]) -> Vec<u8> {
+    let header = serde_json::to_vec(header).unwrap_or_default();
+    let mut frame = Vec::with_capacity(4 + header.len() + image.len());
+    frame.extend_from_slice(&(header.len() as u32).to_be_bytes());
+    frame.extend_from_slice(&header);
+    frame.extend_from_slice(image);
+    frame
+}
+
+/// Decodes a base64 image data URL into its format (e.g. `"jpeg"`) and bytes.
+pub fn decode_data_url(data_url: &str) -> Result<(String, Vec<u8>), String> {
+    use base64::Engine as _;
+
+    let (meta, data) = data_url
+        .strip_prefix("data:image/")
+        .and_then(|rest| rest.split_once(";base64,"))
+        .ok_or_else(|| "Capture did not return a base64 image data URL".to_string())?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid image data: {e}"))?;
+    Ok((meta.to_string(), bytes))
+}
+
+/// Splits a binary frame into its JSON header and image bytes.
+pub fn decode_frame(frame: &[u8]) -> Option<(serde_json::Value, &[u8])> {
+    let length = u32::from_be_bytes(frame.get(..4)?.try_into().ok()?) as usize;
+    let header = frame.get(4..4 + length)?;
+    let header = serde_json::from_slice(header).ok()?;
+    Some((header, &frame[4 + length..]))
+}
+
+/// Screencasts running on one connection.
+///
+/// Dropping the set (when the connection closes) stops every stream.
+#[derive(Default)]
+pub struct Screencasts {
+    streams: HashMap<String, JoinHandle<()>>,
+}
+
+impl Screencasts {
+    /// Starts a stream that captures a frame every `1 / fps` seconds and
+    /// sends it on `sink` until stopped or until a capture fails.
+    ///
+    /// Ticks on which `visible` returns `false` capture nothing; the stream
+    /// reports pausing and resuming on `sink` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `fps` - Frame rate, already validated with [`clamp_fps`]
+    /// * `window_label` - Window being captured, reported in frame headers
+    /// * `sink` - The connection's outbox; the stream ends when it is gone
+    ///   or full
+    /// * `visible` - Whether the window can be captured, checked every tick
+    /// * `capture` - Produces one encoded image and its format per call
+    ///
+    /// # Returns
+    ///
+    /// The new stream's id.
+    pub fn start<V, C, F>(
+        &mut self,
+        fps: f64,
+        window_label: String,
+        sink: Outbox,
+        mut visible: V,
+        mut capture: C,
+    ) -> String
+    where
+        V: FnMut() -> bool + Send + 'static,
+        C: FnMut() -> F + Send + 'static,
+        F: Future<Output = Result<(String, Vec<u8>), String>> + Send,
+    {
+        self.streams.retain(|_, task| !task.inner().is_finished());
+
+        let stream_id = uuid::Uuid::new_v4().to_string();
+        let id = stream_id.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / fps));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+            let mut seq = 0;
+            let mut paused = false;
+
+            loop {
+                ticker.tick().await;
+
+                // Hidden windows can't be captured, or come out blank
+                if visible() == paused {
+                    paused = !paused;
+                    let event = if paused {
+                        serde_json::json!({
+                            "type": "screencast",
+                            "streamId": id,
+                            "state": "paused",
+                            "reason": "hidden"
+                        })
+                    } else {
+                        serde_json::json!({
+                            "type": "screencast",
+                            "streamId": id,
+                            "state": "resumed"
+                        })
+                    };
+                    if sink.send(Message::Text(event.to_string().into())).is_err() {
+                        break;
+                    }
+                }
+                if paused {
+                    continue;
+                }
+
+                let message = match capture().await {
+                    Ok((format, image)) => {
+                        let header = FrameHeader {
+                            frame_type: "screencast_frame",
+                            stream_id: id.clone(),
+                            seq,
+                            timestamp: crate::monitor::current_timestamp(),
+                            format,
+                            window_label: window_label.clone(),
+                        };
+                        Message::Binary(encode_frame(&header, &image).into())
+                    }
+                    Err(error) => {
+                        let event = serde_json::json!({
+                            "type": "screencast_error",
+                            "streamId": id,
+                            "error": error
+                        });
+                        let _ = sink.send(Message::Text(event.to_string().into()));
+                        break;
+                    }
+                };
+                // The connection is gone
+                if sink.send(message).is_err() {
+                    break;
+                }
+                seq += 1;
+            }
+        });
+
+        self.streams.insert(stream_id.clone(), task);
+        stream_id
+    }
+
+    /// Stops one stream, or every stream when `stream_id` is `None`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Vec<String>)` - Ids of the streams that were stopped
+    /// * `Err(String)` - If `stream_id` doesn't name a stream on this connection
+    pub fn stop(&mut self, stream_id: Option<&str>) -> Result<Vec<String>, String> {
+        let ids: Vec<String> = match stream_id {
+            Some(id) if self.streams.contains_key(id) => vec![id.to_string()],
+            Some(id) => return Err(format!("Unknown screencast stream: {id}")),
+            None => self.streams.keys().cloned().collect(),
+        };
+
+        for id in &ids {
+            if let Some(task) = self.streams.remove(id) {
+                task.abort();
+            }
+        }
+        Ok(ids)
+    }
+}
+
+impl Drop for Screencasts {
+    fn drop(&mut self) {
+        for task in self.streams.values() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbound::outbox;
+
+    const FAST_FPS: f64 = 10.0;
+
+    fn start_fake(screencasts: &mut Screencasts, sink: Outbox) -> String {
+        screencasts.start(
+            FAST_FPS,
+            "main".to_string(),
+            sink,
+            || true,
+            || async { Ok(("png".to_string(), vec![0x89, b'P', b'N', b'G'])) },
+        )
+    }
+
+    #[test]
+    fn test_fps_is_capped_and_validated() {
+        assert_eq!(clamp_fps(None), Ok(DEFAULT_SCREENCAST_FPS));
+        assert_eq!(clamp_fps(Some(60.0)), Ok(MAX_SCREENCAST_FPS));
+        assert_eq!(clamp_fps(Some(0.5)), Ok(0.5));
+        assert!(clamp_fps(Some(0.0)).is_err());
+        assert!(clamp_fps(Some(f64::NAN)).is_err());
+    }
+
+    #[test]
+    fn test_frame_envelope_round_trip() {
+        let header = FrameHeader {
+            frame_type: "screencast_frame",
+            stream_id: "s1".to_string(),
+            seq: 3,
+            timestamp: 1,
+            format: "jpeg".to_string(),
+            window_label: "main".to_string(),
+        };
+
+        let frame = encode_frame(&header, b"image");
+        let (decoded, image) = decode_frame(&frame).unwrap();
+
+        assert_eq!(decoded["streamId"], "s1");
+        assert_eq!(decoded["seq"], 3);
+        assert_eq!(decoded["windowLabel"], "main");
+        assert_eq!(image, b"image");
+        assert!(decode_frame(&frame[..2]).is_none());
+    }
+
+    #[test]
+    fn test_data_url_is_decoded_with_its_format() {
+        let (format, bytes) = decode_data_url("data:image/jpeg;base64,aW1hZ2U=").unwrap();
+        assert_eq!(format, "jpeg");
+        assert_eq!(bytes, b"image");
+        assert!(decode_data_url("not a data url").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_frames_stream_until_stopped() {
+        let (tx, mut rx) = outbox();
+        let mut screencasts = Screencasts::default();
+        let stream_id = start_fake(&mut screencasts, tx);
+
+        for expected_seq in 0..2 {
+            let Some(Message::Binary(frame)) = rx.recv().await else {
+                panic!("expected a binary frame");
+            };
+            let (header, image) = decode_frame(&frame).unwrap();
+            assert_eq!(header["streamId"], stream_id.as_str());
+            assert_eq!(header["seq"], expected_seq);
+            assert_eq!(image, [0x89, b'P', b'N', b'G']);
+        }
+
+        assert_eq!(screencasts.stop(Some(&stream_id)), Ok(vec![stream_id]));
+        assert!(screencasts.stop(Some("missing")).is_err());
+
+        // The sender is dropped with the aborted task, closing the channel
+        while rx.recv().await.is_some() {}
+    }
+
+    #[tokio::test]
+    async fn test_dropping_connection_state_stops_streams() {
+        let (tx, mut rx) = outbox();
+        let mut screencasts = Screencasts::default();
+        start_fake(&mut screencasts, tx.clone());
+        start_fake(&mut screencasts, tx);
+
+        drop(screencasts);
+
+        let drained = tokio::time::timeout(Duration::from_secs(1), async {
+            while rx.recv().await.is_some() {}
+        })
+        .await;
+        assert!(drained.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_capture_error_ends_stream() {
+        let (tx, mut rx) = outbox();
+        let mut screencasts = Screencasts::default();
+        screencasts.start(
+            FAST_FPS,
+            "main".to_string(),
+            tx,
+            || true,
+            || async { Err("window closed".to_string()) },
+        );
+
+        let Some(Message::Text(text)) = rx.recv().await else {
+            panic!("expected an error event");
+        };
+        let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(event["type"], "screencast_error");
+        assert_eq!(event["error"], "window closed");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capture_pauses_while_window_is_hidden() {
+        use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+        use std::sync::Arc;
+
+        let (tx, mut rx) = outbox();
+        let mut screencasts = Screencasts::default();
+        let visible = Arc::new(AtomicBool::new(true));
+        let captures = Arc::new(AtomicU64::new(0));
+        let (is_visible, counter) = (visible.clone(), captures.clone());
+        screencasts.start(
+            FAST_FPS,
+            "main".to_string(),
+            tx,
+            move || is_visible.load(Ordering::SeqCst),
+            move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                async { Ok(("png".to_string(), b"image".to_vec())) }
+            },
+        );
+
+        let Some(Message::Binary(_)) = rx.recv().await else {
+            panic!("expected a frame while visible");
+        };
+        visible.store(false, Ordering::SeqCst);
+
+        // Frames captured before the window was hidden may still be queued
+        let paused = loop {
+            match rx.recv().await {
+                Some(Message::Text(text)) => break text,
+                Some(Message::Binary(_)) => continue,
+                other => panic!("unexpected message: {other:?}"),
+            }
+        };
+        let event: serde_json::Value = serde_json::from_str(&paused).unwrap();
+        assert_eq!(event["type"], "screencast");
+        assert_eq!(event["state"], "paused");
+        assert_eq!(event["reason"], "hidden");
+
+        let captured_while_hidden = captures.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(captures.load(Ordering::SeqCst), captured_while_hidden);
+        assert!(rx.try_recv().is_err());
+
+        visible.store(true, Ordering::SeqCst);
+        let Some(Message::Text(resumed)) = rx.recv().await else {
+            panic!("expected a resume event");
+        };
+        let event: serde_json::Value = serde_json::from_str(&resumed).unwrap();
+        assert_eq!(event["state"], "resumed");
+
+        let Some(Message::Binary(frame)) = rx.recv().await else {
+            panic!("expected frames after resuming");
+        };
+        let (header, _) = decode_frame(&frame).unwrap();
+        assert_eq!(header["seq"], captured_while_hidden);
+    }
+}
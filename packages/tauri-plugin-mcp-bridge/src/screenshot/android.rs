@@ -1,4 +1,4 @@
-use super::{Screenshot, ScreenshotError};
+use super::{NativeFormat, Screenshot, ScreenshotError};
 use tauri::{Runtime, WebviewWindow};
 
 /// Android-specific screenshot implementation using WebView.draw()
@@ -8,9 +8,11 @@ use tauri::{Runtime, WebviewWindow};
 /// 2. Creating a Bitmap with those dimensions
 /// 3. Creating a Canvas from the Bitmap
 /// 4. Drawing the WebView to the Canvas
-/// 5. Compressing the Bitmap to PNG bytes
+/// 5. Compressing the Bitmap to PNG bytes, or straight to JPEG when
+///    [`NativeFormat::Jpeg`] is requested
 pub fn capture_viewport<R: Runtime>(
     window: &WebviewWindow<R>,
+    format: NativeFormat,
 ) -> Result<Screenshot, ScreenshotError> {
     #[cfg(target_os = "android")]
     {
@@ -52,8 +54,8 @@ pub fn capture_viewport<R: Runtime>(
                                 })?;
 
                             if width <= 0 || height <= 0 {
-                                return Err(ScreenshotError::CaptureFailed(format!(
-                                    "Invalid WebView dimensions: {width}x{height}"
+                                return Err(ScreenshotError::WebviewNotReady(format!(
+                                    "WebView dimensions are {width}x{height}"
                                 )));
                             }
 
@@ -145,7 +147,11 @@ pub fn capture_viewport<R: Runtime>(
                                 ))
                             })?;
 
-                            // Compress Bitmap to PNG bytes
+                            // Compress Bitmap to PNG (lossless, quality is ignored) or JPEG bytes
+                            let (format_name, quality) = match format {
+                                NativeFormat::Png => ("PNG", 100),
+                                NativeFormat::Jpeg { quality } => ("JPEG", quality.min(100) as i32),
+                            };
                             let baos_class = env
                                 .find_class("java/io/ByteArrayOutputStream")
                                 .map_err(|e| {
@@ -169,21 +175,21 @@ pub fn capture_viewport<R: Runtime>(
                                     ))
                                 })?;
 
-                            let png_format = env
+                            let compress_format = env
                                 .get_static_field(
                                     &compress_format_class,
-                                    "PNG",
+                                    format_name,
                                     "Landroid/graphics/Bitmap$CompressFormat;",
                                 )
                                 .map_err(|e| {
                                     ScreenshotError::CaptureFailed(format!(
-                                        "Failed to get PNG format: {e}"
+                                        "Failed to get {format_name} format: {e}"
                                     ))
                                 })?
                                 .l()
                                 .map_err(|e| {
                                     ScreenshotError::CaptureFailed(format!(
-                                        "Invalid PNG format: {e}"
+                                        "Invalid {format_name} format: {e}"
                                     ))
                                 })?;
 
@@ -192,8 +198,8 @@ pub fn capture_viewport<R: Runtime>(
                                 "compress",
                                 "(Landroid/graphics/Bitmap$CompressFormat;ILjava/io/OutputStream;)Z",
                                 &[
-                                    JValue::Object(&png_format),
-                                    JValue::Int(100),
+                                    JValue::Object(&compress_format),
+                                    JValue::Int(quality),
                                     JValue::Object(&baos),
                                 ],
                             )
@@ -259,7 +265,7 @@ pub fn capture_viewport<R: Runtime>(
 
     #[cfg(not(target_os = "android"))]
     {
-        let _ = window;
+        let _ = (window, format);
         Err(ScreenshotError::PlatformUnsupported)
     }
 }
@@ -0,0 +1,279 @@
+//! Comparing captures with baseline images, for visual regression checks.
+//!
+//! Doing the comparison next to the webview means only the verdict, and
+//! optionally a small diff image, crosses the connection instead of a full
+//! capture per check.
+
+use super::diff::encode_region;
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Outcome of comparing a capture with its baseline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotComparison {
+    /// Whether `diff_ratio` is within the allowed ratio
+    #[serde(rename = "match")]
+    pub matches: bool,
+    /// Pixels whose color differs by more than the tolerance
+    pub diff_pixel_count: u64,
+    /// `diff_pixel_count` as a fraction of all pixels
+    pub diff_ratio: f64,
+    /// PNG of the baseline, faded, with the differing pixels in red
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff_image_data_url: Option<String>,
+}
+
+/// How strictly a capture must match its baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CompareOptions {
+    /// Largest per-channel difference, as a fraction of the channel range
+    /// (0-1), still counted as the same color. 0 requires exact pixels.
+    pub threshold: f64,
+    /// Largest fraction of differing pixels (0-1) that still matches
+    pub max_diff_ratio: f64,
+    /// Whether to render `diff_image_data_url`
+    pub diff_image: bool,
+}
+
+impl CompareOptions {
+    /// Validates the options of `compare_screenshot`.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CompareOptions)` - With unset values defaulting to 0 and no diff
+    ///   image
+    /// * `Err(String)` - If a ratio is outside 0-1
+    pub fn new(
+        threshold: Option<f64>,
+        max_diff_ratio: Option<f64>,
+        diff_image: Option<bool>,
+    ) -> Result<Self, String> {
+        let ratio = |name: &str, value: Option<f64>| match value {
+            Some(value) if !(0.0..=1.0).contains(&value) => {
+                Err(format!("{name} must be between 0 and 1, got {value}"))
+            }
+            value => Ok(value.unwrap_or(0.0)),
+        };
+        Ok(Self {
+            threshold: ratio("threshold", threshold)?,
+            max_diff_ratio: ratio("maxDiffRatio", max_diff_ratio)?,
+            diff_image: diff_image.unwrap_or(false),
+        })
+    }
+}
+
+/// Loads a baseline image from the baselines directory.
+///
+/// Fails when `baselines_dir` is `None`, since there is nothing to confine
+/// the path to.
+///
+/// # Arguments
+///
+/// * `path` - Path of the baseline PNG, relative to `baselines_dir`
+/// * `baselines_dir` - The configured baselines directory
+///
+/// # Returns
+///
+/// * `Ok(RgbaImage)` - The decoded image
+/// * `Err(String)` - If no baselines directory is configured, the path is
+///   rejected, or the file can't be decoded
+pub fn load_baseline(path: &str, baselines_dir: Option<&Path>) -> Result<RgbaImage, String> {
+    let baselines_dir = baselines_dir.ok_or_else(|| {
+        "compare_screenshot is disabled: configure a baselines directory with Builder::baselines_dir"
+            .to_string()
+    })?;
+    let resolved = resolve_baseline_path(path, baselines_dir)?;
+    image::open(&resolved)
+        .map(|image| image.to_rgba8())
+        .map_err(|e| format!("Failed to load baseline {path}: {e}"))
+}
+
+/// Resolves and validates a baseline path.
+///
+/// Symlinks and `..` components are resolved before the checks. Missing
+/// files and files outside the directory get the same error, so the
+/// command can't be used to probe which paths exist on the host.
+pub fn resolve_baseline_path(path: &str, baselines_dir: &Path) -> Result<PathBuf, String> {
+    let dir = baselines_dir.canonicalize().map_err(|e| {
+        format!(
+            "Baselines directory {} is not accessible: {e}",
+            baselines_dir.display()
+        )
+    })?;
+    dir.join(path)
+        .canonicalize()
+        .ok()
+        .filter(|resolved| resolved.starts_with(&dir))
+        .filter(|resolved| resolved.extension().and_then(|ext| ext.to_str()) == Some("png"))
+        .ok_or_else(|| format!("Baseline {path} is not a PNG in the baselines directory"))
+}
+
+/// Compares a capture with its baseline pixel by pixel.
+///
+/// # Returns
+///
+/// * `Ok(ScreenshotComparison)` - The verdict and counts
+/// * `Err(String)` - If the images have different sizes
+pub fn compare_images(
+    baseline: &RgbaImage,
+    current: &RgbaImage,
+    options: CompareOptions,
+) -> Result<ScreenshotComparison, String> {
+    if baseline.dimensions() != current.dimensions() {
+        let (bw, bh) = baseline.dimensions();
+        let (cw, ch) = current.dimensions();
+        return Err(format!(
+            "Size mismatch: the baseline is {bw}x{bh} but the capture is {cw}x{ch}. Capture the baseline at the same window size and scale factor"
+        ));
+    }
+
+    let tolerance = (options.threshold * 255.0).round() as u8;
+    let differs = |a: &Rgba<u8>, b: &Rgba<u8>| {
+        a.0.iter()
+            .zip(b.0.iter())
+            .any(|(a, b)| a.abs_diff(*b) > tolerance)
+    };
+
+    let mut diff_image = options
+        .diff_image
+        .then(|| RgbaImage::new(baseline.width(), baseline.height()));
+    let mut diff_pixel_count = 0u64;
+    for ((x, y, before), after) in baseline.enumerate_pixels().zip(current.pixels()) {
+        let changed = differs(before, after);
+        if changed {
+            diff_pixel_count += 1;
+        }
+        if let Some(diff_image) = diff_image.as_mut() {
+            diff_image.put_pixel(x, y, diff_pixel(before, changed));
+        }
+    }
+
+    let total = u64::from(baseline.width()) * u64::from(baseline.height());
+    let diff_ratio = if total == 0 {
+        0.0
+    } else {
+        diff_pixel_count as f64 / total as f64
+    };
+    let diff_image_data_url = match diff_image {
+        Some(image) => Some(encode_region(&image, "png", 100)?),
+        None => None,
+    };
+    Ok(ScreenshotComparison {
+        matches: diff_ratio <= options.max_diff_ratio,
+        diff_pixel_count,
+        diff_ratio,
+        diff_image_data_url,
+    })
+}
+
+/// A pixel of the diff image: red where the capture changed, otherwise the
+/// baseline in faded gray for context.
+fn diff_pixel(baseline: &Rgba<u8>, changed: bool) -> Rgba<u8> {
+    if changed {
+        return Rgba([255, 0, 0, 255]);
+    }
+    let [r, g, b, _] = baseline.0;
+    let luma = (u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114) / 1000;
+    let faded = (255 - (255 - luma) / 4) as u8;
+    Rgba([faded, faded, faded, 255])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn page() -> RgbaImage {
+        let mut page = RgbaImage::from_pixel(20, 10, Rgba([255, 255, 255, 255]));
+        for x in 2..18 {
+            page.put_pixel(x, 4, Rgba([0, 0, 0, 255]));
+        }
+        page
+    }
+
+    #[test]
+    fn test_identical_image_matches() {
+        let options = CompareOptions::new(None, None, Some(true)).unwrap();
+        let comparison = compare_images(&page(), &page(), options).unwrap();
+
+        assert!(comparison.matches);
+        assert_eq!(comparison.diff_pixel_count, 0);
+        assert_eq!(comparison.diff_ratio, 0.0);
+        assert!(comparison
+            .diff_image_data_url
+            .unwrap()
+            .starts_with("data:image/png;base64,"));
+
+        let shrunk = RgbaImage::new(10, 10);
+        assert_eq!(
+            compare_images(&page(), &shrunk, options).unwrap_err(),
+            "Size mismatch: the baseline is 20x10 but the capture is 10x10. Capture the baseline at the same window size and scale factor"
+        );
+        assert!(CompareOptions::new(Some(1.5), None, None).is_err());
+    }
+
+    #[test]
+    fn test_modified_image_reports_changed_pixels() {
+        let mut modified = page();
+        for x in 0..4 {
+            modified.put_pixel(x, 0, Rgba([255, 0, 0, 255]));
+        }
+        // Within a 5% tolerance of white
+        modified.put_pixel(10, 9, Rgba([250, 250, 250, 255]));
+
+        let strict = CompareOptions::new(None, None, Some(true)).unwrap();
+        let comparison = compare_images(&page(), &modified, strict).unwrap();
+        assert!(!comparison.matches);
+        assert_eq!(comparison.diff_pixel_count, 5);
+        assert_eq!(comparison.diff_ratio, 0.025);
+
+        let diff_url = comparison.diff_image_data_url.unwrap();
+        let (_, bytes) = crate::screencast::decode_data_url(&diff_url).unwrap();
+        let diff = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(diff.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(diff.get_pixel(10, 4), &Rgba([192, 192, 192, 255]));
+        assert_eq!(diff.get_pixel(19, 9), &Rgba([255, 255, 255, 255]));
+
+        let tolerant = CompareOptions::new(Some(0.05), Some(0.02), None).unwrap();
+        let comparison = compare_images(&page(), &modified, tolerant).unwrap();
+        assert_eq!(comparison.diff_pixel_count, 4);
+        assert!(comparison.matches);
+        assert_eq!(comparison.diff_image_data_url, None);
+    }
+
+    #[test]
+    fn test_baselines_outside_the_directory_are_rejected() {
+        let root = std::env::temp_dir().join(format!("mcp-baselines-{}", uuid::Uuid::new_v4()));
+        let dir = root.join("baselines");
+        fs::create_dir_all(&dir).unwrap();
+        page().save(dir.join("page.png")).unwrap();
+        page().save(root.join("secret.png")).unwrap();
+        fs::write(dir.join("notes.txt"), "not an image").unwrap();
+
+        let inside = load_baseline("page.png", Some(&dir));
+        let escaped = load_baseline("../secret.png", Some(&dir));
+        let absolute = load_baseline(root.join("secret.png").to_str().unwrap(), Some(&dir));
+        let missing = load_baseline("../missing.png", Some(&dir));
+        let not_png = resolve_baseline_path("notes.txt", &dir);
+        let disabled = load_baseline("page.png", None);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(inside.unwrap(), page());
+        // Existing and missing files outside the directory look the same
+        assert_eq!(
+            escaped.unwrap_err(),
+            "Baseline ../secret.png is not a PNG in the baselines directory"
+        );
+        assert!(absolute
+            .unwrap_err()
+            .contains("is not a PNG in the baselines directory"));
+        assert_eq!(
+            missing.unwrap_err(),
+            "Baseline ../missing.png is not a PNG in the baselines directory"
+        );
+        assert!(not_png.is_err());
+        assert!(disabled.unwrap_err().contains("Builder::baselines_dir"));
+    }
+}
@@ -0,0 +1,250 @@
+//! Change detection between consecutive captures of the same window.
+//!
+//! With `diff: true`, a capture is compared pixel by pixel with the previous
+//! one taken with `diff` for that window. Only the bounding box of the
+//! changed pixels is encoded and sent, or nothing at all when the frame is
+//! identical, which keeps mostly static UIs cheap to watch.
+
+use super::CapturedImage;
+use image::{ImageFormat, RgbaImage};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+
+/// Bounding box of the pixels that changed, in image pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiffRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Returns the bounding box of the pixels that differ between two frames.
+///
+/// Frames of different sizes differ everywhere, so the whole of `current` is
+/// returned. `None` means the frames are identical.
+pub fn changed_region(previous: &RgbaImage, current: &RgbaImage) -> Option<DiffRegion> {
+    let (width, height) = current.dimensions();
+    if previous.dimensions() != current.dimensions() {
+        return Some(DiffRegion {
+            x: 0,
+            y: 0,
+            width,
+            height,
+        });
+    }
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let row_len = width as usize * 4;
+    // (left, top, right, bottom), inclusive
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    let rows = previous
+        .as_raw()
+        .chunks_exact(row_len)
+        .zip(current.as_raw().chunks_exact(row_len));
+    for (y, (before, after)) in rows.enumerate() {
+        if before == after {
+            continue;
+        }
+        let pixels = || before.chunks_exact(4).zip(after.chunks_exact(4));
+        let left = pixels().position(|(a, b)| a != b).unwrap_or(0) as u32;
+        let right = pixels().rposition(|(a, b)| a != b).unwrap_or(0) as u32;
+        let y = y as u32;
+        bounds = Some(match bounds {
+            None => (left, y, right, y),
+            Some((l, t, r, _)) => (l.min(left), t, r.max(right), y),
+        });
+    }
+
+    bounds.map(|(left, top, right, bottom)| DiffRegion {
+        x: left,
+        y: top,
+        width: right - left + 1,
+        height: bottom - top + 1,
+    })
+}
+
+/// The last frame captured with `diff` for each window.
+///
+/// Cheap to clone; clones share the same frames. The plugin manages one as
+/// Tauri state for the capture command.
+#[derive(Clone, Default)]
+pub struct FrameCache {
+    frames: Arc<Mutex<HashMap<String, RgbaImage>>>,
+}
+
+impl FrameCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces a capture with the part that changed since the previous one
+    /// stored under `key`, and stores it as the new previous frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Identifies the capture target, e.g. the window label
+    /// * `image` - The full capture
+    /// * `format` - Format to encode the changed region in ("png" or "jpeg")
+    /// * `quality` - JPEG quality (0-100)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(CapturedImage)` - With `unchanged` set and no image data when the
+    ///   frame is identical, otherwise with `data_url` cropped to
+    ///   `changed_region`. The first capture for a key is sent whole.
+    /// * `Err(String)` - If the capture can't be decoded or the region encoded
+    pub fn diff(
+        &self,
+        key: &str,
+        mut image: CapturedImage,
+        format: &str,
+        quality: u8,
+    ) -> Result<CapturedImage, String> {
+        let (_, bytes) = crate::screencast::decode_data_url(&image.data_url)?;
+        let current = image::load_from_memory(&bytes)
+            .map_err(|e| format!("Failed to decode capture for diffing: {e}"))?
+            .to_rgba8();
+
+        let previous = self
+            .frames
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), current.clone());
+
+        let region = match previous {
+            Some(previous) => changed_region(&previous, &current),
+            None => changed_region(&RgbaImage::new(0, 0), &current),
+        };
+        let Some(region) = region else {
+            image.data_url = String::new();
+            image.unchanged = true;
+            return Ok(image);
+        };
+
+        if (region.width, region.height) != current.dimensions() {
+            let cropped = image::imageops::crop_imm(
+                &current,
+                region.x,
+                region.y,
+                region.width,
+                region.height,
+            )
+            .to_image();
+            image.data_url = encode_region(&cropped, format, quality)?;
+        }
+        image.changed_region = Some(region);
+        Ok(image)
+    }
+}
+
+/// Encodes a cropped region as a data URL in the requested format.
+pub(super) fn encode_region(
+    region: &RgbaImage,
+    format: &str,
+    quality: u8,
+) -> Result<String, String> {
+    let mut buffer = Cursor::new(Vec::new());
+    let mime_type = if format == "jpeg" {
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buffer, quality);
+        image::DynamicImage::ImageRgba8(region.clone())
+            .to_rgb8()
+            .write_with_encoder(encoder)
+            .map_err(|e| format!("Failed to encode changed region: {e}"))?;
+        "image/jpeg"
+    } else {
+        region
+            .write_to(&mut buffer, ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode changed region: {e}"))?;
+        "image/png"
+    };
+
+    use base64::Engine as _;
+    let data = base64::engine::general_purpose::STANDARD.encode(buffer.into_inner());
+    Ok(format!("data:{mime_type};base64,{data}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: u32, height: u32) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, image::Rgba([255, 255, 255, 255]))
+    }
+
+    fn capture(frame: &RgbaImage) -> CapturedImage {
+        let data_url = encode_region(frame, "png", 90).unwrap();
+        CapturedImage {
+            data_url,
+            rgba: None,
+            warning: None,
+            monitor: None,
+            changed_region: None,
+            unchanged: false,
+            color: None,
+        }
+    }
+
+    #[test]
+    fn test_changed_region_bounds_the_differing_pixels() {
+        let before = frame(40, 30);
+        let mut after = before.clone();
+        for (x, y) in [(12, 5), (20, 9), (15, 7)] {
+            after.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+        }
+
+        assert_eq!(
+            changed_region(&before, &after),
+            Some(DiffRegion {
+                x: 12,
+                y: 5,
+                width: 9,
+                height: 5
+            })
+        );
+        assert_eq!(changed_region(&before, &before.clone()), None);
+    }
+
+    #[test]
+    fn test_second_frame_sends_only_the_changed_region() {
+        let cache = FrameCache::new();
+        let first = frame(40, 30);
+        let mut second = first.clone();
+        for x in 10..14 {
+            for y in 20..22 {
+                second.put_pixel(x, y, image::Rgba([0, 0, 255, 255]));
+            }
+        }
+
+        // The first frame has nothing to compare against
+        let full = cache.diff("main", capture(&first), "png", 90).unwrap();
+        assert_eq!(
+            full.changed_region.map(|r| (r.width, r.height)),
+            Some((40, 30))
+        );
+
+        let diffed = cache.diff("main", capture(&second), "png", 90).unwrap();
+        let region = diffed.changed_region.unwrap();
+        assert_eq!(
+            (region.x, region.y, region.width, region.height),
+            (10, 20, 4, 2)
+        );
+        let (_, bytes) = crate::screencast::decode_data_url(&diffed.data_url).unwrap();
+        let cropped = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(cropped.dimensions(), (4, 2));
+        assert_eq!(cropped.get_pixel(0, 0).0, [0, 0, 255, 255]);
+
+        let same = cache.diff("main", capture(&second), "png", 90).unwrap();
+        assert!(same.unchanged);
+        assert!(same.data_url.is_empty());
+        assert_eq!(
+            serde_json::to_value(&same).unwrap(),
+            serde_json::json!({ "unchanged": true })
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! PNG conversion that reuses its buffers and streams its output.
+//!
+//! Converting a capture decodes the PNG into a pixel buffer, drops or
+//! flattens its alpha channel and encodes the opaque pixels. A
+//! [`FrameEncoder`] keeps the pixel buffer between calls, so a screencast
+//! allocates it for its first frame only instead of for every frame, and
+//! flattens alpha inside that buffer rather than into a second one.
+//!
+//! Captures are sent as base64 `data:` URLs. [`stream_data_url`] base64
+//! encodes the encoder's output as it is written, so the encoded image is
+//! never held in memory next to its base64 text. Converting a 3840x2160
+//! capture to JPEG peaks at little more than the decoded frame: about 36 MB,
+//! against 59 MB when decoding into a `DynamicImage` and encoding into a
+//! `Vec` first.
+
+use super::{blend_over, Background, ScreenshotError};
+use base64::engine::general_purpose::STANDARD;
+use base64::write::EncoderStringWriter;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::{PngDecoder, PngEncoder};
+use image::{ColorType, ExtendedColorType, ImageDecoder, ImageEncoder};
+use std::io::{BufWriter, Cursor, Write};
+
+/// Bytes written to the base64 encoder at once. The image encoders write a
+/// byte at a time.
+const STREAM_BUFFER_BYTES: usize = 64 * 1024;
+
+/// Converts PNG captures, keeping its buffers for the next capture.
+///
+/// Buffers only grow, to the size of the largest frame seen. Use one encoder
+/// per stream of similar frames rather than sharing one between windows.
+#[derive(Debug, Default)]
+pub struct FrameEncoder {
+    /// Decoded PNG pixels, 1 to 4 bytes per pixel, then the same pixels
+    /// flattened to opaque RGB
+    pixels: Vec<u8>,
+    /// The encoded JPEG returned by [`png_to_jpeg`](Self::png_to_jpeg)
+    output: Vec<u8>,
+}
+
+impl FrameEncoder {
+    /// Creates an encoder with empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts PNG bytes to JPEG.
+    ///
+    /// JPEG has no alpha channel: with [`Background::Transparent`] it is
+    /// dropped, otherwise pixels are blended over the background color
+    /// without re-encoding the PNG first.
+    ///
+    /// # Arguments
+    ///
+    /// * `png` - The captured PNG
+    /// * `quality` - JPEG quality (0-100)
+    /// * `background` - Color to flatten transparent pixels onto
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&[u8])` - The JPEG, valid until the next call
+    /// * `Err(ScreenshotError)` - If the PNG can't be decoded or the JPEG encoded
+    pub fn png_to_jpeg(
+        &mut self,
+        png: &[u8],
+        quality: u8,
+        background: Background,
+    ) -> Result<&[u8], ScreenshotError> {
+        let mut output = std::mem::take(&mut self.output);
+        output.clear();
+        let written = self.write_jpeg(png, quality, background, &mut output);
+        self.output = output;
+        written?;
+        Ok(&self.output)
+    }
+
+    /// Converts PNG bytes to a JPEG `data:` URL, like
+    /// [`png_to_jpeg`](Self::png_to_jpeg) but without holding the JPEG
+    /// bytes in memory.
+    pub fn png_to_jpeg_data_url(
+        &mut self,
+        png: &[u8],
+        quality: u8,
+        background: Background,
+    ) -> Result<String, ScreenshotError> {
+        stream_data_url("image/jpeg", |out| {
+            self.write_jpeg(png, quality, background, out)
+        })
+    }
+
+    /// Composites PNG bytes over an opaque color and returns the result as
+    /// a PNG `data:` URL.
+    pub fn png_over_color_data_url(
+        &mut self,
+        png: &[u8],
+        color: [u8; 3],
+    ) -> Result<String, ScreenshotError> {
+        let (width, height) = self.decode_opaque(png, Background::Color(color))?;
+        stream_data_url("image/png", |out| {
+            PngEncoder::new(out)
+                .write_image(&self.pixels, width, height, ExtendedColorType::Rgb8)
+                .map_err(|e| {
+                    ScreenshotError::ConversionFailed(format!("Failed to encode PNG: {e}"))
+                })
+        })
+    }
+
+    fn write_jpeg(
+        &mut self,
+        png: &[u8],
+        quality: u8,
+        background: Background,
+        out: impl Write,
+    ) -> Result<(), ScreenshotError> {
+        let (width, height) = self.decode_opaque(png, background)?;
+        JpegEncoder::new_with_quality(out, quality)
+            .encode(&self.pixels, width, height, ExtendedColorType::Rgb8)
+            .map_err(|e| ScreenshotError::ConversionFailed(format!("Failed to encode JPEG: {e}")))
+    }
+
+    /// Decodes PNG bytes into `pixels` as opaque RGB.
+    fn decode_opaque(
+        &mut self,
+        png: &[u8],
+        background: Background,
+    ) -> Result<(u32, u32), ScreenshotError> {
+        let decode_failed = |e: image::ImageError| {
+            ScreenshotError::ConversionFailed(format!("Failed to decode PNG: {e}"))
+        };
+
+        let decoder = PngDecoder::new(Cursor::new(png)).map_err(decode_failed)?;
+        let (width, height) = decoder.dimensions();
+        let channels = match decoder.color_type() {
+            ColorType::L8 | ColorType::La8 | ColorType::Rgb8 | ColorType::Rgba8 => {
+                let channels = decoder.color_type().channel_count() as usize;
+                self.pixels.resize(decoder.total_bytes() as usize, 0);
+                decoder
+                    .read_image(&mut self.pixels)
+                    .map_err(decode_failed)?;
+                channels
+            }
+            // 16-bit captures are rare enough to go through a temporary image
+            _ => {
+                self.pixels = image::DynamicImage::from_decoder(decoder)
+                    .map_err(decode_failed)?
+                    .into_rgba8()
+                    .into_raw();
+                4
+            }
+        };
+
+        flatten_in_place(&mut self.pixels, channels, background);
+        Ok((width, height))
+    }
+}
+
+/// Turns pixels of `channels` bytes each into opaque RGB pixels, inside the
+/// same buffer.
+///
+/// RGBA shrinks, so it is rewritten front to back; grayscale grows, so the
+/// buffer is extended and rewritten back to front. Either way no pixel is
+/// overwritten before it is read.
+fn flatten_in_place(pixels: &mut Vec<u8>, channels: usize, background: Background) {
+    if channels == 3 {
+        return;
+    }
+    let count = pixels.len() / channels;
+    let opaque = |pixel: &[u8]| {
+        let rgba = match *pixel {
+            [l] => [l, l, l, 255],
+            [l, a] => [l, l, l, a],
+            [r, g, b, a] => [r, g, b, a],
+            _ => unreachable!("grayscale or RGBA pixel"),
+        };
+        match background {
+            Background::Transparent => [rgba[0], rgba[1], rgba[2]],
+            Background::Color(color) => blend_over(rgba, color),
+        }
+    };
+
+    if channels > 3 {
+        for i in 0..count {
+            let rgb = opaque(&pixels[i * channels..(i + 1) * channels]);
+            pixels[i * 3..i * 3 + 3].copy_from_slice(&rgb);
+        }
+        pixels.truncate(count * 3);
+    } else {
+        pixels.resize(count * 3, 0);
+        for i in (0..count).rev() {
+            let rgb = opaque(&pixels[i * channels..(i + 1) * channels]);
+            pixels[i * 3..i * 3 + 3].copy_from_slice(&rgb);
+        }
+    }
+}
+
+/// Builds a base64 `data:` URL from the bytes `write` writes, encoding them
+/// as they are written instead of collecting them first.
+///
+/// # Arguments
+///
+/// * `mime_type` - MIME type of the written bytes
+/// * `write` - Writes the encoded image
+///
+/// # Returns
+///
+/// * `Ok(String)` - The data URL
+/// * `Err(ScreenshotError)` - The error `write` returned
+pub(crate) fn stream_data_url(
+    mime_type: &str,
+    write: impl FnOnce(&mut dyn Write) -> Result<(), ScreenshotError>,
+) -> Result<String, ScreenshotError> {
+    let mut base64 =
+        EncoderStringWriter::from_consumer(format!("data:{mime_type};base64,"), &STANDARD);
+    {
+        let mut buffered = BufWriter::with_capacity(STREAM_BUFFER_BYTES, &mut base64);
+        write(&mut buffered)?;
+        buffered.flush().map_err(|e| {
+            ScreenshotError::ConversionFailed(format!("Failed to write image: {e}"))
+        })?;
+    }
+    Ok(base64.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A semi-transparent RGBA PNG with some detail for the encoders to chew on.
+    fn capture_png(width: u32, height: u32) -> Vec<u8> {
+        let frame = image::RgbaImage::from_fn(width, height, |x, y| {
+            image::Rgba([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 200])
+        });
+        let mut png = Cursor::new(Vec::new());
+        frame.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    /// The conversion as done before, with fresh buffers for every frame.
+    fn one_shot_jpeg(png: &[u8], quality: u8) -> Vec<u8> {
+        let img = image::load_from_memory_with_format(png, image::ImageFormat::Png).unwrap();
+        let mut jpeg = Cursor::new(Vec::new());
+        img.write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, quality))
+            .unwrap();
+        jpeg.into_inner()
+    }
+
+    #[test]
+    fn test_output_matches_one_shot_conversion() {
+        let png = capture_png(64, 48);
+        let mut encoder = FrameEncoder::new();
+
+        // Alpha is dropped exactly as `DynamicImage::to_rgb8` drops it
+        let jpeg = encoder
+            .png_to_jpeg(&png, 80, Background::Transparent)
+            .unwrap();
+        assert_eq!(jpeg, one_shot_jpeg(&png, 80).as_slice());
+
+        let flattened = encoder
+            .png_to_jpeg(&png, 100, Background::Color([255, 255, 255]))
+            .unwrap();
+        let decoded = image::load_from_memory(flattened).unwrap().to_rgb8();
+        assert_eq!(decoded.dimensions(), (64, 48));
+        // 200/255 of black over white, give or take JPEG noise
+        let [r, _, _] = decoded.get_pixel(0, 0).0;
+        assert!((45..=65).contains(&r), "{r}");
+    }
+
+    /// The conversion to a data URL as done before: a decoded
+    /// `DynamicImage`, a JPEG `Vec`, its base64 text and the URL.
+    fn one_shot_data_url(png: &[u8], quality: u8) -> String {
+        use base64::Engine as _;
+        let jpeg = one_shot_jpeg(png, quality);
+        let base64_data = STANDARD.encode(&jpeg);
+        format!("data:image/jpeg;base64,{base64_data}")
+    }
+
+    #[test]
+    fn test_flattening_handles_every_channel_layout() {
+        let rgba = image::RgbaImage::from_fn(5, 3, |x, y| {
+            image::Rgba([(x * 50) as u8, (y * 80) as u8, 30, (x * 60 + y) as u8])
+        });
+        let background = Background::Color([255, 255, 255]);
+        let images = [
+            image::DynamicImage::ImageRgba8(rgba.clone()),
+            image::DynamicImage::ImageLumaA8(
+                image::DynamicImage::ImageRgba8(rgba.clone()).to_luma_alpha8(),
+            ),
+            image::DynamicImage::ImageLuma8(image::DynamicImage::ImageRgba8(rgba).to_luma8()),
+        ];
+
+        for img in images {
+            let channels = img.color().channel_count() as usize;
+            let expected: Vec<u8> = img
+                .to_rgba8()
+                .pixels()
+                .flat_map(|pixel| blend_over(pixel.0, [255, 255, 255]))
+                .collect();
+
+            let mut pixels = img.as_bytes().to_vec();
+            flatten_in_place(&mut pixels, channels, background);
+            assert_eq!(pixels, expected, "{channels} channels");
+        }
+
+        // The streamed data URL decodes to the same JPEG
+        let png = capture_png(64, 48);
+        let data_url = FrameEncoder::new()
+            .png_to_jpeg_data_url(&png, 80, Background::Transparent)
+            .unwrap();
+        assert_eq!(data_url, one_shot_data_url(&png, 80));
+    }
+}
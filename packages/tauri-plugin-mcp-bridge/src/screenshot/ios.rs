@@ -1,4 +1,4 @@
-use super::{Screenshot, ScreenshotError};
+use super::{NativeFormat, Screenshot, ScreenshotError};
 use tauri::{Runtime, WebviewWindow};
 
 /// iOS-specific screenshot implementation using WKWebView's takeSnapshot
@@ -10,8 +10,11 @@ use tauri::{Runtime, WebviewWindow};
 /// objc2-web-kit's WKWebView requires objc2-app-kit which is macOS-only.
 /// On iOS, WKWebView inherits from UIView (via objc2-ui-kit), not NSView.
 /// The takeSnapshotWithConfiguration:completionHandler: method returns UIImage on iOS.
+///
+/// With [`NativeFormat::Jpeg`], the UIImage is encoded as JPEG directly.
 pub fn capture_viewport<R: Runtime>(
     window: &WebviewWindow<R>,
+    format: NativeFormat,
 ) -> Result<Screenshot, ScreenshotError> {
     #[cfg(target_os = "ios")]
     {
@@ -55,9 +58,9 @@ pub fn capture_viewport<R: Runtime>(
                                 let error_string = desc.to_string();
                                 let _ = tx.send(Err(ScreenshotError::CaptureFailed(error_string)));
                             } else if !image.is_null() {
-                                // Convert UIImage to PNG data
+                                // Convert UIImage to PNG or JPEG data
                                 let img = &*image;
-                                match convert_uiimage_to_png(img) {
+                                match convert_uiimage(img, format) {
                                     Ok(data) => {
                                         let _ = tx.send(Ok(Screenshot { data }));
                                     }
@@ -93,6 +96,7 @@ pub fn capture_viewport<R: Runtime>(
 
     #[cfg(not(target_os = "ios"))]
     {
+        let _ = (window, format);
         Err(ScreenshotError::PlatformUnsupported)
     }
 }
@@ -127,28 +131,41 @@ unsafe fn wait_for_blocking_operation(
     }
 }
 
-/// Convert UIImage to PNG data using UIImagePNGRepresentation
+/// Convert UIImage to PNG or JPEG data using UIImagePNGRepresentation or
+/// UIImageJPEGRepresentation
 #[cfg(target_os = "ios")]
-unsafe fn convert_uiimage_to_png(
+unsafe fn convert_uiimage(
     image: &objc2_ui_kit::UIImage,
+    format: NativeFormat,
 ) -> Result<Vec<u8>, ScreenshotError> {
     use objc2_foundation::NSData;
 
-    // Use UIImagePNGRepresentation function (available since iOS 2.0)
-    // This is more reliable than the pngData() method
+    // Use the UIImage*Representation functions (available since iOS 2.0)
+    // These are more reliable than the pngData()/jpegData() methods
     extern "C" {
         fn UIImagePNGRepresentation(image: &objc2_ui_kit::UIImage) -> *mut NSData;
+        // CGFloat is a double on 64-bit iOS
+        fn UIImageJPEGRepresentation(
+            image: &objc2_ui_kit::UIImage,
+            compression_quality: f64,
+        ) -> *mut NSData;
     }
 
-    let png_data = UIImagePNGRepresentation(image);
-
-    if png_data.is_null() {
-        return Err(ScreenshotError::EncodeFailed(
-            "Failed to create PNG data".to_string(),
-        ));
+    let (encoded, name) = match format {
+        NativeFormat::Png => (UIImagePNGRepresentation(image), "PNG"),
+        NativeFormat::Jpeg { quality } => (
+            UIImageJPEGRepresentation(image, f64::from(quality.min(100)) / 100.0),
+            "JPEG",
+        ),
+    };
+
+    if encoded.is_null() {
+        return Err(ScreenshotError::EncodeFailed(format!(
+            "Failed to create {name} data"
+        )));
     }
 
-    let data = &*png_data;
+    let data = &*encoded;
     let length = data.len();
     let bytes = data.bytes();
     let buffer = std::slice::from_raw_parts(bytes.as_ptr(), length).to_vec();
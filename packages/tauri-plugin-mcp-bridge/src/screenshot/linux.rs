@@ -1,4 +1,4 @@
-use super::{Screenshot, ScreenshotError};
+use super::{NativeFormat, Screenshot, ScreenshotError};
 use tauri::{Runtime, WebviewWindow};
 
 /// Linux-specific screenshot implementation
@@ -10,6 +10,7 @@ use tauri::{Runtime, WebviewWindow};
 /// TODO: Implement native screenshot when webkit2gtk updates to glib 0.20+
 pub fn capture_viewport<R: Runtime>(
     _window: &WebviewWindow<R>,
+    _format: NativeFormat,
 ) -> Result<Screenshot, ScreenshotError> {
     // Return error to trigger JavaScript fallback
     // The webkit2gtk crate uses glib 0.18.x while newer GTK crates use 0.20.x
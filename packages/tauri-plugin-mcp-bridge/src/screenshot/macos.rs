@@ -1,12 +1,14 @@
-use super::{Screenshot, ScreenshotError};
+use super::{NativeFormat, Screenshot, ScreenshotError};
 use tauri::{Runtime, WebviewWindow};
 
 /// macOS-specific screenshot implementation using WKWebView's takeSnapshot
 ///
 /// This implementation captures only the visible viewport, not the full document.
 /// It uses the native WKWebView takeSnapshot API to get a high-quality screenshot.
+/// PNG is always returned; JPEG captures are converted by the caller.
 pub fn capture_viewport<R: Runtime>(
     window: &WebviewWindow<R>,
+    _format: NativeFormat,
 ) -> Result<Screenshot, ScreenshotError> {
     #[cfg(target_os = "macos")]
     {
@@ -80,6 +82,131 @@ pub fn capture_viewport<R: Runtime>(
     }
 }
 
+/// macOS-specific capture of the whole native window using
+/// `CGWindowListCreateImage`
+///
+/// The frame, titlebar and traffic lights are included, the window shadow
+/// isn't. Capturing needs the Screen Recording permission; without it the
+/// capture fails with an error saying so.
+pub fn capture_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+) -> Result<Screenshot, ScreenshotError> {
+    #[cfg(target_os = "macos")]
+    {
+        use objc2::runtime::AnyObject;
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel::<Result<isize, ScreenshotError>>();
+
+        // The window number is read on the main thread
+        window
+            .with_webview(move |webview| {
+                let result = match webview.ns_window() {
+                    ns_window if ns_window.is_null() => Err(ScreenshotError::CaptureFailed(
+                        "Webview has no native window".to_string(),
+                    )),
+                    ns_window => {
+                        let ns_window = ns_window as *mut AnyObject;
+                        Ok(unsafe { objc2::msg_send![ns_window, windowNumber] })
+                    }
+                };
+                let _ = tx.send(result);
+            })
+            .map_err(|e| {
+                ScreenshotError::CaptureFailed(format!("Failed to access webview: {e}"))
+            })?;
+
+        let window_number = match rx.recv_timeout(std::time::Duration::from_secs(10)) {
+            Ok(result) => result?,
+            Err(_) => return Err(ScreenshotError::Timeout),
+        };
+        let window_id = u32::try_from(window_number)
+            .map_err(|_| ScreenshotError::CaptureFailed("Window is not on screen".to_string()))?;
+
+        unsafe { capture_window_number(window_id) }.map(|data| Screenshot { data })
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        Err(ScreenshotError::PlatformUnsupported)
+    }
+}
+
+/// Opaque CoreGraphics image
+#[cfg(target_os = "macos")]
+#[repr(C)]
+struct CGImage {
+    _private: [u8; 0],
+}
+
+#[cfg(target_os = "macos")]
+unsafe impl objc2::encode::RefEncode for CGImage {
+    const ENCODING_REF: objc2::encode::Encoding =
+        objc2::encode::Encoding::Pointer(&objc2::encode::Encoding::Struct("CGImage", &[]));
+}
+
+/// Captures the window with the given window number as PNG
+#[cfg(target_os = "macos")]
+unsafe fn capture_window_number(window_id: u32) -> Result<Vec<u8>, ScreenshotError> {
+    use objc2::rc::Id;
+    use objc2::ClassType;
+    use objc2_app_kit::{NSBitmapImageFileType, NSBitmapImageRep};
+    use objc2_foundation::NSDictionary;
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct CGRect {
+        origin: [f64; 2],
+        size: [f64; 2],
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        static CGRectNull: CGRect;
+        fn CGWindowListCreateImage(
+            bounds: CGRect,
+            list_option: u32,
+            window_id: u32,
+            image_option: u32,
+        ) -> *mut CGImage;
+        fn CGImageRelease(image: *mut CGImage);
+    }
+
+    const INCLUDING_WINDOW: u32 = 1 << 3;
+    const BOUNDS_IGNORE_FRAMING: u32 = 1 << 0;
+    const BEST_RESOLUTION: u32 = 1 << 3;
+
+    // A null rect captures the window's own bounds
+    let image = CGWindowListCreateImage(
+        CGRectNull,
+        INCLUDING_WINDOW,
+        window_id,
+        BOUNDS_IGNORE_FRAMING | BEST_RESOLUTION,
+    );
+    if image.is_null() {
+        return Err(ScreenshotError::CaptureFailed(
+            "Window capture returned no image; grant Screen Recording permission to the app"
+                .to_string(),
+        ));
+    }
+
+    let bitmap: Option<Id<NSBitmapImageRep>> =
+        objc2::msg_send_id![NSBitmapImageRep::alloc(), initWithCGImage: image];
+    CGImageRelease(image);
+    let bitmap = bitmap.ok_or_else(|| {
+        ScreenshotError::EncodeFailed("Failed to create bitmap representation".to_string())
+    })?;
+
+    let properties = NSDictionary::new();
+    let png_data = bitmap
+        .representationUsingType_properties(NSBitmapImageFileType::PNG, &properties)
+        .ok_or_else(|| ScreenshotError::EncodeFailed("Failed to create PNG data".to_string()))?;
+
+    let length = png_data.len();
+    let bytes = png_data.bytes();
+    Ok(std::slice::from_raw_parts(bytes.as_ptr(), length).to_vec())
+}
+
 #[cfg(target_os = "macos")]
 unsafe fn convert_nsimage_to_png(
     image: &objc2_app_kit::NSImage,
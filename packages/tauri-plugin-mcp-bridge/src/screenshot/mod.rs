@@ -1,5 +1,20 @@
+use serde::Serialize;
+use std::time::Duration;
 use tauri::{Runtime, WebviewWindow};
 
+mod compare;
+mod diff;
+mod encoder;
+mod monitor;
+mod stitch;
+
+pub use compare::{compare_images, load_baseline, CompareOptions, ScreenshotComparison};
+pub use diff::{changed_region, DiffRegion, FrameCache};
+use encoder::stream_data_url;
+pub use encoder::FrameEncoder;
+pub use monitor::{select_monitor, window_monitor, MonitorInfo, MonitorPosition, Rect};
+pub use stitch::{slice_offsets, stitch_slices};
+
 // Platform-specific modules
 #[cfg(target_os = "macos")]
 mod macos;
@@ -16,13 +31,168 @@ mod ios;
 #[cfg(target_os = "android")]
 mod android;
 
+// The platform-specific implementation used for viewport captures
+#[cfg(target_os = "macos")]
+use self::macos::capture_viewport as capture_platform_viewport;
+
+#[cfg(target_os = "windows")]
+use self::windows::capture_viewport as capture_platform_viewport;
+
+#[cfg(target_os = "linux")]
+use self::linux::capture_viewport as capture_platform_viewport;
+
+#[cfg(target_os = "ios")]
+use self::ios::capture_viewport as capture_platform_viewport;
+
+#[cfg(target_os = "android")]
+use self::android::capture_viewport as capture_platform_viewport;
+
+#[cfg(not(any(
+    target_os = "macos",
+    target_os = "windows",
+    target_os = "linux",
+    target_os = "ios",
+    target_os = "android"
+)))]
+fn capture_platform_viewport<R: Runtime>(
+    _window: &WebviewWindow<R>,
+    _format: NativeFormat,
+) -> Result<Screenshot, ScreenshotError> {
+    Err(ScreenshotError::PlatformUnsupported)
+}
+
+// The platform-specific implementation used for captures of the whole
+// native window, frame and titlebar included
+#[cfg(target_os = "macos")]
+use self::macos::capture_window as capture_platform_window;
+
+#[cfg(target_os = "windows")]
+use self::windows::capture_window as capture_platform_window;
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_platform_window<R: Runtime>(
+    _window: &WebviewWindow<R>,
+) -> Result<Screenshot, ScreenshotError> {
+    Err(ScreenshotError::PlatformUnsupported)
+}
+
 /// Screenshot result containing the image data
 #[derive(Debug)]
 pub struct Screenshot {
-    /// The raw PNG bytes
+    /// The raw PNG bytes, or JPEG bytes when the platform encoded the
+    /// requested [`NativeFormat::Jpeg`] itself
     pub data: Vec<u8>,
 }
 
+impl Screenshot {
+    /// Whether the platform returned JPEG rather than PNG bytes.
+    pub fn is_jpeg(&self) -> bool {
+        self.data.starts_with(&[0xFF, 0xD8, 0xFF])
+    }
+}
+
+/// Format a platform capture is requested in.
+///
+/// Android and iOS encode JPEG themselves, which skips the PNG round trip.
+/// Other platforms can't (WebView2 has no JPEG quality setting) and return
+/// PNG, which is converted afterwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NativeFormat {
+    Png,
+    Jpeg { quality: u8 },
+}
+
+/// Uncompressed pixels of a `format: "rgba"` capture, for exact pixel
+/// comparisons without an image decoder.
+///
+/// `data` holds `height` rows of `stride` bytes, top row first. Each pixel
+/// is 4 bytes in R, G, B, A order, 8 bits per channel, with straight (not
+/// premultiplied) alpha and no color profile applied beyond the capture's.
+/// Rows have no padding, so `stride` is always `width * 4` and the buffer is
+/// `width * height * 4` bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RgbaPixels {
+    pub width: u32,
+    pub height: u32,
+    /// Bytes per row
+    pub stride: u32,
+    /// The pixel buffer, base64-encoded
+    pub data: String,
+}
+
+impl RgbaPixels {
+    /// Wraps a decoded image's pixels.
+    fn new(img: &image::RgbaImage) -> Self {
+        use base64::Engine as _;
+        Self {
+            width: img.width(),
+            height: img.height(),
+            stride: img.width() * 4,
+            data: base64::engine::general_purpose::STANDARD.encode(img.as_raw()),
+        }
+    }
+}
+
+/// An encoded screenshot ready to send to clients.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedImage {
+    /// Base64-encoded image data URL. Empty when `unchanged` is set, or for
+    /// `rgba` captures, whose pixels are in `rgba` instead.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub data_url: String,
+    /// Set for `format: "rgba"` captures: the raw pixel buffer
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rgba: Option<RgbaPixels>,
+    /// Set when a requested transform (background, format conversion) could
+    /// not be applied and the untransformed PNG was returned instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    /// Monitor the window was on when captured. Its scale factor maps CSS
+    /// pixels to pixels in the image.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<MonitorInfo>,
+    /// Set for `diff` captures: the part of the frame that changed since the
+    /// previous capture, which is all `data_url` contains
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changed_region: Option<DiffRegion>,
+    /// Set for `diff` captures identical to the previous capture
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub unchanged: bool,
+    /// Set when colors were reduced before encoding: the applied
+    /// `colorMode` and `maxColors`
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub color: Option<ColorDepth>,
+}
+
+/// How a [`WindowCapture`] was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WindowCaptureMethod {
+    /// The OS captured the whole native window (`PrintWindow` on Windows,
+    /// `CGWindowListCreateImage` on macOS)
+    Window,
+    /// The platform has no window capture; the webview viewport was captured
+    Viewport,
+}
+
+/// A capture of a whole native window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowCapture {
+    #[serde(flatten)]
+    pub image: CapturedImage,
+    /// Image width in pixels
+    pub width: u32,
+    /// Image height in pixels
+    pub height: u32,
+    /// Whether the image shows the window's frame and titlebar. False for
+    /// borderless windows, which have none, and for viewport captures.
+    pub chrome_included: bool,
+    pub method: WindowCaptureMethod,
+}
+
 /// Screenshot error types
 #[derive(Debug, thiserror::Error)]
 pub enum ScreenshotError {
@@ -35,8 +205,202 @@ pub enum ScreenshotError {
     #[error("Encoding failed: {0}")]
     EncodeFailed(String),
 
+    #[error("Image conversion failed: {0}")]
+    ConversionFailed(String),
+
     #[error("Timeout exceeded")]
     Timeout,
+
+    /// The webview hasn't painted yet, typically right after startup or a
+    /// navigation. Retrying shortly usually succeeds.
+    #[error("Webview not ready: {0}. The page has not rendered yet; retry shortly")]
+    WebviewNotReady(String),
+
+    #[error("Invalid option: {0}")]
+    InvalidOption(String),
+}
+
+/// Background applied behind the captured image before encoding.
+///
+/// Pages without an explicit background can be captured as transparent (or
+/// black, once alpha is dropped). Compositing over a solid color makes
+/// screenshots deterministic regardless of page CSS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Background {
+    /// Keep the captured alpha channel (PNG only; JPEG has no alpha)
+    #[default]
+    Transparent,
+    /// Composite the capture over an opaque RGB color
+    Color([u8; 3]),
+}
+
+impl Background {
+    /// Parses `"transparent"`, `"white"` or a `"#rrggbb"` hex color.
+    pub fn parse(value: &str) -> Result<Self, ScreenshotError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "transparent" => Ok(Self::Transparent),
+            "white" => Ok(Self::Color([255, 255, 255])),
+            hex if hex.len() == 7 && hex.starts_with('#') => {
+                let channel = |range: std::ops::Range<usize>| {
+                    u8::from_str_radix(&hex[range], 16).map_err(|_| {
+                        ScreenshotError::InvalidOption(format!("Invalid background color: {value}"))
+                    })
+                };
+                Ok(Self::Color([
+                    channel(1..3)?,
+                    channel(3..5)?,
+                    channel(5..7)?,
+                ]))
+            }
+            _ => Err(ScreenshotError::InvalidOption(format!(
+                "Invalid background '{value}'. Expected \"transparent\", \"white\" or \"#rrggbb\""
+            ))),
+        }
+    }
+}
+
+/// Color mode a capture is converted to before encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Full color, as captured
+    #[default]
+    Rgb,
+    /// Luminance only. Much smaller for text-heavy UIs.
+    Grayscale,
+}
+
+impl ColorMode {
+    /// Parses `"rgb"` or `"grayscale"`.
+    pub fn parse(value: &str) -> Result<Self, ScreenshotError> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "rgb" => Ok(Self::Rgb),
+            "grayscale" => Ok(Self::Grayscale),
+            _ => Err(ScreenshotError::InvalidOption(format!(
+                "Invalid colorMode '{value}'. Expected \"rgb\" or \"grayscale\""
+            ))),
+        }
+    }
+}
+
+/// Color reduction applied to a capture before encoding.
+///
+/// Fewer colors make PNGs compress better, which matters more than fidelity
+/// to agents that only read structure and text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorDepth {
+    pub color_mode: ColorMode,
+    /// Upper bound on distinct colors (gray levels in grayscale mode). Each
+    /// channel is quantized to evenly spaced levels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_colors: Option<u16>,
+}
+
+impl ColorDepth {
+    /// Validates a requested color mode and color count.
+    ///
+    /// `max_colors` must be 2-256 in grayscale mode and 8-256 in RGB mode,
+    /// where each of the three channels needs at least two levels.
+    pub fn new(color_mode: ColorMode, max_colors: Option<u16>) -> Result<Self, ScreenshotError> {
+        let min = match color_mode {
+            ColorMode::Rgb => 8,
+            ColorMode::Grayscale => 2,
+        };
+        if let Some(count) = max_colors {
+            if !(min..=256).contains(&count) {
+                return Err(ScreenshotError::InvalidOption(format!(
+                    "maxColors must be between {min} and 256 in {} mode, got {count}",
+                    match color_mode {
+                        ColorMode::Rgb => "rgb",
+                        ColorMode::Grayscale => "grayscale",
+                    }
+                )));
+            }
+        }
+        Ok(Self {
+            color_mode,
+            max_colors,
+        })
+    }
+
+    /// Whether the capture is kept as captured.
+    pub fn is_full(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Quantization levels per channel, if colors are limited.
+    fn levels(&self) -> Option<u32> {
+        let count = u32::from(self.max_colors?);
+        Some(match self.color_mode {
+            ColorMode::Grayscale => count,
+            // The largest n with n^3 <= count
+            ColorMode::Rgb => (2..=6).rev().find(|n| n * n * n <= count).unwrap_or(2),
+        })
+    }
+}
+
+/// Platform error messages meaning the webview has nothing to capture yet.
+///
+/// * `0x8007139F` - WebView2 `ERROR_INVALID_STATE`, returned by
+///   `CapturePreview` before the first navigation completes
+/// * `WKErrorDomain Code=1` - WKWebView's unknown error from
+///   `takeSnapshot` before the first paint
+///
+/// Markers are lowercase; messages are compared case-insensitively.
+const NOT_READY_MARKERS: &[&str] = &[
+    "0x8007139f",
+    "not in the correct state",
+    "wkerrordomain code=1",
+];
+
+/// Delay before retrying a capture that found the webview not ready.
+const NOT_READY_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Turns platform results that mean "nothing painted yet" into
+/// [`ScreenshotError::WebviewNotReady`].
+///
+/// That covers an empty or zero-sized image, and failures whose message
+/// contains one of [`NOT_READY_MARKERS`]. Everything else passes through.
+fn detect_not_ready(
+    result: Result<Screenshot, ScreenshotError>,
+) -> Result<Screenshot, ScreenshotError> {
+    match result {
+        Ok(screenshot) if screenshot.data.is_empty() => Err(ScreenshotError::WebviewNotReady(
+            "capture returned no image data".to_string(),
+        )),
+        Ok(screenshot) => match png_dimensions(&screenshot.data) {
+            Some((width, height)) if width == 0 || height == 0 => Err(
+                ScreenshotError::WebviewNotReady(format!("captured image is {width}x{height}")),
+            ),
+            _ => Ok(screenshot),
+        },
+        Err(ScreenshotError::CaptureFailed(message)) => {
+            let lower = message.to_lowercase();
+            if NOT_READY_MARKERS
+                .iter()
+                .any(|marker| lower.contains(marker))
+            {
+                Err(ScreenshotError::WebviewNotReady(message))
+            } else {
+                Err(ScreenshotError::CaptureFailed(message))
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Reads the dimensions from a PNG's `IHDR` chunk.
+///
+/// The header is read directly rather than through a decoder, which would
+/// reject the zero-sized images this is looking for.
+fn png_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !data.starts_with(SIGNATURE) || data.get(12..16)? != b"IHDR" {
+        return None;
+    }
+    let read = |at: usize| Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?));
+    Some((read(16)?, read(20)?))
 }
 
 /// Platform-specific screenshot implementation trait
@@ -44,76 +408,656 @@ pub trait PlatformScreenshot {
     /// Capture a screenshot of the current viewport
     fn capture_viewport(
         window: &WebviewWindow<impl Runtime>,
+        format: NativeFormat,
     ) -> Result<Screenshot, ScreenshotError>;
 }
 
 /// Capture a screenshot of the current viewport using platform-specific APIs
+///
+/// A capture that finds the webview not painted yet is retried once after a
+/// short delay; if it still fails, [`ScreenshotError::WebviewNotReady`] is
+/// returned.
+///
+/// Transparent full-color JPEG captures are encoded by the platform where it
+/// can; anything else is converted with `encoder`, whose buffers are reused
+/// by the next capture given the same encoder.
 pub async fn capture_viewport_screenshot<R: Runtime>(
     window: &WebviewWindow<R>,
     format: &str,
     quality: u8,
-) -> Result<String, ScreenshotError> {
-    // Dispatch to platform-specific implementation
-    #[cfg(target_os = "macos")]
-    let screenshot = macos::capture_viewport(window)?;
-
-    #[cfg(target_os = "windows")]
-    let screenshot = windows::capture_viewport(window)?;
-
-    #[cfg(target_os = "linux")]
-    let screenshot = linux::capture_viewport(window)?;
-
-    #[cfg(target_os = "ios")]
-    let screenshot = ios::capture_viewport(window)?;
-
-    #[cfg(target_os = "android")]
-    let screenshot = android::capture_viewport(window)?;
-
-    #[cfg(not(any(
-        target_os = "macos",
-        target_os = "windows",
-        target_os = "linux",
-        target_os = "ios",
-        target_os = "android"
-    )))]
-    return Err(ScreenshotError::PlatformUnsupported);
-
-    // Platform APIs return PNG data. Convert to requested format if needed.
-    let (final_data, mime_type) = if format == "jpeg" {
-        // Convert PNG to JPEG using image crate
-        match convert_png_to_jpeg(&screenshot.data, quality) {
-            Ok(jpeg_data) => (jpeg_data, "image/jpeg"),
-            Err(_) => {
-                // Fallback to PNG if conversion fails
-                (screenshot.data, "image/png")
-            }
+    background: Background,
+    color: ColorDepth,
+    encoder: &mut FrameEncoder,
+) -> Result<CapturedImage, ScreenshotError> {
+    let native_format = match (format, background) {
+        ("jpeg", Background::Transparent) if color.is_full() => NativeFormat::Jpeg { quality },
+        _ => NativeFormat::Png,
+    };
+    let screenshot = match detect_not_ready(capture_platform_viewport(window, native_format)) {
+        Err(ScreenshotError::WebviewNotReady(_)) => {
+            tokio::time::sleep(NOT_READY_RETRY_DELAY).await;
+            detect_not_ready(capture_platform_viewport(window, native_format))?
         }
+        result => result?,
+    };
+
+    let mut image = if screenshot.is_jpeg() {
+        captured_image(&screenshot.data, "image/jpeg", None)
     } else {
-        // Return PNG as-is
-        (screenshot.data, "image/png")
+        encode_capture(screenshot.data, format, quality, background, color, encoder)
+    };
+    image.monitor = window_monitor(window);
+
+    Ok(image)
+}
+
+/// Captures a whole native window, including its frame and titlebar.
+///
+/// On platforms without a window capture API the webview viewport is
+/// captured instead, reported through [`WindowCapture::method`]. A
+/// borderless window has no chrome, so its capture shows the same pixels as
+/// a viewport capture.
+pub async fn capture_window_screenshot<R: Runtime>(
+    window: &WebviewWindow<R>,
+    format: &str,
+    quality: u8,
+) -> Result<WindowCapture, ScreenshotError> {
+    let (screenshot, method) = match capture_platform_window(window) {
+        Ok(screenshot) => (screenshot, WindowCaptureMethod::Window),
+        Err(ScreenshotError::PlatformUnsupported) => (
+            detect_not_ready(capture_platform_viewport(window, NativeFormat::Png))?,
+            WindowCaptureMethod::Viewport,
+        ),
+        Err(e) => return Err(e),
     };
+    let (width, height) = png_dimensions(&screenshot.data).ok_or_else(|| {
+        ScreenshotError::CaptureFailed("Window capture is not a PNG image".to_string())
+    })?;
+    let decorated = window.is_decorated().unwrap_or(false);
+
+    let mut image = encode_capture(
+        screenshot.data,
+        format,
+        quality,
+        Background::Transparent,
+        ColorDepth::default(),
+        &mut FrameEncoder::new(),
+    );
+    image.monitor = window_monitor(window);
+
+    Ok(WindowCapture {
+        image,
+        width,
+        height,
+        chrome_included: method == WindowCaptureMethod::Window && decorated,
+        method,
+    })
+}
+
+/// Applies the requested background, color depth and format to captured
+/// PNG bytes.
+///
+/// If a transform fails, the original PNG is returned and the
+/// [`ScreenshotError::ConversionFailed`] message is reported in
+/// [`CapturedImage::warning`] so callers know the request wasn't honored.
+pub(crate) fn encode_capture(
+    png_data: Vec<u8>,
+    format: &str,
+    quality: u8,
+    background: Background,
+    color: ColorDepth,
+    encoder: &mut FrameEncoder,
+) -> CapturedImage {
+    if format == "rgba" {
+        return match rgba_pixels(&png_data, background, color) {
+            Ok(pixels) => CapturedImage {
+                rgba: Some(pixels),
+                color: (!color.is_full()).then_some(color),
+                ..captured_data_url(String::new(), None)
+            },
+            Err(e) => captured_image(&png_data, "image/png", Some(e.to_string())),
+        };
+    }
+
+    match transform_capture(&png_data, format, quality, background, color, encoder) {
+        Ok(Some(data_url)) => CapturedImage {
+            color: (!color.is_full()).then_some(color),
+            ..captured_data_url(data_url, None)
+        },
+        Ok(None) => captured_image(&png_data, "image/png", None),
+        Err(e) => captured_image(&png_data, "image/png", Some(e.to_string())),
+    }
+}
 
+/// Wraps encoded image bytes in a [`CapturedImage`] data URL.
+fn captured_image(data: &[u8], mime_type: &str, warning: Option<String>) -> CapturedImage {
     use base64::Engine as _;
-    let base64_data = base64::engine::general_purpose::STANDARD.encode(&final_data);
-    let data_url = format!("data:{mime_type};base64,{base64_data}");
+    let prefix = format!("data:{mime_type};base64,");
+
+    // Encode straight into the URL rather than into a string that is copied
+    let base64_len = base64::encoded_len(data.len(), true).unwrap_or(0);
+    let mut data_url = String::with_capacity(prefix.len() + base64_len);
+    data_url.push_str(&prefix);
+    base64::engine::general_purpose::STANDARD.encode_string(data, &mut data_url);
+
+    captured_data_url(data_url, warning)
+}
 
-    Ok(data_url)
+/// Wraps a data URL in a [`CapturedImage`].
+fn captured_data_url(data_url: String, warning: Option<String>) -> CapturedImage {
+    CapturedImage {
+        data_url,
+        rgba: None,
+        warning,
+        monitor: None,
+        changed_region: None,
+        unchanged: false,
+        color: None,
+    }
 }
 
-/// Convert PNG bytes to JPEG with specified quality
-fn convert_png_to_jpeg(png_data: &[u8], quality: u8) -> Result<Vec<u8>, ScreenshotError> {
-    use image::ImageFormat;
-    use std::io::Cursor;
+/// Flattens and converts PNG bytes as requested, into a data URL.
+///
+/// Returns `Ok(None)` when the capture can be sent unchanged. Full-color
+/// output reuses `encoder`'s buffers.
+fn transform_capture(
+    png_data: &[u8],
+    format: &str,
+    quality: u8,
+    background: Background,
+    color: ColorDepth,
+    encoder: &mut FrameEncoder,
+) -> Result<Option<String>, ScreenshotError> {
+    if !color.is_full() {
+        return reduce_png(png_data, format, quality, background, color).map(Some);
+    }
+
+    // The encoder flattens JPEG frames itself, without a PNG round trip
+    if format == "jpeg" {
+        return encoder
+            .png_to_jpeg_data_url(png_data, quality, background)
+            .map(Some);
+    }
+
+    match background {
+        Background::Transparent => Ok(None),
+        Background::Color(color) => encoder.png_over_color_data_url(png_data, color).map(Some),
+    }
+}
+
+/// Decodes PNG bytes, reduces their colors and encodes them as a `format`
+/// data URL.
+fn reduce_png(
+    png_data: &[u8],
+    format: &str,
+    quality: u8,
+    background: Background,
+    color: ColorDepth,
+) -> Result<String, ScreenshotError> {
+    use image::codecs::jpeg::JpegEncoder;
+    use image::codecs::png::PngEncoder;
+    use image::{DynamicImage, ImageFormat};
 
-    // Decode PNG
     let img = image::load_from_memory_with_format(png_data, ImageFormat::Png)
-        .map_err(|e| ScreenshotError::EncodeFailed(format!("Failed to decode PNG: {}", e)))?;
+        .map_err(|e| ScreenshotError::ConversionFailed(format!("Failed to decode PNG: {e}")))?;
+    let img = match background {
+        Background::Transparent => img,
+        Background::Color(color) => {
+            DynamicImage::ImageRgb8(composite_over_color(&img.into_rgba8(), color))
+        }
+    };
+    let reduced = reduce_colors(img, color);
+
+    let encode_failed = |e: image::ImageError| {
+        ScreenshotError::ConversionFailed(format!("Failed to encode reduced image: {e}"))
+    };
+    if format == "jpeg" {
+        // JPEG has no alpha channel
+        let opaque = match color.color_mode {
+            ColorMode::Rgb => DynamicImage::ImageRgb8(reduced.into_rgb8()),
+            ColorMode::Grayscale => DynamicImage::ImageLuma8(reduced.into_luma8()),
+        };
+        stream_data_url("image/jpeg", |out| {
+            opaque
+                .write_with_encoder(JpegEncoder::new_with_quality(out, quality))
+                .map_err(encode_failed)
+        })
+    } else {
+        stream_data_url("image/png", |out| {
+            reduced
+                .write_with_encoder(PngEncoder::new(out))
+                .map_err(encode_failed)
+        })
+    }
+}
+
+/// Decodes PNG bytes into raw RGBA pixels, after the requested background
+/// and color reduction.
+fn rgba_pixels(
+    png_data: &[u8],
+    background: Background,
+    color: ColorDepth,
+) -> Result<RgbaPixels, ScreenshotError> {
+    use image::{DynamicImage, ImageFormat};
+
+    let img = image::load_from_memory_with_format(png_data, ImageFormat::Png)
+        .map_err(|e| ScreenshotError::ConversionFailed(format!("Failed to decode PNG: {e}")))?;
+    let img = match background {
+        Background::Transparent => img,
+        Background::Color(color) => {
+            DynamicImage::ImageRgb8(composite_over_color(&img.into_rgba8(), color))
+        }
+    };
+    let img = if color.is_full() {
+        img
+    } else {
+        reduce_colors(img, color)
+    };
+    Ok(RgbaPixels::new(&img.into_rgba8()))
+}
+
+/// Converts an image to the requested color mode and quantizes its color
+/// channels. Alpha is kept as is.
+fn reduce_colors(img: image::DynamicImage, color: ColorDepth) -> image::DynamicImage {
+    use image::DynamicImage;
+
+    // Images already in the target color type are converted without a copy
+    let has_alpha = img.color().has_alpha();
+    let mut reduced = match (color.color_mode, has_alpha) {
+        (ColorMode::Rgb, false) => DynamicImage::ImageRgb8(img.into_rgb8()),
+        (ColorMode::Rgb, true) => DynamicImage::ImageRgba8(img.into_rgba8()),
+        (ColorMode::Grayscale, false) => DynamicImage::ImageLuma8(img.into_luma8()),
+        (ColorMode::Grayscale, true) => DynamicImage::ImageLumaA8(img.into_luma_alpha8()),
+    };
+
+    if let Some(levels) = color.levels() {
+        let channels = reduced.color().channel_count() as usize;
+        let color_channels = if has_alpha { channels - 1 } else { channels };
+        let bytes = match &mut reduced {
+            DynamicImage::ImageRgb8(img) => img.as_mut(),
+            DynamicImage::ImageRgba8(img) => img.as_mut(),
+            DynamicImage::ImageLuma8(img) => img.as_mut(),
+            DynamicImage::ImageLumaA8(img) => img.as_mut(),
+            _ => unreachable!("8-bit image"),
+        };
+        for pixel in bytes.chunks_exact_mut(channels) {
+            for value in &mut pixel[..color_channels] {
+                *value = quantize(*value, levels);
+            }
+        }
+    }
+
+    reduced
+}
+
+/// Rounds a channel value to the nearest of `levels` evenly spaced levels.
+fn quantize(value: u8, levels: u32) -> u8 {
+    let steps = levels - 1;
+    let level = (u32::from(value) * steps + 127) / 255;
+    ((level * 255 + steps / 2) / steps) as u8
+}
+
+/// Alpha-blend an RGBA image over an opaque color
+fn composite_over_color(img: &image::RgbaImage, color: [u8; 3]) -> image::RgbImage {
+    image::RgbImage::from_fn(img.width(), img.height(), |x, y| {
+        image::Rgb(blend_over(img.get_pixel(x, y).0, color))
+    })
+}
 
-    // Encode as JPEG
-    let mut jpeg_buffer = Cursor::new(Vec::new());
-    let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg_buffer, quality);
-    img.write_with_encoder(encoder)
-        .map_err(|e| ScreenshotError::EncodeFailed(format!("Failed to encode JPEG: {}", e)))?;
+/// Alpha-blend one RGBA pixel over an opaque color
+fn blend_over([r, g, b, a]: [u8; 4], color: [u8; 3]) -> [u8; 3] {
+    let alpha = a as u32;
+    let blend =
+        |src: u8, bg: u8| ((src as u32 * alpha + bg as u32 * (255 - alpha) + 127) / 255) as u8;
+    [blend(r, color[0]), blend(g, color[1]), blend(b, color[2])]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_background() {
+        assert_eq!(
+            Background::parse("transparent").unwrap(),
+            Background::Transparent
+        );
+        assert_eq!(
+            Background::parse("White").unwrap(),
+            Background::Color([255, 255, 255])
+        );
+        assert_eq!(
+            Background::parse("#1a2B3c").unwrap(),
+            Background::Color([0x1a, 0x2b, 0x3c])
+        );
+        assert!(Background::parse("#12345").is_err());
+        assert!(Background::parse("#gg0000").is_err());
+        assert!(Background::parse("black").is_err());
+    }
+
+    #[test]
+    fn test_composite_semi_transparent_over_white() {
+        let mut img = image::RgbaImage::new(2, 1);
+        // Half-transparent black becomes mid grey over white
+        img.put_pixel(0, 0, image::Rgba([0, 0, 0, 128]));
+        // Fully transparent pixel takes the background color
+        img.put_pixel(1, 0, image::Rgba([200, 10, 10, 0]));
+
+        let flattened = composite_over_color(&img, [255, 255, 255]);
+
+        assert_eq!(flattened.get_pixel(0, 0).0, [127, 127, 127]);
+        assert_eq!(flattened.get_pixel(1, 0).0, [255, 255, 255]);
+    }
+
+    #[test]
+    fn test_composite_png_round_trip_is_opaque() {
+        use std::io::Cursor;
+
+        let mut img = image::RgbaImage::new(1, 1);
+        img.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).unwrap();
+
+        let flattened = encode_capture(
+            png.into_inner(),
+            "png",
+            90,
+            Background::Color([0, 0, 255]),
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+        assert!(flattened.data_url.starts_with("data:image/png;base64,"));
+        let decoded = image::load_from_memory(&decode_data_url(&flattened.data_url)).unwrap();
+
+        assert!(!decoded.color().has_alpha());
+        assert_eq!(decoded.to_rgb8().get_pixel(0, 0).0, [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_invalid_image_data_returns_original_with_warning() {
+        let garbage = b"definitely not a png".to_vec();
 
-    Ok(jpeg_buffer.into_inner())
-}
\ No newline at end of file
+        let captured = encode_capture(
+            garbage.clone(),
+            "jpeg",
+            90,
+            Background::Transparent,
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+
+        use base64::Engine as _;
+        let expected = base64::engine::general_purpose::STANDARD.encode(&garbage);
+        assert_eq!(
+            captured.data_url,
+            format!("data:image/png;base64,{expected}")
+        );
+        let warning = captured.warning.expect("conversion failure is reported");
+        assert!(warning.starts_with("Image conversion failed: Failed to decode PNG"));
+
+        let flattened = encode_capture(
+            garbage,
+            "png",
+            90,
+            Background::Color([255, 255, 255]),
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+        assert!(flattened.warning.is_some());
+    }
+
+    #[test]
+    fn test_not_ready_conditions_are_detected() {
+        let webview2 = ScreenshotError::CaptureFailed(
+            "CapturePreview failed: The group or resource is not in the correct state to perform the requested operation. (0x8007139F)".to_string(),
+        );
+        assert!(matches!(
+            detect_not_ready(Err(webview2)),
+            Err(ScreenshotError::WebviewNotReady(message)) if message.contains("0x8007139F")
+        ));
+
+        assert!(matches!(
+            detect_not_ready(Ok(Screenshot { data: Vec::new() })),
+            Err(ScreenshotError::WebviewNotReady(_))
+        ));
+
+        // A PNG header claiming a zero-width image
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend_from_slice(&0u32.to_be_bytes());
+        png.extend_from_slice(&10u32.to_be_bytes());
+        assert!(matches!(
+            detect_not_ready(Ok(Screenshot { data: png })),
+            Err(ScreenshotError::WebviewNotReady(message)) if message == "captured image is 0x10"
+        ));
+    }
+
+    #[test]
+    fn test_other_capture_results_pass_through() {
+        let failure = ScreenshotError::CaptureFailed("Failed to access webview".to_string());
+        assert!(matches!(
+            detect_not_ready(Err(failure)),
+            Err(ScreenshotError::CaptureFailed(_))
+        ));
+        assert!(matches!(
+            detect_not_ready(Err(ScreenshotError::Timeout)),
+            Err(ScreenshotError::Timeout)
+        ));
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::RgbaImage::new(2, 2)
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        assert!(detect_not_ready(Ok(Screenshot {
+            data: png.into_inner()
+        }))
+        .is_ok());
+    }
+
+    #[test]
+    fn test_native_jpeg_passes_through() {
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(2, 2)
+            .write_to(&mut jpeg, image::ImageFormat::Jpeg)
+            .unwrap();
+        let screenshot = detect_not_ready(Ok(Screenshot {
+            data: jpeg.into_inner(),
+        }))
+        .unwrap();
+
+        assert!(screenshot.is_jpeg());
+        assert!(!Screenshot {
+            data: b"\x89PNG\r\n\x1a\n".to_vec()
+        }
+        .is_jpeg());
+    }
+
+    #[test]
+    fn test_untransformed_capture_has_no_warning() {
+        let captured = encode_capture(
+            b"raw".to_vec(),
+            "png",
+            90,
+            Background::Transparent,
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+
+        assert_eq!(captured.data_url, "data:image/png;base64,cmF3");
+        assert_eq!(captured.warning, None);
+    }
+
+    /// A colorful, text-like capture: dark glyph strokes on a tinted page.
+    fn text_capture_png() -> Vec<u8> {
+        let img = image::RgbaImage::from_fn(320, 200, |x, y| {
+            if (x / 3 + y / 7) % 5 == 0 {
+                image::Rgba([20, 30, (x % 60) as u8, 255])
+            } else {
+                image::Rgba([240 - (y % 16) as u8, 235, 220 + (x % 30) as u8, 255])
+            }
+        });
+        let mut png = std::io::Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        png.into_inner()
+    }
+
+    fn decode_data_url(data_url: &str) -> Vec<u8> {
+        use base64::Engine as _;
+        let (_, data) = data_url.split_once(",").unwrap();
+        base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_grayscale_capture_is_gray_and_smaller() {
+        let png = text_capture_png();
+        let encode = |color| {
+            encode_capture(
+                png.clone(),
+                "png",
+                90,
+                Background::Transparent,
+                color,
+                &mut FrameEncoder::new(),
+            )
+        };
+
+        let full = encode(ColorDepth::default());
+        let gray = encode(ColorDepth::new(ColorMode::Grayscale, None).unwrap());
+        let posterized = encode(ColorDepth::new(ColorMode::Grayscale, Some(4)).unwrap());
+
+        let gray_bytes = decode_data_url(&gray.data_url);
+        let decoded = image::load_from_memory(&gray_bytes).unwrap();
+        assert_eq!(decoded.color(), image::ColorType::La8);
+        assert!(
+            gray_bytes.len() < png.len(),
+            "{} vs {}",
+            gray_bytes.len(),
+            png.len()
+        );
+
+        let posterized_bytes = decode_data_url(&posterized.data_url);
+        assert!(posterized_bytes.len() < gray_bytes.len());
+        let mut levels: Vec<u8> = image::load_from_memory(&posterized_bytes)
+            .unwrap()
+            .to_luma8()
+            .pixels()
+            .map(|pixel| pixel.0[0])
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        assert!(
+            levels.iter().all(|level| [0, 85, 170, 255].contains(level)),
+            "{levels:?}"
+        );
+
+        // The applied mode is reported with the image
+        let report = serde_json::to_value(&posterized).unwrap();
+        assert_eq!(report["colorMode"], "grayscale");
+        assert_eq!(report["maxColors"], 4);
+        assert_eq!(full.color, None);
+        assert!(full.warning.is_none() && gray.warning.is_none());
+    }
+
+    #[test]
+    fn test_color_options_are_validated() {
+        assert_eq!(ColorMode::parse("Grayscale").unwrap(), ColorMode::Grayscale);
+        assert!(ColorMode::parse("sepia").is_err());
+        assert!(ColorDepth::new(ColorMode::Grayscale, Some(2)).is_ok());
+        assert!(ColorDepth::new(ColorMode::Grayscale, Some(1)).is_err());
+        assert!(ColorDepth::new(ColorMode::Rgb, Some(4)).is_err());
+        assert!(ColorDepth::new(ColorMode::Rgb, Some(300)).is_err());
+
+        // 64 colors are 4 levels per RGB channel
+        let rgb = ColorDepth::new(ColorMode::Rgb, Some(64)).unwrap();
+        assert_eq!(rgb.levels(), Some(4));
+        assert_eq!(
+            ColorDepth::new(ColorMode::Rgb, Some(100)).unwrap().levels(),
+            Some(4)
+        );
+        assert_eq!(quantize(0, 4), 0);
+        assert_eq!(quantize(100, 4), 85);
+        assert_eq!(quantize(250, 4), 255);
+    }
+
+    #[test]
+    fn test_rgba_capture_returns_unencoded_pixels() {
+        use base64::Engine as _;
+        use std::io::Cursor;
+
+        let mut img = image::RgbaImage::new(3, 2);
+        img.put_pixel(0, 0, image::Rgba([255, 0, 0, 255]));
+        img.put_pixel(2, 1, image::Rgba([10, 20, 30, 0]));
+        let mut png = Cursor::new(Vec::new());
+        img.write_to(&mut png, image::ImageFormat::Png).unwrap();
+        let png = png.into_inner();
+
+        let capture = encode_capture(
+            png.clone(),
+            "rgba",
+            90,
+            Background::Transparent,
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+        let pixels = capture.rgba.unwrap();
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(&pixels.data)
+            .unwrap();
+        assert!(capture.data_url.is_empty());
+        assert_eq!((pixels.width, pixels.height, pixels.stride), (3, 2, 12));
+        assert_eq!(data.len(), (pixels.width * pixels.height * 4) as usize);
+        assert_eq!(data, img.into_raw());
+
+        // A background flattens the alpha channel away
+        let flattened = encode_capture(
+            png,
+            "rgba",
+            90,
+            Background::Color([255, 255, 255]),
+            ColorDepth::default(),
+            &mut FrameEncoder::new(),
+        );
+        let data = base64::engine::general_purpose::STANDARD
+            .decode(flattened.rgba.unwrap().data)
+            .unwrap();
+        assert_eq!(&data[20..24], [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_window_capture_serializes_flat() {
+        let capture = WindowCapture {
+            image: CapturedImage {
+                data_url: "data:image/png;base64,AAAA".to_string(),
+                rgba: None,
+                warning: None,
+                monitor: None,
+                changed_region: None,
+                unchanged: false,
+                color: None,
+            },
+            width: 1280,
+            height: 832,
+            chrome_included: true,
+            method: WindowCaptureMethod::Window,
+        };
+
+        let value = serde_json::to_value(&capture).unwrap();
+        assert_eq!(value["dataUrl"], "data:image/png;base64,AAAA");
+        assert_eq!(value["width"], 1280);
+        assert_eq!(value["height"], 832);
+        assert_eq!(value["chromeIncluded"], true);
+        assert_eq!(value["method"], "window");
+        assert!(value.get("image").is_none());
+
+        let viewport = WindowCapture {
+            chrome_included: false,
+            method: WindowCaptureMethod::Viewport,
+            ..capture
+        };
+        assert_eq!(
+            serde_json::to_value(viewport).unwrap()["method"],
+            "viewport"
+        );
+    }
+}
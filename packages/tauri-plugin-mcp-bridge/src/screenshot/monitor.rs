@@ -0,0 +1,186 @@
+//! Monitor detection for screenshot metadata.
+//!
+//! Native capture APIs return pixels at the backing scale factor of the
+//! display the webview is rendered on. On multi-display setups with mixed
+//! DPI, coordinate math (crops, element rectangles, resizes) must use that
+//! display's scale factor rather than a global one.
+//!
+//! When a window spans several monitors, the monitor covering the largest
+//! part of the window is used. This matches where the OS renders the window's
+//! backing store on macOS and Windows; parts of the window on other displays
+//! are still captured at the selected monitor's scale factor.
+
+use serde::Serialize;
+use tauri::{Monitor, Runtime, WebviewWindow};
+
+/// The monitor a capture was taken on.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    /// OS-provided monitor name, if any
+    pub monitor_name: Option<String>,
+    /// Ratio of physical to logical pixels on this monitor
+    pub monitor_scale_factor: f64,
+    /// Top-left corner of the monitor in physical desktop coordinates
+    pub monitor_position: MonitorPosition,
+}
+
+/// Physical desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct MonitorPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+/// An axis-aligned rectangle in physical desktop coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Area of the intersection with `other`, in physical pixels.
+    fn overlap_area(&self, other: &Rect) -> u64 {
+        let left = i64::from(self.x).max(i64::from(other.x));
+        let top = i64::from(self.y).max(i64::from(other.y));
+        let right = (i64::from(self.x) + i64::from(self.width))
+            .min(i64::from(other.x) + i64::from(other.width));
+        let bottom = (i64::from(self.y) + i64::from(self.height))
+            .min(i64::from(other.y) + i64::from(other.height));
+
+        if right <= left || bottom <= top {
+            0
+        } else {
+            ((right - left) * (bottom - top)) as u64
+        }
+    }
+}
+
+/// Returns the index of the monitor covering the largest part of `window`.
+///
+/// Ties go to the earlier monitor. Returns `None` if the window doesn't
+/// overlap any monitor (e.g. it was moved off-screen).
+pub fn select_monitor(window: &Rect, monitors: &[Rect]) -> Option<usize> {
+    monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| (index, window.overlap_area(monitor)))
+        .filter(|(_, area)| *area > 0)
+        .fold(None, |best: Option<(usize, u64)>, candidate| match best {
+            Some((_, best_area)) if best_area >= candidate.1 => best,
+            _ => Some(candidate),
+        })
+        .map(|(index, _)| index)
+}
+
+/// Detects the monitor `window` is displayed on.
+///
+/// Falls back to the platform's notion of the current monitor when window
+/// geometry is unavailable or off-screen, and to `None` on platforms without
+/// monitor information (mobile).
+pub fn window_monitor<R: Runtime>(window: &WebviewWindow<R>) -> Option<MonitorInfo> {
+    let monitors = window.available_monitors().unwrap_or_default();
+
+    let largest_overlap = match (window.outer_position(), window.outer_size()) {
+        (Ok(position), Ok(size)) => {
+            let window_rect = Rect {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            };
+            let monitor_rects: Vec<Rect> = monitors.iter().map(monitor_rect).collect();
+            select_monitor(&window_rect, &monitor_rects)
+                .and_then(|index| monitors.into_iter().nth(index))
+        }
+        _ => None,
+    };
+
+    let monitor = largest_overlap.or_else(|| window.current_monitor().ok().flatten())?;
+
+    Some(MonitorInfo {
+        monitor_name: monitor.name().cloned(),
+        monitor_scale_factor: monitor.scale_factor(),
+        monitor_position: MonitorPosition {
+            x: monitor.position().x,
+            y: monitor.position().y,
+        },
+    })
+}
+
+fn monitor_rect(monitor: &Monitor) -> Rect {
+    Rect {
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 1x laptop panel with a 2x external display to its right
+    const LAPTOP: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1920,
+        height: 1080,
+    };
+    const EXTERNAL: Rect = Rect {
+        x: 1920,
+        y: 0,
+        width: 5120,
+        height: 2880,
+    };
+
+    #[test]
+    fn test_window_within_one_monitor() {
+        let window = Rect {
+            x: 2200,
+            y: 100,
+            width: 800,
+            height: 600,
+        };
+
+        assert_eq!(select_monitor(&window, &[LAPTOP, EXTERNAL]), Some(1));
+    }
+
+    #[test]
+    fn test_spanning_window_uses_monitor_with_largest_overlap() {
+        // 600px on the laptop, 200px on the external display
+        let window = Rect {
+            x: 1320,
+            y: 0,
+            width: 800,
+            height: 600,
+        };
+        assert_eq!(select_monitor(&window, &[LAPTOP, EXTERNAL]), Some(0));
+
+        // 200px on the laptop, 600px on the external display
+        let window = Rect {
+            x: 1720,
+            y: 0,
+            width: 800,
+            height: 600,
+        };
+        assert_eq!(select_monitor(&window, &[LAPTOP, EXTERNAL]), Some(1));
+    }
+
+    #[test]
+    fn test_off_screen_window_has_no_monitor() {
+        let window = Rect {
+            x: -5000,
+            y: -5000,
+            width: 100,
+            height: 100,
+        };
+
+        assert_eq!(select_monitor(&window, &[LAPTOP, EXTERNAL]), None);
+        assert_eq!(select_monitor(&window, &[]), None);
+    }
+}
@@ -0,0 +1,192 @@
+//! Stitching viewport captures of a scrolled container into one image.
+//!
+//! A container taller than its box is captured by scrolling it through its
+//! `scrollHeight` and capturing the viewport at each offset. Every slice is
+//! cropped to the container's box and only the rows not yet covered by the
+//! previous slices are copied, so the last slice, whose offset is clamped to
+//! the end of the content, adds only what is new.
+//!
+//! Content that stays in place while the container scrolls, such as a
+//! `position: sticky` table header, would repeat in every slice. An
+//! `overlap` of that many CSS pixels makes each slice after the first start
+//! that much higher, and its top `overlap` rows, where the header covers the
+//! content, are dropped.
+
+use image::{imageops, RgbaImage};
+
+/// Most slices one capture may take, bounding the stitched image's size.
+pub const MAX_SLICES: usize = 50;
+
+/// Scroll offsets, in CSS pixels, to capture a container at.
+///
+/// # Arguments
+///
+/// * `scroll_height` - The container's `scrollHeight`
+/// * `client_height` - The container's `clientHeight`, the visible rows
+/// * `overlap` - Rows at the top of the box hidden by sticky content
+///
+/// # Returns
+///
+/// * `Ok(Vec<u32>)` - Offsets in increasing order, starting at 0 and ending
+///   at `scroll_height - client_height`
+/// * `Err(String)` - If the box is empty, `overlap` leaves no rows to step
+///   by, or more than [`MAX_SLICES`] slices are needed
+pub fn slice_offsets(
+    scroll_height: u32,
+    client_height: u32,
+    overlap: u32,
+) -> Result<Vec<u32>, String> {
+    if client_height == 0 {
+        return Err("Scroll container has no visible height".to_string());
+    }
+    if overlap >= client_height {
+        return Err(format!(
+            "scrollOverlap must be less than the container's visible height ({client_height}px), got {overlap}"
+        ));
+    }
+
+    let last = scroll_height.saturating_sub(client_height);
+    let step = client_height - overlap;
+    let mut offsets = vec![0];
+    while offsets[offsets.len() - 1] < last {
+        if offsets.len() == MAX_SLICES {
+            return Err(format!(
+                "Scroll container is too tall to capture: {scroll_height}px needs more than {MAX_SLICES} slices of {step}px"
+            ));
+        }
+        offsets.push((offsets[offsets.len() - 1] + step).min(last));
+    }
+    Ok(offsets)
+}
+
+/// Joins container slices into an image of the whole content.
+///
+/// # Arguments
+///
+/// * `slices` - `(scrollTop, pixels)` pairs in capture order, each cropped
+///   to the container's box. `scrollTop` is the offset the container
+///   actually reached, in CSS pixels.
+/// * `scale` - Image pixels per CSS pixel
+/// * `scroll_height` - The container's `scrollHeight`, in CSS pixels
+/// * `overlap` - Rows at the top of each later slice to drop, as for
+///   [`slice_offsets`]
+///
+/// # Returns
+///
+/// * `Ok(RgbaImage)` - `scroll_height * scale` rows of the slices' width
+/// * `Err(String)` - If there are no slices, or a slice starts below the
+///   rows covered so far, leaving a gap
+pub fn stitch_slices(
+    slices: &[(u32, RgbaImage)],
+    scale: f64,
+    scroll_height: u32,
+    overlap: u32,
+) -> Result<RgbaImage, String> {
+    let (_, first) = slices
+        .first()
+        .ok_or_else(|| "No slices to stitch".to_string())?;
+    let to_px = |css: u32| (f64::from(css) * scale).round() as u32;
+    let width = first.width();
+    let height = to_px(scroll_height).max(1);
+    let mut stitched = RgbaImage::new(width, height);
+
+    let mut covered = 0;
+    for (index, (offset, slice)) in slices.iter().enumerate() {
+        let trimmed = if index == 0 { 0 } else { overlap };
+        let start = covered.max(offset + trimmed);
+        if start > covered {
+            return Err(format!(
+                "Scroll container skipped rows {covered}-{start}; it may have changed size while being captured"
+            ));
+        }
+        let end = scroll_height.min(offset + to_css(slice.height(), scale));
+        if end <= start {
+            continue;
+        }
+
+        let (src_y, dst_y) = (to_px(start - offset), to_px(start));
+        let rows = (to_px(end) - dst_y)
+            .min(slice.height().saturating_sub(src_y))
+            .min(height.saturating_sub(dst_y));
+        let part = imageops::crop_imm(slice, 0, src_y, width.min(slice.width()), rows);
+        imageops::replace(&mut stitched, &*part, 0, i64::from(dst_y));
+        covered = end;
+    }
+
+    Ok(stitched)
+}
+
+/// Image rows to CSS pixels, rounded to the nearest pixel.
+fn to_css(px: u32, scale: f64) -> u32 {
+    (f64::from(px) / scale).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    /// Content whose every CSS row has its own color, at 2 image pixels per
+    /// CSS pixel.
+    fn content(css_height: u32, header: u32) -> RgbaImage {
+        RgbaImage::from_fn(40, css_height * 2, |_, y| row_color(y / 2, header))
+    }
+
+    fn row_color(css_y: u32, header: u32) -> Rgba<u8> {
+        if css_y < header {
+            Rgba([255, 0, 255, 255])
+        } else {
+            Rgba([(css_y % 256) as u8, (css_y / 256) as u8, 0, 255])
+        }
+    }
+
+    /// What the container's box shows at `offset`, with a sticky header of
+    /// `header` rows pinned to its top.
+    fn slice_at(page: &RgbaImage, offset: u32, client_height: u32, header: u32) -> RgbaImage {
+        let mut slice = imageops::crop_imm(page, 0, offset * 2, 40, client_height * 2).to_image();
+        for y in 0..header * 2 {
+            for x in 0..40 {
+                slice.put_pixel(x, y, row_color(0, header));
+            }
+        }
+        slice
+    }
+
+    #[test]
+    fn test_slices_cover_the_content_once() {
+        assert_eq!(slice_offsets(1000, 300, 0).unwrap(), [0, 300, 600, 700]);
+        assert_eq!(slice_offsets(1000, 300, 40).unwrap(), [0, 260, 520, 700]);
+        // Content that fits needs one capture
+        assert_eq!(slice_offsets(200, 300, 0).unwrap(), [0]);
+
+        assert!(slice_offsets(1000, 300, 300).is_err());
+        assert!(slice_offsets(1000, 0, 0).is_err());
+        let err = slice_offsets(100_000, 100, 0).unwrap_err();
+        assert!(err.starts_with("Scroll container is too tall to capture"));
+    }
+
+    #[test]
+    fn test_stitching_restores_the_scrolled_content() {
+        for header in [0, 40] {
+            let page = content(1000, header);
+            let slices: Vec<_> = slice_offsets(1000, 300, header)
+                .unwrap()
+                .into_iter()
+                .map(|offset| (offset, slice_at(&page, offset, 300, header)))
+                .collect();
+
+            let stitched = stitch_slices(&slices, 2.0, 1000, header).unwrap();
+
+            assert_eq!(stitched.dimensions(), page.dimensions());
+            assert!(stitched == page, "sticky header of {header}px");
+        }
+
+        // A slice starting past the covered rows leaves a gap
+        let page = content(1000, 0);
+        let gap = [
+            (0, slice_at(&page, 0, 300, 0)),
+            (400, slice_at(&page, 400, 300, 0)),
+        ];
+        assert!(stitch_slices(&gap, 2.0, 1000, 0).is_err());
+    }
+}
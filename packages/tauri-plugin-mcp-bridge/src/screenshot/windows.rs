@@ -1,12 +1,14 @@
-use super::{Screenshot, ScreenshotError};
+use super::{NativeFormat, Screenshot, ScreenshotError};
 use tauri::{Runtime, WebviewWindow};
 
 /// Windows-specific screenshot implementation using WebView2's CapturePreview
 ///
 /// This implementation captures only the visible viewport.
 /// WebView2's CapturePreview API naturally captures just the viewport.
+/// Its JPEG output has no quality setting, so PNG is always returned.
 pub fn capture_viewport<R: Runtime>(
     window: &WebviewWindow<R>,
+    _format: NativeFormat,
 ) -> Result<Screenshot, ScreenshotError> {
     #[cfg(target_os = "windows")]
     {
@@ -26,7 +28,13 @@ pub fn capture_viewport<R: Runtime>(
                 unsafe {
                     // Get ICoreWebView2 from Tauri's webview handle
                     let controller = webview.controller();
-                    let core_webview = controller.CoreWebView2().unwrap();
+                    let core_webview = match crate::webview2::core_webview2(&controller) {
+                        Ok(core_webview) => core_webview,
+                        Err(e) => {
+                            let _ = tx.send(Err(ScreenshotError::CaptureFailed(e)));
+                            return;
+                        }
+                    };
 
                     // Create memory stream for PNG output
                     let stream: IStream = CreateStreamOnHGlobal(HGLOBAL::default(), true).unwrap();
@@ -77,6 +85,162 @@ pub fn capture_viewport<R: Runtime>(
     }
 }
 
+/// Windows-specific capture of the whole native window using `PrintWindow`
+///
+/// The frame and titlebar are included. The invisible resize borders that
+/// `GetWindowRect` counts are cropped off using the DWM frame bounds.
+pub fn capture_window<R: Runtime>(
+    window: &WebviewWindow<R>,
+) -> Result<Screenshot, ScreenshotError> {
+    #[cfg(target_os = "windows")]
+    {
+        use std::sync::mpsc;
+        use windows::Win32::UI::WindowsAndMessaging::{GetAncestor, GA_ROOT};
+
+        let (tx, rx) = mpsc::channel::<Result<Screenshot, ScreenshotError>>();
+
+        window
+            .with_webview(move |webview| {
+                // SAFETY: the handle comes from the live controller and is only
+                // used on the UI thread within this closure
+                let result = unsafe {
+                    webview
+                        .controller()
+                        .ParentWindow()
+                        .map_err(|e| {
+                            ScreenshotError::CaptureFailed(format!(
+                                "Failed to get window handle: {e}"
+                            ))
+                        })
+                        .and_then(|hwnd| print_window(GetAncestor(hwnd, GA_ROOT)))
+                        .map(|data| Screenshot { data })
+                };
+                let _ = tx.send(result);
+            })
+            .map_err(|e| {
+                ScreenshotError::CaptureFailed(format!("Failed to access webview: {}", e))
+            })?;
+
+        match rx.recv_timeout(std::time::Duration::from_secs(10)) {
+            Ok(result) => result,
+            Err(_) => Err(ScreenshotError::Timeout),
+        }
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err(ScreenshotError::PlatformUnsupported)
+    }
+}
+
+/// Renders a top-level window into a bitmap and encodes it as PNG
+///
+/// # Safety
+/// `hwnd` must be a valid window handle.
+#[cfg(target_os = "windows")]
+unsafe fn print_window(hwnd: windows::Win32::Foundation::HWND) -> Result<Vec<u8>, ScreenshotError> {
+    use std::io::Cursor;
+    use windows::Win32::Foundation::RECT;
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+        ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
+    use windows::Win32::UI::WindowsAndMessaging::GetWindowRect;
+
+    // Renders DirectComposition content such as WebView2, which plain
+    // PrintWindow leaves black
+    const PW_RENDERFULLCONTENT: u32 = 0x2;
+
+    let mut rect = RECT::default();
+    GetWindowRect(hwnd, &mut rect)
+        .map_err(|e| ScreenshotError::CaptureFailed(format!("Failed to get window bounds: {e}")))?;
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    if width <= 0 || height <= 0 {
+        return Err(ScreenshotError::CaptureFailed(
+            "Window has no visible area; is it minimized?".to_string(),
+        ));
+    }
+
+    let screen_dc = GetDC(None);
+    let mem_dc = CreateCompatibleDC(Some(screen_dc));
+    let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+    let previous = SelectObject(mem_dc, bitmap.into());
+
+    let printed = PrintWindow(hwnd, mem_dc, PRINT_WINDOW_FLAGS(PW_RENDERFULLCONTENT)).as_bool();
+
+    // A negative height asks for top-down rows
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width,
+            biHeight: -height,
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    let rows = GetDIBits(
+        mem_dc,
+        bitmap,
+        0,
+        height as u32,
+        Some(pixels.as_mut_ptr() as *mut _),
+        &mut info,
+        DIB_RGB_COLORS,
+    );
+
+    SelectObject(mem_dc, previous);
+    let _ = DeleteObject(bitmap.into());
+    let _ = DeleteDC(mem_dc);
+    ReleaseDC(None, screen_dc);
+
+    if !printed || rows == 0 {
+        return Err(ScreenshotError::CaptureFailed(
+            "PrintWindow failed to render the window".to_string(),
+        ));
+    }
+
+    // GDI pixels are BGRA with an undefined alpha channel
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+        pixel[3] = 255;
+    }
+    let mut image =
+        image::RgbaImage::from_raw(width as u32, height as u32, pixels).ok_or_else(|| {
+            ScreenshotError::EncodeFailed("Window bitmap has an unexpected size".to_string())
+        })?;
+
+    let mut frame = RECT::default();
+    let has_frame = DwmGetWindowAttribute(
+        hwnd,
+        DWMWA_EXTENDED_FRAME_BOUNDS,
+        &mut frame as *mut RECT as *mut _,
+        std::mem::size_of::<RECT>() as u32,
+    )
+    .is_ok();
+    if has_frame {
+        let x = (frame.left - rect.left).clamp(0, width) as u32;
+        let y = (frame.top - rect.top).clamp(0, height) as u32;
+        let w = (frame.right - frame.left).clamp(0, width - x as i32) as u32;
+        let h = (frame.bottom - frame.top).clamp(0, height - y as i32) as u32;
+        if w > 0 && h > 0 {
+            image = image::imageops::crop_imm(&image, x, y, w, h).to_image();
+        }
+    }
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, image::ImageFormat::Png)
+        .map_err(|e| ScreenshotError::EncodeFailed(format!("Failed to encode PNG: {e}")))?;
+    Ok(png.into_inner())
+}
+
 /// Read all bytes from an IStream
 ///
 /// # Safety
@@ -2,7 +2,13 @@
 //!
 //! This module provides a registry for storing script entries that should be
 //! automatically re-injected when pages load or navigate.
+//!
+//! Every registered script is re-injected on each page load, so the registry
+//! is capped both in number of scripts and in their combined size (see
+//! [`Builder::max_scripts`](crate::Builder::max_scripts) and
+//! [`Builder::max_script_bytes`](crate::Builder::max_script_bytes)).
 
+use crate::config::{DEFAULT_MAX_SCRIPTS, DEFAULT_MAX_SCRIPT_BYTES};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
@@ -28,28 +34,150 @@ pub struct ScriptEntry {
     pub content: String,
 }
 
+impl ScriptEntry {
+    /// Builds the JavaScript that injects this entry into the page DOM.
+    ///
+    /// Any existing element with the same ID is replaced. The ID and content
+    /// are embedded as escaped string literals and assigned through the DOM,
+    /// so content containing `</script>` or quotes cannot break out of the
+    /// generated code.
+    pub fn injection_script(&self) -> String {
+        let id = js_string_literal(&self.id);
+        let content = js_string_literal(&self.content);
+        let load = match self.script_type {
+            ScriptType::Inline => format!("script.text = {content};"),
+            ScriptType::Url => {
+                format!("script.src = {content};\n                script.async = true;")
+            }
+        };
+
+        format!(
+            r#"
+            (function() {{
+                var id = {id};
+                document.querySelectorAll('script[data-mcp-script-id]').forEach(function(existing) {{
+                    if (existing.getAttribute('data-mcp-script-id') === id) {{
+                        existing.remove();
+                    }}
+                }});
+                var script = document.createElement('script');
+                script.setAttribute('data-mcp-script-id', id);
+                {load}
+                document.head.appendChild(script);
+            }})();
+            "#
+        )
+    }
+}
+
+/// Encodes `value` as a JavaScript string literal that is safe to embed in
+/// generated code, including code that ends up inside an HTML `<script>` tag.
+///
+/// On top of JSON escaping, `</` becomes `<\/` and `<!--` becomes `<\!--`
+/// so the literal cannot close or comment out an enclosing tag, and U+2028 /
+/// U+2029 are escaped for engines that treat them as line terminators.
+pub fn js_string_literal(value: &str) -> String {
+    serde_json::to_string(value)
+        .unwrap_or_else(|_| "\"\"".to_string())
+        .replace("</", "<\\/")
+        .replace("<!--", "<\\!--")
+        .replace('\u{2028}', "\\u2028")
+        .replace('\u{2029}', "\\u2029")
+}
+
+/// Current size of a [`ScriptRegistry`] against its limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptUsage {
+    /// Number of registered scripts
+    pub count: usize,
+    /// Maximum number of scripts
+    pub max_scripts: usize,
+    /// Combined length of all script contents, in bytes
+    pub total_bytes: usize,
+    /// Maximum combined length of all script contents, in bytes
+    pub max_total_bytes: usize,
+}
+
 /// Registry for managing persistent scripts.
 ///
 /// Scripts added to this registry will be automatically re-injected
 /// when pages load or navigate.
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ScriptRegistry {
     scripts: HashMap<String, ScriptEntry>,
+    max_scripts: usize,
+    max_total_bytes: usize,
+}
+
+impl Default for ScriptRegistry {
+    fn default() -> Self {
+        Self::with_limits(DEFAULT_MAX_SCRIPTS, DEFAULT_MAX_SCRIPT_BYTES)
+    }
 }
 
 impl ScriptRegistry {
-    /// Creates a new empty script registry.
+    /// Creates a new empty script registry with the default limits.
     pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new empty script registry holding at most `max_scripts`
+    /// scripts of at most `max_total_bytes` combined.
+    pub fn with_limits(max_scripts: usize, max_total_bytes: usize) -> Self {
         Self {
             scripts: HashMap::new(),
+            max_scripts,
+            max_total_bytes,
         }
     }
 
     /// Adds a script entry to the registry.
     ///
     /// If a script with the same ID already exists, it will be replaced.
-    pub fn add(&mut self, entry: ScriptEntry) {
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - The script was added
+    /// * `Err(String)` - If the registry would exceed its script count or
+    ///   total size limit. The registry is left unchanged.
+    pub fn add(&mut self, entry: ScriptEntry) -> Result<(), String> {
+        let replaced = self.scripts.get(&entry.id);
+        let count = self.scripts.len() + usize::from(replaced.is_none());
+        if count > self.max_scripts {
+            return Err(format!(
+                "Script limit reached: {} scripts registered (max {}). \
+                 Remove scripts with remove_script or clear_scripts first",
+                self.scripts.len(),
+                self.max_scripts
+            ));
+        }
+
+        let total_bytes =
+            self.total_bytes() - replaced.map_or(0, |e| e.content.len()) + entry.content.len();
+        if total_bytes > self.max_total_bytes {
+            return Err(format!(
+                "Script '{}' would bring registered scripts to {total_bytes} bytes (max {})",
+                entry.id, self.max_total_bytes
+            ));
+        }
+
         self.scripts.insert(entry.id.clone(), entry);
+        Ok(())
+    }
+
+    /// Returns the registry's size and limits.
+    pub fn usage(&self) -> ScriptUsage {
+        ScriptUsage {
+            count: self.scripts.len(),
+            max_scripts: self.max_scripts,
+            total_bytes: self.total_bytes(),
+            max_total_bytes: self.max_total_bytes,
+        }
+    }
+
+    fn total_bytes(&self) -> usize {
+        self.scripts.values().map(|e| e.content.len()).sum()
     }
 
     /// Removes a script from the registry by ID.
@@ -88,16 +216,63 @@ impl ScriptRegistry {
     pub fn is_empty(&self) -> bool {
         self.scripts.is_empty()
     }
+
+    /// Builds the JavaScript that injects every registered script into a
+    /// freshly loaded page through `bridge.js`.
+    ///
+    /// `__MCP_INJECT_SCRIPTS__` skips scripts already present in the page,
+    /// so the result is safe to evaluate more than once per load.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The injection script
+    /// * `None` - If no scripts are registered
+    pub fn page_load_script(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let scripts: Vec<serde_json::Value> = self
+            .scripts
+            .values()
+            .map(|entry| {
+                serde_json::json!({
+                    "id": entry.id,
+                    "type": match entry.script_type {
+                        ScriptType::Inline => "inline",
+                        ScriptType::Url => "url",
+                    },
+                    "content": entry.content
+                })
+            })
+            .collect();
+
+        Some(format!(
+            "if (window.__MCP_INJECT_SCRIPTS__) {{ window.__MCP_INJECT_SCRIPTS__({}); }}",
+            serde_json::to_string(&scripts).unwrap_or_else(|_| "[]".to_string())
+        ))
+    }
 }
 
 /// Thread-safe wrapper for the script registry.
 pub type SharedScriptRegistry = Arc<Mutex<ScriptRegistry>>;
 
-/// Creates a new shared script registry.
+/// Creates a new shared script registry with the default limits.
 pub fn create_shared_registry() -> SharedScriptRegistry {
     Arc::new(Mutex::new(ScriptRegistry::new()))
 }
 
+/// Creates a new shared script registry with the given limits.
+pub fn create_shared_registry_with_limits(
+    max_scripts: usize,
+    max_total_bytes: usize,
+) -> SharedScriptRegistry {
+    Arc::new(Mutex::new(ScriptRegistry::with_limits(
+        max_scripts,
+        max_total_bytes,
+    )))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,7 +286,7 @@ mod tests {
             content: "console.log('hello')".to_string(),
         };
 
-        registry.add(entry.clone());
+        registry.add(entry.clone()).unwrap();
 
         assert!(registry.contains("test-script"));
         assert_eq!(registry.len(), 1);
@@ -124,11 +299,13 @@ mod tests {
     #[test]
     fn test_remove() {
         let mut registry = ScriptRegistry::new();
-        registry.add(ScriptEntry {
-            id: "to-remove".to_string(),
-            script_type: ScriptType::Url,
-            content: "https://example.com/script.js".to_string(),
-        });
+        registry
+            .add(ScriptEntry {
+                id: "to-remove".to_string(),
+                script_type: ScriptType::Url,
+                content: "https://example.com/script.js".to_string(),
+            })
+            .unwrap();
 
         assert!(registry.contains("to-remove"));
 
@@ -141,16 +318,20 @@ mod tests {
     #[test]
     fn test_clear() {
         let mut registry = ScriptRegistry::new();
-        registry.add(ScriptEntry {
-            id: "script1".to_string(),
-            script_type: ScriptType::Inline,
-            content: "1".to_string(),
-        });
-        registry.add(ScriptEntry {
-            id: "script2".to_string(),
-            script_type: ScriptType::Inline,
-            content: "2".to_string(),
-        });
+        registry
+            .add(ScriptEntry {
+                id: "script1".to_string(),
+                script_type: ScriptType::Inline,
+                content: "1".to_string(),
+            })
+            .unwrap();
+        registry
+            .add(ScriptEntry {
+                id: "script2".to_string(),
+                script_type: ScriptType::Inline,
+                content: "2".to_string(),
+            })
+            .unwrap();
 
         assert_eq!(registry.len(), 2);
 
@@ -161,36 +342,158 @@ mod tests {
     #[test]
     fn test_get_all() {
         let mut registry = ScriptRegistry::new();
-        registry.add(ScriptEntry {
-            id: "a".to_string(),
-            script_type: ScriptType::Inline,
-            content: "a".to_string(),
-        });
-        registry.add(ScriptEntry {
-            id: "b".to_string(),
-            script_type: ScriptType::Url,
-            content: "b".to_string(),
-        });
+        registry
+            .add(ScriptEntry {
+                id: "a".to_string(),
+                script_type: ScriptType::Inline,
+                content: "a".to_string(),
+            })
+            .unwrap();
+        registry
+            .add(ScriptEntry {
+                id: "b".to_string(),
+                script_type: ScriptType::Url,
+                content: "b".to_string(),
+            })
+            .unwrap();
 
         let all = registry.get_all();
         assert_eq!(all.len(), 2);
     }
 
+    #[test]
+    fn test_js_string_literal_escapes_closing_tags() {
+        let literal = js_string_literal("a</script><!--b\u{2028}");
+
+        assert!(!literal.contains("</"));
+        assert!(!literal.contains("<!--"));
+        assert!(!literal.contains('\u{2028}'));
+        assert_eq!(literal, r#""a<\/script><\!--b\u2028""#);
+    }
+
+    #[test]
+    fn test_injection_script_keeps_script_close_tag_content_intact() {
+        let content = r#"window.__html = "<b>x</b></script><script>alert(1)</script>";"#;
+        let entry = ScriptEntry {
+            id: "close'tag".to_string(),
+            script_type: ScriptType::Inline,
+            content: content.to_string(),
+        };
+
+        let script = entry.injection_script();
+
+        assert!(!script.contains("</script>"));
+        assert!(script.contains("script.text = "));
+        assert!(script.contains(r#"var id = "close'tag";"#));
+
+        // The embedded literal decodes back to the original content
+        let start = script.find("script.text = ").unwrap() + "script.text = ".len();
+        let end = start + script[start..].find(";\n").unwrap();
+        let decoded: String = serde_json::from_str(&script[start..end]).unwrap();
+        assert_eq!(decoded, content);
+    }
+
+    #[test]
+    fn test_page_load_script_covers_scripts_registered_earlier() {
+        let mut registry = ScriptRegistry::new();
+        assert_eq!(registry.page_load_script(), None);
+
+        registry
+            .add(ScriptEntry {
+                id: "late-window".to_string(),
+                script_type: ScriptType::Inline,
+                content: "window.__late = true;".to_string(),
+            })
+            .unwrap();
+
+        // What a window created after registration evaluates on load
+        let script = registry.page_load_script().unwrap();
+        assert!(script.starts_with("if (window.__MCP_INJECT_SCRIPTS__)"));
+        assert!(script.contains(
+            r#"[{"content":"window.__late = true;","id":"late-window","type":"inline"}]"#
+        ));
+    }
+
+    #[test]
+    fn test_injection_script_for_url() {
+        let entry = ScriptEntry {
+            id: "remote".to_string(),
+            script_type: ScriptType::Url,
+            content: "https://example.com/app.js".to_string(),
+        };
+
+        let script = entry.injection_script();
+
+        assert!(script.contains(r#"script.src = "https://example.com/app.js";"#));
+        assert!(script.contains("script.async = true;"));
+    }
+
     #[test]
     fn test_replace_existing() {
         let mut registry = ScriptRegistry::new();
-        registry.add(ScriptEntry {
-            id: "same-id".to_string(),
-            script_type: ScriptType::Inline,
-            content: "original".to_string(),
-        });
-        registry.add(ScriptEntry {
-            id: "same-id".to_string(),
-            script_type: ScriptType::Inline,
-            content: "replaced".to_string(),
-        });
+        registry
+            .add(ScriptEntry {
+                id: "same-id".to_string(),
+                script_type: ScriptType::Inline,
+                content: "original".to_string(),
+            })
+            .unwrap();
+        registry
+            .add(ScriptEntry {
+                id: "same-id".to_string(),
+                script_type: ScriptType::Inline,
+                content: "replaced".to_string(),
+            })
+            .unwrap();
 
         assert_eq!(registry.len(), 1);
         assert_eq!(registry.get("same-id").unwrap().content, "replaced");
     }
+
+    fn inline(id: &str, content: &str) -> ScriptEntry {
+        ScriptEntry {
+            id: id.to_string(),
+            script_type: ScriptType::Inline,
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_registering_beyond_cap_is_rejected() {
+        let mut registry = ScriptRegistry::with_limits(2, 1024);
+        registry.add(inline("a", "1")).unwrap();
+        registry.add(inline("b", "2")).unwrap();
+
+        let error = registry.add(inline("c", "3")).unwrap_err();
+
+        assert!(error.contains("Script limit reached"), "{error}");
+        assert!(error.contains("max 2"), "{error}");
+        assert!(!registry.contains("c"));
+        assert_eq!(registry.len(), 2);
+
+        // Replacing an existing script doesn't count as a new one
+        registry.add(inline("b", "updated")).unwrap();
+        assert_eq!(registry.get("b").unwrap().content, "updated");
+    }
+
+    #[test]
+    fn test_total_size_is_capped() {
+        let mut registry = ScriptRegistry::with_limits(10, 10);
+        registry.add(inline("a", "123456")).unwrap();
+
+        let error = registry.add(inline("b", "12345")).unwrap_err();
+        assert!(error.contains("11 bytes (max 10)"), "{error}");
+
+        // The replaced content no longer counts
+        registry.add(inline("a", "1234567890")).unwrap();
+        assert_eq!(
+            registry.usage(),
+            ScriptUsage {
+                count: 1,
+                max_scripts: 10,
+                total_bytes: 10,
+                max_total_bytes: 10,
+            }
+        );
+    }
 }
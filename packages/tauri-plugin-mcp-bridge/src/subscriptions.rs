@@ -0,0 +1,104 @@
+//! Per-connection topic filters for broadcast events.
+//!
+//! Broadcast frames carry a `type` field (e.g. `"navigation"`). A client can
+//! narrow what it receives with:
+//!
+//! ```json
+//! { "id": "1", "command": "subscribe", "args": { "topics": ["navigation"] } }
+//! { "id": "2", "command": "unsubscribe", "args": { "topics": ["navigation"] } }
+//! ```
+//!
+//! Both respond with the connection's current topic list. A connection with
+//! no topics receives every event, which keeps clients that never subscribe
+//! working as before. Responses to the client's own requests are never
+//! filtered.
+
+use crate::command_args::{parse_args, TopicsArgs};
+use std::collections::BTreeSet;
+
+/// The set of event types a connection wants to receive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TopicFilter {
+    topics: BTreeSet<String>,
+}
+
+impl TopicFilter {
+    /// Adds `topics` to the set.
+    pub fn subscribe<I: IntoIterator<Item = String>>(&mut self, topics: I) {
+        self.topics.extend(topics);
+    }
+
+    /// Removes `topics` from the set.
+    pub fn unsubscribe<'a, I: IntoIterator<Item = &'a str>>(&mut self, topics: I) {
+        for topic in topics {
+            self.topics.remove(topic);
+        }
+    }
+
+    /// Subscribed topics in sorted order.
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.iter().cloned().collect()
+    }
+
+    /// Returns whether a serialized broadcast frame should be forwarded.
+    ///
+    /// Frames without a `type` are only forwarded to unfiltered connections.
+    pub fn allows(&self, frame: &str) -> bool {
+        if self.topics.is_empty() {
+            return true;
+        }
+
+        serde_json::from_str::<serde_json::Value>(frame)
+            .ok()
+            .and_then(|value| value.get("type")?.as_str().map(|t| self.topics.contains(t)))
+            .unwrap_or(false)
+    }
+}
+
+/// Reads the `topics` string array from command arguments.
+pub fn topics_arg(args: Option<&serde_json::Value>) -> Result<Vec<String>, String> {
+    parse_args::<TopicsArgs>(args).map(|args| args.topics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_navigation_subscription_filters_out_ipc_events() {
+        let mut filter = TopicFilter::default();
+        filter.subscribe(["navigation".to_string()]);
+
+        let navigation = json!({ "type": "navigation", "toUrl": "http://localhost/" }).to_string();
+        let ipc = json!({ "type": "ipc", "command": "greet" }).to_string();
+
+        assert!(filter.allows(&navigation));
+        assert!(!filter.allows(&ipc));
+        assert!(!filter.allows("not json"));
+    }
+
+    #[test]
+    fn test_empty_filter_forwards_everything() {
+        let mut filter = TopicFilter::default();
+        assert!(filter.allows(&json!({ "type": "ipc" }).to_string()));
+
+        filter.subscribe(["navigation".to_string(), "ipc".to_string()]);
+        filter.unsubscribe(["navigation", "ipc"]);
+
+        assert!(filter.topics().is_empty());
+        assert!(filter.allows("not json"));
+    }
+
+    #[test]
+    fn test_topics_arg_validation() {
+        assert_eq!(
+            topics_arg(Some(&json!({ "topics": ["navigation"] }))),
+            Ok(vec!["navigation".to_string()])
+        );
+        assert!(topics_arg(Some(&json!({}))).is_err());
+        assert!(topics_arg(Some(&json!({ "topics": [1] })))
+            .unwrap_err()
+            .contains("expected string"));
+    }
+}
@@ -0,0 +1,113 @@
+//! TCP socket options for accepted connections.
+//!
+//! Commands and responses are small frames, which Nagle's algorithm holds
+//! back waiting for more data to coalesce, so `TCP_NODELAY` is set by default.
+//! TCP keepalive probes an idle connection and lets the OS reset it when the
+//! peer is gone, so half-open connections from crashed or disconnected agents
+//! are reclaimed at the transport layer, even without
+//! [`Builder::idle_timeout`](crate::Builder::idle_timeout).
+//!
+//! Both are configured with [`Builder::tcp_nodelay`](crate::Builder::tcp_nodelay)
+//! and [`Builder::tcp_keepalive`](crate::Builder::tcp_keepalive), and applied
+//! to every accepted stream before the WebSocket upgrade.
+
+use crate::config::Config;
+use socket2::{SockRef, TcpKeepalive};
+use std::time::Duration;
+use tokio::net::TcpStream;
+
+/// Keepalive probe timing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keepalive {
+    /// Idle time before the first probe
+    pub idle: Duration,
+    /// Time between unanswered probes
+    pub interval: Duration,
+}
+
+/// Options applied to each accepted connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TcpOptions {
+    /// Whether `TCP_NODELAY` is set
+    pub nodelay: bool,
+    /// Keepalive timing; `None` turns keepalive off
+    pub keepalive: Option<Keepalive>,
+}
+
+impl TcpOptions {
+    /// The options configured in `config`.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            nodelay: config.tcp_nodelay,
+            keepalive: config.tcp_keepalive.then_some(Keepalive {
+                idle: config.tcp_keepalive_idle,
+                interval: config.tcp_keepalive_interval,
+            }),
+        }
+    }
+
+    /// Sets the options on an accepted stream.
+    pub fn apply(&self, stream: &TcpStream) -> std::io::Result<()> {
+        stream.set_nodelay(self.nodelay)?;
+        let socket = SockRef::from(stream);
+        match self.keepalive {
+            Some(keepalive) => socket.set_tcp_keepalive(
+                &TcpKeepalive::new()
+                    .with_time(keepalive.idle)
+                    .with_interval(keepalive.interval),
+            ),
+            None => socket.set_keepalive(false),
+        }
+    }
+}
+
+impl Default for TcpOptions {
+    fn default() -> Self {
+        Self::from_config(&Config::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// An accepted server-side stream.
+    async fn accepted() -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (_client, server) = tokio::join!(TcpStream::connect(addr), listener.accept());
+        server.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn test_defaults_set_nodelay_and_keepalive() {
+        let stream = accepted().await;
+        let options = TcpOptions::default();
+        assert!(options.nodelay);
+        assert!(options.keepalive.is_some());
+
+        options.apply(&stream).unwrap();
+
+        assert!(stream.nodelay().unwrap());
+        assert!(SockRef::from(&stream).keepalive().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_options_can_be_turned_off() {
+        let stream = accepted().await;
+        TcpOptions::default().apply(&stream).unwrap();
+
+        let config = Config {
+            tcp_nodelay: false,
+            tcp_keepalive: false,
+            ..Config::default()
+        };
+        let options = TcpOptions::from_config(&config);
+        assert_eq!(options.keepalive, None);
+        options.apply(&stream).unwrap();
+
+        assert!(!stream.nodelay().unwrap());
+        assert!(!SockRef::from(&stream).keepalive().unwrap());
+    }
+}
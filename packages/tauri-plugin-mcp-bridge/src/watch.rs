@@ -0,0 +1,271 @@
+//! Watching a JavaScript value over a WebSocket connection.
+//!
+//! A client starts a watch with:
+//!
+//! ```json
+//! { "id": "1", "command": "watch_js", "args": { "expression": "window.store.count", "intervalMs": 500 } }
+//! ```
+//!
+//! and receives `{ watchId, intervalMs }`. The expression is then evaluated
+//! every `intervalMs`, and its value is pushed to that connection only as a
+//! text frame:
+//!
+//! ```json
+//! { "type": "watch", "watchId": "...", "value": 3, "changed": true }
+//! ```
+//!
+//! By default a frame is sent only when the value differs from the previous
+//! evaluation; the first evaluation always counts as a change. With
+//! `everyTick: true` every evaluation is sent and `changed` tells them apart.
+//! If an evaluation fails, a `{ type: "watch_error", watchId, error }` frame
+//! is sent and the watch stops.
+//!
+//! Watches end on `stop_watch`, or automatically when the connection closes.
+//! The interval is bounded below by [`MIN_WATCH_INTERVAL_MS`] so a watch
+//! can't hammer the webview; a slow evaluation skips ticks rather than
+//! bursting to catch up.
+
+use crate::outbound::Outbox;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tokio::time::MissedTickBehavior;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Shortest accepted evaluation interval.
+pub const MIN_WATCH_INTERVAL_MS: u64 = 100;
+
+/// Evaluation interval used when the client doesn't ask for one.
+pub const DEFAULT_WATCH_INTERVAL_MS: u64 = 1_000;
+
+/// A value pushed to the client that started the watch.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchFrame {
+    #[serde(rename = "type")]
+    pub frame_type: &'static str,
+    pub watch_id: String,
+    pub value: Value,
+    /// Whether `value` differs from the previous evaluation
+    pub changed: bool,
+}
+
+/// Validates a requested interval, applying the default and the lower bound.
+pub fn clamp_interval(interval_ms: Option<u64>) -> Result<u64, String> {
+    match interval_ms {
+        None => Ok(DEFAULT_WATCH_INTERVAL_MS),
+        Some(0) => Err("intervalMs must be greater than 0".to_string()),
+        Some(ms) => Ok(ms.max(MIN_WATCH_INTERVAL_MS)),
+    }
+}
+
+/// Wraps a watched expression into a script returning its value.
+pub fn watch_script(expression: &str) -> String {
+    // Newlines keep a trailing `//` comment from swallowing the parenthesis
+    format!("return (\n{expression}\n);")
+}
+
+/// Watches running on one connection.
+///
+/// Dropping the set (when the connection closes) stops every watch.
+#[derive(Default)]
+pub struct Watches {
+    watches: HashMap<String, JoinHandle<()>>,
+}
+
+impl Watches {
+    /// Starts a watch that evaluates a value every `interval_ms` and sends
+    /// it on `sink` until stopped or until an evaluation fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `interval_ms` - Evaluation interval, already validated with
+    ///   [`clamp_interval`]
+    /// * `every_tick` - Send every evaluation, not only changes
+    /// * `sink` - The connection's outbox; the stream ends when it is gone
+    ///   or full
+    /// * `evaluate` - Produces the watched value per call
+    ///
+    /// # Returns
+    ///
+    /// The new watch's id.
+    pub fn start<E, F>(
+        &mut self,
+        interval_ms: u64,
+        every_tick: bool,
+        sink: Outbox,
+        mut evaluate: E,
+    ) -> String
+    where
+        E: FnMut() -> F + Send + 'static,
+        F: Future<Output = Result<Value, String>> + Send,
+    {
+        self.watches.retain(|_, task| !task.inner().is_finished());
+
+        let watch_id = uuid::Uuid::new_v4().to_string();
+        let id = watch_id.clone();
+        let task = tauri::async_runtime::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_millis(interval_ms));
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            let mut previous: Option<Value> = None;
+
+            loop {
+                ticker.tick().await;
+                let message = match evaluate().await {
+                    Ok(value) => {
+                        let changed = previous.as_ref() != Some(&value);
+                        if !changed && !every_tick {
+                            continue;
+                        }
+                        previous = Some(value.clone());
+                        let frame = WatchFrame {
+                            frame_type: "watch",
+                            watch_id: id.clone(),
+                            value,
+                            changed,
+                        };
+                        serde_json::to_string(&frame).unwrap_or_default()
+                    }
+                    Err(error) => {
+                        let event = serde_json::json!({
+                            "type": "watch_error",
+                            "watchId": id,
+                            "error": error
+                        });
+                        let _ = sink.send(Message::Text(event.to_string().into()));
+                        break;
+                    }
+                };
+                // The connection is gone
+                if sink.send(Message::Text(message.into())).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.watches.insert(watch_id.clone(), task);
+        watch_id
+    }
+
+    /// Stops a watch.
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(())` - If the watch was stopped
+    /// * `Err(String)` - If `watch_id` doesn't name a watch on this connection
+    pub fn stop(&mut self, watch_id: &str) -> Result<(), String> {
+        match self.watches.remove(watch_id) {
+            Some(task) => {
+                task.abort();
+                Ok(())
+            }
+            None => Err(format!("Unknown watch: {watch_id}")),
+        }
+    }
+}
+
+impl Drop for Watches {
+    fn drop(&mut self) {
+        for task in self.watches.values() {
+            task.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::outbound::{outbox, OutboxReceiver};
+
+    async fn next_frame(rx: &mut OutboxReceiver) -> Value {
+        let Some(Message::Text(text)) = rx.recv().await else {
+            panic!("expected a text frame");
+        };
+        serde_json::from_str(&text).unwrap()
+    }
+
+    /// A counter that increments every other evaluation, like a page value
+    /// changing more slowly than it is polled.
+    fn counter() -> impl FnMut() -> std::future::Ready<Result<Value, String>> + Send {
+        let mut evaluations = 0u64;
+        move || {
+            evaluations += 1;
+            std::future::ready(Ok(Value::from((evaluations - 1) / 2)))
+        }
+    }
+
+    #[test]
+    fn test_interval_is_bounded_and_validated() {
+        assert_eq!(clamp_interval(None), Ok(DEFAULT_WATCH_INTERVAL_MS));
+        assert_eq!(clamp_interval(Some(5)), Ok(MIN_WATCH_INTERVAL_MS));
+        assert_eq!(clamp_interval(Some(2_500)), Ok(2_500));
+        assert!(clamp_interval(Some(0)).is_err());
+        assert_eq!(
+            watch_script("count // items"),
+            "return (\ncount // items\n);"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_incrementing_counter_sends_change_frames() {
+        let (tx, mut rx) = outbox();
+        let mut watches = Watches::default();
+        let watch_id = watches.start(MIN_WATCH_INTERVAL_MS, false, tx, counter());
+
+        // Repeated values are skipped, so consecutive frames count up
+        for expected in 0..3 {
+            let frame = next_frame(&mut rx).await;
+            assert_eq!(frame["type"], "watch");
+            assert_eq!(frame["watchId"], watch_id.as_str());
+            assert_eq!(frame["value"], expected);
+            assert_eq!(frame["changed"], true);
+        }
+
+        assert_eq!(watches.stop(&watch_id), Ok(()));
+        assert!(watches.stop(&watch_id).is_err());
+        // The sender is dropped with the aborted task, closing the channel
+        while rx.recv().await.is_some() {}
+    }
+
+    #[tokio::test]
+    async fn test_every_tick_reports_unchanged_values() {
+        let (tx, mut rx) = outbox();
+        let mut watches = Watches::default();
+        watches.start(MIN_WATCH_INTERVAL_MS, true, tx, counter());
+
+        let changes: Vec<(Value, Value)> = {
+            let mut frames = Vec::new();
+            for _ in 0..4 {
+                let frame = next_frame(&mut rx).await;
+                frames.push((frame["value"].clone(), frame["changed"].clone()));
+            }
+            frames
+        };
+        assert_eq!(
+            changes,
+            [
+                (Value::from(0), Value::Bool(true)),
+                (Value::from(0), Value::Bool(false)),
+                (Value::from(1), Value::Bool(true)),
+                (Value::from(1), Value::Bool(false)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluation_error_ends_watch() {
+        let (tx, mut rx) = outbox();
+        let mut watches = Watches::default();
+        watches.start(MIN_WATCH_INTERVAL_MS, false, tx, || async {
+            Err("ReferenceError: store is not defined".to_string())
+        });
+
+        let event = next_frame(&mut rx).await;
+        assert_eq!(event["type"], "watch_error");
+        assert_eq!(event["error"], "ReferenceError: store is not defined");
+        assert!(rx.recv().await.is_none());
+    }
+}
@@ -4,17 +4,48 @@
 //! between the Tauri application and external MCP clients. It broadcasts events
 //! to all connected clients and can receive commands from them.
 
-use crate::commands::{resolve_window_with_context, WindowContext};
+use crate::auth::AuthGate;
+use crate::capabilities::Capabilities;
+use crate::capture_store::CaptureStore;
+use crate::clients::ClientRegistry;
+use crate::close_reason::CloseReason;
+use crate::config::Config;
+use crate::dispatch::{reserve, serve};
+use crate::element_handles::release_script;
+use crate::http_fallback;
+use crate::idle_timeout::IdleTimer;
 use crate::logging::{mcp_log_error, mcp_log_info};
-use crate::script_registry::{ScriptEntry, ScriptType, SharedScriptRegistry};
+use crate::mcp::{self, McpMessage};
+use crate::metrics::{broadcast_metrics, ServerMetrics};
+use crate::monitor::IPCMonitor;
+use crate::outbound::{send_frame, OutboundError, OUTBOUND_QUEUE_CAPACITY, WRITE_TIMEOUT};
+use crate::pipeline::{
+    authorize, intercept, parse_request, route, ConnState, RequestSlots, WsResponse,
+};
+use crate::tcp_options::TcpOptions;
 use futures_util::{SinkExt, StreamExt};
 use serde_json;
 use std::net::SocketAddr;
-use tauri::{AppHandle, Manager, Runtime, WebviewWindow};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, mpsc};
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 
+pub use crate::dispatch::inject_all_scripts;
+
+/// How long to wait for a server-initiated Close frame to be written.
+const CLOSE_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// How long a stopping server waits for its connections to close. Covers
+/// their Close flush, and gives up on clients stuck in the handshake.
+const SHUTDOWN_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Pause before accepting again after running out of file descriptors or
+/// buffers, giving closing connections time to free them.
+const ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
 /// WebSocket server for real-time event streaming to MCP clients.
 ///
 /// The server listens on a specified port and accepts multiple concurrent
@@ -29,29 +60,55 @@ use tokio_tungstenite::{accept_async, tungstenite::Message};
 /// - Uses broadcast channels for event distribution
 /// - Handles client disconnections gracefully
 ///
+/// # Runtime Requirements
+///
+/// All tasks are spawned through [`tauri::async_runtime`], so the server runs
+/// on whatever runtime the host registered with Tauri. Socket I/O uses
+/// `tokio::net`, which requires that runtime to be a Tokio runtime with I/O and
+/// time drivers enabled. This holds for Tauri's default runtime and for any
+/// handle passed to `tauri::async_runtime::set` that was built with
+/// `enable_all()`.
+///
 /// # Examples
 ///
 /// ```rust,ignore
 /// use tauri_plugin_mcp_bridge::websocket::WebSocketServer;
 ///
-/// #[tokio::main]
-/// async fn main() {
-///     // Requires a Tauri AppHandle
-///     let (server, _rx) = WebSocketServer::new(9223, "0.0.0.0", app_handle);
+/// // Requires a Tauri AppHandle
+/// let (server, _rx) = WebSocketServer::new(9223, "0.0.0.0", app_handle);
 ///
-///     tokio::spawn(async move {
-///         if let Err(e) = server.start().await {
-///             eprintln!("WebSocket error: {}", e);
-///         }
-///     });
-/// }
+/// tauri::async_runtime::spawn(async move {
+///     if let Err(e) = server.start().await {
+///         eprintln!("WebSocket error: {}", e);
+///     }
+/// });
 /// ```
 pub struct WebSocketServer<R: Runtime> {
     addr: SocketAddr,
     event_tx: broadcast::Sender<String>,
+    shutdown_tx: watch::Sender<bool>,
+    clients: ClientRegistry,
+    captures: CaptureStore,
+    metrics: ServerMetrics,
+    metrics_interval: Option<Duration>,
+    tcp_options: TcpOptions,
     app: AppHandle<R>,
 }
 
+/// Closes every client connection with a `1001 going away` Close frame.
+///
+/// Obtained from [`WebSocketServer::shutdown_handle`] before the server is
+/// moved into its background task.
+#[derive(Clone)]
+pub struct ShutdownHandle(watch::Sender<bool>);
+
+impl ShutdownHandle {
+    /// Asks all connections to close.
+    pub fn shutdown(&self) {
+        let _ = self.0.send(true);
+    }
+}
+
 impl<R: Runtime> WebSocketServer<R> {
     /// Creates a new WebSocket server on the specified port and bind address.
     ///
@@ -84,12 +141,19 @@ impl<R: Runtime> WebSocketServer<R> {
         app: AppHandle<R>,
     ) -> (Self, broadcast::Receiver<String>) {
         let addr: SocketAddr = format!("{bind_address}:{port}").parse().unwrap();
-        let (event_tx, event_rx) = broadcast::channel(100);
+        let (event_tx, event_rx) = broadcast::channel(OUTBOUND_QUEUE_CAPACITY);
+        let (shutdown_tx, _) = watch::channel(false);
 
         (
             Self {
                 addr,
                 event_tx,
+                shutdown_tx,
+                clients: ClientRegistry::new(),
+                captures: CaptureStore::new(),
+                metrics: ServerMetrics::new(),
+                metrics_interval: None,
+                tcp_options: TcpOptions::default(),
                 app,
             },
             event_rx,
@@ -98,50 +162,150 @@ impl<R: Runtime> WebSocketServer<R> {
 
     /// Starts the WebSocket server and begins accepting connections.
     ///
-    /// This method runs indefinitely, accepting new WebSocket connections and
-    /// spawning a handler task for each client. It should be run in a background
-    /// task using `tokio::spawn`.
+    /// This method runs until the [`ShutdownHandle`] fires, accepting new
+    /// WebSocket connections and spawning a handler task for each client. It should be run in a background
+    /// task using `tauri::async_runtime::spawn`; see the
+    /// [runtime requirements](WebSocketServer#runtime-requirements).
     ///
     /// # Returns
     ///
-    /// * `Ok(())` - Never returns normally (runs until error)
-    /// * `Err(Box<dyn std::error::Error>)` - If the server fails to bind or accept connections
+    /// * `Ok(())` - Once the server was shut down
+    /// * `Err(Box<dyn std::error::Error>)` - If the server fails to bind, or the
+    ///   listener fails. Errors accepting a single connection, or running out
+    ///   of file descriptors, are logged and the server keeps accepting.
     ///
     /// # Examples
     ///
     /// ```rust,ignore
     /// use tauri_plugin_mcp_bridge::websocket::WebSocketServer;
     ///
-    /// #[tokio::main]
-    /// async fn main() {
-    ///     // Requires a Tauri AppHandle
-    ///     let (server, _rx) = WebSocketServer::new(9223, "0.0.0.0", app_handle);
+    /// // Requires a Tauri AppHandle
+    /// let (server, _rx) = WebSocketServer::new(9223, "0.0.0.0", app_handle);
     ///
-    ///     tokio::spawn(async move {
-    ///         if let Err(e) = server.start().await {
-    ///             eprintln!("WebSocket server error: {}", e);
-    ///         }
-    ///     });
-    /// }
+    /// tauri::async_runtime::spawn(async move {
+    ///     if let Err(e) = server.start().await {
+    ///         eprintln!("WebSocket server error: {}", e);
+    ///     }
+    /// });
     /// ```
     pub async fn start(self) -> Result<(), Box<dyn std::error::Error>> {
         let listener = TcpListener::bind(&self.addr).await?;
+        let shutdown_tx = self.shutdown_tx.clone();
+        self.serve(listener, shutdown_tx).await
+    }
+
+    /// Accepts connections on an already bound listener until `shutdown_tx`
+    /// is set.
+    ///
+    /// Connections and the metrics task subscribe to `shutdown_tx`, so setting
+    /// it closes them too. The listener is dropped right away, releasing the
+    /// port, and this returns once the connections have sent their Close
+    /// frames, or after [`SHUTDOWN_DRAIN_TIMEOUT`]. Used by [`McpBridgeHandle`](crate::McpBridgeHandle) to
+    /// run one server per activation.
+    pub(crate) async fn serve(
+        &self,
+        listener: TcpListener,
+        shutdown_tx: watch::Sender<bool>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         mcp_log_info(
             "WS_SERVER",
-            &format!("WebSocket server listening on: {}", self.addr),
+            &format!("WebSocket server listening on: {}", listener.local_addr()?),
         );
 
+        if let Some(interval) = self.metrics_interval {
+            let app = self.app.clone();
+            let ipc_buffer_len = move || {
+                app.try_state::<Arc<Mutex<IPCMonitor>>>()
+                    .and_then(|monitor| monitor.lock().ok().map(|monitor| monitor.len()))
+                    .unwrap_or(0)
+            };
+            tauri::async_runtime::spawn(broadcast_metrics(
+                interval,
+                self.metrics.clone(),
+                self.clients.clone(),
+                ipc_buffer_len,
+                self.event_tx.clone(),
+                shutdown_tx.subscribe(),
+            ));
+        }
+
+        // Connection tasks hold a sender until they are torn down
+        let (open_tx, mut open_rx) = mpsc::channel::<()>(1);
+        let mut stop = shutdown_tx.subscribe();
         loop {
-            let (stream, _) = listener.accept().await?;
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = stop.wait_for(|stopped| *stopped) => break,
+            };
+            let (stream, address) = match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => match AcceptFailure::classify(&e) {
+                    AcceptFailure::Connection => {
+                        mcp_log_error("WS_SERVER", &format!("Failed to accept a connection: {e}"));
+                        continue;
+                    }
+                    AcceptFailure::Resources => {
+                        mcp_log_error(
+                            "WS_SERVER",
+                            &format!(
+                                "Failed to accept a connection, retrying in {}ms: {e}",
+                                ACCEPT_RETRY_DELAY.as_millis()
+                            ),
+                        );
+                        tokio::select! {
+                            _ = tokio::time::sleep(ACCEPT_RETRY_DELAY) => continue,
+                            _ = stop.wait_for(|stopped| *stopped) => break,
+                        }
+                    }
+                    AcceptFailure::Fatal => return Err(e.into()),
+                },
+            };
+            if let Err(e) = self.tcp_options.apply(&stream) {
+                mcp_log_error(
+                    "WS_SERVER",
+                    &format!("Failed to set socket options for {address}: {e}"),
+                );
+            }
             let event_tx = self.event_tx.clone();
+            let shutdown_rx = shutdown_tx.subscribe();
+            let clients = self.clients.clone();
+            let captures = self.captures.clone();
+            let metrics = self.metrics.clone();
             let app = self.app.clone();
+            let open = open_tx.clone();
+
+            tauri::async_runtime::spawn(async move {
+                let _open = open;
+                // Plain HTTP requests (screenshot downloads) share the port
+                if let Some(head) = http_fallback::peek_head(&stream).await {
+                    if !head.upgrade {
+                        if let Err(e) = http_fallback::serve(stream, head, &captures).await {
+                            mcp_log_error("WS_SERVER", &format!("HTTP request error: {e}"));
+                        }
+                        return;
+                    }
+                }
 
-            tokio::spawn(async move {
-                if let Err(e) = handle_connection(stream, event_tx, app).await {
+                if let Err(e) = handle_connection(
+                    stream,
+                    address,
+                    clients,
+                    metrics,
+                    event_tx,
+                    shutdown_rx,
+                    app,
+                )
+                .await
+                {
                     mcp_log_error("WS_SERVER", &format!("WebSocket connection error: {e}"));
                 }
             });
         }
+
+        drop(listener);
+        drop(open_tx);
+        let _ = tokio::time::timeout(SHUTDOWN_DRAIN_TIMEOUT, open_rx.recv()).await;
+        Ok(())
     }
 
     /// Broadcasts a message to all connected WebSocket clients.
@@ -165,6 +329,99 @@ impl<R: Runtime> WebSocketServer<R> {
     pub fn broadcast(&self, message: &str) {
         let _ = self.event_tx.send(message.to_string());
     }
+
+    /// Returns a sender for broadcasting to connected clients.
+    ///
+    /// Unlike [`broadcast`](Self::broadcast), the sender can be kept after the
+    /// server has been moved into its background task.
+    pub fn event_sender(&self) -> broadcast::Sender<String> {
+        self.event_tx.clone()
+    }
+
+    /// Returns a handle that closes all connections when the app shuts down.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        ShutdownHandle(self.shutdown_tx.clone())
+    }
+
+    /// Returns the registry of connected clients.
+    pub fn clients(&self) -> ClientRegistry {
+        self.clients.clone()
+    }
+
+    /// Returns the store of captures downloadable over HTTP.
+    pub fn captures(&self) -> CaptureStore {
+        self.captures.clone()
+    }
+
+    /// Broadcasts a `metrics` frame every `interval` once started.
+    ///
+    /// See [`metrics`](crate::metrics) for the frame format.
+    pub fn set_metrics_interval(&mut self, interval: Duration) {
+        self.metrics_interval = Some(interval);
+    }
+
+    /// Sets the TCP options applied to connections accepted from now on.
+    ///
+    /// See [`tcp_options`](crate::tcp_options) for the defaults.
+    pub(crate) fn set_tcp_options(&mut self, options: TcpOptions) {
+        self.tcp_options = options;
+    }
+}
+
+/// How a failed `accept` affects the accept loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AcceptFailure {
+    /// Only the incoming connection failed; accept the next one right away
+    Connection,
+    /// The process ran out of file descriptors or memory; accept again after
+    /// [`ACCEPT_RETRY_DELAY`]
+    Resources,
+    /// The listener itself is broken; stop the server
+    Fatal,
+}
+
+impl AcceptFailure {
+    fn classify(error: &std::io::Error) -> Self {
+        use std::io::ErrorKind;
+
+        // EMFILE, ENFILE, ENOMEM and ENOBUFS have no stable `ErrorKind`
+        #[cfg(unix)]
+        const RESOURCE_ERRORS: &[i32] = &[
+            24, // EMFILE
+            23, // ENFILE
+            12, // ENOMEM
+            #[cfg(any(target_os = "linux", target_os = "android"))]
+            105, // ENOBUFS
+            #[cfg(not(any(target_os = "linux", target_os = "android")))]
+            55, // ENOBUFS
+        ];
+        #[cfg(windows)]
+        const RESOURCE_ERRORS: &[i32] = &[
+            10024, // WSAEMFILE
+            10055, // WSAENOBUFS
+        ];
+        #[cfg(not(any(unix, windows)))]
+        const RESOURCE_ERRORS: &[i32] = &[];
+
+        match error.kind() {
+            // Linux reports connections dropped by firewall rules as EPERM
+            ErrorKind::ConnectionAborted
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::PermissionDenied => Self::Connection,
+            ErrorKind::OutOfMemory => Self::Resources,
+            _ if error
+                .raw_os_error()
+                .is_some_and(|code| RESOURCE_ERRORS.contains(&code)) =>
+            {
+                Self::Resources
+            }
+            _ => Self::Fatal,
+        }
+    }
 }
 
 /// Handles a single WebSocket client connection.
@@ -178,7 +435,14 @@ impl<R: Runtime> WebSocketServer<R> {
 /// # Arguments
 ///
 /// * `stream` - The TCP stream for the client connection
+/// * `address` - The client's remote address
+/// * `clients` - Registry the connection is listed in while open
+/// * `metrics` - Counters updated as broadcasts are delivered
 /// * `event_tx` - Broadcast sender for distributing events
+/// * `shutdown_rx` - Flips to `true` when the server is shutting down
+///
+/// When the server ends the connection itself, it sends a Close frame with a
+/// [`CloseReason`] code before exiting.
 ///
 /// # Returns
 ///
@@ -186,530 +450,228 @@ impl<R: Runtime> WebSocketServer<R> {
 /// * `Err(Box<dyn std::error::Error>)` - If an error occurs during communication
 async fn handle_connection<R: Runtime>(
     stream: TcpStream,
+    address: SocketAddr,
+    clients: ClientRegistry,
+    metrics: ServerMetrics,
     event_tx: broadcast::Sender<String>,
+    mut shutdown_rx: watch::Receiver<bool>,
     app: AppHandle<R>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    // The address the client reached us on, for download URLs
+    let local_addr = stream.local_addr()?;
     let ws_stream = accept_async(stream).await?;
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
     let mut event_rx = event_tx.subscribe();
 
-    // Create channel for sending responses from receive task to send task
-    let (response_tx, mut response_rx) = mpsc::unbounded_channel::<String>();
+    // Apps that manage no `Config` get the defaults
+    let default_config;
+    let config = match app.try_state::<Config>() {
+        Some(config) => config.inner(),
+        None => {
+            default_config = Config::default();
+            &default_config
+        }
+    };
 
-    // Spawn task to handle outgoing messages (both broadcasts and responses)
-    let send_task = tokio::spawn(async move {
-        loop {
+    // Announce supported features before any other traffic
+    let capabilities = Capabilities::from_config(config);
+    ws_sender
+        .send(Message::Text(capabilities.to_frame().into()))
+        .await?;
+
+    // Listed in `get_clients` until the state drops with the connection
+    let auth =
+        AuthGate::new(config.auth_token.clone()).with_read_only(config.read_only_commands.clone());
+    let (mut conn, mut response_rx) =
+        ConnState::new(clients, address, local_addr, capabilities, auth);
+    conn.namespace = config.command_namespace.clone();
+    conn.mcp_mode = config.mcp_mode;
+    conn.command_timeout = config.command_timeout;
+    conn.interceptor = config.request_interceptor.clone();
+    conn.request_slots = RequestSlots::new(config.max_concurrent_requests);
+
+    // Negotiated wire format and subscribed topics, shared with the send task
+    // for broadcasts
+    let format_rx = conn.format_tx.subscribe();
+    let topics_rx = conn.topics.subscribe();
+
+    // Spawn task to handle outgoing messages (both broadcasts and responses).
+    // It ends with the error that stopped it, if any.
+    let mut send_task = tauri::async_runtime::spawn(async move {
+        let outcome = loop {
             tokio::select! {
                 // Handle broadcast events
-                Ok(msg) = event_rx.recv() => {
-                    if let Err(e) = ws_sender.send(Message::Text(msg.into())).await {
-                        eprintln!("Failed to send broadcast: {e}");
-                        break;
+                msg = event_rx.recv() => {
+                    let msg = match msg {
+                        Ok(msg) => msg,
+                        Err(e) => break Err(OutboundError::from(e)),
+                    };
+                    if !topics_rx.borrow().allows(&msg) {
+                        continue;
                     }
+                    let frame = format_rx.borrow().encode_json_text(msg);
+                    if let Err(e) = send_frame(&mut ws_sender, frame, WRITE_TIMEOUT).await {
+                        break Err(e);
+                    }
+                    metrics.record_event_sent();
                 }
-                // Handle responses to client requests
-                Some(response) = response_rx.recv() => {
-                    if let Err(e) = ws_sender.send(Message::Text(response.into())).await {
-                        eprintln!("Failed to send response: {e}");
-                        break;
+                // Handle responses to client requests, and streamed frames
+                response = response_rx.recv() => {
+                    let Some(response) = response else {
+                        break if response_rx.overflowed() {
+                            Err(OutboundError::QueueFull)
+                        } else {
+                            Ok(())
+                        };
+                    };
+                    let is_close = response.is_close();
+                    if let Err(e) = send_frame(&mut ws_sender, response, WRITE_TIMEOUT).await {
+                        break Err(e);
+                    }
+                    // Nothing may follow a server-initiated Close frame
+                    if is_close {
+                        break Ok(());
                     }
                 }
-                else => break,
+                else => break Ok(()),
+            }
+        };
+
+        // Best effort: a client that isn't reading may never see the Close frame
+        if let Err(e) = &outcome {
+            if e.is_slow_consumer() {
+                let close = CloseReason::SlowConsumer.to_message();
+                let _ = send_frame(&mut ws_sender, close, CLOSE_FLUSH_TIMEOUT).await;
             }
         }
+        outcome
     });
+    let mut send_finished = false;
+
+    // Set when the server ends the connection, sent as a Close frame
+    let mut close_reason = None;
+
+    // Closes the connection after `idle_timeout` without a request
+    let mut idle_timer = IdleTimer::new(config.idle_timeout);
 
     // Handle incoming messages from client (request/response)
-    while let Some(msg) = ws_receiver.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = ws_receiver.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            Ok(_) = shutdown_rx.wait_for(|shutting_down| *shutting_down) => {
+                close_reason = Some(CloseReason::GoingAway);
+                break;
+            }
+            _ = idle_timer.expired() => {
+                close_reason = Some(CloseReason::IdleTimeout);
+                break;
+            }
+            // Nothing more can be sent, so stop serving the client
+            outcome = &mut send_task => {
+                send_finished = true;
+                if let Ok(Err(e)) = outcome {
+                    mcp_log_error(
+                        "WS_SERVER",
+                        &format!("[{}] Dropping connection: {e}", conn.client.label()),
+                    );
+                }
+                break;
+            }
+        };
+
+        // Only application messages count as activity, not ping/pong
+        if matches!(msg, Ok(Message::Text(_)) | Ok(Message::Binary(_))) {
+            idle_timer.reset();
+        }
+
         match msg {
             Ok(Message::Text(text)) => {
-                // Parse incoming command and send response
-                if let Ok(command) = serde_json::from_str::<serde_json::Value>(&text) {
-                    let id = command.get("id").and_then(|v| v.as_str()).unwrap_or("");
-                    let cmd_name = command
-                        .get("command")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("unknown");
-
-                    // Handle commands
-                    let response = if cmd_name == "invoke_tauri" {
-                        // Handle Tauri IPC command invocation
-                        if let Some(args) = command.get("args") {
-                            if let Some(tauri_cmd) = args.get("command").and_then(|v| v.as_str()) {
-                                // Call the actual Tauri commands
-                                use crate::commands;
-
-                                // Get optional window_label from args for window targeting
-                                let window_label = args
-                                    .get("args")
-                                    .and_then(|a| a.get("windowLabel"))
-                                    .and_then(|v| v.as_str())
-                                    .map(|s| s.to_string());
-
-                                match tauri_cmd {
-                                    "plugin:mcp-bridge|get_window_info" => {
-                                        match commands::resolve_window(&app, window_label.clone()) {
-                                            Ok(window) => {
-                                                match commands::get_window_info(window).await {
-                                                    Ok(data) => serde_json::json!({
-                                                        "id": id,
-                                                        "success": true,
-                                                        "data": data
-                                                    }),
-                                                    Err(e) => serde_json::json!({
-                                                        "id": id,
-                                                        "success": false,
-                                                        "error": e
-                                                    }),
-                                                }
-                                            }
-                                            Err(e) => serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": e
-                                            }),
-                                        }
-                                    }
-                                    "plugin:mcp-bridge|get_backend_state" => {
-                                        match commands::get_backend_state(app.clone()).await {
-                                            Ok(data) => serde_json::json!({
-                                                "id": id,
-                                                "success": true,
-                                                "data": data
-                                            }),
-                                            Err(e) => serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": e
-                                            }),
-                                        }
-                                    }
-                                    "plugin:mcp-bridge|start_ipc_monitor" => {
-                                        match commands::start_ipc_monitor(app.state()).await {
-                                            Ok(data) => serde_json::json!({
-                                                "id": id,
-                                                "success": true,
-                                                "data": data
-                                            }),
-                                            Err(e) => serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": e
-                                            }),
-                                        }
-                                    }
-                                    "plugin:mcp-bridge|stop_ipc_monitor" => {
-                                        match commands::stop_ipc_monitor(app.state()).await {
-                                            Ok(data) => serde_json::json!({
-                                                "id": id,
-                                                "success": true,
-                                                "data": data
-                                            }),
-                                            Err(e) => serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": e
-                                            }),
-                                        }
-                                    }
-                                    "plugin:mcp-bridge|get_ipc_events" => {
-                                        match commands::get_ipc_events(app.state()).await {
-                                            Ok(data) => serde_json::json!({
-                                                "id": id,
-                                                "success": true,
-                                                "data": data
-                                            }),
-                                            Err(e) => serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": e
-                                            }),
-                                        }
-                                    }
-                                    "plugin:mcp-bridge|emit_event" => {
-                                        if let Some(event_name) = args
-                                            .get("args")
-                                            .and_then(|a| a.get("eventName"))
-                                            .and_then(|v| v.as_str())
-                                        {
-                                            let payload = args
-                                                .get("args")
-                                                .and_then(|a| a.get("payload"))
-                                                .cloned()
-                                                .unwrap_or(serde_json::json!(null));
-                                            match commands::emit_event(
-                                                app.clone(),
-                                                event_name.to_string(),
-                                                payload,
-                                            )
-                                            .await
-                                            {
-                                                Ok(data) => serde_json::json!({
-                                                    "id": id,
-                                                    "success": true,
-                                                    "data": data
-                                                }),
-                                                Err(e) => serde_json::json!({
-                                                    "id": id,
-                                                    "success": false,
-                                                    "error": e
-                                                }),
-                                            }
-                                        } else {
-                                            serde_json::json!({
-                                                "id": id,
-                                                "success": false,
-                                                "error": "Missing eventName in args"
-                                            })
-                                        }
-                                    }
-                                    _ => {
-                                        serde_json::json!({
-                                            "id": id,
-                                            "success": false,
-                                            "error": format!("Unsupported Tauri command: {}", tauri_cmd)
-                                        })
-                                    }
-                                }
-                            } else {
-                                serde_json::json!({
-                                    "id": id,
-                                    "success": false,
-                                    "error": "Missing command in args"
-                                })
-                            }
-                        } else {
-                            serde_json::json!({
-                                "id": id,
-                                "success": false,
-                                "error": "Missing args for invoke_tauri"
-                            })
-                        }
-                    } else if cmd_name == "list_windows" {
-                        // Handle window listing
-                        match crate::commands::list_windows(app.clone()).await {
-                            Ok(data) => serde_json::json!({
-                                "id": id,
-                                "success": true,
-                                "data": data
-                            }),
-                            Err(e) => serde_json::json!({
-                                "id": id,
-                                "success": false,
-                                "error": e
-                            }),
-                        }
-                    } else if cmd_name == "execute_js" {
-                        if let Some(args) = command.get("args") {
-                            if let Some(script) = args.get("script").and_then(|v| v.as_str()) {
-                                // Get optional window_label, defaulting to "main"
-                                let window_label = args
-                                    .get("windowLabel")
-                                    .and_then(|v| v.as_str())
-                                    .map(|s| s.to_string());
-
-                                // Resolve the target window with context
-                                match crate::commands::resolve_window_with_context(
-                                    &app,
-                                    window_label,
-                                ) {
-                                    Ok(resolved) => {
-                                        // Get the script executor state and create State wrapper
-                                        let executor_state =
-                                            app.state::<crate::commands::ScriptExecutor>();
-                                        // Call the execute_js command with state
-                                        match crate::commands::execute_js(
-                                            resolved.window.clone(),
-                                            script.to_string(),
-                                            executor_state,
-                                        )
-                                        .await
-                                        {
-                                            Ok(result) => {
-                                                serde_json::json!({
-                                                    "id": id,
-                                                    "success": result.get("success").and_then(|v| v.as_bool()).unwrap_or(true),
-                                                    "data": result.get("data").cloned(),
-                                                    "error": result.get("error").and_then(|v| v.as_str()),
-                                                    "windowContext": resolved.context
-                                                })
-                                            }
-                                            Err(e) => {
-                                                serde_json::json!({
-                                                    "id": id,
-                                                    "success": false,
-                                                    "error": e,
-                                                    "windowContext": resolved.context
-                                                })
-                                            }
-                                        }
-                                    }
-                                    Err(e) => {
-                                        serde_json::json!({
-                                            "id": id,
-                                            "success": false,
-                                            "error": e
-                                        })
-                                    }
-                                }
-                            } else {
-                                serde_json::json!({
-                                    "id": id,
-                                    "success": false,
-                                    "error": "Missing script argument"
-                                })
-                            }
-                        } else {
-                            serde_json::json!({
-                                "id": id,
-                                "success": false,
-                                "error": "Missing args"
-                            })
-                        }
-                    } else if cmd_name == "capture_native_screenshot" {
-                        // Handle native screenshot capture
-                        let args = command.get("args");
-                        let format = args
-                            .and_then(|a| a.get("format"))
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let quality = args
-                            .and_then(|a| a.get("quality"))
-                            .and_then(|v| v.as_u64())
-                            .map(|q| q as u8);
-                        let window_label = args
-                            .and_then(|a| a.get("windowLabel"))
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-
-                        // Resolve the target window with context
-                        match crate::commands::resolve_window_with_context(&app, window_label) {
-                            Ok(resolved) => {
-                                match crate::commands::capture_native_screenshot(
-                                    resolved.window,
-                                    format,
-                                    quality,
-                                )
-                                .await
-                                {
-                                    Ok(data_url) => {
-                                        serde_json::json!({
-                                            "id": id,
-                                            "success": true,
-                                            "data": data_url,
-                                            "windowContext": resolved.context
-                                        })
-                                    }
-                                    Err(e) => {
-                                        serde_json::json!({
-                                            "id": id,
-                                            "success": false,
-                                            "error": e,
-                                            "windowContext": resolved.context
-                                        })
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                serde_json::json!({
-                                    "id": id,
-                                    "success": false,
-                                    "error": e
-                                })
-                            }
-                        }
-                    } else if cmd_name == "register_script" {
-                        // Handle script registration
-                        if let Some(args) = command.get("args") {
-                            let script_id = args.get("id").and_then(|v| v.as_str());
-                            let script_type_str = args.get("type").and_then(|v| v.as_str());
-                            let content = args.get("content").and_then(|v| v.as_str());
-
-                            match (script_id, script_type_str, content) {
-                                (Some(id_str), Some(type_str), Some(content_str)) => {
-                                    let script_type = match type_str {
-                                        "url" => ScriptType::Url,
-                                        _ => ScriptType::Inline,
-                                    };
-
-                                    let entry = ScriptEntry {
-                                        id: id_str.to_string(),
-                                        script_type,
-                                        content: content_str.to_string(),
-                                    };
-
-                                    // Add to registry
-                                    let registry: tauri::State<'_, SharedScriptRegistry> =
-                                        app.state();
-                                    {
-                                        let mut reg = registry.lock().unwrap();
-                                        reg.add(entry.clone());
-                                    }
-
-                                    // Inject the script into the webview
-                                    let window_label = args
-                                        .get("windowLabel")
-                                        .and_then(|v| v.as_str())
-                                        .map(|s| s.to_string());
-
-                                    match inject_script_to_webview(&app, &entry, window_label) {
-                                        Ok(result) => serde_json::json!({
-                                            "id": id,
-                                            "success": true,
-                                            "data": { "registered": true, "scriptId": id_str },
-                                            "windowContext": {
-                                                "windowLabel": result.window_context.window_label,
-                                                "totalWindows": result.window_context.total_windows,
-                                                "warning": result.window_context.warning
-                                            }
-                                        }),
-                                        Err(e) => serde_json::json!({
-                                            "id": id,
-                                            "success": false,
-                                            "error": e
-                                        }),
-                                    }
-                                }
-                                _ => serde_json::json!({
-                                    "id": id,
-                                    "success": false,
-                                    "error": "Missing required args: id, type, content"
-                                }),
-                            }
-                        } else {
-                            serde_json::json!({
-                                "id": id,
-                                "success": false,
-                                "error": "Missing args for register_script"
-                            })
-                        }
-                    } else if cmd_name == "remove_script" {
-                        // Handle script removal
-                        if let Some(args) = command.get("args") {
-                            if let Some(script_id) = args.get("id").and_then(|v| v.as_str()) {
-                                let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
-                                let removed = {
-                                    let mut reg = registry.lock().unwrap();
-                                    reg.remove(script_id).is_some()
-                                };
-
-                                // Remove from DOM
-                                let window_label = args
-                                    .get("windowLabel")
-                                    .and_then(|v| v.as_str())
-                                    .map(|s| s.to_string());
-
-                                match remove_script_from_webview(&app, script_id, window_label) {
-                                    Ok(result) => serde_json::json!({
-                                        "id": id,
-                                        "success": true,
-                                        "data": { "removed": removed, "scriptId": script_id },
-                                        "windowContext": {
-                                            "windowLabel": result.window_context.window_label,
-                                            "totalWindows": result.window_context.total_windows,
-                                            "warning": result.window_context.warning
-                                        }
-                                    }),
-                                    Err(e) => {
-                                        eprintln!("Failed to remove script from DOM: {e}");
-                                        serde_json::json!({
-                                            "id": id,
-                                            "success": true,
-                                            "data": { "removed": removed, "scriptId": script_id },
-                                            "error": format!("Script removed from registry but DOM removal failed: {e}")
-                                        })
-                                    }
-                                }
-                            } else {
-                                serde_json::json!({
-                                    "id": id,
-                                    "success": false,
-                                    "error": "Missing script id"
-                                })
-                            }
-                        } else {
-                            serde_json::json!({
-                                "id": id,
-                                "success": false,
-                                "error": "Missing args for remove_script"
-                            })
-                        }
-                    } else if cmd_name == "clear_scripts" {
-                        // Handle clearing all scripts
-                        let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
-                        let count = {
-                            let mut reg = registry.lock().unwrap();
-                            let count = reg.len();
-                            reg.clear();
-                            count
-                        };
+                let received = Instant::now();
+                if conn.trace {
+                    mcp_log_info(
+                        "WS_SERVER",
+                        &format!("[{}] <- {}", conn.client.label(), text.as_str()),
+                    );
+                }
 
-                        // Clear from DOM
-                        let window_label = command
-                            .get("args")
-                            .and_then(|a| a.get("windowLabel"))
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-
-                        match clear_scripts_from_webview(&app, window_label) {
-                            Ok(result) => serde_json::json!({
-                                "id": id,
-                                "success": true,
-                                "data": { "cleared": count },
-                                "windowContext": {
-                                    "windowLabel": result.window_context.window_label,
-                                    "totalWindows": result.window_context.total_windows,
-                                    "warning": result.window_context.warning
-                                }
-                            }),
-                            Err(e) => {
-                                eprintln!("Failed to clear scripts from DOM: {e}");
-                                serde_json::json!({
-                                    "id": id,
-                                    "success": true,
-                                    "data": { "cleared": count },
-                                    "error": format!("Scripts cleared from registry but DOM clear failed: {e}")
-                                })
-                            }
+                // MCP tool calls carry unqualified names, so skip routing
+                let mut rpc_id = None;
+                let routed = match conn.mcp_mode.then(|| mcp::parse(&text)).flatten() {
+                    Some(McpMessage::Reply(reply)) => {
+                        conn.respond(WsResponse::new(reply));
+                        continue;
+                    }
+                    Some(McpMessage::Notification) => continue,
+                    Some(McpMessage::Call {
+                        rpc_id: id,
+                        request,
+                    }) => {
+                        rpc_id = Some(id);
+                        Ok(request)
+                    }
+                    None => parse_request(&text)
+                        .and_then(|request| route(request, conn.namespace.as_deref())),
+                };
+                let mut request = match routed {
+                    Ok(request) => request,
+                    Err(e) => {
+                        eprintln!("{e}");
+                        if let Some(response) = e.response("") {
+                            conn.respond(mcp_response(rpc_id.as_ref(), response));
                         }
-                    } else if cmd_name == "get_scripts" {
-                        // Handle getting all registered scripts
-                        let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
-                        let scripts: Vec<serde_json::Value> = {
-                            let reg = registry.lock().unwrap();
-                            reg.get_all()
-                                .iter()
-                                .map(|entry| {
-                                    serde_json::json!({
-                                        "id": entry.id,
-                                        "type": match entry.script_type {
-                                            ScriptType::Inline => "inline",
-                                            ScriptType::Url => "url",
-                                        },
-                                        "content": entry.content
-                                    })
-                                })
-                                .collect()
-                        };
-
-                        serde_json::json!({
-                            "id": id,
-                            "success": true,
-                            "data": { "scripts": scripts }
-                        })
-                    } else {
-                        // Unknown command
-                        serde_json::json!({
-                            "id": id,
-                            "success": false,
-                            "error": format!("Unknown command: {}", cmd_name)
-                        })
-                    };
-
-                    let _ = response_tx.send(response.to_string());
-                } else {
-                    eprintln!("Failed to parse command: {text}");
+                        continue;
+                    }
+                };
+
+                conn.client.record_request();
+                mcp_log_info(
+                    "WS_SERVER",
+                    &format!(
+                        "[{}] {} (id {})",
+                        conn.client.label(),
+                        request.command,
+                        request.id
+                    ),
+                );
+
+                let admitted =
+                    authorize(&request, &mut conn).and_then(|()| intercept(&mut request, &conn));
+                if let Err(e) = admitted {
+                    mcp_log_error("WS_SERVER", &format!("[{}] {e}", conn.client.label()));
+                    if let Some(response) = e.response(&request.id) {
+                        conn.respond(mcp_response(rpc_id.as_ref(), response));
+                        continue;
+                    }
+                    close_reason = e.close_reason();
+                    break;
                 }
+
+                // Requests beyond `max_concurrent_requests` wait in the
+                // socket until a running command has been answered, while
+                // shutdown and the idle timer still end the connection
+                let slot = tokio::select! {
+                    slot = reserve::<R>(&request, &conn.request_slots) => slot,
+                    Ok(_) = shutdown_rx.wait_for(|shutting_down| *shutting_down) => {
+                        close_reason = Some(CloseReason::GoingAway);
+                        break;
+                    }
+                    _ = idle_timer.expired() => {
+                        close_reason = Some(CloseReason::IdleTimeout);
+                        break;
+                    }
+                };
+                let trace = conn.trace_request(&request, received);
+                serve(request, slot, &mut conn, &app, move |mut response| {
+                    if let Some(trace) = trace {
+                        trace.annotate(&mut response);
+                    }
+                    mcp_response(rpc_id.as_ref(), response)
+                })
+                .await;
             }
             Ok(Message::Close(_)) => {
                 println!("Client disconnected");
@@ -717,169 +679,78 @@ async fn handle_connection<R: Runtime>(
             }
             Err(e) => {
                 eprintln!("WebSocket error: {e}");
+                close_reason = CloseReason::from_error(&e);
                 break;
             }
             _ => {}
         }
     }
 
-    send_task.abort();
-    Ok(())
-}
-
-/// Result of a script operation with window context.
-struct ScriptOperationResult {
-    window_context: WindowContext,
-}
-
-/// Injects a script into a specific webview window.
-fn inject_script_to_window<R: Runtime>(
-    window: &WebviewWindow<R>,
-    entry: &ScriptEntry,
-) -> Result<(), String> {
-    let script = match entry.script_type {
-        ScriptType::Inline => format!(
-            r#"
-            (function() {{
-                var existing = document.querySelector('script[data-mcp-script-id="{}"]');
-                if (existing) {{
-                    existing.remove();
-                }}
-                var script = document.createElement('script');
-                script.setAttribute('data-mcp-script-id', '{}');
-                script.textContent = {};
-                document.head.appendChild(script);
-            }})();
-            "#,
-            entry.id,
-            entry.id,
-            serde_json::to_string(&entry.content).unwrap_or_else(|_| "''".to_string())
-        ),
-        ScriptType::Url => format!(
-            r#"
-            (function() {{
-                var existing = document.querySelector('script[data-mcp-script-id="{}"]');
-                if (existing) {{
-                    existing.remove();
-                }}
-                var script = document.createElement('script');
-                script.setAttribute('data-mcp-script-id', '{}');
-                script.src = {};
-                script.async = true;
-                document.head.appendChild(script);
-            }})();
-            "#,
-            entry.id,
-            entry.id,
-            serde_json::to_string(&entry.content).unwrap_or_else(|_| "''".to_string())
-        ),
-    };
-
-    window
-        .eval(&script)
-        .map_err(|e| format!("Failed to inject script: {e}"))
-}
-
-/// Injects a script into the webview DOM.
-/// If a script with the same ID already exists, it is removed first.
-/// Returns window context for the response.
-fn inject_script_to_webview<R: Runtime>(
-    app: &AppHandle<R>,
-    entry: &ScriptEntry,
-    window_label: Option<String>,
-) -> Result<ScriptOperationResult, String> {
-    let resolved = resolve_window_with_context(app, window_label)?;
-
-    inject_script_to_window(&resolved.window, entry)?;
-
-    Ok(ScriptOperationResult {
-        window_context: resolved.context,
-    })
-}
-
-/// Removes a script from a specific window's DOM.
-fn remove_script_from_window<R: Runtime>(
-    window: &WebviewWindow<R>,
-    script_id: &str,
-) -> Result<(), String> {
-    let script = format!(
-        r#"
-        (function() {{
-            var script = document.querySelector('script[data-mcp-script-id="{script_id}"]');
-            if (script) {{
-                script.remove();
-            }}
-        }})();
-        "#
-    );
-
-    window
-        .eval(&script)
-        .map_err(|e| format!("Failed to remove script: {e}"))
-}
-
-/// Removes a script from the webview DOM by ID.
-/// Returns window context for the response.
-fn remove_script_from_webview<R: Runtime>(
-    app: &AppHandle<R>,
-    script_id: &str,
-    window_label: Option<String>,
-) -> Result<ScriptOperationResult, String> {
-    let resolved = resolve_window_with_context(app, window_label)?;
-
-    remove_script_from_window(&resolved.window, script_id)?;
+    if let (Some(reason), false) = (close_reason, send_finished) {
+        // Let the send task flush the Close frame before tearing it down
+        let _ = conn.responses.send(reason.to_message());
+        let _ = tokio::time::timeout(CLOSE_FLUSH_TIMEOUT, &mut send_task).await;
+    }
 
-    Ok(ScriptOperationResult {
-        window_context: resolved.context,
-    })
-}
+    // Drop the connection's element handles from the pages holding them
+    for label in conn.element_handles.windows() {
+        if let Some(window) = app.get_webview_window(label) {
+            let _ = window.eval(release_script(conn.element_handles.owner()));
+        }
+    }
 
-/// Clears all MCP-managed scripts from a specific window's DOM.
-fn clear_scripts_from_window<R: Runtime>(window: &WebviewWindow<R>) -> Result<(), String> {
-    let script = r#"
-        (function() {
-            var scripts = document.querySelectorAll('script[data-mcp-script-id]');
-            scripts.forEach(function(s) { s.remove(); });
-        })();
-    "#;
-
-    window
-        .eval(script)
-        .map_err(|e| format!("Failed to clear scripts: {e}"))
+    send_task.abort();
+    Ok(())
 }
 
-/// Clears all MCP-managed scripts from the webview DOM.
-/// Returns window context for the response.
-fn clear_scripts_from_webview<R: Runtime>(
-    app: &AppHandle<R>,
-    window_label: Option<String>,
-) -> Result<ScriptOperationResult, String> {
-    let resolved = resolve_window_with_context(app, window_label)?;
-
-    clear_scripts_from_window(&resolved.window)?;
-
-    Ok(ScriptOperationResult {
-        window_context: resolved.context,
-    })
+/// Wraps the response to an MCP `tools/call` in its JSON-RPC result; bridge
+/// responses pass through.
+fn mcp_response(rpc_id: Option<&serde_json::Value>, response: WsResponse) -> WsResponse {
+    match rpc_id {
+        Some(rpc_id) => WsResponse::new(mcp::tool_result(rpc_id, &response)),
+        None => response,
+    }
 }
 
-/// Injects all registered scripts into the webview.
-/// Called when a page loads to re-inject persistent scripts.
-pub fn inject_all_scripts<R: Runtime>(
-    app: &AppHandle<R>,
-    window_label: Option<String>,
-) -> Result<usize, String> {
-    let registry: tauri::State<'_, SharedScriptRegistry> = app.state();
-    let scripts: Vec<ScriptEntry> = {
-        let reg = registry.lock().unwrap();
-        reg.get_all().iter().map(|e| (*e).clone()).collect()
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    #[test]
+    fn test_only_fatal_accept_errors_stop_the_server() {
+        for kind in [
+            io::ErrorKind::ConnectionAborted,
+            io::ErrorKind::ConnectionReset,
+            io::ErrorKind::Interrupted,
+            io::ErrorKind::WouldBlock,
+        ] {
+            assert_eq!(
+                AcceptFailure::classify(&io::Error::from(kind)),
+                AcceptFailure::Connection
+            );
+        }
 
-    let resolved = resolve_window_with_context(app, window_label)?;
+        #[cfg(unix)]
+        let too_many_files = io::Error::from_raw_os_error(24);
+        #[cfg(windows)]
+        let too_many_files = io::Error::from_raw_os_error(10024);
+        assert_eq!(
+            AcceptFailure::classify(&too_many_files),
+            AcceptFailure::Resources
+        );
+        assert_eq!(
+            AcceptFailure::classify(&io::Error::from(io::ErrorKind::OutOfMemory)),
+            AcceptFailure::Resources
+        );
 
-    for entry in &scripts {
-        inject_script_to_window(&resolved.window, entry)?;
+        assert_eq!(
+            AcceptFailure::classify(&io::Error::from(io::ErrorKind::InvalidInput)),
+            AcceptFailure::Fatal
+        );
+        assert_eq!(
+            AcceptFailure::classify(&io::Error::from(io::ErrorKind::AddrNotAvailable)),
+            AcceptFailure::Fatal
+        );
     }
-
-    Ok(scripts.len())
 }
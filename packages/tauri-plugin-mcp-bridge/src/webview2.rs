@@ -0,0 +1,119 @@
+//! Shared helpers for the WebView2 backend on Windows.
+
+use crate::logging::{mcp_log_error, mcp_log_info};
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{Runtime, WebviewWindow};
+use tokio::sync::oneshot;
+use webview2_com::Microsoft::Web::WebView2::Win32::{ICoreWebView2, ICoreWebView2Controller};
+
+/// Number of attempts made to acquire `ICoreWebView2` before giving up.
+const CORE_WEBVIEW2_ATTEMPTS: u32 = 3;
+
+/// Delay between acquisition attempts.
+const CORE_WEBVIEW2_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// How long a DevTools protocol call may take to answer.
+const DEVTOOLS_CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Acquires the `ICoreWebView2` behind a controller, retrying transient failures.
+///
+/// `CoreWebView2()` can fail briefly while a window is starting up or
+/// navigating. Each failed attempt is logged; after the last attempt the
+/// returned error includes the attempt count and the final WebView2 error.
+///
+/// # Safety
+///
+/// Must be called on the thread that owns the controller (i.e. from within
+/// `WebviewWindow::with_webview`).
+pub(crate) unsafe fn core_webview2(
+    controller: &ICoreWebView2Controller,
+) -> Result<ICoreWebView2, String> {
+    let mut attempt = 1;
+    loop {
+        match controller.CoreWebView2() {
+            Ok(core_webview2) => return Ok(core_webview2),
+            Err(e) if attempt < CORE_WEBVIEW2_ATTEMPTS => {
+                mcp_log_info(
+                    "WEBVIEW2",
+                    &format!(
+                        "CoreWebView2 unavailable (attempt {attempt}/{CORE_WEBVIEW2_ATTEMPTS}): {e}; retrying"
+                    ),
+                );
+                std::thread::sleep(CORE_WEBVIEW2_RETRY_DELAY);
+                attempt += 1;
+            }
+            Err(e) => {
+                let message =
+                    format!("CoreWebView2 failed after {CORE_WEBVIEW2_ATTEMPTS} attempts: {e}");
+                mcp_log_error("WEBVIEW2", &message);
+                return Err(message);
+            }
+        }
+    }
+}
+
+/// Calls a DevTools protocol method on a window's WebView2.
+///
+/// # Arguments
+///
+/// * `window` - The window whose webview receives the call
+/// * `method` - The method, such as `Runtime.evaluate`
+/// * `params` - The method's parameters object
+///
+/// # Returns
+///
+/// * `Ok(Value)` - The method's result object
+/// * `Err(String)` - If the webview is unavailable, or the call fails or
+///   doesn't answer within 5 seconds
+pub(crate) async fn call_devtools_method<R: Runtime>(
+    window: &WebviewWindow<R>,
+    method: &str,
+    params: Value,
+) -> Result<Value, String> {
+    use webview2_com::CallDevToolsProtocolMethodCompletedHandler;
+    use windows::core::HSTRING;
+
+    let (tx, rx) = oneshot::channel::<Result<String, String>>();
+    let tx = Arc::new(Mutex::new(Some(tx)));
+    let (method_name, params) = (method.to_string(), params.to_string());
+    window
+        .with_webview(move |webview| {
+            let handler_tx = tx.clone();
+            let handler = CallDevToolsProtocolMethodCompletedHandler::create(Box::new(
+                move |result, response| {
+                    if let Some(tx) = handler_tx.lock().unwrap().take() {
+                        let _ = tx.send(result.map(|()| response).map_err(|e| e.to_string()));
+                    }
+                    Ok(())
+                },
+            ));
+
+            let controller = webview.controller();
+            let started = unsafe {
+                core_webview2(&controller).and_then(|core_webview| {
+                    core_webview
+                        .CallDevToolsProtocolMethod(
+                            &HSTRING::from(method_name.as_str()),
+                            &HSTRING::from(params.as_str()),
+                            &handler,
+                        )
+                        .map_err(|e| format!("Failed to call the DevTools protocol: {e}"))
+                })
+            };
+            if let Err(e) = started {
+                if let Some(tx) = tx.lock().unwrap().take() {
+                    let _ = tx.send(Err(e));
+                }
+            }
+        })
+        .map_err(|e| format!("Failed to access webview: {e}"))?;
+
+    let response = tokio::time::timeout(DEVTOOLS_CALL_TIMEOUT, rx)
+        .await
+        .map_err(|_| format!("Timed out calling {method}"))?
+        .map_err(|_| format!("{method} was dropped before answering"))?
+        .map_err(|e| format!("{method} failed: {e}"))?;
+    serde_json::from_str(&response).map_err(|e| format!("Invalid {method} response: {e}"))
+}
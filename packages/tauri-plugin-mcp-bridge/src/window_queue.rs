@@ -0,0 +1,106 @@
+//! Optional per-window serialization of webview operations.
+//!
+//! Concurrent `execute_js` and screenshot calls against the same window can
+//! interleave inside `with_webview` and race on page globals such as the
+//! `__mcp_result_*` slots used for async scripts. When enabled with
+//! [`Builder::serialize_per_window`](crate::Builder::serialize_per_window),
+//! operations on one window run one at a time while different windows still
+//! run in parallel.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+
+/// Per-window async locks, managed as plugin state.
+pub struct WindowQueue {
+    enabled: bool,
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl WindowQueue {
+    /// Creates a queue; when `enabled` is false, [`acquire`](Self::acquire)
+    /// returns immediately.
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            locks: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits for exclusive access to `window_label`'s webview.
+    ///
+    /// The returned guard releases the window when dropped. Returns `None`
+    /// without waiting when serialization is disabled.
+    pub async fn acquire(&self, window_label: &str) -> Option<OwnedMutexGuard<()>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let lock = self
+            .locks
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(window_label.to_string())
+            .or_default()
+            .clone();
+
+        Some(lock.lock_owned().await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Mimics an async script: writes its result to a shared page global,
+    /// yields while the "webview" runs, then reads the global back.
+    async fn run_script(queue: &WindowQueue, global: &Mutex<u32>, label: &str, value: u32) -> u32 {
+        let _guard = queue.acquire(label).await;
+        *global.lock().unwrap() = value;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        *global.lock().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_calls_on_one_window_do_not_cross_contaminate() {
+        let queue = WindowQueue::new(true);
+        let global = Mutex::new(0);
+
+        let (first, second) = tokio::join!(
+            run_script(&queue, &global, "main", 1),
+            run_script(&queue, &global, "main", 2)
+        );
+
+        assert_eq!((first, second), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_queue_lets_calls_interleave() {
+        let queue = WindowQueue::new(false);
+        let global = Mutex::new(0);
+
+        let (first, second) = tokio::join!(
+            run_script(&queue, &global, "main", 1),
+            run_script(&queue, &global, "main", 2)
+        );
+
+        // Both read whichever value was written last
+        assert_eq!(first, second);
+    }
+
+    #[tokio::test]
+    async fn test_different_windows_run_in_parallel() {
+        let queue = WindowQueue::new(true);
+
+        let _main = queue.acquire("main").await;
+        let other = tokio::time::timeout(Duration::from_millis(50), queue.acquire("other")).await;
+
+        assert!(other.is_ok());
+        assert!(
+            tokio::time::timeout(Duration::from_millis(50), queue.acquire("main"))
+                .await
+                .is_err()
+        );
+    }
+}
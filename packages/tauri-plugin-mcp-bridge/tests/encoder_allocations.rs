@@ -0,0 +1,139 @@
+//! Memory use of `FrameEncoder` conversions.
+//!
+//! These tests install a counting global allocator, so they live in their
+//! own test binary rather than next to the encoder, where every other unit
+//! test of the crate would run under it.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use image::codecs::jpeg::JpegEncoder;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io::Cursor;
+use tauri_plugin_mcp_bridge::screenshot::{Background, FrameEncoder};
+
+/// Counts the bytes allocated by the current thread, so concurrently running
+/// tests don't skew each other's numbers. Also tracks the thread's live bytes
+/// and their peak.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+fn grow(bytes: usize) {
+    let _ = ALLOCATED.try_with(|allocated| allocated.set(allocated.get() + bytes));
+    let _ = LIVE.try_with(|live| {
+        live.set(live.get() + bytes);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(live.get())));
+    });
+}
+
+fn shrink(bytes: usize) {
+    let _ = LIVE.try_with(|live| live.set(live.get().saturating_sub(bytes)));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        grow(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        shrink(layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // Moving to a new block briefly holds both
+        grow(new_size);
+        shrink(layout.size());
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Bytes allocated by the current thread while running `f`.
+fn allocated_by<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = ALLOCATED.with(Cell::get);
+    let result = f();
+    (ALLOCATED.with(Cell::get) - before, result)
+}
+
+/// Most bytes the current thread held at once while running `f`, on top of
+/// what it held before.
+fn peak_of<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(before));
+    let result = f();
+    (PEAK.with(Cell::get) - before, result)
+}
+
+/// A semi-transparent RGBA PNG with some detail for the encoders to chew on.
+fn capture_png(width: u32, height: u32) -> Vec<u8> {
+    let frame = image::RgbaImage::from_fn(width, height, |x, y| {
+        image::Rgba([(x % 256) as u8, (y % 256) as u8, ((x + y) % 256) as u8, 200])
+    });
+    let mut png = Cursor::new(Vec::new());
+    frame.write_to(&mut png, image::ImageFormat::Png).unwrap();
+    png.into_inner()
+}
+
+/// The conversion as done before, with fresh buffers for every frame.
+fn one_shot_jpeg(png: &[u8], quality: u8) -> Vec<u8> {
+    let img = image::load_from_memory_with_format(png, image::ImageFormat::Png).unwrap();
+    let mut jpeg = Cursor::new(Vec::new());
+    img.write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, quality))
+        .unwrap();
+    jpeg.into_inner()
+}
+
+/// The conversion to a data URL as done before: a decoded `DynamicImage`, a
+/// JPEG `Vec`, its base64 text and the URL.
+fn one_shot_data_url(png: &[u8], quality: u8) -> String {
+    let jpeg = one_shot_jpeg(png, quality);
+    let base64_data = STANDARD.encode(&jpeg);
+    format!("data:image/jpeg;base64,{base64_data}")
+}
+
+#[test]
+fn test_streamed_conversion_peaks_lower() {
+    let png = capture_png(640, 400);
+    let (before, expected) = peak_of(|| one_shot_data_url(&png, 70));
+    let (after, data_url) = peak_of(|| {
+        FrameEncoder::new()
+            .png_to_jpeg_data_url(&png, 70, Background::Transparent)
+            .unwrap()
+    });
+    assert_eq!(data_url, expected);
+
+    // No RGB copy of the decoded frame is made
+    let frame_bytes = 640 * 400 * 4;
+    assert!(after + frame_bytes / 4 < before, "{after} vs {before}");
+}
+
+#[test]
+fn test_reused_encoder_allocates_less_per_frame() {
+    let png = capture_png(320, 200);
+    let mut encoder = FrameEncoder::new();
+    encoder
+        .png_to_jpeg(&png, 70, Background::Transparent)
+        .unwrap();
+
+    let (reused, _) = allocated_by(|| {
+        encoder
+            .png_to_jpeg(&png, 70, Background::Transparent)
+            .map(<[u8]>::len)
+            .unwrap()
+    });
+    let (fresh, _) = allocated_by(|| one_shot_jpeg(&png, 70));
+
+    // The decoded frame alone is 256 KB; the encoder keeps its buffer
+    let frame_bytes = 320 * 200 * 4;
+    assert!(fresh > frame_bytes, "{fresh}");
+    assert!(reused < fresh - frame_bytes, "{reused} vs {fresh}");
+}